@@ -0,0 +1,186 @@
+"""
+Long-term archive of derived results.
+Statistic tracks, layouts and exports are kept in the config directory with
+the provenance that produced them (operation, parameters, package versions),
+keyed by the content fingerprint of the tree sequence they were computed from.
+Expensive results therefore survive across sessions and restarts, and are
+found again whenever the same data is loaded, under any filename. The archive
+is kept within a size quota by dropping the least recently opened results.
+"""
+
+import json
+import logging
+import shutil
+import threading
+import time
+import uuid
+from importlib import metadata as importlib_metadata
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+
+from argscape.backend.settings import get_cache_dir, settings
+
+logger = logging.getLogger(__name__)
+
+ARCHIVE_DIRNAME = "archive"
+INDEX_FILENAME = "index.json"
+
+# Result kinds
+KIND_STATISTIC = "statistic"
+KIND_LAYOUT = "layout"
+KIND_EXPORT = "export"
+RESULT_KINDS = (KIND_STATISTIC, KIND_LAYOUT, KIND_EXPORT)
+
+PROVENANCE_PACKAGES = ("argscape", "tskit", "msprime", "tsdate")
+
+
+def _package_versions() -> Dict[str, Optional[str]]:
+    versions = {}
+    for package in PROVENANCE_PACKAGES:
+        try:
+            versions[package] = importlib_metadata.version(package)
+        except importlib_metadata.PackageNotFoundError:
+            versions[package] = None
+    return versions
+
+
+class ResultArchive:
+    """Derived results on disk with a JSON index of their provenance."""
+
+    def __init__(self, directory: Optional[Path] = None):
+        self._directory = directory
+        self._lock = threading.Lock()
+        self._entries: Dict[str, Dict[str, Any]] = {}
+        self._load()
+
+    @property
+    def directory(self) -> Path:
+        return self._directory or get_cache_dir() / ARCHIVE_DIRNAME
+
+    @property
+    def _index_path(self) -> Path:
+        return self.directory / INDEX_FILENAME
+
+    def _load(self):
+        if not self._index_path.exists():
+            return
+        try:
+            with open(self._index_path, "r") as f:
+                self._entries = json.load(f)
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable result archive index {self._index_path}: {e}")
+            return
+        # Drop entries whose payload was removed outside the app
+        missing = [entry_id for entry_id, entry in self._entries.items() if not (self.directory / entry["payload"]).exists()]
+        for entry_id in missing:
+            del self._entries[entry_id]
+
+    def _save(self):
+        try:
+            self.directory.mkdir(parents=True, exist_ok=True)
+            with open(self._index_path, "w") as f:
+                json.dump(self._entries, f, indent=2)
+        except Exception as e:
+            logger.warning(f"Failed to save result archive index to {self._index_path}: {e}")
+
+    def add(
+        self,
+        fingerprint: str,
+        kind: str,
+        name: str,
+        payload: bytes,
+        operation: str,
+        params: Optional[Dict[str, Any]] = None,
+        filename: Optional[str] = None,
+        content_type: str = "application/json"
+    ) -> Dict[str, Any]:
+        """Archive a result; returns its index entry."""
+        if kind not in RESULT_KINDS:
+            raise ValueError(f"kind must be one of {RESULT_KINDS}")
+        entry_id = uuid.uuid4().hex
+        now = time.time()
+        entry = {
+            "id": entry_id,
+            "fingerprint": fingerprint,
+            "kind": kind,
+            "name": name,
+            "source_filename": filename,
+            "content_type": content_type,
+            "payload": f"{entry_id}.bin",
+            "size_bytes": len(payload),
+            "created_at": now,
+            "opened_at": now,
+            "provenance": {"operation": operation, "params": params or {}, "versions": _package_versions()},
+        }
+        with self._lock:
+            self.directory.mkdir(parents=True, exist_ok=True)
+            (self.directory / entry["payload"]).write_bytes(payload)
+            self._entries[entry_id] = entry
+            self._prune()
+            self._save()
+        logger.info(f"Archived {kind} result '{name}' ({len(payload)} bytes)")
+        return dict(entry)
+
+    def add_json(self, fingerprint: str, kind: str, name: str, value: Any, operation: str, **options) -> Dict[str, Any]:
+        return self.add(fingerprint, kind, name, json.dumps(value).encode("utf-8"), operation, **options)
+
+    def list(self, fingerprint: Optional[str] = None, kind: Optional[str] = None) -> List[Dict[str, Any]]:
+        """Entries, newest first, optionally for one tree sequence or kind."""
+        with self._lock:
+            entries = [
+                dict(entry) for entry in self._entries.values()
+                if (fingerprint is None or entry["fingerprint"] == fingerprint) and (kind is None or entry["kind"] == kind)
+            ]
+        return sorted(entries, key=lambda e: -e["created_at"])
+
+    def open(self, entry_id: str) -> Tuple[Dict[str, Any], bytes]:
+        """An entry and its payload; raises KeyError if it is not archived."""
+        with self._lock:
+            entry = self._entries[entry_id]
+            payload = (self.directory / entry["payload"]).read_bytes()
+            entry["opened_at"] = time.time()
+            self._save()
+            return dict(entry), payload
+
+    def delete(self, entry_id: str) -> bool:
+        with self._lock:
+            entry = self._entries.pop(entry_id, None)
+            if entry is None:
+                return False
+            (self.directory / entry["payload"]).unlink(missing_ok=True)
+            self._save()
+        return True
+
+    def clear(self) -> int:
+        with self._lock:
+            count = len(self._entries)
+            self._entries = {}
+            shutil.rmtree(self.directory, ignore_errors=True)
+        return count
+
+    def _prune(self):
+        """Drop least recently opened results beyond the size quota (lock held)."""
+        quota_mb = settings.get("archive_quota_mb")
+        if quota_mb is None:
+            return
+        limit = quota_mb * 1024 * 1024
+        total = sum(entry["size_bytes"] for entry in self._entries.values())
+        for entry in sorted(self._entries.values(), key=lambda e: e["opened_at"]):
+            if total <= limit:
+                break
+            (self.directory / entry["payload"]).unlink(missing_ok=True)
+            del self._entries[entry["id"]]
+            total -= entry["size_bytes"]
+            logger.info(f"Dropped archived result '{entry['name']}' to stay within the archive quota")
+
+    def usage(self) -> Dict[str, Any]:
+        with self._lock:
+            return {
+                "directory": str(self.directory),
+                "num_results": len(self._entries),
+                "total_bytes": sum(entry["size_bytes"] for entry in self._entries.values()),
+                "quota_mb": settings.get("archive_quota_mb"),
+            }
+
+
+result_archive = ResultArchive()
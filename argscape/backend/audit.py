@@ -0,0 +1,194 @@
+"""
+Per-file audit trail for reproducibility records.
+Every input tree sequence gets a local log, keyed by its content fingerprint,
+of when and how it was opened, which operations ran on it (and on files
+derived from it) with which parameters, and which exports were produced.
+Logs live in the config directory, survive restarts, and can be exported as
+JSON for lab notebooks and methods sections. Protected files, and every file
+in protected mode, are not logged (see argscape.backend.protected).
+"""
+
+import json
+import logging
+import re
+import threading
+import time
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from argscape.backend.session_storage import session_storage
+from argscape.backend.settings import get_data_dir
+
+logger = logging.getLogger(__name__)
+
+AUDIT_DIRNAME = "audit"
+BINDINGS_FILENAME = "bindings.json"
+AUDIT_FORMAT = "argscape-audit"
+AUDIT_VERSION = 1
+
+# Oldest events are dropped past this, so a long-lived file's log stays readable
+MAX_EVENTS_PER_FILE = 5000
+
+# Event kinds
+EVENT_OPENED = "opened"
+EVENT_OPERATION = "operation"
+EVENT_EXPORT = "export"
+
+_AUDIT_ID_PATTERN = re.compile(r"^[0-9a-f]{16}$")
+
+
+class AuditLog:
+    """Audit logs per input file, and which session files belong to which log."""
+
+    def __init__(self, directory: Optional[Path] = None):
+        self._directory = directory
+        self._lock = threading.Lock()
+        # "session_id/filename" -> audit ID, so derived files log against their input
+        self._bindings: Dict[str, str] = {}
+        self._load_bindings()
+
+    @property
+    def directory(self) -> Path:
+        return self._directory or get_data_dir() / AUDIT_DIRNAME
+
+    def _log_path(self, audit_id: str) -> Path:
+        if not _AUDIT_ID_PATTERN.match(audit_id):
+            raise KeyError(audit_id)
+        return self.directory / f"{audit_id}.json"
+
+    def _load_bindings(self):
+        path = self.directory / BINDINGS_FILENAME
+        if not path.exists():
+            return
+        try:
+            with open(path, "r") as f:
+                self._bindings = json.load(f)
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable audit bindings {path}: {e}")
+
+    def _save_bindings(self):
+        self.directory.mkdir(parents=True, exist_ok=True)
+        with open(self.directory / BINDINGS_FILENAME, "w") as f:
+            json.dump(self._bindings, f)
+
+    def _read(self, audit_id: str) -> Dict[str, Any]:
+        with open(self._log_path(audit_id), "r") as f:
+            return json.load(f)
+
+    def _append(self, audit_id: str, event: Dict[str, Any], name: Optional[str] = None):
+        """Add an event to a log, creating the log if needed (lock held)."""
+        path = self._log_path(audit_id)
+        if path.exists():
+            log = self._read(audit_id)
+        else:
+            log = {"format": AUDIT_FORMAT, "version": AUDIT_VERSION, "id": audit_id, "names": [], "created_at": time.time(), "events": []}
+        if name and name not in log["names"]:
+            log["names"].append(name)
+        log["events"].append({"time": time.time(), **event})
+        log["events"] = log["events"][-MAX_EVENTS_PER_FILE:]
+        log["updated_at"] = time.time()
+        self.directory.mkdir(parents=True, exist_ok=True)
+        with open(path, "w") as f:
+            json.dump(log, f, indent=2)
+
+    def record_open(self, session_id: str, filename: str, fingerprint: str, details: Optional[Dict[str, Any]] = None):
+        """Log that an input file was opened; a file stored again under the same name is not logged twice."""
+        if session_storage.is_file_protected(session_id, filename):
+            return
+        audit_id = fingerprint[:16]
+        key = f"{session_id}/{filename}"
+        try:
+            with self._lock:
+                if self._bindings.get(key) == audit_id:
+                    return
+                self._bindings[key] = audit_id
+                self._save_bindings()
+                self._append(audit_id, {"kind": EVENT_OPENED, "filename": filename, "fingerprint": fingerprint, **(details or {})}, filename)
+        except Exception as e:
+            logger.warning(f"Failed to update audit log for {filename}: {e}")
+
+    def record_derived(self, session_id: str, filename: str, parent: str):
+        """Attach a derived file to its input's log; the operation that made it is logged with record()."""
+        if session_storage.is_file_protected(session_id, filename):
+            return
+        try:
+            with self._lock:
+                audit_id = self._bindings.get(f"{session_id}/{parent}")
+                if audit_id is None:
+                    return
+                self._bindings[f"{session_id}/{filename}"] = audit_id
+                self._save_bindings()
+        except Exception as e:
+            logger.warning(f"Failed to update audit log for {filename}: {e}")
+
+    def record(
+        self,
+        session_id: str,
+        filename: str,
+        kind: str,
+        operation: str,
+        params: Optional[Dict[str, Any]] = None,
+        output: Optional[str] = None
+    ):
+        """Log an operation or export on a session file, if it belongs to an input's log."""
+        # Protected files (and anything in protected mode) leave nothing on disk, parameters included
+        if session_storage.is_file_protected(session_id, filename):
+            return
+        try:
+            with self._lock:
+                audit_id = self._bindings.get(f"{session_id}/{filename}")
+                if audit_id is None:
+                    return
+                event = {"kind": kind, "operation": operation, "filename": filename, "params": params or {}}
+                if output:
+                    event["output"] = output
+                self._append(audit_id, event)
+        except Exception as e:
+            logger.warning(f"Failed to update audit log for {filename}: {e}")
+
+    def audit_id_for(self, session_id: str, filename: str) -> Optional[str]:
+        with self._lock:
+            return self._bindings.get(f"{session_id}/{filename}")
+
+    def get(self, audit_id: str) -> Dict[str, Any]:
+        """A full log; raises KeyError if there is none."""
+        with self._lock:
+            try:
+                return self._read(audit_id)
+            except FileNotFoundError:
+                raise KeyError(audit_id)
+
+    def list(self) -> List[Dict[str, Any]]:
+        """Summaries of all logs, most recently updated first."""
+        summaries = []
+        with self._lock:
+            paths = list(self.directory.glob("*.json")) if self.directory.is_dir() else []
+            for path in paths:
+                if path.name == BINDINGS_FILENAME:
+                    continue
+                try:
+                    with open(path, "r") as f:
+                        log = json.load(f)
+                except Exception:
+                    continue
+                summaries.append({
+                    "id": log["id"],
+                    "names": log["names"],
+                    "created_at": log["created_at"],
+                    "updated_at": log.get("updated_at", log["created_at"]),
+                    "num_events": len(log["events"]),
+                })
+        return sorted(summaries, key=lambda s: -s["updated_at"])
+
+    def delete(self, audit_id: str) -> bool:
+        with self._lock:
+            path = self._log_path(audit_id)
+            if not path.exists():
+                return False
+            path.unlink()
+            self._bindings = {k: v for k, v in self._bindings.items() if v != audit_id}
+            self._save_bindings()
+        return True
+
+
+audit_log = AuditLog()
@@ -0,0 +1,58 @@
+"""
+Per-launch bearer token authentication for the local backend.
+The CLI generates a random token and passes it to the server via the
+environment; the browser receives it once through the launch URL and keeps it
+in an HttpOnly cookie, so other local processes cannot read loaded data.
+"""
+
+import hmac
+import os
+import secrets
+from typing import Optional
+
+from fastapi import Request
+
+AUTH_TOKEN_ENV_VAR = "ARGSCAPE_AUTH_TOKEN"
+AUTH_COOKIE_NAME = "argscape_token"
+AUTH_QUERY_PARAM = "token"
+
+# Paths reachable without a token (readiness probes)
+PUBLIC_PATHS = ("/api/health",)
+
+
+def generate_auth_token() -> str:
+    """Generate a random token for one server launch."""
+    return secrets.token_urlsafe(32)
+
+
+def get_auth_token() -> Optional[str]:
+    """The token required by this server, or None when authentication is disabled."""
+    return os.getenv(AUTH_TOKEN_ENV_VAR) or None
+
+
+def is_local_mode() -> bool:
+    """Whether the CLI launched this server with a token, so every API caller is this machine's user.
+
+    Hosted deployments (startup.py) and `--no-auth` launches have no token, so
+    anything that changes process-wide configuration, reads server paths or
+    runs commands is refused there.
+    """
+    return get_auth_token() is not None
+
+
+def token_matches(candidate: Optional[str], token: str) -> bool:
+    return candidate is not None and hmac.compare_digest(candidate.encode(), token.encode())
+
+
+def get_request_token(request: Request) -> Optional[str]:
+    """Token presented by a request via Authorization header or cookie."""
+    authorization = request.headers.get("authorization", "")
+    if authorization.lower().startswith("bearer "):
+        return authorization[7:].strip()
+    return request.cookies.get(AUTH_COOKIE_NAME)
+
+
+def is_request_authorized(request: Request, token: str) -> bool:
+    if request.method == "OPTIONS" or request.url.path in PUBLIC_PATHS:
+        return True
+    return token_matches(get_request_token(request), token)
@@ -0,0 +1,152 @@
+"""
+Axis tick computation for genomic and time scales.
+Ticks fall on "nice" numbers (1, 2 or 5 times a power of ten), genomic labels
+switch between bp, kb and Mb, and time axes can be shown in generations or
+converted to years, linearly or on a log scale. Every view and export asks
+this module for its axes, so they all agree.
+"""
+
+import math
+from typing import Any, Dict, List, Optional
+
+from argscape.backend.formatting import DEFAULT_LOCALE, format_number
+
+DEFAULT_NUM_TICKS = 6
+MAX_NUM_TICKS = 50
+
+GENOMIC_UNITS = (("Mb", 1e6), ("kb", 1e3), ("bp", 1.0))
+
+
+def nice_step(span: float, target_ticks: int = DEFAULT_NUM_TICKS) -> float:
+    """Tick spacing of 1, 2 or 5 times a power of ten giving about `target_ticks` ticks."""
+    if span <= 0:
+        return 1.0
+    raw = span / max(target_ticks, 1)
+    magnitude = 10 ** math.floor(math.log10(raw))
+    residual = raw / magnitude
+    # Round to the nearest nice multiple (thresholds are the geometric midpoints)
+    for multiple, threshold in ((1, 1.5), (2, 3.5), (5, 7.5)):
+        if residual < threshold:
+            return multiple * magnitude
+    return 10 * magnitude
+
+
+def nice_ticks(vmin: float, vmax: float, target_ticks: int = DEFAULT_NUM_TICKS) -> List[float]:
+    """Nice tick values within [vmin, vmax]."""
+    if not 1 <= target_ticks <= MAX_NUM_TICKS:
+        raise ValueError(f"Number of ticks must be between 1 and {MAX_NUM_TICKS}")
+    if vmax <= vmin:
+        return [vmin]
+    step = nice_step(vmax - vmin, target_ticks)
+    first = math.ceil(vmin / step - 1e-9) * step
+    count = int(math.floor((vmax - first) / step + 1e-9)) + 1
+    return [round(first + i * step, 12) for i in range(count)]
+
+
+def log_ticks(vmin: float, vmax: float) -> Dict[str, List[float]]:
+    """Major ticks at powers of ten and minor ticks at 2-9 times them, within [vmin, vmax]."""
+    if vmax <= 0:
+        return {"major": [], "minor": []}
+    low = vmin if vmin > 0 else vmax * 1e-6
+    major, minor = [], []
+    for exponent in range(math.floor(math.log10(low)), math.ceil(math.log10(vmax)) + 1):
+        base = 10.0 ** exponent
+        if low <= base <= vmax:
+            major.append(base)
+        minor.extend(m * base for m in range(2, 10) if low <= m * base <= vmax)
+    return {"major": major, "minor": minor}
+
+
+def decimals_for_step(step: float) -> int:
+    """Decimal places needed to tell ticks `step` apart."""
+    if step <= 0 or step >= 1:
+        return 0
+    return max(0, -math.floor(math.log10(step) + 1e-9))
+
+
+def genomic_unit(span: float) -> tuple:
+    """Display unit for a genomic span: Mb from 1 Mb, kb from 10 kb, otherwise bp."""
+    if span >= 1e6:
+        return GENOMIC_UNITS[0]
+    if span >= 1e4:
+        return GENOMIC_UNITS[1]
+    return GENOMIC_UNITS[2]
+
+
+def genomic_axis(
+    start: float,
+    end: float,
+    target_ticks: int = DEFAULT_NUM_TICKS,
+    locale: str = DEFAULT_LOCALE
+) -> Dict[str, Any]:
+    """Ticks and labels for a genomic coordinate axis."""
+    if end < start:
+        raise ValueError("Axis end must not be before its start")
+    unit, scale = genomic_unit(max(abs(start), abs(end)))
+    ticks = nice_ticks(start, end, target_ticks)
+    step = ticks[1] - ticks[0] if len(ticks) > 1 else max(end - start, 1.0)
+    decimals = decimals_for_step(step / scale)
+    return {
+        "kind": "genomic",
+        "min": start,
+        "max": end,
+        "unit": unit,
+        "ticks": [{"value": t, "label": f"{format_number(t / scale, decimals, locale)} {unit}"} for t in ticks],
+    }
+
+
+def time_axis(
+    vmin: float,
+    vmax: float,
+    time_units: str = "generations",
+    generation_time: Optional[float] = None,
+    log_scale: bool = False,
+    target_ticks: int = DEFAULT_NUM_TICKS,
+    locale: str = DEFAULT_LOCALE,
+    display_scale: Optional[float] = None,
+    display_unit: Optional[str] = None
+) -> Dict[str, Any]:
+    """Ticks and labels for a time axis.
+
+    Args:
+        vmin, vmax: Range in the tree sequence's time units
+        time_units: The tree sequence's time units
+        generation_time: Years per generation; when given for a tree sequence in
+            generations, labels are shown in years (tick values stay in generations)
+        log_scale: Powers-of-ten ticks with minor ticks
+        display_scale, display_unit: A conversion from a TimeCalibration, used
+            instead of `generation_time` when given
+    """
+    if vmax < vmin:
+        raise ValueError("Axis end must not be before its start")
+    scale, unit = 1.0, time_units
+    if display_scale is not None:
+        scale, unit = display_scale, display_unit or time_units
+    elif generation_time is not None:
+        if generation_time <= 0:
+            raise ValueError("generation_time must be positive")
+        if time_units == "generations":
+            scale, unit = generation_time, "years"
+
+    if log_scale:
+        ticks = log_ticks(vmin * scale, vmax * scale)
+        major = ticks["major"]
+        minor = [t / scale for t in ticks["minor"]]
+        labels = [format_number(t, decimals_for_step(t), locale) for t in major]
+        major = [t / scale for t in major]
+    else:
+        # Nice numbers in the displayed unit, mapped back to tree sequence time
+        shown = nice_ticks(vmin * scale, vmax * scale, target_ticks)
+        step = shown[1] - shown[0] if len(shown) > 1 else 1.0
+        labels = [format_number(t, decimals_for_step(step), locale) for t in shown]
+        major, minor = [t / scale for t in shown], []
+
+    return {
+        "kind": "time",
+        "min": vmin,
+        "max": vmax,
+        "unit": unit,
+        "log_scale": log_scale,
+        "ticks": [{"value": value, "label": label} for value, label in zip(major, labels)],
+        "minor_ticks": minor,
+    }
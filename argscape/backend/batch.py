@@ -0,0 +1,315 @@
+"""
+Batch processing of many tree sequences through a fixed pipeline.
+Each file runs validate -> simplify -> layout -> export steps in isolation, so a
+failure in one file is recorded in the report without stopping the others.
+Outputs are written to a per-batch directory in the session and can be
+downloaded together as a zip archive with the summary report.
+"""
+
+import csv
+import io
+import json
+import logging
+import time
+import zipfile
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import Job, JobCancelled
+from argscape.backend.layout_utils import build_layout_document, relax_neighborhood, serialize_layout_document
+from argscape.backend.palettes import resolve_palette
+from argscape.backend.timing import timed
+
+logger = logging.getLogger(__name__)
+
+BATCH_STEPS = ("validate", "simplify", "layout", "export_tables", "export_image")
+DEFAULT_BATCH_STEPS = ("validate", "simplify", "layout", "export_tables")
+IMAGE_FORMATS = ("png", "svg")
+MAX_BATCH_WORKERS = 4
+REPORT_FILENAME = "batch_report.json"
+
+# Per-file states
+FILE_QUEUED = "queued"
+FILE_RUNNING = "running"
+FILE_DONE = "done"
+FILE_FAILED = "failed"
+FILE_SKIPPED = "skipped"
+
+EXPORTED_TABLES = {
+    "nodes": ("flags", "time", "population", "individual", "metadata"),
+    "edges": ("left", "right", "parent", "child"),
+    "sites": ("position", "ancestral_state", "metadata"),
+    "mutations": ("site", "node", "time", "parent", "derived_state", "metadata"),
+    "individuals": ("flags", "location", "parents", "metadata"),
+    "populations": ("metadata",),
+}
+
+
+def validate_tree_sequence(ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """Integrity check and basic statistics; raises ValueError for unusable files."""
+    try:
+        tables = ts.dump_tables()
+        tables.tree_sequence()  # Re-runs tskit's table integrity checks
+    except Exception as e:
+        raise ValueError(f"Table integrity check failed: {e}")
+    if ts.num_samples == 0:
+        raise ValueError("Tree sequence has no sample nodes")
+
+    warnings = []
+    if ts.num_sites == 0:
+        warnings.append("No sites or mutations")
+    multi_root_trees = sum(1 for tree in ts.trees() if tree.num_roots > 1)
+    if multi_root_trees:
+        warnings.append(f"{multi_root_trees} local trees have more than one root")
+    in_edges = np.zeros(ts.num_nodes, dtype=bool)
+    in_edges[ts.edges_parent] = True
+    in_edges[ts.edges_child] = True
+    is_sample = (ts.nodes_flags & tskit.NODE_IS_SAMPLE) != 0
+    unreferenced = int(np.sum(~in_edges & ~is_sample))
+    if unreferenced:
+        warnings.append(f"{unreferenced} non-sample nodes are not in any edge")
+    isolated_samples = int(np.sum(~in_edges & is_sample))
+    if isolated_samples:
+        warnings.append(f"{isolated_samples} samples are not in any edge")
+    return {
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_samples": ts.num_samples,
+        "num_trees": ts.num_trees,
+        "num_mutations": ts.num_mutations,
+        "sequence_length": ts.sequence_length,
+        "warnings": warnings,
+    }
+
+
+def compute_batch_layout(ts: tskit.TreeSequence, iterations: int = 50, job: Optional[Job] = None) -> Dict[int, List[float]]:
+    """Headless layout: samples in first-tree order, ancestors above their children, then relaxed."""
+    times = ts.tables.nodes.time
+    max_time = float(times.max()) or 1.0
+    first_tree = ts.first()
+    sample_order = [u for u in first_tree.nodes(order="postorder") if ts.node(u).is_sample()]
+    seen = set(sample_order)
+    sample_order += [int(u) for u in ts.samples() if u not in seen]
+
+    x = np.full(ts.num_nodes, np.nan)
+    for i, u in enumerate(sample_order):
+        x[u] = i / max(len(sample_order) - 1, 1)
+
+    # Ancestors sit at the mean x of their children, filled in from the youngest nodes up
+    children: Dict[int, set] = {}
+    for edge in ts.edges():
+        children.setdefault(edge.parent, set()).add(edge.child)
+    for u in np.argsort(times, kind="stable"):
+        if np.isnan(x[u]) and u in children:
+            child_x = [x[c] for c in children[u] if not np.isnan(x[c])]
+            x[u] = float(np.mean(child_x)) if child_x else 0.5
+    x[np.isnan(x)] = 0.5
+
+    positions = {int(u): [float(x[u]), float(times[u]) / max_time] for u in range(ts.num_nodes)}
+    relaxed = relax_neighborhood(
+        ts,
+        {u: (p[0], p[1]) for u, p in positions.items()},
+        [int(u) for u in ts.samples()],
+        free_node_ids=[u for u in positions if not ts.node(u).is_sample()],
+        iterations=iterations,
+        cancel_token=job.token if job else None
+    )
+    for u, position in relaxed["positions"].items():
+        positions[int(u)] = list(position)
+    return positions
+
+
+def _csv_value(value: Any) -> Any:
+    if isinstance(value, (list, tuple, np.ndarray)):
+        return ";".join(str(v) for v in value)
+    if isinstance(value, bytes):
+        return value.decode("utf-8", errors="replace")
+    if isinstance(value, dict):
+        return json.dumps(value)
+    return value
+
+
+def export_tables_zip(ts: tskit.TreeSequence) -> bytes:
+    """The main tskit tables as CSV files in a zip archive."""
+    buffer = io.BytesIO()
+    with zipfile.ZipFile(buffer, "w", zipfile.ZIP_DEFLATED) as archive:
+        for name, columns in EXPORTED_TABLES.items():
+            table = getattr(ts.tables, name)
+            if table.num_rows == 0:
+                continue
+            text = io.StringIO()
+            writer = csv.writer(text)
+            writer.writerow(("id",) + columns)
+            for i, row in enumerate(table):
+                writer.writerow([i] + [_csv_value(getattr(row, column)) for column in columns])
+            archive.writestr(f"{name}.csv", text.getvalue())
+    return buffer.getvalue()
+
+
+@timed("render")
+def render_layout_image(
+    ts: tskit.TreeSequence,
+    positions: Dict[int, List[float]],
+    image_format: str = "png",
+    palette_choice: Optional[Dict[str, Any]] = None
+) -> bytes:
+    """Render nodes and edges at the given positions with matplotlib.
+
+    With several populations, nodes use the same population colors as the
+    frontend (see argscape.backend.palettes).
+    """
+    import matplotlib
+    matplotlib.use("Agg")
+    import matplotlib.pyplot as plt
+    from matplotlib.collections import LineCollection
+
+    segments = [
+        (positions[edge.child][:2], positions[edge.parent][:2])
+        for edge in ts.edges() if edge.child in positions and edge.parent in positions
+    ]
+    if ts.num_populations > 1:
+        node_population = ts.tables.nodes.population
+        groups = [
+            (population["color"], [u for u in positions if node_population[u] == population["id"]])
+            for population in resolve_palette(ts, palette_choice)["populations"]
+        ]
+    else:
+        samples = set(int(u) for u in ts.samples())
+        groups = [
+            ("#1f77b4", [u for u in positions if u not in samples]),
+            ("#2ca02c", [u for u in positions if u in samples]),
+        ]
+    fig, ax = plt.subplots(figsize=(10, 7), dpi=150)
+    try:
+        ax.add_collection(LineCollection(segments, colors="#888888", linewidths=0.5, alpha=0.6))
+        for color, nodes in groups:
+            points = np.array([positions[u][:2] for u in nodes])
+            if len(points):
+                ax.scatter(points[:, 0], points[:, 1], s=6, c=color, zorder=2)
+        ax.set_xlabel("Layout position")
+        ax.set_ylabel("Relative time")
+        ax.autoscale()
+        buffer = io.BytesIO()
+        fig.savefig(buffer, format=image_format, bbox_inches="tight")
+        return buffer.getvalue()
+    finally:
+        plt.close(fig)
+
+
+def _dump_tree_sequence(ts: tskit.TreeSequence) -> bytes:
+    buffer = io.BytesIO()
+    ts.dump(buffer)
+    return buffer.getvalue()
+
+
+def process_file(
+    job: Job,
+    filename: str,
+    ts: tskit.TreeSequence,
+    steps: List[str],
+    options: Dict[str, Any],
+    write_output: Callable[[str, bytes], None],
+    set_step: Callable[[str], None]
+) -> Dict[str, Any]:
+    """Run the pipeline steps on one file and return its report entry."""
+    base = filename.rsplit(".", 1)[0]
+    outputs: List[str] = []
+    timings: Dict[str, float] = {}
+    result: Dict[str, Any] = {}
+    positions = None
+
+    for step in steps:
+        job.token.check()
+        set_step(step)
+        start = time.perf_counter()
+        if step == "validate":
+            result["validation"] = validate_tree_sequence(ts)
+        elif step == "simplify":
+            num_nodes = ts.num_nodes
+            ts = ts.simplify(keep_unary=options.get("keep_unary", False))
+            write_output(f"{base}_simplified.trees", _dump_tree_sequence(ts))
+            outputs.append(f"{base}_simplified.trees")
+            result["simplify"] = {"nodes_before": num_nodes, "nodes_after": ts.num_nodes}
+        elif step == "layout":
+            positions = compute_batch_layout(ts, options.get("layout_iterations", 50), job)
+            document = build_layout_document(ts, filename, positions, "batch_relax", {"iterations": options.get("layout_iterations", 50)})
+            write_output(f"{base}_layout.json", serialize_layout_document(document, "json"))
+            outputs.append(f"{base}_layout.json")
+        elif step == "export_tables":
+            write_output(f"{base}_tables.zip", export_tables_zip(ts))
+            outputs.append(f"{base}_tables.zip")
+        elif step == "export_image":
+            if positions is None:
+                positions = compute_batch_layout(ts, options.get("layout_iterations", 50), job)
+            image_format = options.get("image_format", "png")
+            palette_choice = options.get("palettes", {}).get(filename)
+            write_output(f"{base}.{image_format}", render_layout_image(ts, positions, image_format, palette_choice))
+            outputs.append(f"{base}.{image_format}")
+        timings[step] = round(time.perf_counter() - start, 4)
+
+    return {**result, "outputs": outputs, "step_seconds": timings}
+
+
+def run_batch(
+    job: Job,
+    filenames: List[str],
+    steps: List[str],
+    options: Dict[str, Any],
+    get_tree_sequence: Callable[[str], Optional[tskit.TreeSequence]],
+    write_output: Callable[[str, bytes], None],
+    workers: int = 1
+) -> Dict[str, Any]:
+    """Process files sequentially (workers=1) or in parallel, isolating failures per file.
+
+    Live per-file status is published in job.details["files"].
+    """
+    files = {name: {"status": FILE_QUEUED, "step": None, "error": None} for name in filenames}
+    job.details["files"] = files
+    completed: List[str] = []
+    started = time.perf_counter()
+
+    def run_one(filename: str) -> Tuple[str, Dict[str, Any]]:
+        entry = files[filename]
+        if job.token.cancelled:
+            entry["status"] = FILE_SKIPPED
+            return filename, {}
+        entry["status"] = FILE_RUNNING
+        file_start = time.perf_counter()
+        try:
+            ts = get_tree_sequence(filename)
+            if ts is None:
+                raise ValueError("File not found in session")
+            report = process_file(job, filename, ts, steps, options, write_output, lambda step: entry.update(step=step))
+            entry.update(status=FILE_DONE, step=None)
+        except JobCancelled:
+            entry["status"] = FILE_SKIPPED
+            return filename, {}
+        except Exception as e:
+            entry.update(status=FILE_FAILED, error=str(e))
+            logger.warning(f"Batch {job.job_id}: {filename} failed at step {entry['step']}: {e}")
+            report = {}
+        entry["seconds"] = round(time.perf_counter() - file_start, 4)
+        completed.append(filename)
+        job.progress = len(completed) / len(filenames)
+        return filename, report
+
+    with ThreadPoolExecutor(max_workers=max(1, min(workers, MAX_BATCH_WORKERS)), thread_name_prefix="argscape-batch") as executor:
+        reports = dict(executor.map(run_one, filenames))
+    job.token.check()
+
+    summary = {
+        "job_id": job.job_id,
+        "steps": steps,
+        "options": options,
+        "num_files": len(filenames),
+        "num_succeeded": sum(1 for f in files.values() if f["status"] == FILE_DONE),
+        "num_failed": sum(1 for f in files.values() if f["status"] == FILE_FAILED),
+        "total_seconds": round(time.perf_counter() - started, 4),
+        "files": [{"filename": name, **files[name], **reports.get(name, {})} for name in filenames],
+    }
+    write_output(REPORT_FILENAME, json.dumps(summary, indent=2).encode("utf-8"))
+    logger.info(f"Batch {job.job_id} finished: {summary['num_succeeded']}/{len(filenames)} files succeeded")
+    return summary
@@ -0,0 +1,326 @@
+"""
+Benchmark suite for ARGscape's backend pipeline.
+Times load, simplify, layout and stats on synthetic ARGs of increasing size,
+compares the vectorized edge-table traversal against per-edge loops, and
+returns a machine-readable report for comparing machines and tracking
+regressions.
+"""
+
+import io
+import json
+import logging
+import platform
+import statistics
+import sys
+import time
+from collections import deque
+from datetime import datetime
+from typing import Any, Callable, Dict, List, Optional
+
+import msprime
+import numpy as np
+import tskit
+
+from argscape import __version__
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.graph_utils import convert_tree_sequence_to_graph_data
+from argscape.backend.layout_utils import relax_neighborhood
+from argscape.backend.resources import get_cpu_count
+from argscape.backend.tskit_utils import (
+    compute_node_metrics,
+    get_polytomy_stats,
+    load_tree_sequence_from_file,
+)
+from argscape.backend.tskit_utils.traversal import (
+    adjacency_pairs,
+    clear_edge_index,
+    get_edge_index,
+    neighborhood,
+    node_gaps,
+    simd_features,
+)
+
+logger = logging.getLogger(__name__)
+
+BENCHMARK_REPORT_VERSION = 1
+
+# Datasets are simulated with fixed seeds so every machine benchmarks identical ARGs
+BENCHMARK_DATASETS = {
+    "tiny": {"num_samples": 20, "sequence_length": 1e5},
+    "small": {"num_samples": 100, "sequence_length": 1e6},
+    "medium": {"num_samples": 500, "sequence_length": 5e6},
+    "large": {"num_samples": 2000, "sequence_length": 2e7},
+    "chromosome": {"num_samples": 1000, "sequence_length": 1e8},
+}
+
+BENCHMARK_PROFILES = {
+    "quick": {"datasets": ["tiny", "small"], "repeats": 1},
+    "standard": {"datasets": ["tiny", "small", "medium"], "repeats": 3},
+    "full": {"datasets": ["tiny", "small", "medium", "large"], "repeats": 3},
+    # Only the edge-table traversal comparison is meaningful at this size
+    "chromosome": {"datasets": ["chromosome"], "repeats": 1, "traversal_only": True},
+}
+
+BENCHMARK_SEED = 42
+
+
+def simulate_benchmark_dataset(name: str) -> tskit.TreeSequence:
+    """Simulate one of the benchmark datasets deterministically."""
+    spec = BENCHMARK_DATASETS[name]
+    ts = msprime.sim_ancestry(
+        samples=spec["num_samples"],
+        sequence_length=spec["sequence_length"],
+        recombination_rate=1e-8,
+        population_size=10_000,
+        random_seed=BENCHMARK_SEED,
+    )
+    return msprime.sim_mutations(ts, rate=1e-8, random_seed=BENCHMARK_SEED)
+
+
+def _time_stage(
+    func: Callable[[], Any],
+    repeats: int,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, float]:
+    durations = []
+    for _ in range(repeats):
+        check_cancelled(cancel_token)
+        start = time.perf_counter()
+        func()
+        durations.append(time.perf_counter() - start)
+    return {
+        "median_seconds": statistics.median(durations),
+        "min_seconds": min(durations),
+        "max_seconds": max(durations),
+    }
+
+
+def _initial_positions(ts: tskit.TreeSequence) -> Dict[int, List[float]]:
+    """Spread nodes by ID along x and by time along y, as a layout starting point."""
+    times = ts.tables.nodes.time
+    max_time = float(times.max()) if len(times) else 1.0
+    return {u: [float(u) / max(ts.num_nodes, 1), float(times[u]) / (max_time or 1.0)] for u in range(ts.num_nodes)}
+
+
+def benchmark_tree_sequence(
+    ts: tskit.TreeSequence,
+    repeats: int = 1,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Dict[str, float]]:
+    """Time each pipeline stage on a single tree sequence."""
+    buffer = io.BytesIO()
+    ts.dump(buffer)
+    contents = buffer.getvalue()
+
+    stages = {}
+
+    stages["load"] = _time_stage(lambda: load_tree_sequence_from_file(contents, "benchmark.trees"), repeats, cancel_token)
+    stages["simplify"] = _time_stage(lambda: ts.simplify(), repeats, cancel_token)
+    stages["graph_data"] = _time_stage(lambda: convert_tree_sequence_to_graph_data(ts), repeats, cancel_token)
+
+    positions = _initial_positions(ts)
+    anchors = [int(ts.samples()[0])] if ts.num_samples else []
+    stages["layout_relax"] = _time_stage(
+        lambda: relax_neighborhood(ts, positions, anchors, hops=3, cancel_token=cancel_token), repeats, cancel_token
+    )
+
+    def compute_stats():
+        compute_node_metrics(ts, include_betweenness=False, cancel_token=cancel_token)
+        get_polytomy_stats(ts, cancel_token)
+        if ts.num_sites > 0:
+            ts.diversity()
+
+    stages["stats"] = _time_stage(compute_stats, repeats, cancel_token)
+
+    return stages
+
+
+def _loop_missing_intervals(ts: tskit.TreeSequence) -> Dict[int, List[List[float]]]:
+    """Per-edge reference for node_gaps over the samples."""
+    covered: Dict[int, List[List[float]]] = {int(u): [] for u in ts.samples()}
+    for edge in ts.edges():
+        for u in (edge.parent, edge.child):
+            if u in covered:
+                covered[u].append([edge.left, edge.right])
+    gaps = {}
+    for u, intervals in covered.items():
+        intervals.sort()
+        position, missing = 0.0, []
+        for left, right in intervals:
+            if left > position:
+                missing.append([position, left])
+            position = max(position, right)
+        if position < ts.sequence_length:
+            missing.append([position, ts.sequence_length])
+        gaps[u] = missing
+    return gaps
+
+
+def _loop_neighborhood(ts: tskit.TreeSequence, seeds: List[int], hops: int) -> set:
+    """Per-edge adjacency dict and queue BFS, the reference for neighborhood."""
+    adjacency: Dict[int, set] = {}
+    for edge in ts.edges():
+        adjacency.setdefault(edge.parent, set()).add(edge.child)
+        adjacency.setdefault(edge.child, set()).add(edge.parent)
+    distance = {s: 0 for s in seeds}
+    queue = deque(seeds)
+    while queue:
+        u = queue.popleft()
+        if distance[u] >= hops:
+            continue
+        for v in adjacency.get(u, ()):
+            if v not in distance:
+                distance[v] = distance[u] + 1
+                queue.append(v)
+    return set(distance)
+
+
+def _loop_polytomic_parents(ts: tskit.TreeSequence) -> List[int]:
+    """Per-edge reference for finding parents with more than two distinct children."""
+    children: Dict[int, set] = {}
+    for edge in ts.edges():
+        children.setdefault(edge.parent, set()).add(edge.child)
+    return sorted(u for u, c in children.items() if len(c) > 2)
+
+
+def benchmark_traversal(
+    ts: tskit.TreeSequence,
+    repeats: int = 1,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Time the vectorized edge-table primitives against the per-edge loops they replace."""
+    seeds = [int(u) for u in ts.samples()[:10]]
+    samples = ts.samples()
+    # The vectorized side is timed with a fresh index each repeat, so building it is included
+    comparisons = {
+        "missing_intervals": (
+            lambda: node_gaps(ts, samples),
+            lambda: _loop_missing_intervals(ts),
+        ),
+        "neighborhood": (
+            lambda: neighborhood(ts, seeds, 3),
+            lambda: _loop_neighborhood(ts, seeds, 3),
+        ),
+        "adjacency": (
+            lambda: adjacency_pairs(ts),
+            lambda: {(e.parent, e.child) for e in ts.edges()},
+        ),
+        "polytomic_parents": (
+            lambda: np.flatnonzero(get_edge_index(ts).num_children() > 2),
+            lambda: _loop_polytomic_parents(ts),
+        ),
+    }
+
+    results = {}
+    for name, (vectorized, loop) in comparisons.items():
+        def fresh(func=vectorized):
+            clear_edge_index(ts)
+            func()
+
+        fast = _time_stage(fresh, repeats, cancel_token)
+        slow = _time_stage(loop, repeats, cancel_token)
+        results[name] = {
+            "vectorized": fast,
+            "loop": slow,
+            "speedup": slow["median_seconds"] / max(fast["median_seconds"], 1e-9),
+        }
+    return results
+
+
+def run_benchmark(
+    profile: str = "quick",
+    cancel_token: Optional[CancellationToken] = None,
+    on_progress: Optional[Callable[[str, float], None]] = None
+) -> Dict[str, Any]:
+    """Run a benchmark profile and return a machine-readable report.
+
+    Args:
+        profile: One of BENCHMARK_PROFILES ("quick", "standard", "full", "chromosome")
+        cancel_token: Optional token checked between timed repeats
+        on_progress: Called with (dataset, percent done) as each dataset starts
+    """
+    if profile not in BENCHMARK_PROFILES:
+        raise ValueError(f"Unknown benchmark profile '{profile}'. Expected one of {list(BENCHMARK_PROFILES)}")
+
+    config = BENCHMARK_PROFILES[profile]
+    logger.info(f"Running '{profile}' benchmark on datasets {config['datasets']}")
+
+    results = []
+    total_start = time.perf_counter()
+    for i, name in enumerate(config["datasets"]):
+        check_cancelled(cancel_token)
+        if on_progress:
+            on_progress(name, 100 * i / len(config["datasets"]))
+        sim_start = time.perf_counter()
+        ts = simulate_benchmark_dataset(name)
+        simulate_seconds = time.perf_counter() - sim_start
+
+        logger.info(f"Benchmarking '{name}': {ts.num_nodes} nodes, {ts.num_edges} edges, {ts.num_trees} trees")
+        results.append({
+            "dataset": name,
+            "num_samples": ts.num_samples,
+            "num_nodes": ts.num_nodes,
+            "num_edges": ts.num_edges,
+            "num_trees": ts.num_trees,
+            "num_mutations": ts.num_mutations,
+            "simulate_seconds": simulate_seconds,
+            "stages": {} if config.get("traversal_only") else benchmark_tree_sequence(ts, config["repeats"], cancel_token),
+            "traversal": benchmark_traversal(ts, config["repeats"], cancel_token),
+        })
+
+    return {
+        "format": "argscape-benchmark",
+        "version": BENCHMARK_REPORT_VERSION,
+        "argscape_version": __version__,
+        "profile": profile,
+        "repeats": config["repeats"],
+        "created_at": datetime.now().isoformat(),
+        "environment": {
+            "platform": platform.platform(),
+            "machine": platform.machine(),
+            "processor": platform.processor(),
+            "python_version": sys.version.split()[0],
+            "cpu_count": get_cpu_count(),
+            "tskit_version": tskit.__version__,
+            "msprime_version": msprime.__version__,
+            "numpy_version": np.__version__,
+            "simd_features": simd_features(),
+        },
+        "total_seconds": time.perf_counter() - total_start,
+        "results": results,
+    }
+
+
+def format_benchmark_report(report: Dict[str, Any]) -> str:
+    """Human-readable summary table for a benchmark report."""
+    stage_names = list(report["results"][0]["stages"]) if report["results"] else []
+    lines = [
+        f"ARGscape {report['argscape_version']} benchmark ({report['profile']}, {report['repeats']} repeats)",
+        f"{report['environment']['platform']}, {report['environment']['cpu_count']} CPUs",
+        "",
+        f"{'dataset':<10}{'nodes':>10}" + "".join(f"{name:>14}" for name in stage_names),
+    ]
+    for result in report["results"]:
+        lines.append(
+            f"{result['dataset']:<10}{result['num_nodes']:>10}"
+            + "".join(f"{result['stages'][name]['median_seconds']:>13.3f}s" for name in stage_names)
+        )
+    traversal_names = list(report["results"][0].get("traversal", {})) if report["results"] else []
+    if traversal_names:
+        lines.append("")
+        lines.append("Edge-table traversal speedup over per-edge loops")
+        lines.append(f"{'dataset':<10}{'edges':>10}" + "".join(f"{name:>20}" for name in traversal_names))
+        for result in report["results"]:
+            lines.append(
+                f"{result['dataset']:<10}{result['num_edges']:>10}"
+                + "".join(f"{result['traversal'][name]['speedup']:>19.1f}x" for name in traversal_names)
+            )
+    lines.append("")
+    lines.append(f"Total: {report['total_seconds']:.1f}s")
+    return "\n".join(lines)
+
+
+def write_benchmark_report(report: Dict[str, Any], path: str):
+    """Write a benchmark report as JSON."""
+    with open(path, 'w') as f:
+        json.dump(report, f, indent=2)
@@ -0,0 +1,227 @@
+"""
+Collaborative viewing (experimental).
+One instance hosts a room; the presenter's window reports its navigation and
+selection state over a WebSocket and followers on the LAN watch along. The
+hub keeps the last state per room and only sends what changed, as patches of
+dotted key paths, so followers stay in step without re-sending whole views.
+A follower joining late, or one that missed a version, gets a full snapshot.
+
+Followers need to reach the host, so run it on a LAN address
+(`argscape --host 0.0.0.0`) and enable the `collaboration` setting. The room
+code is a follower's credential: it opens that room's state, join route and
+WebSocket without the launch token, and nothing else.
+"""
+
+import asyncio
+import json
+import logging
+import re
+import secrets
+import time
+from typing import Any, Dict, List, Optional, Tuple
+
+from fastapi import WebSocket
+
+logger = logging.getLogger(__name__)
+
+ROLE_PRESENTER = "presenter"
+ROLE_FOLLOWER = "follower"
+COLLAB_ROLES = (ROLE_PRESENTER, ROLE_FOLLOWER)
+
+# Join codes avoid characters that are easy to misread aloud or on a projector
+ROOM_CODE_ALPHABET = "ABCDEFGHJKMNPQRSTUVWXYZ23456789"
+ROOM_CODE_LENGTH = 6
+MAX_ROOMS = 32
+MAX_STATE_BYTES = 64 * 1024
+# Rooms with no connections for this long are closed
+IDLE_SECONDS = 6 * 3600
+# HTTP routes a follower may call with only a room code: the room's state, and joining it
+FOLLOWER_ROUTES = (
+    ("GET", re.compile(r"^/api/collab/rooms/(?P<room_id>[^/]+)$")),
+    ("POST", re.compile(r"^/api/collab/rooms/(?P<room_id>[^/]+)/join$")),
+)
+
+
+def follower_route_room(method: str, path: str) -> Optional[str]:
+    """The room code in a request to one of FOLLOWER_ROUTES, else None."""
+    for route_method, pattern in FOLLOWER_ROUTES:
+        match = pattern.match(path)
+        if match and method == route_method:
+            return match.group("room_id")
+    return None
+
+
+def diff_state(old: Dict[str, Any], new: Dict[str, Any], prefix: str = "") -> Tuple[Dict[str, Any], List[str]]:
+    """Changed values and removed keys of `new` relative to `old`, as dotted paths.
+
+    Nested objects are compared key by key; lists and scalars are replaced whole.
+    """
+    changed: Dict[str, Any] = {}
+    removed: List[str] = []
+    for key, value in new.items():
+        path = f"{prefix}{key}"
+        previous = old.get(key)
+        if isinstance(value, dict) and isinstance(previous, dict):
+            sub_changed, sub_removed = diff_state(previous, value, f"{path}.")
+            changed.update(sub_changed)
+            removed.extend(sub_removed)
+        elif key not in old or previous != value:
+            changed[path] = value
+    removed.extend(f"{prefix}{key}" for key in old if key not in new)
+    return changed, removed
+
+
+def _validate_state(state: Any) -> Dict[str, Any]:
+    if not isinstance(state, dict):
+        raise ValueError("state must be a JSON object")
+    if any("." in key for key in state):
+        raise ValueError("State keys may not contain '.'")
+    if len(json.dumps(state)) > MAX_STATE_BYTES:
+        raise ValueError(f"state is larger than {MAX_STATE_BYTES // 1024} KiB")
+    return state
+
+
+class CollaborationRoom:
+    """A presenter, their followers and the shared view state."""
+
+    def __init__(self, room_id: str, session_id: str, filename: Optional[str]):
+        self.room_id = room_id
+        self.session_id = session_id
+        self.presenter_token = secrets.token_urlsafe(24)
+        self.filename = filename
+        self.state: Dict[str, Any] = {"filename": filename} if filename else {}
+        self.version = 0
+        self.presenter: Optional[WebSocket] = None
+        self.followers: Dict[WebSocket, float] = {}
+        self.created_at = time.time()
+        self.last_activity = self.created_at
+
+    def snapshot(self) -> Dict[str, Any]:
+        return {"type": "snapshot", "room_id": self.room_id, "version": self.version, "state": self.state}
+
+    def info(self) -> Dict[str, Any]:
+        return {
+            "room_id": self.room_id,
+            "filename": self.filename,
+            "version": self.version,
+            "presenter_connected": self.presenter is not None,
+            "followers": len(self.followers),
+            "created_at": self.created_at,
+        }
+
+
+class CollaborationHub:
+    """Open rooms, keyed by join code."""
+
+    def __init__(self):
+        self._rooms: Dict[str, CollaborationRoom] = {}
+        self._lock = asyncio.Lock()
+
+    def _expire(self):
+        now = time.time()
+        for room_id in [
+            r for r, room in self._rooms.items()
+            if room.presenter is None and not room.followers and now - room.last_activity > IDLE_SECONDS
+        ]:
+            del self._rooms[room_id]
+
+    async def create_room(self, session_id: str, filename: Optional[str] = None) -> CollaborationRoom:
+        async with self._lock:
+            self._expire()
+            if len(self._rooms) >= MAX_ROOMS:
+                raise ValueError(f"At most {MAX_ROOMS} rooms can be open at once")
+            room_id = "".join(secrets.choice(ROOM_CODE_ALPHABET) for _ in range(ROOM_CODE_LENGTH))
+            while room_id in self._rooms:
+                room_id = "".join(secrets.choice(ROOM_CODE_ALPHABET) for _ in range(ROOM_CODE_LENGTH))
+            room = CollaborationRoom(room_id, session_id, filename)
+            self._rooms[room_id] = room
+        logger.info(f"Opened collaboration room {room_id} for {filename or 'no file'}")
+        return room
+
+    def get(self, room_id: str) -> Optional[CollaborationRoom]:
+        return self._rooms.get(room_id.upper())
+
+    async def close_room(self, room_id: str):
+        async with self._lock:
+            room = self._rooms.pop(room_id.upper(), None)
+        if room is None:
+            return
+        for websocket in [room.presenter, *room.followers]:
+            if websocket is not None:
+                try:
+                    await websocket.send_json({"type": "closed", "room_id": room.room_id})
+                    await websocket.close()
+                except Exception:
+                    pass
+        logger.info(f"Closed collaboration room {room.room_id}")
+
+    async def connect(self, room: CollaborationRoom, websocket: WebSocket, role: str):
+        await websocket.accept()
+        async with self._lock:
+            if role == ROLE_PRESENTER:
+                previous, room.presenter = room.presenter, websocket
+            else:
+                previous = None
+                room.followers[websocket] = time.time()
+            room.last_activity = time.time()
+        if previous is not None:
+            # A reloaded presenter window takes over from the old one
+            try:
+                await previous.close(code=1000)
+            except Exception:
+                pass
+        await websocket.send_json(room.snapshot())
+        await self._send_presence(room)
+
+    async def disconnect(self, room: CollaborationRoom, websocket: WebSocket):
+        async with self._lock:
+            if room.presenter is websocket:
+                room.presenter = None
+            room.followers.pop(websocket, None)
+            room.last_activity = time.time()
+        await self._send_presence(room)
+
+    async def update(self, room: CollaborationRoom, state: Any) -> Dict[str, Any]:
+        """Take the presenter's full state and broadcast what changed to followers."""
+        state = _validate_state(state)
+        changed, removed = diff_state(room.state, state)
+        if not changed and not removed:
+            return {"type": "ack", "version": room.version, "delivered": 0}
+        room.state = state
+        room.version += 1
+        room.last_activity = time.time()
+        if isinstance(state.get("filename"), str):
+            room.filename = state["filename"]
+        patch = {"type": "patch", "version": room.version, "set": changed, "removed": removed}
+        delivered = await self._broadcast(room, patch)
+        return {"type": "ack", "version": room.version, "delivered": delivered}
+
+    async def _broadcast(self, room: CollaborationRoom, message: Dict[str, Any]) -> int:
+        async with self._lock:
+            recipients = list(room.followers)
+        delivered = 0
+        for websocket in recipients:
+            try:
+                await websocket.send_json(message)
+                delivered += 1
+            except Exception as e:
+                logger.debug(f"Dropping collaboration follower: {e}")
+                async with self._lock:
+                    room.followers.pop(websocket, None)
+        return delivered
+
+    async def _send_presence(self, room: CollaborationRoom):
+        """Tell everyone in the room who is there."""
+        presence = {"type": "presence", "presenter_connected": room.presenter is not None, "followers": len(room.followers)}
+        await self._broadcast(room, presence)
+        if room.presenter is not None:
+            try:
+                await room.presenter.send_json(presence)
+            except Exception:
+                pass
+
+    def rooms(self) -> List[Dict[str, Any]]:
+        return [room.info() for room in self._rooms.values()]
+
+
+collaboration_hub = CollaborationHub()
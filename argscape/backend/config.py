@@ -0,0 +1,147 @@
+"""
+Layered configuration files (argscape.toml).
+Settings come from, lowest to highest precedence: built-in defaults, a
+site-wide file (for HPC admins: memory budgets, cache locations), the user's
+file in the config directory, a project file in the working directory (or
+the one given with --config), settings saved from the app, and finally
+environment variables and CLI flags. Files hold the same keys as the
+settings schema at the top level, plus an optional [server] table with
+defaults for the CLI's --host and --port.
+"""
+
+import difflib
+import os
+import sys
+from pathlib import Path
+from typing import Any, Dict, List
+
+CONFIG_FILENAME = "argscape.toml"
+# Explicit project config (set by `argscape --config FILE`) and site-wide config locations
+CONFIG_ENV_VAR = "ARGSCAPE_CONFIG"
+SITE_CONFIG_ENV_VAR = "ARGSCAPE_SITE_CONFIG"
+
+# Layer names in increasing precedence
+CONFIG_LAYERS = ("site", "user", "project")
+
+# [server] table key -> validator
+SERVER_KEYS = {
+    "host": str,
+    "port": int,
+}
+
+
+class ConfigError(ValueError):
+    """A config file that can't be read or holds an invalid value; the message names the file and key."""
+
+
+def load_toml(text: str) -> Dict[str, Any]:
+    """Parse TOML with tomllib, or the tomli backport before Python 3.11."""
+    try:
+        import tomllib
+    except ImportError:
+        try:
+            import tomli as tomllib
+        except ImportError:
+            raise ConfigError("Reading TOML files needs Python 3.11+ or the tomli package")
+    return tomllib.loads(text)
+
+
+def site_config_path() -> Path:
+    env_path = os.getenv(SITE_CONFIG_ENV_VAR)
+    if env_path:
+        return Path(env_path).expanduser()
+    if sys.platform == "win32":
+        return Path(os.getenv("PROGRAMDATA", "C:\\ProgramData")) / "argscape" / CONFIG_FILENAME
+    return Path("/etc/argscape") / CONFIG_FILENAME
+
+
+def config_paths() -> Dict[str, Path]:
+    """Where each layer is read from; only files that exist are used."""
+    from argscape.backend.settings import get_config_dir
+
+    explicit = os.getenv(CONFIG_ENV_VAR)
+    return {
+        "site": site_config_path(),
+        "user": get_config_dir() / CONFIG_FILENAME,
+        "project": Path(explicit).expanduser() if explicit else Path.cwd() / CONFIG_FILENAME,
+    }
+
+
+def _unknown_key(path: Path, key: str, known) -> ConfigError:
+    matches = difflib.get_close_matches(key, list(known), n=1)
+    hint = f" (did you mean '{matches[0]}'?)" if matches else ""
+    return ConfigError(f"{path}: unknown setting '{key}'{hint}")
+
+
+def parse_config(text: str, path: Path) -> Dict[str, Any]:
+    """Validate one file's contents into {"settings": {...}, "server": {...}}."""
+    # Imported here because the settings store loads this module while it is created
+    from argscape.backend.settings import SETTINGS_SCHEMA
+
+    try:
+        raw = load_toml(text)
+    except ConfigError:
+        raise
+    except ValueError as e:
+        raise ConfigError(f"{path}: not valid TOML: {e}")
+
+    server = raw.pop("server", {})
+    if not isinstance(server, dict):
+        raise ConfigError(f"{path}: [server] must be a table")
+
+    values: Dict[str, Any] = {}
+    for key, value in raw.items():
+        if key not in SETTINGS_SCHEMA:
+            raise _unknown_key(path, key, list(SETTINGS_SCHEMA) + ["server"])
+        try:
+            values[key] = SETTINGS_SCHEMA[key][1](value)
+        except (TypeError, ValueError) as e:
+            raise ConfigError(f"{path}: {key} = {value!r}: {e}")
+
+    server_values: Dict[str, Any] = {}
+    for key, value in server.items():
+        if key not in SERVER_KEYS:
+            raise _unknown_key(path, f"server.{key}", [f"server.{k}" for k in SERVER_KEYS])
+        try:
+            server_values[key] = SERVER_KEYS[key](value)
+        except (TypeError, ValueError) as e:
+            raise ConfigError(f"{path}: server.{key} = {value!r}: {e}")
+    return {"settings": values, "server": server_values}
+
+
+def load_config_file(path: Path) -> Dict[str, Any]:
+    try:
+        text = path.read_text(encoding="utf-8")
+    except OSError as e:
+        raise ConfigError(f"{path}: can't be read: {e}")
+    return parse_config(text, path)
+
+
+def load_config_layers(strict: bool = True) -> List[Dict[str, Any]]:
+    """
+    The config files that exist, lowest precedence first, each as
+    {"layer", "path", "settings", "server"}. With strict=False a broken file
+    is skipped (and reported under "error") instead of raising.
+    """
+    layers = []
+    paths = config_paths()
+    for layer in CONFIG_LAYERS:
+        path = paths[layer]
+        # An explicit --config file must exist; discovered ones are optional
+        if not path.is_file() and not (layer == "project" and os.getenv(CONFIG_ENV_VAR)):
+            continue
+        try:
+            layers.append({"layer": layer, "path": str(path), **load_config_file(path)})
+        except ConfigError as e:
+            if strict:
+                raise
+            layers.append({"layer": layer, "path": str(path), "settings": {}, "server": {}, "error": str(e)})
+    return layers
+
+
+def merged_server_defaults(layers: List[Dict[str, Any]]) -> Dict[str, Any]:
+    """[server] values from all layers, higher layers winning."""
+    merged: Dict[str, Any] = {}
+    for layer in layers:
+        merged.update(layer["server"])
+    return merged
@@ -0,0 +1,207 @@
+"""
+Multiple contigs in one tree sequence.
+Some pipelines concatenate chromosomes into one coordinate space: msprime's
+multi-chromosome recipe separates them by a 1 bp interval with recombination
+rate log(2), and other tools list contig names and boundaries in the
+top-level metadata. A ContigMap found from either (or set by the user)
+converts between the global axis of the tree sequence and per-contig
+positions, so navigation, statistic tables and genome browser links can
+say "chr2:1,200" instead of "position 251,000,000". Positions in the gaps
+between contigs belong to no contig.
+"""
+
+import bisect
+import json
+import logging
+from typing import Any, Dict, List, Optional, Sequence
+
+import numpy as np
+import tskit
+
+logger = logging.getLogger(__name__)
+
+# Top-level metadata keys that list contigs, as [{"name", "start"/"offset", "end"/"length"}] or {name: length}
+CONTIG_METADATA_KEYS = ("contigs", "chromosomes")
+# ... or give the boundaries between them, optionally with names
+BREAKPOINT_METADATA_KEYS = ("contig_breakpoints", "chromosome_breakpoints")
+NAME_METADATA_KEYS = ("contig_names", "chromosome_names")
+# A recombination rate this high over at most this span is an unlinking interval between contigs
+UNLINKING_MIN_RATE = 0.5
+UNLINKING_MAX_SPAN = 1.0
+
+SOURCE_USER = "user"
+SOURCE_METADATA = "metadata"
+SOURCE_RATE_MAP = "recombination map"
+
+
+class ContigMap:
+    """Named, non-overlapping [start, end) intervals of the global coordinate axis."""
+
+    def __init__(self, contigs: List[Dict[str, Any]], sequence_length: float, source: str):
+        contigs = sorted(
+            ({"name": str(c["name"]), "start": float(c["start"]), "end": float(c["end"])} for c in contigs),
+            key=lambda c: c["start"]
+        )
+        if len(contigs) < 2:
+            raise ValueError("A contig map needs at least two contigs")
+        names = [c["name"] for c in contigs]
+        if len(set(names)) != len(names):
+            raise ValueError(f"Contig names must be unique: {names}")
+        previous_end = 0.0
+        for contig in contigs:
+            if not previous_end <= contig["start"] < contig["end"] <= sequence_length:
+                raise ValueError(
+                    f"Contig {contig['name']} [{contig['start']:g}, {contig['end']:g}) overlaps another "
+                    f"or lies outside the sequence (length {sequence_length:g})"
+                )
+            previous_end = contig["end"]
+        self.contigs = contigs
+        self.sequence_length = sequence_length
+        self.source = source
+        self._starts = [c["start"] for c in contigs]
+        self._by_name = {c["name"]: c for c in contigs}
+
+    def locate(self, position: float) -> Optional[Dict[str, Any]]:
+        """The contig containing a global position, or None in a gap."""
+        index = bisect.bisect_right(self._starts, position) - 1
+        if index >= 0 and position < self.contigs[index]["end"]:
+            return self.contigs[index]
+        return None
+
+    def to_contig(self, position: float) -> Dict[str, Any]:
+        contig = self.locate(position)
+        if contig is None:
+            return {"contig": None, "position": None}
+        return {"contig": contig["name"], "position": position - contig["start"]}
+
+    def to_global(self, name: str, position: float) -> float:
+        contig = self._by_name.get(name)
+        if contig is None:
+            raise ValueError(f"Unknown contig '{name}'. Expected one of {list(self._by_name)}")
+        length = contig["end"] - contig["start"]
+        if not 0 <= position <= length:
+            raise ValueError(f"Position {position:g} is outside {name} (length {length:g})")
+        return contig["start"] + position
+
+    def split_interval(self, left: float, right: float) -> List[Dict[str, Any]]:
+        """The parts of a global interval on each contig, in contig coordinates."""
+        parts = []
+        for contig in self.contigs:
+            start, end = max(left, contig["start"]), min(right, contig["end"])
+            if start < end:
+                parts.append({
+                    "contig": contig["name"],
+                    "start": start - contig["start"],
+                    "end": end - contig["start"],
+                    "global_start": start,
+                    "global_end": end,
+                })
+        return parts
+
+    def split_windows(self, windows: Sequence[float]) -> np.ndarray:
+        """Window breakpoints with every contig boundary added, so no window spans two contigs."""
+        boundaries = [b for c in self.contigs for b in (c["start"], c["end"]) if 0 < b < self.sequence_length]
+        return np.unique(np.concatenate([np.asarray(windows, dtype=float), boundaries]))
+
+    def annotate_table(self, columns: Dict[str, List[Any]]) -> Dict[str, List[Any]]:
+        """Add contig, contig_left and contig_right columns to a table with window_left/window_right."""
+        annotated = {"contig": [], "contig_left": [], "contig_right": []}
+        for left, right in zip(columns["window_left"], columns["window_right"]):
+            contig = self.locate(left)
+            annotated["contig"].append(contig["name"] if contig else None)
+            annotated["contig_left"].append(left - contig["start"] if contig else None)
+            annotated["contig_right"].append(min(right, contig["end"]) - contig["start"] if contig else None)
+        return {**columns, **annotated}
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "source": self.source,
+            "contigs": [{**c, "length": c["end"] - c["start"]} for c in self.contigs],
+        }
+
+
+def _as_list(value: Any) -> Optional[List[float]]:
+    """A JSON list, or the array encodings provenance records use for numpy arrays."""
+    if isinstance(value, dict):
+        value = value.get("__ndarray__", value.get("data"))
+    if isinstance(value, list) and all(isinstance(v, (int, float)) for v in value):
+        return [float(v) for v in value]
+    return None
+
+
+def _from_metadata(metadata: Any, sequence_length: float) -> Optional[List[Dict[str, Any]]]:
+    if not isinstance(metadata, dict):
+        return None
+    for key in CONTIG_METADATA_KEYS:
+        listed = metadata.get(key)
+        if isinstance(listed, dict):
+            listed = [{"name": name, "length": length} for name, length in listed.items()]
+        if not isinstance(listed, list) or not listed or not all(isinstance(c, dict) for c in listed):
+            continue
+        contigs, position = [], 0.0
+        for i, contig in enumerate(listed):
+            start = float(contig.get("start", contig.get("offset", position)))
+            end = float(contig["end"]) if "end" in contig else start + float(contig.get("length", sequence_length - start))
+            contigs.append({"name": contig.get("name", contig.get("id", f"contig{i + 1}")), "start": start, "end": end})
+            position = end
+        return contigs
+    for key in BREAKPOINT_METADATA_KEYS:
+        breakpoints = _as_list(metadata.get(key))
+        if not breakpoints:
+            continue
+        edges = sorted({0.0, *breakpoints, float(sequence_length)})
+        names = next((metadata[k] for k in NAME_METADATA_KEYS if isinstance(metadata.get(k), list)), None)
+        if names is None or len(names) != len(edges) - 1:
+            names = [f"contig{i + 1}" for i in range(len(edges) - 1)]
+        return [{"name": name, "start": start, "end": end} for name, start, end in zip(names, edges[:-1], edges[1:])]
+    return None
+
+
+def _find_rate_maps(value: Any):
+    if isinstance(value, dict):
+        positions, rates = _as_list(value.get("position")), _as_list(value.get("rate"))
+        if positions and rates and len(positions) == len(rates) + 1:
+            yield positions, rates
+        for child in value.values():
+            yield from _find_rate_maps(child)
+    elif isinstance(value, list):
+        for child in value:
+            yield from _find_rate_maps(child)
+
+
+def _from_rate_map(ts: tskit.TreeSequence) -> Optional[List[Dict[str, Any]]]:
+    for provenance in ts.provenances():
+        try:
+            record = json.loads(provenance.record)
+        except ValueError:
+            continue
+        parameters = record.get("parameters", {}) if isinstance(record, dict) else {}
+        for positions, rates in _find_rate_maps(parameters.get("recombination_rate")):
+            if positions[-1] != ts.sequence_length:
+                continue
+            gaps = [
+                (left, right) for left, right, rate in zip(positions[:-1], positions[1:], rates)
+                if rate >= UNLINKING_MIN_RATE and right - left <= UNLINKING_MAX_SPAN
+            ]
+            if not gaps:
+                continue
+            starts = [0.0] + [right for _, right in gaps]
+            ends = [left for left, _ in gaps] + [float(ts.sequence_length)]
+            return [{"name": f"chr{i + 1}", "start": s, "end": e} for i, (s, e) in enumerate(zip(starts, ends)) if s < e]
+    return None
+
+
+def detect_contigs(ts: tskit.TreeSequence) -> Optional[ContigMap]:
+    """A contig map from the top-level metadata or an msprime multi-chromosome rate map, if either has one."""
+    try:
+        metadata = ts.metadata
+    except Exception:
+        metadata = None
+    for source, finder in ((SOURCE_METADATA, lambda: _from_metadata(metadata, ts.sequence_length)), (SOURCE_RATE_MAP, lambda: _from_rate_map(ts))):
+        try:
+            contigs = finder()
+            if contigs:
+                return ContigMap(contigs, ts.sequence_length, source)
+        except (ValueError, KeyError, TypeError) as e:
+            logger.info(f"Ignoring contig layout from {source}: {e}")
+    return None
@@ -0,0 +1,172 @@
+"""
+Crash handling for the backend process.
+When an unhandled exception is about to take the process down (for example
+during a long layout), registered emergency savers flush session state to
+disk, running jobs are cancelled so the worker pool doesn't hold up exit, and
+a crash marker with the traceback is written to the config directory. Fatal
+errors in native code are captured by faulthandler next to the marker. On the
+next launch the frontend reads the marker to tell the user what happened.
+"""
+
+import faulthandler
+import json
+import logging
+import os
+import sys
+import threading
+import time
+import traceback
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from argscape.backend.settings import get_config_dir
+
+logger = logging.getLogger(__name__)
+
+CRASH_MARKER_FILENAME = "crash.json"
+FAULT_LOG_FILENAME = "crash-fault.log"
+
+# Exit status when the memory watchdog ends the process (as if killed by SIGKILL)
+MEMORY_LIMIT_EXIT_CODE = 137
+
+# (name, func) pairs run by emergency_save, in registration order
+_savers: List[Tuple[str, Callable[[], Any]]] = []
+_fault_log = None
+_installed = False
+_lock = threading.Lock()
+
+
+def register_emergency_save(name: str, func: Callable[[], Any]):
+    """Register a function that flushes state to disk when the process crashes."""
+    _savers.append((name, func))
+
+
+def emergency_save() -> Dict[str, List[str]]:
+    """Run every registered saver, continuing past failures."""
+    saved, failed = [], []
+    for name, func in _savers:
+        try:
+            func()
+            saved.append(name)
+        except Exception as e:
+            failed.append(name)
+            logger.error(f"Emergency save of {name} failed: {e}")
+    return {"saved": saved, "failed": failed}
+
+
+def write_marker(
+    kind: str,
+    exception_type: str,
+    message: str,
+    details: str,
+    thread: Optional[str] = None,
+    save_result: Optional[Dict[str, List[str]]] = None,
+    when: Optional[float] = None
+):
+    marker = {
+        "kind": kind,
+        "time": when or time.time(),
+        "pid": os.getpid() if when is None else None,
+        "thread": thread,
+        "exception_type": exception_type,
+        "message": message,
+        "traceback": details,
+        "emergency_save": save_result or {"saved": [], "failed": []},
+    }
+    path = get_config_dir() / CRASH_MARKER_FILENAME
+    try:
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(json.dumps(marker, indent=2))
+    except OSError as e:
+        logger.error(f"Failed to write crash marker to {path}: {e}")
+
+
+def write_crash_marker(exc_type, exc: BaseException, tb, thread_name: str, save_result=None):
+    details = "".join(traceback.format_exception(exc_type, exc, tb))
+    write_marker("exception", exc_type.__name__, str(exc), details, thread_name, save_result)
+
+
+def read_crash_marker() -> Optional[Dict[str, Any]]:
+    path = get_config_dir() / CRASH_MARKER_FILENAME
+    if not path.exists():
+        return None
+    try:
+        return json.loads(path.read_text())
+    except (OSError, ValueError) as e:
+        logger.warning(f"Ignoring unreadable crash marker {path}: {e}")
+        return None
+
+
+def clear_crash_marker() -> bool:
+    path = get_config_dir() / CRASH_MARKER_FILENAME
+    if not path.exists():
+        return False
+    path.unlink()
+    return True
+
+
+def _handle_crash(exc_type, exc, tb, thread_name: str):
+    # Only the first crash saves; a second one during the save must not recurse
+    if not _lock.acquire(blocking=False):
+        return
+    logger.critical(f"Unhandled {exc_type.__name__} in {thread_name}: {exc}", exc_info=(exc_type, exc, tb))
+    save_result = emergency_save()
+    write_crash_marker(exc_type, exc, tb, thread_name, save_result)
+
+
+def _record_previous_fault(fault_path):
+    """Turn a fault log left by a native crash into a crash marker."""
+    try:
+        text = fault_path.read_text().strip()
+    except OSError:
+        return
+    if text and not (get_config_dir() / CRASH_MARKER_FILENAME).exists():
+        write_marker("fatal_error", "FatalError", text.splitlines()[0], text, when=fault_path.stat().st_mtime)
+
+
+def terminate(kind: str, message: str, exit_code: int = 1):
+    """Save what can be saved, leave a crash marker and end the process immediately."""
+    logger.critical(message)
+    save_result = emergency_save()
+    exception_type = "".join(word.capitalize() for word in kind.split("_"))
+    write_marker(kind, exception_type, message, "", threading.current_thread().name, save_result)
+    logging.shutdown()
+    os._exit(exit_code)
+
+
+def install_crash_handler():
+    """Hook unhandled exceptions and native faults (idempotent)."""
+    global _fault_log, _installed
+    if _installed:
+        return
+    _installed = True
+
+    previous_excepthook = sys.excepthook
+    previous_thread_excepthook = threading.excepthook
+
+    def excepthook(exc_type, exc, tb):
+        if not issubclass(exc_type, KeyboardInterrupt):
+            _handle_crash(exc_type, exc, tb, "main")
+        previous_excepthook(exc_type, exc, tb)
+
+    def thread_excepthook(args):
+        # A dying worker thread doesn't end the process, so save without leaving a crash marker
+        if args.exc_type is not SystemExit:
+            logger.critical(
+                f"Unhandled {args.exc_type.__name__} in thread {getattr(args.thread, 'name', '?')}: {args.exc_value}",
+                exc_info=(args.exc_type, args.exc_value, args.exc_traceback)
+            )
+            emergency_save()
+        previous_thread_excepthook(args)
+
+    sys.excepthook = excepthook
+    threading.excepthook = thread_excepthook
+
+    fault_path = get_config_dir() / FAULT_LOG_FILENAME
+    try:
+        fault_path.parent.mkdir(parents=True, exist_ok=True)
+        if fault_path.exists():
+            _record_previous_fault(fault_path)
+        _fault_log = open(fault_path, "w")
+        faulthandler.enable(file=_fault_log, all_threads=True)
+    except OSError as e:
+        logger.warning(f"Native crash logging unavailable: {e}")
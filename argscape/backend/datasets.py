@@ -0,0 +1,174 @@
+"""
+Published datasets by DOI.
+A pasted DOI, doi.org link or Zenodo/Figshare record URL is resolved to its
+record and the files in it are listed, with tree sequences marked as
+openable. A chosen file is downloaded, resuming after interruptions, and
+verified against the checksum the repository publishes before it is added
+to the shared download cache and opened.
+"""
+
+import hashlib
+import json
+import logging
+import re
+import urllib.error
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from argscape.backend.download_cache import download_cache
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.network import http_open, http_request
+from argscape.backend.settings import get_cache_dir
+
+logger = logging.getLogger(__name__)
+
+ZENODO_API = "https://zenodo.org/api/records/{}"
+FIGSHARE_API = "https://api.figshare.com/v2/articles/{}"
+DOI_RESOLVER = "https://doi.org/{}"
+
+OPENABLE_EXTENSIONS = (".trees", ".tsz")
+DOWNLOADS_DIRNAME = "downloads"
+CHUNK_SIZE = 1 << 20
+MAX_DOWNLOAD_ATTEMPTS = 3
+
+_ZENODO_PATTERNS = (
+    re.compile(r"\bzenodo\.(\d+)\b", re.IGNORECASE),  # DOI 10.5281/zenodo.1234567
+    re.compile(r"zenodo\.org/(?:records?|deposit)/(\d+)", re.IGNORECASE),
+)
+_FIGSHARE_PATTERNS = (
+    re.compile(r"\bfigshare\.(\d+)\b", re.IGNORECASE),  # DOI 10.6084/m9.figshare.12345678.v2
+    re.compile(r"figshare\.com/articles/(?:[^/\s]+/)*?(\d+)(?:/\d+)?/?(?:$|[?#])", re.IGNORECASE),
+)
+_DOI_PATTERN = re.compile(r"\b(10\.\d{4,9}/\S+)")
+
+
+def parse_dataset_reference(text: str) -> Optional[Tuple[str, str]]:
+    """(source, record ID) for a Zenodo or Figshare DOI or URL, or None."""
+    for source, patterns in (("zenodo", _ZENODO_PATTERNS), ("figshare", _FIGSHARE_PATTERNS)):
+        for pattern in patterns:
+            match = pattern.search(text.strip())
+            if match:
+                return source, match.group(1)
+    return None
+
+
+def resolve_dataset_reference(text: str) -> Tuple[str, str]:
+    """Like parse_dataset_reference, but other DOIs are followed to their landing page."""
+    reference = parse_dataset_reference(text)
+    if reference:
+        return reference
+    match = _DOI_PATTERN.search(text)
+    if not match:
+        raise ValueError("Expected a DOI (10.xxxx/...) or a Zenodo or Figshare record URL")
+    with http_open(DOI_RESOLVER.format(match.group(1)), "DOI resolution", method="HEAD") as response:
+        landing = response.geturl()
+    reference = parse_dataset_reference(landing)
+    if not reference:
+        raise ValueError(f"{match.group(1)} resolves to {landing}, which is not a Zenodo or Figshare record")
+    return reference
+
+
+def _file_entry(name: str, size: Optional[int], url: str, md5: Optional[str]) -> Dict[str, Any]:
+    return {
+        "name": name,
+        "size": size,
+        "url": url,
+        "md5": md5,
+        "openable": name.lower().endswith(OPENABLE_EXTENSIONS),
+    }
+
+
+def list_dataset_files(text: str) -> Dict[str, Any]:
+    """Look up a dataset record and list its files."""
+    source, record_id = resolve_dataset_reference(text)
+    headers = {"Accept": "application/json"}
+    files: List[Dict[str, Any]] = []
+    if source == "zenodo":
+        record = json.loads(http_request(ZENODO_API.format(record_id), "Zenodo record lookup", headers=headers))
+        title = record.get("metadata", {}).get("title")
+        for item in record.get("files", []):
+            checksum = item.get("checksum", "")
+            links = item.get("links", {})
+            files.append(_file_entry(
+                item.get("key") or item.get("filename"),
+                item.get("size"),
+                links.get("content") or links.get("self"),
+                checksum[4:] if checksum.startswith("md5:") else None,
+            ))
+    else:
+        record = json.loads(http_request(FIGSHARE_API.format(record_id), "Figshare record lookup", headers=headers))
+        title = record.get("title")
+        for item in record.get("files", []):
+            files.append(_file_entry(item.get("name"), item.get("size"), item.get("download_url"), item.get("computed_md5") or None))
+    return {
+        "source": source,
+        "record_id": record_id,
+        "title": title,
+        "doi": record.get("doi"),
+        "files": files,
+    }
+
+
+def _md5(path: Path) -> str:
+    digest = hashlib.md5()
+    with open(path, "rb") as f:
+        for block in iter(lambda: f.read(CHUNK_SIZE), b""):
+            digest.update(block)
+    return digest.hexdigest()
+
+
+def download_dataset_file(
+    file_entry: Dict[str, Any],
+    progress: Optional[Callable[[float], None]] = None,
+    cancel_token: Optional[CancellationToken] = None
+) -> Path:
+    """Download a listed file, resuming a partial download, and verify its MD5.
+
+    Returns the file's path in the download cache; files already cached are
+    not downloaded again.
+    """
+    keys = ([f"md5:{file_entry['md5']}"] if file_entry["md5"] else []) + [f"url:{file_entry['url']}"]
+    for key in keys:
+        cached = download_cache.lookup(key)
+        if cached is not None:
+            return cached
+
+    directory = get_cache_dir() / DOWNLOADS_DIRNAME
+    directory.mkdir(parents=True, exist_ok=True)
+    partial_name = file_entry["md5"] or hashlib.sha256(file_entry["url"].encode()).hexdigest()[:32]
+    partial_path = directory / f"{partial_name}.part"
+
+    for attempt in range(1, MAX_DOWNLOAD_ATTEMPTS + 1):
+        have = partial_path.stat().st_size if partial_path.exists() else 0
+        headers = {"Range": f"bytes={have}-"} if have else {}
+        try:
+            with http_open(file_entry["url"], "dataset download", headers=headers, timeout=60) as response:
+                if have and response.status != 206:
+                    have = 0  # The server ignored the range; start over
+                length = response.headers.get("Content-Length")
+                total = have + int(length) if length else file_entry.get("size")
+                with open(partial_path, "ab" if have else "wb") as f:
+                    done = have
+                    for block in iter(lambda: response.read(CHUNK_SIZE), b""):
+                        check_cancelled(cancel_token)
+                        f.write(block)
+                        done += len(block)
+                        if progress and total:
+                            progress(min(done / total, 1.0))
+            break
+        except urllib.error.HTTPError as e:
+            if e.code == 416 and have:
+                break  # Nothing left to fetch
+            raise
+        except (urllib.error.URLError, OSError) as e:
+            if attempt == MAX_DOWNLOAD_ATTEMPTS:
+                raise
+            logger.warning(f"Download of {file_entry['name']} interrupted ({e}); resuming")
+
+    if file_entry["md5"]:
+        actual = _md5(partial_path)
+        if actual != file_entry["md5"]:
+            partial_path.unlink()
+            raise ValueError(f"Checksum mismatch for {file_entry['name']}: expected {file_entry['md5']}, got {actual}")
+    logger.info(f"Downloaded {file_entry['name']}")
+    return download_cache.add_file(partial_path, file_entry["name"], keys, source=file_entry["url"])
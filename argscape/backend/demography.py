@@ -0,0 +1,275 @@
+"""
+Demes demographic models (https://popsim-consortium.github.io/demes-spec-docs/).
+Parses Demes YAML into fully resolved population sizes, splits, migrations and
+pulses for the demography panel, aligns models with tree sequence node times,
+and converts models to msprime demographies for simulation.
+"""
+
+import logging
+import math
+from typing import Any, Dict, List, Optional
+
+import msprime
+import tskit
+import yaml
+
+logger = logging.getLogger(__name__)
+
+INFINITY = math.inf
+
+# Time units whose values are already in generations
+GENERATION_UNITS = ("generations",)
+
+
+def _to_float(value: Any, field: str) -> float:
+    if isinstance(value, str) and value.strip().lower() in ("infinity", "inf", ".inf"):
+        return INFINITY
+    try:
+        return float(value)
+    except (TypeError, ValueError):
+        raise ValueError(f"Demes model: '{field}' must be a number, got {value!r}")
+
+
+def _resolve_epochs(deme: Dict[str, Any], defaults: Dict[str, Any], start_time: float) -> List[Dict[str, Any]]:
+    name = deme["name"]
+    raw_epochs = deme.get("epochs") or [{}]
+    epoch_defaults = {**defaults.get("epoch", {}), **deme.get("defaults", {}).get("epoch", {})}
+
+    epochs = []
+    previous_end_time = start_time
+    previous_end_size = None
+    for i, raw in enumerate(raw_epochs):
+        epoch = {**epoch_defaults, **raw}
+        is_last = i == len(raw_epochs) - 1
+        end_time = _to_float(epoch.get("end_time", 0 if is_last else None), f"{name}.epochs[{i}].end_time")
+        if end_time >= previous_end_time:
+            raise ValueError(f"Demes model: epochs of deme '{name}' must have decreasing end times")
+
+        start_size = epoch.get("start_size")
+        end_size = epoch.get("end_size")
+        if start_size is None:
+            start_size = end_size if i == 0 else previous_end_size
+        if end_size is None:
+            end_size = start_size
+        if start_size is None:
+            raise ValueError(f"Demes model: first epoch of deme '{name}' needs start_size or end_size")
+        start_size = _to_float(start_size, f"{name}.epochs[{i}].start_size")
+        end_size = _to_float(end_size, f"{name}.epochs[{i}].end_size")
+        if start_size <= 0 or end_size <= 0:
+            raise ValueError(f"Demes model: sizes of deme '{name}' must be positive")
+        if math.isinf(previous_end_time) and start_size != end_size:
+            raise ValueError(f"Demes model: deme '{name}' cannot change size in an epoch starting at infinity")
+
+        size_function = epoch.get("size_function", "constant" if start_size == end_size else "exponential")
+        epochs.append({
+            "start_time": previous_end_time,
+            "end_time": end_time,
+            "start_size": start_size,
+            "end_size": end_size,
+            "size_function": size_function,
+            "selfing_rate": float(epoch.get("selfing_rate", 0)),
+            "cloning_rate": float(epoch.get("cloning_rate", 0)),
+        })
+        previous_end_time = end_time
+        previous_end_size = end_size
+    return epochs
+
+
+def _resolve_migrations(raw_migrations: List[Dict[str, Any]], defaults: Dict[str, Any], demes: Dict[str, Dict]) -> List[Dict[str, Any]]:
+    migrations = []
+    for i, raw in enumerate(raw_migrations):
+        migration = {**defaults.get("migration", {}), **raw}
+        if "demes" in migration:
+            names = migration["demes"]
+            pairs = [(a, b) for a in names for b in names if a != b]
+        else:
+            pairs = [(migration.get("source"), migration.get("dest"))]
+
+        for source, dest in pairs:
+            if source not in demes or dest not in demes:
+                raise ValueError(f"Demes model: migration {i} refers to an unknown deme")
+            # Default to the interval where both demes exist
+            overlap_start = min(demes[source]["start_time"], demes[dest]["start_time"])
+            overlap_end = max(demes[source]["end_time"], demes[dest]["end_time"])
+            start_time = _to_float(migration.get("start_time", overlap_start), f"migrations[{i}].start_time")
+            end_time = _to_float(migration.get("end_time", overlap_end), f"migrations[{i}].end_time")
+            if start_time <= end_time:
+                raise ValueError(f"Demes model: migration {i} between '{source}' and '{dest}' has no time span")
+            migrations.append({
+                "source": source,
+                "dest": dest,
+                "start_time": start_time,
+                "end_time": end_time,
+                "rate": _to_float(migration.get("rate"), f"migrations[{i}].rate"),
+            })
+    return migrations
+
+
+def _resolve_pulses(raw_pulses: List[Dict[str, Any]], defaults: Dict[str, Any], demes: Dict[str, Dict]) -> List[Dict[str, Any]]:
+    pulses = []
+    for i, raw in enumerate(raw_pulses):
+        pulse = {**defaults.get("pulse", {}), **raw}
+        sources = pulse.get("sources") or []
+        proportions = [_to_float(p, f"pulses[{i}].proportions") for p in pulse.get("proportions") or []]
+        if len(sources) != len(proportions) or not sources:
+            raise ValueError(f"Demes model: pulse {i} needs one proportion per source")
+        if any(name not in demes for name in sources + [pulse.get("dest")]):
+            raise ValueError(f"Demes model: pulse {i} refers to an unknown deme")
+        pulses.append({
+            "sources": sources,
+            "dest": pulse["dest"],
+            "time": _to_float(pulse.get("time"), f"pulses[{i}].time"),
+            "proportions": proportions,
+        })
+    return sorted(pulses, key=lambda p: -p["time"])
+
+
+def parse_demes_model(text: str) -> Dict[str, Any]:
+    """Parse and resolve a Demes YAML (or JSON) model.
+
+    Defaults are filled in as the Demes specification describes, so every deme
+    has explicit start/end times and every epoch explicit sizes.
+
+    Raises:
+        ValueError: If the document is not a valid Demes model
+    """
+    try:
+        data = yaml.safe_load(text)
+    except yaml.YAMLError as e:
+        raise ValueError(f"Demes model is not valid YAML: {e}")
+    if not isinstance(data, dict) or "demes" not in data:
+        raise ValueError("Demes model must be a mapping with a 'demes' list")
+    if "time_units" not in data:
+        raise ValueError("Demes model must specify time_units")
+
+    defaults = data.get("defaults") or {}
+    deme_defaults = defaults.get("deme", {})
+    time_units = data["time_units"]
+    generation_time = data.get("generation_time")
+    if time_units in GENERATION_UNITS:
+        generation_time = generation_time or 1
+    elif generation_time is None:
+        raise ValueError(f"Demes model with time_units '{time_units}' must specify generation_time")
+
+    demes: Dict[str, Dict[str, Any]] = {}
+    for i, raw in enumerate(data["demes"] or []):
+        deme = {**deme_defaults, **raw}
+        name = deme.get("name")
+        if not name or name in demes:
+            raise ValueError(f"Demes model: deme {i} needs a unique name")
+
+        ancestors = deme.get("ancestors") or []
+        if any(a not in demes for a in ancestors):
+            raise ValueError(f"Demes model: ancestors of '{name}' must be defined before it")
+        proportions = deme.get("proportions")
+        if proportions is None:
+            proportions = [1.0] if len(ancestors) == 1 else []
+        proportions = [_to_float(p, f"{name}.proportions") for p in proportions]
+        if len(proportions) != len(ancestors) or (ancestors and not math.isclose(sum(proportions), 1.0)):
+            raise ValueError(f"Demes model: proportions of '{name}' must match its ancestors and sum to 1")
+
+        if "start_time" in deme:
+            start_time = _to_float(deme["start_time"], f"{name}.start_time")
+        elif len(ancestors) == 1:
+            start_time = demes[ancestors[0]]["end_time"]
+        elif not ancestors:
+            start_time = INFINITY
+        else:
+            raise ValueError(f"Demes model: deme '{name}' with several ancestors needs a start_time")
+        for ancestor in ancestors:
+            if not demes[ancestor]["end_time"] <= start_time < demes[ancestor]["start_time"]:
+                raise ValueError(f"Demes model: ancestor '{ancestor}' does not exist when '{name}' starts")
+
+        epochs = _resolve_epochs(deme, defaults, start_time)
+        demes[name] = {
+            "name": name,
+            "description": deme.get("description", ""),
+            "ancestors": ancestors,
+            "proportions": proportions,
+            "start_time": start_time,
+            "end_time": epochs[-1]["end_time"],
+            "epochs": epochs,
+        }
+
+    if not demes:
+        raise ValueError("Demes model has no demes")
+
+    migrations = _resolve_migrations(data.get("migrations") or [], defaults, demes)
+    pulses = _resolve_pulses(data.get("pulses") or [], defaults, demes)
+
+    finite_times = [t for d in demes.values() for t in (d["start_time"], d["end_time"]) if not math.isinf(t)]
+    finite_times += [p["time"] for p in pulses]
+    return {
+        "description": data.get("description", ""),
+        "doi": data.get("doi", []),
+        "time_units": time_units,
+        "generation_time": generation_time,
+        "demes": list(demes.values()),
+        "migrations": migrations,
+        "pulses": pulses,
+        "time_range": [0.0, max(finite_times) if finite_times else 0.0],
+    }
+
+
+def deme_size_at(deme: Dict[str, Any], time: float) -> Optional[float]:
+    """Population size of a resolved deme at a time (in model units), or None if it does not exist."""
+    for epoch in deme["epochs"]:
+        if epoch["end_time"] <= time < epoch["start_time"] or (time == 0 and epoch["end_time"] == 0):
+            if epoch["size_function"] == "constant" or math.isinf(epoch["start_time"]):
+                return epoch["start_size"]
+            span = epoch["start_time"] - epoch["end_time"]
+            fraction = (epoch["start_time"] - time) / span
+            if epoch["size_function"] == "linear":
+                return epoch["start_size"] + fraction * (epoch["end_size"] - epoch["start_size"])
+            return epoch["start_size"] * (epoch["end_size"] / epoch["start_size"]) ** fraction
+    return None
+
+
+def align_with_tree_sequence(model: Dict[str, Any], ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """Match demes to tree sequence populations and express times on the node time axis.
+
+    Node times are assumed to be in generations, as written by msprime and SLiM.
+    """
+    scale = 1.0 / model["generation_time"] if model["time_units"] not in GENERATION_UNITS else 1.0
+    population_ids = {}
+    for population in ts.populations():
+        metadata = population.metadata if isinstance(population.metadata, dict) else {}
+        if metadata.get("name"):
+            population_ids[metadata["name"]] = population.id
+
+    node_times = ts.tables.nodes.time
+    return {
+        "model_time_to_generations": scale,
+        "population_map": {d["name"]: population_ids.get(d["name"]) for d in model["demes"]},
+        "unmatched_demes": [d["name"] for d in model["demes"] if d["name"] not in population_ids],
+        "node_time_range": [float(node_times.min()), float(node_times.max())] if len(node_times) else [0.0, 0.0],
+        "model_time_range_generations": [t * scale for t in model["time_range"]],
+    }
+
+
+def to_json_compatible(model: Any) -> Any:
+    """Replace infinite times with "Infinity", as in Demes' own JSON output."""
+    if isinstance(model, dict):
+        return {k: to_json_compatible(v) for k, v in model.items()}
+    if isinstance(model, list):
+        return [to_json_compatible(v) for v in model]
+    if isinstance(model, float) and math.isinf(model):
+        return "Infinity"
+    return model
+
+
+def build_msprime_demography(text: str) -> msprime.Demography:
+    """Convert a Demes YAML model into an msprime Demography for simulation."""
+    import demes  # Installed with msprime; validates the model against the full specification
+
+    parse_demes_model(text)  # Consistent error messages for common mistakes
+    return msprime.Demography.from_demes(demes.loads(text))
+
+
+def default_sample_sets(model: Dict[str, Any], num_samples: int) -> Dict[str, int]:
+    """Split samples evenly across the demes that exist at time zero."""
+    extant = [d["name"] for d in model["demes"] if d["end_time"] == 0]
+    if not extant:
+        raise ValueError("Demes model has no demes existing at time zero to sample from")
+    base, extra = divmod(num_samples, len(extant))
+    return {name: base + (1 if i < extra else 0) for i, name in enumerate(extant) if base + (1 if i < extra else 0) > 0}
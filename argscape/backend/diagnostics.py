@@ -0,0 +1,184 @@
+"""
+Diagnostic bundles for error reports.
+Recent log records are kept in memory, and when an operation fails the user
+can preview a bundle of sanitized logs, system information and table sizes,
+then save it locally or send it to the configured report endpoint. Bundles
+never contain genetic data: only row counts of the tree sequence tables, with
+file names, paths, IP addresses and session IDs redacted.
+"""
+
+import collections
+import json
+import logging
+import os
+import platform
+import re
+import sys
+import threading
+import time
+from importlib import metadata as importlib_metadata
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+import tskit
+
+from argscape.backend.network import http_request
+from argscape.backend.settings import get_data_dir, settings
+
+logger = logging.getLogger(__name__)
+
+MAX_LOG_RECORDS = 500
+BUNDLE_FORMAT = "argscape-diagnostics"
+BUNDLE_VERSION = 1
+DIAGNOSTICS_DIRNAME = "diagnostics"
+
+REPORTED_PACKAGES = ("argscape", "tskit", "msprime", "tszip", "numpy", "fastapi", "uvicorn", "pydantic")
+
+# Settings that may identify the user or their network are left out of bundles
+REDACTED_SETTINGS = ("proxy_url", "ca_bundle_path", "inbox_dir", "diagnostics_endpoint", "python_interpreter")
+
+_IP_PATTERN = re.compile(r"\b\d{1,3}(?:\.\d{1,3}){3}\b")
+_ID_PATTERN = re.compile(r"\b[0-9a-f]{8}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{12}\b|\b[0-9a-f]{32,64}\b", re.IGNORECASE)
+_FILE_PATTERN = re.compile(r"[\w.\-]+\.(?:trees|tsz|vcf|vcf\.gz|anc|mut|smc|fa|fasta|gff3?|bed|chain)(?:\.gz)?\b", re.IGNORECASE)
+
+
+class RecentLogHandler(logging.Handler):
+    """Keeps the most recent log records in a ring buffer."""
+
+    def __init__(self, capacity: int = MAX_LOG_RECORDS):
+        super().__init__(level=logging.INFO)
+        self._records = collections.deque(maxlen=capacity)
+        self._lock = threading.Lock()
+
+    def emit(self, record: logging.LogRecord):
+        try:
+            entry = {
+                "time": record.created,
+                "level": record.levelname,
+                "logger": record.name,
+                "message": record.getMessage(),
+            }
+            if record.exc_info:
+                entry["exception"] = logging.Formatter().formatException(record.exc_info)
+        except Exception:
+            return
+        with self._lock:
+            self._records.append(entry)
+
+    def get_records(self) -> List[Dict[str, Any]]:
+        with self._lock:
+            return list(self._records)
+
+
+log_capture = RecentLogHandler()
+
+
+def install_log_capture():
+    """Attach the ring buffer to the root logger (idempotent)."""
+    root = logging.getLogger()
+    if log_capture not in root.handlers:
+        root.addHandler(log_capture)
+
+
+def sanitize(text: str) -> str:
+    """Redact home directories, file names, IP addresses and session IDs."""
+    home = str(Path.home())
+    if home and home != "/":
+        text = text.replace(home, "~")
+    text = _FILE_PATTERN.sub("<file>", text)
+    text = _IP_PATTERN.sub("<ip>", text)
+    return _ID_PATTERN.sub("<id>", text)
+
+
+def _package_versions() -> Dict[str, Optional[str]]:
+    versions = {}
+    for package in REPORTED_PACKAGES:
+        try:
+            versions[package] = importlib_metadata.version(package)
+        except importlib_metadata.PackageNotFoundError:
+            versions[package] = None
+    return versions
+
+
+def system_info() -> Dict[str, Any]:
+    from argscape.backend.hardware import get_gpu_info, get_memory_info
+    from argscape.backend.resources import get_cpu_count
+    return {
+        "platform": platform.platform(),
+        "machine": platform.machine(),
+        "python": sys.version.split()[0],
+        "implementation": platform.python_implementation(),
+        "cpu_count": get_cpu_count(),
+        "memory": get_memory_info(),
+        "gpu": get_gpu_info(),
+        "packages": _package_versions(),
+        "settings": {k: v for k, v in settings.get_all().items() if k not in REDACTED_SETTINGS},
+        "frozen": bool(getattr(sys, "frozen", False)),
+        "environment": sorted(k for k in os.environ if k.startswith("ARGSCAPE_")),
+    }
+
+
+def table_sizes(ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """Row counts only: no node times, positions, states or metadata."""
+    tables = ts.tables
+    return {
+        "sequence_length": ts.sequence_length,
+        "num_trees": ts.num_trees,
+        "time_units": ts.time_units,
+        "rows": {
+            name: getattr(tables, name).num_rows
+            for name in ("nodes", "edges", "sites", "mutations", "individuals", "populations", "migrations", "provenances")
+        },
+    }
+
+
+def build_bundle(
+    error: Optional[str] = None,
+    operation: Optional[str] = None,
+    user_comment: Optional[str] = None,
+    ts: Optional[tskit.TreeSequence] = None,
+    include_logs: bool = True
+) -> Dict[str, Any]:
+    """Assemble a sanitized diagnostic bundle for preview, saving or submission."""
+    bundle = {
+        "format": BUNDLE_FORMAT,
+        "version": BUNDLE_VERSION,
+        "created_at": time.time(),
+        "operation": operation,
+        "error": sanitize(error) if error else None,
+        "user_comment": user_comment,
+        "system": system_info(),
+        "tree_sequence": table_sizes(ts) if ts is not None else None,
+        "logs": [],
+    }
+    if include_logs:
+        bundle["logs"] = [
+            {**record, "message": sanitize(record["message"]), **({"exception": sanitize(record["exception"])} if "exception" in record else {})}
+            for record in log_capture.get_records()
+        ]
+    return bundle
+
+
+def save_bundle(bundle: Dict[str, Any]) -> Path:
+    """Write a bundle to the diagnostics folder in the config directory."""
+    directory = get_data_dir() / DIAGNOSTICS_DIRNAME
+    directory.mkdir(parents=True, exist_ok=True)
+    path = directory / f"argscape-diagnostics-{time.strftime('%Y%m%d-%H%M%S')}.json"
+    path.write_text(json.dumps(bundle, indent=2))
+    logger.info(f"Saved diagnostic bundle to {path}")
+    return path
+
+
+def submit_bundle(bundle: Dict[str, Any]) -> Dict[str, Any]:
+    """POST a bundle as JSON to the configured report endpoint."""
+    endpoint = settings.get("diagnostics_endpoint")
+    if not endpoint:
+        raise ValueError("No diagnostics endpoint is configured; save the bundle locally instead")
+    response = http_request(
+        endpoint,
+        "diagnostic report",
+        method="POST",
+        data=json.dumps(bundle).encode("utf-8"),
+        headers={"Content-Type": "application/json"},
+    )
+    return {"submitted": True, "endpoint": endpoint, "response": response.decode("utf-8", errors="replace")[:2000]}
@@ -0,0 +1,197 @@
+"""
+Content-addressed cache of downloaded files, shared across sessions.
+Files fetched from dataset records and remote storage are stored once under
+their SHA-256 in the config directory, with source keys (a published MD5, or
+a remote URL with its ETag) pointing at them. Opening the same file again
+finds it by key without touching the network, which also makes it available
+offline. The cache stays within the download_cache_mb quota by dropping the
+least recently used files; pinned files are never dropped.
+"""
+
+import hashlib
+import json
+import logging
+import shutil
+import threading
+import time
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from argscape.backend.settings import get_cache_dir, settings
+
+logger = logging.getLogger(__name__)
+
+CACHE_DIRNAME = "download_cache"
+INDEX_FILENAME = "index.json"
+HASH_BLOCK_SIZE = 1 << 20
+
+
+def sha256_file(path: Path) -> str:
+    digest = hashlib.sha256()
+    with open(path, "rb") as f:
+        for block in iter(lambda: f.read(HASH_BLOCK_SIZE), b""):
+            digest.update(block)
+    return digest.hexdigest()
+
+
+class DownloadCache:
+    """Blobs named by their SHA-256, with an index of names, source keys and pins."""
+
+    def __init__(self, directory: Optional[Path] = None):
+        self._directory = directory
+        self._lock = threading.Lock()
+        self._entries: Dict[str, Dict[str, Any]] = {}
+        self._load()
+
+    @property
+    def directory(self) -> Path:
+        # Follows the cache_dir setting, so the folder can be moved while running
+        return self._directory or get_cache_dir() / CACHE_DIRNAME
+
+    @property
+    def _index_path(self) -> Path:
+        return self.directory / INDEX_FILENAME
+
+    def blob_path(self, sha256: str) -> Path:
+        return self.directory / f"{sha256}.blob"
+
+    def _load(self):
+        if not self._index_path.exists():
+            return
+        try:
+            with open(self._index_path, "r") as f:
+                self._entries = json.load(f)
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable download cache index {self._index_path}: {e}")
+            return
+        # Drop entries whose blob was removed outside the app
+        for sha256 in [sha for sha in self._entries if not self.blob_path(sha).exists()]:
+            del self._entries[sha256]
+
+    def _save(self):
+        try:
+            self.directory.mkdir(parents=True, exist_ok=True)
+            with open(self._index_path, "w") as f:
+                json.dump(self._entries, f, indent=2)
+        except Exception as e:
+            logger.warning(f"Failed to save download cache index to {self._index_path}: {e}")
+
+    def lookup(self, key: str) -> Optional[Path]:
+        """Path of the cached file for a source key, or None."""
+        with self._lock:
+            for entry in self._entries.values():
+                if key in entry["keys"]:
+                    entry["used_at"] = time.time()
+                    self._save()
+                    return self.blob_path(entry["sha256"])
+        return None
+
+    def add_file(self, path: Path, name: str, keys: List[str], source: Optional[str] = None) -> Path:
+        """Move a finished download into the cache; returns the cached path."""
+        sha256 = sha256_file(path)
+        now = time.time()
+        with self._lock:
+            self.directory.mkdir(parents=True, exist_ok=True)
+            target = self.blob_path(sha256)
+            if target.exists():
+                path.unlink(missing_ok=True)
+            else:
+                shutil.move(str(path), target)
+            # Source keys point at their newest content, e.g. after a remote file changed
+            for other in self._entries.values():
+                if other["sha256"] != sha256:
+                    other["keys"] = [k for k in other["keys"] if k not in keys]
+            entry = self._entries.setdefault(sha256, {
+                "sha256": sha256,
+                "name": name,
+                "size_bytes": target.stat().st_size,
+                "keys": [],
+                "sources": [],
+                "pinned": False,
+                "added_at": now,
+            })
+            entry["keys"] = sorted(set(entry["keys"]) | set(keys))
+            if source and source not in entry["sources"]:
+                entry["sources"].append(source)
+            entry["used_at"] = now
+            self._prune(keep=sha256)
+            self._save()
+        logger.info(f"Cached {name} as {sha256[:12]} ({entry['size_bytes']} bytes)")
+        return target
+
+    def add_bytes(self, contents: bytes, name: str, keys: List[str], source: Optional[str] = None) -> Path:
+        self.directory.mkdir(parents=True, exist_ok=True)
+        temp_path = self.directory / f".incoming-{hashlib.sha256(contents).hexdigest()}"
+        temp_path.write_bytes(contents)
+        return self.add_file(temp_path, name, keys, source)
+
+    def list(self) -> List[Dict[str, Any]]:
+        """Entries, most recently used first."""
+        with self._lock:
+            entries = [dict(entry) for entry in self._entries.values()]
+        return sorted(entries, key=lambda e: -e["used_at"])
+
+    def get(self, sha256: str) -> Dict[str, Any]:
+        """An entry, marked as used; raises KeyError if it is not cached."""
+        with self._lock:
+            entry = self._entries[sha256]
+            entry["used_at"] = time.time()
+            self._save()
+            return dict(entry)
+
+    def pin(self, sha256: str, pinned: bool = True) -> Dict[str, Any]:
+        with self._lock:
+            entry = self._entries[sha256]
+            entry["pinned"] = pinned
+            self._prune()
+            self._save()
+            return dict(entry)
+
+    def evict(self, sha256: str) -> bool:
+        with self._lock:
+            entry = self._entries.pop(sha256, None)
+            if entry is None:
+                return False
+            self.blob_path(sha256).unlink(missing_ok=True)
+            self._save()
+        return True
+
+    def clear(self, include_pinned: bool = False) -> int:
+        """Evict everything (pinned files only with include_pinned)."""
+        with self._lock:
+            evicted = [sha for sha, entry in self._entries.items() if include_pinned or not entry["pinned"]]
+            for sha256 in evicted:
+                self.blob_path(sha256).unlink(missing_ok=True)
+                del self._entries[sha256]
+            self._save()
+        return len(evicted)
+
+    def _prune(self, keep: Optional[str] = None):
+        """Drop least recently used unpinned files beyond the quota (lock held)."""
+        quota_mb = settings.get("download_cache_mb")
+        if quota_mb is None:
+            return
+        limit = quota_mb * 1024 * 1024
+        total = sum(entry["size_bytes"] for entry in self._entries.values())
+        for entry in sorted(self._entries.values(), key=lambda e: e["used_at"]):
+            if total <= limit:
+                break
+            if entry["pinned"] or entry["sha256"] == keep:
+                continue
+            self.blob_path(entry["sha256"]).unlink(missing_ok=True)
+            del self._entries[entry["sha256"]]
+            total -= entry["size_bytes"]
+            logger.info(f"Dropped cached download {entry['name']} to stay within the cache quota")
+
+    def usage(self) -> Dict[str, Any]:
+        with self._lock:
+            return {
+                "directory": str(self.directory),
+                "num_files": len(self._entries),
+                "num_pinned": sum(1 for entry in self._entries.values() if entry["pinned"]),
+                "total_bytes": sum(entry["size_bytes"] for entry in self._entries.values()),
+                "quota_mb": settings.get("download_cache_mb"),
+            }
+
+
+download_cache = DownloadCache()
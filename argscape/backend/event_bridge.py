@@ -0,0 +1,165 @@
+"""
+Local event bridge for external tools.
+When enabled, ARGscape windows report what they show (open file, genome
+position, selected node) over a localhost WebSocket, and external tools such
+as IGV scripts, dashboards or Jupyter widgets can follow along or send
+navigation commands for the windows to carry out. Tools that can't keep a
+socket open can publish the same messages over REST, and a notebook helper
+(argscape.notebook) can push tree sequences into a running instance to view.
+"""
+
+import asyncio
+import json
+import logging
+import os
+import time
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from fastapi import WebSocket
+
+from argscape.backend.settings import get_config_dir
+
+logger = logging.getLogger(__name__)
+
+# Written by `argscape --listen` so local helpers can find the running instance
+INSTANCE_FILENAME = "instance.json"
+
+# Messages viewers send about their own state, and commands tools send to viewers
+EVENT_TYPES = ("file_opened", "position_changed", "selection_changed")
+COMMAND_TYPES = ("open_file", "navigate", "select")
+
+CLIENT_VIEWER = "viewer"
+CLIENT_TOOL = "tool"
+CLIENT_ROLES = (CLIENT_VIEWER, CLIENT_TOOL)
+
+LOOPBACK_HOSTS = ("127.0.0.1", "::1", "localhost")
+
+
+def is_loopback(host: Optional[str]) -> bool:
+    return host in LOOPBACK_HOSTS or (host or "").startswith("127.")
+
+
+def get_instance_path() -> Path:
+    return get_config_dir() / INSTANCE_FILENAME
+
+
+def write_instance_file(url: str, token: Optional[str]) -> Path:
+    """Record where the listening instance is; readable by the current user only, as it holds the token."""
+    path = get_instance_path()
+    path.parent.mkdir(parents=True, exist_ok=True)
+    fd = os.open(path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+    with os.fdopen(fd, "w") as f:
+        json.dump({"url": url, "token": token, "pid": os.getpid(), "started_at": time.time()}, f)
+    return path
+
+
+def read_instance_file() -> Optional[Dict[str, Any]]:
+    path = get_instance_path()
+    try:
+        with open(path, "r") as f:
+            return json.load(f)
+    except (OSError, ValueError):
+        return None
+
+
+def remove_instance_file():
+    """Remove the instance file if it still belongs to this process."""
+    instance = read_instance_file()
+    if instance and instance.get("pid") == os.getpid():
+        try:
+            get_instance_path().unlink()
+        except OSError:
+            pass
+
+
+def _optional_filename(message: Dict[str, Any]) -> Optional[str]:
+    filename = message.get("filename")
+    if filename is not None and not isinstance(filename, str):
+        raise ValueError("filename must be a string")
+    return filename
+
+
+def validate_message(message: Any) -> Dict[str, Any]:
+    """Check an incoming event or command and return its normalized form."""
+    if not isinstance(message, dict):
+        raise ValueError("Messages must be JSON objects")
+    message_type = message.get("type")
+    if message_type not in EVENT_TYPES + COMMAND_TYPES:
+        raise ValueError(f"Unknown message type '{message_type}'. Expected one of {EVENT_TYPES + COMMAND_TYPES}")
+
+    normalized: Dict[str, Any] = {
+        "type": message_type,
+        "kind": "event" if message_type in EVENT_TYPES else "command",
+        "filename": _optional_filename(message),
+    }
+    if message_type in ("file_opened", "open_file"):
+        if not normalized["filename"]:
+            raise ValueError(f"{message_type} requires a filename")
+    elif message_type in ("position_changed", "navigate"):
+        try:
+            start, end = float(message["start"]), float(message["end"])
+        except (KeyError, TypeError, ValueError):
+            raise ValueError(f"{message_type} requires numeric start and end positions")
+        if not 0 <= start <= end:
+            raise ValueError("Positions must satisfy 0 <= start <= end")
+        normalized.update(start=start, end=end)
+    else:
+        node_id = message.get("node_id")
+        if node_id is not None and (isinstance(node_id, bool) or not isinstance(node_id, int) or node_id < 0):
+            raise ValueError(f"{message_type} requires a non-negative integer node_id, or null to clear")
+        normalized["node_id"] = node_id
+    normalized["time"] = time.time()
+    return normalized
+
+
+class EventBridge:
+    """Connected WebSocket clients and the latest state reported by viewers."""
+
+    def __init__(self):
+        self._clients: Dict[WebSocket, str] = {}
+        self._state: Dict[str, Dict[str, Any]] = {}
+        self._lock = asyncio.Lock()
+
+    async def connect(self, websocket: WebSocket, role: str):
+        await websocket.accept()
+        async with self._lock:
+            self._clients[websocket] = role
+        # Bring new clients up to date with what the viewers currently show
+        for message in list(self._state.values()):
+            await websocket.send_json(message)
+        logger.info(f"Event bridge {role} connected ({len(self._clients)} clients)")
+
+    async def disconnect(self, websocket: WebSocket):
+        async with self._lock:
+            self._clients.pop(websocket, None)
+
+    async def publish(self, message: Any, sender: Optional[WebSocket] = None) -> Dict[str, Any]:
+        """Validate and deliver a message; events go to every other client, commands to viewers only."""
+        message = validate_message(message)
+        if message["kind"] == "event":
+            self._state[message["type"]] = message
+        async with self._lock:
+            recipients = [
+                ws for ws, role in self._clients.items()
+                if ws is not sender and (message["kind"] == "event" or role == CLIENT_VIEWER)
+            ]
+        delivered = 0
+        for websocket in recipients:
+            try:
+                await websocket.send_json(message)
+                delivered += 1
+            except Exception as e:
+                logger.debug(f"Dropping event bridge client: {e}")
+                await self.disconnect(websocket)
+        return {**message, "delivered": delivered}
+
+    def state(self) -> Dict[str, Any]:
+        roles: List[str] = list(self._clients.values())
+        return {
+            "state": dict(self._state),
+            "clients": {role: roles.count(role) for role in CLIENT_ROLES},
+        }
+
+
+event_bridge = EventBridge()
@@ -0,0 +1,148 @@
+"""
+Export size and duration estimates.
+Before an export runs, the expected output size and time are estimated from
+element counts and the chosen resolution, so a million-edge ARG does not
+quietly turn into a multi-gigabyte SVG. Vector exports with more elements
+than export_rasterize_above fall back to PNG, and estimates above
+export_warn_mb (or the free disk space, for files written locally) come with
+a warning. The per-element constants are averages measured on typical
+exports and are meant for orders of magnitude, not exact byte counts.
+"""
+
+import shutil
+from pathlib import Path
+from typing import Any, Dict, Optional
+
+import tskit
+
+from argscape.backend.settings import settings
+
+# Average bytes per element in the frontend's SVG output
+SVG_BYTES_PER_NODE = 140
+SVG_BYTES_PER_EDGE = 110
+SVG_BYTES_PER_MUTATION = 120
+SVG_BYTES_PER_LABEL = 90
+SVG_OVERHEAD_BYTES = 4_000
+
+# PNG of a graph on a flat background compresses to roughly this fraction of raw RGBA
+PNG_COMPRESSION_RATIO = 0.12
+
+# Throughputs, in elements (or pixels) per second
+SVG_ELEMENTS_PER_SECOND = 150_000
+RASTER_PIXELS_PER_SECOND = 40_000_000
+TREES_BYTES_PER_SECOND = 100_000_000
+TSZ_BYTES_PER_SECOND = 25_000_000
+TSZ_COMPRESSION_RATIO = 0.2
+
+# Layout documents: one position row per node
+LAYOUT_JSON_BYTES_PER_NODE = 48
+LAYOUT_PARQUET_BYTES_PER_NODE = 20
+CSV_BYTES_PER_ROW = 40
+
+VECTOR_FORMATS = ("svg",)
+EXPORT_FORMATS = ("svg", "png", "trees", "tsz", "layout_json", "layout_parquet", "edges_csv")
+
+
+def element_counts(ts: tskit.TreeSequence, overrides: Optional[Dict[str, int]] = None) -> Dict[str, int]:
+    """Elements an export draws; the viewer passes its own counts when it shows a subset."""
+    counts = {
+        "nodes": ts.num_nodes,
+        "edges": ts.num_edges,
+        "mutations": ts.num_mutations,
+        "labels": ts.num_samples,
+    }
+    counts.update({key: int(value) for key, value in (overrides or {}).items() if key in counts})
+    return counts
+
+
+def _raster_estimate(width: int, height: int, scale: float) -> Dict[str, Any]:
+    pixels = int(width * scale) * int(height * scale)
+    return {
+        "bytes": int(pixels * 4 * PNG_COMPRESSION_RATIO),
+        "seconds": pixels / RASTER_PIXELS_PER_SECOND,
+        "pixels": pixels,
+    }
+
+
+def estimate_export(
+    ts: tskit.TreeSequence,
+    format: str,
+    counts: Optional[Dict[str, int]] = None,
+    width: int = 1920,
+    height: int = 1080,
+    scale: float = 1.0,
+    destination: Optional[Path] = None
+) -> Dict[str, Any]:
+    """Expected size and time of an export, with the format it will actually use.
+
+    `destination` is the directory a server-side export is written to; its free
+    space is checked as well.
+    """
+    if format not in EXPORT_FORMATS:
+        raise ValueError(f"format must be one of {EXPORT_FORMATS}")
+    counts = element_counts(ts, counts)
+    elements = sum(counts.values())
+    rasterize_above = settings.get("export_rasterize_above")
+    used_format = format
+    rasterized = False
+
+    if format in VECTOR_FORMATS and rasterize_above is not None and elements > rasterize_above:
+        used_format = "png"
+        rasterized = True
+
+    if used_format == "svg":
+        size = (
+            SVG_OVERHEAD_BYTES
+            + counts["nodes"] * SVG_BYTES_PER_NODE
+            + counts["edges"] * SVG_BYTES_PER_EDGE
+            + counts["mutations"] * SVG_BYTES_PER_MUTATION
+            + counts["labels"] * SVG_BYTES_PER_LABEL
+        )
+        seconds = elements / SVG_ELEMENTS_PER_SECOND
+    elif used_format == "png":
+        raster = _raster_estimate(width, height, scale)
+        size = raster["bytes"]
+        # Drawing the vector source dominates for dense graphs
+        seconds = raster["seconds"] + elements / SVG_ELEMENTS_PER_SECOND
+    elif used_format == "trees":
+        size = ts.nbytes
+        seconds = size / TREES_BYTES_PER_SECOND
+    elif used_format == "tsz":
+        size = int(ts.nbytes * TSZ_COMPRESSION_RATIO)
+        seconds = ts.nbytes / TSZ_BYTES_PER_SECOND
+    elif used_format == "layout_json":
+        size = counts["nodes"] * LAYOUT_JSON_BYTES_PER_NODE
+        seconds = counts["nodes"] / SVG_ELEMENTS_PER_SECOND
+    elif used_format == "layout_parquet":
+        size = counts["nodes"] * LAYOUT_PARQUET_BYTES_PER_NODE
+        seconds = counts["nodes"] / SVG_ELEMENTS_PER_SECOND
+    else:  # edges_csv
+        size = (counts["edges"] + 1) * CSV_BYTES_PER_ROW
+        seconds = counts["edges"] / SVG_ELEMENTS_PER_SECOND
+
+    warnings = []
+    if rasterized:
+        warnings.append(
+            f"{elements:,} elements is above the vector export limit of {rasterize_above:,}; "
+            f"exporting as PNG instead"
+        )
+    warn_mb = settings.get("export_warn_mb")
+    if warn_mb is not None and size > warn_mb * 1024 * 1024:
+        warnings.append(f"Expected output is about {size / 1024 / 1024:,.0f} MB, above the {warn_mb:,} MB warning threshold")
+    free_bytes = None
+    if destination is not None:
+        free_bytes = shutil.disk_usage(destination).free
+        if size > free_bytes:
+            warnings.append(f"Expected output is larger than the {free_bytes / 1024 / 1024:,.0f} MB free at {destination}")
+
+    return {
+        "requested_format": format,
+        "format": used_format,
+        "rasterized": rasterized,
+        "elements": counts,
+        "num_elements": elements,
+        "bytes": int(size),
+        "seconds": round(seconds, 2),
+        "free_bytes": free_bytes,
+        "warnings": warnings,
+    }
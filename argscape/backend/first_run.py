@@ -0,0 +1,177 @@
+"""
+Backend for the first-run setup wizard.
+The wizard chooses where data and caches live, memory and disk budgets,
+telemetry consent and the default layout. Choices go through the settings
+module's validators; changing the data or cache folder later moves the
+existing audit logs, downloads and caches to the new place (and moves them
+back if anything fails), so nothing is silently left behind.
+"""
+
+import logging
+import shutil
+from pathlib import Path
+from typing import Any, Dict, List, Tuple
+
+from argscape.backend.archive import ARCHIVE_DIRNAME
+from argscape.backend.audit import AUDIT_DIRNAME
+from argscape.backend.datasets import DOWNLOADS_DIRNAME
+from argscape.backend.diagnostics import DIAGNOSTICS_DIRNAME
+from argscape.backend.download_cache import CACHE_DIRNAME
+from argscape.backend.hardware import get_disk_info, get_memory_info
+from argscape.backend.portable import portable_status
+from argscape.backend.remote_files import BLOCK_CACHE_DIRNAME
+from argscape.backend.settings import SETTINGS_SCHEMA, get_cache_dir, get_config_dir, get_data_dir, settings
+
+logger = logging.getLogger(__name__)
+
+# Settings the wizard sets
+SETUP_KEYS = (
+    "data_dir",
+    "cache_dir",
+    "memory_limit_mb",
+    "derived_data_quota_mb",
+    "archive_quota_mb",
+    "download_cache_mb",
+    "telemetry_consent",
+    "default_layout",
+)
+# Folders under the data and cache locations that move when those change
+DATA_SUBDIRS = (AUDIT_DIRNAME, DIAGNOSTICS_DIRNAME)
+CACHE_SUBDIRS = (CACHE_DIRNAME, BLOCK_CACHE_DIRNAME, ARCHIVE_DIRNAME, DOWNLOADS_DIRNAME)
+# Suggested memory ceiling as a share of physical memory
+SUGGESTED_MEMORY_FRACTION = 0.75
+
+
+def _folder_size(path: Path) -> int:
+    return sum(p.stat().st_size for p in path.rglob("*") if p.is_file())
+
+
+def check_directory(path: str) -> Dict[str, Any]:
+    """Validate a folder the user picked and report its free space; raises ValueError."""
+    validated = Path(SETTINGS_SCHEMA["data_dir"][1](path))
+    disk = get_disk_info({"chosen": validated})[0]
+    return {
+        "path": str(validated),
+        "exists": validated.exists(),
+        "free_mb": disk.get("free_mb"),
+        "total_mb": disk.get("total_mb"),
+    }
+
+
+def get_setup_state() -> Dict[str, Any]:
+    """Current choices, where things are stored now, and suggested budgets for this machine."""
+    memory = get_memory_info()
+    cache_disk = get_disk_info({"cache": get_cache_dir()})[0]
+    suggested_memory = int(memory["total_mb"] * SUGGESTED_MEMORY_FRACTION) if memory["total_mb"] else None
+    suggested_cache = SETTINGS_SCHEMA["download_cache_mb"][0]
+    if cache_disk.get("free_mb") is not None:
+        # Leave most of a small drive free
+        suggested_cache = min(suggested_cache, cache_disk["free_mb"] // 10)
+    return {
+        "completed": settings.get("setup_completed"),
+        "settings": {key: settings.get(key) for key in SETUP_KEYS},
+        "locations": {"config": str(get_config_dir()), "data": str(get_data_dir()), "cache": str(get_cache_dir())},
+        "disks": get_disk_info({"data": get_data_dir(), "cache": get_cache_dir()}),
+        "memory": memory,
+        "suggestions": {"memory_limit_mb": suggested_memory, "download_cache_mb": suggested_cache},
+        # Folders outside the portable folder are left behind when the drive moves to another machine
+        "portable": portable_status(),
+    }
+
+
+def _move_back(moved: List[Dict[str, Any]]):
+    for entry in reversed(moved):
+        try:
+            shutil.move(entry["to"], entry["from"])
+        except OSError as e:
+            logger.error(f"Could not move {entry['to']} back to {entry['from']}: {e}")
+
+
+def migrate_folders(names: Tuple[str, ...], old_root: Path, new_root: Path) -> List[Dict[str, Any]]:
+    """
+    Move each named folder from `old_root` to `new_root`; returns what moved.
+    Refuses to overwrite a non-empty folder at the destination, and undoes
+    earlier moves if a later one fails.
+    """
+    if old_root.resolve() == new_root.resolve():
+        return []
+    moved: List[Dict[str, Any]] = []
+    try:
+        for name in names:
+            source, target = old_root / name, new_root / name
+            if not source.exists():
+                continue
+            if source.resolve() == new_root.resolve() or source.resolve() in new_root.resolve().parents:
+                raise ValueError(f"{new_root} is inside {source}, which would be moved into itself")
+            if target.exists() and any(target.iterdir()):
+                raise ValueError(f"{target} already exists and is not empty; move or remove it first")
+            size = _folder_size(source)
+            new_root.mkdir(parents=True, exist_ok=True)
+            if target.exists():
+                target.rmdir()
+            shutil.move(str(source), str(target))
+            moved.append({"folder": name, "from": str(source), "to": str(target), "bytes": size})
+    except ValueError:
+        _move_back(moved)
+        raise
+    except OSError as e:
+        _move_back(moved)
+        raise ValueError(f"Could not move data to {new_root}: {e}")
+    return moved
+
+
+def _new_root(key: str, values: Dict[str, Any], fallback: Path) -> Path:
+    """Where a folder setting will point once `values` are saved."""
+    # An environment override keeps winning, so its folder doesn't move
+    if settings.get_source(key)["source"] == "environment":
+        return Path(settings.get(key))
+    configured = values.get(key, settings.get(key))
+    return Path(configured) if configured else fallback
+
+
+def _warnings(values: Dict[str, Any], cache_root: Path) -> List[str]:
+    warnings = []
+    for key in ("data_dir", "cache_dir"):
+        if key in values and settings.get_source(key)["source"] == "environment":
+            warnings.append(f"{key} is set by {SETTINGS_SCHEMA[key][2]}, which takes precedence until it is unset")
+    total_memory = get_memory_info()["total_mb"]
+    limit = values.get("memory_limit_mb", settings.get("memory_limit_mb"))
+    if limit and total_memory and limit > total_memory:
+        warnings.append(f"The memory limit ({limit} MB) is more than this machine has ({total_memory} MB)")
+    budgets = sum(values.get(key, settings.get(key)) or 0 for key in ("derived_data_quota_mb", "archive_quota_mb", "download_cache_mb"))
+    free = get_disk_info({"cache": cache_root})[0].get("free_mb")
+    if free is not None and budgets > free:
+        warnings.append(f"Disk budgets add up to {budgets} MB but only {free} MB is free at {cache_root}")
+    return warnings
+
+
+def apply_setup(choices: Dict[str, Any]) -> Dict[str, Any]:
+    """Validate the wizard's choices, move data if its folders changed, then save; raises ValueError."""
+    unknown = [key for key in choices if key not in SETUP_KEYS]
+    if unknown:
+        raise ValueError(f"Unknown setup choices: {unknown}. Expected some of {list(SETUP_KEYS)}")
+    values = {}
+    for key, value in choices.items():
+        try:
+            values[key] = SETTINGS_SCHEMA[key][1](value)
+        except (TypeError, ValueError) as e:
+            raise ValueError(f"{key}: {e}")
+
+    old_data, old_cache = get_data_dir(), get_cache_dir()
+    new_data = _new_root("data_dir", values, get_config_dir())
+    new_cache = _new_root("cache_dir", values, new_data)
+
+    moved = migrate_folders(DATA_SUBDIRS, old_data, new_data)
+    try:
+        moved += migrate_folders(CACHE_SUBDIRS, old_cache, new_cache)
+        effective = settings.update({**values, "setup_completed": True})
+    except ValueError:
+        _move_back(moved)
+        raise
+    for entry in moved:
+        logger.info(f"Moved {entry['folder']} ({entry['bytes']} bytes) to {entry['to']}")
+    return {
+        "settings": {key: effective[key] for key in SETUP_KEYS},
+        "moved": moved,
+        "warnings": _warnings(values, new_cache),
+    }
@@ -0,0 +1,187 @@
+"""
+Small synthetic ARGs with known, documented properties.
+Each fixture is built table by table (no simulation), so it is identical on
+every machine and tskit version, and its summary (topology, breakpoints,
+polytomies, isolated samples, locations) can be kept as a golden file and
+compared after a change. Used by `argscape generate-fixture` and available
+to plugin authors as `from argscape.backend.fixtures import generate_fixture`.
+"""
+
+import json
+from pathlib import Path
+from typing import Any, Dict, List
+
+import tskit
+
+SEQUENCE_LENGTH = 100.0
+
+
+def _new_tables() -> tskit.TableCollection:
+    tables = tskit.TableCollection(sequence_length=SEQUENCE_LENGTH)
+    tables.populations.metadata_schema = tskit.MetadataSchema.permissive_json()
+    tables.populations.add_row(metadata={"name": "pop_0"})
+    return tables
+
+
+def _add_nodes(tables: tskit.TableCollection, num_samples: int, ancestor_times: List[float], individuals: bool = False):
+    for _ in range(num_samples):
+        individual = tables.individuals.add_row() if individuals else tskit.NULL
+        tables.nodes.add_row(flags=tskit.NODE_IS_SAMPLE, time=0, population=0, individual=individual)
+    for time in ancestor_times:
+        tables.nodes.add_row(time=time, population=0)
+
+
+def _add_edges(tables: tskit.TableCollection, edges: List[tuple]):
+    """Edges as (left, right, parent, [children])."""
+    for left, right, parent, children in edges:
+        for child in children:
+            tables.edges.add_row(left=left, right=right, parent=parent, child=child)
+
+
+def _add_sites(tables: tskit.TableCollection, mutations: List[tuple]):
+    """One biallelic site per (position, node)."""
+    for position, node in mutations:
+        site = tables.sites.add_row(position=position, ancestral_state="A")
+        tables.mutations.add_row(site=site, node=node, derived_state="T")
+
+
+def _finish(tables: tskit.TableCollection) -> tskit.TreeSequence:
+    tables.sort()
+    tables.build_index()
+    tables.compute_mutation_parents()
+    return tables.tree_sequence()
+
+
+def binary_tree() -> tskit.TreeSequence:
+    tables = _new_tables()
+    _add_nodes(tables, 4, [1.0, 2.0, 3.0])
+    _add_edges(tables, [(0, 100, 4, [0, 1]), (0, 100, 5, [2, 3]), (0, 100, 6, [4, 5])])
+    _add_sites(tables, [(10, 0), (60, 5)])
+    return _finish(tables)
+
+
+def polytomy() -> tskit.TreeSequence:
+    tables = _new_tables()
+    _add_nodes(tables, 5, [1.0, 2.0])
+    _add_edges(tables, [(0, 100, 5, [0, 1, 2]), (0, 100, 6, [3, 4, 5])])
+    _add_sites(tables, [(30, 5)])
+    return _finish(tables)
+
+
+def recombination() -> tskit.TreeSequence:
+    tables = _new_tables()
+    _add_nodes(tables, 4, [1.0, 1.0, 3.0, 1.5, 2.0, 4.0])
+    _add_edges(tables, [
+        (0, 50, 4, [0, 1]), (0, 50, 5, [2, 3]), (0, 50, 6, [4, 5]),
+        (50, 100, 7, [0, 2]), (50, 100, 8, [1, 3]), (50, 100, 9, [7, 8]),
+    ])
+    _add_sites(tables, [(25, 4), (75, 7)])
+    return _finish(tables)
+
+
+def missing_data() -> tskit.TreeSequence:
+    tables = _new_tables()
+    _add_nodes(tables, 4, [1.0, 2.0, 3.0])
+    # Sample 3 has no ancestry over [0, 40), which tskit reads as missing data
+    _add_edges(tables, [(0, 100, 4, [0, 1]), (0, 100, 5, [2]), (40, 100, 5, [3]), (0, 100, 6, [4, 5])])
+    _add_sites(tables, [(20, 4), (70, 5)])
+    return _finish(tables)
+
+
+def spatial() -> tskit.TreeSequence:
+    tables = _new_tables()
+    _add_nodes(tables, 4, [1.0, 2.0, 3.0], individuals=True)
+    tables.individuals.packset_location([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]])
+    _add_edges(tables, [(0, 100, 4, [0, 1]), (0, 100, 5, [2, 3]), (0, 100, 6, [4, 5])])
+    _add_sites(tables, [(50, 4)])
+    return _finish(tables)
+
+
+# name -> (builder, description, documented properties)
+FIXTURES: Dict[str, tuple] = {
+    "binary_tree": (binary_tree, "One balanced binary tree over 4 samples, 2 sites", {
+        "num_samples": 4, "num_trees": 1, "breakpoints": [0.0, 100.0], "polytomies": 0,
+    }),
+    "polytomy": (polytomy, "5 samples under two nodes with 3 children each", {
+        "num_samples": 5, "num_trees": 1, "max_children": 3, "polytomies": 2,
+    }),
+    "recombination": (recombination, "Two different binary trees over 4 samples, breakpoint at 50", {
+        "num_samples": 4, "num_trees": 2, "breakpoints": [0.0, 50.0, 100.0], "polytomies": 0,
+    }),
+    "missing_data": (missing_data, "Sample 3 isolated (missing) over [0, 40)", {
+        "num_samples": 4, "num_trees": 2, "isolated": {"3": [[0.0, 40.0]]},
+    }),
+    "spatial": (spatial, "Balanced tree whose 4 sample individuals sit on the corners of the unit square", {
+        "num_samples": 4, "has_locations": True,
+    }),
+}
+
+FIXTURE_NAMES = tuple(FIXTURES)
+
+
+def generate_fixture(name: str) -> tskit.TreeSequence:
+    if name not in FIXTURES:
+        raise ValueError(f"Unknown fixture '{name}'. Expected one of {FIXTURE_NAMES}")
+    return FIXTURES[name][0]()
+
+
+def list_fixtures() -> List[Dict[str, Any]]:
+    return [{"name": name, "description": description, "properties": properties} for name, (_, description, properties) in FIXTURES.items()]
+
+
+def _isolated_intervals(ts: tskit.TreeSequence) -> Dict[str, List[List[float]]]:
+    isolated: Dict[str, List[List[float]]] = {}
+    for tree in ts.trees():
+        for sample in ts.samples():
+            if tree.parent(sample) != tskit.NULL or tree.num_children(sample) > 0:
+                continue
+            spans = isolated.setdefault(str(sample), [])
+            if spans and spans[-1][1] == tree.interval.left:
+                spans[-1][1] = tree.interval.right
+            else:
+                spans.append([tree.interval.left, tree.interval.right])
+    return isolated
+
+
+def summarize(ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """The properties fixtures document, computed from any tree sequence."""
+    from argscape.backend.tskit_utils import tree_sequence_fingerprint
+
+    polytomy_nodes = set()
+    max_children = 0
+    topologies = []
+    for tree in ts.trees():
+        for node in tree.nodes():
+            num_children = tree.num_children(node)
+            max_children = max(max_children, num_children)
+            if num_children > 2:
+                polytomy_nodes.add(node)
+        # Isolated samples are extra roots, so a tree can have several
+        topologies.append(" ".join(tree.as_newick(root=root, include_branch_lengths=False) for root in tree.roots))
+    return {
+        "num_samples": ts.num_samples,
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_trees": ts.num_trees,
+        "num_sites": ts.num_sites,
+        "breakpoints": [float(b) for b in ts.breakpoints()],
+        "topologies": topologies,
+        "max_children": max_children,
+        "polytomies": len(polytomy_nodes),
+        "isolated": _isolated_intervals(ts),
+        "has_locations": len(ts.tables.individuals.location) > 0,
+        "fingerprint": tree_sequence_fingerprint(ts),
+    }
+
+
+def compare_summaries(expected: Dict[str, Any], actual: Dict[str, Any]) -> List[str]:
+    """Differences between a golden summary (or a fixture's documented properties) and an actual one."""
+    return [
+        f"{key}: expected {expected[key]!r}, got {actual.get(key)!r}"
+        for key in sorted(expected) if actual.get(key) != expected[key]
+    ]
+
+
+def check_golden(ts: tskit.TreeSequence, golden_path: Path) -> List[str]:
+    golden = json.loads(Path(golden_path).read_text(encoding="utf-8"))
+    return compare_summaries(golden, summarize(ts))
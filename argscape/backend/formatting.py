@@ -0,0 +1,112 @@
+"""
+Locale-aware number and unit formatting, and UI message catalogs.
+Numbers use the locale's thousands and decimal separators, large values can be
+abbreviated with SI suffixes, and times in generations can be shown in years
+with a configurable generation time. The frontend receives the same rules from
+the formatting config endpoint so on-screen values and exports agree.
+"""
+
+import json
+import logging
+import math
+from pathlib import Path
+from typing import Any, Dict, Optional
+
+logger = logging.getLogger(__name__)
+
+# Thousands and decimal separators per locale
+LOCALE_SEPARATORS = {
+    "en": (",", "."),
+    "de": (".", ","),
+    "fr": (" ", ","),
+    "es": (".", ","),
+    "it": (".", ","),
+    "pt": (".", ","),
+    "ch": ("'", "."),
+    "none": ("", "."),
+}
+DEFAULT_LOCALE = "en"
+
+SI_PREFIXES = ((1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k"))
+
+CATALOG_DIR = Path(__file__).parent / "locales"
+
+
+def format_number(value: float, decimals: int = 0, locale: str = DEFAULT_LOCALE) -> str:
+    """Format with the locale's thousands and decimal separators."""
+    thousands, decimal = LOCALE_SEPARATORS.get(locale, LOCALE_SEPARATORS[DEFAULT_LOCALE])
+    text = f"{value:,.{decimals}f}"
+    return text.replace(",", "\0").replace(".", decimal).replace("\0", thousands)
+
+
+def format_si(value: float, unit: str = "", decimals: int = 1, locale: str = DEFAULT_LOCALE) -> str:
+    """Abbreviate with an SI suffix, e.g. 1234567 -> "1.2 M" (or "1.2 Mb" with unit "b")."""
+    if math.isnan(value) or math.isinf(value):
+        return str(value)
+    for factor, prefix in SI_PREFIXES:
+        if abs(value) >= factor:
+            return f"{format_number(value / factor, decimals, locale)} {prefix}{unit}"
+    number = format_number(value, 0 if float(value).is_integer() else decimals, locale)
+    return f"{number} {unit}" if unit else number
+
+
+def generations_to_years(generations: float, generation_time: float) -> float:
+    if generation_time <= 0:
+        raise ValueError("generation_time must be positive")
+    return generations * generation_time
+
+
+def format_time(
+    value: float,
+    time_units: str = "generations",
+    generation_time: Optional[float] = None,
+    locale: str = DEFAULT_LOCALE,
+    decimals: int = 0
+) -> str:
+    """A node time with its unit, converted to years when a generation time is set."""
+    if generation_time is not None and time_units == "generations":
+        value, time_units = generations_to_years(value, generation_time), "years"
+    return f"{format_number(value, decimals, locale)} {time_units}"
+
+
+def formatting_config(locale: str, generation_time: Optional[float]) -> Dict[str, Any]:
+    """Formatting rules for the frontend, which mirrors the functions above."""
+    thousands, decimal = LOCALE_SEPARATORS.get(locale, LOCALE_SEPARATORS[DEFAULT_LOCALE])
+    return {
+        "locale": locale,
+        "thousands_separator": thousands,
+        "decimal_separator": decimal,
+        "si_prefixes": [{"factor": factor, "prefix": prefix} for factor, prefix in SI_PREFIXES],
+        "generation_time_years": generation_time,
+        "supported_locales": list(LOCALE_SEPARATORS),
+    }
+
+
+def _read_catalog(path: Path) -> Dict[str, str]:
+    try:
+        with open(path, "r", encoding="utf-8") as f:
+            return {str(k): str(v) for k, v in json.load(f).items()}
+    except FileNotFoundError:
+        return {}
+    except (OSError, ValueError) as e:
+        logger.warning(f"Ignoring unreadable message catalog {path}: {e}")
+        return {}
+
+
+def load_message_catalog(locale: str, user_dir: Optional[Path] = None) -> Dict[str, Any]:
+    """UI messages for a locale, falling back to English for missing keys.
+
+    Catalogs in `user_dir` (the user's config directory) override the bundled ones,
+    so translations can be tried without rebuilding the app.
+    """
+    messages: Dict[str, str] = {}
+    sources = []
+    for name in dict.fromkeys((DEFAULT_LOCALE, locale)):
+        for directory in (CATALOG_DIR, user_dir):
+            if directory is None:
+                continue
+            catalog = _read_catalog(Path(directory) / f"{name}.json")
+            if catalog:
+                messages.update(catalog)
+                sources.append(str(Path(directory) / f"{name}.json"))
+    return {"locale": locale, "messages": messages, "sources": sources}
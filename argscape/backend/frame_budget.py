@@ -0,0 +1,110 @@
+"""
+Frame-budget-aware level of detail.
+The viewer reports the frame rate it achieves while the user pans, zooms or
+drags; a controller per file turns those reports into a decimation level.
+Each level halves the geometry the backend streams: the graph stream keeps
+half as many samples and map tiles merge nodes over cells twice as wide.
+Levels go up quickly when frames are dropped and come down slowly once there
+is headroom, so the view settles instead of oscillating.
+"""
+
+import threading
+import time
+from typing import Any, Dict, Optional, Tuple
+
+from argscape.backend.settings import settings
+
+MAX_LOD_LEVEL = 6
+# Reports needed before changing level; dropping detail reacts faster than restoring it
+REPORTS_TO_DECREASE_DETAIL = 2
+REPORTS_TO_INCREASE_DETAIL = 5
+# Below target * LOW_WATERMARK is too slow; above target * HIGH_WATERMARK leaves room for more detail
+LOW_WATERMARK = 0.85
+HIGH_WATERMARK = 1.3
+MIN_STREAM_SAMPLES = 16
+# Controllers nobody reported to for this long are dropped
+IDLE_SECONDS = 3600
+
+
+class FrameBudgetController:
+    """Decimation level for one session and file, driven by frame-rate reports."""
+
+    def __init__(self, target_fps: Optional[int] = None):
+        self.target_fps = target_fps or settings.get("render_target_fps")
+        self.level = 0
+        self._slow = 0
+        self._fast = 0
+        self.last_fps: Optional[float] = None
+        self.last_report = time.time()
+
+    def report(self, fps: float, target_fps: Optional[int] = None) -> bool:
+        """Record one measurement; returns whether the level changed."""
+        if fps <= 0:
+            raise ValueError("fps must be positive")
+        if target_fps is not None:
+            self.target_fps = target_fps
+        self.last_fps = fps
+        self.last_report = time.time()
+
+        if fps < self.target_fps * LOW_WATERMARK:
+            self._slow += 1
+            self._fast = 0
+        elif fps > self.target_fps * HIGH_WATERMARK:
+            self._fast += 1
+            self._slow = 0
+        else:
+            self._slow = self._fast = 0
+
+        if self._slow >= REPORTS_TO_DECREASE_DETAIL and self.level < MAX_LOD_LEVEL:
+            self.level += 1
+            self._slow = 0
+            return True
+        if self._fast >= REPORTS_TO_INCREASE_DETAIL and self.level > 0:
+            self.level -= 1
+            self._fast = 0
+            return True
+        return False
+
+    def max_samples(self, requested: int) -> int:
+        """Samples to stream at this level, for a request of `requested`."""
+        return max(min(requested, MIN_STREAM_SAMPLES), requested >> self.level)
+
+    def cell_scale(self) -> int:
+        """Multiplier for tile node/edge merge cells."""
+        return 2 ** self.level
+
+    def state(self) -> Dict[str, Any]:
+        return {
+            "level": self.level,
+            "max_level": MAX_LOD_LEVEL,
+            "target_fps": self.target_fps,
+            "last_fps": self.last_fps,
+            "detail_fraction": 1 / (2 ** self.level),
+        }
+
+
+class FrameBudgetRegistry:
+    """Controllers per session and file."""
+
+    def __init__(self):
+        self._controllers: Dict[Tuple[str, str], FrameBudgetController] = {}
+        self._lock = threading.Lock()
+
+    def get(self, session_id: str, filename: str) -> FrameBudgetController:
+        with self._lock:
+            now = time.time()
+            for key in [k for k, c in self._controllers.items() if now - c.last_report > IDLE_SECONDS]:
+                del self._controllers[key]
+            return self._controllers.setdefault((session_id, filename), FrameBudgetController())
+
+    def peek(self, session_id: str, filename: str) -> Optional[FrameBudgetController]:
+        """The controller if the viewer has reported for this file, without creating one."""
+        with self._lock:
+            return self._controllers.get((session_id, filename))
+
+    def reset(self, session_id: str, filename: str):
+        with self._lock:
+            self._controllers.pop((session_id, filename), None)
+
+
+frame_budgets = FrameBudgetRegistry()
@@ -0,0 +1,99 @@
+"""
+Coordinate hand-off to genome browsers.
+The viewed genomic interval can be opened in the UCSC Genome Browser, igv.js
+or a running IGV desktop (its batch port), or written as an IGV batch script.
+The other way round, UCSC-style region strings such as
+"chr2:136,545,410-136,617,158" are parsed to navigate the ARG. Browsers use
+1-based inclusive coordinates; tree sequences use 0-based half-open ones, and
+`offset` is the genome position of tree sequence position 0 for ARGs of a
+region rather than a whole chromosome.
+"""
+
+import re
+from typing import Any, Dict, Optional
+from urllib.parse import urlencode
+
+DEFAULT_ASSEMBLY = "hg38"
+IGV_BATCH_PORT = 60151
+
+UCSC_URL = "https://genome.ucsc.edu/cgi-bin/hgTracks"
+IGV_WEB_URL = "https://igv.org/app/"
+
+UNIT_SCALES = {"": 1, "bp": 1, "kb": 1_000, "mb": 1_000_000}
+
+_REGION_PATTERN = re.compile(
+    r"^\s*(?:(?P<contig>[^\s:]+):)?\s*(?P<start>[\d.]+)\s*(?P<start_unit>bp|kb|mb)?"
+    r"(?:\s*[-–]\s*(?P<end>[\d.]+)\s*(?P<end_unit>bp|kb|mb)?)?\s*$",
+    re.IGNORECASE,
+)
+
+
+def _to_bases(number: str, unit: Optional[str]) -> int:
+    value = float(number) * UNIT_SCALES[(unit or "").lower()]
+    if value != int(value):
+        raise ValueError(f"'{number}{unit or ''}' is not a whole number of bases")
+    return int(value)
+
+
+def parse_region(text: str) -> Dict[str, Any]:
+    """Parse "chr:start-end" (1-based inclusive; commas, bp/kb/Mb allowed) to 0-based half-open."""
+    match = _REGION_PATTERN.match(text.replace(",", "").replace("_", ""))
+    if not match:
+        raise ValueError(f"Could not read '{text}' as a region; expected e.g. chr2:136,545,410-136,617,158")
+    # A single unit applies to both ends ("1.5-2Mb")
+    start = _to_bases(match["start"], match["start_unit"] or match["end_unit"])
+    end = _to_bases(match["end"], match["end_unit"] or match["start_unit"]) if match["end"] else start
+    if start < 1 or end < start:
+        raise ValueError("Regions must satisfy 1 <= start <= end")
+    return {"contig": match["contig"], "start": start - 1, "end": end}
+
+
+def format_region(contig: str, start: float, end: float, separators: bool = True) -> str:
+    """Format a 0-based half-open interval as a 1-based UCSC-style region string."""
+    first, last = int(start) + 1, max(int(start) + 1, int(round(end)))
+    if separators:
+        return f"{contig}:{first:,}-{last:,}"
+    return f"{contig}:{first}-{last}"
+
+
+def region_to_ts_interval(
+    region: Dict[str, Any],
+    sequence_length: float,
+    offset: int = 0,
+    contig: Optional[str] = None
+) -> Dict[str, Any]:
+    """Map a parsed region onto the tree sequence, clamped to its extent."""
+    if contig and region["contig"] and region["contig"] != contig:
+        raise ValueError(f"Region is on {region['contig']}, but this ARG is on {contig}")
+    start, end = region["start"] - offset, region["end"] - offset
+    if end <= 0 or start >= sequence_length:
+        raise ValueError(
+            f"Region is outside this ARG, which covers "
+            f"{format_region(contig or region['contig'] or '', offset, offset + sequence_length)}"
+        )
+    clamped = (max(0, start), min(sequence_length, end))
+    return {"start": clamped[0], "end": clamped[1], "clamped": clamped != (start, end)}
+
+
+def browser_links(contig: str, start: float, end: float, assembly: str = DEFAULT_ASSEMBLY) -> Dict[str, str]:
+    locus = format_region(contig, start, end, separators=False)
+    return {
+        "ucsc": f"{UCSC_URL}?{urlencode({'db': assembly, 'position': locus})}",
+        "igv_web": f"{IGV_WEB_URL}?{urlencode({'genome': assembly, 'locus': locus})}",
+        # Only works while IGV desktop is running with its batch port enabled
+        "igv_desktop": f"http://localhost:{IGV_BATCH_PORT}/goto?{urlencode({'locus': locus})}",
+    }
+
+
+def igv_batch_script(
+    contig: str,
+    start: float,
+    end: float,
+    assembly: str = DEFAULT_ASSEMBLY,
+    snapshot_directory: Optional[str] = None
+) -> str:
+    """IGV batch commands that load the assembly and go to the interval (and optionally snapshot it)."""
+    lines = ["new", f"genome {assembly}", f"goto {format_region(contig, start, end, separators=False)}"]
+    if snapshot_directory:
+        lines += [f"snapshotDirectory {snapshot_directory}", "snapshot"]
+    return "\n".join(lines) + "\n"
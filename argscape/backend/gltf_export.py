@@ -0,0 +1,343 @@
+"""
+glTF and OBJ export of 3D ARG layouts.
+Writes the layout as a self-contained glTF 2.0 file (.gltf, JSON with the
+binary buffer embedded as a data URI) that Blender, three.js and most web
+viewers open directly. glTF is Y-up, so time runs along +Y and the layout's
+x/y plane becomes glTF's x/-z plane. Nodes are colored by population with the
+tree sequence's palette.
+
+Two geometries are available: "lines" (points and line segments, small and
+quick to load) and "mesh" (a sphere per node and a tube per edge, which is
+what renderers such as Blender or Cycles need to show anything). In glTF the
+sphere mesh is shared per population and instanced by scene nodes; OBJ has no
+instancing, so each node's sphere is written out, with colors as per-vertex
+RGB (read by Blender, MeshLab and three.js).
+"""
+
+import base64
+import io
+import json
+import math
+from typing import Any, Dict, List, Optional
+
+import numpy as np
+import tskit
+
+from argscape.backend.palettes import resolve_palette
+
+GLTF_COMPONENT_FLOAT = 5126
+GLTF_COMPONENT_UINT32 = 5125
+GLTF_TARGET_ARRAY_BUFFER = 34962
+GLTF_TARGET_ELEMENT_ARRAY_BUFFER = 34963
+GLTF_MODE_POINTS = 0
+GLTF_MODE_LINES = 1
+GLTF_MODE_TRIANGLES = 4
+
+MESH_EXPORT_FORMATS = ("gltf", "obj")
+GEOMETRY_MODES = ("lines", "mesh")
+
+DEFAULT_TIME_HEIGHT = 1.5
+UNASSIGNED_COLOR = "#95a5a6"
+EDGE_COLOR = "#888888"
+DEFAULT_NODE_RADIUS = 0.012
+DEFAULT_EDGE_RADIUS = 0.003
+SPHERE_SEGMENTS = 12
+SPHERE_RINGS = 8
+TUBE_SIDES = 6
+
+
+def _rgb(color: str) -> List[float]:
+    color = color.lstrip("#")
+    return [int(color[i:i + 2], 16) / 255 for i in (0, 2, 4)]
+
+
+def to_gltf_space(position: List[float], time_height: float = DEFAULT_TIME_HEIGHT) -> List[float]:
+    """Layout [x, y, time] -> glTF [x, time, -y]."""
+    x, y, z = position[:3]
+    return [float(x), float(z) * time_height, -float(y)]
+
+
+def node_colors(ts: tskit.TreeSequence, node_ids: List[int], palette_choice: Optional[Dict[str, Any]] = None) -> np.ndarray:
+    palette = resolve_palette(ts, palette_choice)
+    colors = {p["id"]: _rgb(p["color"]) for p in palette["populations"]}
+    population = ts.tables.nodes.population
+    return np.array([colors.get(int(population[u]), _rgb(UNASSIGNED_COLOR)) for u in node_ids], dtype=np.float32)
+
+
+def sphere_mesh(segments: int = SPHERE_SEGMENTS, rings: int = SPHERE_RINGS):
+    """Unit UV sphere: (vertices, normals, triangle indices)."""
+    theta = np.linspace(0, math.pi, rings + 1)[:, None]
+    phi = np.linspace(0, 2 * math.pi, segments + 1)[None, :]
+    vertices = np.stack([
+        np.sin(theta) * np.cos(phi),
+        np.cos(theta) * np.ones_like(phi),
+        np.sin(theta) * np.sin(phi),
+    ], axis=-1).reshape(-1, 3).astype(np.float32)
+    triangles = []
+    for r in range(rings):
+        for s in range(segments):
+            a = r * (segments + 1) + s
+            b = a + segments + 1
+            # Counter-clockwise seen from outside, so normals face out
+            if r != 0:
+                triangles.append((a, a + 1, b))
+            if r != rings - 1:
+                triangles.append((a + 1, b + 1, b))
+    return vertices, vertices.copy(), np.array(triangles, dtype=np.uint32)
+
+
+def tube_geometry(
+    starts: np.ndarray,
+    ends: np.ndarray,
+    start_colors: np.ndarray,
+    end_colors: np.ndarray,
+    radius: float = DEFAULT_EDGE_RADIUS,
+    sides: int = TUBE_SIDES
+):
+    """Open cylinders from starts[i] to ends[i], merged into one mesh.
+
+    Returns (vertices, normals, colors, triangle indices); colors run from the
+    start color to the end color along each tube.
+    """
+    direction = ends - starts
+    direction /= np.maximum(np.linalg.norm(direction, axis=1), 1e-12)[:, None]
+    # Any vector not parallel to the tube gives a perpendicular frame
+    helper = np.where(np.abs(direction[:, 1:2]) > 0.9, [[1.0, 0.0, 0.0]], [[0.0, 1.0, 0.0]])
+    u = np.cross(direction, helper)
+    u /= np.maximum(np.linalg.norm(u, axis=1), 1e-12)[:, None]
+    v = np.cross(direction, u)
+    angles = np.linspace(0, 2 * math.pi, sides, endpoint=False)
+    ring = np.cos(angles)[None, :, None] * u[:, None, :] + np.sin(angles)[None, :, None] * v[:, None, :]
+
+    # Per tube: `sides` vertices around the start, then `sides` around the end
+    vertices = np.concatenate([starts[:, None, :] + radius * ring, ends[:, None, :] + radius * ring], axis=1)
+    normals = np.concatenate([ring, ring], axis=1)
+    colors = np.concatenate([
+        np.repeat(start_colors[:, None, :], sides, axis=1),
+        np.repeat(end_colors[:, None, :], sides, axis=1),
+    ], axis=1)
+
+    side = np.arange(sides)
+    following = (side + 1) % sides
+    quad = np.stack([side, following, side + sides, following, following + sides, side + sides], axis=1).reshape(-1)
+    offsets = np.arange(len(starts))[:, None] * (2 * sides)
+    indices = (offsets + quad[None, :]).reshape(-1)
+    return (
+        vertices.reshape(-1, 3).astype(np.float32),
+        normals.reshape(-1, 3).astype(np.float32),
+        colors.reshape(-1, 3).astype(np.float32),
+        indices.astype(np.uint32),
+    )
+
+
+def _edge_pairs(ts: tskit.TreeSequence, index: Dict[int, int]) -> np.ndarray:
+    """(parent, child) vertex indices, one per connected pair however many intervals join them."""
+    pairs = {(index[int(p)], index[int(c)]) for p, c in zip(ts.edges_parent, ts.edges_child) if int(p) in index and int(c) in index}
+    return np.array(sorted(pairs), dtype=np.int64).reshape(-1, 2)
+
+
+class GltfBuilder:
+    """Accumulates buffer views and accessors into a single embedded buffer."""
+
+    def __init__(self):
+        self.data = bytearray()
+        self.document: Dict[str, Any] = {
+            "asset": {"version": "2.0", "generator": "ARGscape"},
+            "scene": 0,
+            "scenes": [{"nodes": []}],
+            "nodes": [],
+            "meshes": [],
+            "materials": [],
+            "accessors": [],
+            "bufferViews": [],
+        }
+
+    def add_accessor(self, array: np.ndarray, accessor_type: str, target: int) -> int:
+        """Append an array to the buffer; returns the accessor index."""
+        while len(self.data) % 4:
+            self.data.append(0)
+        raw = array.tobytes()
+        self.document["bufferViews"].append({"buffer": 0, "byteOffset": len(self.data), "byteLength": len(raw), "target": target})
+        self.data.extend(raw)
+        accessor = {
+            "bufferView": len(self.document["bufferViews"]) - 1,
+            "componentType": GLTF_COMPONENT_UINT32 if array.dtype == np.uint32 else GLTF_COMPONENT_FLOAT,
+            "count": int(array.shape[0]) if accessor_type != "SCALAR" else int(array.size),
+            "type": accessor_type,
+        }
+        if accessor_type == "VEC3" and array.dtype == np.float32 and len(array):
+            accessor["min"] = [float(v) for v in array.min(axis=0)]
+            accessor["max"] = [float(v) for v in array.max(axis=0)]
+        self.document["accessors"].append(accessor)
+        return len(self.document["accessors"]) - 1
+
+    def add_material(self, name: str, color: Optional[List[float]] = None, unlit: bool = False) -> int:
+        material: Dict[str, Any] = {"name": name, "pbrMetallicRoughness": {"baseColorFactor": (color or [1, 1, 1]) + [1.0], "metallicFactor": 0.0, "roughnessFactor": 0.8}}
+        if unlit:
+            material["extensions"] = {"KHR_materials_unlit": {}}
+            self.document.setdefault("extensionsUsed", [])
+            if "KHR_materials_unlit" not in self.document["extensionsUsed"]:
+                self.document["extensionsUsed"].append("KHR_materials_unlit")
+        self.document["materials"].append(material)
+        return len(self.document["materials"]) - 1
+
+    def add_mesh(self, name: str, primitives: List[Dict[str, Any]], create_node: bool = True) -> int:
+        self.document["meshes"].append({"name": name, "primitives": primitives})
+        mesh = len(self.document["meshes"]) - 1
+        if create_node:
+            self.add_node(name, mesh)
+        return mesh
+
+    def add_node(self, name: str, mesh: int, translation: Optional[List[float]] = None, scale: Optional[float] = None) -> int:
+        node: Dict[str, Any] = {"name": name, "mesh": mesh}
+        if translation is not None:
+            node["translation"] = translation
+        if scale is not None:
+            node["scale"] = [scale, scale, scale]
+        self.document["nodes"].append(node)
+        self.document["scenes"][0]["nodes"].append(len(self.document["nodes"]) - 1)
+        return len(self.document["nodes"]) - 1
+
+    def to_gltf(self) -> bytes:
+        encoded = base64.b64encode(bytes(self.data)).decode("ascii")
+        document = {**self.document, "buffers": [{"byteLength": len(self.data), "uri": f"data:application/octet-stream;base64,{encoded}"}]}
+        return json.dumps(document, separators=(",", ":")).encode("utf-8")
+
+
+def _prepare(ts: tskit.TreeSequence, positions: Dict[int, List[float]], palette_choice: Optional[Dict[str, Any]], time_height: float):
+    node_ids = sorted(int(u) for u in positions)
+    if not node_ids:
+        raise ValueError("No positions to export")
+    index = {u: i for i, u in enumerate(node_ids)}
+    vertices = np.array([to_gltf_space(positions[u], time_height) for u in node_ids], dtype=np.float32)
+    return node_ids, vertices, node_colors(ts, node_ids, palette_choice), _edge_pairs(ts, index)
+
+
+def layout_to_gltf(
+    ts: tskit.TreeSequence,
+    positions: Dict[int, List[float]],
+    palette_choice: Optional[Dict[str, Any]] = None,
+    time_height: float = DEFAULT_TIME_HEIGHT,
+    geometry: str = "lines",
+    node_radius: float = DEFAULT_NODE_RADIUS,
+    edge_radius: float = DEFAULT_EDGE_RADIUS
+) -> bytes:
+    """The layout as an embedded .gltf file, as points and lines or as spheres and tubes."""
+    if geometry not in GEOMETRY_MODES:
+        raise ValueError(f"geometry must be one of {GEOMETRY_MODES}")
+    node_ids, vertices, colors, pairs = _prepare(ts, positions, palette_choice, time_height)
+    if geometry == "mesh":
+        return _mesh_gltf(ts, node_ids, vertices, colors, pairs, palette_choice, node_radius, edge_radius)
+    lines = pairs.astype(np.uint32).reshape(-1)
+
+    builder = GltfBuilder()
+    position_accessor = builder.add_accessor(vertices, "VEC3", GLTF_TARGET_ARRAY_BUFFER)
+    color_accessor = builder.add_accessor(colors, "VEC3", GLTF_TARGET_ARRAY_BUFFER)
+    builder.add_mesh("nodes", [{
+        "attributes": {"POSITION": position_accessor, "COLOR_0": color_accessor},
+        "mode": GLTF_MODE_POINTS,
+        "material": builder.add_material("nodes", unlit=True),
+    }])
+    if len(lines):
+        builder.add_mesh("edges", [{
+            "attributes": {"POSITION": position_accessor},
+            "indices": builder.add_accessor(lines, "SCALAR", GLTF_TARGET_ELEMENT_ARRAY_BUFFER),
+            "mode": GLTF_MODE_LINES,
+            "material": builder.add_material("edges", _rgb(EDGE_COLOR), unlit=True),
+        }])
+    return builder.to_gltf()
+
+
+def _mesh_gltf(
+    ts: tskit.TreeSequence,
+    node_ids: List[int],
+    vertices: np.ndarray,
+    colors: np.ndarray,
+    pairs: np.ndarray,
+    palette_choice: Optional[Dict[str, Any]],
+    node_radius: float,
+    edge_radius: float
+) -> bytes:
+    builder = GltfBuilder()
+    sphere_vertices, sphere_normals, sphere_triangles = sphere_mesh()
+    sphere_attributes = {
+        "POSITION": builder.add_accessor(sphere_vertices, "VEC3", GLTF_TARGET_ARRAY_BUFFER),
+        "NORMAL": builder.add_accessor(sphere_normals, "VEC3", GLTF_TARGET_ARRAY_BUFFER),
+    }
+    sphere_indices = builder.add_accessor(sphere_triangles.reshape(-1), "SCALAR", GLTF_TARGET_ELEMENT_ARRAY_BUFFER)
+
+    # One sphere mesh per population color, instanced by a scene node per ARG node
+    palette = resolve_palette(ts, palette_choice)
+    names = {p["id"]: p["name"] for p in palette["populations"]}
+    population = ts.tables.nodes.population
+    meshes: Dict[int, int] = {}
+    for i, u in enumerate(node_ids):
+        pop = int(population[u])
+        if pop not in meshes:
+            material = builder.add_material(f"population {names.get(pop, 'unassigned')}", [float(c) for c in colors[i]])
+            meshes[pop] = builder.add_mesh(
+                f"node sphere {names.get(pop, 'unassigned')}",
+                [{"attributes": sphere_attributes, "indices": sphere_indices, "mode": GLTF_MODE_TRIANGLES, "material": material}],
+                create_node=False
+            )
+        builder.add_node(f"node {u}", meshes[pop], [float(v) for v in vertices[i]], node_radius)
+
+    if len(pairs):
+        tube_vertices, tube_normals, tube_colors, tube_indices = tube_geometry(
+            vertices[pairs[:, 0]].astype(float), vertices[pairs[:, 1]].astype(float),
+            colors[pairs[:, 0]], colors[pairs[:, 1]], edge_radius
+        )
+        builder.add_mesh("edges", [{
+            "attributes": {
+                "POSITION": builder.add_accessor(tube_vertices, "VEC3", GLTF_TARGET_ARRAY_BUFFER),
+                "NORMAL": builder.add_accessor(tube_normals, "VEC3", GLTF_TARGET_ARRAY_BUFFER),
+                "COLOR_0": builder.add_accessor(tube_colors, "VEC3", GLTF_TARGET_ARRAY_BUFFER),
+            },
+            "indices": builder.add_accessor(tube_indices, "SCALAR", GLTF_TARGET_ELEMENT_ARRAY_BUFFER),
+            "mode": GLTF_MODE_TRIANGLES,
+            "material": builder.add_material("edges"),
+        }])
+    return builder.to_gltf()
+
+
+def layout_to_obj(
+    ts: tskit.TreeSequence,
+    positions: Dict[int, List[float]],
+    palette_choice: Optional[Dict[str, Any]] = None,
+    time_height: float = DEFAULT_TIME_HEIGHT,
+    node_radius: float = DEFAULT_NODE_RADIUS,
+    edge_radius: float = DEFAULT_EDGE_RADIUS
+) -> bytes:
+    """Spheres and tubes as a Wavefront OBJ with per-vertex colors, in the same Y-up space as glTF."""
+    node_ids, vertices, colors, pairs = _prepare(ts, positions, palette_choice, time_height)
+    sphere_vertices, sphere_normals, sphere_triangles = sphere_mesh()
+    out = io.StringIO()
+    out.write("# ARGscape 3D ARG layout\n")
+    offset = 1  # OBJ indices are 1-based and shared by v and vn here
+
+    def write_group(name: str, group_vertices: np.ndarray, group_normals: np.ndarray, group_colors: np.ndarray, triangles: np.ndarray):
+        nonlocal offset
+        out.write(f"o {name}\n")
+        for (x, y, z), (r, g, b) in zip(group_vertices, group_colors):
+            out.write(f"v {x:.6f} {y:.6f} {z:.6f} {r:.4f} {g:.4f} {b:.4f}\n")
+        for x, y, z in group_normals:
+            out.write(f"vn {x:.4f} {y:.4f} {z:.4f}\n")
+        for a, b, c in triangles.astype(np.int64) + offset:
+            out.write(f"f {a}//{a} {b}//{b} {c}//{c}\n")
+        offset += len(group_vertices)
+
+    for i, u in enumerate(node_ids):
+        write_group(
+            f"node_{u}",
+            sphere_vertices * node_radius + vertices[i],
+            sphere_normals,
+            np.repeat(colors[i:i + 1], len(sphere_vertices), axis=0),
+            sphere_triangles
+        )
+    if len(pairs):
+        tube_vertices, tube_normals, tube_colors, tube_indices = tube_geometry(
+            vertices[pairs[:, 0]].astype(float), vertices[pairs[:, 1]].astype(float),
+            colors[pairs[:, 0]], colors[pairs[:, 1]], edge_radius
+        )
+        write_group("edges", tube_vertices, tube_normals, tube_colors, tube_indices.reshape(-1, 3))
+    return out.getvalue().encode("utf-8")
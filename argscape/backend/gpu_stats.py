@@ -0,0 +1,290 @@
+"""
+GPU (wgpu) path for windowed branch statistics.
+Branch-mode diversity, and the mean TMRCA that is half of it, add up every
+branch's length times the fraction of sample pairs it separates, over the
+span where that branch has the same parent and sample count. The edge diffs
+are walked once per sample set to list those branch segments (cached, so
+re-windowing on zoom only reruns the kernel), and a compute shader then sums
+the segments overlapping each window in parallel. The GPU works in 32-bit
+floats, so positions are split into high and low parts and sums are
+compensated; every run is cross-checked against tskit on a sample of
+windows, and any mismatch, missing wgpu or missing adapter falls back to
+tskit's exact CPU result with the reason reported.
+"""
+
+import logging
+import time
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.hardware import get_gpu_info
+from argscape.backend.ts_cache import TreeSequenceCache
+
+logger = logging.getLogger(__name__)
+
+ACCELERATORS = ("cpu", "auto", "gpu")
+GPU_STATISTICS = ("diversity", "tmrca")
+# "auto" only uses the GPU when there are enough trees for the transfer to pay off
+AUTO_MIN_TREES = 10000
+# Segments summed by one shader invocation, so a single wide window still runs in parallel
+SEGMENTS_PER_INVOCATION = 4096
+WORKGROUP_SIZE = 64
+# Longer segments are split so that the segments overlapping a window form a short sorted range
+SPLIT_FRACTION = 1 / 1024
+CHECK_WINDOWS = 8
+CHECK_RTOL = 1e-4
+MAX_CACHED_SEGMENTS = 8
+
+SHADER = f"""
+@group(0) @binding(0) var<storage, read_write> segment_positions: array<vec4<f32>>;
+@group(0) @binding(1) var<storage, read_write> segment_weights: array<f32>;
+@group(0) @binding(2) var<storage, read_write> item_bounds: array<vec4<f32>>;
+@group(0) @binding(3) var<storage, read_write> item_ranges: array<vec2<u32>>;
+@group(0) @binding(4) var<storage, read_write> totals: array<f32>;
+
+// a - b for positions stored as (high, low) float pairs
+fn difference(a: vec2<f32>, b: vec2<f32>) -> f32 {{
+    return (a.x - b.x) + (a.y - b.y);
+}}
+
+@compute @workgroup_size({WORKGROUP_SIZE})
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {{
+    let item = id.x;
+    if (item >= arrayLength(&item_ranges)) {{
+        return;
+    }}
+    let bounds = item_bounds[item];
+    var sum = 0.0;
+    var compensation = 0.0;
+    for (var i = item_ranges[item].x; i < item_ranges[item].y; i = i + 1u) {{
+        let segment = segment_positions[i];
+        // min(right) - max(left) as the smallest of the four right - left differences
+        let overlap = min(
+            min(difference(segment.zw, segment.xy), difference(segment.zw, bounds.xy)),
+            min(difference(bounds.zw, segment.xy), difference(bounds.zw, bounds.xy))
+        );
+        if (overlap > 0.0) {{
+            let y = segment_weights[i] * overlap - compensation;
+            let t = sum + y;
+            compensation = (t - sum) - y;
+            sum = t;
+        }}
+    }}
+    totals[item] = sum;
+}}
+"""
+
+_cache = TreeSequenceCache("branch_segments", MAX_CACHED_SEGMENTS)
+
+
+def branch_segments(ts: tskit.TreeSequence, samples: Sequence[int]) -> Dict[str, np.ndarray]:
+    """Spans over which each branch has a fixed length and sample count, weighted by the pairs it separates.
+
+    Returns left, right and weight arrays sorted by left, where weight is the
+    branch length times 2x(n - x) / (n(n - 1)) for x of the n samples below it.
+    """
+    samples = np.asarray(samples, dtype=np.int64)
+    cached = _cache.get(ts, samples.tobytes())
+    if cached is not None:
+        return cached
+
+    n = len(samples)
+    if n < 2:
+        raise ValueError("Branch diversity needs at least two samples in each set")
+    scale = 2.0 / (n * (n - 1))
+    times = ts.nodes_time
+    parent = np.full(ts.num_nodes, tskit.NULL, dtype=np.int64)
+    count = np.zeros(ts.num_nodes, dtype=np.int64)
+    count[samples] = 1
+    start = np.zeros(ts.num_nodes)
+    lefts: List[float] = []
+    rights: List[float] = []
+    weights: List[float] = []
+
+    def close(u: int, position: float):
+        p = parent[u]
+        if p != tskit.NULL and position > start[u] and 0 < count[u] < n:
+            lefts.append(start[u])
+            rights.append(position)
+            weights.append((times[p] - times[u]) * count[u] * (n - count[u]) * scale)
+        start[u] = position
+
+    def propagate(u: int, delta: int, position: float):
+        # Every ancestor's sample count, and so its segment, changes
+        while u != tskit.NULL:
+            close(u, position)
+            count[u] += delta
+            u = parent[u]
+
+    for (left, _), edges_out, edges_in in ts.edge_diffs():
+        for edge in edges_out:
+            close(edge.child, left)
+            if count[edge.child]:
+                propagate(edge.parent, -count[edge.child], left)
+            parent[edge.child] = tskit.NULL
+        for edge in edges_in:
+            start[edge.child] = left
+            parent[edge.child] = edge.parent
+            if count[edge.child]:
+                propagate(edge.parent, count[edge.child], left)
+    for u in np.flatnonzero(parent != tskit.NULL):
+        close(int(u), ts.sequence_length)
+
+    left, right, weight = np.asarray(lefts), np.asarray(rights), np.asarray(weights)
+    order = np.argsort(left, kind="stable")
+    segments = {"left": left[order], "right": right[order], "weight": weight[order]}
+    _cache.put(ts, segments, samples.tobytes())
+    logger.info(f"Built {len(left)} branch segments for {n} samples over {ts.num_trees} trees")
+    return segments
+
+
+def _split_long(segments: Dict[str, np.ndarray], max_length: float) -> Dict[str, np.ndarray]:
+    """Cut segments longer than max_length into equal pieces; sums over windows are unchanged."""
+    left, right, weight = segments["left"], segments["right"], segments["weight"]
+    pieces = np.maximum(np.ceil((right - left) / max_length).astype(np.int64), 1)
+    if pieces.max(initial=1) == 1:
+        return segments
+    index = np.repeat(np.arange(len(left)), pieces)
+    part = np.arange(pieces.sum()) - np.repeat(np.cumsum(pieces) - pieces, pieces)
+    length = (right - left)[index] / pieces[index]
+    split_left = left[index] + part * length
+    # The last piece ends exactly on the segment's right
+    split_right = np.where(part == pieces[index] - 1, right[index], split_left + length)
+    order = np.argsort(split_left, kind="stable")
+    return {"left": split_left[order], "right": split_right[order], "weight": weight[index][order]}
+
+
+def _split_float(positions: np.ndarray) -> Tuple[np.ndarray, np.ndarray]:
+    high = positions.astype(np.float32)
+    return high, (positions - high.astype(np.float64)).astype(np.float32)
+
+
+def _run_kernel(segments: Dict[str, np.ndarray], windows: np.ndarray) -> np.ndarray:
+    """Sum of weight x overlap for each window, on the GPU; result in float64."""
+    from wgpu.utils.compute import compute_with_buffers
+
+    left, right = segments["left"], segments["right"]
+    max_length = float((right - left).max(initial=0.0))
+    lefts, rights = windows[:-1], windows[1:]
+    # Segments are sorted by left and no longer than max_length, so these ranges hold every overlap
+    first = np.searchsorted(left, lefts - max_length, side="left")
+    last = np.searchsorted(left, rights, side="left")
+    chunks = np.maximum(np.ceil((last - first) / SEGMENTS_PER_INVOCATION).astype(np.int64), 1)
+    item_window = np.repeat(np.arange(len(lefts)), chunks)
+    offset = (np.arange(chunks.sum()) - np.repeat(np.cumsum(chunks) - chunks, chunks)) * SEGMENTS_PER_INVOCATION
+    item_first = first[item_window] + offset
+    item_last = np.minimum(item_first + SEGMENTS_PER_INVOCATION, last[item_window])
+
+    segment_positions = np.column_stack([*_split_float(left), *_split_float(right)]).astype(np.float32)
+    item_bounds = np.column_stack([*_split_float(lefts[item_window]), *_split_float(rights[item_window])]).astype(np.float32)
+    item_ranges = np.column_stack([item_first, np.maximum(item_last, item_first)]).astype(np.uint32)
+    num_items = len(item_window)
+    # A dummy segment keeps the buffers non-empty when there are no segments
+    if len(left) == 0:
+        segment_positions = np.zeros((1, 4), dtype=np.float32)
+    out = compute_with_buffers(
+        input_arrays={
+            0: segment_positions,
+            1: segments["weight"].astype(np.float32) if len(left) else np.zeros(1, dtype=np.float32),
+            2: item_bounds,
+            3: item_ranges,
+        },
+        output_arrays={4: (num_items, "f")},
+        shader=SHADER,
+        n=(int(np.ceil(num_items / WORKGROUP_SIZE)), 1, 1),
+    )
+    partial = np.frombuffer(out[4], dtype=np.float32).astype(np.float64)
+    totals = np.zeros(len(lefts))
+    np.add.at(totals, item_window, partial)
+    return totals
+
+
+def _cpu_values(ts: tskit.TreeSequence, statistic: str, windows: np.ndarray, sample_sets: List[List[int]]) -> np.ndarray:
+    values = np.asarray(ts.diversity(sample_sets, windows=windows, mode="branch"), dtype=float)
+    return values / 2 if statistic == "tmrca" else values
+
+
+def _cross_check(
+    ts: tskit.TreeSequence,
+    statistic: str,
+    windows: np.ndarray,
+    sample_sets: List[List[int]],
+    values: np.ndarray
+) -> Dict[str, Any]:
+    """Compare GPU values with tskit on a few windows, computed on just those parts of the genome."""
+    num_windows = len(windows) - 1
+    rng = np.random.default_rng(num_windows)
+    chosen = np.sort(rng.choice(num_windows, size=min(CHECK_WINDOWS, num_windows), replace=False))
+    intervals = np.column_stack([windows[chosen], windows[chosen + 1]])
+    trimmed = ts.keep_intervals(intervals, simplify=False)
+    check_windows = np.unique(np.concatenate([[0.0, ts.sequence_length], intervals.ravel()]))
+    expected_all = _cpu_values(trimmed, statistic, check_windows, sample_sets).reshape(len(check_windows) - 1, len(sample_sets))
+    expected = expected_all[np.searchsorted(check_windows, windows[chosen])]
+    observed = values[chosen]
+    scale = np.maximum(np.abs(expected), np.abs(expected).max(initial=0.0) * 1e-6)
+    with np.errstate(divide="ignore", invalid="ignore"):
+        errors = np.where(scale > 0, np.abs(observed - expected) / scale, 0.0)
+    max_error = float(np.nanmax(errors)) if errors.size else 0.0
+    return {
+        "windows": [int(w) for w in chosen],
+        "max_relative_error": max_error,
+        "tolerance": CHECK_RTOL,
+        "passed": bool(max_error <= CHECK_RTOL),
+    }
+
+
+def windowed_branch_values(
+    ts: tskit.TreeSequence,
+    statistic: str,
+    windows: np.ndarray,
+    sample_sets: List[List[int]],
+    accelerator: str = "auto"
+) -> Tuple[np.ndarray, Dict[str, Any]]:
+    """Branch diversity or mean TMRCA per window and sample set, on the GPU when asked and possible.
+
+    Returns:
+        (values of shape (num_windows, num_sets), report of which path ran and why)
+    """
+    if accelerator not in ACCELERATORS:
+        raise ValueError(f"Unknown accelerator '{accelerator}'. Expected one of {ACCELERATORS}")
+    report: Dict[str, Any] = {"requested": accelerator, "used": "cpu", "reason": None, "device": None, "check": None, "seconds": {}}
+    gpu = get_gpu_info()
+    if accelerator == "cpu":
+        reason = None
+    elif statistic not in GPU_STATISTICS:
+        reason = f"The GPU path covers branch-mode {', '.join(GPU_STATISTICS)}"
+    elif not gpu["available"]:
+        reason = gpu["reason"]
+    elif accelerator == "auto" and ts.num_trees < AUTO_MIN_TREES:
+        reason = f"Fewer than {AUTO_MIN_TREES} trees; the CPU is faster"
+    else:
+        reason = None
+        start = time.perf_counter()
+        try:
+            segments = [_split_long(branch_segments(ts, samples), ts.sequence_length * SPLIT_FRACTION) for samples in sample_sets]
+            report["seconds"]["segments"] = time.perf_counter() - start
+            start = time.perf_counter()
+            lengths = np.diff(windows)
+            values = np.column_stack([_run_kernel(s, windows) / lengths for s in segments])
+            if statistic == "tmrca":
+                values /= 2
+            report["seconds"]["kernel"] = time.perf_counter() - start
+            start = time.perf_counter()
+            report["check"] = _cross_check(ts, statistic, windows, sample_sets, values)
+            report["seconds"]["check"] = time.perf_counter() - start
+        except Exception as e:
+            logger.warning(f"GPU {statistic} failed, using the CPU: {e}")
+            reason = f"GPU computation failed: {e}"
+        else:
+            if report["check"]["passed"]:
+                report.update({"used": "gpu", "device": gpu.get("device")})
+                return values, report
+            logger.warning(f"GPU {statistic} differed from tskit by {report['check']['max_relative_error']:.2e}; using the CPU")
+            reason = "GPU values did not match tskit on the cross-check"
+    report["reason"] = reason
+    start = time.perf_counter()
+    values = _cpu_values(ts, statistic, windows, sample_sets).reshape(len(windows) - 1, len(sample_sets))
+    report["seconds"]["cpu"] = time.perf_counter() - start
+    return values, report
@@ -5,14 +5,19 @@ Graph data conversion utilities for ARG visualization
 
 import logging
 import math
-from typing import Dict, Any, List, Tuple, Optional, Union
+from typing import Dict, Any, Iterator, List, Tuple, Optional, Union
 
 import numpy as np
 import tskit
 from argscape.backend.geo_utils import check_spatial_completeness
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.timing import timed
 
 logger = logging.getLogger(__name__)
 
+# Node metadata up to this size is sent with the graph; larger values are loaded on demand
+INLINE_METADATA_MAX_BYTES = 1024
+
 
 def get_tree_intervals(ts: tskit.TreeSequence) -> List[Tuple[int, float, float]]:
     """Get tree intervals as (tree_index, left, right) tuples."""
@@ -252,6 +257,34 @@ def filter_by_tree_indices(ts: tskit.TreeSequence, start_tree_idx: int, end_tree
     return filtered_ts, expected_tree_count
 
 
+def build_node_data(ts: tskit.TreeSequence, node: tskit.Node) -> Dict[str, Any]:
+    """Build the D3 node record for a single tree sequence node."""
+    time = node.time
+    log_time = math.log(time + 1e-10) if time > 0 else 0
+    
+    node_data = {
+        'id': node.id,
+        'time': time,
+        'log_time': log_time,
+        'is_sample': node.is_sample(),
+        'individual': node.individual
+    }
+    
+    # Add spatial location if available
+    if node.individual != -1 and node.individual < ts.num_individuals:
+        individual = ts.individual(node.individual)
+        if individual.location is not None and len(individual.location) >= 2:
+            node_data['location'] = {
+                'x': float(individual.location[0]),
+                'y': float(individual.location[1])
+            }
+            if len(individual.location) >= 3 and individual.location[2] != 0:
+                node_data['location']['z'] = float(individual.location[2])
+    
+    return node_data
+
+
+@timed("graph")
 def convert_to_graph_data(ts: tskit.TreeSequence, expected_tree_count: int = None, sample_order: str = "custom") -> Dict[str, Any]:
     """Convert a tskit.TreeSequence to graph data format for D3 visualization.
     
@@ -270,29 +303,7 @@ def convert_to_graph_data(ts: tskit.TreeSequence, expected_tree_count: int = Non
     nodes = []
     for node in ts.nodes():
         if node.is_sample() or node.id in connected_node_ids:
-            time = node.time
-            log_time = math.log(time + 1e-10) if time > 0 else 0
-            
-            node_data = {
-                'id': node.id,
-                'time': time,
-                'log_time': log_time,
-                'is_sample': node.is_sample(),
-                'individual': node.individual
-            }
-            
-            # Add spatial location if available
-            if node.individual != -1 and node.individual < ts.num_individuals:
-                individual = ts.individual(node.individual)
-                if individual.location is not None and len(individual.location) >= 2:
-                    node_data['location'] = {
-                        'x': float(individual.location[0]),
-                        'y': float(individual.location[1])
-                    }
-                    if len(individual.location) >= 3 and individual.location[2] != 0:
-                        node_data['location']['z'] = float(individual.location[2])
-            
-            nodes.append(node_data)
+            nodes.append(build_node_data(ts, node))
     
     edges = [
         {
@@ -392,14 +403,19 @@ def convert_tree_sequence_to_graph_data(
     edges = []
     node_times = []
     
+    # Large metadata is left out and fetched per node from /node-metadata
+    metadata_sizes = np.diff(ts.tables.nodes.metadata_offset)
+    
     # Process nodes
     for node in ts.nodes():
         node_data = {
             "id": str(node.id),
             "time": node.time,
             "is_sample": node.is_sample(),
-            "metadata": node.metadata
+            "metadata_bytes": int(metadata_sizes[node.id])
         }
+        if 0 < metadata_sizes[node.id] <= INLINE_METADATA_MAX_BYTES:
+            node_data["metadata"] = node.metadata
         
         # Add location data if available
         if has_locations and hasattr(node, "location"):
@@ -441,4 +457,263 @@ def convert_tree_sequence_to_graph_data(
             },
             "has_locations": has_locations
         }
-    } 
\ No newline at end of file
+    } 
+
+def get_population_name(ts: tskit.TreeSequence, population_id: int) -> str:
+    """Get a display name for a population, falling back to its ID."""
+    if population_id == tskit.NULL:
+        return "unassigned"
+    metadata = ts.population(population_id).metadata
+    if isinstance(metadata, dict) and metadata.get("name"):
+        return str(metadata["name"])
+    return f"pop_{population_id}"
+
+
+def convert_to_population_graph_data(ts: tskit.TreeSequence, collapse_pure_clades: bool = True) -> Dict[str, Any]:
+    """Collapse samples by population into summary nodes for a population-level ARG view.
+
+    Each population is represented by a single summary node carrying its
+    sample count. With collapse_pure_clades, internal nodes whose descendant
+    samples (anywhere along the genome) all come from one population are folded
+    into that population's summary node as well. Edges between the remaining
+    nodes are merged, keeping the union of their genomic intervals.
+
+    Args:
+        ts: The tree sequence to aggregate
+        collapse_pure_clades: Also fold single-population clades into the summary nodes
+    """
+    logger.info(f"Aggregating tree sequence by population: {ts.num_samples} samples, {ts.num_nodes} nodes")
+
+    node_population = ts.tables.nodes.population
+    node_time = ts.tables.nodes.time
+    sample_ids = ts.samples()
+
+    # Summary node IDs are allocated after the real node IDs
+    population_ids = sorted({int(node_population[u]) for u in sample_ids})
+    summary_id = {pop: ts.num_nodes + i for i, pop in enumerate(population_ids)}
+
+    representative = {int(u): summary_id[int(node_population[u])] for u in sample_ids}
+    collapsed_counts: Dict[int, int] = {pop: 0 for pop in population_ids}
+
+    if collapse_pure_clades:
+        # Propagate descendant-population bitmasks up the ARG in time order
+        pop_bit = {pop: 1 << i for i, pop in enumerate(population_ids)}
+        masks = {int(u): pop_bit[int(node_population[u])] for u in sample_ids}
+        order = np.argsort(node_time[ts.edges_parent], kind="stable")
+        for parent, child in zip(ts.edges_parent[order], ts.edges_child[order]):
+            masks[int(parent)] = masks.get(int(parent), 0) | masks.get(int(child), 0)
+        bit_pop = {bit: pop for pop, bit in pop_bit.items()}
+        for node_id, mask in masks.items():
+            if node_id not in representative and mask in bit_pop:
+                representative[node_id] = summary_id[bit_pop[mask]]
+                collapsed_counts[bit_pop[mask]] += 1
+
+    # Merge edges that map onto the same (parent, child) pair
+    merged_intervals: Dict[Tuple[int, int], List[List[float]]] = {}
+    edge_weights: Dict[Tuple[int, int], int] = {}
+    for edge in ts.edges():
+        parent = representative.get(edge.parent, edge.parent)
+        child = representative.get(edge.child, edge.child)
+        if parent == child:
+            continue
+        key = (parent, child)
+        merged_intervals.setdefault(key, []).append([edge.left, edge.right])
+        edge_weights[key] = edge_weights.get(key, 0) + 1
+
+    edges = []
+    connected_node_ids = set()
+    for (parent, child), intervals in merged_intervals.items():
+        intervals.sort()
+        union = [intervals[0]]
+        for left, right in intervals[1:]:
+            if left <= union[-1][1]:
+                union[-1][1] = max(union[-1][1], right)
+            else:
+                union.append([left, right])
+        for left, right in union:
+            edges.append({
+                'source': parent,
+                'target': child,
+                'left': left,
+                'right': right,
+                'weight': edge_weights[(parent, child)]
+            })
+        connected_node_ids.update([parent, child])
+
+    sample_counts = {pop: 0 for pop in population_ids}
+    sample_min_time = {pop: math.inf for pop in population_ids}
+    for u in sample_ids:
+        pop = int(node_population[u])
+        sample_counts[pop] += 1
+        sample_min_time[pop] = min(sample_min_time[pop], float(node_time[u]))
+
+    nodes = []
+    for order_position, pop in enumerate(population_ids):
+        nodes.append({
+            'id': summary_id[pop],
+            'time': sample_min_time[pop],
+            'log_time': math.log(sample_min_time[pop] + 1e-10) if sample_min_time[pop] > 0 else 0,
+            'is_sample': True,
+            'individual': -1,
+            'is_population_summary': True,
+            'population': pop,
+            'population_name': get_population_name(ts, pop),
+            'sample_count': sample_counts[pop],
+            'collapsed_node_count': collapsed_counts[pop],
+            'order_position': order_position
+        })
+
+    for node_id in sorted(connected_node_ids):
+        if node_id >= ts.num_nodes:
+            continue
+        time = float(node_time[node_id])
+        nodes.append({
+            'id': node_id,
+            'time': time,
+            'log_time': math.log(time + 1e-10) if time > 0 else 0,
+            'is_sample': False,
+            'individual': int(ts.tables.nodes.individual[node_id]),
+            'population': int(node_population[node_id])
+        })
+
+    metadata = {
+        'num_nodes': len(nodes),
+        'num_edges': len(edges),
+        'num_samples': ts.num_samples,
+        'num_populations': len(population_ids),
+        'sequence_length': ts.sequence_length,
+        'genomic_start': 0,
+        'genomic_end': ts.sequence_length,
+        'num_local_trees': ts.num_trees,
+        'original_nodes': ts.num_nodes,
+        'original_edges': ts.num_edges,
+        'collapse_pure_clades': collapse_pure_clades,
+        'is_population_view': True,
+        'tree_intervals': get_tree_intervals(ts)
+    }
+
+    logger.info(f"Population view: {len(nodes)} nodes, {len(edges)} edges from {ts.num_nodes} nodes")
+
+    return {
+        'nodes': nodes,
+        'edges': edges,
+        'metadata': metadata
+    }
+
+
+def iter_graph_data_chunks(
+    ts: tskit.TreeSequence,
+    sample_order: str = "custom",
+    num_time_bands: int = 8,
+    cancel_token: Optional[CancellationToken] = None
+) -> Iterator[Dict[str, Any]]:
+    """Yield graph data progressively so the frontend can render a growing picture.
+
+    Emits a "metadata" record, then a "chunk" of sample nodes, then one "chunk"
+    per time band of internal nodes (youngest first). Each edge is sent with
+    the later of its endpoints' chunks, so it arrives after both of them.
+    A final "complete" record carries the spatial metadata.
+
+    The tree sequence is already fully loaded, and the band of every node and
+    edge is computed from the table columns before the first record. What is
+    progressive is building the per-node records and sending them, which is
+    where the time goes for large graphs.
+    """
+    connected = np.zeros(ts.num_nodes, dtype=bool)
+    connected[ts.edges_parent] = True
+    connected[ts.edges_child] = True
+    node_table = ts.tables.nodes
+    is_sample = (node_table.flags & tskit.NODE_IS_SAMPLE) != 0
+    times = node_table.time
+
+    internal_ids = np.where(connected & ~is_sample)[0]
+    internal_ids = internal_ids[np.argsort(times[internal_ids], kind="stable")]
+    bands = [band for band in np.array_split(internal_ids, max(1, num_time_bands)) if len(band)]
+
+    band_of_node = np.full(ts.num_nodes, -1, dtype=np.int64)
+    for band_index, band in enumerate(bands):
+        band_of_node[band] = band_index + 1
+    band_of_node[is_sample] = 0
+    # Sample parents are in band 0, so the child's band can be the later one
+    edge_bands = np.maximum(band_of_node[ts.edges_parent], band_of_node[ts.edges_child])
+    edges_left = ts.edges_left
+    edges_right = ts.edges_right
+
+    def edges_for_band(band_index: int) -> List[Dict[str, Any]]:
+        return [
+            {
+                'source': int(ts.edges_parent[i]),
+                'target': int(ts.edges_child[i]),
+                'left': float(edges_left[i]),
+                'right': float(edges_right[i])
+            }
+            for i in np.where(edge_bands == band_index)[0]
+        ]
+
+    num_chunks = len(bands) + 1
+    total_nodes = int((connected | is_sample).sum())
+    yield {
+        'type': 'metadata',
+        'metadata': {
+            'num_nodes': total_nodes,
+            'num_edges': ts.num_edges,
+            'num_samples': ts.num_samples,
+            'sequence_length': ts.sequence_length,
+            'genomic_start': 0,
+            'genomic_end': ts.sequence_length,
+            'num_local_trees': ts.num_trees,
+            'tree_intervals': get_tree_intervals(ts),
+            'sample_order': sample_order,
+            'num_chunks': num_chunks
+        }
+    }
+
+    sample_nodes = apply_sample_ordering(
+        [build_node_data(ts, ts.node(int(u))) for u in ts.samples()], sample_order, ts
+    )
+    emitted_nodes = len(sample_nodes)
+    yield {
+        'type': 'chunk',
+        'chunk_index': 0,
+        'num_chunks': num_chunks,
+        'label': 'samples',
+        'nodes': sample_nodes,
+        'edges': edges_for_band(0),
+        'nodes_loaded': emitted_nodes,
+        'total_nodes': total_nodes
+    }
+
+    for band_index, band in enumerate(bands, start=1):
+        check_cancelled(cancel_token)
+        emitted_nodes += len(band)
+        yield {
+            'type': 'chunk',
+            'chunk_index': band_index,
+            'num_chunks': num_chunks,
+            'label': 'time_band',
+            'time_range': [float(times[band[0]]), float(times[band[-1]])],
+            'nodes': [build_node_data(ts, ts.node(int(u))) for u in band],
+            'edges': edges_for_band(band_index),
+            'nodes_loaded': emitted_nodes,
+            'total_nodes': total_nodes
+        }
+
+    coordinates = []
+    for node_id in np.where(connected | is_sample)[0]:
+        node = ts.node(int(node_id))
+        if node.individual != -1 and node.individual < ts.num_individuals:
+            location = ts.individual(node.individual).location
+            if location is not None and len(location) >= 2:
+                coordinates.append((float(location[0]), float(location[1])))
+
+    complete: Dict[str, Any] = {'type': 'complete'}
+    if coordinates:
+        from argscape.backend.geo_utils.crs_detect import detect_coordinate_system
+        crs_detection = detect_coordinate_system(coordinates)
+        complete['coordinate_system_detection'] = crs_detection
+        complete['suggested_geographic_mode'] = crs_detection['suggested_geographic_mode']
+        if crs_detection['bounds']:
+            complete['spatial_bounds'] = crs_detection['bounds']
+    else:
+        complete['suggested_geographic_mode'] = "unit_grid"
+    yield complete
@@ -0,0 +1,168 @@
+"""
+What this machine can do: GPU adapter, CPU cores and instruction set
+features, memory and free disk space.
+Used to pick defaults (worker threads capped by memory, whether GPU compute
+paths are offered) and shown in the About panel. Every probe is optional:
+without wgpu, psutil or /proc the report says what could not be measured
+instead of guessing.
+"""
+
+import functools
+import logging
+import os
+import platform
+import shutil
+import sys
+import tempfile
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from argscape.backend.settings import get_config_dir
+
+logger = logging.getLogger(__name__)
+
+# CPU features worth reporting (x86 names from /proc/cpuinfo, ARM ones as "asimd"/"neon")
+REPORTED_CPU_FEATURES = ("sse4_2", "avx", "avx2", "fma", "avx512f", "asimd", "neon", "sve")
+# Memory each worker thread is assumed to need when capping the default thread count
+MEMORY_PER_WORKER_MB = 512
+
+
+@functools.lru_cache(maxsize=1)
+def get_gpu_info() -> Dict[str, Any]:
+    """The default GPU adapter as seen by wgpu; probed once per process."""
+    try:
+        import wgpu
+    except ImportError:
+        return {"available": False, "reason": "wgpu is not installed (pip install wgpu)"}
+    try:
+        if hasattr(wgpu.gpu, "request_adapter_sync"):
+            adapter = wgpu.gpu.request_adapter_sync(power_preference="high-performance")
+        else:
+            adapter = wgpu.gpu.request_adapter(power_preference="high-performance")
+        if adapter is None:
+            return {"available": False, "reason": "No compatible GPU adapter found"}
+        info = adapter.info if hasattr(adapter, "info") else adapter.request_adapter_info()
+        info = dict(info)
+    except Exception as e:
+        logger.info(f"GPU probe failed: {e}")
+        return {"available": False, "reason": f"GPU probe failed: {e}"}
+    # Software adapters (e.g. llvmpipe) work but are slower than the CPU paths
+    adapter_type = str(info.get("adapter_type", "")).lower()
+    return {
+        "available": "cpu" not in adapter_type,
+        "reason": "Only a software (CPU) adapter is available" if "cpu" in adapter_type else None,
+        "vendor": info.get("vendor"),
+        "device": info.get("device"),
+        "adapter_type": info.get("adapter_type"),
+        "backend": info.get("backend_type"),
+        "driver": info.get("description") or info.get("driver"),
+    }
+
+
+def _read_cpuinfo() -> Dict[str, str]:
+    fields: Dict[str, str] = {}
+    try:
+        with open("/proc/cpuinfo") as f:
+            for line in f:
+                key, _, value = line.partition(":")
+                key = key.strip()
+                # The first processor block describes them all
+                if not key and fields:
+                    break
+                fields.setdefault(key, value.strip())
+    except OSError:
+        pass
+    return fields
+
+
+@functools.lru_cache(maxsize=1)
+def get_cpu_info() -> Dict[str, Any]:
+    from argscape.backend.resources import get_cpu_count
+
+    cpuinfo = _read_cpuinfo()
+    flags = set((cpuinfo.get("flags") or cpuinfo.get("Features") or "").split())
+    physical = None
+    try:
+        import psutil
+        physical = psutil.cpu_count(logical=False)
+    except ImportError:
+        pass
+    return {
+        "model": cpuinfo.get("model name") or platform.processor() or None,
+        "architecture": platform.machine(),
+        "available_cores": get_cpu_count(),
+        "logical_cores": os.cpu_count(),
+        "physical_cores": physical,
+        # None when the OS doesn't expose the flags (macOS, Windows without psutil)
+        "features": sorted(flags.intersection(REPORTED_CPU_FEATURES)) if flags else None,
+    }
+
+
+def get_memory_info() -> Dict[str, Optional[int]]:
+    """Total and available memory in MB, or None where they can't be read."""
+    try:
+        import psutil
+        memory = psutil.virtual_memory()
+        return {"total_mb": memory.total // 2**20, "available_mb": memory.available // 2**20}
+    except ImportError:
+        pass
+    values: Dict[str, int] = {}
+    try:
+        with open("/proc/meminfo") as f:
+            for line in f:
+                key, _, value = line.partition(":")
+                if key in ("MemTotal", "MemAvailable"):
+                    values[key] = int(value.split()[0]) // 1024
+    except (OSError, ValueError):
+        pass
+    total = values.get("MemTotal")
+    if total is None and hasattr(os, "sysconf"):
+        try:
+            total = os.sysconf("SC_PAGE_SIZE") * os.sysconf("SC_PHYS_PAGES") // 2**20
+        except (ValueError, OSError):
+            pass
+    return {"total_mb": total, "available_mb": values.get("MemAvailable")}
+
+
+def get_disk_info(paths: Optional[Dict[str, Path]] = None) -> List[Dict[str, Any]]:
+    """Free space on the drives holding each named location (config and temp by default)."""
+    paths = paths or {"config": get_config_dir(), "temp": Path(tempfile.gettempdir())}
+    disks = []
+    for name, path in paths.items():
+        # Measure the nearest existing parent so unused locations are still reported
+        probe = Path(path)
+        while not probe.exists() and probe.parent != probe:
+            probe = probe.parent
+        try:
+            usage = shutil.disk_usage(probe)
+        except OSError as e:
+            disks.append({"location": name, "error": str(e)})
+            continue
+        disks.append({"location": name, "total_mb": usage.total // 2**20, "free_mb": usage.free // 2**20})
+    return disks
+
+
+def recommended_worker_count() -> int:
+    """All available cores, but no more than memory allows at MEMORY_PER_WORKER_MB each."""
+    cores = get_cpu_info()["available_cores"]
+    total = get_memory_info()["total_mb"]
+    if total is None:
+        return cores
+    return max(1, min(cores, total // MEMORY_PER_WORKER_MB))
+
+
+def get_system_capabilities(disk_paths: Optional[Dict[str, Path]] = None) -> Dict[str, Any]:
+    """GPU, CPU, memory and disk, plus the defaults chosen from them."""
+    gpu = get_gpu_info()
+    return {
+        "platform": platform.platform(),
+        "python": sys.version.split()[0],
+        "gpu": gpu,
+        "cpu": get_cpu_info(),
+        "memory": get_memory_info(),
+        "disks": get_disk_info(disk_paths),
+        "recommended": {
+            "num_threads": recommended_worker_count(),
+            "gpu_compute": gpu["available"],
+        },
+    }
@@ -0,0 +1,294 @@
+"""
+User automation hooks: external commands run when a file is opened or an
+export completes.
+Hooks are kept in hooks.json in the config folder and only run while the
+`hooks` setting is on. Each is an argument list, never a shell line, whose
+{placeholders} are filled per argument from the event (file name, staged
+export path, format, ...), so values can't inject extra commands. Commands
+run one at a time on a worker thread with a cleared environment (the
+restricted-mode allowlist plus ARGSCAPE_HOOK_* variables), no stdin, a
+timeout that kills the whole process group, and captured, truncated output.
+Exports are handed over as a staged copy that is deleted once the hook
+exits. Hooks only run in local mode (a CLI launch with its access token),
+never in protected or restricted mode, and protected files never reach them.
+"""
+
+import fnmatch
+import json
+import logging
+import os
+import queue
+import shutil
+import signal
+import string
+import subprocess
+import sys
+import threading
+import time
+import uuid
+from collections import deque
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from argscape.backend.auth import is_local_mode
+from argscape.backend.protected import is_protected_mode
+from argscape.backend.sandbox import SANDBOX_ENV_ALLOWLIST, is_sandbox_enabled
+from argscape.backend.settings import get_config_dir, get_data_dir, settings
+
+logger = logging.getLogger(__name__)
+
+HOOKS_FILENAME = "hooks.json"
+EVENT_FILE_OPENED = "file_opened"
+EVENT_EXPORT_COMPLETED = "export_completed"
+HOOK_EVENTS = (EVENT_FILE_OPENED, EVENT_EXPORT_COMPLETED)
+# Placeholders each event fills; anything else in a command is rejected when the hook is saved
+HOOK_FIELDS = {
+    EVENT_FILE_OPENED: ("filename", "path", "session", "num_samples", "num_trees", "sequence_length", "date", "time"),
+    EVENT_EXPORT_COMPLETED: ("filename", "output", "path", "format", "endpoint", "session", "size", "date", "time"),
+}
+DEFAULT_TIMEOUT_SECONDS = 60
+MAX_TIMEOUT_SECONDS = 600
+MAX_OUTPUT_CHARS = 4000
+MAX_QUEUED_RUNS = 100
+MAX_RECORDED_RUNS = 50
+# Downloads larger than this are passed through without running export hooks
+MAX_STAGED_EXPORT_MB = 1024
+
+
+def hooks_available() -> Optional[str]:
+    """None when hooks may run, else why not."""
+    if not is_local_mode():
+        return "Hooks only run when ARGscape is started locally with `argscape` (with its access token)"
+    if not settings.get("hooks"):
+        return "Hooks are off; turn on the hooks setting to run them"
+    if is_protected_mode():
+        return "Hooks don't run in protected mode"
+    if is_sandbox_enabled():
+        return "Hooks don't run in restricted mode, as external commands would escape its limits"
+    return None
+
+
+def _placeholders(argument: str) -> List[str]:
+    names = []
+    for _, name, spec, conversion in string.Formatter().parse(argument):
+        if name is None:
+            continue
+        if spec or conversion or not name.isidentifier():
+            raise ValueError(f"Placeholders are plain names like {{path}}, not {{{name}{'!' + conversion if conversion else ''}{':' + spec if spec else ''}}}")
+        names.append(name)
+    return names
+
+
+def validate_hook(hook: Dict[str, Any]) -> Dict[str, Any]:
+    """A hook with defaults filled in; raises ValueError if it is malformed."""
+    if not isinstance(hook, dict):
+        raise ValueError("Each hook is an object")
+    name = hook.get("name")
+    if not isinstance(name, str) or not name.strip():
+        raise ValueError("Each hook needs a name")
+    event = hook.get("event")
+    if event not in HOOK_EVENTS:
+        raise ValueError(f"Hook '{name}': event must be one of {HOOK_EVENTS}")
+    command = hook.get("command")
+    if not isinstance(command, list) or not command or not all(isinstance(a, str) for a in command):
+        raise ValueError(f"Hook '{name}': command is a non-empty list of arguments (no shell syntax)")
+    if _placeholders(command[0]):
+        raise ValueError(f"Hook '{name}': the program itself can't be a placeholder")
+    for argument in command:
+        unknown = [p for p in _placeholders(argument) if p not in HOOK_FIELDS[event]]
+        if unknown:
+            raise ValueError(f"Hook '{name}': unknown placeholders {unknown}. {event} has {list(HOOK_FIELDS[event])}")
+    timeout = hook.get("timeout", DEFAULT_TIMEOUT_SECONDS)
+    if not isinstance(timeout, (int, float)) or not 0 < timeout <= MAX_TIMEOUT_SECONDS:
+        raise ValueError(f"Hook '{name}': timeout must be between 0 and {MAX_TIMEOUT_SECONDS} seconds")
+    cwd = hook.get("cwd")
+    if cwd is not None and not Path(cwd).expanduser().is_dir():
+        raise ValueError(f"Hook '{name}': working folder not found: {cwd}")
+    return {
+        "name": name.strip(),
+        "event": event,
+        "command": command,
+        "match": hook.get("match") or "*",
+        "enabled": bool(hook.get("enabled", True)),
+        "timeout": timeout,
+        "cwd": str(Path(cwd).expanduser()) if cwd else None,
+    }
+
+
+def render_command(hook: Dict[str, Any], values: Dict[str, Any]) -> List[str]:
+    """The hook's argument list with each placeholder replaced by its value, as text."""
+    fields = {name: "" if values.get(name) is None else str(values[name]) for name in HOOK_FIELDS[hook["event"]]}
+    return [argument.format_map(fields) for argument in hook["command"]]
+
+
+def _environment(event: str, values: Dict[str, Any]) -> Dict[str, str]:
+    env = {key: os.environ[key] for key in SANDBOX_ENV_ALLOWLIST if key in os.environ}
+    env["ARGSCAPE_HOOK_EVENT"] = event
+    for name in HOOK_FIELDS[event]:
+        if values.get(name) is not None:
+            env[f"ARGSCAPE_HOOK_{name.upper()}"] = str(values[name])
+    return env
+
+
+def _truncate(text: str) -> str:
+    return text if len(text) <= MAX_OUTPUT_CHARS else "..." + text[-MAX_OUTPUT_CHARS:]
+
+
+class HookRunner:
+    """Loads hooks.json and runs matching hooks on a single worker thread."""
+
+    def __init__(self, path: Optional[Path] = None):
+        self.path = path or get_config_dir() / HOOKS_FILENAME
+        self._lock = threading.Lock()
+        self._hooks: List[Dict[str, Any]] = []
+        self._queue: "queue.Queue[Dict[str, Any]]" = queue.Queue(maxsize=MAX_QUEUED_RUNS)
+        self._runs: deque = deque(maxlen=MAX_RECORDED_RUNS)
+        self._thread: Optional[threading.Thread] = None
+        self._load()
+
+    def _load(self):
+        if not self.path.exists():
+            return
+        try:
+            with open(self.path, "r") as f:
+                stored = json.load(f)
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable hooks file {self.path}: {e}")
+            return
+        for hook in stored if isinstance(stored, list) else []:
+            try:
+                self._hooks.append(validate_hook(hook))
+            except ValueError as e:
+                logger.warning(f"Ignoring hook: {e}")
+
+    def get_hooks(self) -> List[Dict[str, Any]]:
+        with self._lock:
+            return [dict(h) for h in self._hooks]
+
+    def set_hooks(self, hooks: List[Dict[str, Any]]) -> List[Dict[str, Any]]:
+        """Replace every hook; raises ValueError (and keeps the old ones) if any is invalid."""
+        validated = [validate_hook(h) for h in hooks]
+        names = [h["name"] for h in validated]
+        duplicates = sorted({n for n in names if names.count(n) > 1})
+        if duplicates:
+            raise ValueError(f"Hook names must be unique: {duplicates}")
+        with self._lock:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            with open(self.path, "w") as f:
+                json.dump(validated, f, indent=2)
+            self._hooks = validated
+        logger.info(f"Saved {len(validated)} hooks to {self.path}")
+        return [dict(h) for h in validated]
+
+    def has_hooks(self, event: str) -> bool:
+        with self._lock:
+            return any(h["enabled"] and h["event"] == event for h in self._hooks)
+
+    def recent_runs(self) -> List[Dict[str, Any]]:
+        with self._lock:
+            return list(reversed(self._runs))
+
+    def fire(self, event: str, values: Dict[str, Any], match_on: str, staged: Optional[Path] = None) -> int:
+        """Queue every enabled hook for `event` whose match pattern fits `match_on`; returns how many."""
+        reason = hooks_available()
+        with self._lock:
+            matching = [h for h in self._hooks if h["enabled"] and h["event"] == event and fnmatch.fnmatch(match_on, h["match"])]
+        if reason is not None or not matching:
+            self._discard(staged)
+            return 0
+        task = {"event": event, "values": values, "hooks": matching, "staged": staged}
+        try:
+            self._queue.put_nowait(task)
+        except queue.Full:
+            logger.warning(f"Hook queue is full; skipped {len(matching)} {event} hooks")
+            self._discard(staged)
+            return 0
+        self._ensure_worker()
+        return len(matching)
+
+    def stage_export(self, output: str, contents: bytes) -> Path:
+        """Write an export's bytes where its hooks can read them, under the data folder."""
+        directory = get_data_dir() / "hooks" / uuid.uuid4().hex
+        directory.mkdir(parents=True, exist_ok=True)
+        path = directory / Path(output).name
+        path.write_bytes(contents)
+        return path
+
+    def _discard(self, staged: Optional[Path]):
+        if staged is not None:
+            shutil.rmtree(staged.parent, ignore_errors=True)
+
+    def _ensure_worker(self):
+        with self._lock:
+            if self._thread is None or not self._thread.is_alive():
+                self._thread = threading.Thread(target=self._work, name="argscape-hooks", daemon=True)
+                self._thread.start()
+
+    def _work(self):
+        while True:
+            task = self._queue.get()
+            try:
+                for hook in task["hooks"]:
+                    run = self.run_hook(hook, task["values"])
+                    with self._lock:
+                        self._runs.append(run)
+            finally:
+                self._discard(task["staged"])
+                self._queue.task_done()
+
+    def run_hook(self, hook: Dict[str, Any], values: Dict[str, Any]) -> Dict[str, Any]:
+        """Run one hook to completion and describe how it went."""
+        argv = render_command(hook, values)
+        run = {"hook": hook["name"], "event": hook["event"], "command": argv, "started_at": time.time()}
+        program = shutil.which(argv[0])
+        if program is None:
+            logger.warning(f"Hook {hook['name']}: program not found: {argv[0]}")
+            return {**run, "status": "error", "error": f"Program not found: {argv[0]}", "exit_code": None}
+
+        workdir = Path(hook["cwd"]) if hook["cwd"] else get_data_dir() / "hooks"
+        workdir.mkdir(parents=True, exist_ok=True)
+        start = time.perf_counter()
+        try:
+            process = subprocess.Popen(
+                [program, *argv[1:]],
+                cwd=str(workdir),
+                env=_environment(hook["event"], values),
+                stdin=subprocess.DEVNULL,
+                stdout=subprocess.PIPE,
+                stderr=subprocess.PIPE,
+                text=True,
+                errors="replace",
+                # Its own process group, so a timeout also ends whatever the command started
+                start_new_session=sys.platform != "win32",
+                creationflags=subprocess.CREATE_NEW_PROCESS_GROUP if sys.platform == "win32" else 0,
+            )
+        except OSError as e:
+            return {**run, "status": "error", "error": str(e), "exit_code": None}
+        try:
+            stdout, stderr = process.communicate(timeout=hook["timeout"])
+            status = "ok" if process.returncode == 0 else "failed"
+        except subprocess.TimeoutExpired:
+            try:
+                if sys.platform == "win32":
+                    process.kill()
+                else:
+                    os.killpg(process.pid, signal.SIGKILL)
+            except ProcessLookupError:
+                pass
+            stdout, stderr = process.communicate()
+            status = "timeout"
+        duration = time.perf_counter() - start
+        log = logger.info if status == "ok" else logger.warning
+        log(f"Hook {hook['name']} ({hook['event']}) {status} in {duration:.1f}s")
+        return {
+            **run,
+            "status": status,
+            "exit_code": process.returncode,
+            "duration_seconds": duration,
+            "stdout": _truncate(stdout or ""),
+            "stderr": _truncate(stderr or ""),
+            "error": None,
+        }
+
+
+hook_runner = HookRunner()
@@ -0,0 +1,244 @@
+"""
+Self-contained HTML export of an ARG view.
+One file holds the laid-out nodes and edges, their decoded node metadata
+(shortened like the metadata inspector's previews), file metadata and a
+small canvas viewer (pan, zoom, hover details, click to highlight a node's
+parents and children), so a figure can be emailed to someone without
+ARGscape and opened offline in any browser. Nothing is fetched at view time.
+"""
+
+import json
+from typing import Any, Dict, List, Optional, Sequence
+from xml.sax.saxutils import escape
+
+import tskit
+
+from argscape.backend.palettes import resolve_palette
+from argscape.backend.tskit_utils.metadata import preview_node_metadata
+
+# Above this the embedded data makes the file slow to open in a browser
+MAX_BUNDLE_NODES = 50000
+BUNDLE_FORMAT_VERSION = 1
+
+VIEWER_CSS = """
+html, body { margin: 0; height: 100%; font-family: Arial, sans-serif; background: #ffffff; color: #2c3e50; }
+#header { position: absolute; top: 0; left: 0; right: 0; padding: 8px 12px; background: rgba(255,255,255,0.9); border-bottom: 1px solid #e0e0e0; font-size: 13px; z-index: 1; }
+#header h1 { display: inline; font-size: 15px; margin-right: 12px; }
+#header button { margin-left: 8px; }
+#legend { position: absolute; bottom: 12px; left: 12px; background: rgba(255,255,255,0.9); border: 1px solid #e0e0e0; padding: 6px 8px; font-size: 12px; }
+#legend span { display: inline-block; width: 10px; height: 10px; border-radius: 5px; margin-right: 4px; }
+#tooltip { position: absolute; pointer-events: none; background: rgba(44,62,80,0.92); color: #ffffff; padding: 6px 8px; border-radius: 4px; font-size: 12px; display: none; white-space: pre; }
+canvas { display: block; width: 100%; height: 100%; cursor: grab; }
+"""
+
+# Reads the embedded data and draws it; kept dependency-free so the file works offline
+VIEWER_JS = """
+(function () {
+  var data = JSON.parse(document.getElementById('argscape-data').textContent);
+  var canvas = document.getElementById('view'), ctx = canvas.getContext('2d');
+  var tooltip = document.getElementById('tooltip');
+  var nodes = data.nodes, edges = data.edges, byId = {};
+  nodes.forEach(function (n) { byId[n.id] = n; n.parents = []; n.children = []; });
+  edges.forEach(function (e) { byId[e[1]].parents.push(e[0]); byId[e[0]].children.push(e[1]); });
+  var colors = {};
+  data.populations.forEach(function (p) { colors[p.id] = p.color; });
+  var view = { x: 0, y: 0, k: 1 }, selected = null, dpr = window.devicePixelRatio || 1;
+
+  function fit() {
+    var xs = nodes.map(function (n) { return n.x; }), ys = nodes.map(function (n) { return n.y; });
+    var minX = Math.min.apply(null, xs), maxX = Math.max.apply(null, xs);
+    var minY = Math.min.apply(null, ys), maxY = Math.max.apply(null, ys);
+    var w = canvas.clientWidth, h = canvas.clientHeight - 40;
+    view.k = 0.9 * Math.min(w / ((maxX - minX) || 1), h / ((maxY - minY) || 1));
+    view.x = w / 2 - view.k * (minX + maxX) / 2;
+    view.y = 40 + h / 2 - view.k * (minY + maxY) / 2;
+    draw();
+  }
+  function toScreen(n) { return [n.x * view.k + view.x, n.y * view.k + view.y]; }
+  function draw() {
+    canvas.width = canvas.clientWidth * dpr; canvas.height = canvas.clientHeight * dpr;
+    ctx.setTransform(dpr, 0, 0, dpr, 0, 0);
+    ctx.clearRect(0, 0, canvas.clientWidth, canvas.clientHeight);
+    var linked = {};
+    if (selected) { linked[selected.id] = true; selected.parents.concat(selected.children).forEach(function (u) { linked[u] = true; }); }
+    edges.forEach(function (e) {
+      var a = toScreen(byId[e[0]]), b = toScreen(byId[e[1]]);
+      var highlight = selected && (e[0] === selected.id || e[1] === selected.id);
+      ctx.strokeStyle = highlight ? '#e67e22' : 'rgba(127,140,141,' + (selected ? 0.15 : 0.5) + ')';
+      ctx.lineWidth = highlight ? 2 : 1;
+      ctx.beginPath(); ctx.moveTo(a[0], a[1]); ctx.lineTo(b[0], b[1]); ctx.stroke();
+    });
+    var r = Math.max(2, Math.min(8, 4 * Math.sqrt(view.k)));
+    nodes.forEach(function (n) {
+      var p = toScreen(n);
+      ctx.globalAlpha = selected && !linked[n.id] ? 0.25 : 1;
+      ctx.fillStyle = colors[n.population] || '#95a5a6';
+      ctx.beginPath(); ctx.arc(p[0], p[1], n.is_sample ? r : r * 0.75, 0, 2 * Math.PI); ctx.fill();
+      if (n === selected) { ctx.strokeStyle = '#2c3e50'; ctx.lineWidth = 2; ctx.stroke(); }
+    });
+    ctx.globalAlpha = 1;
+  }
+  function nodeAt(px, py) {
+    var best = null, bestDistance = 8;
+    nodes.forEach(function (n) {
+      var p = toScreen(n), d = Math.hypot(p[0] - px, p[1] - py);
+      if (d < bestDistance) { best = n; bestDistance = d; }
+    });
+    return best;
+  }
+  function describe(n) {
+    var population = data.populations[n.population];
+    var lines = ['Node ' + n.id + (n.is_sample ? ' (sample)' : ''), 'Time: ' + n.time];
+    if (population) lines.push('Population: ' + population.name);
+    if (n.individual >= 0) lines.push('Individual: ' + n.individual);
+    lines.push('Parents: ' + (n.parents.join(', ') || 'none'), 'Children: ' + (n.children.join(', ') || 'none'));
+    if (n.metadata !== undefined) {
+      var text = JSON.stringify(n.metadata, null, 1);
+      lines.push('Metadata: ' + (text.length > 400 ? text.slice(0, 400) + '…' : text));
+    }
+    return lines.join('\\n');
+  }
+
+  var drag = null, moved = false;
+  canvas.addEventListener('mousedown', function (ev) { drag = [ev.clientX, ev.clientY]; moved = false; canvas.style.cursor = 'grabbing'; });
+  window.addEventListener('mouseup', function (ev) {
+    canvas.style.cursor = 'grab';
+    if (drag && !moved && ev.target === canvas) { selected = nodeAt(ev.offsetX, ev.offsetY); draw(); }
+    drag = null;
+  });
+  canvas.addEventListener('mousemove', function (ev) {
+    if (drag) {
+      view.x += ev.clientX - drag[0]; view.y += ev.clientY - drag[1];
+      moved = moved || Math.abs(ev.clientX - drag[0]) + Math.abs(ev.clientY - drag[1]) > 2;
+      drag = [ev.clientX, ev.clientY]; draw(); tooltip.style.display = 'none';
+      return;
+    }
+    var n = nodeAt(ev.offsetX, ev.offsetY);
+    if (n) {
+      tooltip.textContent = describe(n);
+      tooltip.style.left = (ev.clientX + 12) + 'px'; tooltip.style.top = (ev.clientY + 12) + 'px';
+      tooltip.style.display = 'block';
+    } else {
+      tooltip.style.display = 'none';
+    }
+  });
+  canvas.addEventListener('wheel', function (ev) {
+    ev.preventDefault();
+    var factor = Math.exp(-ev.deltaY * 0.0015);
+    view.x = ev.offsetX - (ev.offsetX - view.x) * factor;
+    view.y = ev.offsetY - (ev.offsetY - view.y) * factor;
+    view.k *= factor; draw();
+  }, { passive: false });
+  document.getElementById('fit').addEventListener('click', function () { selected = null; fit(); });
+  window.addEventListener('resize', draw);
+  fit();
+})();
+"""
+
+
+def _script_json(payload: Dict[str, Any]) -> str:
+    """JSON safe to embed in a <script> element."""
+    return json.dumps(payload, separators=(",", ":")).replace("</", "<\\/")
+
+
+def build_bundle_data(
+    ts: tskit.TreeSequence,
+    positions: Dict[int, Sequence[float]],
+    palette_choice: Optional[Dict[str, Any]] = None,
+    title: Optional[str] = None,
+    layout: Optional[Dict[str, Any]] = None
+) -> Dict[str, Any]:
+    """The data embedded in the bundle: the view's nodes, the edges between them and file metadata."""
+    if not positions:
+        raise ValueError("No positions to export; lay out the view first")
+    if len(positions) > MAX_BUNDLE_NODES:
+        raise ValueError(f"The view has {len(positions)} nodes; simplify or filter it below {MAX_BUNDLE_NODES} to export HTML")
+
+    node_ids = sorted(int(u) for u in positions)
+    included = set(node_ids)
+    for u in node_ids:
+        if not 0 <= u < ts.num_nodes:
+            raise ValueError(f"Node {u} is not in the tree sequence")
+    nodes_table = ts.tables.nodes
+    nodes: List[Dict[str, Any]] = [
+        {
+            "id": u,
+            "x": round(float(positions[u][0]), 3),
+            "y": round(float(positions[u][1]), 3),
+            "time": float(nodes_table.time[u]),
+            "population": int(nodes_table.population[u]),
+            "individual": int(nodes_table.individual[u]),
+            "is_sample": bool(nodes_table.flags[u] & tskit.NODE_IS_SAMPLE),
+        }
+        for u in node_ids
+    ]
+    # Shortened, so large metadata blobs don't bloat the file
+    node_metadata = preview_node_metadata(ts, node_ids)
+    for node in nodes:
+        if node["id"] in node_metadata:
+            node["metadata"] = node_metadata[node["id"]]
+    edges = sorted({
+        (int(p), int(c)) for p, c in zip(ts.edges_parent, ts.edges_child)
+        if int(p) in included and int(c) in included
+    })
+
+    palette = resolve_palette(ts, palette_choice)
+    return {
+        "format_version": BUNDLE_FORMAT_VERSION,
+        "title": title,
+        "metadata": {
+            "num_nodes": ts.num_nodes,
+            "num_edges": ts.num_edges,
+            "num_samples": ts.num_samples,
+            "num_trees": ts.num_trees,
+            "sequence_length": ts.sequence_length,
+            "time_units": ts.time_units,
+            "nodes_shown": len(nodes),
+            "edges_shown": len(edges),
+        },
+        "layout": layout or {},
+        "populations": [{"id": p["id"], "name": p["name"], "color": p["color"]} for p in palette["populations"]],
+        "nodes": nodes,
+        "edges": [list(edge) for edge in edges],
+    }
+
+
+def build_html_bundle(
+    ts: tskit.TreeSequence,
+    positions: Dict[int, Sequence[float]],
+    palette_choice: Optional[Dict[str, Any]] = None,
+    title: Optional[str] = None,
+    layout: Optional[Dict[str, Any]] = None
+) -> str:
+    """A single HTML file that shows the view read-only, with the viewer and data inlined."""
+    data = build_bundle_data(ts, positions, palette_choice, title, layout)
+    metadata = data["metadata"]
+    summary = (
+        f"{metadata['nodes_shown']:,} of {metadata['num_nodes']:,} nodes · "
+        f"{metadata['num_samples']:,} samples · {metadata['num_trees']:,} trees · "
+        f"sequence length {metadata['sequence_length']:g}"
+    )
+    legend = "".join(
+        f'<div><span style="background:{escape(p["color"])}"></span>{escape(p["name"])}</div>'
+        for p in data["populations"]
+    )
+    heading = escape(title or "ARG")
+    return f"""<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="generator" content="ARGscape">
+<title>{heading}</title>
+<style>{VIEWER_CSS}</style>
+</head>
+<body>
+<div id="header"><h1>{heading}</h1>{escape(summary)}<button id="fit">Reset view</button></div>
+<canvas id="view"></canvas>
+<div id="legend">{legend}</div>
+<div id="tooltip"></div>
+<script type="application/json" id="argscape-data">{_script_json(data)}</script>
+<script>{VIEWER_JS}</script>
+</body>
+</html>
+"""
@@ -0,0 +1,160 @@
+"""
+Watched "inbox" folder for pipeline outputs.
+When an inference pipeline drops a new tree sequence into the configured
+folder, it is loaded (once its size has stopped changing), optionally
+preprocessed, and added to the local user's session. The frontend polls the
+event list to notify the user or open the new file automatically.
+"""
+
+import logging
+import threading
+import time
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+import tskit
+
+from argscape.backend.settings import settings
+
+logger = logging.getLogger(__name__)
+
+INBOX_EXTENSIONS = (".trees", ".tsz")
+INBOX_PREPROCESS_STEPS = ("none", "validate", "simplify")
+INBOX_ACTIONS = ("notify", "open")
+POLL_INTERVAL_SECONDS = 2.0
+MAX_INBOX_EVENTS = 200
+
+# Files arriving in the inbox belong to the desktop user, whose browser connects over loopback
+LOCAL_CLIENT_IP = "127.0.0.1"
+
+
+class InboxWatcher:
+    """Polls a directory for new tree sequence files and imports them."""
+
+    def __init__(self, import_file: Callable[[Path, str], Dict[str, Any]]):
+        """
+        Args:
+            import_file: Loads the file at the given path into the local session with the
+                given preprocessing step and returns details for the event
+        """
+        self.import_file = import_file
+        self._lock = threading.Lock()
+        self._thread: Optional[threading.Thread] = None
+        self._stop = threading.Event()
+        self._directory: Optional[Path] = None
+        self._seen: Dict[str, Tuple[int, float]] = {}
+        self._pending: Dict[str, Tuple[int, float]] = {}
+        self._events: List[Dict[str, Any]] = []
+        self._next_event_id = 1
+        self._last_error: Optional[str] = None
+
+    def configure(self, directory: Optional[str]):
+        """Start watching `directory` (None stops watching). Existing files are not imported."""
+        new_directory = Path(directory).expanduser() if directory else None
+        with self._lock:
+            if new_directory == self._directory and (self._thread is not None) == (new_directory is not None):
+                return
+        self.stop()
+        if new_directory is None:
+            return
+
+        with self._lock:
+            self._directory = new_directory
+            self._seen = dict(self._scan(new_directory))
+            self._pending = {}
+            self._last_error = None
+            self._stop = threading.Event()
+            self._thread = threading.Thread(target=self._run, name="argscape-inbox", daemon=True)
+            self._thread.start()
+        logger.info(f"Watching inbox folder {new_directory} ({len(self._seen)} existing files ignored)")
+
+    def stop(self):
+        with self._lock:
+            thread, self._thread = self._thread, None
+            self._directory = None
+            self._stop.set()
+        if thread is not None and thread is not threading.current_thread():
+            thread.join(timeout=POLL_INTERVAL_SECONDS * 2)
+
+    def _scan(self, directory: Path) -> Dict[str, Tuple[int, float]]:
+        found = {}
+        for path in directory.iterdir():
+            if path.is_file() and path.name.lower().endswith(INBOX_EXTENSIONS) and not path.name.startswith("."):
+                stat = path.stat()
+                found[str(path)] = (stat.st_size, stat.st_mtime)
+        return found
+
+    def _run(self):
+        stop = self._stop
+        while not stop.wait(POLL_INTERVAL_SECONDS):
+            directory = self._directory
+            if directory is None:
+                return
+            try:
+                current = self._scan(directory)
+                self._last_error = None
+            except OSError as e:
+                self._last_error = str(e)
+                continue
+
+            for path, signature in current.items():
+                if self._seen.get(path) == signature:
+                    continue
+                # Only import once the writer has finished: unchanged across two polls
+                if self._pending.get(path) != signature:
+                    self._pending[path] = signature
+                    continue
+                del self._pending[path]
+                self._seen[path] = signature
+                self._process(Path(path))
+
+    def _process(self, path: Path):
+        preprocess = settings.get("inbox_preprocess")
+        event: Dict[str, Any] = {
+            "source_name": path.name,
+            "preprocess": preprocess,
+            "action": settings.get("inbox_action"),
+            "detected_at": time.time(),
+        }
+        try:
+            event.update(self.import_file(path, preprocess))
+            event["status"] = "imported"
+            logger.info(f"Imported inbox file {path.name} as {event.get('filename')}")
+        except Exception as e:
+            event.update(status="failed", error=str(e))
+            logger.warning(f"Failed to import inbox file {path.name}: {e}")
+
+        with self._lock:
+            event["event_id"] = self._next_event_id
+            self._next_event_id += 1
+            self._events.append(event)
+            del self._events[:-MAX_INBOX_EVENTS]
+
+    def get_events(self, after: int = 0) -> List[Dict[str, Any]]:
+        """Events with an ID greater than `after`, oldest first."""
+        with self._lock:
+            return [event for event in self._events if event["event_id"] > after]
+
+    def get_status(self) -> Dict[str, Any]:
+        with self._lock:
+            return {
+                "watching": self._thread is not None,
+                "directory": str(self._directory) if self._directory else None,
+                "preprocess": settings.get("inbox_preprocess"),
+                "action": settings.get("inbox_action"),
+                "poll_interval_seconds": POLL_INTERVAL_SECONDS,
+                "num_events": len(self._events),
+                "last_event_id": self._next_event_id - 1,
+                "error": self._last_error,
+            }
+
+
+def preprocess_tree_sequence(ts: tskit.TreeSequence, step: str) -> Tuple[tskit.TreeSequence, Dict[str, Any]]:
+    """Apply the configured inbox preprocessing step."""
+    if step == "validate":
+        from argscape.backend.batch import validate_tree_sequence
+        return ts, {"validation": validate_tree_sequence(ts)}
+    if step == "simplify":
+        simplified = ts.simplify()
+        return simplified, {"nodes_before": ts.num_nodes, "nodes_after": simplified.num_nodes}
+    return ts, {}
@@ -0,0 +1,165 @@
+"""
+Python interpreter resolution for launching the backend.
+The backend can run in the interpreter that started the CLI (or the bundled
+PyInstaller build), or in a configured system interpreter, virtualenv or conda
+environment. Candidates are checked for the backend's required packages so a
+broken environment fails with a clear message naming what to install.
+"""
+
+import json
+import logging
+import os
+import shutil
+import subprocess
+import sys
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+# Import name -> distribution name, for the install hint
+REQUIRED_PACKAGES = {
+    "argscape": "argscape",
+    "tskit": "tskit",
+    "msprime": "msprime",
+    "tszip": "tszip",
+    "numpy": "numpy",
+    "fastapi": "fastapi",
+    "uvicorn": "uvicorn",
+    "pydantic": "pydantic",
+    "multipart": "python-multipart",
+}
+
+RUNTIME_MODES = ("auto", "bundled", "system")
+
+DEPENDENCY_CHECK_TIMEOUT_SECONDS = 30
+
+
+class MissingDependencyError(RuntimeError):
+    """The chosen interpreter can't run the backend."""
+
+
+def is_bundled() -> bool:
+    """True when running from the frozen (PyInstaller) build."""
+    return bool(getattr(sys, "frozen", False))
+
+
+def interpreter_in_environment(path: Path) -> Optional[Path]:
+    """The python executable inside a virtualenv or conda environment directory."""
+    if sys.platform == "win32":
+        candidates = (path / "python.exe", path / "Scripts" / "python.exe")
+    else:
+        candidates = (path / "bin" / "python3", path / "bin" / "python")
+    for candidate in candidates:
+        if candidate.is_file():
+            return candidate
+    return None
+
+
+def _normalize(value: str) -> Optional[Path]:
+    path = Path(value).expanduser()
+    if path.is_dir():
+        return interpreter_in_environment(path)
+    if path.is_file():
+        return path
+    found = shutil.which(value)
+    return Path(found) if found else None
+
+
+def candidate_interpreters(configured: Optional[str] = None) -> List[Dict[str, Any]]:
+    """Interpreters to try, most specific first."""
+    candidates = []
+    if configured:
+        candidates.append({"source": "configured", "path": _normalize(configured)})
+    for source, variable in (("virtualenv", "VIRTUAL_ENV"), ("conda", "CONDA_PREFIX")):
+        if os.getenv(variable):
+            candidates.append({"source": source, "path": interpreter_in_environment(Path(os.environ[variable]))})
+    if not is_bundled():
+        candidates.append({"source": "current", "path": Path(sys.executable)})
+    for name in ("python3", "python"):
+        found = shutil.which(name)
+        if found:
+            candidates.append({"source": "PATH", "path": Path(found)})
+
+    seen, unique = set(), []
+    for candidate in candidates:
+        key = str(candidate["path"])
+        if key not in seen:
+            seen.add(key)
+            unique.append(candidate)
+    return unique
+
+
+def check_dependencies(interpreter: Path) -> Dict[str, Any]:
+    """Run `interpreter` to see which required packages it can import."""
+    script = (
+        "import importlib.util, json, sys; "
+        f"names = {list(REQUIRED_PACKAGES)!r}; "
+        "print(json.dumps({'version': sys.version.split()[0], "
+        "'missing': [n for n in names if importlib.util.find_spec(n) is None]}))"
+    )
+    try:
+        result = subprocess.run(
+            [str(interpreter), "-c", script],
+            capture_output=True, text=True, timeout=DEPENDENCY_CHECK_TIMEOUT_SECONDS
+        )
+    except (OSError, subprocess.TimeoutExpired) as e:
+        return {"ok": False, "version": None, "missing": [], "error": str(e)}
+    if result.returncode != 0:
+        return {"ok": False, "version": None, "missing": [], "error": result.stderr.strip()[-500:]}
+    report = json.loads(result.stdout.strip().splitlines()[-1])
+    return {"ok": not report["missing"], "version": report["version"], "missing": report["missing"], "error": None}
+
+
+def missing_dependency_message(interpreter: Path, missing: List[str]) -> str:
+    packages = " ".join(REQUIRED_PACKAGES[name] for name in missing)
+    return (
+        f"{interpreter} is missing packages required by the ARGscape backend: {', '.join(missing)}.\n"
+        f"Install them with: {interpreter} -m pip install {packages}"
+    )
+
+
+def resolve_interpreter(configured: Optional[str] = None, runtime: str = "auto") -> Dict[str, Any]:
+    """Pick the interpreter to run the backend in.
+
+    "bundled" always uses this process (the frozen build or the CLI's own
+    interpreter). "system" uses the first candidate with every required package
+    and fails if there is none. "auto" prefers a working configured interpreter
+    and otherwise falls back to this process.
+    """
+    if runtime not in RUNTIME_MODES:
+        raise ValueError(f"backend_runtime must be one of {list(RUNTIME_MODES)}")
+    current = {"source": "bundled" if is_bundled() else "current", "path": Path(sys.executable), "reexec": False}
+    if runtime == "bundled":
+        return current
+
+    if configured and _normalize(configured) is None:
+        message = f"Configured Python interpreter not found: {configured}"
+        if runtime == "system":
+            raise MissingDependencyError(message)
+        logger.warning(f"{message}; using {current['path']}")
+        return current
+
+    checked = []
+    for candidate in candidate_interpreters(configured):
+        if candidate["path"] is None:
+            continue
+        if runtime == "auto" and candidate["source"] != "configured":
+            break
+        report = check_dependencies(candidate["path"])
+        checked.append((candidate, report))
+        if report["ok"]:
+            same = candidate["path"].resolve() == Path(sys.executable).resolve()
+            return {**candidate, "version": report["version"], "reexec": not same}
+
+    if runtime == "auto":
+        for candidate, report in checked:
+            logger.warning(missing_dependency_message(candidate["path"], report["missing"]) if report["missing"]
+                           else f"Cannot run {candidate['path']}: {report['error']}")
+        return current
+
+    details = "\n".join(
+        missing_dependency_message(c["path"], r["missing"]) if r["missing"] else f"{c['path']}: {r['error']}"
+        for c, r in checked
+    )
+    raise MissingDependencyError("No Python interpreter with the ARGscape backend's dependencies was found.\n" + details)
@@ -0,0 +1,186 @@
+"""
+Background jobs with cooperative cancellation for long-running computations.
+Job functions receive a CancellationToken and call check() at safe points, so
+cancel_job() stops work promptly instead of letting it run to completion.
+"""
+
+import logging
+import threading
+import time
+import uuid
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+logger = logging.getLogger(__name__)
+
+MAX_JOB_WORKERS = 2
+MAX_FINISHED_JOBS = 100
+
+# Job states
+JOB_PENDING = "pending"
+JOB_RUNNING = "running"
+JOB_COMPLETED = "completed"
+JOB_FAILED = "failed"
+JOB_CANCELLED = "cancelled"
+FINISHED_STATES = (JOB_COMPLETED, JOB_FAILED, JOB_CANCELLED)
+
+
+class JobCancelled(Exception):
+    """Raised inside a job when its cancellation token has been triggered."""
+
+
+class CancellationToken:
+    """Thread-safe flag checked by long-running code at safe points."""
+
+    def __init__(self):
+        self._event = threading.Event()
+
+    def cancel(self):
+        self._event.set()
+
+    @property
+    def cancelled(self) -> bool:
+        return self._event.is_set()
+
+    def check(self):
+        """Raise JobCancelled if cancellation was requested."""
+        if self._event.is_set():
+            raise JobCancelled()
+
+
+def check_cancelled(token: Optional[CancellationToken]):
+    """Check an optional token; no-op when the caller didn't pass one."""
+    if token is not None:
+        token.check()
+
+
+class Job:
+    """A single background computation and its state."""
+
+    def __init__(self, session_id: str, operation: str, description: str = ""):
+        self.job_id = str(uuid.uuid4())
+        self.session_id = session_id
+        self.operation = operation
+        self.description = description
+        self.token = CancellationToken()
+        self.status = JOB_PENDING
+        self.progress: Optional[float] = None
+        self.details: Dict[str, Any] = {}  # Live, operation-specific state (e.g. per-file batch status)
+        self.result: Any = None
+        self.artifact: Optional[Tuple[str, bytes]] = None  # (filename, contents) written by an export job
+        self.error: Optional[str] = None
+        self.created_at = time.time()
+        self.started_at: Optional[float] = None
+        self.finished_at: Optional[float] = None
+
+    def set_progress(self, fraction: float):
+        """Report progress in [0, 1]; also a cancellation point."""
+        self.progress = max(0.0, min(1.0, fraction))
+        self.token.check()
+
+    def to_dict(self, include_result: bool = False) -> Dict[str, Any]:
+        data = {
+            "job_id": self.job_id,
+            "operation": self.operation,
+            "description": self.description,
+            "status": self.status,
+            "progress": self.progress,
+            "details": self.details,
+            "error": self.error,
+            "created_at": self.created_at,
+            "started_at": self.started_at,
+            "finished_at": self.finished_at,
+        }
+        if include_result and self.status == JOB_COMPLETED:
+            data["result"] = self.result
+        return data
+
+
+class JobManager:
+    """Runs jobs on a worker pool and tracks their state."""
+
+    def __init__(self, max_workers: int = MAX_JOB_WORKERS):
+        self._executor = ThreadPoolExecutor(max_workers=max_workers, thread_name_prefix="argscape-job")
+        self._jobs: Dict[str, Job] = {}
+        self._lock = threading.Lock()
+
+    def submit(
+        self,
+        session_id: str,
+        operation: str,
+        func: Callable[[Job], Any],
+        description: str = ""
+    ) -> Job:
+        """Schedule func(job) on the worker pool. func should call job.token.check() regularly."""
+        job = Job(session_id, operation, description)
+        with self._lock:
+            self._jobs[job.job_id] = job
+            self._prune_finished()
+        self._executor.submit(self._run, job, func)
+        logger.info(f"Submitted job {job.job_id} ({operation}) for session {session_id}")
+        return job
+
+    def _run(self, job: Job, func: Callable[[Job], Any]):
+        if job.token.cancelled:
+            job.status = JOB_CANCELLED
+            job.finished_at = time.time()
+            return
+
+        job.status = JOB_RUNNING
+        job.started_at = time.time()
+        try:
+            job.result = func(job)
+            job.status = JOB_COMPLETED
+            job.progress = 1.0
+        except JobCancelled:
+            job.status = JOB_CANCELLED
+            logger.info(f"Job {job.job_id} ({job.operation}) cancelled")
+        except Exception as e:
+            job.status = JOB_FAILED
+            job.error = str(e)
+            logger.error(f"Job {job.job_id} ({job.operation}) failed: {e}")
+        finally:
+            job.finished_at = time.time()
+
+    def _prune_finished(self):
+        finished = sorted(
+            (job for job in self._jobs.values() if job.status in FINISHED_STATES),
+            key=lambda job: job.finished_at or 0
+        )
+        for job in finished[:max(0, len(finished) - MAX_FINISHED_JOBS)]:
+            del self._jobs[job.job_id]
+
+    def get_job(self, job_id: str, session_id: Optional[str] = None) -> Optional[Job]:
+        """Get a job, optionally restricted to the owning session."""
+        job = self._jobs.get(job_id)
+        if job is None or (session_id is not None and job.session_id != session_id):
+            return None
+        return job
+
+    def list_jobs(self, session_id: str) -> List[Job]:
+        """All tracked jobs for a session, newest first."""
+        with self._lock:
+            jobs = [job for job in self._jobs.values() if job.session_id == session_id]
+        return sorted(jobs, key=lambda job: job.created_at, reverse=True)
+
+    def cancel_job(self, job_id: str, session_id: Optional[str] = None) -> bool:
+        """Request cancellation. Returns False if the job is unknown or already finished."""
+        job = self.get_job(job_id, session_id)
+        if job is None or job.status in FINISHED_STATES:
+            return False
+        job.token.cancel()
+        logger.info(f"Cancellation requested for job {job_id} ({job.operation})")
+        return True
+
+    def cancel_all(self) -> int:
+        """Request cancellation of every unfinished job. Returns how many were cancelled."""
+        with self._lock:
+            jobs = [job for job in self._jobs.values() if job.status not in FINISHED_STATES]
+        for job in jobs:
+            job.token.cancel()
+        if jobs:
+            logger.info(f"Cancellation requested for {len(jobs)} job(s)")
+        return len(jobs)
+
+
+job_manager = JobManager()
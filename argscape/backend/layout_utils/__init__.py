@@ -0,0 +1,64 @@
+"""
+Layout utilities for ARGscape.
+"""
+
+from .incremental import relax_neighborhood, parse_positions, DEFAULT_NEIGHBORHOOD_HOPS, DEFAULT_RELAX_ITERATIONS
+from .layout3d import iter_layout_3d, camera_path, DEFAULT_3D_ITERATIONS, DEFAULT_SNAPSHOT_EVERY
+from .bundling import bundle_edges, polyline_lookup, DEFAULT_BUNDLING_CYCLES, DEFAULT_COMPATIBILITY_THRESHOLD, DEFAULT_MAX_NEIGHBORS
+from .labels import place_labels, label_priorities, DEFAULT_FONT_SIZE, DEFAULT_NODE_RADIUS
+from .picking import pick_indexes, PickIndex, QuadTree, PICK_KINDS
+from .tiles import tile_sets, TileSet, TILE_FORMATS, TILE_SIZE, MAX_TILE_ZOOM
+from .io import (
+    build_layout_document,
+    serialize_layout_document,
+    parse_layout_document,
+    check_layout_compatibility,
+    LAYOUT_EXPORT_FORMATS
+)
+
+__all__ = [
+    # Incremental layout
+    'relax_neighborhood',
+    'parse_positions',
+    'DEFAULT_NEIGHBORHOOD_HOPS',
+    'DEFAULT_RELAX_ITERATIONS',
+
+    # 3D layout
+    'iter_layout_3d',
+    'camera_path',
+    'DEFAULT_3D_ITERATIONS',
+    'DEFAULT_SNAPSHOT_EVERY',
+
+    # Edge bundling
+    'bundle_edges',
+    'polyline_lookup',
+    'DEFAULT_BUNDLING_CYCLES',
+    'DEFAULT_COMPATIBILITY_THRESHOLD',
+    'DEFAULT_MAX_NEIGHBORS',
+
+    # Label placement
+    'place_labels',
+    'label_priorities',
+    'DEFAULT_FONT_SIZE',
+    'DEFAULT_NODE_RADIUS',
+
+    # Hit-testing
+    'pick_indexes',
+    'PickIndex',
+    'QuadTree',
+    'PICK_KINDS',
+
+    # Tiles
+    'tile_sets',
+    'TileSet',
+    'TILE_FORMATS',
+    'TILE_SIZE',
+    'MAX_TILE_ZOOM',
+
+    # Sidecar I/O
+    'build_layout_document',
+    'serialize_layout_document',
+    'parse_layout_document',
+    'check_layout_compatibility',
+    'LAYOUT_EXPORT_FORMATS'
+]
@@ -0,0 +1,190 @@
+"""
+Incremental re-layout of a neighborhood of an existing ARG layout.
+Only nodes near the pinned/dragged nodes are re-solved; every other node keeps
+its position, so interactive edits stay cheap on huge layouts.
+"""
+
+import logging
+import math
+from typing import Any, Callable, Dict, Iterable, List, Optional, Set, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.timing import timed
+from argscape.backend.tskit_utils.traversal import adjacency_pairs, neighborhood
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_NEIGHBORHOOD_HOPS = 2
+DEFAULT_RELAX_ITERATIONS = 50
+MIN_DISTANCE = 1e-6
+REPULSION_CHUNK_SIZE = 512
+
+
+def parse_positions(ts: tskit.TreeSequence, positions: Any) -> Dict[int, Tuple[float, float]]:
+    """Client layout positions as {node ID: (x, y)}.
+
+    Raises ValueError unless every key is a node of `ts` and every value a
+    finite [x, y] pair of numbers.
+    """
+    if not isinstance(positions, dict):
+        raise ValueError("positions must map node IDs to [x, y] pairs")
+    parsed = {}
+    for key, position in positions.items():
+        try:
+            node_id = int(key)
+        except (TypeError, ValueError):
+            raise ValueError(f"positions: {key!r} is not a node ID")
+        if not 0 <= node_id < ts.num_nodes:
+            raise ValueError(f"positions: node {node_id} is not in the tree sequence")
+        if (
+            not isinstance(position, (list, tuple)) or len(position) != 2
+            or not all(isinstance(v, (int, float)) and not isinstance(v, bool) and math.isfinite(v) for v in position)
+        ):
+            raise ValueError(f"positions: node {node_id} needs an [x, y] pair of finite numbers")
+        parsed[node_id] = (float(position[0]), float(position[1]))
+    return parsed
+
+
+def get_layout_adjacency(ts: tskit.TreeSequence, nodes: Optional[Iterable[int]] = None) -> Dict[int, Set[int]]:
+    """Undirected adjacency between nodes connected by at least one edge, for edges touching `nodes` (default: all)."""
+    pairs = adjacency_pairs(ts)
+    if nodes is not None:
+        wanted = np.zeros(ts.num_nodes, dtype=bool)
+        wanted[np.fromiter(nodes, dtype=np.int64)] = True
+        pairs = pairs[wanted[pairs[:, 0]] | wanted[pairs[:, 1]]]
+    adjacency: Dict[int, Set[int]] = {}
+    for parent, child in pairs.tolist():
+        adjacency.setdefault(parent, set()).add(child)
+        adjacency.setdefault(child, set()).add(parent)
+    return adjacency
+
+
+def get_neighborhood(
+    ts: tskit.TreeSequence,
+    seeds: Iterable[int],
+    hops: int,
+    allowed: Set[int]
+) -> Set[int]:
+    """Nodes within `hops` edges of any seed, restricted to the `allowed` node set."""
+    mask = np.zeros(ts.num_nodes, dtype=bool)
+    mask[[u for u in allowed if 0 <= u < ts.num_nodes]] = True
+    return {int(u) for u in neighborhood(ts, [s for s in seeds if s in allowed], hops, mask)}
+
+
+@timed("layout")
+def relax_neighborhood(
+    ts: tskit.TreeSequence,
+    positions: Dict[int, Tuple[float, float]],
+    anchor_node_ids: List[int],
+    free_node_ids: Optional[List[int]] = None,
+    hops: int = DEFAULT_NEIGHBORHOOD_HOPS,
+    iterations: int = DEFAULT_RELAX_ITERATIONS,
+    lock_y: bool = True,
+    cancel_token: Optional[CancellationToken] = None,
+    progress: Optional[Callable[[float], None]] = None
+) -> Dict[str, object]:
+    """Re-solve node positions around pinned or dragged nodes.
+
+    A Fruchterman-Reingold style force relaxation is run on the free nodes
+    only; all other nodes act as fixed boundary conditions.
+
+    Args:
+        ts: Tree sequence whose edges define the graph
+        positions: Current layout positions keyed by node ID (only displayed nodes)
+        anchor_node_ids: Pinned/dragged nodes; they stay fixed at their given positions
+        free_node_ids: Explicit set of nodes to re-solve (e.g. a filtered region).
+            Defaults to the `hops`-neighborhood of the anchors.
+        hops: Neighborhood radius in edges when free_node_ids is not given
+        iterations: Number of relaxation iterations
+        lock_y: Keep y fixed, since the frontend places nodes vertically by time
+        cancel_token: Optional token checked once per iteration
+        progress: Optional callback given the completed fraction after each iteration
+
+    Returns:
+        Dictionary with the updated positions of the re-solved nodes.
+    """
+    displayed = set(positions)
+    anchors = {u for u in anchor_node_ids if u in displayed}
+
+    if free_node_ids is not None:
+        free = {u for u in free_node_ids if u in displayed} - anchors
+    else:
+        free = get_neighborhood(ts, anchors, hops, displayed) - anchors
+
+    if not free:
+        return {"positions": {}, "num_free_nodes": 0, "num_context_nodes": 0, "iterations": 0}
+
+    free_ids = sorted(free)
+    adjacency = get_layout_adjacency(ts, free_ids)
+    free_index = {u: i for i, u in enumerate(free_ids)}
+    pos = np.array([positions[u] for u in free_ids], dtype=float)
+
+    # Edges touching the free region; other endpoints act as fixed springs
+    region_edges = [(u, v) for u in free_ids for v in adjacency.get(u, ()) if v in displayed and (v not in free or u < v)]
+
+    # Nodes near the region provide repulsion but never move
+    lo = pos.min(axis=0)
+    hi = pos.max(axis=0)
+    span = max(float((hi - lo).max()), 1.0)
+    margin = 0.25 * span
+    context_ids = [
+        u for u in displayed - free
+        if lo[0] - margin <= positions[u][0] <= hi[0] + margin and lo[1] - margin <= positions[u][1] <= hi[1] + margin
+    ]
+    context_pos = np.array([positions[u] for u in context_ids], dtype=float).reshape(-1, 2)
+
+    edge_lengths = [
+        np.hypot(positions[u][0] - positions[v][0], positions[u][1] - positions[v][1])
+        for u, v in region_edges
+    ]
+    ideal_length = float(np.median(edge_lengths)) if edge_lengths else span / max(len(free_ids), 1)
+    ideal_length = max(ideal_length, MIN_DISTANCE)
+
+    temperature = ideal_length
+    cooling = temperature / max(iterations, 1)
+
+    for iteration in range(iterations):
+        check_cancelled(cancel_token)
+        displacement = np.zeros_like(pos)
+
+        # Repulsion from other free nodes and the fixed context, in row chunks
+        # to bound memory; a node's zero offset to itself contributes nothing
+        others = np.vstack([pos, context_pos])
+        for start in range(0, len(free_ids), REPULSION_CHUNK_SIZE):
+            delta = pos[start:start + REPULSION_CHUNK_SIZE, None, :] - others[None, :, :]
+            dist = np.maximum(np.linalg.norm(delta, axis=2), MIN_DISTANCE)
+            displacement[start:start + REPULSION_CHUNK_SIZE] += (
+                delta * ((ideal_length ** 2) / dist ** 2)[:, :, None]
+            ).sum(axis=1)
+
+        # Spring attraction along edges
+        for u, v in region_edges:
+            pu = pos[free_index[u]]
+            pv = pos[free_index[v]] if v in free_index else np.asarray(positions[v], dtype=float)
+            d = pu - pv
+            length = max(float(np.hypot(d[0], d[1])), MIN_DISTANCE)
+            force = d / length * (length ** 2) / ideal_length
+            displacement[free_index[u]] -= force
+            if v in free_index:
+                displacement[free_index[v]] += force
+
+        if lock_y:
+            displacement[:, 1] = 0.0
+
+        magnitude = np.maximum(np.linalg.norm(displacement, axis=1), MIN_DISTANCE)
+        pos += displacement / magnitude[:, None] * np.minimum(magnitude, temperature)[:, None]
+        temperature = max(temperature - cooling, ideal_length * 0.01)
+        if progress is not None:
+            progress((iteration + 1) / iterations)
+
+    logger.info(f"Relaxed {len(free_ids)} nodes against {len(context_ids)} fixed context nodes")
+
+    return {
+        "positions": {u: [float(pos[i, 0]), float(pos[i, 1])] for u, i in free_index.items()},
+        "num_free_nodes": len(free_ids),
+        "num_context_nodes": len(context_ids),
+        "iterations": iterations
+    }
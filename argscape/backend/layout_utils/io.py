@@ -0,0 +1,131 @@
+"""
+Layout sidecar files for reproducible figures.
+A sidecar stores the computed node positions together with the algorithm,
+parameters and seed used, plus a fingerprint of the tree sequence they belong to.
+"""
+
+import io
+import json
+import logging
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+
+import tskit
+
+from argscape.backend.tskit_utils import tree_sequence_fingerprint
+
+logger = logging.getLogger(__name__)
+
+LAYOUT_FORMAT_VERSION = 1
+LAYOUT_EXPORT_FORMATS = ("json", "parquet")
+
+
+def build_layout_document(
+    ts: tskit.TreeSequence,
+    filename: str,
+    positions: Dict[int, List[float]],
+    algorithm: str,
+    parameters: Optional[Dict[str, Any]] = None,
+    random_seed: Optional[int] = None
+) -> Dict[str, Any]:
+    """Build a layout sidecar document for the given positions."""
+    return {
+        "format": "argscape-layout",
+        "version": LAYOUT_FORMAT_VERSION,
+        "created_at": datetime.now().isoformat(),
+        "source_filename": filename,
+        "tree_sequence_fingerprint": tree_sequence_fingerprint(ts),
+        "num_nodes": ts.num_nodes,
+        "algorithm": algorithm,
+        "parameters": parameters or {},
+        "random_seed": random_seed,
+        "positions": {str(node_id): list(position) for node_id, position in positions.items()},
+    }
+
+
+def serialize_layout_document(document: Dict[str, Any], format: str = "json") -> bytes:
+    """Serialize a layout document as JSON or Parquet bytes."""
+    if format == "json":
+        return json.dumps(document, indent=2).encode("utf-8")
+
+    if format == "parquet":
+        try:
+            import pyarrow as pa
+            import pyarrow.parquet as pq
+        except ImportError:
+            raise ValueError("Parquet export requires the pyarrow package")
+
+        node_ids = [int(node_id) for node_id in document["positions"]]
+        coordinates = list(document["positions"].values())
+        num_dims = max((len(c) for c in coordinates), default=2)
+        columns = {"node_id": node_ids}
+        for dim, name in enumerate(["x", "y", "z"][:num_dims]):
+            columns[name] = [c[dim] if dim < len(c) else 0.0 for c in coordinates]
+
+        table = pa.table(columns)
+        header = {k: v for k, v in document.items() if k != "positions"}
+        table = table.replace_schema_metadata({"argscape_layout": json.dumps(header)})
+        buffer = io.BytesIO()
+        pq.write_table(table, buffer)
+        return buffer.getvalue()
+
+    raise ValueError(f"Unknown layout format '{format}'. Expected one of {LAYOUT_EXPORT_FORMATS}")
+
+
+def parse_layout_document(contents: bytes, filename: str) -> Dict[str, Any]:
+    """Parse a layout sidecar previously written by serialize_layout_document."""
+    if filename.lower().endswith(".parquet"):
+        try:
+            import pyarrow.parquet as pq
+        except ImportError:
+            raise ValueError("Parquet import requires the pyarrow package")
+
+        table = pq.read_table(io.BytesIO(contents))
+        metadata = table.schema.metadata or {}
+        if b"argscape_layout" not in metadata:
+            raise ValueError(f"{filename} is not an ARGscape layout file")
+        document = json.loads(metadata[b"argscape_layout"])
+        if not isinstance(document, dict):
+            raise ValueError(f"{filename} is not an ARGscape layout file: its layout metadata is not a JSON object")
+        columns = table.to_pydict()
+        dims = [name for name in ("x", "y", "z") if name in columns]
+        document["positions"] = {
+            str(node_id): [columns[dim][i] for dim in dims]
+            for i, node_id in enumerate(columns["node_id"])
+        }
+    else:
+        try:
+            document = json.loads(contents.decode("utf-8"))
+        except (UnicodeDecodeError, json.JSONDecodeError) as e:
+            raise ValueError(f"Could not parse layout file {filename}: {e}")
+
+    if not isinstance(document, dict):
+        raise ValueError(f"{filename} is not an ARGscape layout file: expected a JSON object, found {type(document).__name__}")
+    if document.get("format") != "argscape-layout" or not isinstance(document.get("positions"), dict):
+        raise ValueError(f"{filename} is not an ARGscape layout file")
+    if not isinstance(document.get("version", 0), (int, float)):
+        raise ValueError(f"{filename} has an invalid layout version")
+    if document.get("version", 0) > LAYOUT_FORMAT_VERSION:
+        raise ValueError(f"Layout file version {document['version']} is newer than supported ({LAYOUT_FORMAT_VERSION})")
+
+    return document
+
+
+def check_layout_compatibility(document: Dict[str, Any], ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """Check whether a layout document can be applied to a tree sequence."""
+    node_ids = [int(node_id) for node_id in document["positions"]]
+    out_of_range = [u for u in node_ids if u < 0 or u >= ts.num_nodes]
+    fingerprint_matches = document.get("tree_sequence_fingerprint") == tree_sequence_fingerprint(ts)
+
+    warnings = []
+    if not fingerprint_matches:
+        warnings.append("Layout was computed for a different tree sequence; positions may not match")
+    if out_of_range:
+        warnings.append(f"{len(out_of_range)} positions refer to nodes not present in this tree sequence")
+
+    return {
+        "fingerprint_matches": fingerprint_matches,
+        "num_positions": len(node_ids),
+        "num_out_of_range": len(out_of_range),
+        "warnings": warnings,
+    }
@@ -0,0 +1,144 @@
+"""
+Overlap-free node label placement.
+A greedy pass in priority order: each label tries a few candidate positions
+around its node (below, right, above, left) and takes the first one that
+overlaps neither an already placed label nor another node; labels with no
+free position are hidden. Placement happens in screen space, so the result
+depends on the zoom level: zooming in spreads the nodes apart and more labels
+fit. A uniform grid indexes placed boxes, so each label only checks its
+neighbours and the pass stays close to linear in the number of nodes.
+"""
+
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+import numpy as np
+import tskit
+
+DEFAULT_FONT_SIZE = 10.0
+DEFAULT_NODE_RADIUS = 5.0
+# Average glyph width as a fraction of the font size
+CHAR_WIDTH_RATIO = 0.6
+LABEL_GAP = 2.0
+# (name, text-anchor); offsets are worked out from the node radius and label size
+CANDIDATE_POSITIONS = (("below", "middle"), ("right", "start"), ("above", "middle"), ("left", "end"))
+
+Box = Tuple[float, float, float, float]
+
+
+def _overlaps(a: Box, b: Box) -> bool:
+    return a[0] < b[2] and b[0] < a[2] and a[1] < b[3] and b[1] < a[3]
+
+
+class _BoxGrid:
+    """Uniform grid of boxes for neighbour-only overlap checks."""
+
+    def __init__(self, cell_size: float):
+        self.cell_size = max(cell_size, 1.0)
+        self.cells: Dict[Tuple[int, int], List[Box]] = {}
+
+    def _cells(self, box: Box):
+        size = self.cell_size
+        for cx in range(int(box[0] // size), int(box[2] // size) + 1):
+            for cy in range(int(box[1] // size), int(box[3] // size) + 1):
+                yield cx, cy
+
+    def add(self, box: Box):
+        for cell in self._cells(box):
+            self.cells.setdefault(cell, []).append(box)
+
+    def collides(self, box: Box, ignore: Optional[Box] = None) -> bool:
+        for cell in self._cells(box):
+            for other in self.cells.get(cell, ()):
+                if other is not ignore and _overlaps(box, other):
+                    return True
+        return False
+
+
+def _candidate(name: str, x: float, y: float, width: float, height: float, radius: float) -> Tuple[Box, float, float]:
+    """Screen-space box and (dx, dy) text offset for one candidate position."""
+    offset = radius + LABEL_GAP
+    if name == "below":
+        return (x - width / 2, y + offset, x + width / 2, y + offset + height), 0.0, offset + height
+    if name == "above":
+        return (x - width / 2, y - offset - height, x + width / 2, y - offset), 0.0, -offset
+    if name == "right":
+        return (x + offset, y - height / 2, x + offset + width, y + height / 2), offset, height / 3
+    return (x - offset - width, y - height / 2, x - offset, y + height / 2), -offset, height / 3
+
+
+def label_priorities(ts: tskit.TreeSequence, node_ids: Sequence[int]) -> Dict[int, float]:
+    """Importance of each node: span-weighted descendant samples, with degree as a tie-breaker."""
+    from argscape.backend.tskit_utils.node_metrics import compute_node_metrics
+
+    metrics = compute_node_metrics(ts, include_betweenness=False)
+    descendants = np.asarray(metrics["descendant_samples"], dtype=float)
+    span = np.asarray(metrics["genomic_span"], dtype=float) / (ts.sequence_length or 1.0)
+    degree = np.asarray(metrics["degree"], dtype=float)
+    return {int(u): float(descendants[u] * span[u] + 1e-3 * degree[u]) for u in node_ids}
+
+
+def place_labels(
+    positions: Dict[int, Sequence[float]],
+    texts: Optional[Dict[int, str]] = None,
+    priorities: Optional[Dict[int, float]] = None,
+    zoom: float = 1.0,
+    font_size: float = DEFAULT_FONT_SIZE,
+    node_radius: float = DEFAULT_NODE_RADIUS,
+    viewport: Optional[Sequence[float]] = None,
+    candidate_node_ids: Optional[Sequence[int]] = None,
+    max_labels: Optional[int] = None,
+    avoid_nodes: bool = True
+) -> Dict[str, Any]:
+    """Choose which labels to show at this zoom and where.
+
+    Args:
+        positions: Node ID -> [x, y] in layout (world) coordinates
+        texts: Node ID -> label text; defaults to the node ID
+        priorities: Higher is placed first; defaults to node ID order
+        zoom: World-to-screen scale; font size and radii are in screen pixels
+        viewport: [min_x, min_y, max_x, max_y] in world coordinates; nodes
+            outside are skipped
+        candidate_node_ids: Nodes that may get a label (default: all)
+        max_labels: Stop after this many labels
+        avoid_nodes: Also keep labels off other nodes' circles
+
+    Returns:
+        {"labels": [{"node_id", "dx", "dy", "anchor", "position"}], "num_candidates", "num_hidden"},
+        with dx/dy the text offset from the node in screen pixels
+    """
+    if zoom <= 0:
+        raise ValueError("zoom must be positive")
+    if font_size <= 0:
+        raise ValueError("font_size must be positive")
+    texts = texts or {}
+    candidates = [int(u) for u in (candidate_node_ids if candidate_node_ids is not None else positions) if int(u) in positions]
+    if viewport is not None:
+        min_x, min_y, max_x, max_y = viewport
+        candidates = [u for u in candidates if min_x <= positions[u][0] <= max_x and min_y <= positions[u][1] <= max_y]
+    priorities = priorities or {}
+    candidates.sort(key=lambda u: (-priorities.get(u, 0.0), u))
+
+    screen = {int(u): (float(p[0]) * zoom, float(p[1]) * zoom) for u, p in positions.items()}
+    grid = _BoxGrid(font_size * 4)
+    node_boxes: Dict[int, Box] = {}
+    if avoid_nodes:
+        for u, (x, y) in screen.items():
+            box = (x - node_radius, y - node_radius, x + node_radius, y + node_radius)
+            node_boxes[u] = box
+            grid.add(box)
+
+    placed: List[Dict[str, Any]] = []
+    for u in candidates:
+        if max_labels is not None and len(placed) >= max_labels:
+            break
+        text = texts.get(u, str(u))
+        width = max(len(text), 1) * font_size * CHAR_WIDTH_RATIO
+        x, y = screen[u]
+        for name, anchor in CANDIDATE_POSITIONS:
+            box, dx, dy = _candidate(name, x, y, width, font_size, node_radius)
+            if not grid.collides(box, ignore=node_boxes.get(u)):
+                grid.add(box)
+                placed.append({"node_id": u, "dx": round(dx, 2), "dy": round(dy, 2), "anchor": anchor, "position": name})
+                break
+
+    return {"labels": placed, "num_candidates": len(candidates), "num_hidden": len(candidates) - len(placed)}
@@ -0,0 +1,151 @@
+"""
+3D ARG layout with time on the z-axis.
+Node heights are fixed by time; x/y are solved with a Fruchterman-Reingold
+style force relaxation (springs along edges, repulsion between all nodes),
+so clades spread out in the plane while the time structure stays exact.
+Positions are produced as a series of snapshots so the viewer can show the
+layout settling, and a camera path orbiting the result is generated for
+turntable views and recordings.
+"""
+
+import logging
+import math
+from typing import Any, Dict, Iterator, List, Optional
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_3D_ITERATIONS = 150
+DEFAULT_SNAPSHOT_EVERY = 10
+DEFAULT_CAMERA_KEYFRAMES = 60
+# Repulsion is all-pairs, so larger graphs should be simplified first
+MAX_3D_LAYOUT_NODES = 20_000
+REPULSION_CHUNK_SIZE = 512
+MIN_DISTANCE = 1e-6
+
+
+def _normalized_times(ts: tskit.TreeSequence, node_ids: np.ndarray, log_time: bool) -> np.ndarray:
+    times = ts.tables.nodes.time[node_ids].astype(float)
+    if log_time:
+        times = np.log10(times + 1)
+    top = times.max() if len(times) else 0
+    return times / top if top > 0 else times
+
+
+def iter_layout_3d(
+    ts: tskit.TreeSequence,
+    iterations: int = DEFAULT_3D_ITERATIONS,
+    snapshot_every: int = DEFAULT_SNAPSHOT_EVERY,
+    random_seed: Optional[int] = None,
+    log_time: bool = False,
+    cancel_token: Optional[CancellationToken] = None
+) -> Iterator[Dict[str, Any]]:
+    """Solve the layout, yielding {"iteration", "positions": {node ID: [x, y, z]}, "final"} snapshots.
+
+    Only nodes on at least one edge (plus samples) are placed. x/y lie roughly
+    in [-1, 1] and z in [0, 1], with 0 the present.
+    """
+    if iterations < 1:
+        raise ValueError("iterations must be at least 1")
+    if snapshot_every < 1:
+        raise ValueError("snapshot_every must be at least 1")
+    connected = np.union1d(np.union1d(ts.edges_parent, ts.edges_child), ts.samples())
+    if len(connected) > MAX_3D_LAYOUT_NODES:
+        raise ValueError(
+            f"3D layout supports up to {MAX_3D_LAYOUT_NODES:,} nodes; this ARG has {len(connected):,}. "
+            f"Simplify it or reduce the number of samples first"
+        )
+    node_ids = connected.astype(np.int64)
+    index = {int(u): i for i, u in enumerate(node_ids)}
+    edges = np.array([(index[int(p)], index[int(c)]) for p, c in zip(ts.edges_parent, ts.edges_child)], dtype=np.int64).reshape(-1, 2)
+    # One spring per connected pair, however many edges (genome intervals) join them
+    edges = np.unique(edges, axis=0)
+    z = _normalized_times(ts, node_ids, log_time)
+
+    rng = np.random.default_rng(random_seed)
+    n = len(node_ids)
+    xy = rng.uniform(-1, 1, size=(n, 2))
+    ideal_length = 1.0 / math.sqrt(max(n, 1))
+    temperature = 0.1
+    cooling = temperature / iterations
+
+    def snapshot(iteration: int, final: bool) -> Dict[str, Any]:
+        return {
+            "iteration": iteration,
+            "num_iterations": iterations,
+            "final": final,
+            "positions": {int(u): [float(xy[i, 0]), float(xy[i, 1]), float(z[i])] for i, u in enumerate(node_ids)},
+        }
+
+    for iteration in range(1, iterations + 1):
+        check_cancelled(cancel_token)
+        displacement = np.zeros_like(xy)
+
+        # Repulsion in the plane, in row chunks to bound memory
+        for start in range(0, n, REPULSION_CHUNK_SIZE):
+            delta = xy[start:start + REPULSION_CHUNK_SIZE, None, :] - xy[None, :, :]
+            dist = np.maximum(np.linalg.norm(delta, axis=2), MIN_DISTANCE)
+            displacement[start:start + REPULSION_CHUNK_SIZE] += (delta * ((ideal_length ** 2) / dist ** 2)[:, :, None]).sum(axis=1)
+
+        # Springs measure full 3D length, so edges spanning long times pull less in x/y
+        if len(edges):
+            delta = xy[edges[:, 0]] - xy[edges[:, 1]]
+            dist3d = np.maximum(np.sqrt((delta ** 2).sum(axis=1) + (z[edges[:, 0]] - z[edges[:, 1]]) ** 2), MIN_DISTANCE)
+            pull = delta * (dist3d / ideal_length)[:, None]
+            np.subtract.at(displacement, edges[:, 0], pull)
+            np.add.at(displacement, edges[:, 1], pull)
+
+        lengths = np.maximum(np.linalg.norm(displacement, axis=1), MIN_DISTANCE)
+        xy += displacement / lengths[:, None] * np.minimum(lengths, temperature)[:, None]
+        temperature = max(temperature - cooling, 1e-4)
+
+        if iteration % snapshot_every == 0 or iteration == iterations:
+            # Keep the layout centred and within [-1, 1] so the camera path has a stable frame
+            xy -= xy.mean(axis=0)
+            extent = np.abs(xy).max() or 1.0
+            xy /= extent
+            yield snapshot(iteration, iteration == iterations)
+
+
+def camera_path(
+    positions: Dict[int, List[float]],
+    num_keyframes: int = DEFAULT_CAMERA_KEYFRAMES,
+    elevation_degrees: float = 25.0,
+    duration_seconds: float = 12.0
+) -> List[Dict[str, Any]]:
+    """Keyframes of one turntable orbit around the layout, looking at its centre.
+
+    Each keyframe has a world-space camera `position` and `target` (for glTF and
+    Blender) and the equivalent orbit angles `rotation_orbit`/`rotation_x` in
+    degrees (for deck.gl's OrbitView).
+    """
+    if num_keyframes < 2:
+        raise ValueError("num_keyframes must be at least 2")
+    points = np.array(list(positions.values()), dtype=float).reshape(-1, 3)
+    if not len(points):
+        raise ValueError("No positions to frame")
+    centre = (points.min(axis=0) + points.max(axis=0)) / 2
+    radius = float(np.linalg.norm(points - centre, axis=1).max()) or 1.0
+    distance = 2.5 * radius
+    elevation = math.radians(elevation_degrees)
+    keyframes = []
+    for k in range(num_keyframes):
+        angle = 2 * math.pi * k / (num_keyframes - 1)
+        offset = distance * np.array([
+            math.cos(elevation) * math.sin(angle),
+            math.cos(elevation) * -math.cos(angle),
+            math.sin(elevation),
+        ])
+        keyframes.append({
+            "time": duration_seconds * k / (num_keyframes - 1),
+            "position": [float(v) for v in centre + offset],
+            "target": [float(v) for v in centre],
+            "rotation_orbit": math.degrees(angle),
+            "rotation_x": elevation_degrees,
+            "distance": distance,
+        })
+    return keyframes
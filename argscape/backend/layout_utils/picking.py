@@ -0,0 +1,212 @@
+"""
+Quadtree hit-testing for laid-out ARGs.
+The viewer uploads its node positions once per layout; nodes (as points) and
+edges (as segments, stored by bounding box) go into a quadtree, and each
+pointer event becomes a `pick(x, y, radius)` query that only looks at the
+few items near the cursor. The answer carries the details a tooltip or
+selection needs (parents, children, genome intervals), so the frontend no
+longer scans every node and edge on hover.
+"""
+
+import logging
+import threading
+from collections import OrderedDict
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+import numpy as np
+import tskit
+
+logger = logging.getLogger(__name__)
+
+QUADTREE_MAX_ITEMS = 16
+QUADTREE_MAX_DEPTH = 16
+MAX_PICK_INDEXES = 16
+PICK_KINDS = ("node", "edge")
+
+Box = Tuple[float, float, float, float]
+
+
+class QuadTree:
+    """Region quadtree over bounding boxes; items spanning a split stay at the parent."""
+
+    def __init__(self, bounds: Box, depth: int = 0):
+        self.bounds = bounds
+        self.depth = depth
+        self.items: List[Tuple[int, Box]] = []
+        self.children: Optional[List["QuadTree"]] = None
+
+    def _child_for(self, box: Box) -> Optional["QuadTree"]:
+        for child in self.children or ():
+            x0, y0, x1, y1 = child.bounds
+            if box[0] >= x0 and box[2] <= x1 and box[1] >= y0 and box[3] <= y1:
+                return child
+        return None
+
+    def _split(self):
+        x0, y0, x1, y1 = self.bounds
+        mx, my = (x0 + x1) / 2, (y0 + y1) / 2
+        self.children = [
+            QuadTree((x0, y0, mx, my), self.depth + 1),
+            QuadTree((mx, y0, x1, my), self.depth + 1),
+            QuadTree((x0, my, mx, y1), self.depth + 1),
+            QuadTree((mx, my, x1, y1), self.depth + 1),
+        ]
+        items, self.items = self.items, []
+        for item in items:
+            self.insert(*item)
+
+    def insert(self, item: int, box: Box):
+        if self.children is not None:
+            child = self._child_for(box)
+            if child is not None:
+                child.insert(item, box)
+                return
+        self.items.append((item, box))
+        if self.children is None and len(self.items) > QUADTREE_MAX_ITEMS and self.depth < QUADTREE_MAX_DEPTH:
+            self._split()
+
+    def query(self, box: Box, found: Optional[List[int]] = None) -> List[int]:
+        """Items whose bounding box intersects `box`."""
+        found = [] if found is None else found
+        x0, y0, x1, y1 = self.bounds
+        if box[0] > x1 or box[2] < x0 or box[1] > y1 or box[3] < y0:
+            return found
+        for item, item_box in self.items:
+            if item_box[0] <= box[2] and box[0] <= item_box[2] and item_box[1] <= box[3] and box[1] <= item_box[3]:
+                found.append(item)
+        for child in self.children or ():
+            child.query(box, found)
+        return found
+
+    def max_depth(self) -> int:
+        return max([self.depth] + [child.max_depth() for child in self.children or ()])
+
+
+def _segment_distance(px: float, py: float, a: Sequence[float], b: Sequence[float]) -> float:
+    dx, dy = b[0] - a[0], b[1] - a[1]
+    length_squared = dx * dx + dy * dy
+    t = 0.0 if length_squared == 0 else max(0.0, min(1.0, ((px - a[0]) * dx + (py - a[1]) * dy) / length_squared))
+    return float(np.hypot(px - (a[0] + t * dx), py - (a[1] + t * dy)))
+
+
+class PickIndex:
+    """Nodes and edges of one layout, indexed for nearest-item queries."""
+
+    def __init__(self, ts: tskit.TreeSequence, positions: Dict[int, Sequence[float]]):
+        self.ts = ts
+        self.positions = {int(u): (float(p[0]), float(p[1])) for u, p in positions.items()}
+        if not self.positions:
+            raise ValueError("No positions to index")
+
+        # One segment per connected (parent, child) pair, with all its genome intervals
+        self.intervals: Dict[Tuple[int, int], List[Tuple[float, float]]] = {}
+        self.parents: Dict[int, set] = {}
+        self.children: Dict[int, set] = {}
+        for edge in ts.edges():
+            if edge.parent in self.positions and edge.child in self.positions:
+                self.intervals.setdefault((edge.parent, edge.child), []).append((edge.left, edge.right))
+            self.parents.setdefault(edge.child, set()).add(edge.parent)
+            self.children.setdefault(edge.parent, set()).add(edge.child)
+        self.edges = sorted(self.intervals)
+
+        xs = [p[0] for p in self.positions.values()]
+        ys = [p[1] for p in self.positions.values()]
+        self.nodes = QuadTree((min(xs), min(ys), max(xs), max(ys)))
+        for u, (x, y) in self.positions.items():
+            self.nodes.insert(u, (x, y, x, y))
+        self.segments = QuadTree((min(xs), min(ys), max(xs), max(ys)))
+        for i, (parent, child) in enumerate(self.edges):
+            (x1, y1), (x2, y2) = self.positions[parent], self.positions[child]
+            self.segments.insert(i, (min(x1, x2), min(y1, y2), max(x1, x2), max(y1, y2)))
+
+    def stats(self) -> Dict[str, Any]:
+        return {
+            "num_nodes": len(self.positions),
+            "num_edges": len(self.edges),
+            "node_tree_depth": self.nodes.max_depth(),
+            "edge_tree_depth": self.segments.max_depth(),
+        }
+
+    def node_details(self, u: int) -> Dict[str, Any]:
+        node = self.ts.node(u)
+        return {
+            "kind": "node",
+            "id": u,
+            "time": node.time,
+            "population": node.population,
+            "individual": node.individual,
+            "flags": node.flags,
+            "is_sample": node.is_sample(),
+            "parents": sorted(self.parents.get(u, ())),
+            "children": sorted(self.children.get(u, ())),
+            "position": list(self.positions[u]),
+        }
+
+    def edge_details(self, i: int) -> Dict[str, Any]:
+        parent, child = self.edges[i]
+        intervals = self.intervals[(parent, child)]
+        return {
+            "kind": "edge",
+            "parent": parent,
+            "child": child,
+            "intervals": [[left, right] for left, right in intervals],
+            "span": sum(right - left for left, right in intervals),
+        }
+
+    def pick(self, x: float, y: float, radius: float, kinds: Sequence[str] = PICK_KINDS) -> Optional[Dict[str, Any]]:
+        """Nearest node within `radius` of (x, y), else nearest edge, with its details and distance."""
+        if radius < 0:
+            raise ValueError("radius must be non-negative")
+        box = (x - radius, y - radius, x + radius, y + radius)
+        if "node" in kinds:
+            best = None
+            for u in self.nodes.query(box):
+                px, py = self.positions[u]
+                distance = float(np.hypot(px - x, py - y))
+                if distance <= radius and (best is None or distance < best[0]):
+                    best = (distance, u)
+            if best is not None:
+                return {**self.node_details(best[1]), "distance": best[0]}
+        if "edge" in kinds:
+            best = None
+            for i in self.segments.query(box):
+                parent, child = self.edges[i]
+                distance = _segment_distance(x, y, self.positions[parent], self.positions[child])
+                if distance <= radius and (best is None or distance < best[0]):
+                    best = (distance, i)
+            if best is not None:
+                return {**self.edge_details(best[1]), "distance": best[0]}
+        return None
+
+
+class PickIndexRegistry:
+    """Most recently built pick indexes, per session and file."""
+
+    def __init__(self, max_indexes: int = MAX_PICK_INDEXES):
+        self.max_indexes = max_indexes
+        self._indexes: "OrderedDict[Tuple[str, str], PickIndex]" = OrderedDict()
+        self._lock = threading.Lock()
+
+    def build(self, session_id: str, filename: str, ts: tskit.TreeSequence, positions: Dict[int, Sequence[float]]) -> PickIndex:
+        index = PickIndex(ts, positions)
+        with self._lock:
+            self._indexes[(session_id, filename)] = index
+            self._indexes.move_to_end((session_id, filename))
+            while len(self._indexes) > self.max_indexes:
+                self._indexes.popitem(last=False)
+        logger.info(f"Built pick index for {filename}: {index.stats()}")
+        return index
+
+    def get(self, session_id: str, filename: str) -> Optional[PickIndex]:
+        with self._lock:
+            index = self._indexes.get((session_id, filename))
+            if index is not None:
+                self._indexes.move_to_end((session_id, filename))
+            return index
+
+    def drop(self, session_id: str, filename: str):
+        with self._lock:
+            self._indexes.pop((session_id, filename), None)
+
+
+pick_indexes = PickIndexRegistry()
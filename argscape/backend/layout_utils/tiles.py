@@ -0,0 +1,194 @@
+"""
+Zoom-level tiles of a laid-out ARG for slippy-map style renderers.
+The layout is fitted into a square world that zoom level z splits into
+2^z x 2^z tiles of TILE_SIZE pixels. Each tile holds the nodes inside it and
+the edges crossing it, in tile pixel coordinates, either as JSON (vector) or
+as a PNG (pre-rasterized). Tiles are thinned to what is distinguishable at
+their zoom: nodes sharing a pixel cell collapse to one (samples first) and
+edges between the same pair of cells are drawn once, so even a tile covering
+the whole of a huge ARG stays small.
+"""
+
+import io
+import logging
+import threading
+from collections import OrderedDict
+from typing import Any, Dict, Optional, Sequence, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.palettes import resolve_palette
+
+logger = logging.getLogger(__name__)
+
+TILE_SIZE = 256
+MAX_TILE_ZOOM = 12
+TILE_FORMATS = ("json", "png")
+# Nodes closer than this many tile pixels are merged
+NODE_CELL_PX = 2
+EDGE_CELL_PX = 2
+MAX_CACHED_TILES = 512
+MAX_TILE_SETS = 8
+UNASSIGNED_COLOR = "#95a5a6"
+EDGE_COLOR = "#888888"
+
+
+class TileSet:
+    """One layout, ready to be cut into tiles on demand."""
+
+    def __init__(self, ts: tskit.TreeSequence, positions: Dict[int, Sequence[float]], palette_choice: Optional[Dict[str, Any]] = None):
+        if not positions:
+            raise ValueError("No positions to tile")
+        self.node_ids = np.array(sorted(int(u) for u in positions), dtype=np.int64)
+        self.xy = np.array([positions[int(u)][:2] for u in self.node_ids], dtype=float)
+        nodes = ts.tables.nodes
+        self.population = nodes.population[self.node_ids].astype(np.int64)
+        self.is_sample = (nodes.flags[self.node_ids] & tskit.NODE_IS_SAMPLE) != 0
+        # Samples come first so they survive cell merging
+        self.order = np.lexsort((self.node_ids, ~self.is_sample))
+
+        index = {int(u): i for i, u in enumerate(self.node_ids)}
+        pairs = {(index[int(p)], index[int(c)]) for p, c in zip(ts.edges_parent, ts.edges_child) if int(p) in index and int(c) in index}
+        self.edges = np.array(sorted(pairs), dtype=np.int64).reshape(-1, 2)
+
+        low = self.xy.min(axis=0)
+        extent = float((self.xy.max(axis=0) - low).max()) or 1.0
+        padding = extent * 0.02
+        self.origin = low - padding
+        self.world_size = extent + 2 * padding
+
+        palette = resolve_palette(ts, palette_choice)
+        self.colors = {p["id"]: p["color"] for p in palette["populations"]}
+        self._tiles: "OrderedDict[Tuple[int, int, int, str, int], Any]" = OrderedDict()
+        self._lock = threading.Lock()
+
+    def metadata(self) -> Dict[str, Any]:
+        return {
+            "tile_size": TILE_SIZE,
+            "max_zoom": MAX_TILE_ZOOM,
+            "formats": list(TILE_FORMATS),
+            "origin": self.origin.tolist(),
+            "world_size": self.world_size,
+            "num_nodes": len(self.node_ids),
+            "num_edges": len(self.edges),
+            "population_colors": {str(k): v for k, v in self.colors.items()},
+        }
+
+    def _to_tile(self, points: np.ndarray, z: int, x: int, y: int) -> np.ndarray:
+        """Layout coordinates -> pixel coordinates within tile (z, x, y)."""
+        scale = TILE_SIZE * (2 ** z) / self.world_size
+        return (points - self.origin) * scale - np.array([x, y]) * TILE_SIZE
+
+    def vector_tile(self, z: int, x: int, y: int, cell_scale: int = 1) -> Dict[str, Any]:
+        """Nodes as [id, px, py, population] and edges as [parent, child, x1, y1, x2, y2], in tile pixels.
+
+        `cell_scale` widens the merge cells for coarser tiles (see argscape.backend.frame_budget).
+        """
+        if not 0 <= z <= MAX_TILE_ZOOM:
+            raise ValueError(f"z must be between 0 and {MAX_TILE_ZOOM}")
+        if not (0 <= x < 2 ** z and 0 <= y < 2 ** z):
+            raise ValueError(f"Tile ({x}, {y}) is outside zoom level {z}")
+
+        pixels = self._to_tile(self.xy, z, x, y)
+        inside = np.all((pixels >= 0) & (pixels < TILE_SIZE), axis=1)
+        candidates = self.order[inside[self.order]]
+        cells = np.floor(pixels[candidates] / (NODE_CELL_PX * cell_scale)).astype(np.int64)
+        _, first = np.unique(cells[:, 0] * (TILE_SIZE + 1) + cells[:, 1], return_index=True)
+        keep = candidates[np.sort(first)]
+
+        tile_nodes = [
+            [int(self.node_ids[i]), round(float(pixels[i, 0]), 1), round(float(pixels[i, 1]), 1), int(self.population[i])]
+            for i in keep
+        ]
+
+        tile_edges = []
+        if len(self.edges):
+            start, end = pixels[self.edges[:, 0]], pixels[self.edges[:, 1]]
+            low, high = np.minimum(start, end), np.maximum(start, end)
+            crossing = np.all((high >= 0) & (low < TILE_SIZE), axis=1)
+            indices = np.nonzero(crossing)[0]
+            if len(indices):
+                cells = np.floor(np.concatenate([start[indices], end[indices]], axis=1) / (EDGE_CELL_PX * cell_scale)).astype(np.int64)
+                _, first = np.unique(cells, axis=0, return_index=True)
+                for i in indices[np.sort(first)]:
+                    parent, child = self.edges[i]
+                    tile_edges.append([
+                        int(self.node_ids[parent]), int(self.node_ids[child]),
+                        round(float(start[i, 0]), 1), round(float(start[i, 1]), 1),
+                        round(float(end[i, 0]), 1), round(float(end[i, 1]), 1),
+                    ])
+
+        return {"z": z, "x": x, "y": y, "tile_size": TILE_SIZE, "nodes": tile_nodes, "edges": tile_edges}
+
+    def raster_tile(self, z: int, x: int, y: int, cell_scale: int = 1) -> bytes:
+        """The vector tile drawn to a transparent PNG."""
+        import matplotlib
+        matplotlib.use("Agg")
+        import matplotlib.pyplot as plt
+        from matplotlib.collections import LineCollection
+
+        tile = self.vector_tile(z, x, y, cell_scale)
+        dpi = 64
+        fig = plt.figure(figsize=(TILE_SIZE / dpi, TILE_SIZE / dpi), dpi=dpi)
+        try:
+            ax = fig.add_axes([0, 0, 1, 1])
+            ax.set_xlim(0, TILE_SIZE)
+            ax.set_ylim(TILE_SIZE, 0)  # Pixel rows grow downwards
+            ax.axis("off")
+            if tile["edges"]:
+                segments = [((e[2], e[3]), (e[4], e[5])) for e in tile["edges"]]
+                ax.add_collection(LineCollection(segments, colors=EDGE_COLOR, linewidths=0.6, alpha=0.6))
+            if tile["nodes"]:
+                points = np.array([[n[1], n[2]] for n in tile["nodes"]])
+                colors = [self.colors.get(n[3], UNASSIGNED_COLOR) for n in tile["nodes"]]
+                ax.scatter(points[:, 0], points[:, 1], s=max(2, min(16, z * 2)), c=colors, linewidths=0, zorder=2)
+            buffer = io.BytesIO()
+            fig.savefig(buffer, format="png", transparent=True, dpi=dpi)
+            return buffer.getvalue()
+        finally:
+            plt.close(fig)
+
+    def tile(self, z: int, x: int, y: int, tile_format: str = "json", cell_scale: int = 1):
+        if tile_format not in TILE_FORMATS:
+            raise ValueError(f"format must be one of {TILE_FORMATS}")
+        key = (z, x, y, tile_format, cell_scale)
+        with self._lock:
+            if key in self._tiles:
+                self._tiles.move_to_end(key)
+                return self._tiles[key]
+        result = self.vector_tile(z, x, y, cell_scale) if tile_format == "json" else self.raster_tile(z, x, y, cell_scale)
+        with self._lock:
+            self._tiles[key] = result
+            while len(self._tiles) > MAX_CACHED_TILES:
+                self._tiles.popitem(last=False)
+        return result
+
+
+class TileSetRegistry:
+    """Most recently built tile sets, per session and file."""
+
+    def __init__(self, max_tile_sets: int = MAX_TILE_SETS):
+        self.max_tile_sets = max_tile_sets
+        self._tile_sets: "OrderedDict[Tuple[str, str], TileSet]" = OrderedDict()
+        self._lock = threading.Lock()
+
+    def build(self, session_id: str, filename: str, ts: tskit.TreeSequence, positions: Dict[int, Sequence[float]], palette_choice: Optional[Dict[str, Any]] = None) -> TileSet:
+        tile_set = TileSet(ts, positions, palette_choice)
+        with self._lock:
+            self._tile_sets[(session_id, filename)] = tile_set
+            self._tile_sets.move_to_end((session_id, filename))
+            while len(self._tile_sets) > self.max_tile_sets:
+                self._tile_sets.popitem(last=False)
+        logger.info(f"Built tile set for {filename}: {len(tile_set.node_ids)} nodes, {len(tile_set.edges)} edges")
+        return tile_set
+
+    def get(self, session_id: str, filename: str) -> Optional[TileSet]:
+        with self._lock:
+            tile_set = self._tile_sets.get((session_id, filename))
+            if tile_set is not None:
+                self._tile_sets.move_to_end((session_id, filename))
+            return tile_set
+
+
+tile_sets = TileSetRegistry()
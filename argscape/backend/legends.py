@@ -0,0 +1,121 @@
+"""
+Legend and scale-bar specifications as structured data.
+The frontend legends and exported figures are drawn from the same
+specification, so category colors, value bins and time ticks always match the
+binning that was used to color the data.
+"""
+
+import math
+from typing import Any, Dict, Optional
+
+import numpy as np
+import tskit
+
+from argscape.backend.axes import time_axis
+from argscape.backend.palettes import categorical_palette, ramp_color, resolve_palette, value_fraction
+
+COLOR_BY_OPTIONS = ("population", "time", "statistic", "category")
+DEFAULT_NUM_BINS = 5
+MAX_NUM_BINS = 20
+
+
+def _value_bins(
+    values: np.ndarray,
+    ramp: str,
+    num_bins: int,
+    log_scale: bool
+) -> Dict[str, Any]:
+    vmin, vmax = float(values.min()), float(values.max())
+    if log_scale and vmax > 0:
+        floor = vmin if vmin > 0 else vmax * 1e-6
+        edges = np.logspace(math.log10(floor), math.log10(vmax), num_bins + 1)
+        edges[0] = vmin
+    else:
+        edges = np.linspace(vmin, vmax, num_bins + 1)
+
+    counts, _ = np.histogram(values, bins=edges)
+    bins = []
+    for i in range(num_bins):
+        low, high = float(edges[i]), float(edges[i + 1])
+        midpoint = value_fraction((low + high) / 2, vmin, vmax, log_scale)
+        bins.append({"min": low, "max": high, "count": int(counts[i]), "color": ramp_color(ramp, midpoint)})
+    return {"min": vmin, "max": vmax, "bins": bins}
+
+
+def build_legend(
+    ts: tskit.TreeSequence,
+    color_by: str,
+    palette_choice: Optional[Dict[str, Any]] = None,
+    values: Optional[Dict[int, float]] = None,
+    statistic_name: Optional[str] = None,
+    num_bins: int = DEFAULT_NUM_BINS,
+    log_scale: bool = False,
+    labels: Optional[Dict[int, str]] = None
+) -> Dict[str, Any]:
+    """Legend for the current node coloring plus the time scale bar.
+
+    Args:
+        ts: Tree sequence being drawn
+        color_by: "population", "time", "statistic" (node values given in `values`)
+            or "category" (node labels given in `labels`)
+        palette_choice: Stored palette choice (see palettes.resolve_palette)
+        values: Node ID -> statistic value, for color_by="statistic"
+        statistic_name: Label for the statistic legend
+        num_bins: Number of color bins for continuous legends
+        log_scale: Bin continuous values on a log scale
+    """
+    if color_by not in COLOR_BY_OPTIONS:
+        raise ValueError(f"color_by must be one of {COLOR_BY_OPTIONS}")
+    if not 1 <= num_bins <= MAX_NUM_BINS:
+        raise ValueError(f"num_bins must be between 1 and {MAX_NUM_BINS}")
+
+    palette = resolve_palette(ts, palette_choice)
+    times = ts.tables.nodes.time
+    time_units = ts.time_units if ts.time_units != tskit.TIME_UNITS_UNKNOWN else "time"
+
+    if color_by == "category":
+        if not labels:
+            raise ValueError("labels are required to build a category legend")
+        names = sorted(set(labels.values()))
+        colors = categorical_palette(palette["categorical"], len(names))
+        counts = {name: 0 for name in names}
+        for label in labels.values():
+            counts[label] += 1
+        legend: Dict[str, Any] = {
+            "type": "categorical",
+            "title": statistic_name or "Category",
+            "categories": [{"id": i, "name": name, "color": colors[i], "count": counts[name]} for i, name in enumerate(names)],
+        }
+    elif color_by == "population":
+        node_population = ts.tables.nodes.population
+        node_counts = np.bincount(node_population[node_population >= 0], minlength=ts.num_populations)
+        legend = {
+            "type": "categorical",
+            "title": "Population",
+            "categories": [{**population, "count": int(node_counts[population["id"]])} for population in palette["populations"]],
+        }
+    else:
+        if color_by == "time":
+            data, title = times, f"Node time ({time_units})"
+        else:
+            if not values:
+                raise ValueError("values are required to build a statistic legend")
+            data = np.array([v for v in values.values() if v is not None and not math.isnan(v)], dtype=float)
+            if len(data) == 0:
+                raise ValueError("No finite statistic values")
+            title = statistic_name or "Statistic"
+        legend = {
+            "type": "continuous",
+            "title": title,
+            "ramp": palette["continuous"],
+            "log_scale": log_scale,
+            **_value_bins(np.asarray(data, dtype=float), palette["continuous"], num_bins, log_scale),
+        }
+
+    max_time = float(times.max()) if ts.num_nodes else 0.0
+    return {
+        "color_by": color_by,
+        "legend": legend,
+        "colorblind_safe": palette["colorblind_safe"],
+        "time_scale": time_axis(0.0, max_time, time_units, log_scale=log_scale),
+    }
@@ -0,0 +1,110 @@
+"""
+Lifecycle of the backend server process.
+The CLI runs the server through the module-level `backend_manager`, which
+owns the uvicorn server and the state behind the stop, restart and status
+endpoints. All state changes go through one lock, so a stop and a restart
+arriving together can't both act on the same server. A restart re-launches
+the CLI with the same arguments once the current server has shut down,
+so settings read at import time (thread pools, interpreter) are applied
+afresh.
+"""
+
+import logging
+import os
+import subprocess
+import sys
+import threading
+import time
+from typing import Any, Dict, Optional
+
+import uvicorn
+
+logger = logging.getLogger(__name__)
+
+class BackendManager:
+    """Owns the running uvicorn server and its start/stop/restart state."""
+
+    def __init__(self):
+        self._lock = threading.Lock()
+        self._server: Optional[uvicorn.Server] = None
+        # unmanaged, starting, running, stopping or restarting
+        self.state = "unmanaged"
+        self.started_at: Optional[float] = None
+        self.url: Optional[str] = None
+        self._restart_requested = False
+
+    def run(self, app: str, host: str, port: int, url: str):
+        """Serve `app` until stopped; blocks, and re-launches the CLI if a restart was requested."""
+        with self._lock:
+            if self.state != "unmanaged":
+                raise RuntimeError(f"The backend is already {self.state}")
+            server = self._server = uvicorn.Server(uvicorn.Config(app, host=host, port=port))
+            self.state = "starting"
+            self.started_at = time.time()
+            self.url = url
+            self._restart_requested = False
+
+        try:
+            server.run()
+        finally:
+            with self._lock:
+                restart = self._restart_requested
+                self._server = None
+                self.state = "unmanaged"
+                self.started_at = None
+
+        if restart:
+            self._relaunch()
+
+    def _require_server(self) -> uvicorn.Server:
+        if self._server is None:
+            raise RuntimeError("The backend was not started by the argscape CLI and can't be controlled from here")
+        if self.state in ("stopping", "restarting"):
+            raise RuntimeError(f"The backend is already {self.state}")
+        return self._server
+
+    def stop(self):
+        """Ask the server to finish its open requests and exit."""
+        with self._lock:
+            server = self._require_server()
+            self.state = "stopping"
+            server.should_exit = True
+        logger.info("Backend stop requested")
+
+    def restart(self):
+        """Stop the server, then start it again in a fresh process."""
+        with self._lock:
+            server = self._require_server()
+            self.state = "restarting"
+            self._restart_requested = True
+            server.should_exit = True
+        logger.info("Backend restart requested")
+
+    def status(self) -> Dict[str, Any]:
+        with self._lock:
+            if self.state == "starting" and self._server is not None and self._server.started:
+                self.state = "running"
+            started_at = self.started_at
+            return {
+                "managed": self._server is not None,
+                "state": self.state,
+                "pid": os.getpid(),
+                "started_at": started_at,
+                "uptime_seconds": round(time.time() - started_at, 1) if started_at else None,
+                "url": self.url,
+            }
+
+    def _relaunch(self):
+        # The browser is already open on this URL, and the access token is
+        # still in the environment, so the new server accepts the same page
+        argv = [sys.executable, "-m", "argscape.cli", *sys.argv[1:]]
+        if "--no-browser" not in argv:
+            argv.append("--no-browser")
+        logger.info("Restarting the backend")
+        if sys.platform == "win32":
+            sys.exit(subprocess.call(argv))
+        os.execv(argv[0], argv)
+
+
+# Global backend manager instance
+backend_manager = BackendManager()
@@ -0,0 +1,232 @@
+"""
+Coordinate liftover between genome assemblies using UCSC chain files.
+Annotation intervals and bookmarks are remapped to the coordinate system of
+the loaded ARG, with a per-interval report of how much of each interval mapped.
+"""
+
+import bisect
+import gzip
+import logging
+import threading
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+# Fraction of bases that must map for an interval to count as lifted (UCSC liftOver default)
+DEFAULT_MIN_MATCH = 0.95
+
+# Liftover status values
+STATUS_LIFTED = "lifted"
+STATUS_PARTIAL = "partial"
+STATUS_UNMAPPED = "unmapped"
+
+
+@dataclass
+class ChainBlock:
+    t_start: int
+    t_end: int
+    q_name: str
+    q_start: int  # On the query strand given by q_strand
+    q_strand: str
+    q_size: int
+    chain_id: int
+
+    def map_range(self, start: int, end: int):
+        """Map [start, end) within this block to forward-strand query coordinates."""
+        q_start = self.q_start + (start - self.t_start)
+        q_end = q_start + (end - start)
+        if self.q_strand == "-":
+            return self.q_size - q_end, self.q_size - q_start
+        return q_start, q_end
+
+
+class ChainFile:
+    """Aligned blocks of a chain file, indexed by source contig."""
+
+    def __init__(self, text: str, name: str = "chain"):
+        self.name = name
+        self.blocks: Dict[str, List[ChainBlock]] = {}
+        self.num_chains = 0
+        self._parse(text)
+        for blocks in self.blocks.values():
+            blocks.sort(key=lambda b: b.t_start)
+        self._starts = {chrom: [b.t_start for b in blocks] for chrom, blocks in self.blocks.items()}
+        self._max_block = {chrom: max(b.t_end - b.t_start for b in blocks) for chrom, blocks in self.blocks.items()}
+
+    @classmethod
+    def from_bytes(cls, contents: bytes, name: str = "chain") -> "ChainFile":
+        if contents[:2] == b"\x1f\x8b":
+            contents = gzip.decompress(contents)
+        return cls(contents.decode("utf-8"), name)
+
+    @classmethod
+    def from_path(cls, path: Path) -> "ChainFile":
+        return cls.from_bytes(Path(path).read_bytes(), Path(path).name)
+
+    def _parse(self, text: str):
+        header = None
+        t_pos = q_pos = 0
+        for line_number, line in enumerate(text.splitlines(), 1):
+            fields = line.split()
+            if not fields:
+                continue
+            if fields[0] == "chain":
+                if len(fields) < 12:
+                    raise ValueError(f"Malformed chain header on line {line_number}")
+                header = {
+                    "t_name": fields[2], "t_strand": fields[4],
+                    "q_name": fields[7], "q_size": int(fields[8]), "q_strand": fields[9],
+                    "id": int(fields[12]) if len(fields) > 12 else self.num_chains,
+                }
+                if header["t_strand"] != "+":
+                    raise ValueError(f"Chain on line {line_number} has a reverse-strand source, which is not supported")
+                t_pos, q_pos = int(fields[5]), int(fields[10])
+                self.num_chains += 1
+                continue
+            if header is None:
+                raise ValueError(f"Alignment data before any chain header on line {line_number}")
+
+            size = int(fields[0])
+            self.blocks.setdefault(header["t_name"], []).append(ChainBlock(
+                t_pos, t_pos + size, header["q_name"], q_pos, header["q_strand"], header["q_size"], header["id"]
+            ))
+            t_pos += size
+            q_pos += size
+            if len(fields) >= 3:
+                t_pos += int(fields[1])
+                q_pos += int(fields[2])
+        if not self.blocks:
+            raise ValueError("Chain file contains no alignments")
+
+    def summary(self) -> Dict[str, Any]:
+        return {
+            "name": self.name,
+            "num_chains": self.num_chains,
+            "source_contigs": sorted(self.blocks),
+            "num_blocks": sum(len(blocks) for blocks in self.blocks.values()),
+        }
+
+    def _overlapping_blocks(self, chrom: str, start: int, end: int) -> List[ChainBlock]:
+        blocks = self.blocks.get(chrom)
+        if not blocks:
+            return []
+        starts = self._starts[chrom]
+        first = bisect.bisect_left(starts, start - self._max_block[chrom])
+        last = bisect.bisect_left(starts, end)
+        return [b for b in blocks[first:last] if b.t_end > start]
+
+    def lift_interval(self, chrom: str, start: int, end: int, min_match: float = DEFAULT_MIN_MATCH) -> Dict[str, Any]:
+        """Lift a 0-based half-open interval (points: end = start + 1)."""
+        length = max(end - start, 1)
+        by_chain: Dict[int, List] = {}
+        for block in self._overlapping_blocks(chrom, start, end):
+            overlap_start, overlap_end = max(start, block.t_start), min(end, block.t_end)
+            by_chain.setdefault(block.chain_id, []).append((block, block.map_range(overlap_start, overlap_end)))
+
+        if not by_chain:
+            return {"status": STATUS_UNMAPPED, "mapped_fraction": 0.0, "reason": "No alignment covers this interval"}
+
+        # Like liftOver, keep the chain covering the most bases
+        best = max(by_chain.values(), key=lambda pieces: sum(q_end - q_start for _, (q_start, q_end) in pieces))
+        mapped = sum(q_end - q_start for _, (q_start, q_end) in best)
+        fraction = mapped / length
+        block = best[0][0]
+        result = {
+            "status": STATUS_LIFTED if fraction >= min_match else STATUS_PARTIAL,
+            "chrom": block.q_name,
+            "start": min(q_start for _, (q_start, _) in best),
+            "end": max(q_end for _, (_, q_end) in best),
+            "strand": block.q_strand,
+            "mapped_fraction": round(fraction, 6),
+            "num_chains": len(by_chain),
+        }
+        if result["status"] == STATUS_PARTIAL:
+            result["reason"] = f"Only {fraction:.1%} of bases map (minimum {min_match:.0%})"
+        elif len(by_chain) > 1:
+            result["reason"] = "Interval is split across chains; the best chain was used"
+        return result
+
+
+def parse_bed(text: str) -> List[Dict[str, Any]]:
+    """Read BED intervals (chrom, start, end and optional name)."""
+    intervals = []
+    for line in text.splitlines():
+        if not line.strip() or line.startswith(("#", "track", "browser")):
+            continue
+        fields = line.split("\t") if "\t" in line else line.split()
+        if len(fields) < 3:
+            raise ValueError(f"BED line has fewer than 3 columns: {line[:80]}")
+        interval = {"chrom": fields[0], "start": int(fields[1]), "end": int(fields[2])}
+        if len(fields) > 3:
+            interval["name"] = fields[3]
+        intervals.append(interval)
+    return intervals
+
+
+def lift_intervals(
+    chain: ChainFile,
+    intervals: List[Dict[str, Any]],
+    min_match: float = DEFAULT_MIN_MATCH,
+    target_chrom: Optional[str] = None
+) -> Dict[str, Any]:
+    """Lift annotation intervals or bookmarks, keeping any extra fields.
+
+    Args:
+        chain: Chain from the annotations' assembly to the ARG's assembly
+        intervals: Dicts with "chrom", "start" and "end" (or "position" for bookmarks)
+        min_match: Fraction of bases that must map for the "lifted" status
+        target_chrom: Contig of the loaded ARG; intervals landing elsewhere are unmapped
+    """
+    results = []
+    counts = {STATUS_LIFTED: 0, STATUS_PARTIAL: 0, STATUS_UNMAPPED: 0}
+    for i, interval in enumerate(intervals):
+        if "position" in interval and "start" not in interval:
+            start = int(interval["position"])
+            end = start + 1
+        else:
+            start, end = int(interval["start"]), int(interval["end"])
+        if end < start:
+            raise ValueError(f"Interval {i} has end before start")
+
+        lifted = chain.lift_interval(str(interval["chrom"]), start, end, min_match)
+        if target_chrom is not None and lifted["status"] != STATUS_UNMAPPED and lifted["chrom"] != target_chrom:
+            lifted = {
+                "status": STATUS_UNMAPPED,
+                "mapped_fraction": 0.0,
+                "reason": f"Maps to {lifted['chrom']}, not the ARG's contig {target_chrom}",
+            }
+        if "position" in interval and lifted["status"] != STATUS_UNMAPPED:
+            lifted["position"] = lifted["start"]
+        counts[lifted["status"]] += 1
+        results.append({"index": i, "original": interval, **lifted})
+
+    logger.info(f"Lifted {len(intervals)} intervals with {chain.name}: {counts}")
+    return {
+        "chain": chain.name,
+        "min_match": min_match,
+        "num_intervals": len(intervals),
+        "counts": counts,
+        "intervals": results,
+    }
+
+
+class ChainRegistry:
+    """Chain file loaded by each session."""
+
+    def __init__(self):
+        self._chains: Dict[str, ChainFile] = {}
+        self._lock = threading.Lock()
+
+    def set(self, session_id: str, chain: ChainFile):
+        with self._lock:
+            self._chains[session_id] = chain
+        logger.info(f"Loaded chain file {chain.name} ({chain.num_chains} chains) for session {session_id}")
+
+    def get(self, session_id: str) -> Optional[ChainFile]:
+        with self._lock:
+            return self._chains.get(session_id)
+
+
+chain_registry = ChainRegistry()
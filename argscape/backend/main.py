@@ -3,25 +3,94 @@ ARGscape API - FastAPI Backend
 Main API endpoints for tree sequence visualization and analysis
 """
 
+import io
+import json
 import logging
 import os
 import tempfile
 import time
 import re
-from typing import Dict, Optional
+import secrets
+import threading
+import zipfile
+from typing import Any, Dict, List, Optional, Union
 from datetime import datetime
+from urllib.parse import quote, urlencode
 
 import numpy as np
 import tskit
 import tszip
 import uvicorn
 import msprime
-from fastapi import FastAPI, File, HTTPException, UploadFile, Request, BackgroundTasks, Query, APIRouter
+from fastapi import FastAPI, File, HTTPException, UploadFile, Request, BackgroundTasks, Query, APIRouter, WebSocket, WebSocketDisconnect
 from fastapi.middleware.cors import CORSMiddleware
-from fastapi.responses import FileResponse
+from fastapi.responses import FileResponse, JSONResponse, RedirectResponse, Response, StreamingResponse
+from starlette.concurrency import run_in_threadpool
 from fastapi.staticfiles import StaticFiles
 from pydantic import BaseModel
-from argscape.backend.tskit_utils import load_tree_sequence_from_file
+from argscape.backend.layout_utils import (
+    relax_neighborhood,
+    parse_positions,
+    DEFAULT_NEIGHBORHOOD_HOPS,
+    DEFAULT_RELAX_ITERATIONS,
+    build_layout_document,
+    serialize_layout_document,
+    parse_layout_document,
+    check_layout_compatibility,
+    LAYOUT_EXPORT_FORMATS,
+    iter_layout_3d,
+    camera_path,
+    DEFAULT_3D_ITERATIONS,
+    DEFAULT_SNAPSHOT_EVERY,
+    bundle_edges,
+    polyline_lookup,
+    DEFAULT_BUNDLING_CYCLES,
+    DEFAULT_COMPATIBILITY_THRESHOLD,
+    DEFAULT_MAX_NEIGHBORS,
+    place_labels,
+    label_priorities,
+    DEFAULT_FONT_SIZE as LABEL_FONT_SIZE,
+    DEFAULT_NODE_RADIUS as LABEL_NODE_RADIUS,
+    pick_indexes,
+    PICK_KINDS,
+    tile_sets,
+    TILE_FORMATS
+)
+from argscape.backend.tskit_utils import (
+    load_tree_sequence_from_file,
+    TreeSequenceLoadError,
+    upgrade_legacy_tree_sequence,
+    tree_sequence_fingerprint,
+    describe_node,
+    describe_tree,
+    describe_breakpoint,
+    navigation_order,
+    topology_blocks,
+    unique_topologies,
+    sweep_scan,
+    detect_introgression,
+    tracts_to_csv,
+    estimate_ne,
+    compute_tmrca_matrix,
+    sample_embedding,
+    list_populations,
+    apply_population_edits,
+    get_polytomy_stats,
+    resolve_polytomies,
+    get_display_times,
+    TIME_SCALE_MODES,
+    compute_node_metrics,
+    detect_format,
+    convert_arg_files,
+    CONVERTIBLE_FORMATS,
+    get_simulation_context,
+    get_metadata_schemas,
+    decode_metadata,
+    get_node_metadata,
+    apply_metadata_edits,
+    METADATA_TABLES,
+    TOP_LEVEL_METADATA
+)
 from pathlib import Path
 
 # Configure logging first
@@ -38,6 +107,16 @@ try:
 except ImportError:
     pass  # File doesn't exist, that's fine
 
+# Restricted mode must be applied before any user data is touched
+if os.getenv("ARGSCAPE_SANDBOX", "").lower() in ("1", "true", "yes"):
+    from argscape.backend.sandbox import apply_sandbox
+    from argscape.backend.settings import get_cache_dir, get_config_dir, get_data_dir, settings
+    apply_sandbox(
+        Path(os.getenv("PERSISTENT_SESSION_PATH") or Path(tempfile.gettempdir()) / "argscape_sessions"),
+        get_config_dir(),
+        [get_data_dir(), get_cache_dir()] + [Path(d) for d in settings.get("scratch_dirs") or []]
+    )
+
 # Try to import optional dependencies
 try:
     from fastgaia import infer_locations
@@ -132,7 +211,242 @@ except ImportError as e:
     MIDPOINT_AVAILABLE = False
 
 # Import graph utilities
-from argscape.backend.graph_utils import convert_tree_sequence_to_graph_data
+from argscape.backend.graph_utils import convert_tree_sequence_to_graph_data, iter_graph_data_chunks
+
+# Local API authentication
+from argscape.backend.auth import (
+    AUTH_COOKIE_NAME,
+    AUTH_QUERY_PARAM,
+    get_auth_token,
+    get_request_token,
+    is_local_mode,
+    is_request_authorized,
+    token_matches,
+)
+
+# Background jobs with cooperative cancellation
+from argscape.backend.jobs import CancellationToken, Job, check_cancelled, job_manager, JOB_COMPLETED
+from argscape.backend.batch import BATCH_STEPS, DEFAULT_BATCH_STEPS, IMAGE_FORMATS, MAX_BATCH_WORKERS, run_batch
+from argscape.backend.transforms import TRANSFORM_PARAMS, apply_transform_stack, clear_transform_cache, normalize_stack
+
+# Timing spans for the performance HUD
+from argscape.backend.timing import current_session, timing_registry, get_performance_metrics
+
+# Settings and CPU resource controls
+from argscape.backend.settings import SETTINGS_SCHEMA, settings, get_config_dir
+from argscape.backend.resources import apply_resource_settings, get_worker_count, MemoryWatchdog
+from argscape.backend.lifecycle import backend_manager
+from argscape.backend.hardware import get_system_capabilities
+from argscape.backend.first_run import apply_setup, check_directory, get_setup_state
+
+
+def apply_current_resource_settings(store=settings):
+    """Apply thread-pool, priority and low-power settings from the settings store."""
+    return apply_resource_settings(store.get("num_threads"), store.get("low_power_mode"), store.get("process_priority"))
+
+
+apply_current_resource_settings()
+settings.add_listener(apply_current_resource_settings)
+
+# Recent log records for diagnostic bundles (before the protected-mode scrubber attaches its filters)
+from argscape.backend.diagnostics import install_log_capture, build_bundle, save_bundle, submit_bundle
+
+install_log_capture()
+
+# Emergency save and crash marker for unhandled errors and the memory ceiling
+from argscape.backend.crash import (
+    install_crash_handler,
+    register_emergency_save,
+    read_crash_marker,
+    clear_crash_marker,
+    terminate,
+    MEMORY_LIMIT_EXIT_CODE
+)
+
+install_crash_handler()
+register_emergency_save("sessions", session_storage.flush)
+register_emergency_save("jobs", job_manager.cancel_all)
+
+
+def end_process_over_memory_limit(usage: int, limit: int):
+    terminate(
+        "memory_limit",
+        f"Memory usage {usage // 2**20} MB stayed above the {limit // 2**20} MB limit after cancelling jobs",
+        exit_code=MEMORY_LIMIT_EXIT_CODE
+    )
+
+
+memory_watchdog = MemoryWatchdog(on_exceeded=lambda usage, limit: job_manager.cancel_all(), on_kill=end_process_over_memory_limit)
+
+
+def apply_memory_limit_setting(store=settings):
+    memory_watchdog.set_limit(store.get("memory_limit_mb"))
+
+
+apply_memory_limit_setting()
+settings.add_listener(apply_memory_limit_setting)
+
+# Protected data handling
+from argscape.backend.protected import install_log_scrubber, is_protected_mode
+from argscape.backend.sandbox import is_sandbox_enabled
+
+
+def apply_protected_mode_setting(store=settings):
+    """Start scrubbing file paths from logs once protected mode is enabled."""
+    if store.get("protected_mode"):
+        install_log_scrubber()
+
+
+apply_protected_mode_setting()
+settings.add_listener(apply_protected_mode_setting)
+
+# Offline mode network kill-switch
+from argscape.backend.network import get_network_status, install_network_guard, test_connection, NetworkDisabledError
+
+install_network_guard()
+
+# Genomic context: reference sequence, liftover and demographic models
+from argscape.backend.reference import reference_registry, annotate_mutations, MAX_FETCH_BASES
+from argscape.backend.datasets import download_dataset_file, list_dataset_files
+from argscape.backend.remote_files import RemoteFile
+from argscape.backend.download_cache import download_cache
+from argscape.backend.genome_browser import (
+    DEFAULT_ASSEMBLY,
+    browser_links,
+    format_region,
+    igv_batch_script,
+    parse_region,
+    region_to_ts_interval,
+)
+from argscape.backend.liftover import ChainFile, chain_registry, lift_intervals, parse_bed, DEFAULT_MIN_MATCH
+from argscape.backend.contigs import ContigMap, detect_contigs, SOURCE_USER
+from argscape.backend.export_estimates import estimate_export
+from argscape.backend.html_bundle import build_html_bundle
+from argscape.backend.missing_data import missing_data_report, missingness_track
+from argscape.backend.node_attributes import filter_nodes, get_attribute_values, reference as expression_reference
+from argscape.backend.sample_sets import describe_sample_sets, evaluate as evaluate_sample_set, referenced_sets, resolve_sample_sets
+from argscape.backend.tracks import (
+    DEFAULT_MAX_BINS, describe_track, export_track, from_columns, from_sweep_scan, merge_tracks, parse_track_file,
+    query_track, validate_track_name,
+)
+from argscape.backend.time_units import DISPLAY_UNITS, TimeCalibration, resolve_calibration, scale_statistic
+from argscape.backend.windowed_stats import compute_statistic, make_windows, sample_sets_for, serialize_table, SAMPLE_SET_OPTIONS, TABLE_FORMATS as STAT_TABLE_FORMATS
+from argscape.backend.view_links import decode_view_state, encode_view_state, fingerprint_matches
+from argscape.backend.svg_export import build_svg, default_positions, DEFAULT_NUM_TIME_BANDS, DEFAULT_WIDTH as SVG_DEFAULT_WIDTH, DEFAULT_HEIGHT as SVG_DEFAULT_HEIGHT
+from argscape.backend.frame_budget import frame_budgets
+from argscape.backend.gltf_export import (
+    layout_to_gltf,
+    layout_to_obj,
+    MESH_EXPORT_FORMATS,
+    DEFAULT_NODE_RADIUS,
+    DEFAULT_EDGE_RADIUS
+)
+from argscape.backend.figures import DEFAULT_TEMPLATE, parse_template, render_figure, validate_template
+from argscape.backend.archive import result_archive, RESULT_KINDS, KIND_LAYOUT
+from argscape.backend.rate_maps import RateMap, rate_map_registry, RATE_MAP_KINDS, DEFAULT_NUM_WINDOWS as DEFAULT_RATE_WINDOWS
+from argscape.backend.event_bridge import CLIENT_ROLES, CLIENT_VIEWER, event_bridge, is_loopback
+from argscape.backend.collaboration import COLLAB_ROLES, ROLE_PRESENTER, collaboration_hub, follower_route_room
+from argscape.backend.demography import (
+    parse_demes_model,
+    align_with_tree_sequence,
+    to_json_compatible,
+    build_msprime_demography,
+    default_sample_sets
+)
+
+# Shared presentation: palettes, legends, axes, number formatting and guided tours
+from argscape.backend.palettes import list_presets, resolve_palette, validate_choice
+from argscape.backend.legends import build_legend, DEFAULT_NUM_BINS
+from argscape.backend.axes import genomic_axis, time_axis, DEFAULT_NUM_TICKS
+from argscape.backend.formatting import formatting_config, load_message_catalog
+from argscape.backend.tours import tour_store, build_sample_tree_sequence, SAMPLE_FILENAME
+from argscape.backend.fixtures import generate_fixture, list_fixtures
+
+# Graph data prefetching for genome scrubbing
+from argscape.backend.prefetch import frame_prefetcher, DEFAULT_PREFETCH_RADIUS
+
+# Readiness state and warm-up of first-call code paths
+from argscape.backend.warmup import readiness
+
+# Recipes for rebuilding session files after a restart
+from argscape.backend.replay import session_replay, RECIPE_PATH, RECIPE_SIMULATION, RECIPE_SAMPLE, RECIPE_FIXTURE, RECIPE_DERIVED
+
+session_replay.attach(session_storage)
+
+# Per-file audit trail of opens, operations and exports
+from argscape.backend.audit import audit_log, EVENT_OPERATION, EVENT_EXPORT
+
+
+def audit_stored_file(session_id: str, filename: str, ts: tskit.TreeSequence, derived_from: Optional[str]):
+    """Start or extend an input file's audit log when a file is stored."""
+    if is_file_kept_in_memory(session_id, filename):
+        return
+    if derived_from is not None:
+        audit_log.record_derived(session_id, filename, derived_from)
+        return
+    try:
+        fingerprint = tree_sequence_fingerprint(ts)
+    except Exception as e:
+        logger.warning(f"Could not fingerprint {filename} for the audit log: {e}")
+        return
+    audit_log.record_open(session_id, filename, fingerprint, {
+        "num_samples": ts.num_samples,
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_trees": ts.num_trees,
+        "sequence_length": ts.sequence_length,
+    })
+
+
+# External commands run on file open and export (see argscape.backend.hooks)
+from argscape.backend.hooks import EVENT_EXPORT_COMPLETED, EVENT_FILE_OPENED, MAX_STAGED_EXPORT_MB, hook_runner, hooks_available, render_command, validate_hook
+
+
+def file_stored(session_id: str, filename: str, ts: tskit.TreeSequence, derived_from: Optional[str]):
+    """Audit a stored file and, for files the user opened rather than derived, run the open hooks."""
+    audit_stored_file(session_id, filename, ts, derived_from)
+    if derived_from is not None or not hook_runner.has_hooks(EVENT_FILE_OPENED):
+        return
+    now = datetime.now()
+    hook_runner.fire(EVENT_FILE_OPENED, {
+        "filename": filename,
+        "path": session_storage.tree_sequence_path(session_id, filename),
+        "session": session_id,
+        "num_samples": ts.num_samples,
+        "num_trees": ts.num_trees,
+        "sequence_length": ts.sequence_length,
+        "date": now.strftime("%Y-%m-%d"),
+        "time": now.strftime("%H%M%S"),
+    }, filename)
+
+
+session_storage.file_stored_listener = file_stored
+
+# Watched inbox folder for pipeline outputs
+from argscape.backend.inbox import InboxWatcher, LOCAL_CLIENT_IP, preprocess_tree_sequence
+
+
+def import_inbox_file(path: Path, preprocess: str) -> Dict[str, Any]:
+    """Load a file dropped into the inbox into the local user's session."""
+    contents = path.read_bytes()
+    ts, filename = load_tree_sequence_from_file(contents, path.name)
+    ts, preprocess_info = preprocess_tree_sequence(ts, preprocess)
+    session_id = session_storage.get_or_create_session(LOCAL_CLIENT_IP)
+    session_storage.store_tree_sequence(session_id, filename, ts)
+    session_replay.record(session_id, filename, RECIPE_PATH, path=str(path), preprocess=preprocess)
+    return {"filename": filename, "num_nodes": ts.num_nodes, "num_trees": ts.num_trees, **preprocess_info}
+
+
+inbox_watcher = InboxWatcher(import_inbox_file)
+
+
+def apply_inbox_setting(store=settings):
+    """Start, move or stop the inbox watcher to match the inbox_dir setting."""
+    inbox_watcher.configure(store.get("inbox_dir"))
+
+
+apply_inbox_setting()
+settings.add_listener(apply_inbox_setting)
 
 # Import temporal inference functionality - disabled by DISABLE_TSDATE env var
 DISABLE_TSDATE = os.getenv("DISABLE_TSDATE", "0").lower() in ("1", "true", "yes")
@@ -149,6 +463,9 @@ else:
     TSDATE_AVAILABLE = False
     logger.info("Temporal inference disabled by configuration")
 
+# Re-dating checks work whether or not tsdate is available, and report if it isn't
+from argscape.backend.temporal_inference import validate_redating, compare_node_times
+
 # FastAPI app instance
 app = FastAPI(
     title="ARGscape API",
@@ -194,6 +511,20 @@ class SimulationRequest(BaseModel):
     crs: Optional[str] = "unit_grid"  # Coordinate reference system for simulation
     mutation_rate: Optional[float] = 1e-8  # Mutation rate for simulation
     recombination_rate: Optional[float] = 1e-8  # Recombination rate for simulation
+    demes_model: Optional[str] = None  # Demes YAML; replaces population_size when given
+    demes_samples: Optional[Dict[str, int]] = None  # Samples per deme (default: split across extant demes)
+
+class PrefetchRequest(BaseModel):
+    tree_start_idx: int
+    tree_end_idx: int  # Inclusive
+    max_samples: int = DEFAULT_MAX_SAMPLES_FOR_GRAPH
+    sample_order: str = "custom"
+    radius: int = DEFAULT_PREFETCH_RADIUS  # Windows to prepare in each direction
+    step: Optional[int] = None  # Trees between windows (default: the window width)
+
+class DemesModelRequest(BaseModel):
+    model: str  # Demes YAML or JSON
+    filename: Optional[str] = None  # Align with this tree sequence's populations and node times
 
 class CoordinateTransformRequest(BaseModel):
     filename: str
@@ -227,6 +558,299 @@ class TsdateInferenceRequest(BaseModel):
     filter_individuals: bool = False
     filter_sites: bool = False
 
+class PolytomyResolutionRequest(BaseModel):
+    filename: str
+    method: str = "random"  # "random" or "zero_length"
+    random_seed: Optional[int] = None
+
+class NeighborhoodRelayoutRequest(BaseModel):
+    filename: str
+    positions: Dict[int, List[float]]  # Current layout: node ID -> [x, y]
+    anchor_node_ids: List[int] = []  # Pinned/dragged nodes, kept fixed
+    free_node_ids: Optional[List[int]] = None  # Explicit region to re-solve
+    hops: int = DEFAULT_NEIGHBORHOOD_HOPS
+    iterations: int = DEFAULT_RELAX_ITERATIONS
+    lock_y: bool = True
+
+class EdgeBundlingRequest(BaseModel):
+    filename: str
+    positions: Dict[int, List[float]]  # Current layout: node ID -> [x, y]
+    cycles: int = DEFAULT_BUNDLING_CYCLES
+    compatibility_threshold: float = DEFAULT_COMPATIBILITY_THRESHOLD
+    max_neighbors: int = DEFAULT_MAX_NEIGHBORS
+
+class LabelPlacementRequest(BaseModel):
+    filename: str
+    positions: Dict[int, List[float]]  # Current layout: node ID -> [x, y]
+    zoom: float = 1.0
+    font_size: float = LABEL_FONT_SIZE
+    node_radius: float = LABEL_NODE_RADIUS
+    viewport: Optional[List[float]] = None  # [min_x, min_y, max_x, max_y] in layout coordinates
+    candidate_node_ids: Optional[List[int]] = None  # Default: every positioned node
+    texts: Dict[int, str] = {}  # Default: node IDs
+    max_labels: Optional[int] = None
+
+class PickIndexRequest(BaseModel):
+    filename: str
+    positions: Dict[int, List[float]]  # Current layout: node ID -> [x, y]
+
+class TileSetRequest(BaseModel):
+    positions: Dict[int, List[float]]  # Current layout: node ID -> [x, y]
+
+class FrameStatsRequest(BaseModel):
+    filename: str
+    fps: float  # Frame rate achieved over the last measurement window
+    target_fps: Optional[int] = None  # Defaults to the render_target_fps setting
+
+class LayoutExportRequest(BaseModel):
+    filename: str
+    positions: Dict[int, List[float]]  # node ID -> [x, y] or [x, y, z]
+    algorithm: str
+    parameters: Dict[str, Any] = {}
+    random_seed: Optional[int] = None
+    format: str = "json"  # "json" or "parquet"
+    archive: bool = False  # Also keep the export in the derived-result archive
+
+class ExportEstimateRequest(BaseModel):
+    filename: str
+    format: str  # One of EXPORT_FORMATS
+    element_counts: Dict[str, int] = {}  # What the view draws, when it shows a subset
+    width: int = 1920
+    height: int = 1080
+    scale: float = 1.0
+
+class SvgExportRequest(BaseModel):
+    filename: str
+    positions: Dict[int, List[float]] = {}  # Node ID -> [x, y] from the view; empty for a default layout
+    group_by: str = "population"  # "population" or "time_band"
+    num_time_bands: int = DEFAULT_NUM_TIME_BANDS
+    show_labels: bool = True
+    show_mutations: bool = True
+    force: bool = False  # Write SVG even above export_rasterize_above
+    bundle_edges: bool = False  # Draw edges as force-bundled paths
+
+class HtmlBundleRequest(BaseModel):
+    filename: str
+    positions: Dict[int, List[float]] = {}  # Node ID -> [x, y] of the nodes in the view; empty for a default layout
+    title: Optional[str] = None
+    algorithm: Optional[str] = None  # Layout provenance, recorded in the bundle
+    parameters: Dict[str, Any] = {}
+
+class FigureTemplateRequest(BaseModel):
+    template: Optional[Dict[str, Any]] = None
+    template_text: Optional[str] = None  # JSON or TOML source, read according to template_filename
+    template_filename: str = "template.json"
+
+class FigureRenderRequest(FigureTemplateRequest):
+    filename: str
+    format: str = "png"  # One of FIGURE_FORMATS
+
+class JobRequest(BaseModel):
+    operation: str  # One of JOB_OPERATIONS
+    filename: Optional[str] = None  # Not needed for "benchmark"
+    params: Dict[str, Any] = {}
+
+class ReferenceLoadRequest(BaseModel):
+    fasta_path: str  # Local FASTA file; its .fai index is used if present
+    fai_path: Optional[str] = None
+    gff_path: Optional[str] = None  # GFF3 with CDS features, for codon context
+
+class ChainLoadRequest(BaseModel):
+    chain_path: str  # Local UCSC chain file (optionally gzipped)
+
+class ArchiveRequest(BaseModel):
+    filename: str  # Results are archived under this tree sequence's content fingerprint
+    kind: str  # "statistic", "layout" or "export"
+    name: str
+    value: Any = None  # JSON result to archive
+    job_id: Optional[str] = None  # Or archive a completed job's result
+    operation: Optional[str] = None  # Provenance; taken from the job when job_id is given
+    params: Dict[str, Any] = {}
+
+class RateMapLoadRequest(BaseModel):
+    path: str  # Local HapMap recombination map or BEDgraph mutation rate map (optionally gzipped)
+
+class LiftoverRequest(BaseModel):
+    intervals: List[Dict[str, Any]] = []  # {"chrom", "start", "end"} or {"chrom", "position"}, extra fields kept
+    bed: Optional[str] = None  # BED text, lifted in addition to intervals
+    min_match: float = DEFAULT_MIN_MATCH
+    target_chrom: Optional[str] = None  # Contig of the loaded ARG
+
+class DatasetLookupRequest(BaseModel):
+    reference: str  # DOI, doi.org link, or Zenodo/Figshare record URL
+
+class DatasetDownloadRequest(BaseModel):
+    reference: str
+    file_name: str  # One of the files listed for the record
+
+class RemoteFileRequest(BaseModel):
+    url: str  # s3://bucket/key, gs://bucket/key or https://...
+    # Used for this request only, never stored: access_key_id, secret_access_key, session_token,
+    # region, endpoint_url (S3) or token (GCS/HTTPS bearer token)
+    credentials: Optional[Dict[str, str]] = None
+
+class DownloadCachePinRequest(BaseModel):
+    pinned: bool = True
+
+class GenomeBrowserRequest(BaseModel):
+    filename: str
+    start: float
+    end: float
+    contig: Optional[str] = None  # Default: the first contig of the loaded reference
+    assembly: str = DEFAULT_ASSEMBLY
+    offset: int = 0  # Genome position of tree sequence position 0
+    snapshot_directory: Optional[str] = None  # IGV batch scripts only
+
+class ContigLayoutRequest(BaseModel):
+    filename: str
+    contigs: Optional[List[Dict[str, Any]]] = None  # [{"name", "start", "end"}] in global coordinates; None restores detection
+
+class TimeCalibrationRequest(BaseModel):
+    filename: str
+    generation_time: Optional[float] = None  # years per generation; None falls back to the setting
+    mutation_rate: Optional[float] = None  # per base per generation, to calibrate uncalibrated times
+
+class SampleSetRequest(BaseModel):
+    filename: str
+    name: str
+    definition: Optional[Dict[str, Any]] = None  # See argscape.backend.sample_sets; None deletes the set
+
+class GenomeRegionRequest(BaseModel):
+    filename: str
+    region: str  # e.g. "chr2:136,545,410-136,617,158"
+    contig: Optional[str] = None  # Contig of the ARG; regions on other contigs are rejected
+    offset: int = 0
+
+class MetadataEditRequest(BaseModel):
+    filename: str
+    edits: Dict[str, Dict[int, Any]] = {}  # Table -> row ID -> new metadata ("tree_sequence" row 0 for top-level)
+    schemas: Dict[str, Optional[Dict[str, Any]]] = {}  # Table -> new JSON metadata schema
+
+class PopulationEditRequest(BaseModel):
+    filename: str
+    edits: Dict[int, Dict[str, Any]] = {}  # Population ID -> {"name", "description", "color", "display_order"}
+    order: Optional[List[int]] = None  # Every population ID in display order
+
+class NodeAttributeRequest(BaseModel):
+    filename: str
+    name: str
+    expression: Optional[str] = None  # See argscape.backend.node_attributes; None deletes the attribute
+
+class NodeAttributeEvaluateRequest(BaseModel):
+    filename: str
+    expression: str  # An expression, or the name of a saved attribute
+
+class TrackRequest(BaseModel):
+    filename: str
+    name: str
+    source: str  # statistic, missingness or sweep_scan
+    params: Dict[str, Any] = {}  # The source endpoint's parameters, e.g. statistic, mode, num_windows
+
+class TrackMergeRequest(BaseModel):
+    filename: str
+    name: str
+    tracks: List[str]
+    operation: str = "stack"  # stack, mean, sum, difference or ratio
+
+class NodeMetadataRequest(BaseModel):
+    filename: str
+    node_id: int
+    full: bool = False  # Whole value even when over the preview size
+    decode_schema: Optional[Dict[str, Any]] = None  # JSON schema for tables stored without one (e.g. struct codec)
+
+class TransformStackRequest(BaseModel):
+    filename: str  # The loaded original; the stack always starts from it
+    steps: List[Dict[str, Any]] = []  # [{"operation", "params", "enabled"}], run in list order
+
+class BatchRequest(BaseModel):
+    filenames: List[str]  # Tree sequences already uploaded to the session
+    steps: List[str] = list(DEFAULT_BATCH_STEPS)  # Pipeline, run in BATCH_STEPS order
+    workers: int = 1  # 1 processes files sequentially
+    keep_unary: bool = False
+    layout_iterations: int = 50
+    image_format: str = "png"
+
+class PaletteRequest(BaseModel):
+    categorical: str = "okabe_ito"  # Population colors, see palettes.CATEGORICAL_PALETTES
+    continuous: str = "viridis"  # Time and statistic ramps, see palettes.CONTINUOUS_RAMPS
+    population_overrides: Dict[int, str] = {}  # Population ID -> hex color
+
+class LegendRequest(BaseModel):
+    color_by: str = "population"  # "population", "time", "statistic" or "category"
+    values: Optional[Dict[int, Optional[float]]] = None  # Node ID -> statistic value, for "statistic"
+    attribute: Optional[str] = None  # A derived node attribute (name or expression), instead of values
+    statistic_name: Optional[str] = None
+    num_bins: int = DEFAULT_NUM_BINS
+    log_scale: bool = False
+
+class DiagnosticsRequest(BaseModel):
+    error: Optional[str] = None  # Message of the failed operation
+    operation: Optional[str] = None
+    filename: Optional[str] = None  # Only table sizes of this file are included
+    user_comment: Optional[str] = None
+    include_logs: bool = True
+
+class ViewLinkRequest(BaseModel):
+    filename: str
+    state: Dict[str, Any] = {}  # window, tree_range, filter_mode, filters, coloring, camera, view_mode, selected_node
+
+class SessionSnapshotRequest(BaseModel):
+    ui_state: Dict[str, Any]  # Opaque frontend state (current tree sequence, view options, ...)
+
+class IntrogressionRequest(BaseModel):
+    filename: str
+    recipient: Union[int, str]  # Population ID or name
+    donor: Union[int, str]
+    relative_threshold: float = 0.5  # Fraction of the sample's mean donor coalescence time
+    max_time: Optional[float] = None
+    min_length: float = 0.0
+    format: str = "json"  # "json" or "csv"
+
+class NeEstimateRequest(BaseModel):
+    filename: str
+    num_epochs: int = 20
+    time_breaks: Optional[List[float]] = None  # Epoch start times, overriding num_epochs
+    num_bootstrap: int = 100
+    ploidy: int = 2
+    random_seed: Optional[int] = None
+
+class TmrcaMatrixRequest(BaseModel):
+    filename: str
+    samples: Optional[List[int]] = None  # Default: all samples
+    sample_set: Optional[str] = None  # A named sample set, instead of samples
+    positions: Optional[List[float]] = None  # Local trees at these positions; default: genome-wide mean
+    max_samples: int = 200  # Larger sample sets are downsampled
+    random_seed: Optional[int] = None
+
+class RedatingRequest(BaseModel):
+    filename: str
+    mutation_rate: Optional[float] = None
+    method: Optional[str] = None  # tsdate's default when not given
+    population_size: Optional[float] = None  # Ne prior, needed by some methods
+
+class CollabRoomRequest(BaseModel):
+    filename: Optional[str] = None
+
+class SetupPathRequest(BaseModel):
+    path: str
+
+class HooksRequest(BaseModel):
+    hooks: List[Dict[str, Any]]  # [{"name", "event", "command", "match", "enabled", "timeout", "cwd"}]
+
+class HookTestRequest(BaseModel):
+    hook: Dict[str, Any]
+    values: Dict[str, Any] = {}  # Example placeholder values
+    run: bool = False
+
+class SampleEmbeddingRequest(BaseModel):
+    filename: str
+    distance: str = "tmrca"  # "tmrca" (branch divergence) or "genetic" (site divergence)
+    dimensions: int = 2
+    samples: Optional[List[int]] = None
+    sample_set: Optional[str] = None  # A named sample set, instead of samples
+    max_samples: int = 200
+    random_seed: Optional[int] = None
+
 #### Utility functions ####
 
 def get_client_ip(request: Request) -> str:
@@ -246,8 +870,118 @@ def get_client_ip(request: Request) -> str:
     client_host = request.client.host if request.client else "127.0.0.1"
     return client_host
 
+
+def get_derived_filename(filename: str, suffix: str) -> str:
+    """Build the filename for a tree sequence derived from `filename`."""
+    if filename.endswith('.trees'):
+        return filename[:-6] + f'_{suffix}.trees'
+    elif filename.endswith('.tsz'):
+        return filename[:-4] + f'_{suffix}.tsz'
+    return filename + f'_{suffix}.trees'
+
 #### API endpoints ####
 
+@app.middleware("http")
+async def auth_token_middleware(request: Request, call_next):
+    """Require the per-launch token when one is configured (see argscape.backend.auth)."""
+    token = get_auth_token()
+    if token is None:
+        return await call_next(request)
+
+    # Launch URL handshake: move the token from the URL into an HttpOnly cookie
+    if token_matches(request.query_params.get(AUTH_QUERY_PARAM), token):
+        params = [(k, v) for k, v in request.query_params.multi_items() if k != AUTH_QUERY_PARAM]
+        url = request.url.replace(query=urlencode(params))
+        response = RedirectResponse(str(url), status_code=303)
+        response.set_cookie(AUTH_COOKIE_NAME, token, httponly=True, samesite="strict")
+        return response
+
+    if not is_request_authorized(request, token):
+        # Collaboration followers have no launch token; the code of an open room admits them to its routes
+        room_id = follower_route_room(request.method, request.url.path)
+        if room_id is None or get_open_collab_room(room_id) is None:
+            return JSONResponse(status_code=401, content={"detail": "Missing or invalid authentication token"})
+    return await call_next(request)
+
+
+@app.middleware("http")
+async def timing_middleware(request: Request, call_next):
+    """Time API requests per session for the performance HUD (see argscape.backend.timing)."""
+    if not request.url.path.startswith("/api/"):
+        return await call_next(request)
+    client_ip = get_client_ip(request)
+    context_token = current_session.set(client_ip)
+    start = time.perf_counter()
+    try:
+        response = await call_next(request)
+    finally:
+        current_session.reset(context_token)
+    route = request.scope.get("route")
+    name = f"{request.method} {getattr(route, 'path', request.url.path)}"
+    timing_registry.record("request", name, time.perf_counter() - start, session=client_ip)
+    return response
+
+
+@app.middleware("http")
+async def export_hook_middleware(request: Request, call_next):
+    """Hand each download (an attachment response) to the export hooks, via a staged copy."""
+    response = await call_next(request)
+    disposition = response.headers.get("content-disposition", "")
+    if (
+        not request.url.path.startswith("/api/")
+        or not disposition.startswith("attachment")
+        or not hook_runner.has_hooks(EVENT_EXPORT_COMPLETED)
+        or hooks_available() is not None
+    ):
+        return response
+    limit = MAX_STAGED_EXPORT_MB * 1024 * 1024
+    declared = int(response.headers.get("content-length") or 0)
+    if declared > limit:
+        logger.warning(f"Skipping export hooks for a {declared / 1e6:.0f} MB download")
+        return response
+
+    # Chunked downloads are buffered only up to the limit; past it the rest streams through untouched
+    chunks, size = [], 0
+    async for chunk in response.body_iterator:
+        chunks.append(chunk)
+        size += len(chunk)
+        if size > limit:
+            break
+    if size > limit:
+        logger.warning(f"Skipping export hooks for a download over {MAX_STAGED_EXPORT_MB} MB")
+
+        async def passthrough():
+            for chunk in chunks:
+                yield chunk
+            async for chunk in response.body_iterator:
+                yield chunk
+
+        return StreamingResponse(passthrough(), status_code=response.status_code, headers=dict(response.headers), media_type=response.media_type)
+
+    body = b"".join(chunks)
+    output = re.search(r'filename="?([^";]+)"?', disposition)
+    output = output.group(1) if output else "export"
+    filename = request.path_params.get("filename") or request.query_params.get("filename")
+    try:
+        staged = await run_in_threadpool(hook_runner.stage_export, output, body)
+        now = datetime.now()
+        hook_runner.fire(EVENT_EXPORT_COMPLETED, {
+            "filename": filename,
+            "output": output,
+            "path": staged,
+            "format": output.rsplit(".", 1)[-1] if "." in output else "",
+            "endpoint": request.url.path,
+            "session": get_client_ip(request),
+            "size": len(body),
+            "date": now.strftime("%Y-%m-%d"),
+            "time": now.strftime("%H%M%S"),
+        }, output, staged=staged)
+    except OSError as e:
+        logger.warning(f"Could not stage {output} for export hooks: {e}")
+    headers = {k: v for k, v in response.headers.items() if k.lower() != "content-length"}
+    return Response(content=body, status_code=response.status_code, headers=headers, media_type=response.media_type)
+
+
 @app.middleware("http")
 async def remove_double_slash_middleware(request: Request, call_next):
     scope = request.scope
@@ -276,6 +1010,12 @@ async def api_root():
         }
     }
 
+@api_router.get("/readiness")
+async def get_readiness():
+    """Readiness state after launch: starting, warming (first-call warm-up) or ready."""
+    return readiness.status()
+
+
 @api_router.get("/health")
 async def health_check():
     """Comprehensive health check for Railway deployment."""
@@ -389,6 +1129,97 @@ async def get_current_session(request: Request):
         raise HTTPException(status_code=500, detail=f"Failed to get session: {str(e)}")
 
 
+SESSION_UI_STATE_KEY = "ui_state"
+
+
+@api_router.get("/session/snapshot")
+async def snapshot_session(request: Request):
+    """Everything the frontend needs to rehydrate after a reload or crash of the page."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    files = session_storage.get_file_list(session_id)
+    reference = reference_registry.get(session_id)
+    chain = chain_registry.get(session_id)
+
+    return {
+        "session_id": session_id,
+        "session_info": session_storage.get_session_stats(session_id),
+        "files": files,
+        "protected_files": [name for name in files if session_storage.is_file_protected(session_id, name)],
+        "stored_layouts": [name for name in files if session_storage.get_session_data(session_id, f"layout:{name}") is not None],
+        "jobs": [job.to_dict() for job in job_manager.list_jobs(session_id)],
+        "reference": reference.summary() if reference else None,
+        "chain": chain.summary() if chain else None,
+        "ui_state": session_storage.get_session_data(session_id, SESSION_UI_STATE_KEY),
+        "transform_stacks": {
+            name: session_storage.get_session_data(session_id, f"transforms:{name}")
+            for name in files if session_storage.get_session_data(session_id, f"transforms:{name}") is not None
+        },
+        "sample_sets": {
+            name: session_storage.get_session_data(session_id, f"sample_sets:{name}")
+            for name in files if session_storage.get_session_data(session_id, f"sample_sets:{name}")
+        },
+    }
+
+
+@api_router.post("/session/snapshot")
+async def save_session_snapshot(request: Request, snapshot_request: SessionSnapshotRequest):
+    """Store the frontend's UI state so a later snapshot can restore it."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    try:
+        session_storage.store_session_data(session_id, SESSION_UI_STATE_KEY, snapshot_request.ui_state)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"saved": True}
+
+
+@api_router.post("/view-links")
+async def create_view_link(request: Request, link_request: ViewLinkRequest):
+    """Encode a view of a file as a compact string and a link that restores it."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, link_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    try:
+        fingerprint = await run_in_threadpool(tree_sequence_fingerprint, ts)
+        state = {**link_request.state, "file": fingerprint, "filename": link_request.filename}
+        # The file's stored palette is part of what the view looks like
+        state.setdefault("coloring", session_storage.get_session_data(session_id, f"palette:{link_request.filename}"))
+        code = encode_view_state(state)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"code": code, "path": f"/visualize/{quote(link_request.filename)}?view={code}"}
+
+
+@api_router.get("/view-links/{code}")
+async def resolve_view_link(request: Request, code: str):
+    """Decode a view link and find the loaded file it was made from.
+
+    `filename` is null when no file in this session has the link's
+    fingerprint; the state is still returned so the caller can say which
+    file to load.
+    """
+    try:
+        state = decode_view_state(code)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    files = session_storage.get_file_list(session_id)
+    # The file the link was made from is the likeliest match, so check it first
+    hinted = state.get("filename")
+    if hinted in files:
+        files = [hinted] + [name for name in files if name != hinted]
+    match = None
+    for name in files:
+        ts = session_storage.get_tree_sequence(session_id, name)
+        if ts is not None and fingerprint_matches(await run_in_threadpool(tree_sequence_fingerprint, ts), state):
+            match = name
+            break
+    return {"state": state, "filename": match}
+
+
 @api_router.get("/session-stats/{session_id}")
 async def get_session_stats(session_id: str):
     """Get statistics for a specific session."""
@@ -405,54 +1236,382 @@ async def get_storage_stats(request: Request):
     return session_storage.get_global_stats()
 
 
-@api_router.get("/uploaded-files/")
-async def list_uploaded_files_current(request: Request):
-    """List uploaded files for current client IP session."""
+@api_router.get("/capabilities")
+async def get_capabilities():
+    """Report the filesystem, network and environment boundary enforced on the backend."""
+    from argscape.backend.sandbox import get_capabilities_report
+    return get_capabilities_report()
+
+
+@api_router.get("/network-status")
+async def network_status():
+    """Report whether offline mode is blocking outbound network access."""
+    return get_network_status()
+
+
+@api_router.post("/network/test")
+async def test_network_connection(url: str):
+    """Check that a URL is reachable through the configured proxy and CA bundle."""
+    require_local_mode("Testing network connections")
+    if not url.startswith(("http://", "https://")):
+        raise HTTPException(status_code=400, detail="url must be an http(s) URL")
+    return await run_in_threadpool(test_connection, url)
+
+
+@api_router.get("/inbox")
+async def get_inbox_status():
+    """Get the watched inbox folder state (configure it with the inbox_* settings)."""
+    return inbox_watcher.get_status()
+
+
+@api_router.get("/inbox/events")
+async def get_inbox_events(after: int = 0):
+    """Files imported from the inbox since event `after`, for notifications or auto-open."""
+    return {"events": inbox_watcher.get_events(after), **inbox_watcher.get_status()}
+
+
+def build_request_bundle(request: Request, diagnostics_request: DiagnosticsRequest) -> Dict[str, Any]:
+    ts = None
+    if diagnostics_request.filename:
+        session_id = session_storage.get_or_create_session(get_client_ip(request))
+        ts = session_storage.get_tree_sequence(session_id, diagnostics_request.filename)
+    return build_bundle(
+        error=diagnostics_request.error,
+        operation=diagnostics_request.operation,
+        user_comment=diagnostics_request.user_comment,
+        ts=ts,
+        include_logs=diagnostics_request.include_logs
+    )
+
+
+@api_router.post("/diagnostics/preview")
+async def preview_diagnostics(request: Request, diagnostics_request: DiagnosticsRequest):
+    """The exact diagnostic bundle that saving or submitting would produce."""
+    return build_request_bundle(request, diagnostics_request)
+
+
+@api_router.post("/diagnostics/save")
+async def save_diagnostics(request: Request, diagnostics_request: DiagnosticsRequest):
+    """Save a diagnostic bundle to the config directory."""
+    bundle = build_request_bundle(request, diagnostics_request)
     try:
-        client_ip = get_client_ip(request)
-        session_id = session_storage.get_or_create_session(client_ip)
-        return {"uploaded_tree_sequences": session_storage.get_file_list(session_id)}
+        path = save_bundle(bundle)
+    except OSError as e:
+        raise HTTPException(status_code=500, detail=f"Failed to save diagnostics: {str(e)}")
+    return {"saved": True, "path": str(path)}
+
+
+@api_router.post("/diagnostics/submit")
+async def submit_diagnostics(request: Request, diagnostics_request: DiagnosticsRequest):
+    """Send a diagnostic bundle to the configured diagnostics_endpoint."""
+    bundle = build_request_bundle(request, diagnostics_request)
+    try:
+        return await run_in_threadpool(submit_bundle, bundle)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except NetworkDisabledError as e:
+        raise HTTPException(status_code=403, detail=str(e))
     except Exception as e:
-        logger.error(f"Error getting uploaded files: {str(e)}")
-        raise HTTPException(status_code=500, detail=f"Failed to get files: {str(e)}")
+        logger.error(f"Failed to submit diagnostics: {e}")
+        raise HTTPException(status_code=502, detail=f"Failed to submit diagnostics: {str(e)}")
 
-#### Tree sequence API endpoints ####
 
-@api_router.post("/upload-tree-sequence")
-async def upload_tree_sequence(request: Request, file: UploadFile = File(...)):
-    """Upload and process tree sequence files."""
+@api_router.get("/crash-report")
+async def get_crash_report():
+    """Crash marker left by the previous run, if it ended in an unhandled error."""
+    marker = read_crash_marker()
+    return {"crashed": marker is not None, "report": marker}
+
+
+@api_router.delete("/crash-report")
+async def dismiss_crash_report():
+    """Remove the crash marker once the user has seen it."""
+    return {"cleared": clear_crash_marker()}
+
+
+@api_router.get("/formatting")
+async def get_formatting_config():
+    """Number formatting rules for the configured locale and generation time."""
+    return formatting_config(settings.get("locale"), settings.get("generation_time_years"))
+
+
+@api_router.get("/i18n/{locale}")
+async def get_message_catalog(locale: str):
+    """UI message catalog for a locale, with English fallbacks and user overrides."""
+    if not locale.replace("_", "").replace("-", "").isalnum():
+        raise HTTPException(status_code=400, detail="Invalid locale")
+    return load_message_catalog(locale, get_config_dir() / "locales")
+
+
+def require_local_mode(action: str):
+    """Refuse `action` unless the server runs in local mode (see argscape.backend.auth.is_local_mode)."""
+    if not is_local_mode():
+        raise HTTPException(status_code=403, detail=f"{action} is only available when ARGscape is started locally with `argscape`")
+
+
+@api_router.get("/settings")
+async def get_settings():
+    """Get the effective user settings."""
+    return settings.get_all()
+
+
+@api_router.get("/settings/sources")
+async def get_settings_sources():
+    """Which layer (default, site/user/project argscape.toml, app or environment) sets each setting."""
+    return {
+        "sources": {key: settings.get_source(key) for key in SETTINGS_SCHEMA},
+        "config_files": [
+            {key: layer[key] for key in ("layer", "path", "error") if key in layer}
+            for layer in settings.config_layers
+        ],
+        "editable": is_local_mode(),
+    }
+
+
+@api_router.post("/settings")
+async def update_settings(updates: Dict[str, Any]):
+    """Validate and persist setting updates (local mode only, as settings apply to every session)."""
+    require_local_mode("Changing settings")
     try:
-        client_ip = get_client_ip(request)
+        return settings.update(updates)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.get("/hooks")
+async def get_hooks():
+    """Configured hooks, whether they can run, and the most recent runs."""
+    return {
+        "hooks": hook_runner.get_hooks(),
+        "available": hooks_available() is None,
+        "reason": hooks_available(),
+        "runs": hook_runner.recent_runs(),
+        "path": str(hook_runner.path),
+    }
+
+
+@api_router.post("/hooks")
+async def save_hooks(hooks_request: HooksRequest):
+    """Replace the hook list (local mode only, as hooks run commands on this machine)."""
+    require_local_mode("Changing hooks")
+    try:
+        return {"hooks": hook_runner.set_hooks(hooks_request.hooks)}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/hooks/test")
+async def test_hook(test_request: HookTestRequest):
+    """Show the command a hook would run for example values, and run it if asked."""
+    require_local_mode("Testing hooks")
+    try:
+        hook = validate_hook(test_request.hook)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    command = render_command(hook, test_request.values)
+    if not test_request.run:
+        return {"command": command, "ran": False}
+    reason = hooks_available()
+    if reason is not None:
+        raise HTTPException(status_code=409, detail=reason)
+    return {"command": command, "ran": True, **await run_in_threadpool(hook_runner.run_hook, hook, test_request.values)}
+
+
+@api_router.get("/resources")
+async def get_resource_status():
+    """Get resource usage configuration (thread pools, priority, low-power mode, memory limit)."""
+    return {**apply_current_resource_settings(), **memory_watchdog.status()}
+
+
+@api_router.get("/backend/status")
+async def get_backend_status():
+    """State, process id and uptime of the backend server."""
+    return backend_manager.status()
+
+
+def control_backend(action: str):
+    # Without the access token, any page the browser visits could stop the server
+    if get_auth_token() is None:
+        raise HTTPException(status_code=403, detail=f"Backend {action} requires the per-launch access token (start without --no-auth)")
+    try:
+        getattr(backend_manager, action)()
+    except RuntimeError as e:
+        raise HTTPException(status_code=409, detail=str(e))
+    return backend_manager.status()
+
+
+@api_router.post("/backend/stop")
+async def stop_backend():
+    """Shut the backend down once open requests finish."""
+    return control_backend("stop")
+
+
+@api_router.post("/backend/restart")
+async def restart_backend():
+    """Shut the backend down and start it again with the same command line."""
+    return control_backend("restart")
+
+
+@api_router.get("/setup")
+async def get_setup():
+    """First-run wizard state: current choices, storage locations and suggested budgets."""
+    return await run_in_threadpool(get_setup_state)
+
+
+@api_router.post("/setup/check-path")
+async def check_setup_path(path_request: SetupPathRequest):
+    """Check that a chosen data or cache folder is usable and report its free space (local mode only)."""
+    require_local_mode("Checking server folders")
+    try:
+        return check_directory(path_request.path)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/setup")
+async def complete_setup(choices: Dict[str, Any]):
+    """Save the wizard's choices, moving existing data when the data or cache folder changes."""
+    require_local_mode("The setup wizard")
+    try:
+        return await run_in_threadpool(apply_setup, choices)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error applying setup choices: {e}")
+        raise HTTPException(status_code=500, detail=f"Setup failed: {str(e)}")
+
+
+@api_router.get("/system-capabilities")
+async def get_system_capabilities_endpoint():
+    """GPU adapter, CPU cores and features, memory and free disk space, with the defaults chosen from them."""
+    paths = {"config": get_config_dir(), "sessions": session_storage.storage_base_path}
+    return await run_in_threadpool(get_system_capabilities, paths)
+
+
+@api_router.get("/performance-metrics")
+async def get_performance_metrics_endpoint(request: Request):
+    """Time spent per category (request, parse, graph, layout, analysis, render) and per operation."""
+    return get_performance_metrics(get_client_ip(request))
+
+
+@api_router.post("/performance-metrics/reset")
+async def reset_performance_metrics(request: Request):
+    """Clear this session's timing metrics."""
+    timing_registry.reset(get_client_ip(request))
+    return {"reset": True}
+
+
+@api_router.get("/derived-data")
+async def get_derived_data_usage(request: Request):
+    """Get disk usage of derived tree sequences and caches for the current session."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    return session_storage.get_derived_data_usage(session_id)
+
+
+@api_router.post("/derived-data/clear")
+async def clear_derived_data(request: Request):
+    """Delete all derived tree sequences and caches for the current session."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    result = session_storage.clear_derived_data(session_id)
+    logger.info(f"Cleared derived data for session {session_id}: {result['bytes_freed']} bytes freed")
+    return {"status": "success", **result}
+
+
+@api_router.get("/uploaded-files/")
+async def list_uploaded_files_current(request: Request):
+    """List uploaded files for current client IP session."""
+    try:
+        client_ip = get_client_ip(request)
+        session_id = session_storage.get_or_create_session(client_ip)
+        return {"uploaded_tree_sequences": session_storage.get_file_list(session_id)}
+    except Exception as e:
+        logger.error(f"Error getting uploaded files: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Failed to get files: {str(e)}")
+
+#### Tree sequence API endpoints ####
+
+def load_uploaded_tree_sequence(
+    session_id: str,
+    filename: str,
+    contents: bytes,
+    protected: bool = False,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Store an upload and load it into the session, checking for cancellation between stages."""
+    session_storage.store_file(session_id, filename, contents, protected=protected)
+    check_cancelled(cancel_token)
+
+    conversion = None
+    if detect_format(filename, contents) in CONVERTIBLE_FORMATS:
+        ts, updated_filename, conversion = convert_arg_files({filename: contents})
+    else:
+        ts, updated_filename = load_tree_sequence_from_file(contents, filename)
+    check_cancelled(cancel_token)
+    session_storage.store_tree_sequence(session_id, updated_filename, ts, protected=protected)
+
+    has_temporal = any(node.time != 0 for node in ts.nodes() if node.flags & tskit.NODE_IS_SAMPLE == 0)
+    spatial_info = check_spatial_completeness(ts)
+
+    logger.info(f"Successfully loaded tree sequence: {ts.num_nodes} nodes, {ts.num_edges} edges")
+
+    return {
+        "filename": updated_filename,
+        "original_filename": filename,
+        "size": len(contents),
+        "status": "tree_sequence_loaded",
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_samples": ts.num_samples,
+        "num_trees": ts.num_trees,
+        "has_temporal": has_temporal,
+        "protected": session_storage.is_file_protected(session_id, updated_filename),
+        "conversion": conversion,
+        **spatial_info
+    }
+
+
+@api_router.post("/upload-tree-sequence")
+async def upload_tree_sequence(request: Request, file: UploadFile = File(...), protected: bool = False, background: bool = False):
+    """Upload and process tree sequence files.
+    
+    Protected uploads (and everything derived from them) are kept in memory only.
+    With `background`, the file is loaded in a cancellable "load" job and the job is returned.
+    """
+    try:
+        client_ip = get_client_ip(request)
         session_id = session_storage.get_or_create_session(client_ip)
         
         logger.info(f"Processing upload: {file.filename} for session {session_id}")
         
         contents = await file.read()
-        
-        # Store file in session
-        session_storage.store_file(session_id, file.filename, contents)
-        
-        ts, updated_filename = load_tree_sequence_from_file(contents, file.filename)
-        session_storage.store_tree_sequence(session_id, updated_filename, ts)
-        
-        has_temporal = any(node.time != 0 for node in ts.nodes() if node.flags & tskit.NODE_IS_SAMPLE == 0)
-        spatial_info = check_spatial_completeness(ts)
-        
-        logger.info(f"Successfully loaded tree sequence: {ts.num_nodes} nodes, {ts.num_edges} edges")
-        
-        return {
-            "filename": updated_filename,
-            "original_filename": file.filename,
-            "size": len(contents),
-            "content_type": file.content_type,
-            "status": "tree_sequence_loaded",
-            "num_nodes": ts.num_nodes,
-            "num_edges": ts.num_edges,
-            "num_samples": ts.num_samples,
-            "num_trees": ts.num_trees,
-            "has_temporal": has_temporal,
-            **spatial_info
-        }
+
+        if background:
+            def run(job: Job):
+                try:
+                    return load_uploaded_tree_sequence(session_id, file.filename, contents, protected, job.token)
+                except TreeSequenceLoadError as e:
+                    job.details["error"] = e.to_dict()
+                    if not e.upgradable:
+                        session_storage.delete_file(session_id, file.filename)
+                    raise
+                except Exception:
+                    # Including JobCancelled, so a cancelled load leaves nothing behind
+                    session_storage.delete_file(session_id, file.filename)
+                    raise
+
+            return job_manager.submit(session_id, "load", run, file.filename).to_dict()
+
+        result = await run_in_threadpool(load_uploaded_tree_sequence, session_id, file.filename, contents, protected)
+        return {**result, "content_type": file.content_type}
+    except TreeSequenceLoadError as e:
+        logger.warning(f"Rejected {file.filename} ({e.kind}): {e.message}")
+        # Legacy files are kept so POST /upgrade-tree-sequence can convert them
+        if not e.upgradable:
+            session_storage.delete_file(session_id, file.filename)
+        # `detail` stays a string for existing clients; `error` has the kind and hint separately
+        return JSONResponse(status_code=400, content={"detail": str(e), "error": e.to_dict()})
     except ValueError as e:
         logger.error(f"Storage error for {file.filename}: {str(e)}")
         raise HTTPException(status_code=400, detail=str(e))
@@ -462,6 +1621,99 @@ async def upload_tree_sequence(request: Request, file: UploadFile = File(...)):
         raise HTTPException(status_code=400, detail=f"Failed to upload: {str(e)}")
 
 
+@api_router.post("/upgrade-tree-sequence/{filename}")
+async def upgrade_tree_sequence(request: Request, filename: str):
+    """
+    Convert an uploaded file in an old tskit/msprime format to the current one.
+    Upload rejects such files with an `upgradable` error and keeps them, so the
+    user can review what will change and upgrade. The report lists every change.
+    """
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    contents = session_storage.get_file_data(session_id, filename)
+    if contents is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        ts, report = await run_in_threadpool(upgrade_legacy_tree_sequence, contents)
+    except TreeSequenceLoadError as e:
+        return JSONResponse(status_code=400, content={"detail": str(e), "error": e.to_dict()})
+    except Exception as e:
+        logger.error(f"Failed to upgrade {filename}: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Upgrade failed: {str(e)}")
+
+    upgraded_filename = get_derived_filename(filename.rsplit(".", 1)[0] if filename.endswith((".hdf5", ".h5")) else filename, "upgraded")
+    try:
+        session_storage.store_tree_sequence(session_id, upgraded_filename, ts, protected=session_storage.is_file_protected(session_id, filename))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    audit_log.record(session_id, filename, EVENT_OPERATION, "upgrade_tree_sequence", {"from_format": report["from_format"]}, output=upgraded_filename)
+    return {
+        "filename": upgraded_filename,
+        "original_filename": filename,
+        "status": "tree_sequence_loaded",
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_samples": ts.num_samples,
+        "num_trees": ts.num_trees,
+        "has_temporal": any(node.time != 0 for node in ts.nodes() if node.flags & tskit.NODE_IS_SAMPLE == 0),
+        "protected": session_storage.is_file_protected(session_id, upgraded_filename),
+        "upgrade": report,
+        **check_spatial_completeness(ts)
+    }
+
+
+@api_router.post("/import-arg")
+async def import_arg(request: Request, files: List[UploadFile] = File(...), protected: bool = False):
+    """Convert Relate (.anc + optional .mut) or ARGweaver (.smc) output into a tree sequence.
+    
+    The format is detected from file names and content; gzipped files are accepted.
+    """
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    
+    contents = {file.filename: await file.read() for file in files}
+    try:
+        ts, output_filename, conversion = await run_in_threadpool(convert_arg_files, contents)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Failed to convert ARG files {sorted(contents)}: {str(e)}")
+        raise HTTPException(status_code=400, detail=f"Failed to convert: {str(e)}")
+    
+    try:
+        session_storage.store_tree_sequence(session_id, output_filename, ts, protected=protected)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    
+    return {
+        "filename": output_filename,
+        "status": "tree_sequence_loaded",
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_samples": ts.num_samples,
+        "num_trees": ts.num_trees,
+        "has_temporal": True,
+        "protected": session_storage.is_file_protected(session_id, output_filename),
+        "conversion": conversion,
+        **check_spatial_completeness(ts)
+    }
+
+
+@api_router.post("/tree-sequence/{filename}/protected")
+async def set_tree_sequence_protected(request: Request, filename: str, protected: bool = True):
+    """Enable or disable protected data handling for a file and anything derived from it."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    if session_storage.get_tree_sequence(session_id, filename) is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    if not protected and is_protected_mode():
+        raise HTTPException(status_code=400, detail="Protected mode is enabled globally")
+    
+    session_storage.set_file_protected(session_id, filename, protected)
+    return {"filename": filename, "protected": session_storage.is_file_protected(session_id, filename)}
+
+
 @api_router.get("/tree-sequence-metadata/{filename}")
 async def get_tree_sequence_metadata(request: Request, filename: str):
     """Get metadata for a tree sequence."""
@@ -528,6 +1780,19 @@ async def download_tree_sequence(
         if ts is None:
             raise HTTPException(status_code=404, detail="Tree sequence not found")
         
+        # Protected data never touches disk, so serve it from memory
+        if session_storage.is_file_protected(session_id, filename):
+            if format == "tsz":
+                raise HTTPException(status_code=400, detail="Compressed download is unavailable for protected data; use .trees")
+            buffer = io.BytesIO()
+            ts.dump(buffer)
+            download_filename = filename.replace("/", "_").replace("\\", "_").rsplit(".", 1)[0] + ".trees"
+            return Response(
+                content=buffer.getvalue(),
+                media_type='application/octet-stream',
+                headers={"Content-Disposition": f'attachment; filename="{download_filename}"'}
+            )
+        
         # Create a more unique temporary filename to avoid conflicts
         timestamp = int(time.time() * FILENAME_TIMESTAMP_PRECISION_MICROSECONDS)
         safe_filename = filename.replace("/", "_").replace("\\", "_")
@@ -550,6 +1815,7 @@ async def download_tree_sequence(
                 ts.dump(temp_file.name)
             
             download_filename = f"{base_filename}.{format}"
+            audit_log.record(session_id, filename, EVENT_EXPORT, "download_tree_sequence", {"format": format}, output=download_filename)
             
             # Add cleanup task to remove temp file after response is sent
             def cleanup_temp_file(temp_path: str):
@@ -581,6 +1847,28 @@ async def download_tree_sequence(
         raise HTTPException(status_code=500, detail=f"Failed to download file: {str(e)}")
 
 
+def subsample_for_graph(ts: tskit.TreeSequence, max_samples: int) -> tskit.TreeSequence:
+    """Simplify to `max_samples` evenly spaced samples when there are more."""
+    if ts.num_samples <= max_samples:
+        return ts
+    sample_nodes = [node for node in ts.nodes() if node.is_sample()]
+    indices = [int(i * (len(sample_nodes) - 1) / (max_samples - 1)) for i in range(max_samples)]
+    selected_sample_ids = [sample_nodes[i].id for i in indices]
+    ts = ts.simplify(samples=selected_sample_ids)
+    logger.info(f"Simplified to {max_samples} samples: {ts.num_nodes} nodes, {ts.num_edges} edges")
+    return ts
+
+
+def build_tree_window_graph_data(ts: tskit.TreeSequence, start_idx: int, end_idx: int, max_samples: int, sample_order: str) -> Dict[str, Any]:
+    """Graph data for the trees start_idx..end_idx (inclusive)."""
+    from argscape.backend.graph_utils import convert_to_graph_data, filter_by_tree_indices
+    logger.info(f"Applying tree index filter: {start_idx} - {end_idx}")
+    ts, expected_tree_count = filter_by_tree_indices(ts, start_idx, end_idx)
+    logger.info(f"After tree index filtering: {ts.num_nodes} nodes, {ts.num_edges} edges")
+    ts = subsample_for_graph(ts, max_samples)
+    return convert_to_graph_data(ts, expected_tree_count, sample_order)
+
+
 @api_router.get("/graph-data/{filename}")
 async def get_graph_data(
     request: Request,
@@ -619,7 +1907,7 @@ async def get_graph_data(
 
     try:
         # Import here to avoid import errors during startup
-        from argscape.backend.graph_utils import convert_to_graph_data, filter_by_tree_indices
+        from argscape.backend.graph_utils import convert_to_graph_data
         
         expected_tree_count = None
         
@@ -636,9 +1924,15 @@ async def get_graph_data(
                     detail=f"Invalid tree index range: [{start_idx}, {end_idx}] for {ts.num_trees} trees"
                 )
             
-            logger.info(f"Applying tree index filter: {start_idx} - {end_idx}")
-            ts, expected_tree_count = filter_by_tree_indices(ts, start_idx, end_idx)
-            logger.info(f"After tree index filtering: {ts.num_nodes} nodes, {ts.num_edges} edges")
+            # Tree windows may already be prepared by a prefetch while scrubbing
+            frame_params = (start_idx, end_idx, max_samples, sample_order)
+            cached = frame_prefetcher.get(session_id, filename, ts, frame_params)
+            if cached is not None:
+                logger.info(f"Serving prefetched graph data for trees {start_idx} - {end_idx}")
+                return cached
+            graph_data = await run_in_threadpool(build_tree_window_graph_data, ts, *frame_params)
+            frame_prefetcher.put(session_id, filename, ts, frame_params, graph_data)
+            return graph_data
             
         elif genomic_start is not None or genomic_end is not None:
             # Apply genomic filtering if tree index filtering not specified
@@ -663,134 +1957,413 @@ async def get_graph_data(
                 ts = ts.delete_intervals(intervals_to_delete, simplify=True)
             logger.info(f"After genomic filtering: {ts.num_nodes} nodes, {ts.num_edges} edges")
 
-        if ts.num_samples > max_samples:
-            sample_nodes = [node for node in ts.nodes() if node.is_sample()]
-            indices = [int(i * (len(sample_nodes) - 1) / (max_samples - 1)) for i in range(max_samples)]
-            selected_sample_ids = [sample_nodes[i].id for i in indices]
-            ts = ts.simplify(samples=selected_sample_ids)
-            logger.info(f"Simplified to {max_samples} samples: {ts.num_nodes} nodes, {ts.num_edges} edges")
+        ts = subsample_for_graph(ts, max_samples)
 
         logger.info(f"Converting tree sequence to graph data: {ts.num_nodes} nodes, {ts.num_edges} edges")
         # Pass expected tree count if we filtered by tree indices and sample ordering
         graph_data = convert_to_graph_data(ts, expected_tree_count, sample_order)
         
         return graph_data
+    except HTTPException:
+        raise
     except Exception as e:
         logger.error(f"Error generating graph data: {str(e)}")
         raise HTTPException(status_code=500, detail=f"Failed to generate graph data: {str(e)}")
 
 
-@api_router.post("/simulate-tree-sequence/")  # Original version with trailing slash
-async def simulate_tree_sequence(request: Request, simulation_request: SimulationRequest):
-    """Simulate a tree sequence using msprime."""
+@api_router.post("/graph-data/{filename}/prefetch")
+async def prefetch_graph_data(request: Request, filename: str, prefetch_request: PrefetchRequest):
+    """Prepare graph data for the tree windows around the current one in the background."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    if not 0 <= prefetch_request.tree_start_idx <= prefetch_request.tree_end_idx < ts.num_trees:
+        raise HTTPException(status_code=400, detail=f"Invalid tree index range for {ts.num_trees} trees")
+    if prefetch_request.max_samples < 2:
+        raise HTTPException(status_code=400, detail="max_samples must be at least 2")
     try:
-        # Get session ID from request
-        session_id = session_storage.get_or_create_session(get_client_ip(request))
-        
-        # Validate parameters
-        if simulation_request.num_samples < 2:
-            raise HTTPException(status_code=400, detail="Number of samples must be at least 2")
-        if simulation_request.sequence_length <= 0:
-            raise HTTPException(status_code=400, detail="Sequence length must be positive")
-        if simulation_request.max_time < 1:
-            raise HTTPException(status_code=400, detail="Maximum time must be at least 1")
-        if simulation_request.population_size is not None and simulation_request.population_size < 1:
-            raise HTTPException(status_code=400, detail="Population size must be at least 1")
-        if simulation_request.mutation_rate is not None and simulation_request.mutation_rate <= 0:
-            raise HTTPException(status_code=400, detail="Mutation rate must be positive")
-        if simulation_request.recombination_rate is not None and simulation_request.recombination_rate <= 0:
-            raise HTTPException(status_code=400, detail="Recombination rate must be positive")
-        
-        # Log simulation parameters
-        logger.info(f"Simulating tree sequence with parameters: {simulation_request.dict()}")
-        
-        # Simulate the tree sequence
+        result = frame_prefetcher.prefetch(
+            session_id, filename, ts,
+            prefetch_request.tree_start_idx,
+            prefetch_request.tree_end_idx,
+            prefetch_request.max_samples,
+            prefetch_request.sample_order,
+            build_tree_window_graph_data,
+            radius=prefetch_request.radius,
+            step=prefetch_request.step
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {**result, **frame_prefetcher.stats()}
+
+
+@api_router.get("/graph-data-stream/{filename}")
+async def stream_graph_data(
+    request: Request,
+    filename: str,
+    max_samples: int = DEFAULT_MAX_SAMPLES_FOR_GRAPH,
+    sample_order: str = "custom",
+    num_time_bands: int = 8,
+    adaptive: bool = False
+):
+    """Stream graph data as server-sent events for progressive rendering.
+
+    Samples are sent first, then internal nodes by time band, each as a `chunk`
+    event followed by a `load-progress` event. Closing the connection aborts.
+    With `adaptive`, max_samples is reduced to the file's frame-budget level
+    (see /render/frame-stats).
+    """
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+
+    if max_samples < 2:
+        raise HTTPException(status_code=400, detail="max_samples must be at least 2")
+    if num_time_bands < 1:
+        raise HTTPException(status_code=400, detail="num_time_bands must be at least 1")
+    if adaptive:
+        max_samples = frame_budgets.get(session_id, filename).max_samples(max_samples)
+
+    if ts.num_samples > max_samples:
+        sample_nodes = ts.samples()
+        indices = [int(i * (len(sample_nodes) - 1) / (max_samples - 1)) for i in range(max_samples)]
+        ts = ts.simplify(samples=[int(sample_nodes[i]) for i in indices])
+        logger.info(f"Simplified to {max_samples} samples for streaming: {ts.num_nodes} nodes")
+
+    def format_event(event: str, data: Dict[str, Any]) -> str:
+        return f"event: {event}\ndata: {json.dumps(data)}\n\n"
+
+    async def event_stream():
+        chunks = iter_graph_data_chunks(ts, sample_order, num_time_bands)
         try:
-            # First simulate ancestry
-            ts = msprime.sim_ancestry(
-                samples=simulation_request.num_samples,
-                sequence_length=simulation_request.sequence_length,
-                recombination_rate=simulation_request.recombination_rate,
-                population_size=simulation_request.population_size,
-                random_seed=simulation_request.random_seed,
-                model=simulation_request.model,
-                end_time=simulation_request.max_time
-            )
-            
-            # Then add mutations if mutation_rate is provided
-            if simulation_request.mutation_rate is not None:
-                logger.info(f"Adding mutations with rate {simulation_request.mutation_rate}")
-                ts = msprime.sim_mutations(
-                    ts,
-                    rate=simulation_request.mutation_rate,
-                    random_seed=simulation_request.random_seed
-                )
-                logger.info(f"Added {ts.num_mutations} mutations to the tree sequence")
-            
-            # Generate spatial locations for samples based on genealogical relationships
-            logger.info(f"Generating spatial locations for samples using CRS: {simulation_request.crs}")
-            ts = generate_spatial_locations_for_samples(
-                ts,
-                random_seed=simulation_request.random_seed,
-                crs=simulation_request.crs
-            )
-            
-            # Generate a unique filename
-            timestamp = datetime.now().strftime("%Y%m%d_%H%M%S")
-            filename = f"{simulation_request.filename_prefix}_{timestamp}.trees"
-            
-            # Store in session (this will handle saving to disk)
-            session_storage.store_tree_sequence(session_id, filename, ts)
-            logger.info(f"Successfully simulated and saved tree sequence to {filename}")
-            
-            return {
-                "message": "Tree sequence simulated successfully",
-                "filename": filename,
-                "num_samples": ts.num_samples,
-                "num_trees": ts.num_trees,
-                "num_mutations": ts.num_mutations if simulation_request.mutation_rate is not None else 0,
-                "sequence_length": ts.sequence_length,
-                "crs": simulation_request.crs
-            }
-            
+            while True:
+                if await request.is_disconnected():
+                    logger.info(f"Graph data stream for {filename} aborted by client")
+                    return
+                record = await run_in_threadpool(next, chunks, None)
+                if record is None:
+                    return
+                yield format_event(record["type"], record)
+                if record["type"] == "chunk":
+                    yield format_event("load-progress", {
+                        "chunk_index": record["chunk_index"],
+                        "num_chunks": record["num_chunks"],
+                        "nodes_loaded": record["nodes_loaded"],
+                        "total_nodes": record["total_nodes"],
+                        "fraction": record["nodes_loaded"] / max(record["total_nodes"], 1),
+                    })
         except Exception as e:
-            logger.error(f"Error during tree sequence simulation: {str(e)}")
-            raise HTTPException(status_code=500, detail=f"Simulation failed: {str(e)}")
-            
-    except HTTPException:
-        raise
-    except Exception as e:
-        logger.error(f"Error in simulate_tree_sequence: {str(e)}")
-        raise HTTPException(status_code=500, detail=f"Failed to simulate tree sequence: {str(e)}")
+            logger.error(f"Error streaming graph data: {str(e)}")
+            yield format_event("error", {"detail": f"Failed to stream graph data: {str(e)}"})
+        finally:
+            chunks.close()
 
+    return StreamingResponse(event_stream(), media_type="text/event-stream")
 
-@api_router.post("/infer-locations-fast")
-async def infer_locations_fast(request: Request, inference_request: FastLocationInferenceRequest):
-    """Infer locations using the fastgaia package for fast spatial inference."""
-    if not FASTGAIA_AVAILABLE:
-        raise HTTPException(status_code=503, detail="fastgaia not available")
-    
-    logger.info(f"Received fast location inference request for file: {inference_request.filename}")
-    
+
+@api_router.get("/population-graph-data/{filename}")
+async def get_population_graph_data(
+    request: Request,
+    filename: str,
+    collapse_pure_clades: bool = True
+):
+    """Get a population-level graph where samples are collapsed into per-population summary nodes."""
     client_ip = get_client_ip(request)
     session_id = session_storage.get_or_create_session(client_ip)
-    ts = session_storage.get_tree_sequence(session_id, inference_request.filename)
+    ts = session_storage.get_tree_sequence(session_id, filename)
     if ts is None:
-        raise HTTPException(status_code=404, detail="File not found")
-    
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+
     try:
-        # Run fastgaia inference
-        ts_with_locations, inference_info = run_fastgaia_inference(
-            ts,
-            weight_span=inference_request.weight_span,
-            weight_branch_length=inference_request.weight_branch_length
+        from argscape.backend.graph_utils import convert_to_population_graph_data
+        return convert_to_population_graph_data(ts, collapse_pure_clades=collapse_pure_clades)
+    except Exception as e:
+        logger.error(f"Error generating population graph data: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Failed to generate population graph data: {str(e)}")
+
+
+@api_router.get("/node-metrics/{filename}")
+async def get_node_metrics(
+    request: Request,
+    filename: str,
+    include_betweenness: bool = True,
+    max_pivots: Optional[int] = None,
+    random_seed: Optional[int] = None
+):
+    """Get per-node structural metrics (descendant samples, span, betweenness, recombination flag)."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+
+    if max_pivots is not None and max_pivots < 1:
+        raise HTTPException(status_code=400, detail="max_pivots must be at least 1")
+
+    try:
+        return {
+            "filename": filename,
+            **compute_node_metrics(
+                ts,
+                include_betweenness=include_betweenness,
+                max_pivots=max_pivots,
+                random_seed=random_seed
+            )
+        }
+    except Exception as e:
+        logger.error(f"Error computing node metrics for {filename}: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Failed to compute node metrics: {str(e)}")
+
+
+def resolve_attribute_expression(session_id: str, filename: str, name_or_expression: str) -> str:
+    """A saved attribute's expression, or the argument itself when no attribute has that name."""
+    saved = session_storage.get_session_data(session_id, f"node_attributes:{filename}") or {}
+    return saved.get(name_or_expression, name_or_expression)
+
+
+@api_router.get("/node-attributes/{filename}")
+async def get_node_attributes(request: Request, filename: str):
+    """The file's derived node attributes, and the variables and functions expressions can use."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if session_storage.get_tree_sequence(session_id, filename) is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    saved = session_storage.get_session_data(session_id, f"node_attributes:{filename}") or {}
+    return {
+        "filename": filename,
+        "attributes": [{"name": name, "expression": expression} for name, expression in saved.items()],
+        "reference": expression_reference(),
+    }
+
+
+@api_router.post("/node-attributes")
+async def save_node_attribute(request: Request, attribute_request: NodeAttributeRequest):
+    """Define, redefine or (expression: null) delete a named node attribute for the file."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    filename = attribute_request.filename
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    name = attribute_request.name.strip()
+    if not name.isidentifier():
+        raise HTTPException(status_code=400, detail="Attribute names must be identifiers (letters, digits and underscores)")
+
+    key = f"node_attributes:{filename}"
+    saved = dict(session_storage.get_session_data(session_id, key) or {})
+    summary = None
+    if attribute_request.expression is None:
+        saved.pop(name, None)
+    else:
+        try:
+            # Evaluated once here, so a saved attribute always works for this file
+            result = await run_in_threadpool(get_attribute_values, ts, attribute_request.expression)
+        except ValueError as e:
+            raise HTTPException(status_code=400, detail=str(e))
+        saved[name] = attribute_request.expression
+        summary = {k: v for k, v in result.items() if k != "values"}
+    if saved:
+        session_storage.store_session_data(session_id, key, saved, filename=filename)
+    else:
+        session_storage.delete_session_data(session_id, key)
+    return {"filename": filename, "name": name, "expression": attribute_request.expression, "summary": summary}
+
+
+@api_router.post("/node-attributes/evaluate")
+async def evaluate_node_attribute(request: Request, evaluate_request: NodeAttributeEvaluateRequest):
+    """Per-node values of an expression or saved attribute, for coloring."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    filename = evaluate_request.filename
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    expression = resolve_attribute_expression(session_id, filename, evaluate_request.expression)
+    try:
+        result = await run_in_threadpool(get_attribute_values, ts, expression)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"filename": filename, "expression": expression, **result}
+
+
+@api_router.post("/node-attributes/filter")
+async def filter_by_node_attribute(request: Request, evaluate_request: NodeAttributeEvaluateRequest):
+    """IDs of the nodes for which an expression (or saved attribute) is true."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    filename = evaluate_request.filename
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    expression = resolve_attribute_expression(session_id, filename, evaluate_request.expression)
+    try:
+        node_ids = await run_in_threadpool(filter_nodes, ts, expression)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"filename": filename, "expression": expression, "node_ids": node_ids, "num_nodes": len(node_ids)}
+
+
+def simulation_demography(simulation_request: SimulationRequest):
+    """The msprime demography and sample sets for a simulation request (None, num_samples without a Demes model)."""
+    if simulation_request.demes_model is None:
+        return None, simulation_request.num_samples
+    demography = build_msprime_demography(simulation_request.demes_model)
+    samples = simulation_request.demes_samples or default_sample_sets(
+        parse_demes_model(simulation_request.demes_model), simulation_request.num_samples
+    )
+    return demography, samples
+
+
+def run_simulation(simulation_request: SimulationRequest, demography, samples) -> tskit.TreeSequence:
+    """Simulate ancestry, mutations and sample locations for a simulation request."""
+    # First simulate ancestry
+    ts = msprime.sim_ancestry(
+        samples=samples,
+        sequence_length=simulation_request.sequence_length,
+        recombination_rate=simulation_request.recombination_rate,
+        population_size=simulation_request.population_size if demography is None else None,
+        demography=demography,
+        random_seed=simulation_request.random_seed,
+        model=simulation_request.model,
+        end_time=simulation_request.max_time
+    )
+    
+    # Then add mutations if mutation_rate is provided
+    if simulation_request.mutation_rate is not None:
+        logger.info(f"Adding mutations with rate {simulation_request.mutation_rate}")
+        ts = msprime.sim_mutations(
+            ts,
+            rate=simulation_request.mutation_rate,
+            random_seed=simulation_request.random_seed
+        )
+        logger.info(f"Added {ts.num_mutations} mutations to the tree sequence")
+    
+    # Generate spatial locations for samples based on genealogical relationships
+    logger.info(f"Generating spatial locations for samples using CRS: {simulation_request.crs}")
+    return generate_spatial_locations_for_samples(
+        ts,
+        random_seed=simulation_request.random_seed,
+        crs=simulation_request.crs
+    )
+
+
+@api_router.post("/simulate-tree-sequence/")  # Original version with trailing slash
+async def simulate_tree_sequence(request: Request, simulation_request: SimulationRequest):
+    """Simulate a tree sequence using msprime."""
+    try:
+        # Get session ID from request
+        session_id = session_storage.get_or_create_session(get_client_ip(request))
+        
+        # Validate parameters
+        if simulation_request.num_samples < 2:
+            raise HTTPException(status_code=400, detail="Number of samples must be at least 2")
+        if simulation_request.sequence_length <= 0:
+            raise HTTPException(status_code=400, detail="Sequence length must be positive")
+        if simulation_request.max_time < 1:
+            raise HTTPException(status_code=400, detail="Maximum time must be at least 1")
+        if simulation_request.population_size is not None and simulation_request.population_size < 1:
+            raise HTTPException(status_code=400, detail="Population size must be at least 1")
+        if simulation_request.mutation_rate is not None and simulation_request.mutation_rate <= 0:
+            raise HTTPException(status_code=400, detail="Mutation rate must be positive")
+        if simulation_request.recombination_rate is not None and simulation_request.recombination_rate <= 0:
+            raise HTTPException(status_code=400, detail="Recombination rate must be positive")
+        
+        # Fix the seed so the simulation can be replayed after a restart
+        if simulation_request.random_seed is None:
+            simulation_request.random_seed = secrets.randbelow(2**31 - 2) + 1
+
+        # Log simulation parameters
+        logger.info(f"Simulating tree sequence with parameters: {simulation_request.dict()}")
+        
+        try:
+            demography, samples = simulation_demography(simulation_request)
+        except Exception as e:
+            raise HTTPException(status_code=400, detail=f"Invalid Demes model: {str(e)}")
+        
+        # Simulate the tree sequence
+        try:
+            ts = run_simulation(simulation_request, demography, samples)
+            
+            # Generate a unique filename
+            timestamp = datetime.now().strftime("%Y%m%d_%H%M%S")
+            filename = f"{simulation_request.filename_prefix}_{timestamp}.trees"
+            
+            # Store in session (this will handle saving to disk)
+            session_storage.store_tree_sequence(session_id, filename, ts)
+            session_replay.record(session_id, filename, RECIPE_SIMULATION, params=simulation_request.dict())
+            logger.info(f"Successfully simulated and saved tree sequence to {filename}")
+            
+            return {
+                "message": "Tree sequence simulated successfully",
+                "filename": filename,
+                "num_samples": ts.num_samples,
+                "num_trees": ts.num_trees,
+                "num_mutations": ts.num_mutations if simulation_request.mutation_rate is not None else 0,
+                "sequence_length": ts.sequence_length,
+                "crs": simulation_request.crs
+            }
+            
+        except Exception as e:
+            logger.error(f"Error during tree sequence simulation: {str(e)}")
+            raise HTTPException(status_code=500, detail=f"Simulation failed: {str(e)}")
+            
+    except HTTPException:
+        raise
+    except Exception as e:
+        logger.error(f"Error in simulate_tree_sequence: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Failed to simulate tree sequence: {str(e)}")
+
+
+@api_router.post("/demes/parse")
+async def parse_demes(request: Request, demes_request: DemesModelRequest):
+    """Parse a Demes demographic model for the demography panel.
+    
+    With a filename, deme names are matched to the tree sequence's populations
+    and model times are converted to the node time axis.
+    """
+    try:
+        model = parse_demes_model(demes_request.model)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    
+    response = {"model": model}
+    if demes_request.filename is not None:
+        session_id = session_storage.get_or_create_session(get_client_ip(request))
+        ts = session_storage.get_tree_sequence(session_id, demes_request.filename)
+        if ts is None:
+            raise HTTPException(status_code=404, detail="File not found")
+        response["alignment"] = align_with_tree_sequence(model, ts)
+    
+    return to_json_compatible(response)
+
+
+@api_router.post("/infer-locations-fast")
+async def infer_locations_fast(request: Request, inference_request: FastLocationInferenceRequest):
+    """Infer locations using the fastgaia package for fast spatial inference."""
+    if not FASTGAIA_AVAILABLE:
+        raise HTTPException(status_code=503, detail="fastgaia not available")
+    
+    logger.info(f"Received fast location inference request for file: {inference_request.filename}")
+    
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, inference_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        # Run fastgaia inference
+        ts_with_locations, inference_info = run_fastgaia_inference(
+            ts,
+            weight_span=inference_request.weight_span,
+            weight_branch_length=inference_request.weight_branch_length
         )
         
         # Generate new filename
         new_filename = f"{inference_request.filename.rsplit('.', 1)[0]}_fastgaia.trees"
         
         # Store the result
-        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations)
+        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations, derived_from=inference_request.filename)
+        audit_log.record(session_id, inference_request.filename, EVENT_OPERATION, "infer_locations_fast", inference_request.dict(), output=new_filename)
         
         # Check spatial completeness
         spatial_info = check_spatial_completeness(ts_with_locations)
@@ -841,7 +2414,8 @@ async def infer_locations_gaia(request: Request, inference_request: FastGAIAInfe
         
         # Store the result with new filename
         new_filename = inference_info["new_filename"]
-        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations)
+        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations, derived_from=inference_request.filename)
+        audit_log.record(session_id, inference_request.filename, EVENT_OPERATION, "infer_locations_gaia", inference_request.dict(), output=new_filename)
         
         # Update spatial info for the new tree sequence
         updated_spatial_info = check_spatial_completeness(ts_with_locations)
@@ -894,7 +2468,8 @@ async def infer_locations_gaia_quadratic(request: Request, inference_request: GA
             new_filename = base_filename + '_gaia_quad.trees'
         
         # Store the result
-        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations)
+        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations, derived_from=inference_request.filename)
+        audit_log.record(session_id, inference_request.filename, EVENT_OPERATION, "infer_locations_gaia_quadratic", inference_request.dict(), output=new_filename)
         
         # Update spatial info for the new tree sequence
         updated_spatial_info = check_spatial_completeness(ts_with_locations)
@@ -950,7 +2525,8 @@ async def infer_locations_gaia_linear(request: Request, inference_request: GAIAL
             new_filename = base_filename + '_gaia_linear.trees'
         
         # Store the result
-        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations)
+        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations, derived_from=inference_request.filename)
+        audit_log.record(session_id, inference_request.filename, EVENT_OPERATION, "infer_locations_gaia_linear", inference_request.dict(), output=new_filename)
         
         # Update spatial info for the new tree sequence
         updated_spatial_info = check_spatial_completeness(ts_with_locations)
@@ -1006,7 +2582,8 @@ async def infer_locations_midpoint(request: Request, inference_request: Midpoint
             new_filename = base_filename + '_midpoint.trees'
         
         # Store the result
-        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations)
+        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations, derived_from=inference_request.filename)
+        audit_log.record(session_id, inference_request.filename, EVENT_OPERATION, "infer_locations_midpoint", inference_request.dict(), output=new_filename)
         
         # Update spatial info for the new tree sequence
         updated_spatial_info = check_spatial_completeness(ts_with_locations)
@@ -1120,7 +2697,8 @@ async def update_tree_sequence_locations(request: Request, location_request: Cus
         sample_locations_applied_count = len(sample_locations)
         
         # Store the updated tree sequence
-        session_storage.store_tree_sequence(session_id, new_filename, updated_ts)
+        session_storage.store_tree_sequence(session_id, new_filename, updated_ts, derived_from=location_request.tree_sequence_filename)
+        audit_log.record(session_id, location_request.tree_sequence_filename, EVENT_OPERATION, "update_tree_sequence_locations", location_request.dict(), output=new_filename)
         
         # Clean up CSV files
         session_storage.delete_file(session_id, location_request.sample_locations_filename)
@@ -1216,7 +2794,8 @@ async def infer_locations_sparg(request: Request, inference_request: SpargInfere
             new_filename = base_filename + '_sparg.trees'
         
         # Store the result
-        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations)
+        session_storage.store_tree_sequence(session_id, new_filename, ts_with_locations, derived_from=inference_request.filename)
+        audit_log.record(session_id, inference_request.filename, EVENT_OPERATION, "infer_locations_sparg", inference_request.dict(), output=new_filename)
         
         # Update spatial info for the new tree sequence
         updated_spatial_info = check_spatial_completeness(ts_with_locations)
@@ -1285,7 +2864,8 @@ async def infer_times_tsdate(request: Request, inference_request: TsdateInferenc
             new_filename = base_filename + '_tsdate.trees'
         
         # Store the result
-        session_storage.store_tree_sequence(session_id, new_filename, ts_with_times)
+        session_storage.store_tree_sequence(session_id, new_filename, ts_with_times, derived_from=inference_request.filename)
+        audit_log.record(session_id, inference_request.filename, EVENT_OPERATION, "infer_times_tsdate", inference_request.dict(), output=new_filename)
         
         # Get temporal info for the new tree sequence
         has_temporal = True  # tsdate always adds temporal info
@@ -1304,108 +2884,2869 @@ async def infer_times_tsdate(request: Request, inference_request: TsdateInferenc
         logger.error(f"Error during tsdate temporal inference: {str(e)}")
         raise HTTPException(status_code=500, detail=f"tsdate temporal inference failed: {str(e)}")
 
-#### Geographic API endpoints ####
-
-@api_router.get("/geographic/crs")
-async def get_available_crs():
-    """Get list of available coordinate reference systems."""
-    return {
-        "builtin_crs": {key: crs.to_dict() for key, crs in BUILTIN_CRS.items()}
-}
-
-
-@api_router.get("/geographic/shapes")
-async def get_available_shapes():
-    """Get list of built-in geographic shapes."""
-    try:
-        builtin_shapes = get_builtin_shapes()
-        return {
-            "builtin_shapes": builtin_shapes,
-        }
-    except Exception as e:
-        logger.error(f"Error getting built-in shapes: {e}")
-        raise HTTPException(status_code=500, detail=f"Could not get shapes: {str(e)}")
-
+#### Tree sequence transformation endpoints ####
 
-@api_router.post("/geographic/upload-shapefile")
-async def upload_shapefile(request: Request, file: UploadFile = File(...)):
-    """Upload and process a shapefile."""
+@api_router.get("/polytomy-stats/{filename}")
+async def get_tree_sequence_polytomy_stats(request: Request, filename: str):
+    """Quantify polytomy sizes across the local trees of a tree sequence."""
     client_ip = get_client_ip(request)
     session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
     
     try:
-        contents = await file.read()
-        if len(contents) == 0:
-            raise HTTPException(status_code=400, detail="Empty file")
-        
-        # Process the shapefile
-        shape_data = process_shapefile(contents, file.filename)
-        
-        # Store the shape data in session storage
-        # We'll extend session storage to handle shapes later
-        shape_id = f"uploaded_{file.filename}_{int(time.time())}"
-        
         return {
-            "status": "success",
-            "shape_id": shape_id,
-            "shape_name": shape_data["name"],
-            "bounds": shape_data["bounds"],
-            "feature_count": shape_data["feature_count"],
-            "crs": shape_data["crs"]
+            "filename": filename,
+            **get_polytomy_stats(ts)
         }
-        
     except Exception as e:
-        logger.error(f"Error uploading shapefile: {e}")
-        raise HTTPException(status_code=500, detail=f"Could not process shapefile: {str(e)}")
+        logger.error(f"Error computing polytomy stats for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to compute polytomy stats: {str(e)}")
 
 
-@api_router.get("/geographic/shape/{shape_name}")
-async def get_shape_data(shape_name: str):
-    """Get geometric data for a built-in shape."""
+@api_router.get("/topology-blocks/{filename}")
+async def get_topology_blocks(request: Request, filename: str, tolerance: int = 0):
+    """Group consecutive local trees into blocks of identical or near-identical topology.
+
+    `tolerance` is the largest Robinson-Foulds distance to a block's first tree
+    that still counts as the same topology (0 for exact matches).
+    """
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
     try:
-        builtin_shapes = get_builtin_shapes()
-        if shape_name in builtin_shapes:
-            return builtin_shapes[shape_name]
-        elif shape_name == "unit_grid":
-            return generate_grid_outline(10)
-        else:
-            raise HTTPException(status_code=404, detail=f"Shape '{shape_name}' not found")
+        result = await run_in_threadpool(topology_blocks, ts, tolerance)
+        return {"filename": filename, **result}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
     except Exception as e:
-        logger.error(f"Error getting shape data for {shape_name}: {e}")
-        raise HTTPException(status_code=500, detail=f"Could not get shape data: {str(e)}")
+        logger.error(f"Error computing topology blocks for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to compute topology blocks: {str(e)}")
 
 
-@api_router.post("/geographic/transform-coordinates")
-async def transform_tree_sequence_coordinates(request: Request, transform_request: CoordinateTransformRequest):
-    """Transform coordinates of a tree sequence between CRS."""
+@api_router.get("/unique-topologies/{filename}")
+async def get_unique_topologies(request: Request, filename: str):
+    """Find the genomic intervals that share each distinct local tree topology."""
     client_ip = get_client_ip(request)
     session_id = session_storage.get_or_create_session(client_ip)
-    ts = session_storage.get_tree_sequence(session_id, transform_request.filename)
+    ts = session_storage.get_tree_sequence(session_id, filename)
     if ts is None:
         raise HTTPException(status_code=404, detail="File not found")
-    
+
     try:
-        # Extract coordinates from the tree sequence
-        coordinates = []
-        node_ids = []
-        
-        for node in ts.nodes():
-            if node.individual != -1:
-                individual = ts.individual(node.individual)
-                if individual.location is not None and len(individual.location) >= 2:
-                    coordinates.append((individual.location[0], individual.location[1]))
-                    node_ids.append(node.id)
-        
-        if not coordinates:
-            raise HTTPException(status_code=400, detail="No spatial coordinates found in tree sequence")
-        
-        # Transform coordinates
-        transformed_coords = transform_coordinates(
-            coordinates, 
-            transform_request.source_crs, 
-            transform_request.target_crs
-        )
-        
+        result = await run_in_threadpool(unique_topologies, ts)
+        return {"filename": filename, **result}
+    except Exception as e:
+        logger.error(f"Error hashing topologies for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to find unique topologies: {str(e)}")
+
+
+def get_sample_set_definitions(session_id: str, filename: str) -> Dict[str, Dict[str, Any]]:
+    return session_storage.get_session_data(session_id, f"sample_sets:{filename}") or {}
+
+
+def resolve_request_samples(
+    session_id: str,
+    filename: str,
+    ts: tskit.TreeSequence,
+    samples: Optional[List[int]],
+    sample_set: Optional[str]
+) -> Optional[List[int]]:
+    """Explicit samples, or those of a named sample set; raises ValueError if both are given."""
+    if sample_set is None:
+        return samples
+    if samples is not None:
+        raise ValueError("Give samples or sample_set, not both")
+    return resolve_sample_sets(ts, get_sample_set_definitions(session_id, filename), [sample_set])[sample_set]
+
+
+def get_statistic_sample_sets(session_id: str, filename: str, ts: tskit.TreeSequence, spec: str) -> Dict[str, List[int]]:
+    """Sample sets for a statistic: "all", "population", or comma-separated names of the file's sets."""
+    if spec in SAMPLE_SET_OPTIONS:
+        return sample_sets_for(ts, spec)
+    names = [name.strip() for name in spec.split(",") if name.strip()]
+    return resolve_sample_sets(ts, get_sample_set_definitions(session_id, filename), names)
+
+
+def get_stat_workers(session_id: str, filename: str) -> int:
+    """Processes for a windowed statistic (the stat_workers setting).
+
+    One for protected files, since the workers may read them from a temporary
+    file, and in restricted mode, whose limits don't extend to child processes.
+    """
+    if session_storage.is_file_protected(session_id, filename) or is_sandbox_enabled():
+        return 1
+    return settings.get("stat_workers")
+
+
+@api_router.get("/sample-sets/{filename}")
+async def get_sample_sets(request: Request, filename: str):
+    """The file's named sample sets, with their sizes (or why they don't resolve)."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    definitions = get_sample_set_definitions(session_id, filename)
+    return {"filename": filename, "sample_sets": await run_in_threadpool(describe_sample_sets, ts, definitions)}
+
+
+@api_router.post("/sample-sets")
+async def save_sample_set(request: Request, set_request: SampleSetRequest):
+    """Define, redefine or (definition: null) delete a named sample set for the file."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    filename = set_request.filename
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    name = set_request.name.strip()
+    if not name or "," in name or name in SAMPLE_SET_OPTIONS:
+        raise HTTPException(status_code=400, detail=f"Sample set names must be non-empty, without commas, and not {list(SAMPLE_SET_OPTIONS)}")
+
+    definitions = dict(get_sample_set_definitions(session_id, filename))
+    if set_request.definition is None:
+        dependents = [other for other, definition in definitions.items() if other != name and name in referenced_sets(definition)]
+        if dependents:
+            raise HTTPException(status_code=400, detail=f"Sample set '{name}' is used by {dependents}")
+        definitions.pop(name, None)
+    else:
+        definitions[name] = set_request.definition
+        try:
+            samples = await run_in_threadpool(evaluate_sample_set, ts, set_request.definition, definitions, (name,))
+        except (ValueError, TypeError) as e:
+            raise HTTPException(status_code=400, detail=f"Invalid sample set '{name}': {e}")
+        if not samples:
+            raise HTTPException(status_code=400, detail=f"Sample set '{name}' selects no samples")
+    if definitions:
+        session_storage.store_session_data(session_id, f"sample_sets:{filename}", definitions, filename=filename)
+    else:
+        session_storage.delete_session_data(session_id, f"sample_sets:{filename}")
+    return await get_sample_sets(request, filename)
+
+
+@api_router.get("/statistics/{filename}")
+async def get_windowed_statistic(
+    request: Request,
+    filename: str,
+    statistic: str = "diversity",
+    mode: str = "site",
+    num_windows: Optional[int] = None,
+    window_size: Optional[float] = None,
+    tree_windows: bool = False,
+    sample_sets: str = "all",
+    polarised: bool = True,
+    missing_data: str = "ignore",
+    time_units: str = "native",
+    generation_time: Optional[float] = None,
+    mutation_rate: Optional[float] = None,
+    accelerator: Optional[str] = None,
+    format: str = "json"
+):
+    """A windowed statistic as a tidy table; the same computation as `argscape stats`.
+
+    `sample_sets` is all, population, or comma-separated names of the file's
+    sample sets. `format` is json (returned inline), csv or parquet (downloaded). `missing_data`
+    is ignore, mask or drop (see argscape.backend.missing_data). Branch-mode
+    values are converted to `time_units` (native, generations or years).
+    `accelerator` (cpu, auto or gpu; default from the stat_accelerator setting)
+    picks the GPU path for branch-mode diversity and tmrca. The stat_workers
+    setting splits the windows across that many processes.
+    """
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    if format not in STAT_TABLE_FORMATS:
+        raise HTTPException(status_code=400, detail=f"format must be one of {list(STAT_TABLE_FORMATS)}")
+
+    try:
+        windows = make_windows(ts, num_windows, window_size, tree_windows)
+        contig_map = get_contig_map(session_id, filename, ts)
+        if contig_map is not None:
+            windows = contig_map.split_windows(windows)
+        result = await run_in_threadpool(
+            compute_statistic, ts, statistic, mode, windows, get_statistic_sample_sets(session_id, filename, ts, sample_sets), polarised, missing_data,
+            accelerator or settings.get("stat_accelerator"), get_stat_workers(session_id, filename)
+        )
+        calibration = get_time_calibration(session_id, filename, ts, generation_time, mutation_rate)
+        result = scale_statistic(result, calibration, time_units)
+        if contig_map is not None:
+            result["columns"] = contig_map.annotate_table(result["columns"])
+        contents = None if format == "json" else serialize_table(result, format)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error computing {statistic} for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to compute {statistic}: {str(e)}")
+
+    if contents is None:
+        return {"filename": filename, **result}
+    base_filename = filename.rsplit(".", 1)[0]
+    audit_log.record(session_id, filename, EVENT_EXPORT, "statistic_export", {"statistic": statistic, "mode": mode, "num_windows": result["num_windows"], "sample_sets": sample_sets, "missing_data": missing_data, "time_units": time_units, "format": format}, output=f"{base_filename}_{statistic}.{format}")
+    return Response(
+        content=contents,
+        media_type="text/csv" if format == "csv" else "application/octet-stream",
+        headers={"Content-Disposition": f'attachment; filename="{base_filename}_{statistic}.{format}"'}
+    )
+
+
+@api_router.get("/missing-data/{filename}")
+async def get_missing_data(request: Request, filename: str):
+    """Samples isolated (missing) over parts of the genome, per sample and as merged regions."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        report = await run_in_threadpool(missing_data_report, ts)
+        return {"filename": filename, **report}
+    except Exception as e:
+        logger.error(f"Error finding missing data in {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to find missing data: {str(e)}")
+
+
+@api_router.get("/missing-data/{filename}/track")
+async def get_missingness_track(
+    request: Request,
+    filename: str,
+    num_windows: Optional[int] = 100,
+    window_size: Optional[float] = None,
+    tree_windows: bool = False,
+    per_sample: bool = False
+):
+    """Per-window fraction of sample ancestry that is missing, for drawing as a track (one per sample if asked)."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        windows = make_windows(ts, None if window_size or tree_windows else num_windows, window_size, tree_windows)
+        contig_map = get_contig_map(session_id, filename, ts)
+        if contig_map is not None:
+            windows = contig_map.split_windows(windows)
+        result = await run_in_threadpool(missingness_track, ts, windows, per_sample)
+        if contig_map is not None:
+            result["columns"] = contig_map.annotate_table(result["columns"])
+        return {"filename": filename, **result}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error building missingness track for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to build missingness track: {str(e)}")
+
+
+@api_router.get("/sweep-scan/{filename}")
+async def get_sweep_scan(request: Request, filename: str, num_windows: int = 100, threshold: float = 2.0):
+    """Windowed selection scan: haplotype homozygosity, relative TMRCA and branch-length skew."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        result = await run_in_threadpool(sweep_scan, ts, num_windows, threshold)
+        # Low recombination inflates homozygosity, so show the rate alongside each window
+        recombination_map = rate_map_registry.get(session_id, "recombination")
+        if recombination_map is not None:
+            rates = recombination_map.contig()
+            for window in result["windows"]:
+                window["recombination_rate"] = rates.mean_rate(window["left"], window["right"])
+        return {"filename": filename, **result}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error running sweep scan for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to run sweep scan: {str(e)}")
+
+
+def get_tracks(session_id: str, filename: str) -> Dict[str, Dict[str, Any]]:
+    return session_storage.get_session_data(session_id, f"tracks:{filename}") or {}
+
+
+def store_track(session_id: str, filename: str, track: Dict[str, Any]):
+    tracks = get_tracks(session_id, filename)
+    tracks[track["name"]] = track
+    session_storage.store_session_data(session_id, f"tracks:{filename}", tracks, filename=filename)
+
+
+def build_track(session_id: str, filename: str, ts: tskit.TreeSequence, name: str, source: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """Run a windowed analysis with the parameters of its own endpoint and turn the result into a track."""
+    num_windows = params.get("num_windows", 100)
+    window_size = params.get("window_size")
+    tree_windows = params.get("tree_windows", False)
+    if source == "sweep_scan":
+        return from_sweep_scan(name, sweep_scan(ts, num_windows, params.get("threshold", 2.0)), params)
+    if source not in ("statistic", "missingness"):
+        raise ValueError(f"Tracks are built from statistic, missingness or sweep_scan, not '{source}'")
+
+    windows = make_windows(ts, None if window_size or tree_windows else num_windows, window_size, tree_windows)
+    contig_map = get_contig_map(session_id, filename, ts)
+    if contig_map is not None:
+        windows = contig_map.split_windows(windows)
+    if source == "missingness":
+        result = missingness_track(ts, windows, params.get("per_sample", False))
+        return from_columns(name, source, result["columns"], unit="fraction missing", params=params)
+
+    statistic = params.get("statistic", "diversity")
+    mode = params.get("mode", "site")
+    sample_sets = get_statistic_sample_sets(session_id, filename, ts, params.get("sample_sets", "all"))
+    result = compute_statistic(
+        ts, statistic, mode, windows, sample_sets, params.get("polarised", True), params.get("missing_data", "ignore"),
+        params.get("accelerator") or settings.get("stat_accelerator"), get_stat_workers(session_id, filename)
+    )
+    calibration = get_time_calibration(session_id, filename, ts, params.get("generation_time"), params.get("mutation_rate"))
+    result = scale_statistic(result, calibration, params.get("time_units", "native"))
+    return from_columns(name, source, result["columns"], unit=result["time_units"], params={"statistic": statistic, "mode": mode, **params})
+
+
+@api_router.get("/tracks/{filename}")
+async def list_tracks(request: Request, filename: str):
+    """The file's registered tracks, without their values."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if session_storage.get_tree_sequence(session_id, filename) is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    return {"filename": filename, "tracks": [describe_track(t) for t in get_tracks(session_id, filename).values()]}
+
+
+@api_router.post("/tracks")
+async def register_track(request: Request, track_request: TrackRequest):
+    """Compute a statistic, missingness or sweep scan and register it as a track (replacing one of the same name)."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, track_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        name = validate_track_name(track_request.name)
+        track = await run_in_threadpool(build_track, session_id, track_request.filename, ts, name, track_request.source, track_request.params)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error building track {track_request.name} for {track_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to build track: {str(e)}")
+    store_track(session_id, track_request.filename, track)
+    return describe_track(track)
+
+
+@api_router.post("/tracks/{filename}/upload")
+async def upload_track(request: Request, filename: str, file: UploadFile = File(...), name: Optional[str] = None, kind: Optional[str] = None):
+    """Register a BED (feature) or BEDgraph (value) file as a track; `kind` defaults from the extension."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    upload_name = file.filename or "track.bed"
+    kind = kind or ("bedgraph" if upload_name.lower().endswith((".bedgraph", ".bdg", ".bg")) else "bed")
+    contents = await file.read()
+    try:
+        name = validate_track_name(name or upload_name.split(".", 1)[0])
+        track = await run_in_threadpool(
+            parse_track_file, contents.decode("utf-8"), kind, name, ts.sequence_length, get_contig_map(session_id, filename, ts)
+        )
+    except (ValueError, UnicodeDecodeError) as e:
+        raise HTTPException(status_code=400, detail=f"Invalid {kind} file: {str(e)}")
+    store_track(session_id, filename, track)
+    return describe_track(track)
+
+
+@api_router.post("/tracks/merge")
+async def merge_registered_tracks(request: Request, merge_request: TrackMergeRequest):
+    """Combine registered tracks on a shared grid of their breakpoints and register the result."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if session_storage.get_tree_sequence(session_id, merge_request.filename) is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    tracks = get_tracks(session_id, merge_request.filename)
+    missing = [t for t in merge_request.tracks if t not in tracks]
+    if missing:
+        raise HTTPException(status_code=404, detail=f"Unknown tracks: {missing}")
+
+    try:
+        track = await run_in_threadpool(
+            merge_tracks, merge_request.name, [tracks[t] for t in merge_request.tracks], merge_request.operation
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    store_track(session_id, merge_request.filename, track)
+    return describe_track(track)
+
+
+@api_router.get("/tracks/{filename}/{name}")
+async def get_track(
+    request: Request,
+    filename: str,
+    name: str,
+    start: float = 0,
+    end: Optional[float] = None,
+    max_bins: int = DEFAULT_MAX_BINS,
+    method: Optional[str] = None,
+    series: Optional[str] = None
+):
+    """A track's values over the viewport [start, end), binned to at most `max_bins` (mean, max or min)."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    track = get_tracks(session_id, filename).get(name)
+    if track is None:
+        raise HTTPException(status_code=404, detail="Track not found")
+    try:
+        result = await run_in_threadpool(
+            query_track, track, start, end, max_bins, method, series.split(",") if series else None
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"filename": filename, **result}
+
+
+@api_router.get("/tracks/{filename}/{name}/export")
+async def export_registered_track(
+    request: Request,
+    filename: str,
+    name: str,
+    format: str = "bedgraph",
+    series: Optional[str] = None,
+    chrom: str = "1"
+):
+    """Download a track as BEDgraph (one series) or CSV (all series)."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    track = get_tracks(session_id, filename).get(name)
+    if ts is None or track is None:
+        raise HTTPException(status_code=404, detail="Track not found")
+    try:
+        contents = await run_in_threadpool(export_track, track, format, series, chrom, get_contig_map(session_id, filename, ts))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    output = f"{filename.rsplit('.', 1)[0]}_{name.replace(' ', '_')}.{format}"
+    audit_log.record(session_id, filename, EVENT_EXPORT, "track_export", {"track": name, "format": format, "series": series}, output=output)
+    return Response(
+        content=contents,
+        media_type="text/csv" if format == "csv" else "text/plain",
+        headers={"Content-Disposition": f'attachment; filename="{output}"'}
+    )
+
+
+@api_router.delete("/tracks/{filename}/{name}")
+async def delete_track(request: Request, filename: str, name: str):
+    """Remove a registered track."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    tracks = get_tracks(session_id, filename)
+    if tracks.pop(name, None) is None:
+        raise HTTPException(status_code=404, detail="Track not found")
+    session_storage.store_session_data(session_id, f"tracks:{filename}", tracks, filename=filename)
+    return {"status": "deleted", "filename": filename, "name": name}
+
+
+@api_router.post("/introgression")
+async def find_introgressed_tracts(request: Request, introgression_request: IntrogressionRequest):
+    """Candidate tracts where recipient samples coalesce unexpectedly recently with the donor population."""
+    if introgression_request.format not in ("json", "csv"):
+        raise HTTPException(status_code=400, detail="format must be 'json' or 'csv'")
+
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, introgression_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        result = await run_in_threadpool(
+            detect_introgression,
+            ts,
+            introgression_request.recipient,
+            introgression_request.donor,
+            introgression_request.relative_threshold,
+            introgression_request.max_time,
+            introgression_request.min_length
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error detecting introgression in {introgression_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to detect introgression: {str(e)}")
+
+    if introgression_request.format == "csv":
+        base_filename = introgression_request.filename.rsplit(".", 1)[0]
+        audit_log.record(
+            session_id,
+            introgression_request.filename,
+            EVENT_EXPORT,
+            "introgression",
+            introgression_request.dict(),
+            output=f"{base_filename}_introgression.csv"
+        )
+        return Response(
+            content=tracts_to_csv(result["tracts"]),
+            media_type="text/csv",
+            headers={"Content-Disposition": f'attachment; filename="{base_filename}_introgression.csv"'}
+        )
+    return {"filename": introgression_request.filename, **result}
+
+
+@api_router.post("/ne-through-time")
+async def estimate_ne_through_time(request: Request, ne_request: NeEstimateRequest):
+    """Piecewise-constant Ne(t) from coalescence rates, per population, with bootstrap intervals."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, ne_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        result = await run_in_threadpool(
+            estimate_ne,
+            ts,
+            ne_request.num_epochs,
+            ne_request.time_breaks,
+            ne_request.num_bootstrap,
+            ne_request.ploidy,
+            ne_request.random_seed
+        )
+        return {"filename": ne_request.filename, **result}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error estimating Ne for {ne_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to estimate Ne: {str(e)}")
+
+
+@api_router.post("/tmrca-matrix")
+async def get_tmrca_matrix(request: Request, tmrca_request: TmrcaMatrixRequest):
+    """Pairwise TMRCA matrix for heatmap and MDS views, genome-wide or at given positions."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, tmrca_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        result = await run_in_threadpool(
+            compute_tmrca_matrix,
+            ts,
+            resolve_request_samples(session_id, tmrca_request.filename, ts, tmrca_request.samples, tmrca_request.sample_set),
+            tmrca_request.positions,
+            tmrca_request.max_samples,
+            tmrca_request.random_seed,
+            get_worker_count(settings.get("num_threads"), settings.get("low_power_mode"))
+        )
+        return {"filename": tmrca_request.filename, **result}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error computing TMRCA matrix for {tmrca_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to compute TMRCA matrix: {str(e)}")
+
+
+@api_router.post("/sample-embedding")
+async def get_sample_embedding(request: Request, embedding_request: SampleEmbeddingRequest):
+    """2D/3D MDS embedding of samples from ARG-derived distances, with population colors."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, embedding_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        result = await run_in_threadpool(
+            sample_embedding,
+            ts,
+            embedding_request.distance,
+            embedding_request.dimensions,
+            resolve_request_samples(session_id, embedding_request.filename, ts, embedding_request.samples, embedding_request.sample_set),
+            embedding_request.max_samples,
+            embedding_request.random_seed,
+            get_worker_count(settings.get("num_threads"), settings.get("low_power_mode"))
+        )
+        palette = resolve_palette(ts, session_storage.get_session_data(session_id, f"palette:{embedding_request.filename}"))
+        return {"filename": embedding_request.filename, **result, "populations": palette["populations"]}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error embedding samples of {embedding_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to embed samples: {str(e)}")
+
+
+@api_router.post("/redate/validate")
+async def validate_redating_request(request: Request, redating_request: RedatingRequest):
+    """Check mutation rate, Ne prior and tsdate availability before starting a re-dating job."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, redating_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    return {
+        "filename": redating_request.filename,
+        **validate_redating(ts, redating_request.mutation_rate, redating_request.method, redating_request.population_size)
+    }
+
+
+@api_router.get("/redate/compare")
+async def compare_redated_times(request: Request, before: str, after: str):
+    """Before/after comparison of node times between a tree sequence and its re-dated version."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    stored = session_storage.get_session_data(session_id, f"redate:{before}:{after}")
+    if stored is not None:
+        return {"before": before, "after": after, **stored}
+
+    before_ts = session_storage.get_tree_sequence(session_id, before)
+    after_ts = session_storage.get_tree_sequence(session_id, after)
+    if before_ts is None or after_ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    try:
+        comparison = await run_in_threadpool(compare_node_times, before_ts, after_ts)
+        return {"before": before, "after": after, **comparison}
+    except Exception as e:
+        logger.error(f"Error comparing node times of {before} and {after}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to compare node times: {str(e)}")
+
+
+@api_router.post("/resolve-polytomies")
+async def resolve_tree_sequence_polytomies(request: Request, resolution_request: PolytomyResolutionRequest):
+    """Resolve polytomies into random binary subtrees, producing a derived tree sequence."""
+    logger.info(f"Received polytomy resolution request for file: {resolution_request.filename}")
+    
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, resolution_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        resolved_ts, resolution_info = resolve_polytomies(
+            ts,
+            method=resolution_request.method,
+            random_seed=resolution_request.random_seed
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error resolving polytomies: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Polytomy resolution failed: {str(e)}")
+    
+    new_filename = get_derived_filename(resolution_request.filename, "resolved")
+    session_storage.store_tree_sequence(session_id, new_filename, resolved_ts, derived_from=resolution_request.filename)
+    audit_log.record(session_id, resolution_request.filename, EVENT_OPERATION, "resolve_tree_sequence_polytomies", resolution_request.dict(), output=new_filename)
+    record_resolution_recipe(session_id, new_filename, resolution_request.filename, resolution_request.method, resolution_request.random_seed)
+    
+    logger.info(f"Polytomy resolution completed successfully: {new_filename}")
+    
+    return {
+        "status": "success",
+        "message": "Polytomies resolved successfully",
+        "original_filename": resolution_request.filename,
+        "new_filename": new_filename,
+        "num_nodes": resolved_ts.num_nodes,
+        "num_edges": resolved_ts.num_edges,
+        "num_trees": resolved_ts.num_trees,
+        **resolution_info
+    }
+
+@api_router.get("/transforms/{filename}")
+async def get_transform_stack(request: Request, filename: str):
+    """The file's saved transform stack and the operations a stack can hold."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if filename not in session_storage.get_file_list(session_id):
+        raise HTTPException(status_code=404, detail="File not found")
+    display_filename = get_derived_filename(filename, "display")
+    return {
+        "filename": filename,
+        "steps": session_storage.get_session_data(session_id, f"transforms:{filename}", []),
+        "operations": TRANSFORM_PARAMS,
+        "display_filename": display_filename if display_filename in session_storage.get_file_list(session_id) else None,
+    }
+
+
+@api_router.post("/transforms")
+async def apply_transforms(request: Request, stack_request: TransformStackRequest):
+    """
+    Save a file's transform stack and rebuild its display tree sequence from the original.
+    Steps whose inputs haven't changed since the last run come from cache.
+    With no enabled steps the display file is removed and the original is shown.
+    """
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    filename = stack_request.filename
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        for step in stack_request.steps:
+            params = step.get("params") or {}
+            if params.get("sample_set"):
+                params["samples"] = resolve_request_samples(session_id, filename, ts, None, params.pop("sample_set"))
+        steps = normalize_stack(stack_request.steps)
+        display_filename = get_derived_filename(filename, "display")
+        if not any(step["enabled"] for step in steps):
+            session_storage.delete_file(session_id, display_filename)
+            clear_transform_cache(ts)
+            session_storage.store_session_data(session_id, f"transforms:{filename}", steps, filename=filename)
+            return {"filename": filename, "steps": steps, "display_filename": None, "report": []}
+        display_ts, report = await run_in_threadpool(apply_transform_stack, ts, steps)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error applying transform stack to {filename}: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Transform stack failed: {str(e)}")
+
+    session_storage.store_tree_sequence(session_id, display_filename, display_ts, derived_from=filename)
+    session_storage.store_session_data(session_id, f"transforms:{filename}", steps, filename=filename)
+    session_replay.record(session_id, display_filename, RECIPE_DERIVED, parent=filename, operation="transform_stack", params={"steps": steps})
+    audit_log.record(session_id, filename, EVENT_OPERATION, "apply_transforms", {"steps": steps}, output=display_filename)
+    return {
+        "filename": filename,
+        "steps": steps,
+        "display_filename": display_filename,
+        "report": report,
+        "num_nodes": display_ts.num_nodes,
+        "num_edges": display_ts.num_edges,
+        "num_trees": display_ts.num_trees,
+    }
+
+
+@api_router.get("/metadata-schemas/{filename}")
+async def get_tree_sequence_metadata_schemas(request: Request, filename: str):
+    """Get the metadata schemas of the top-level metadata and every table."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    return {"filename": filename, "schemas": get_metadata_schemas(ts)}
+
+
+@api_router.get("/metadata/{filename}/{table}")
+async def get_tree_sequence_table_metadata(
+    request: Request,
+    filename: str,
+    table: str,
+    offset: int = 0,
+    limit: int = 100,
+    ids: Optional[str] = None
+):
+    """Decode metadata rows of a table into typed values (ids: comma-separated row IDs)."""
+    if table not in METADATA_TABLES and table != TOP_LEVEL_METADATA:
+        raise HTTPException(status_code=400, detail=f"table must be one of {list(METADATA_TABLES) + [TOP_LEVEL_METADATA]}")
+    
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        row_ids = [int(i) for i in ids.split(",") if i.strip()] if ids else None
+        return {"filename": filename, **decode_metadata(ts, table, row_ids, offset, limit)}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/node-metadata")
+async def get_tree_sequence_node_metadata(request: Request, node_request: NodeMetadataRequest):
+    """
+    Decode one node's metadata (and its individual's) on demand.
+    Values over the preview size are shortened unless full is set;
+    decode_schema decodes tables stored without a schema, such as raw struct bytes.
+    """
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, node_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        result = await run_in_threadpool(
+            get_node_metadata, ts, node_request.node_id, node_request.full, node_request.decode_schema
+        )
+        return {"filename": node_request.filename, **result}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/metadata/edit")
+async def edit_tree_sequence_metadata(request: Request, edit_request: MetadataEditRequest):
+    """Write edited metadata and schemas into a derived tree sequence."""
+    unknown = set(edit_request.edits) | set(edit_request.schemas)
+    unknown -= set(METADATA_TABLES) | {TOP_LEVEL_METADATA}
+    if unknown:
+        raise HTTPException(status_code=400, detail=f"Unknown tables: {sorted(unknown)}")
+    if not edit_request.edits and not edit_request.schemas:
+        raise HTTPException(status_code=400, detail="No metadata edits given")
+    
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, edit_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        edited_ts = await run_in_threadpool(apply_metadata_edits, ts, edit_request.edits, edit_request.schemas)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error editing metadata of {edit_request.filename}: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Metadata edit failed: {str(e)}")
+    
+    new_filename = get_derived_filename(edit_request.filename, "metadata")
+    session_storage.store_tree_sequence(session_id, new_filename, edited_ts, derived_from=edit_request.filename)
+    audit_log.record(session_id, edit_request.filename, EVENT_OPERATION, "edit_tree_sequence_metadata", edit_request.dict(), output=new_filename)
+    
+    return {
+        "status": "success",
+        "original_filename": edit_request.filename,
+        "new_filename": new_filename,
+        "num_rows_edited": {table: len(rows) for table, rows in edit_request.edits.items()},
+        "schemas_changed": sorted(edit_request.schemas),
+    }
+
+
+@api_router.get("/populations/{filename}")
+async def get_populations(request: Request, filename: str):
+    """The populations table: names, descriptions, colors, display order and sample counts."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    try:
+        result = list_populations(ts, session_storage.get_session_data(session_id, f"palette:{filename}"))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"filename": filename, **result}
+
+
+@api_router.post("/populations/edit")
+async def edit_populations(request: Request, edit_request: PopulationEditRequest):
+    """Write population names, descriptions, colors and display order into a derived tree sequence."""
+    if not edit_request.edits and edit_request.order is None:
+        raise HTTPException(status_code=400, detail="No population edits given")
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    filename = edit_request.filename
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        edited_ts, report = await run_in_threadpool(apply_population_edits, ts, edit_request.edits, edit_request.order)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error editing populations of {filename}: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Failed to edit populations: {str(e)}")
+
+    new_filename = get_derived_filename(filename, "populations")
+    session_storage.store_tree_sequence(session_id, new_filename, edited_ts, derived_from=filename)
+    params = {"edits": {str(k): v for k, v in edit_request.edits.items()}, "order": edit_request.order}
+    session_replay.record(session_id, new_filename, RECIPE_DERIVED, parent=filename, operation="edit_populations", params=params)
+    audit_log.record(session_id, filename, EVENT_OPERATION, "edit_populations", params, output=new_filename)
+    return {
+        "status": "success",
+        "original_filename": filename,
+        "new_filename": new_filename,
+        **report,
+        **list_populations(edited_ts, session_storage.get_session_data(session_id, f"palette:{filename}")),
+    }
+
+@api_router.get("/display-times/{filename}")
+async def get_tree_sequence_display_times(
+    request: Request,
+    filename: str,
+    mode: str = "time",
+    generation_time: Optional[float] = None
+):
+    """Get per-node display times for an alternative time scale (time, log, rank or years)."""
+    if mode not in TIME_SCALE_MODES:
+        raise HTTPException(status_code=400, detail=f"mode must be one of {list(TIME_SCALE_MODES)}")
+    
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        if mode == "years":
+            # Years per native unit, which is the generation time only when times are in generations
+            generation_time = get_time_calibration(session_id, filename, ts, generation_time).factor("years")
+        return {
+            "filename": filename,
+            **get_display_times(ts, mode, generation_time)
+        }
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error computing display times for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to compute display times: {str(e)}")
+
+@api_router.get("/simulation-context/{filename}")
+async def get_tree_sequence_simulation_context(request: Request, filename: str):
+    """Get the demographic model and simulation parameters recorded by SLiM, stdpopsim or msprime."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        return {"filename": filename, **get_simulation_context(ts)}
+    except Exception as e:
+        logger.error(f"Error reading simulation context for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to read simulation context: {str(e)}")
+
+#### Export estimate endpoints ####
+
+@api_router.post("/export/estimate")
+async def estimate_export_size(request: Request, estimate_request: ExportEstimateRequest):
+    """Expected size and duration of an export, and whether it will be rasterized."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, estimate_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    try:
+        return estimate_export(
+            ts,
+            estimate_request.format,
+            estimate_request.element_counts,
+            estimate_request.width,
+            estimate_request.height,
+            estimate_request.scale
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/export/svg")
+async def export_svg(request: Request, svg_request: SvgExportRequest):
+    """Download the ARG as SVG with per-population or per-time-band layers and ID-matched elements.
+
+    Returns 413 when the drawing is above the rasterization limit, so the
+    viewer can export PNG instead.
+    """
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, svg_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    counts = {"nodes": len(svg_request.positions)} if svg_request.positions else {}
+    estimate = estimate_export(ts, "svg", counts)
+    if estimate["rasterized"] and not svg_request.force:
+        raise HTTPException(status_code=413, detail=estimate["warnings"][0])
+
+    try:
+        edge_polylines = None
+        if svg_request.bundle_edges:
+            bundled = await run_in_threadpool(bundle_edges, ts, svg_request.positions or default_positions(ts, SVG_DEFAULT_WIDTH, SVG_DEFAULT_HEIGHT))
+            edge_polylines = polyline_lookup(bundled)
+        contents = await run_in_threadpool(
+            build_svg,
+            ts,
+            svg_request.positions or None,
+            svg_request.group_by,
+            svg_request.num_time_bands,
+            session_storage.get_session_data(session_id, f"palette:{svg_request.filename}"),
+            show_labels=svg_request.show_labels,
+            show_mutations=svg_request.show_mutations,
+            title=svg_request.filename,
+            edge_polylines=edge_polylines
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error exporting SVG for {svg_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"SVG export failed: {str(e)}")
+
+    base_filename = svg_request.filename.rsplit(".", 1)[0]
+    audit_log.record(
+        session_id,
+        svg_request.filename,
+        EVENT_EXPORT,
+        "svg_export",
+        {"group_by": svg_request.group_by, "num_time_bands": svg_request.num_time_bands, "show_labels": svg_request.show_labels, "show_mutations": svg_request.show_mutations, "bundle_edges": svg_request.bundle_edges},
+        output=f"{base_filename}_arg.svg"
+    )
+    return Response(
+        content=contents,
+        media_type="image/svg+xml",
+        headers={"Content-Disposition": f'attachment; filename="{base_filename}_arg.svg"'}
+    )
+
+
+@api_router.post("/export/html")
+async def export_html_bundle(request: Request, bundle_request: HtmlBundleRequest):
+    """Download the view as one self-contained, read-only HTML file with an embedded viewer."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, bundle_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    layout = {"algorithm": bundle_request.algorithm, "parameters": bundle_request.parameters} if bundle_request.algorithm else None
+    try:
+        contents = await run_in_threadpool(
+            build_html_bundle,
+            ts,
+            bundle_request.positions or default_positions(ts, SVG_DEFAULT_WIDTH, SVG_DEFAULT_HEIGHT),
+            session_storage.get_session_data(session_id, f"palette:{bundle_request.filename}"),
+            bundle_request.title or bundle_request.filename,
+            layout
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error exporting HTML bundle for {bundle_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"HTML export failed: {str(e)}")
+
+    base_filename = bundle_request.filename.rsplit(".", 1)[0]
+    audit_log.record(
+        session_id,
+        bundle_request.filename,
+        EVENT_EXPORT,
+        "html_bundle_export",
+        {"num_nodes": len(bundle_request.positions), "algorithm": bundle_request.algorithm, "parameters": bundle_request.parameters},
+        output=f"{base_filename}_view.html"
+    )
+    return Response(
+        content=contents,
+        media_type="text/html",
+        headers={"Content-Disposition": f'attachment; filename="{base_filename}_view.html"'}
+    )
+
+
+#### Figure template endpoints ####
+
+def read_figure_template(template_request: FigureTemplateRequest) -> Dict[str, Any]:
+    if template_request.template_text is not None:
+        return parse_template(template_request.template_text, template_request.template_filename)
+    return validate_template(template_request.template or DEFAULT_TEMPLATE)
+
+
+@api_router.get("/figures/default-template")
+async def get_default_figure_template():
+    return DEFAULT_TEMPLATE
+
+
+@api_router.post("/figures/validate")
+async def validate_figure_template(template_request: FigureTemplateRequest):
+    """The template with defaults filled in, or 400 with what is wrong."""
+    try:
+        return read_figure_template(template_request)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/figures/render")
+async def render_figure_template(request: Request, render_request: FigureRenderRequest):
+    """Render a figure template for a tree sequence as PNG, SVG or PDF."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, render_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    try:
+        template = read_figure_template(render_request)
+        contents = await run_in_threadpool(
+            render_figure,
+            ts,
+            template,
+            render_request.format,
+            session_storage.get_session_data(session_id, f"palette:{render_request.filename}")
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error rendering figure for {render_request.filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Figure rendering failed: {str(e)}")
+
+    base_filename = render_request.filename.rsplit(".", 1)[0]
+    output = f"{base_filename}_figure.{render_request.format}"
+    audit_log.record(session_id, render_request.filename, EVENT_EXPORT, "figure", {"template": template, "format": render_request.format}, output=output)
+    media_types = {"png": "image/png", "svg": "image/svg+xml", "pdf": "application/pdf"}
+    return Response(
+        content=contents,
+        media_type=media_types[render_request.format],
+        headers={"Content-Disposition": f'attachment; filename="{output}"'}
+    )
+
+
+#### Frame budget endpoints ####
+
+@api_router.post("/render/frame-stats")
+async def report_frame_stats(request: Request, stats: FrameStatsRequest):
+    """Report the viewer's frame rate; returns the level of detail to use from now on."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if stats.target_fps is not None and not 5 <= stats.target_fps <= 240:
+        raise HTTPException(status_code=400, detail="target_fps must be between 5 and 240")
+    budget = frame_budgets.get(session_id, stats.filename)
+    try:
+        changed = budget.report(stats.fps, stats.target_fps)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    if changed:
+        logger.info(f"Frame budget for {stats.filename}: level {budget.level} at {stats.fps:.1f} fps")
+    return {"filename": stats.filename, "changed": changed, **budget.state()}
+
+@api_router.get("/render/lod/{filename}")
+async def get_render_lod(request: Request, filename: str):
+    """Current level of detail for a file."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    return {"filename": filename, "changed": False, **frame_budgets.get(session_id, filename).state()}
+
+@api_router.delete("/render/lod/{filename}")
+async def reset_render_lod(request: Request, filename: str):
+    """Return a file's level of detail to full detail."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    frame_budgets.reset(session_id, filename)
+    return {"filename": filename, "reset": True}
+
+
+#### Layout API endpoints ####
+
+@api_router.post("/layout/relax-neighborhood")
+async def relax_layout_neighborhood(request: Request, relayout_request: NeighborhoodRelayoutRequest):
+    """Re-solve only the neighborhood of pinned/dragged nodes, keeping the rest of the layout fixed."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, relayout_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    if relayout_request.hops < 0:
+        raise HTTPException(status_code=400, detail="hops must be non-negative")
+    if relayout_request.iterations < 1:
+        raise HTTPException(status_code=400, detail="iterations must be at least 1")
+    try:
+        positions = parse_positions(ts, relayout_request.positions)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    try:
+        return relax_neighborhood(
+            ts,
+            positions,
+            relayout_request.anchor_node_ids,
+            free_node_ids=relayout_request.free_node_ids,
+            hops=relayout_request.hops,
+            iterations=relayout_request.iterations,
+            lock_y=relayout_request.lock_y
+        )
+    except Exception as e:
+        logger.error(f"Error relaxing layout neighborhood: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Neighborhood re-layout failed: {str(e)}")
+
+@api_router.post("/layout/bundle-edges")
+async def bundle_layout_edges(request: Request, bundling_request: EdgeBundlingRequest):
+    """Force-directed edge bundling for the current 2D layout; returns one polyline per parent-child pair."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, bundling_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    if any(len(position) < 2 for position in bundling_request.positions.values()):
+        raise HTTPException(status_code=400, detail="Each position must be an [x, y] pair")
+
+    try:
+        return await run_in_threadpool(
+            bundle_edges,
+            ts,
+            bundling_request.positions,
+            bundling_request.cycles,
+            bundling_request.compatibility_threshold,
+            max_neighbors=bundling_request.max_neighbors
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error bundling edges: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Edge bundling failed: {str(e)}")
+
+@api_router.post("/layout/labels")
+async def place_layout_labels(request: Request, label_request: LabelPlacementRequest):
+    """Choose non-overlapping labels for the current zoom, most important nodes first."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, label_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    if label_request.viewport is not None and len(label_request.viewport) != 4:
+        raise HTTPException(status_code=400, detail="viewport must be [min_x, min_y, max_x, max_y]")
+
+    try:
+        # Importance does not depend on the view, so compute it once per file
+        priorities_key = f"label-priorities:{label_request.filename}"
+        priorities = session_storage.get_session_data(session_id, priorities_key)
+        if priorities is None:
+            priorities = await run_in_threadpool(label_priorities, ts, range(ts.num_nodes))
+            session_storage.store_session_data(session_id, priorities_key, priorities, filename=label_request.filename)
+        return await run_in_threadpool(
+            place_labels,
+            label_request.positions,
+            label_request.texts,
+            {int(u): p for u, p in priorities.items()},
+            label_request.zoom,
+            label_request.font_size,
+            label_request.node_radius,
+            label_request.viewport,
+            label_request.candidate_node_ids,
+            label_request.max_labels
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error placing labels: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Label placement failed: {str(e)}")
+
+@api_router.post("/layout/pick-index")
+async def build_pick_index(request: Request, index_request: PickIndexRequest):
+    """Index the current layout for /layout/pick; call again whenever the layout changes."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, index_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    if any(len(position) < 2 for position in index_request.positions.values()):
+        raise HTTPException(status_code=400, detail="Each position must be an [x, y] pair")
+
+    try:
+        index = await run_in_threadpool(pick_indexes.build, session_id, index_request.filename, ts, index_request.positions)
+        return {"filename": index_request.filename, **index.stats()}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error building pick index: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Failed to build pick index: {str(e)}")
+
+@api_router.get("/layout/pick/{filename}")
+async def pick_layout_element(request: Request, filename: str, x: float, y: float, radius: float = 5.0, kinds: str = "node,edge"):
+    """Nearest node (or, failing that, edge) within `radius` of (x, y) in layout coordinates."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    index = pick_indexes.get(session_id, filename)
+    if index is None:
+        raise HTTPException(status_code=404, detail="No pick index for this file; build one with /layout/pick-index")
+    requested_kinds = [kind.strip() for kind in kinds.split(",") if kind.strip()]
+    if any(kind not in PICK_KINDS for kind in requested_kinds):
+        raise HTTPException(status_code=400, detail=f"kinds must be a comma-separated subset of {PICK_KINDS}")
+    try:
+        return {"hit": index.pick(x, y, radius, requested_kinds)}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+@api_router.post("/tiles/{filename}")
+async def build_tile_set(request: Request, filename: str, tile_request: TileSetRequest):
+    """Prepare zoom-level tiles of the current layout; returns the tile URL template and world geometry."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    if any(len(position) < 2 for position in tile_request.positions.values()):
+        raise HTTPException(status_code=400, detail="Each position must be an [x, y] pair")
+
+    try:
+        tile_set = await run_in_threadpool(
+            tile_sets.build, session_id, filename, ts, tile_request.positions,
+            session_storage.get_session_data(session_id, f"palette:{filename}")
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {
+        "filename": filename,
+        "url_template": f"/api/tiles/{quote(filename)}/{{z}}/{{x}}/{{y}}.{{format}}",
+        **tile_set.metadata()
+    }
+
+@api_router.get("/tiles/{filename}/{z}/{x}/{tile}")
+async def get_tile(request: Request, filename: str, z: int, x: int, tile: str):
+    """One tile, as `{y}.json` (vector) or `{y}.png` (raster)."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    tile_set = tile_sets.get(session_id, filename)
+    if tile_set is None:
+        raise HTTPException(status_code=404, detail="No tiles for this file; prepare them with POST /tiles/{filename}")
+    y, _, tile_format = tile.partition(".")
+    if not y.isdigit() or tile_format not in TILE_FORMATS:
+        raise HTTPException(status_code=400, detail="Tile must be {y}.json or {y}.png")
+    try:
+        budget = frame_budgets.peek(session_id, filename)
+        content = await run_in_threadpool(tile_set.tile, z, x, int(y), tile_format, budget.cell_scale() if budget else 1)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    if tile_format == "png":
+        return Response(content=content, media_type="image/png")
+    return content
+
+@api_router.post("/layout/export")
+async def export_layout(request: Request, export_request: LayoutExportRequest):
+    """Export node positions with their algorithm, parameters and seed as a sidecar file."""
+    if export_request.format not in LAYOUT_EXPORT_FORMATS:
+        raise HTTPException(status_code=400, detail=f"format must be one of {list(LAYOUT_EXPORT_FORMATS)}")
+
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, export_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    try:
+        document = build_layout_document(
+            ts,
+            export_request.filename,
+            export_request.positions,
+            export_request.algorithm,
+            export_request.parameters,
+            export_request.random_seed
+        )
+        contents = serialize_layout_document(document, export_request.format)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error exporting layout for {export_request.filename}: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Layout export failed: {str(e)}")
+
+    session_storage.store_session_data(session_id, f"layout:{export_request.filename}", document, filename=export_request.filename)
+    if export_request.archive and not is_file_kept_in_memory(session_id, export_request.filename):
+        result_archive.add(
+            tree_sequence_fingerprint(ts),
+            KIND_LAYOUT,
+            f"{export_request.filename.rsplit('.', 1)[0]}_layout.{export_request.format}",
+            contents if isinstance(contents, bytes) else contents.encode("utf-8"),
+            "layout_export",
+            params={"algorithm": export_request.algorithm, "parameters": export_request.parameters, "random_seed": export_request.random_seed},
+            filename=export_request.filename,
+            content_type="application/json" if export_request.format == "json" else "application/octet-stream"
+        )
+
+    base_filename = export_request.filename.rsplit(".", 1)[0]
+    audit_log.record(
+        session_id,
+        export_request.filename,
+        EVENT_EXPORT,
+        "layout_export",
+        {"format": export_request.format, "algorithm": export_request.algorithm, "parameters": export_request.parameters, "random_seed": export_request.random_seed},
+        output=f"{base_filename}_layout.{export_request.format}"
+    )
+    return Response(
+        content=contents,
+        media_type="application/json" if export_request.format == "json" else "application/octet-stream",
+        headers={"Content-Disposition": f'attachment; filename="{base_filename}_layout.{export_request.format}"'}
+    )
+
+
+@api_router.get("/layout-3d-stream/{filename}")
+async def stream_layout_3d(
+    request: Request,
+    filename: str,
+    iterations: int = DEFAULT_3D_ITERATIONS,
+    snapshot_every: int = DEFAULT_SNAPSHOT_EVERY,
+    random_seed: Optional[int] = None,
+    log_time: bool = False
+):
+    """Stream a time-as-z 3D layout as server-sent events.
+
+    `positions` events carry snapshots while the layout settles; the final one
+    is followed by a `camera` event with an orbit path and `complete`. The
+    final layout is kept in the session for glTF export.
+    """
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+
+    def format_event(event: str, data: Dict[str, Any]) -> str:
+        return f"event: {event}\ndata: {json.dumps(data)}\n\n"
+
+    async def event_stream():
+        snapshots = iter_layout_3d(ts, iterations, snapshot_every, random_seed, log_time)
+        try:
+            while True:
+                if await request.is_disconnected():
+                    logger.info(f"3D layout stream for {filename} aborted by client")
+                    return
+                snapshot = await run_in_threadpool(next, snapshots, None)
+                if snapshot is None:
+                    break
+                yield format_event("positions", snapshot)
+                if snapshot["final"]:
+                    session_storage.store_session_data(session_id, f"layout3d:{filename}", {
+                        "positions": snapshot["positions"],
+                        "params": {"iterations": iterations, "random_seed": random_seed, "log_time": log_time},
+                    }, filename=filename)
+                    yield format_event("camera", {"keyframes": camera_path(snapshot["positions"])})
+            yield format_event("complete", {"filename": filename})
+        except ValueError as e:
+            yield format_event("error", {"detail": str(e)})
+        except Exception as e:
+            logger.error(f"Error streaming 3D layout: {str(e)}")
+            yield format_event("error", {"detail": f"Failed to compute 3D layout: {str(e)}"})
+        finally:
+            snapshots.close()
+
+    return StreamingResponse(event_stream(), media_type="text/event-stream")
+
+
+@api_router.get("/layout-3d/{filename}/{export_format}")
+async def export_layout_3d(
+    request: Request,
+    filename: str,
+    export_format: str,
+    geometry: str = "mesh",
+    node_radius: float = DEFAULT_NODE_RADIUS,
+    edge_radius: float = DEFAULT_EDGE_RADIUS
+):
+    """Download the session's last 3D layout as glTF or OBJ.
+
+    `mesh` geometry writes nodes as spheres and edges as tubes colored with the
+    file's palette; `lines` (glTF only) writes points and line segments.
+    """
+    if export_format not in MESH_EXPORT_FORMATS:
+        raise HTTPException(status_code=400, detail=f"Format must be one of {MESH_EXPORT_FORMATS}")
+    if node_radius <= 0 or edge_radius <= 0:
+        raise HTTPException(status_code=400, detail="node_radius and edge_radius must be positive")
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    layout = session_storage.get_session_data(session_id, f"layout3d:{filename}")
+    if layout is None:
+        raise HTTPException(status_code=404, detail="No 3D layout for this file yet; compute one first")
+    positions = {int(u): p for u, p in layout["positions"].items()}
+    palette_choice = session_storage.get_session_data(session_id, f"palette:{filename}")
+    try:
+        if export_format == "obj":
+            contents = await run_in_threadpool(
+                layout_to_obj, ts, positions, palette_choice,
+                node_radius=node_radius, edge_radius=edge_radius
+            )
+        else:
+            contents = await run_in_threadpool(
+                layout_to_gltf, ts, positions, palette_choice,
+                geometry=geometry, node_radius=node_radius, edge_radius=edge_radius
+            )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    base_filename = filename.rsplit(".", 1)[0]
+    output_filename = f"{base_filename}_3d.{export_format}"
+    audit_log.record(
+        session_id, filename, EVENT_EXPORT, f"layout_3d_{export_format}",
+        {**layout["params"], "geometry": "mesh" if export_format == "obj" else geometry, "node_radius": node_radius, "edge_radius": edge_radius},
+        output=output_filename
+    )
+    return Response(
+        content=contents,
+        media_type="model/obj" if export_format == "obj" else "model/gltf+json",
+        headers={"Content-Disposition": f'attachment; filename="{output_filename}"'}
+    )
+
+
+@api_router.post("/layout/import")
+async def import_layout(request: Request, filename: str, file: UploadFile = File(...)):
+    """Import a layout sidecar file and attach it to a loaded tree sequence."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    contents = await file.read()
+    if len(contents) == 0:
+        raise HTTPException(status_code=400, detail="Empty file")
+
+    try:
+        document = parse_layout_document(contents, file.filename)
+        compatibility = check_layout_compatibility(document, ts)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error importing layout {file.filename}: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"Layout import failed: {str(e)}")
+
+    session_storage.store_session_data(session_id, f"layout:{filename}", document, filename=filename)
+    logger.info(f"Imported layout {file.filename} for {filename}: {compatibility['num_positions']} positions")
+
+    return {
+        "status": "success",
+        "filename": filename,
+        "layout": document,
+        **compatibility
+    }
+
+
+@api_router.get("/layout/{filename}")
+async def get_stored_layout(request: Request, filename: str):
+    """Get the most recently exported or imported layout for a tree sequence."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    document = session_storage.get_session_data(session_id, f"layout:{filename}")
+    if document is None:
+        raise HTTPException(status_code=404, detail="No stored layout for this file")
+    return document
+
+#### Palette API endpoints ####
+
+@api_router.get("/palettes")
+async def get_palette_presets():
+    """Categorical and continuous palette presets, colorblind-safe ones first."""
+    return list_presets()
+
+
+@api_router.get("/palettes/{filename}")
+async def get_palette(request: Request, filename: str):
+    """Resolved population colors and ramp for a tree sequence's stored palette choice."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    try:
+        return resolve_palette(ts, session_storage.get_session_data(session_id, f"palette:{filename}"))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/palettes/{filename}")
+async def set_palette(request: Request, filename: str, palette_request: PaletteRequest):
+    """Store the palette choice for a tree sequence, used by every view and export of it."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    try:
+        choice = validate_choice(palette_request.dict())
+        session_storage.store_session_data(session_id, f"palette:{filename}", choice, filename=filename)
+        return resolve_palette(ts, choice)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+@api_router.post("/legend/{filename}")
+async def get_legend(request: Request, filename: str, legend_request: LegendRequest):
+    """Legend and time scale-bar specification for the current coloring, shared by views and exports."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    try:
+        values, labels = legend_request.values, None
+        if legend_request.attribute is not None:
+            attribute = await run_in_threadpool(
+                get_attribute_values, ts, resolve_attribute_expression(session_id, filename, legend_request.attribute)
+            )
+            if attribute["kind"] == "categorical":
+                labels = dict(enumerate(attribute["values"]))
+            else:
+                values = {u: None if v is None else float(v) for u, v in enumerate(attribute["values"])}
+        return build_legend(
+            ts,
+            legend_request.color_by,
+            palette_choice=session_storage.get_session_data(session_id, f"palette:{filename}"),
+            values=values,
+            statistic_name=legend_request.statistic_name or legend_request.attribute,
+            num_bins=legend_request.num_bins,
+            log_scale=legend_request.log_scale,
+            labels=labels
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+@api_router.get("/axes/{filename}")
+async def get_axes(
+    request: Request,
+    filename: str,
+    start: Optional[float] = None,
+    end: Optional[float] = None,
+    num_ticks: int = DEFAULT_NUM_TICKS,
+    locale: Optional[str] = None,
+    generation_time: Optional[float] = None,
+    mutation_rate: Optional[float] = None,
+    time_units: Optional[str] = None,
+    log_time: bool = False
+):
+    """Genomic and time axis ticks with formatted labels, shared by all views and exports.
+
+    The locale defaults to the user's settings, and the calibration to the file's
+    (see get_time_calibration). `time_units` is native, generations or years;
+    by default years when the times are generations and a generation time is set.
+    """
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    try:
+        locale = locale or settings.get("locale")
+        calibration = get_time_calibration(session_id, filename, ts, generation_time, mutation_rate)
+        display = time_units or calibration.default_display()
+        max_time = float(ts.tables.nodes.time.max()) if ts.num_nodes else 0.0
+        return {
+            "genomic": genomic_axis(start or 0.0, ts.sequence_length if end is None else end, num_ticks, locale),
+            "time": time_axis(
+                0.0, max_time, calibration.native, log_scale=log_time, target_ticks=num_ticks, locale=locale,
+                display_scale=calibration.factor(display), display_unit=calibration.label(display)
+            ),
+            "time_calibration": calibration.to_dict(),
+        }
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+def get_time_calibration(
+    session_id: str,
+    filename: str,
+    ts: tskit.TreeSequence,
+    generation_time: Optional[float] = None,
+    mutation_rate: Optional[float] = None
+) -> TimeCalibration:
+    """The file's time calibration: request values, then the stored ones, then the generation time setting."""
+    stored = session_storage.get_session_data(session_id, f"time_calibration:{filename}") or {}
+    if generation_time is None:
+        generation_time = stored.get("generation_time", settings.get("generation_time_years"))
+    if mutation_rate is None:
+        mutation_rate = stored.get("mutation_rate")
+    # The mutation-rate scale costs two diversity passes, so the stored one is reused
+    scale = stored.get("generations_per_unit") if mutation_rate == stored.get("mutation_rate") else None
+    return resolve_calibration(ts, generation_time, mutation_rate, scale)
+
+
+@api_router.get("/time-units/{filename}")
+async def get_time_units(request: Request, filename: str):
+    """What the file's node times are in, and which units they can be shown in."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    try:
+        calibration = await run_in_threadpool(get_time_calibration, session_id, filename, ts)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"filename": filename, "time_units": ts.time_units, "display_units": list(DISPLAY_UNITS), **calibration.to_dict()}
+
+
+@api_router.post("/time-units")
+async def set_time_calibration(request: Request, calibration_request: TimeCalibrationRequest):
+    """Store a generation time and mutation rate for the file; both null clears them."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    filename = calibration_request.filename
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    key = f"time_calibration:{filename}"
+    values = {k: v for k, v in (("generation_time", calibration_request.generation_time), ("mutation_rate", calibration_request.mutation_rate)) if v is not None}
+    try:
+        # Validate before storing, so a bad value can't break every later view of the file
+        calibration = await run_in_threadpool(resolve_calibration, ts, values.get("generation_time"), values.get("mutation_rate"))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    if "mutation_rate" in values:
+        values["generations_per_unit"] = calibration.generations_per_unit
+    if values:
+        session_storage.store_session_data(session_id, key, values, filename=filename)
+    else:
+        session_storage.delete_session_data(session_id, key)
+    return await get_time_units(request, filename)
+
+
+#### Accessibility API endpoints ####
+
+@api_router.get("/describe/{filename}/node/{node_id}")
+async def get_node_description(request: Request, filename: str, node_id: int):
+    """Screen-reader summary of a node."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    try:
+        return describe_node(ts, node_id, settings.get("locale"), settings.get("generation_time_years"))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.get("/describe/{filename}/tree/{tree_index}")
+async def get_tree_description(request: Request, filename: str, tree_index: int):
+    """Screen-reader summary of a local tree."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    try:
+        return describe_tree(ts, tree_index, settings.get("locale"))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.get("/describe/{filename}/breakpoint/{tree_index}")
+async def get_breakpoint_description(request: Request, filename: str, tree_index: int):
+    """What changed in the genealogy at the breakpoint before tree `tree_index`."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    try:
+        return await run_in_threadpool(describe_breakpoint, ts, tree_index, settings.get("locale"))
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.get("/navigation-order/{filename}")
+async def get_navigation_order(request: Request, filename: str, tree_index: Optional[int] = None):
+    """Keyboard traversal order and arrow-key neighbors, using the stored layout when there is one."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="Tree sequence not found")
+    document = session_storage.get_session_data(session_id, f"layout:{filename}")
+    positions = {int(u): p for u, p in document["positions"].items()} if document else None
+    try:
+        return await run_in_threadpool(navigation_order, ts, tree_index, positions)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+#### Guided tour endpoints ####
+
+@api_router.get("/tours")
+async def list_tours(request: Request, filename: Optional[str] = None):
+    """Tour definitions with the user's progress, and tours worth suggesting for `filename`."""
+    ts = None
+    if filename:
+        session_id = session_storage.get_or_create_session(get_client_ip(request))
+        ts = session_storage.get_tree_sequence(session_id, filename)
+    return {"tours": tour_store.get_all(), "suggested": tour_store.suggestions(ts)}
+
+
+def build_tour_sample() -> tskit.TreeSequence:
+    ts = build_sample_tree_sequence()
+    return generate_spatial_locations_for_samples(ts, random_seed=42, crs="unit_grid")
+
+
+@api_router.post("/tours/sample-data")
+async def load_tour_sample_data(request: Request):
+    """Load the tour's example ARG into the session."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        ts = await run_in_threadpool(build_tour_sample)
+        session_storage.store_tree_sequence(session_id, SAMPLE_FILENAME, ts)
+        session_replay.record(session_id, SAMPLE_FILENAME, RECIPE_SAMPLE)
+    except Exception as e:
+        logger.error(f"Failed to create tour sample data: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to create sample data: {str(e)}")
+    return {
+        "filename": SAMPLE_FILENAME,
+        "num_samples": ts.num_samples,
+        "num_nodes": ts.num_nodes,
+        "num_trees": ts.num_trees,
+        "num_mutations": ts.num_mutations,
+    }
+
+
+@api_router.post("/tours/{tour_id}/{action}")
+async def update_tour(tour_id: str, action: str):
+    """Start, advance, go back in, dismiss or reset a tour."""
+    try:
+        return tour_store.apply(tour_id, action)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+@api_router.get("/fixtures")
+async def get_fixtures():
+    """Synthetic test ARGs and the properties each is documented to have."""
+    return {"fixtures": list_fixtures()}
+
+
+@api_router.post("/fixtures/{name}")
+async def load_fixture(request: Request, name: str):
+    """Load a synthetic test ARG into the session as fixture_<name>.trees."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        ts = await run_in_threadpool(generate_fixture, name)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    filename = f"fixture_{name}.trees"
+    session_storage.store_tree_sequence(session_id, filename, ts)
+    session_replay.record(session_id, filename, RECIPE_FIXTURE, name=name)
+    return {
+        "filename": filename,
+        "num_samples": ts.num_samples,
+        "num_nodes": ts.num_nodes,
+        "num_trees": ts.num_trees,
+        "num_mutations": ts.num_mutations,
+    }
+
+#### Background job endpoints ####
+
+def run_redating_job(job: Job, ts: tskit.TreeSequence, filename: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """Validate, re-date with tsdate, store the result and compare node times with the original."""
+    validation = validate_redating(ts, params.get("mutation_rate"), params.get("method"), params.get("population_size"))
+    if not validation["valid"]:
+        raise ValueError("; ".join(validation["errors"]))
+    options = {
+        "mutation_rate": params["mutation_rate"],
+        "preprocess": params.get("preprocess", True),
+        "method": params.get("method"),
+        "population_size": params.get("population_size"),
+    }
+    job.set_progress(0.05)
+    dated_ts, inference_info = run_tsdate_inference(ts, progress=False, progress_callback=job.set_progress, **options)
+    job.set_progress(0.9)
+
+    new_filename = get_derived_filename(filename, "tsdate")
+    session_storage.store_tree_sequence(job.session_id, new_filename, dated_ts, derived_from=filename)
+    session_replay.record(job.session_id, new_filename, RECIPE_DERIVED, parent=filename, operation="redate", params=options)
+    comparison = compare_node_times(ts, dated_ts)
+    session_storage.store_session_data(job.session_id, f"redate:{filename}:{new_filename}", comparison, filename=new_filename)
+    return {
+        "original_filename": filename,
+        "new_filename": new_filename,
+        "warnings": validation["warnings"],
+        "inference": inference_info,
+        "comparison": comparison,
+    }
+
+
+def run_export_job(job: Job, ts: tskit.TreeSequence, filename: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """Write a .trees or .tsz download; the client fetches it once from GET /jobs/{id}/download."""
+    format = params.get("format", "trees")
+    if format not in ("trees", "tsz"):
+        raise ValueError("format must be trees or tsz")
+    # Protected data never touches disk, and tszip only compresses to a file
+    if format == "tsz" and session_storage.is_file_protected(job.session_id, filename):
+        raise ValueError("Compressed download is unavailable for protected data; use .trees")
+    job.set_progress(0.0)
+    if format == "tsz":
+        with tempfile.TemporaryDirectory() as directory:
+            path = Path(directory) / "export.tsz"
+            tszip.compress(ts, str(path))
+            job.set_progress(0.9)
+            contents = path.read_bytes()
+    else:
+        buffer = io.BytesIO()
+        ts.dump(buffer)
+        contents = buffer.getvalue()
+    job.token.check()
+    output = filename.replace("/", "_").replace("\\", "_").rsplit(".", 1)[0] + f".{format}"
+    job.artifact = (output, contents)
+    return {"output": output, "format": format, "size": len(contents)}
+
+
+JOB_OPERATIONS = ("node_metrics", "polytomy_stats", "topology_blocks", "unique_topologies", "sweep_scan", "introgression", "ne_through_time", "tmrca_matrix", "sample_embedding", "redate", "relax_neighborhood", "resolve_polytomies", "simplify", "export_tree_sequence", "benchmark")
+
+
+def run_job_operation(job: Job, operation: str, ts: Optional[tskit.TreeSequence], filename: Optional[str], params: Dict[str, Any]):
+    """Run a job operation, passing the job's cancellation token down to the computation."""
+    token = job.token
+    if operation == "node_metrics":
+        return compute_node_metrics(
+            ts,
+            include_betweenness=params.get("include_betweenness", True),
+            max_pivots=params.get("max_pivots"),
+            random_seed=params.get("random_seed"),
+            cancel_token=token
+        )
+    if operation == "polytomy_stats":
+        return get_polytomy_stats(ts, cancel_token=token)
+    if operation == "topology_blocks":
+        return topology_blocks(ts, tolerance=params.get("tolerance", 0), cancel_token=token)
+    if operation == "unique_topologies":
+        return unique_topologies(ts, cancel_token=token)
+    if operation == "sweep_scan":
+        return sweep_scan(
+            ts,
+            num_windows=params.get("num_windows", 100),
+            threshold=params.get("threshold", 2.0),
+            cancel_token=token
+        )
+    if operation == "introgression":
+        return detect_introgression(
+            ts,
+            params["recipient"],
+            params["donor"],
+            relative_threshold=params.get("relative_threshold", 0.5),
+            max_time=params.get("max_time"),
+            min_length=params.get("min_length", 0.0),
+            cancel_token=token
+        )
+    if operation == "ne_through_time":
+        return estimate_ne(
+            ts,
+            num_epochs=params.get("num_epochs", 20),
+            time_breaks=params.get("time_breaks"),
+            num_bootstrap=params.get("num_bootstrap", 100),
+            ploidy=params.get("ploidy", 2),
+            random_seed=params.get("random_seed"),
+            cancel_token=token
+        )
+    if operation == "tmrca_matrix":
+        return compute_tmrca_matrix(
+            ts,
+            samples=params.get("samples"),
+            positions=params.get("positions"),
+            max_samples=params.get("max_samples", 200),
+            random_seed=params.get("random_seed"),
+            workers=get_worker_count(settings.get("num_threads"), settings.get("low_power_mode")),
+            cancel_token=token
+        )
+    if operation == "sample_embedding":
+        return sample_embedding(
+            ts,
+            distance=params.get("distance", "tmrca"),
+            dimensions=params.get("dimensions", 2),
+            samples=params.get("samples"),
+            max_samples=params.get("max_samples", 200),
+            random_seed=params.get("random_seed"),
+            workers=get_worker_count(settings.get("num_threads"), settings.get("low_power_mode")),
+            cancel_token=token
+        )
+    if operation == "relax_neighborhood":
+        positions = parse_positions(ts, params.get("positions"))
+        return relax_neighborhood(
+            ts,
+            positions,
+            params.get("anchor_node_ids", []),
+            free_node_ids=params.get("free_node_ids"),
+            hops=params.get("hops", DEFAULT_NEIGHBORHOOD_HOPS),
+            iterations=params.get("iterations", DEFAULT_RELAX_ITERATIONS),
+            lock_y=params.get("lock_y", True),
+            cancel_token=token,
+            progress=job.set_progress
+        )
+    if operation == "resolve_polytomies":
+        resolved_ts, resolution_info = resolve_polytomies(
+            ts,
+            method=params.get("method", "random"),
+            random_seed=params.get("random_seed"),
+            cancel_token=token
+        )
+        token.check()
+        new_filename = get_derived_filename(filename, "resolved")
+        session_storage.store_tree_sequence(job.session_id, new_filename, resolved_ts, derived_from=filename)
+        record_resolution_recipe(job.session_id, new_filename, filename, params.get("method", "random"), params.get("random_seed"))
+        return {"original_filename": filename, "new_filename": new_filename, **resolution_info}
+    if operation == "simplify":
+        simplified_ts = ts.simplify(
+            samples=params.get("samples"),
+            keep_unary=params.get("keep_unary", False),
+            filter_sites=params.get("filter_sites", True)
+        )
+        token.check()
+        new_filename = get_derived_filename(filename, "simplified")
+        session_storage.store_tree_sequence(job.session_id, new_filename, simplified_ts, derived_from=filename)
+        session_replay.record(job.session_id, new_filename, RECIPE_DERIVED, parent=filename, operation="simplify", params={
+            "samples": params.get("samples"),
+            "keep_unary": params.get("keep_unary", False),
+            "filter_sites": params.get("filter_sites", True),
+        })
+        return {
+            "original_filename": filename,
+            "new_filename": new_filename,
+            "nodes_before": ts.num_nodes,
+            "nodes_after": simplified_ts.num_nodes,
+            "edges_before": ts.num_edges,
+            "edges_after": simplified_ts.num_edges,
+        }
+    if operation == "redate":
+        return run_redating_job(job, ts, filename, params)
+    if operation == "export_tree_sequence":
+        return run_export_job(job, ts, filename, params)
+    if operation == "benchmark":
+        from argscape.backend.benchmark import run_benchmark
+        return run_benchmark(params.get("profile", "quick"), cancel_token=token)
+    raise ValueError(f"Unknown job operation '{operation}'. Expected one of {JOB_OPERATIONS}")
+
+
+@api_router.post("/jobs")
+async def submit_job(request: Request, job_request: JobRequest):
+    """Start a long-running computation in the background and return its job ID."""
+    if job_request.operation not in JOB_OPERATIONS:
+        raise HTTPException(status_code=400, detail=f"Unknown job operation '{job_request.operation}'. Expected one of {JOB_OPERATIONS}")
+
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+
+    ts = None
+    if job_request.operation == "benchmark":
+        # Benchmarks (up to chromosome scale) load the whole machine; hosted servers never run them
+        require_local_mode("Running benchmarks")
+    else:
+        if not job_request.filename:
+            raise HTTPException(status_code=400, detail="filename is required for this operation")
+        ts = session_storage.get_tree_sequence(session_id, job_request.filename)
+        if ts is None:
+            raise HTTPException(status_code=404, detail="Tree sequence not found")
+        if job_request.operation == "relax_neighborhood":
+            try:
+                parse_positions(ts, job_request.params.get("positions"))
+            except ValueError as e:
+                raise HTTPException(status_code=400, detail=str(e))
+        if job_request.params.get("sample_set"):
+            # Resolved now, so the job (and any replay recipe it records) has explicit samples
+            try:
+                job_request.params["samples"] = resolve_request_samples(session_id, job_request.filename, ts, None, job_request.params.pop("sample_set"))
+            except ValueError as e:
+                raise HTTPException(status_code=400, detail=str(e))
+
+    def run_and_audit(job: Job):
+        result = run_job_operation(job, job_request.operation, ts, job_request.filename, job_request.params)
+        if job_request.filename:
+            output = (result.get("new_filename") or result.get("output")) if isinstance(result, dict) else None
+            kind = EVENT_EXPORT if job_request.operation == "export_tree_sequence" else EVENT_OPERATION
+            audit_log.record(session_id, job_request.filename, kind, job_request.operation, job_request.params, output=output)
+        return result
+
+    job = job_manager.submit(
+        session_id,
+        job_request.operation,
+        run_and_audit,
+        description=job_request.filename or ""
+    )
+    return job.to_dict()
+
+
+@api_router.get("/jobs")
+async def list_jobs(request: Request):
+    """List background jobs for the current session."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    return {"jobs": [job.to_dict() for job in job_manager.list_jobs(session_id)]}
+
+
+@api_router.get("/jobs/{job_id}")
+async def get_job_status(request: Request, job_id: str):
+    """Get a job's status, including its result once completed."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    job = job_manager.get_job(job_id, session_id)
+    if job is None:
+        raise HTTPException(status_code=404, detail="Job not found")
+    return job.to_dict(include_result=True)
+
+
+@api_router.get("/jobs/{job_id}/download")
+async def download_job_file(request: Request, job_id: str):
+    """The file a completed export job wrote; it is released once downloaded."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    job = job_manager.get_job(job_id, session_id)
+    if job is None:
+        raise HTTPException(status_code=404, detail="Job not found")
+    if job.status != JOB_COMPLETED or job.artifact is None:
+        raise HTTPException(status_code=409, detail="This job has no file to download")
+    output, contents = job.artifact
+    job.artifact = None
+    return Response(
+        content=contents,
+        media_type="application/octet-stream",
+        headers={"Content-Disposition": f'attachment; filename="{output}"'}
+    )
+
+
+@api_router.post("/jobs/{job_id}/cancel")
+async def cancel_job(request: Request, job_id: str):
+    """Request cancellation of a running job; the computation stops at its next check point."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    job = job_manager.get_job(job_id, session_id)
+    if job is None:
+        raise HTTPException(status_code=404, detail="Job not found")
+    cancelled = job_manager.cancel_job(job_id, session_id)
+    return {"job_id": job_id, "cancellation_requested": cancelled, "status": job.status}
+
+
+#### Batch processing endpoints ####
+
+@api_router.post("/batch")
+async def submit_batch(request: Request, batch_request: BatchRequest):
+    """Queue many files through a validate -> simplify -> layout -> export pipeline as one background job."""
+    unknown_steps = [step for step in batch_request.steps if step not in BATCH_STEPS]
+    if unknown_steps or not batch_request.steps:
+        raise HTTPException(status_code=400, detail=f"steps must be a non-empty subset of {list(BATCH_STEPS)}")
+    if batch_request.image_format not in IMAGE_FORMATS:
+        raise HTTPException(status_code=400, detail=f"image_format must be one of {list(IMAGE_FORMATS)}")
+    if not 1 <= batch_request.workers <= MAX_BATCH_WORKERS:
+        raise HTTPException(status_code=400, detail=f"workers must be between 1 and {MAX_BATCH_WORKERS}")
+    if not batch_request.filenames:
+        raise HTTPException(status_code=400, detail="No files to process")
+    if is_protected_mode():
+        raise HTTPException(status_code=400, detail="Batch outputs cannot be written to disk in protected mode")
+    
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    missing = [name for name in batch_request.filenames if name not in session_storage.get_file_list(session_id)]
+    if missing:
+        raise HTTPException(status_code=404, detail=f"Files not found: {missing}")
+    
+    steps = [step for step in BATCH_STEPS if step in batch_request.steps]
+    options = {
+        "keep_unary": batch_request.keep_unary,
+        "layout_iterations": batch_request.layout_iterations,
+        "image_format": batch_request.image_format,
+        # Stored palette choices, so exported images match the on-screen colors
+        "palettes": {name: session_storage.get_session_data(session_id, f"palette:{name}") for name in batch_request.filenames},
+    }
+    
+    def get_batch_tree_sequence(filename: str) -> Optional[tskit.TreeSequence]:
+        if session_storage.is_file_protected(session_id, filename):
+            raise ValueError("Protected files are not exported by batch processing")
+        return session_storage.get_tree_sequence(session_id, filename)
+    
+    def run(job: Job):
+        def write_output(name: str, contents: bytes):
+            session_storage.store_auxiliary_file(session_id, f"batch_{job.job_id}", name, contents)
+        return run_batch(
+            job, batch_request.filenames, steps, options, get_batch_tree_sequence, write_output, batch_request.workers
+        )
+    
+    job = job_manager.submit(session_id, "batch", run, f"batch of {len(batch_request.filenames)} files")
+    return job.to_dict()
+
+
+@api_router.get("/batch/{job_id}/download")
+async def download_batch_outputs(request: Request, job_id: str):
+    """Download all outputs of a finished batch, with its summary report, as a zip archive."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    job = job_manager.get_job(job_id, session_id)
+    if job is None or job.operation != "batch":
+        raise HTTPException(status_code=404, detail="Batch not found")
+    
+    output_dir = session_storage.get_auxiliary_dir(session_id, f"batch_{job_id}")
+    if not output_dir.is_dir():
+        raise HTTPException(status_code=404, detail="Batch has no outputs yet")
+    
+    def build_archive() -> bytes:
+        buffer = io.BytesIO()
+        with zipfile.ZipFile(buffer, "w", zipfile.ZIP_DEFLATED) as archive:
+            for path in sorted(output_dir.iterdir()):
+                archive.write(path, path.name)
+        return buffer.getvalue()
+    
+    return Response(
+        content=await run_in_threadpool(build_archive),
+        media_type="application/zip",
+        headers={"Content-Disposition": f'attachment; filename="argscape_batch_{job_id[:8]}.zip"'}
+    )
+
+
+#### Result archive endpoints ####
+
+def is_file_kept_in_memory(session_id: str, filename: str) -> bool:
+    """Nothing about protected files (or anything in protected mode) is written to disk."""
+    return is_protected_mode() or session_storage.is_file_protected(session_id, filename)
+
+
+@api_router.post("/archive")
+async def archive_result(request: Request, archive_request: ArchiveRequest):
+    """Keep a statistic track, layout or export, with its provenance, across sessions."""
+    if archive_request.kind not in RESULT_KINDS:
+        raise HTTPException(status_code=400, detail=f"kind must be one of {list(RESULT_KINDS)}")
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if is_file_kept_in_memory(session_id, archive_request.filename):
+        raise HTTPException(status_code=400, detail="Results of protected files are not archived")
+    ts = session_storage.get_tree_sequence(session_id, archive_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+
+    value, operation = archive_request.value, archive_request.operation
+    if archive_request.job_id:
+        job = job_manager.get_job(archive_request.job_id, session_id)
+        if job is None or job.status != JOB_COMPLETED:
+            raise HTTPException(status_code=404, detail="No completed job with this ID")
+        value, operation = job.result, operation or job.operation
+    if value is None or not operation:
+        raise HTTPException(status_code=400, detail="Give a value and operation, or a completed job_id")
+
+    try:
+        fingerprint = await run_in_threadpool(tree_sequence_fingerprint, ts)
+        return result_archive.add_json(
+            fingerprint, archive_request.kind, archive_request.name, value, operation,
+            params=archive_request.params, filename=archive_request.filename
+        )
+    except (TypeError, ValueError) as e:
+        raise HTTPException(status_code=400, detail=f"Cannot archive result: {str(e)}")
+
+
+@api_router.get("/archive")
+async def list_archived_results(request: Request, filename: Optional[str] = None, kind: Optional[str] = None):
+    """Archived results, all or those computed from the same data as `filename`."""
+    fingerprint = None
+    if filename:
+        session_id = session_storage.get_or_create_session(get_client_ip(request))
+        ts = session_storage.get_tree_sequence(session_id, filename)
+        if ts is None:
+            raise HTTPException(status_code=404, detail="File not found")
+        fingerprint = await run_in_threadpool(tree_sequence_fingerprint, ts)
+    return {"results": result_archive.list(fingerprint, kind), **result_archive.usage()}
+
+
+@api_router.get("/archive/{entry_id}")
+async def open_archived_result(entry_id: str):
+    """Re-open an archived result: JSON results are returned inline, other exports as a download."""
+    try:
+        entry, payload = result_archive.open(entry_id)
+    except KeyError:
+        raise HTTPException(status_code=404, detail="Archived result not found")
+    if entry["content_type"] == "application/json":
+        return {**entry, "value": json.loads(payload)}
+    return Response(
+        content=payload,
+        media_type=entry["content_type"],
+        headers={"Content-Disposition": f'attachment; filename="{entry["name"].replace(" ", "_")}"'}
+    )
+
+
+@api_router.delete("/archive/{entry_id}")
+async def delete_archived_result(entry_id: str):
+    """Delete one archived result."""
+    if not result_archive.delete(entry_id):
+        raise HTTPException(status_code=404, detail="Archived result not found")
+    return {"id": entry_id, "deleted": True}
+
+
+@api_router.delete("/archive")
+async def clear_archive():
+    """Delete every archived result."""
+    return {"deleted": result_archive.clear()}
+
+
+#### Published dataset endpoints ####
+
+@api_router.post("/datasets/lookup")
+async def lookup_dataset(dataset_request: DatasetLookupRequest):
+    """List the files of a Zenodo or Figshare record given its DOI or URL."""
+    try:
+        return await run_in_threadpool(list_dataset_files, dataset_request.reference)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except NetworkDisabledError as e:
+        raise HTTPException(status_code=403, detail=str(e))
+    except Exception as e:
+        logger.error(f"Failed to look up dataset {dataset_request.reference}: {e}")
+        raise HTTPException(status_code=502, detail=f"Failed to look up dataset: {str(e)}")
+
+
+@api_router.post("/datasets/download")
+async def download_dataset(request: Request, dataset_request: DatasetDownloadRequest):
+    """Download a tree sequence from a dataset record in a background job and open it.
+
+    The file list is looked up again here, so the URL and checksum come from the repository.
+    """
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+
+    def run(job: Job):
+        record = list_dataset_files(dataset_request.reference)
+        entry = next((f for f in record["files"] if f["name"] == dataset_request.file_name), None)
+        if entry is None:
+            raise ValueError(f"{dataset_request.file_name} is not in {record['source']} record {record['record_id']}")
+        if not entry["openable"]:
+            raise ValueError(f"{entry['name']} is not a .trees or .tsz file")
+        path = download_dataset_file(entry, job.set_progress, job.token)
+        ts, filename = load_tree_sequence_from_file(path.read_bytes(), entry["name"])
+        session_storage.store_tree_sequence(session_id, filename, ts)
+        session_replay.record(session_id, filename, RECIPE_PATH, path=str(path), preprocess="none")
+        return {
+            "filename": filename,
+            "source": record["source"],
+            "record_id": record["record_id"],
+            "title": record["title"],
+            "verified": entry["md5"] is not None,
+            "num_nodes": ts.num_nodes,
+            "num_trees": ts.num_trees,
+        }
+
+    job = job_manager.submit(session_id, "dataset_download", run, dataset_request.file_name)
+    return job.to_dict()
+
+
+#### Remote file endpoints ####
+
+@api_router.post("/remote/probe")
+async def probe_remote_file(remote_request: RemoteFileRequest):
+    """Check that a remote file is reachable with the given credentials, and its size."""
+    require_local_mode("Opening remote files")
+    try:
+        remote = RemoteFile(remote_request.url, remote_request.credentials)
+        return await run_in_threadpool(remote.probe)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except (PermissionError, NetworkDisabledError) as e:
+        raise HTTPException(status_code=403, detail=str(e))
+    except FileNotFoundError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    except Exception as e:
+        logger.error(f"Failed to probe remote file: {e}")
+        raise HTTPException(status_code=502, detail=f"Failed to reach remote file: {str(e)}")
+
+
+@api_router.post("/remote/open")
+async def open_remote_file(request: Request, remote_request: RemoteFileRequest):
+    """Load a remote tree sequence in a background job, reusing cached blocks."""
+    require_local_mode("Opening remote files")
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        remote = RemoteFile(remote_request.url, remote_request.credentials)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    if not remote.name.endswith((".trees", ".tsz")):
+        raise HTTPException(status_code=400, detail="Remote files must be .trees or .tsz tree sequences")
+
+    def run(job: Job):
+        contents = remote.read_all(job.set_progress, job.token)
+        ts, filename = load_tree_sequence_from_file(contents, remote.name)
+        session_storage.store_tree_sequence(session_id, filename, ts)
+        return {"filename": filename, "url": remote.url, "size": remote.size, "num_nodes": ts.num_nodes, "num_trees": ts.num_trees}
+
+    job = job_manager.submit(session_id, "remote_open", run, remote.name)
+    return job.to_dict()
+
+
+#### Download cache endpoints ####
+
+@api_router.get("/download-cache")
+async def list_download_cache():
+    """Cached downloads, most recently used first, with total size and quota."""
+    return {"files": download_cache.list(), **download_cache.usage()}
+
+
+@api_router.post("/download-cache/{sha256}/open")
+async def open_cached_download(request: Request, sha256: str):
+    """Load a cached download into the session without any network access."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        entry = download_cache.get(sha256)
+    except KeyError:
+        raise HTTPException(status_code=404, detail="Cached file not found")
+    path = download_cache.blob_path(sha256)
+    try:
+        ts, filename = await run_in_threadpool(load_tree_sequence_from_file, path.read_bytes(), entry["name"])
+    except Exception as e:
+        raise HTTPException(status_code=400, detail=f"Cached file is not a tree sequence: {str(e)}")
+    session_storage.store_tree_sequence(session_id, filename, ts)
+    session_replay.record(session_id, filename, RECIPE_PATH, path=str(path), preprocess="none")
+    return {"filename": filename, "num_nodes": ts.num_nodes, "num_trees": ts.num_trees}
+
+
+@api_router.post("/download-cache/{sha256}/pin")
+async def pin_cached_download(sha256: str, pin_request: DownloadCachePinRequest):
+    """Pin a cached download so the quota never evicts it (or unpin it)."""
+    try:
+        return download_cache.pin(sha256, pin_request.pinned)
+    except KeyError:
+        raise HTTPException(status_code=404, detail="Cached file not found")
+
+
+@api_router.delete("/download-cache/{sha256}")
+async def evict_cached_download(sha256: str):
+    if not download_cache.evict(sha256):
+        raise HTTPException(status_code=404, detail="Cached file not found")
+    return {"sha256": sha256, "evicted": True}
+
+
+@api_router.delete("/download-cache")
+async def clear_download_cache(include_pinned: bool = False):
+    """Evict all unpinned downloads (and pinned ones with include_pinned)."""
+    return {"evicted": download_cache.clear(include_pinned)}
+
+
+#### Audit trail endpoints ####
+
+@api_router.get("/audit")
+async def list_audit_logs():
+    """Input files with an audit log, most recently used first."""
+    return {"logs": audit_log.list()}
+
+
+@api_router.get("/audit/file/{filename}")
+async def get_file_audit_log(request: Request, filename: str):
+    """The audit log of the input a session file is, or was derived from."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    audit_id = audit_log.audit_id_for(session_id, filename)
+    if audit_id is None:
+        raise HTTPException(status_code=404, detail="No audit log for this file")
+    try:
+        return audit_log.get(audit_id)
+    except KeyError:
+        raise HTTPException(status_code=404, detail="No audit log for this file")
+
+
+@api_router.get("/audit/{audit_id}/export")
+async def export_audit_log(audit_id: str):
+    """Download an audit log as JSON."""
+    try:
+        log = audit_log.get(audit_id)
+    except KeyError:
+        raise HTTPException(status_code=404, detail="Audit log not found")
+    name = log["names"][0].rsplit(".", 1)[0] if log["names"] else audit_id
+    return Response(
+        content=json.dumps(log, indent=2),
+        media_type="application/json",
+        headers={"Content-Disposition": f'attachment; filename="{name}_audit.json"'}
+    )
+
+
+@api_router.delete("/audit/{audit_id}")
+async def delete_audit_log(audit_id: str):
+    try:
+        deleted = audit_log.delete(audit_id)
+    except KeyError:
+        deleted = False
+    if not deleted:
+        raise HTTPException(status_code=404, detail="Audit log not found")
+    return {"deleted": audit_id}
+
+
+#### Reference genome API endpoints ####
+
+@api_router.post("/reference/load")
+async def load_reference(request: Request, load_request: ReferenceLoadRequest):
+    """Load a reference genome from server paths (local mode only; hosted clients use /reference/upload)."""
+    require_local_mode("Loading a reference from a server path")
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        reference = await run_in_threadpool(
+            reference_registry.load,
+            session_id,
+            Path(load_request.fasta_path),
+            Path(load_request.fai_path) if load_request.fai_path else None,
+            Path(load_request.gff_path) if load_request.gff_path else None,
+        )
+    except (ValueError, OSError) as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return reference.summary()
+
+
+@api_router.post("/reference/upload")
+async def upload_reference(
+    request: Request,
+    fasta: UploadFile = File(...),
+    fai: Optional[UploadFile] = File(None),
+    gff: Optional[UploadFile] = File(None)
+):
+    """Upload a reference FASTA (and optionally its .fai index and a GFF3 annotation)."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        fasta_path = session_storage.store_auxiliary_file(session_id, "reference", fasta.filename, await fasta.read())
+        fai_path = session_storage.store_auxiliary_file(session_id, "reference", f"{fasta_path.name}.fai", await fai.read()) if fai else None
+        gff_path = session_storage.store_auxiliary_file(session_id, "reference", gff.filename, await gff.read()) if gff else None
+        reference = await run_in_threadpool(reference_registry.load, session_id, fasta_path, fai_path, gff_path)
+    except (ValueError, OSError) as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return reference.summary()
+
+
+@api_router.get("/reference")
+async def get_reference(request: Request):
+    """Describe the session's loaded reference genome."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    reference = reference_registry.get(session_id)
+    return {"loaded": reference is not None, **(reference.summary() if reference else {})}
+
+
+@api_router.get("/reference/sequence")
+async def get_reference_sequence(request: Request, start: int, end: int, contig: Optional[str] = None):
+    """Reference sequence for a 0-based half-open genomic window."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    reference = reference_registry.get(session_id)
+    if reference is None:
+        raise HTTPException(status_code=404, detail="No reference genome loaded")
+    if end - start > MAX_FETCH_BASES:
+        raise HTTPException(status_code=400, detail=f"Window is larger than {MAX_FETCH_BASES} bases")
+    
+    contig = contig or reference.default_contig()
+    try:
+        sequence = await run_in_threadpool(reference.fetch, contig, start, end)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"contig": contig, "start": start, "end": start + len(sequence), "sequence": sequence}
+
+
+@api_router.get("/reference/mutations/{filename}")
+async def get_reference_mutation_context(
+    request: Request,
+    filename: str,
+    start: float = 0,
+    end: Optional[float] = None,
+    contig: Optional[str] = None,
+    offset: int = 0
+):
+    """Map a tree sequence's mutations in a window to reference alleles and codon context."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    reference = reference_registry.get(session_id)
+    if reference is None:
+        raise HTTPException(status_code=404, detail="No reference genome loaded")
+    
+    try:
+        return {"filename": filename, **await run_in_threadpool(annotate_mutations, ts, reference, contig, start, end, offset)}
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    except Exception as e:
+        logger.error(f"Error mapping mutations to reference for {filename}: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to map mutations: {str(e)}")
+
+
+#### Liftover API endpoints ####
+
+@api_router.post("/liftover/chain")
+async def upload_liftover_chain(request: Request, file: UploadFile = File(...)):
+    """Upload a chain file mapping annotation coordinates to the ARG's assembly."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    contents = await file.read()
+    try:
+        chain = await run_in_threadpool(ChainFile.from_bytes, contents, file.filename)
+    except (ValueError, UnicodeDecodeError) as e:
+        raise HTTPException(status_code=400, detail=f"Invalid chain file: {str(e)}")
+    chain_registry.set(session_id, chain)
+    return chain.summary()
+
+
+@api_router.post("/liftover/chain/load")
+async def load_liftover_chain(request: Request, load_request: ChainLoadRequest):
+    """Load a chain file from a server path (local mode only; hosted clients use /liftover/chain)."""
+    require_local_mode("Loading a chain file from a server path")
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        chain = await run_in_threadpool(ChainFile.from_path, Path(load_request.chain_path))
+    except (ValueError, OSError, UnicodeDecodeError) as e:
+        raise HTTPException(status_code=400, detail=f"Invalid chain file: {str(e)}")
+    chain_registry.set(session_id, chain)
+    return chain.summary()
+
+
+@api_router.get("/liftover/chain")
+async def get_liftover_chain(request: Request):
+    """Describe the session's loaded chain file."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    chain = chain_registry.get(session_id)
+    return {"loaded": chain is not None, **(chain.summary() if chain else {})}
+
+
+@api_router.post("/liftover")
+async def liftover_intervals(request: Request, liftover_request: LiftoverRequest):
+    """Remap annotation intervals and bookmarks, reporting success per interval."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    chain = chain_registry.get(session_id)
+    if chain is None:
+        raise HTTPException(status_code=404, detail="No chain file loaded")
+    if not 0 < liftover_request.min_match <= 1:
+        raise HTTPException(status_code=400, detail="min_match must be in (0, 1]")
+    
+    try:
+        intervals = list(liftover_request.intervals)
+        if liftover_request.bed:
+            intervals.extend(parse_bed(liftover_request.bed))
+        return await run_in_threadpool(
+            lift_intervals, chain, intervals, liftover_request.min_match, liftover_request.target_chrom
+        )
+    except (ValueError, KeyError) as e:
+        raise HTTPException(status_code=400, detail=f"Invalid intervals: {str(e)}")
+
+
+#### Contig endpoints ####
+
+def get_contig_map(session_id: str, filename: str, ts: tskit.TreeSequence) -> Optional[ContigMap]:
+    """The user's contig layout for the file if one was set, else the detected one (or None)."""
+    stored = session_storage.get_session_data(session_id, f"contigs:{filename}")
+    if stored:
+        try:
+            return ContigMap(stored, ts.sequence_length, SOURCE_USER)
+        except ValueError as e:
+            logger.warning(f"Ignoring stored contig layout for {filename}: {e}")
+    return detect_contigs(ts)
+
+
+@api_router.get("/contigs/{filename}")
+async def get_contigs(request: Request, filename: str):
+    """Contigs packed into the file's coordinate space; `contigs` is null for a single contig."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    contig_map = await run_in_threadpool(get_contig_map, session_id, filename, ts)
+    return {
+        "filename": filename,
+        "sequence_length": ts.sequence_length,
+        "detected": contig_map is not None and contig_map.source != SOURCE_USER,
+        **(contig_map.to_dict() if contig_map else {"source": None, "contigs": None}),
+    }
+
+
+@api_router.post("/contigs")
+async def set_contigs(request: Request, layout_request: ContigLayoutRequest):
+    """Set the file's contig layout by hand, or clear it (contigs: null) to go back to detection."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, layout_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    key = f"contigs:{layout_request.filename}"
+    if layout_request.contigs is None:
+        session_storage.delete_session_data(session_id, key)
+        return await get_contigs(request, layout_request.filename)
+    try:
+        contig_map = ContigMap(layout_request.contigs, ts.sequence_length, SOURCE_USER)
+    except (ValueError, KeyError, TypeError) as e:
+        raise HTTPException(status_code=400, detail=f"Invalid contig layout: {e}")
+    session_storage.store_session_data(session_id, key, contig_map.contigs, filename=layout_request.filename)
+    return {"filename": layout_request.filename, "sequence_length": ts.sequence_length, "detected": False, **contig_map.to_dict()}
+
+
+#### Genome browser endpoints ####
+
+def resolve_browser_interval(session_id: str, browser_request: GenomeBrowserRequest) -> tuple:
+    """Contig and genome coordinates of the requested tree sequence interval."""
+    ts = session_storage.get_tree_sequence(session_id, browser_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    if not 0 <= browser_request.start < browser_request.end <= ts.sequence_length:
+        raise HTTPException(status_code=400, detail=f"Interval must lie within 0-{ts.sequence_length:g}")
+    contig = browser_request.contig
+    contig_map = get_contig_map(session_id, browser_request.filename, ts)
+    if contig is None and contig_map is not None:
+        parts = contig_map.split_interval(browser_request.start, browser_request.end)
+        if len(parts) != 1:
+            raise HTTPException(status_code=400, detail="The interval spans more than one contig; view one contig at a time")
+        return parts[0]["contig"], parts[0]["start"] + browser_request.offset, parts[0]["end"] + browser_request.offset
+    if contig is None:
+        reference = reference_registry.get(session_id)
+        if reference is None:
+            raise HTTPException(status_code=400, detail="Specify the contig, or load a reference genome to use its first contig")
+        contig = reference.default_contig()
+    offset = browser_request.offset
+    return contig, browser_request.start + offset, browser_request.end + offset
+
+
+@api_router.post("/genome-browser/links")
+async def get_genome_browser_links(request: Request, browser_request: GenomeBrowserRequest):
+    """UCSC, igv.js and IGV desktop links for the viewed interval."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    contig, start, end = resolve_browser_interval(session_id, browser_request)
+    return {
+        "region": format_region(contig, start, end),
+        "links": browser_links(contig, start, end, browser_request.assembly),
+    }
+
+
+@api_router.post("/genome-browser/igv-batch")
+async def get_igv_batch_script(request: Request, browser_request: GenomeBrowserRequest):
+    """Download an IGV batch script that goes to the viewed interval."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    contig, start, end = resolve_browser_interval(session_id, browser_request)
+    script = igv_batch_script(contig, start, end, browser_request.assembly, browser_request.snapshot_directory)
+    base_filename = browser_request.filename.rsplit(".", 1)[0]
+    audit_log.record(session_id, browser_request.filename, EVENT_EXPORT, "igv_batch", browser_request.dict(), output=f"{base_filename}_igv.txt")
+    return Response(
+        content=script,
+        media_type="text/plain",
+        headers={"Content-Disposition": f'attachment; filename="{base_filename}_igv.txt"'}
+    )
+
+
+@api_router.post("/genome-browser/parse-region")
+async def parse_genome_region(request: Request, region_request: GenomeRegionRequest):
+    """Map a pasted region string onto the tree sequence's coordinates."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    ts = session_storage.get_tree_sequence(session_id, region_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    contig_map = get_contig_map(session_id, region_request.filename, ts)
+    try:
+        region = parse_region(region_request.region)
+        if contig_map is not None and region["contig"] in {c["name"] for c in contig_map.contigs}:
+            # Per-contig coordinates onto the concatenated axis, clamped to that contig
+            contig = next(c for c in contig_map.contigs if c["name"] == region["contig"])
+            local = region_to_ts_interval(region, contig["end"] - contig["start"], region_request.offset, region["contig"])
+            interval = {**local, "start": contig["start"] + local["start"], "end": contig["start"] + local["end"]}
+        else:
+            interval = region_to_ts_interval(region, ts.sequence_length, region_request.offset, region_request.contig)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"contig": region["contig"], **interval}
+
+
+#### Rate map API endpoints ####
+
+def check_rate_map_kind(kind: str):
+    if kind not in RATE_MAP_KINDS:
+        raise HTTPException(status_code=400, detail=f"kind must be one of {list(RATE_MAP_KINDS)}")
+
+
+@api_router.post("/rate-maps/{kind}/upload")
+async def upload_rate_map(request: Request, kind: str, file: UploadFile = File(...)):
+    """Upload a HapMap recombination map or a BEDgraph mutation rate map."""
+    check_rate_map_kind(kind)
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    contents = await file.read()
+    try:
+        rate_map = await run_in_threadpool(RateMap.from_bytes, kind, contents, file.filename)
+    except (ValueError, UnicodeDecodeError) as e:
+        raise HTTPException(status_code=400, detail=f"Invalid {kind} map: {str(e)}")
+    rate_map_registry.set(session_id, rate_map)
+    return rate_map.summary()
+
+
+@api_router.post("/rate-maps/{kind}/load")
+async def load_rate_map(request: Request, kind: str, load_request: RateMapLoadRequest):
+    """Load a rate map from a server path (local mode only; hosted clients upload it)."""
+    require_local_mode("Loading a rate map from a server path")
+    check_rate_map_kind(kind)
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    try:
+        rate_map = await run_in_threadpool(RateMap.from_path, kind, Path(load_request.path))
+    except (ValueError, OSError, UnicodeDecodeError) as e:
+        raise HTTPException(status_code=400, detail=f"Invalid {kind} map: {str(e)}")
+    rate_map_registry.set(session_id, rate_map)
+    return rate_map.summary()
+
+
+@api_router.get("/rate-maps")
+async def get_rate_maps(request: Request):
+    """Describe the session's loaded rate maps."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    loaded = rate_map_registry.get_all(session_id)
+    return {kind: loaded[kind].summary() if kind in loaded else None for kind in RATE_MAP_KINDS}
+
+
+@api_router.get("/rate-maps/{kind}/windows")
+async def get_rate_map_windows(
+    request: Request,
+    kind: str,
+    start: float = 0,
+    end: Optional[float] = None,
+    num_windows: int = DEFAULT_RATE_WINDOWS,
+    contig: Optional[str] = None,
+    filename: Optional[str] = None
+):
+    """Mean rates in equal windows, for a track over [start, end) or the whole of `filename`."""
+    check_rate_map_kind(kind)
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    rate_map = rate_map_registry.get(session_id, kind)
+    if rate_map is None:
+        raise HTTPException(status_code=404, detail=f"No {kind} map loaded")
+    if end is None:
+        if filename is None:
+            raise HTTPException(status_code=400, detail="Give either end or filename")
+        ts = session_storage.get_tree_sequence(session_id, filename)
+        if ts is None:
+            raise HTTPException(status_code=404, detail="File not found")
+        end = ts.sequence_length
+
+    try:
+        return await run_in_threadpool(rate_map.window_rates, start, end, num_windows, contig)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.delete("/rate-maps/{kind}")
+async def remove_rate_map(request: Request, kind: str):
+    """Unload a rate map."""
+    check_rate_map_kind(kind)
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    return {"kind": kind, "removed": rate_map_registry.remove(session_id, kind)}
+
+
+#### Geographic API endpoints ####
+
+@api_router.get("/geographic/crs")
+async def get_available_crs():
+    """Get list of available coordinate reference systems."""
+    return {
+        "builtin_crs": {key: crs.to_dict() for key, crs in BUILTIN_CRS.items()}
+}
+
+
+@api_router.get("/geographic/shapes")
+async def get_available_shapes():
+    """Get list of built-in geographic shapes."""
+    try:
+        builtin_shapes = get_builtin_shapes()
+        return {
+            "builtin_shapes": builtin_shapes,
+        }
+    except Exception as e:
+        logger.error(f"Error getting built-in shapes: {e}")
+        raise HTTPException(status_code=500, detail=f"Could not get shapes: {str(e)}")
+
+
+@api_router.post("/geographic/upload-shapefile")
+async def upload_shapefile(request: Request, file: UploadFile = File(...)):
+    """Upload and process a shapefile."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    
+    try:
+        contents = await file.read()
+        if len(contents) == 0:
+            raise HTTPException(status_code=400, detail="Empty file")
+        
+        # Process the shapefile
+        shape_data = process_shapefile(contents, file.filename)
+        
+        # Store the shape data in session storage
+        # We'll extend session storage to handle shapes later
+        shape_id = f"uploaded_{file.filename}_{int(time.time())}"
+        
+        return {
+            "status": "success",
+            "shape_id": shape_id,
+            "shape_name": shape_data["name"],
+            "bounds": shape_data["bounds"],
+            "feature_count": shape_data["feature_count"],
+            "crs": shape_data["crs"]
+        }
+        
+    except Exception as e:
+        logger.error(f"Error uploading shapefile: {e}")
+        raise HTTPException(status_code=500, detail=f"Could not process shapefile: {str(e)}")
+
+
+@api_router.get("/geographic/shape/{shape_name}")
+async def get_shape_data(shape_name: str):
+    """Get geometric data for a built-in shape."""
+    try:
+        builtin_shapes = get_builtin_shapes()
+        if shape_name in builtin_shapes:
+            return builtin_shapes[shape_name]
+        elif shape_name == "unit_grid":
+            return generate_grid_outline(10)
+        else:
+            raise HTTPException(status_code=404, detail=f"Shape '{shape_name}' not found")
+    except Exception as e:
+        logger.error(f"Error getting shape data for {shape_name}: {e}")
+        raise HTTPException(status_code=500, detail=f"Could not get shape data: {str(e)}")
+
+
+@api_router.post("/geographic/transform-coordinates")
+async def transform_tree_sequence_coordinates(request: Request, transform_request: CoordinateTransformRequest):
+    """Transform coordinates of a tree sequence between CRS."""
+    client_ip = get_client_ip(request)
+    session_id = session_storage.get_or_create_session(client_ip)
+    ts = session_storage.get_tree_sequence(session_id, transform_request.filename)
+    if ts is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    
+    try:
+        # Extract coordinates from the tree sequence
+        coordinates = []
+        node_ids = []
+        
+        for node in ts.nodes():
+            if node.individual != -1:
+                individual = ts.individual(node.individual)
+                if individual.location is not None and len(individual.location) >= 2:
+                    coordinates.append((individual.location[0], individual.location[1]))
+                    node_ids.append(node.id)
+        
+        if not coordinates:
+            raise HTTPException(status_code=400, detail="No spatial coordinates found in tree sequence")
+        
+        # Transform coordinates
+        transformed_coords = transform_coordinates(
+            coordinates, 
+            transform_request.source_crs, 
+            transform_request.target_crs
+        )
+        
         # Create new tree sequence with transformed coordinates
         tables = ts.dump_tables()
         
@@ -1443,7 +5784,8 @@ async def transform_tree_sequence_coordinates(request: Request, transform_reques
         
         # Store the transformed tree sequence
         new_filename = f"{transform_request.filename.rsplit('.', 1)[0]}_transformed_{transform_request.target_crs.replace(':', '_')}.trees"
-        session_storage.store_tree_sequence(session_id, new_filename, transformed_ts)
+        session_storage.store_tree_sequence(session_id, new_filename, transformed_ts, derived_from=transform_request.filename)
+        audit_log.record(session_id, transform_request.filename, EVENT_OPERATION, "transform_tree_sequence_coordinates", transform_request.dict(), output=new_filename)
         
         return {
             "status": "success",
@@ -1515,9 +5857,273 @@ async def validate_spatial_data(request: Request, validation_request: SpatialVal
         logger.error(f"Error validating spatial data: {e}")
         raise HTTPException(status_code=500, detail=f"Spatial validation failed: {str(e)}")
 
+#### Event bridge endpoints ####
+
+def require_event_bridge():
+    if not settings.get("event_bridge"):
+        raise HTTPException(status_code=404, detail="The event bridge is disabled; enable the event_bridge setting to use it")
+
+
+@api_router.get("/events")
+async def get_event_bridge_state():
+    """Whether the bridge is enabled, connected clients and the latest viewer state."""
+    if not settings.get("event_bridge"):
+        return {"enabled": False}
+    return {"enabled": True, **event_bridge.state()}
+
+
+@api_router.post("/events")
+async def publish_bridge_message(request: Request, message: Dict[str, Any]):
+    """Publish an event or navigation command for tools without a WebSocket."""
+    require_event_bridge()
+    if not is_loopback(request.client.host if request.client else None):
+        raise HTTPException(status_code=403, detail="The event bridge only accepts local connections")
+    try:
+        return await event_bridge.publish(message)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@api_router.post("/events/push")
+async def push_tree_sequence(request: Request, name: Optional[str] = None, path: Optional[str] = None, focus: bool = True):
+    """Load a tree sequence from a local helper and open it in the connected viewers.
+
+    Send the file as the request body, or (in local mode) the `path` of a local file to read.
+    """
+    require_event_bridge()
+    if not is_loopback(request.client.host if request.client else None):
+        raise HTTPException(status_code=403, detail="The event bridge only accepts local connections")
+    session_id = session_storage.get_or_create_session(LOCAL_CLIENT_IP)
+
+    if path:
+        require_local_mode("Pushing a server file path")
+    source = Path(path).expanduser() if path else None
+    if source is not None and not source.is_file():
+        raise HTTPException(status_code=400, detail=f"No such file: {path}")
+    name = name or (source.name if source else "notebook.trees")
+    if not name.endswith((".trees", ".tsz")):
+        name += ".trees"
+    try:
+        contents = await run_in_threadpool(source.read_bytes) if source else await request.body()
+        if not contents:
+            raise ValueError("Send the tree sequence as the request body, or the path of a local file")
+        ts, filename = await run_in_threadpool(load_tree_sequence_from_file, contents, name)
+    except Exception as e:
+        raise HTTPException(status_code=400, detail=f"Could not load tree sequence: {str(e)}")
+
+    session_storage.store_tree_sequence(session_id, filename, ts)
+    if source is not None:
+        session_replay.record(session_id, filename, RECIPE_PATH, path=str(source.resolve()), preprocess="none")
+    logger.info(f"Received {filename} from a local helper ({ts.num_nodes} nodes)")
+
+    viewers = 0
+    if focus:
+        viewers = (await event_bridge.publish({"type": "open_file", "filename": filename}))["delivered"]
+    return {
+        "filename": filename,
+        "num_nodes": ts.num_nodes,
+        "num_trees": ts.num_trees,
+        "num_samples": ts.num_samples,
+        "viewers": viewers,
+    }
+
+
+@api_router.websocket("/events/ws")
+async def event_bridge_socket(websocket: WebSocket, client: str = CLIENT_VIEWER):
+    """Two-way event stream; messages sent are published, messages received are from other clients."""
+    if not settings.get("event_bridge") or client not in CLIENT_ROLES:
+        await websocket.close(code=1008)
+        return
+    if not is_loopback(websocket.client.host if websocket.client else None):
+        await websocket.close(code=1008)
+        return
+    # WebSockets bypass the HTTP middleware, so check the launch token here
+    token = get_auth_token()
+    if token is not None:
+        presented = get_request_token(websocket) or websocket.query_params.get(AUTH_QUERY_PARAM)
+        if not token_matches(presented, token):
+            await websocket.close(code=1008)
+            return
+
+    await event_bridge.connect(websocket, client)
+    try:
+        while True:
+            text = await websocket.receive_text()
+            try:
+                await event_bridge.publish(json.loads(text), sender=websocket)
+            except ValueError as e:
+                await websocket.send_json({"type": "error", "detail": str(e)})
+    except WebSocketDisconnect:
+        pass
+    except Exception as e:
+        logger.warning(f"Event bridge connection closed: {e}")
+    finally:
+        await event_bridge.disconnect(websocket)
+
+#### Collaboration endpoints ####
+
+def require_collaboration():
+    if not settings.get("collaboration"):
+        raise HTTPException(status_code=404, detail="Collaboration is disabled; enable the collaboration setting to use it")
+
+
+def get_open_collab_room(room_id: str):
+    """The room a code admits followers to, on the HTTP routes and the WebSocket alike."""
+    if not settings.get("collaboration"):
+        return None
+    return collaboration_hub.get(room_id)
+
+
+def get_collab_room(room_id: str):
+    room = collaboration_hub.get(room_id)
+    if room is None:
+        raise HTTPException(status_code=404, detail=f"No collaboration room '{room_id}'")
+    return room
+
+
+@api_router.post("/collab/rooms")
+async def create_collab_room(request: Request, room_request: CollabRoomRequest):
+    """Open a room presenting this session's view; the presenter token is only returned here."""
+    require_collaboration()
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if room_request.filename and session_storage.get_tree_sequence(session_id, room_request.filename) is None:
+        raise HTTPException(status_code=404, detail="File not found")
+    try:
+        room = await collaboration_hub.create_room(session_id, room_request.filename)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {**room.info(), "presenter_token": room.presenter_token}
+
+
+@api_router.get("/collab/rooms/{room_id}")
+async def get_collab_room_state(room_id: str):
+    require_collaboration()
+    room = get_collab_room(room_id)
+    return {**room.info(), "state": room.state}
+
+
+@api_router.post("/collab/rooms/{room_id}/join")
+async def join_collab_room(request: Request, room_id: str):
+    """Copy the presenter's current file into this session so the follower can open it."""
+    require_collaboration()
+    room = get_collab_room(room_id)
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    if room.filename and session_id != room.session_id:
+        ts = session_storage.get_tree_sequence(room.session_id, room.filename)
+        if ts is None:
+            raise HTTPException(status_code=404, detail="The presenter's file is no longer available")
+        session_storage.store_tree_sequence(session_id, room.filename, ts)
+    return {**room.info(), "state": room.state}
+
+
+@api_router.delete("/collab/rooms/{room_id}")
+async def close_collab_room(room_id: str, token: str):
+    require_collaboration()
+    room = get_collab_room(room_id)
+    if not token_matches(token, room.presenter_token):
+        raise HTTPException(status_code=403, detail="Only the presenter can close the room")
+    await collaboration_hub.close_room(room_id)
+    return {"closed": room.room_id}
+
+
+@api_router.websocket("/collab/ws/{room_id}")
+async def collaboration_socket(websocket: WebSocket, room_id: str, role: str = "follower", token: Optional[str] = None):
+    """Presenters send {"type": "state", "state": {...}}; followers receive snapshots and patches.
+
+    Unlike the event bridge this accepts LAN connections: the room code admits
+    followers and only the presenter token may change the shared state.
+    """
+    room = get_open_collab_room(room_id)
+    if room is None or role not in COLLAB_ROLES:
+        await websocket.close(code=1008)
+        return
+    if role == ROLE_PRESENTER and not token_matches(token, room.presenter_token):
+        await websocket.close(code=1008)
+        return
+
+    await collaboration_hub.connect(room, websocket, role)
+    try:
+        while True:
+            text = await websocket.receive_text()
+            try:
+                message = json.loads(text)
+                if not isinstance(message, dict):
+                    raise ValueError("Messages must be JSON objects")
+                if message.get("type") == "resync":
+                    await websocket.send_json(room.snapshot())
+                elif role == ROLE_PRESENTER and message.get("type") == "state":
+                    await websocket.send_json(await collaboration_hub.update(room, message.get("state")))
+                else:
+                    raise ValueError(f"Unexpected message type '{message.get('type')}' from a {role}")
+            except ValueError as e:
+                await websocket.send_json({"type": "error", "detail": str(e)})
+    except WebSocketDisconnect:
+        pass
+    except Exception as e:
+        logger.warning(f"Collaboration connection closed: {e}")
+    finally:
+        await collaboration_hub.disconnect(room, websocket)
+
+#### Session replay endpoints ####
+
+def record_resolution_recipe(session_id: str, filename: str, parent: str, method: str, random_seed: Optional[int]):
+    """Polytomy resolution can only be replayed when it is deterministic."""
+    if method == "random" and random_seed is None:
+        return
+    session_replay.record(session_id, filename, RECIPE_DERIVED, parent=parent, operation="resolve_polytomies", params={
+        "method": method,
+        "random_seed": random_seed,
+    })
+
+
+def replay_path(recipe: Dict[str, Any], parent_ts: Optional[tskit.TreeSequence]) -> tskit.TreeSequence:
+    path = Path(recipe["path"])
+    ts, _ = load_tree_sequence_from_file(path.read_bytes(), path.name)
+    return preprocess_tree_sequence(ts, recipe.get("preprocess", "none"))[0]
+
+
+def replay_simulation(recipe: Dict[str, Any], parent_ts: Optional[tskit.TreeSequence]) -> tskit.TreeSequence:
+    simulation_request = SimulationRequest(**recipe["params"])
+    demography, samples = simulation_demography(simulation_request)
+    return run_simulation(simulation_request, demography, samples)
+
+
+def replay_derived(recipe: Dict[str, Any], parent_ts: Optional[tskit.TreeSequence]) -> tskit.TreeSequence:
+    params = recipe["params"]
+    if recipe["operation"] == "simplify":
+        return parent_ts.simplify(**params)
+    if recipe["operation"] == "resolve_polytomies":
+        return resolve_polytomies(parent_ts, **params)[0]
+    if recipe["operation"] == "redate":
+        return run_tsdate_inference(parent_ts, progress=False, **params)[0]
+    if recipe["operation"] == "transform_stack":
+        return apply_transform_stack(parent_ts, params["steps"])[0]
+    if recipe["operation"] == "edit_populations":
+        return apply_population_edits(parent_ts, params["edits"], params["order"])[0]
+    raise ValueError(f"Cannot replay operation '{recipe['operation']}'")
+
+
+session_replay.register_replayer(RECIPE_PATH, replay_path)
+session_replay.register_replayer(RECIPE_SIMULATION, replay_simulation)
+session_replay.register_replayer(RECIPE_SAMPLE, lambda recipe, parent_ts: build_tour_sample())
+session_replay.register_replayer(RECIPE_FIXTURE, lambda recipe, parent_ts: generate_fixture(recipe["name"]))
+session_replay.register_replayer(RECIPE_DERIVED, replay_derived)
+
+
+@api_router.get("/session/replay")
+async def get_session_replay(request: Request):
+    """Which files can be rebuilt after a restart, and what the last replay restored."""
+    session_id = session_storage.get_or_create_session(get_client_ip(request))
+    return session_replay.status(session_id)
+
+
 # Mount the API router FIRST
 app.include_router(api_router)
 
+# Warm up, and rebuild files lost since the last run, in the background once every route is registered
+readiness.start(enabled=settings.get("warmup_on_launch"))
+threading.Thread(target=session_replay.replay_all, name="argscape-replay", daemon=True).start()
+
 # Mount static files AFTER API router
 frontend_dist = Path(__file__).resolve().parent.parent / "frontend_dist"
 if frontend_dist.exists():
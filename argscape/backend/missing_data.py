@@ -0,0 +1,161 @@
+"""
+Missing data: samples isolated over parts of the genome.
+tskit encodes missing data as a sample with no parent and no children over
+an interval. Statistics count such samples as carrying the ancestral allele
+(and a zero-length branch), which silently biases diversity and similar
+values downwards. This module finds those intervals, reports them per
+sample, builds a windowed missingness track, and lets statistics either
+mask windows that touch missing data or drop the affected regions and
+renormalise by the span that remains.
+"""
+
+import logging
+from typing import Any, Dict, List, Optional, Sequence
+
+import numpy as np
+import tskit
+
+from argscape.backend.tskit_utils.traversal import node_gaps
+
+logger = logging.getLogger(__name__)
+
+# How statistics treat missing data: as tskit does, null out touched windows, or cut the regions out
+MISSING_DATA_OPTIONS = ("ignore", "mask", "drop")
+# Statistics that are per unit of sequence, so dropping span needs renormalising
+SPAN_NORMALISED_STATISTICS = ("diversity", "segregating_sites", "sfs", "tmrca")
+# Interval lists in the report are capped per sample
+MAX_REPORTED_INTERVALS = 100
+
+
+def _merge(intervals: List[List[float]]) -> List[List[float]]:
+    merged: List[List[float]] = []
+    for left, right in sorted(intervals):
+        if merged and left <= merged[-1][1]:
+            merged[-1][1] = max(merged[-1][1], right)
+        else:
+            merged.append([left, right])
+    return merged
+
+
+def sample_missing_intervals(ts: tskit.TreeSequence, samples: Optional[Sequence[int]] = None) -> Dict[int, List[List[float]]]:
+    """Intervals over which each sample is isolated (in no edge), for samples that have any."""
+    samples = ts.samples() if samples is None else np.asarray(samples)
+    # A sample is present wherever it is the child or the parent of an edge
+    nodes, lefts, rights = node_gaps(ts, samples)
+    missing: Dict[int, List[List[float]]] = {}
+    for node, left, right in zip(nodes.tolist(), lefts.tolist(), rights.tolist()):
+        missing.setdefault(node, []).append([left, right])
+    return missing
+
+
+def missing_regions(ts: tskit.TreeSequence, samples: Optional[Sequence[int]] = None) -> List[List[float]]:
+    """Genome intervals where at least one of `samples` (default: all) is missing."""
+    return _merge([interval for gaps in sample_missing_intervals(ts, samples).values() for interval in gaps])
+
+
+def _span(intervals: List[List[float]]) -> float:
+    return float(sum(right - left for left, right in intervals))
+
+
+def _overlap(intervals: List[List[float]], left: float, right: float) -> float:
+    return float(sum(max(0.0, min(r, right) - max(l, left)) for l, r in intervals))
+
+
+def missing_data_report(ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """Per-sample missing spans, the regions where any sample is missing, and the sites inside them."""
+    missing = sample_missing_intervals(ts)
+    regions = _merge([interval for gaps in missing.values() for interval in gaps])
+    positions = ts.tables.sites.position
+    in_regions = np.zeros(len(positions), dtype=bool)
+    for left, right in regions:
+        in_regions |= (positions >= left) & (positions < right)
+
+    samples = []
+    for sample, gaps in sorted(missing.items()):
+        span = _span(gaps)
+        samples.append({
+            "id": sample,
+            "missing_span": span,
+            "missing_fraction": span / ts.sequence_length,
+            "num_intervals": len(gaps),
+            "intervals": gaps[:MAX_REPORTED_INTERVALS],
+        })
+    # Samples missing everywhere usually mean a broken file rather than missing data
+    fully_missing = [s["id"] for s in samples if s["missing_fraction"] >= 1.0]
+    warnings = []
+    if fully_missing:
+        warnings.append(f"{len(fully_missing)} samples have no ancestry anywhere: {fully_missing[:20]}")
+    if samples:
+        warnings.append(
+            "Statistics treat missing samples as carrying the ancestral allele; "
+            "use missing_data=mask or drop for unbiased values"
+        )
+    return {
+        "num_samples": ts.num_samples,
+        "num_samples_with_missing": len(samples),
+        "samples": samples,
+        "regions": regions[:MAX_REPORTED_INTERVALS],
+        "num_regions": len(regions),
+        "missing_span": _span(regions),
+        "missing_fraction": _span(regions) / ts.sequence_length,
+        "num_sites_in_missing_regions": int(in_regions.sum()),
+        "warnings": warnings,
+    }
+
+
+def missingness_track(ts: tskit.TreeSequence, windows: Sequence[float], per_sample: bool = False) -> Dict[str, Any]:
+    """The fraction of each window that is missing: averaged over samples, or one row per sample that has any."""
+    windows = np.asarray(windows, dtype=float)
+    missing = sample_missing_intervals(ts)
+    columns: Dict[str, List[Any]] = {"window_left": [], "window_right": []}
+    if per_sample:
+        columns.update({"sample": [], "value": []})
+        for sample, gaps in sorted(missing.items()):
+            for left, right in zip(windows[:-1], windows[1:]):
+                columns["window_left"].append(float(left))
+                columns["window_right"].append(float(right))
+                columns["sample"].append(sample)
+                columns["value"].append(_overlap(gaps, left, right) / (right - left))
+    else:
+        columns.update({"value": [], "num_samples_missing": []})
+        for left, right in zip(windows[:-1], windows[1:]):
+            overlaps = [_overlap(gaps, left, right) for gaps in missing.values()]
+            columns["window_left"].append(float(left))
+            columns["window_right"].append(float(right))
+            columns["value"].append(float(sum(overlaps) / ((right - left) * ts.num_samples)) if ts.num_samples else 0.0)
+            columns["num_samples_missing"].append(sum(1 for o in overlaps if o > 0))
+    return {"statistic": "missingness", "num_windows": len(windows) - 1, "per_sample": per_sample, "columns": columns}
+
+
+def prepare(ts: tskit.TreeSequence, option: str, samples: Sequence[int]) -> Dict[str, Any]:
+    """The tree sequence to compute on and the missing regions, for compute_statistic."""
+    if option not in MISSING_DATA_OPTIONS:
+        raise ValueError(f"Unknown missing_data option '{option}'. Expected one of {MISSING_DATA_OPTIONS}")
+    # Found even when ignored, so callers can warn that the values are biased
+    regions = missing_regions(ts, samples)
+    prepared = ts
+    if option == "drop" and regions:
+        prepared = ts.delete_intervals(regions, simplify=False)
+    return {"option": option, "ts": prepared, "regions": regions}
+
+
+def adjust_columns(columns: Dict[str, List[Any]], statistic: str, prepared: Dict[str, Any]) -> Dict[str, Any]:
+    """Null masked windows, or rescale dropped ones to the span that remains; returns a summary."""
+    regions = prepared["regions"]
+    affected = 0
+    if regions and prepared["option"] != "ignore":
+        for i, (left, right) in enumerate(zip(columns["window_left"], columns["window_right"])):
+            overlap = _overlap(regions, left, right)
+            if overlap <= 0 or "value" not in columns:
+                continue
+            affected += 1
+            if prepared["option"] == "mask" or overlap >= right - left:
+                columns["value"][i] = None
+            elif statistic in SPAN_NORMALISED_STATISTICS and columns["value"][i] is not None:
+                columns["value"][i] *= (right - left) / (right - left - overlap)
+    return {
+        "handling": prepared["option"],
+        "missing_span": _span(regions),
+        # Rows, so a window is counted once per sample set (and frequency for the SFS)
+        "affected_rows": affected,
+    }
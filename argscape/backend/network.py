@@ -0,0 +1,161 @@
+"""
+Outbound network policy and HTTP client for the backend.
+Offline mode is a kill-switch for air-gapped environments: once enabled, every
+non-loopback connection and DNS lookup made by this process is refused, and
+features that need the network check ensure_online() to fail with a clear message.
+All outbound HTTP goes through http_request(), which honours the proxy and
+custom CA bundle settings needed on institutional networks.
+"""
+
+import logging
+import socket
+import ssl
+import sys
+import threading
+import urllib.parse
+import urllib.request
+from typing import Any, Dict, Optional, Sequence
+
+from argscape.backend.sandbox import is_loopback_host
+from argscape.backend.settings import settings
+
+logger = logging.getLogger(__name__)
+
+_hook_lock = threading.Lock()
+_hook_installed = False
+
+
+class NetworkDisabledError(PermissionError):
+    """Raised when a feature needs the network while offline mode is on."""
+
+
+def is_offline() -> bool:
+    """Whether offline mode is enabled (setting or ARGSCAPE_OFFLINE)."""
+    return settings.get("offline_mode")
+
+
+def ensure_online(purpose: str):
+    """Raise NetworkDisabledError if offline mode forbids network access for `purpose`."""
+    if is_offline():
+        raise NetworkDisabledError(f"Offline mode is enabled; {purpose} requires network access")
+
+
+def _offline_audit_hook(event: str, args: tuple):
+    if event not in ("socket.connect", "socket.getaddrinfo") or not is_offline():
+        return
+    if event == "socket.connect":
+        sock, address = args[0], args[1]
+        if sock.family in (socket.AF_INET, socket.AF_INET6) and not is_loopback_host(address[0]):
+            raise NetworkDisabledError("Offline mode is enabled; outbound connections are blocked")
+    elif not is_loopback_host(args[0]):
+        raise NetworkDisabledError("Offline mode is enabled; name resolution is blocked")
+
+
+def install_network_guard():
+    """Enforce offline mode for all code in this process (idempotent).
+
+    The audit hook stays installed but only acts while offline mode is on,
+    so the setting can be toggled at runtime.
+    """
+    global _hook_installed
+    with _hook_lock:
+        if _hook_installed:
+            return
+        sys.addaudithook(_offline_audit_hook)
+        _hook_installed = True
+    logger.info("Network guard installed")
+
+
+def get_proxies() -> Dict[str, str]:
+    """Proxies to use: the manual override if set, otherwise the system configuration."""
+    proxy_url = settings.get("proxy_url")
+    if proxy_url:
+        return {"http": proxy_url, "https": proxy_url}
+    return urllib.request.getproxies()
+
+
+def get_ssl_context() -> ssl.SSLContext:
+    """TLS context trusting the system store plus the configured CA bundle, if any."""
+    context = ssl.create_default_context()
+    ca_bundle = settings.get("ca_bundle_path")
+    if ca_bundle:
+        context.load_verify_locations(cafile=ca_bundle)
+    return context
+
+
+def build_opener(*handlers: urllib.request.BaseHandler) -> urllib.request.OpenerDirector:
+    """urllib opener configured with the current proxy and CA settings, plus any extra handlers."""
+    return urllib.request.build_opener(
+        urllib.request.ProxyHandler(get_proxies()),
+        urllib.request.HTTPSHandler(context=get_ssl_context()),
+        *handlers,
+    )
+
+
+def http_open(
+    url: str,
+    purpose: str,
+    method: str = "GET",
+    data: Optional[bytes] = None,
+    headers: Optional[Dict[str, str]] = None,
+    timeout: float = 30.0,
+    handlers: Sequence[urllib.request.BaseHandler] = ()
+):
+    """Open an outbound HTTP request and return the response for streaming (use as a context manager).
+
+    Args:
+        url: Absolute http(s) URL
+        purpose: Short description used in offline-mode errors and logs
+        handlers: Extra urllib handlers, e.g. one that vets redirects
+    """
+    ensure_online(purpose)
+    request = urllib.request.Request(url, data=data, method=method, headers=headers or {})
+    logger.info(f"Outbound request for {purpose}: {method} {_redact_credentials(url)}")
+    return build_opener(*handlers).open(request, timeout=timeout)
+
+
+def http_request(
+    url: str,
+    purpose: str,
+    method: str = "GET",
+    data: Optional[bytes] = None,
+    headers: Optional[Dict[str, str]] = None,
+    timeout: float = 30.0
+) -> bytes:
+    """Perform an outbound HTTP request and return the response body (see http_open)."""
+    with http_open(url, purpose, method, data, headers, timeout) as response:
+        return response.read()
+
+
+def _redact_credentials(url: str) -> str:
+    parts = urllib.parse.urlsplit(url)
+    if parts.password is None:
+        return url
+    netloc = f"{parts.username}:***@{parts.hostname}" + (f":{parts.port}" if parts.port else "")
+    return urllib.parse.urlunsplit(parts._replace(netloc=netloc))
+
+
+def get_network_status() -> Dict[str, Any]:
+    """Network state for the UI's offline badge and proxy diagnostics."""
+    proxy_url = settings.get("proxy_url")
+    return {
+        "offline": is_offline(),
+        "outbound": "blocked (loopback only)" if is_offline() else "allowed",
+        "guard_installed": _hook_installed,
+        "proxy": {
+            "source": "manual" if proxy_url else "system",
+            "proxies": {scheme: _redact_credentials(url) for scheme, url in get_proxies().items()},
+        },
+        "ca_bundle_path": settings.get("ca_bundle_path"),
+    }
+
+
+def test_connection(url: str, timeout: float = 10.0) -> Dict[str, Any]:
+    """Check that a URL is reachable with the current proxy/CA settings."""
+    try:
+        http_request(url, "connection test", method="HEAD", timeout=timeout)
+        return {"url": url, "reachable": True}
+    except NetworkDisabledError as e:
+        return {"url": url, "reachable": False, "error": str(e)}
+    except Exception as e:
+        return {"url": url, "reachable": False, "error": f"{type(e).__name__}: {e}"}
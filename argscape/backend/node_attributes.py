@@ -0,0 +1,288 @@
+"""
+Derived per-node attributes from expressions.
+Instead of new frontend code for every coloring idea, a user writes an
+expression such as `log10(time + 1)`, `num_samples / total` or
+`meta("age") > 50` and ARGscape evaluates it over every node at once. The
+expression is parsed with Python's ast module and only a whitelist of
+names, functions and operators is allowed: there is no attribute access,
+indexing, import or call of anything not listed here. Named attributes are
+stored per file and can color nodes (numbers bin like statistics, strings
+become categories) or filter them (any expression that is true or false).
+"""
+
+import ast
+import json
+import logging
+import math
+from typing import Any, Callable, Dict, List, Optional, Set
+
+import numpy as np
+import tskit
+
+from argscape.backend.ts_cache import TreeSequenceCache
+
+logger = logging.getLogger(__name__)
+
+MAX_EXPRESSION_LENGTH = 500
+MAX_CACHED_ATTRIBUTES = 64
+
+# Per-node variables, and what each means (sent to the frontend for the editor's help)
+NODE_VARIABLES = {
+    "id": "Node ID",
+    "time": "Node time, in the file's time units",
+    "population": "Population ID (-1 for none)",
+    "individual": "Individual ID (-1 for none)",
+    "flags": "Node flags",
+    "is_sample": "1 for sample nodes, else 0",
+    "num_samples": "Mean number of samples below the node, weighted by span",
+    "genomic_span": "Length of genome over which the node is ancestral to a sample",
+    "num_parents": "Distinct parents in the ARG",
+    "num_children": "Distinct children in the ARG",
+    "degree": "num_parents + num_children",
+    "is_recombination": "1 for nodes with several parents (or msprime's RE flag)",
+}
+# Values that are the same for every node
+CONSTANTS = {
+    "total": "Number of samples",
+    "num_nodes": "Number of nodes",
+    "max_time": "Oldest node time",
+    "sequence_length": "Sequence length",
+    "pi": "3.14159...",
+    "e": "2.71828...",
+}
+# Variables that need the node metrics pass (two sample-count statistics over the trees)
+METRIC_VARIABLES = ("num_samples", "genomic_span", "num_parents", "num_children", "degree", "is_recombination")
+
+FUNCTIONS: Dict[str, Callable] = {
+    "log": np.log,
+    "log2": np.log2,
+    "log10": np.log10,
+    "exp": np.exp,
+    "sqrt": np.sqrt,
+    "abs": np.abs,
+    "floor": np.floor,
+    "ceil": np.ceil,
+    "round": np.round,
+    "isnan": lambda x: np.isnan(np.asarray(x, dtype=float)),
+    "min": np.minimum,
+    "max": np.maximum,
+    "clip": np.clip,
+    "where": np.where,
+    "rank": lambda x: np.unique(np.asarray(x), return_inverse=True)[1].astype(float),
+}
+# Functions taking a metadata key as a string literal: node, individual or population metadata
+METADATA_FUNCTIONS = {"meta": "nodes", "ind_meta": "individuals", "pop_meta": "populations"}
+
+BINARY_OPERATORS = {
+    ast.Add: np.add, ast.Sub: np.subtract, ast.Mult: np.multiply, ast.Div: np.true_divide,
+    ast.FloorDiv: np.floor_divide, ast.Mod: np.mod, ast.Pow: np.power,
+}
+COMPARISONS = {
+    ast.Eq: np.equal, ast.NotEq: np.not_equal, ast.Lt: np.less,
+    ast.LtE: np.less_equal, ast.Gt: np.greater, ast.GtE: np.greater_equal,
+}
+
+_cache = TreeSequenceCache("node_attributes", MAX_CACHED_ATTRIBUTES)
+
+
+def parse_expression(expression: str) -> ast.Expression:
+    """Parse and check an expression against the whitelist; raises ValueError naming what isn't allowed."""
+    if not expression or not expression.strip():
+        raise ValueError("Expression is empty")
+    if len(expression) > MAX_EXPRESSION_LENGTH:
+        raise ValueError(f"Expressions are limited to {MAX_EXPRESSION_LENGTH} characters")
+    try:
+        tree = ast.parse(expression.strip(), mode="eval")
+    except SyntaxError as e:
+        raise ValueError(f"Invalid expression: {e.msg} at column {e.offset}")
+
+    allowed_names = set(NODE_VARIABLES) | set(CONSTANTS)
+    for node in ast.walk(tree):
+        if isinstance(node, ast.Name) and node.id not in allowed_names and node.id not in FUNCTIONS and node.id not in METADATA_FUNCTIONS:
+            raise ValueError(f"Unknown name '{node.id}'. Variables: {sorted(allowed_names)}")
+        if isinstance(node, ast.Call):
+            if not isinstance(node.func, ast.Name) or (node.func.id not in FUNCTIONS and node.func.id not in METADATA_FUNCTIONS):
+                raise ValueError(f"Only these functions can be called: {sorted(FUNCTIONS) + sorted(METADATA_FUNCTIONS)}")
+            if node.keywords:
+                raise ValueError("Functions take positional arguments only")
+            if node.func.id in METADATA_FUNCTIONS and not (
+                len(node.args) in (1, 2) and isinstance(node.args[0], ast.Constant) and isinstance(node.args[0].value, str)
+            ):
+                raise ValueError(f"{node.func.id}() takes a metadata key string and optionally a default")
+        if not isinstance(node, (
+            ast.Expression, ast.BinOp, ast.UnaryOp, ast.BoolOp, ast.Compare, ast.IfExp, ast.Call, ast.Name, ast.Constant,
+            ast.Load, ast.And, ast.Or, ast.Not, ast.USub, ast.UAdd, *BINARY_OPERATORS, *COMPARISONS,
+        )):
+            raise ValueError(f"'{type(node).__name__}' is not allowed in expressions")
+    return tree
+
+
+def referenced_names(tree: ast.Expression) -> Set[str]:
+    return {node.id for node in ast.walk(tree) if isinstance(node, ast.Name)}
+
+
+def _decoded(metadata: Any) -> Any:
+    if isinstance(metadata, bytes):
+        try:
+            return json.loads(metadata.decode("utf-8")) if metadata else {}
+        except (UnicodeDecodeError, ValueError):
+            return {}
+    return metadata
+
+
+def _metadata_column(ts: tskit.TreeSequence, table: str, key: str, default: Any) -> np.ndarray:
+    """A metadata field per node (via the node's individual or population for those tables)."""
+    values = []
+    for row in getattr(ts, table)():
+        value = _decoded(row.metadata)
+        # Dotted keys reach into nested metadata, e.g. "location.region"
+        for part in key.split("."):
+            value = value.get(part) if isinstance(value, dict) else None
+        values.append(default if value is None else value)
+    if table == "nodes":
+        per_node = values
+    else:
+        link = ts.tables.nodes.individual if table == "individuals" else ts.tables.nodes.population
+        per_node = [values[i] if i >= 0 else default for i in link]
+    if all(isinstance(v, (int, float)) or v is None for v in per_node):
+        return np.array([np.nan if v is None else float(v) for v in per_node], dtype=float)
+    return np.array(["" if v is None else (v if isinstance(v, str) else json.dumps(v)) for v in per_node], dtype=object)
+
+
+class _Evaluator:
+    def __init__(self, ts: tskit.TreeSequence, variables: Dict[str, Any]):
+        self.ts = ts
+        self.variables = variables
+
+    def visit(self, node: ast.AST) -> Any:
+        if isinstance(node, ast.Expression):
+            return self.visit(node.body)
+        if isinstance(node, ast.Constant):
+            if not isinstance(node.value, (int, float, str)):
+                raise ValueError(f"Unsupported constant {node.value!r}")
+            return node.value
+        if isinstance(node, ast.Name):
+            return self.variables[node.id]
+        if isinstance(node, ast.BinOp):
+            return BINARY_OPERATORS[type(node.op)](self.visit(node.left), self.visit(node.right))
+        if isinstance(node, ast.UnaryOp):
+            operand = self.visit(node.operand)
+            if isinstance(node.op, ast.Not):
+                return np.logical_not(operand)
+            return np.negative(operand) if isinstance(node.op, ast.USub) else operand
+        if isinstance(node, ast.BoolOp):
+            combine = np.logical_and if isinstance(node.op, ast.And) else np.logical_or
+            result = self.visit(node.values[0])
+            for value in node.values[1:]:
+                result = combine(result, self.visit(value))
+            return result
+        if isinstance(node, ast.Compare):
+            result, left = True, self.visit(node.left)
+            for op, comparator in zip(node.ops, node.comparators):
+                right = self.visit(comparator)
+                result = np.logical_and(result, COMPARISONS[type(op)](left, right))
+                left = right
+            return result
+        if isinstance(node, ast.IfExp):
+            return np.where(self.visit(node.test), self.visit(node.body), self.visit(node.orelse))
+        if isinstance(node, ast.Call):
+            name = node.func.id
+            if name in METADATA_FUNCTIONS:
+                default = self.visit(node.args[1]) if len(node.args) > 1 else None
+                return _metadata_column(self.ts, METADATA_FUNCTIONS[name], node.args[0].value, default)
+            return FUNCTIONS[name](*(self.visit(arg) for arg in node.args))
+        raise ValueError(f"'{type(node).__name__}' is not allowed in expressions")
+
+
+def _variables(ts: tskit.TreeSequence, names: Set[str]) -> Dict[str, Any]:
+    nodes = ts.tables.nodes
+    variables: Dict[str, Any] = {
+        "id": np.arange(ts.num_nodes, dtype=float),
+        "time": nodes.time.astype(float),
+        "population": nodes.population.astype(float),
+        "individual": nodes.individual.astype(float),
+        "flags": nodes.flags.astype(np.int64),
+        "is_sample": ((nodes.flags & tskit.NODE_IS_SAMPLE) != 0).astype(float),
+        "total": float(ts.num_samples),
+        "num_nodes": float(ts.num_nodes),
+        "max_time": float(nodes.time.max()) if ts.num_nodes else 0.0,
+        "sequence_length": float(ts.sequence_length),
+        "pi": math.pi,
+        "e": math.e,
+    }
+    if names & set(METRIC_VARIABLES):
+        from argscape.backend.tskit_utils.node_metrics import compute_node_metrics
+
+        metrics = compute_node_metrics(ts, include_betweenness=False)
+        variables["num_samples"] = np.asarray(metrics["descendant_samples"], dtype=float)
+        for name in METRIC_VARIABLES[1:]:
+            variables[name] = np.asarray(metrics[name], dtype=float)
+    return variables
+
+
+def evaluate_expression(ts: tskit.TreeSequence, expression: str) -> Dict[str, Any]:
+    """Evaluate over every node; returns {"kind": "numeric"|"boolean"|"categorical", "values", ...}.
+
+    Raises:
+        ValueError: If the expression isn't allowed, fails, or doesn't give one value per node
+    """
+    tree = parse_expression(expression)
+    variables = _variables(ts, referenced_names(tree))
+    try:
+        with np.errstate(all="ignore"):
+            values = _Evaluator(ts, variables).visit(tree)
+    except ValueError:
+        raise
+    except Exception as e:
+        raise ValueError(f"Expression failed: {e}")
+    values = np.broadcast_to(np.asarray(values), (ts.num_nodes,))
+    # Comparisons of text fields give object arrays of booleans
+    if values.dtype == object and all(isinstance(v, (bool, np.bool_)) for v in values):
+        values = values.astype(bool)
+
+    if values.dtype == object or values.dtype.kind in "US":
+        categories = sorted({str(v) for v in values})
+        return {"kind": "categorical", "values": [str(v) for v in values], "categories": categories}
+    if values.dtype == bool:
+        return {"kind": "boolean", "values": values.tolist(), "num_true": int(values.sum())}
+    numeric = values.astype(float)
+    finite = numeric[np.isfinite(numeric)]
+    return {
+        "kind": "numeric",
+        # NaN and infinities (log of zero, missing metadata) are sent as null
+        "values": [float(v) if math.isfinite(v) else None for v in numeric],
+        "min": float(finite.min()) if len(finite) else None,
+        "max": float(finite.max()) if len(finite) else None,
+        "num_missing": int(len(numeric) - len(finite)),
+    }
+
+
+def get_attribute_values(ts: tskit.TreeSequence, expression: str) -> Dict[str, Any]:
+    """evaluate_expression, cached per tree sequence and expression."""
+    cached = _cache.get(ts, expression)
+    if cached is not None:
+        return cached
+
+    result = evaluate_expression(ts, expression)
+    _cache.put(ts, result, expression)
+    logger.info(f"Evaluated node attribute '{expression}' over {ts.num_nodes} nodes")
+    return result
+
+
+def filter_nodes(ts: tskit.TreeSequence, expression: str) -> List[int]:
+    """IDs of the nodes for which an expression is true (non-zero)."""
+    result = get_attribute_values(ts, expression)
+    if result["kind"] == "categorical":
+        raise ValueError("A filter must be true or false for each node, not text")
+    return [u for u, value in enumerate(result["values"]) if value]
+
+
+def reference() -> Dict[str, Any]:
+    """Everything an expression may use, for the editor's help."""
+    return {
+        "variables": NODE_VARIABLES,
+        "constants": CONSTANTS,
+        "functions": sorted(FUNCTIONS),
+        "metadata_functions": {name: f"{table} metadata field, e.g. {name}(\"name\")" for name, table in METADATA_FUNCTIONS.items()},
+        "examples": ["log10(time + 1)", "num_samples / total", "meta(\"age\", 0) > 50", "pop_meta(\"name\")"],
+    }
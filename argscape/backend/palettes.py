@@ -0,0 +1,228 @@
+"""
+Color palettes shared by every view and export.
+Categorical palettes color populations, continuous ramps color time and
+statistics, and the colorblind-safe presets are flagged so the UI can offer
+them first. A tree sequence's palette choice is stored in its session, so the
+frontend and backend renderers always resolve the same colors.
+"""
+
+import colorsys
+import json
+import logging
+import math
+from typing import Any, Dict, List, Optional
+
+import tskit
+
+logger = logging.getLogger(__name__)
+
+CATEGORICAL_PALETTES: Dict[str, Dict[str, Any]] = {
+    "okabe_ito": {
+        "label": "Okabe-Ito",
+        "colorblind_safe": True,
+        "colors": ["#E69F00", "#56B4E9", "#009E73", "#F0E442", "#0072B2", "#D55E00", "#CC79A7", "#000000"],
+    },
+    "tol_bright": {
+        "label": "Tol bright",
+        "colorblind_safe": True,
+        "colors": ["#4477AA", "#EE6677", "#228833", "#CCBB44", "#66CCEE", "#AA3377", "#BBBBBB"],
+    },
+    "tol_muted": {
+        "label": "Tol muted",
+        "colorblind_safe": True,
+        "colors": ["#CC6677", "#332288", "#DDCC77", "#117733", "#88CCEE", "#882255", "#44AA99", "#999933", "#AA4499"],
+    },
+    "tableau10": {
+        "label": "Tableau 10",
+        "colorblind_safe": False,
+        "colors": ["#4E79A7", "#F28E2B", "#E15759", "#76B7B2", "#59A14F", "#EDC948", "#B07AA1", "#FF9DA7", "#9C755F", "#BAB0AC"],
+    },
+}
+
+# Continuous ramps as evenly spaced color stops, interpolated linearly in RGB
+CONTINUOUS_RAMPS: Dict[str, Dict[str, Any]] = {
+    "viridis": {
+        "label": "Viridis",
+        "colorblind_safe": True,
+        "stops": ["#440154", "#482878", "#3E4A89", "#31688E", "#26828E", "#1F9E89", "#35B779", "#6DCD59", "#B4DE2C", "#FDE725"],
+    },
+    "cividis": {
+        "label": "Cividis",
+        "colorblind_safe": True,
+        "stops": ["#00224E", "#123570", "#3B496C", "#575D6D", "#707173", "#8A8779", "#A69D75", "#C4B56C", "#E4CF5B", "#FEE838"],
+    },
+    "magma": {
+        "label": "Magma",
+        "colorblind_safe": True,
+        "stops": ["#000004", "#180F3D", "#440F76", "#721F81", "#9E2F7F", "#CD4071", "#F1605D", "#FD9668", "#FECA8D", "#FCFDBF"],
+    },
+    "blue_red": {
+        "label": "Blue-red (diverging)",
+        "colorblind_safe": True,
+        "stops": ["#2166AC", "#4393C3", "#92C5DE", "#D1E5F0", "#F7F7F7", "#FDDBC7", "#F4A582", "#D6604D", "#B2182B"],
+    },
+    "grayscale": {
+        "label": "Grayscale",
+        "colorblind_safe": True,
+        "stops": ["#F0F0F0", "#101010"],
+    },
+}
+
+DEFAULT_CATEGORICAL = "okabe_ito"
+DEFAULT_CONTINUOUS = "viridis"
+
+
+def _hex_to_rgb(color: str) -> List[int]:
+    color = color.lstrip("#")
+    return [int(color[i:i + 2], 16) for i in (0, 2, 4)]
+
+
+def _rgb_to_hex(rgb: List[float]) -> str:
+    return "#" + "".join(f"{int(round(max(0, min(255, c)))):02X}" for c in rgb)
+
+
+def validate_color(color: str) -> str:
+    if not isinstance(color, str) or len(color.lstrip("#")) != 6:
+        raise ValueError(f"Colors must be hex strings like '#1F77B4', got {color!r}")
+    try:
+        _hex_to_rgb(color)
+    except ValueError:
+        raise ValueError(f"Colors must be hex strings like '#1F77B4', got {color!r}")
+    return "#" + color.lstrip("#").upper()
+
+
+def list_presets() -> Dict[str, Any]:
+    """All palettes, colorblind-safe ones first."""
+    def describe(palettes: Dict[str, Dict[str, Any]]) -> List[Dict[str, Any]]:
+        items = [{"name": name, **spec} for name, spec in palettes.items()]
+        return sorted(items, key=lambda item: not item["colorblind_safe"])
+
+    return {
+        "categorical": describe(CATEGORICAL_PALETTES),
+        "continuous": describe(CONTINUOUS_RAMPS),
+        "default_categorical": DEFAULT_CATEGORICAL,
+        "default_continuous": DEFAULT_CONTINUOUS,
+    }
+
+
+def categorical_palette(name: str, n: int) -> List[str]:
+    """`n` distinct colors; past the preset's size, lighter and darker variants are added."""
+    if name not in CATEGORICAL_PALETTES:
+        raise ValueError(f"Unknown categorical palette '{name}'. Expected one of {tuple(CATEGORICAL_PALETTES)}")
+    base = CATEGORICAL_PALETTES[name]["colors"]
+    colors = []
+    for i in range(n):
+        color = base[i % len(base)]
+        cycle = i // len(base)
+        if cycle:
+            # Alternate lighter/darker variants on each pass through the preset
+            r, g, b = (c / 255 for c in _hex_to_rgb(color))
+            h, l, s = colorsys.rgb_to_hls(r, g, b)
+            shift = 0.15 * ((cycle + 1) // 2) * (1 if cycle % 2 else -1)
+            r, g, b = colorsys.hls_to_rgb(h, max(0.1, min(0.9, l + shift)), s)
+            color = _rgb_to_hex([r * 255, g * 255, b * 255])
+        colors.append(color)
+    return colors
+
+
+def ramp_color(name: str, fraction: float) -> str:
+    """Color at `fraction` in [0, 1] along a continuous ramp."""
+    if name not in CONTINUOUS_RAMPS:
+        raise ValueError(f"Unknown continuous ramp '{name}'. Expected one of {tuple(CONTINUOUS_RAMPS)}")
+    stops = [_hex_to_rgb(c) for c in CONTINUOUS_RAMPS[name]["stops"]]
+    if math.isnan(fraction):
+        fraction = 0.0
+    position = max(0.0, min(1.0, fraction)) * (len(stops) - 1)
+    i = min(int(position), len(stops) - 2)
+    t = position - i
+    return _rgb_to_hex([a + (b - a) * t for a, b in zip(stops[i], stops[i + 1])])
+
+
+def continuous_ramp(name: str, n: int = 256) -> List[str]:
+    """The ramp sampled at `n` evenly spaced points, for color-bar textures and lookup tables."""
+    return [ramp_color(name, i / max(n - 1, 1)) for i in range(n)]
+
+
+def value_fraction(value: float, vmin: float, vmax: float, log_scale: bool = False) -> float:
+    """Position of a value within [vmin, vmax], optionally on a log scale."""
+    if log_scale:
+        # Zero (e.g. sample times) is clamped to a millionth of the range's upper end
+        floor = vmin if vmin > 0 else (vmax * 1e-6 if vmax > 0 else 1.0)
+        value, vmin, vmax = (math.log10(max(v, floor)) for v in (value, vmin, vmax))
+    if vmax <= vmin:
+        return 0.0
+    return (value - vmin) / (vmax - vmin)
+
+
+def population_metadata(ts: tskit.TreeSequence) -> List[Dict[str, Any]]:
+    """Each population's metadata as a dict; {} where it isn't a JSON object."""
+    records = []
+    for population in ts.populations():
+        metadata = population.metadata
+        if isinstance(metadata, bytes):
+            try:
+                metadata = json.loads(metadata.decode("utf-8")) if metadata else {}
+            except (UnicodeDecodeError, ValueError):
+                metadata = {}
+        records.append(metadata if isinstance(metadata, dict) else {})
+    return records
+
+
+def population_names(ts: tskit.TreeSequence) -> List[str]:
+    """Display name of each population, from its metadata where available."""
+    return [str(m["name"]) if m.get("name") else f"pop_{i}" for i, m in enumerate(population_metadata(ts))]
+
+
+def _metadata_color(metadata: Dict[str, Any]) -> Optional[str]:
+    try:
+        return validate_color(metadata["color"]) if metadata.get("color") else None
+    except ValueError:
+        return None
+
+
+def resolve_palette(ts: tskit.TreeSequence, choice: Optional[Dict[str, Any]] = None) -> Dict[str, Any]:
+    """Concrete colors for a tree sequence under a stored palette choice.
+
+    Args:
+        ts: Tree sequence whose populations are colored
+        choice: {"categorical", "continuous", "population_overrides": {pop ID: color}};
+            missing entries use the defaults
+    """
+    choice = choice or {}
+    categorical = choice.get("categorical", DEFAULT_CATEGORICAL)
+    continuous = choice.get("continuous", DEFAULT_CONTINUOUS)
+    overrides = {int(k): validate_color(v) for k, v in (choice.get("population_overrides") or {}).items()}
+
+    names = population_names(ts)
+    metadata = population_metadata(ts)
+    colors = categorical_palette(categorical, len(names))
+    # Session overrides win over colors saved in the populations table, which win over the palette
+    populations = [
+        {
+            "id": i,
+            "name": name,
+            "color": overrides.get(i) or _metadata_color(metadata[i]) or colors[i],
+            "display_order": metadata[i].get("display_order", i),
+        }
+        for i, name in enumerate(names)
+    ]
+    return {
+        "categorical": categorical,
+        "continuous": continuous,
+        "colorblind_safe": CATEGORICAL_PALETTES[categorical]["colorblind_safe"] and CONTINUOUS_RAMPS[continuous]["colorblind_safe"],
+        "populations": populations,
+        "population_overrides": {str(k): v for k, v in overrides.items()},
+        "ramp": continuous_ramp(continuous, 32),
+    }
+
+
+def validate_choice(choice: Dict[str, Any]) -> Dict[str, Any]:
+    """Check a palette choice before it is stored; raises ValueError."""
+    categorical = choice.get("categorical", DEFAULT_CATEGORICAL)
+    continuous = choice.get("continuous", DEFAULT_CONTINUOUS)
+    if categorical not in CATEGORICAL_PALETTES:
+        raise ValueError(f"Unknown categorical palette '{categorical}'. Expected one of {tuple(CATEGORICAL_PALETTES)}")
+    if continuous not in CONTINUOUS_RAMPS:
+        raise ValueError(f"Unknown continuous ramp '{continuous}'. Expected one of {tuple(CONTINUOUS_RAMPS)}")
+    overrides = {str(int(k)): validate_color(v) for k, v in (choice.get("population_overrides") or {}).items()}
+    return {"categorical": categorical, "continuous": continuous, "population_overrides": overrides}
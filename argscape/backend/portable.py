@@ -0,0 +1,73 @@
+"""
+Portable mode: all state beside the executable.
+For running ARGscape from a USB stick or a shared lab drive without install
+rights. When a marker file named `argscape-portable` sits next to the
+executable (or `--portable` / ARGSCAPE_PORTABLE is given), settings, caches,
+crash logs and session files go to an `argscape-data` folder there instead of
+the OS app-data and temp locations. The marker may contain a folder to use
+instead, relative to the marker.
+"""
+
+import os
+import sys
+from pathlib import Path
+from typing import Any, Dict, Optional
+
+PORTABLE_MARKER = "argscape-portable"
+PORTABLE_DIRNAME = "argscape-data"
+# "1" for the default folder, or a folder path; set by --portable so child processes agree
+PORTABLE_ENV_VAR = "ARGSCAPE_PORTABLE"
+SESSIONS_DIRNAME = "sessions"
+
+
+def executable_dir() -> Path:
+    """The folder of the packaged executable, or of the `argscape` launcher script."""
+    if getattr(sys, "frozen", False):
+        return Path(sys.executable).resolve().parent
+    if sys.argv and sys.argv[0] and Path(sys.argv[0]).exists():
+        return Path(sys.argv[0]).resolve().parent
+    return Path.cwd()
+
+
+def _from_marker(marker: Path) -> Path:
+    try:
+        target = marker.read_text(encoding="utf-8").strip().splitlines()
+    except OSError:
+        target = []
+    if target and target[0].strip():
+        path = Path(target[0].strip()).expanduser()
+        return path if path.is_absolute() else marker.parent / path
+    return marker.parent / PORTABLE_DIRNAME
+
+
+def get_portable_dir() -> Optional[Path]:
+    """The portable state folder, or None when ARGscape runs installed."""
+    value = os.getenv(PORTABLE_ENV_VAR, "").strip()
+    if value.lower() in ("0", "false", "no", "off"):
+        return None
+    if value.lower() in ("1", "true", "yes", "on"):
+        return executable_dir() / PORTABLE_DIRNAME
+    if value:
+        return Path(value).expanduser().resolve()
+    marker = executable_dir() / PORTABLE_MARKER
+    return _from_marker(marker) if marker.is_file() else None
+
+
+def apply_portable_environment() -> Optional[Path]:
+    """Pin the portable folder and session storage in the environment for this process and its children."""
+    directory = get_portable_dir()
+    if directory is None:
+        return None
+    directory.mkdir(parents=True, exist_ok=True)
+    os.environ[PORTABLE_ENV_VAR] = str(directory)
+    os.environ.setdefault("PERSISTENT_SESSION_PATH", str(directory / SESSIONS_DIRNAME))
+    return directory
+
+
+def portable_status() -> Dict[str, Any]:
+    directory = get_portable_dir()
+    return {
+        "portable": directory is not None,
+        "directory": str(directory) if directory else None,
+        "marker": str(executable_dir() / PORTABLE_MARKER),
+    }
@@ -0,0 +1,125 @@
+"""
+Prefetching of graph data for genome scrubbing.
+When the user drags the genome position slider, the frontend asks for
+neighboring tree windows to be prepared in the background. Results sit in a
+small LRU cache that the graph data endpoint checks first, so moving to the
+next breakpoint doesn't wait on filtering, simplification and conversion.
+"""
+
+import collections
+import logging
+import threading
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any, Callable, Dict, Optional, Tuple
+
+import tskit
+
+logger = logging.getLogger(__name__)
+
+MAX_CACHED_FRAMES = 32
+MAX_PREFETCH_WORKERS = 2
+DEFAULT_PREFETCH_RADIUS = 3
+MAX_PREFETCH_RADIUS = 10
+
+# (start_idx, end_idx, max_samples, sample_order)
+FrameParams = Tuple[int, int, int, str]
+FrameBuilder = Callable[[tskit.TreeSequence, int, int, int, str], Dict[str, Any]]
+
+
+class FramePrefetcher:
+    """LRU cache of graph data per tree window, filled ahead of the user's position."""
+
+    def __init__(self, max_entries: int = MAX_CACHED_FRAMES, max_workers: int = MAX_PREFETCH_WORKERS):
+        self.max_entries = max_entries
+        self._executor = ThreadPoolExecutor(max_workers=max_workers, thread_name_prefix="argscape-prefetch")
+        self._lock = threading.Lock()
+        # (session_id, filename, params) -> (tree sequence it was built from, graph data)
+        self._cache: "collections.OrderedDict[tuple, Tuple[tskit.TreeSequence, Dict[str, Any]]]" = collections.OrderedDict()
+        self._pending = set()
+        # Bumped on each prefetch request so work queued for an old position is dropped
+        self._generation: Dict[Tuple[str, str], int] = {}
+        self.hits = 0
+        self.misses = 0
+
+    def get(self, session_id: str, filename: str, ts: tskit.TreeSequence, params: FrameParams) -> Optional[Dict[str, Any]]:
+        key = (session_id, filename, params)
+        with self._lock:
+            entry = self._cache.get(key)
+            # Entries built from another tree sequence under the same name are stale
+            if entry is None or entry[0] is not ts:
+                self.misses += 1
+                return None
+            self._cache.move_to_end(key)
+            self.hits += 1
+            return entry[1]
+
+    def put(self, session_id: str, filename: str, ts: tskit.TreeSequence, params: FrameParams, data: Dict[str, Any]):
+        with self._lock:
+            key = (session_id, filename, params)
+            self._cache[key] = (ts, data)
+            self._cache.move_to_end(key)
+            while len(self._cache) > self.max_entries:
+                self._cache.popitem(last=False)
+
+    def prefetch(
+        self,
+        session_id: str,
+        filename: str,
+        ts: tskit.TreeSequence,
+        start_idx: int,
+        end_idx: int,
+        max_samples: int,
+        sample_order: str,
+        builder: FrameBuilder,
+        radius: int = DEFAULT_PREFETCH_RADIUS,
+        step: Optional[int] = None
+    ) -> Dict[str, Any]:
+        """Queue windows shifted by `step` trees (default: the window width), nearest first, in both directions."""
+        if not 1 <= radius <= MAX_PREFETCH_RADIUS:
+            raise ValueError(f"radius must be between 1 and {MAX_PREFETCH_RADIUS}")
+        width = end_idx - start_idx + 1
+        step = step or width
+        file_key = (session_id, filename)
+        with self._lock:
+            generation = self._generation.get(file_key, 0) + 1
+            self._generation[file_key] = generation
+
+        queued, cached = [], 0
+        for distance in range(1, radius + 1):
+            for direction in (1, -1):
+                start = start_idx + direction * distance * step
+                end = start + width - 1
+                if start < 0 or end >= ts.num_trees:
+                    continue
+                params = (start, end, max_samples, sample_order)
+                key = (session_id, filename, params)
+                with self._lock:
+                    if key in self._pending:
+                        continue
+                    entry = self._cache.get(key)
+                    if entry is not None and entry[0] is ts:
+                        cached += 1
+                        continue
+                    self._pending.add(key)
+                self._executor.submit(self._build, key, ts, params, builder, file_key, generation)
+                queued.append([start, end])
+        return {"queued": queued, "already_cached": cached}
+
+    def _build(self, key, ts, params, builder, file_key, generation):
+        try:
+            if self._generation.get(file_key) != generation:
+                return
+            data = builder(ts, *params)
+            self.put(key[0], key[1], ts, params, data)
+        except Exception as e:
+            logger.debug(f"Prefetch of trees {params[0]}-{params[1]} for {key[1]} failed: {e}")
+        finally:
+            with self._lock:
+                self._pending.discard(key)
+
+    def stats(self) -> Dict[str, Any]:
+        with self._lock:
+            return {"cached_frames": len(self._cache), "pending": len(self._pending), "hits": self.hits, "misses": self.misses}
+
+
+frame_prefetcher = FramePrefetcher()
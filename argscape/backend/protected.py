@@ -0,0 +1,98 @@
+"""
+Protected data handling for users working under data-access agreements.
+Protected tree sequences (per file, or every file when protected mode is on
+globally) are kept in memory only: no derived files, caches or generated file
+data are written to disk, and file paths and protected filenames are scrubbed
+from log output.
+"""
+
+import logging
+import os
+import re
+import tempfile
+import threading
+from typing import List, Set
+
+from argscape.backend.settings import settings
+
+SCRUBBED_PATH = "<path>"
+SCRUBBED_NAME = "<protected file>"
+
+
+def _path_prefixes() -> List[str]:
+    """Where user data lives: home directories, temporary folders and the working directory."""
+    prefixes = {os.path.expanduser("~"), tempfile.gettempdir(), os.getcwd(), "/home", "/Users", "/tmp", "/var/tmp"}
+    # A working directory of / would match every URL path
+    prefixes = {prefix.rstrip("\\/") for prefix in prefixes if prefix.rstrip("\\/")}
+    patterns = [re.escape(prefix) for prefix in sorted(prefixes, key=len, reverse=True)]
+    return [*patterns, r"[A-Za-z]:[\\/]Users"]
+
+
+# Filesystem paths under those prefixes; other absolute paths (URL paths in access logs) are left alone
+PATH_PATTERN = re.compile(
+    r"(?<![\w.])(?:" + "|".join(_path_prefixes()) + r")(?![^\s'\",;:)\]\\/])[^\s'\",;:)\]]*"
+)
+
+SCRUBBED_LOGGERS = ("", "uvicorn", "uvicorn.access", "uvicorn.error")
+
+_sensitive_names: Set[str] = set()
+_lock = threading.Lock()
+_installed = False
+
+
+def is_protected_mode() -> bool:
+    """Whether protected mode is enabled globally."""
+    return settings.get("protected_mode")
+
+
+class SensitiveDataFilter(logging.Filter):
+    """Rewrites log records to remove file paths and protected filenames."""
+
+    def filter(self, record: logging.LogRecord) -> bool:
+        message = record.getMessage()
+        scrubbed = scrub_text(message)
+        if scrubbed != message:
+            record.msg = scrubbed
+            record.args = ()
+        return True
+
+
+def scrub_text(text: str) -> str:
+    """Replace protected filenames and absolute paths in text."""
+    with _lock:
+        names = sorted(_sensitive_names, key=len, reverse=True)
+    for name in names:
+        text = text.replace(name, SCRUBBED_NAME)
+    return PATH_PATTERN.sub(SCRUBBED_PATH, text)
+
+
+def install_log_scrubber():
+    """Attach the scrubbing filter to the application and server loggers and their handlers (idempotent).
+
+    A logger's filter sees what is logged on it, including records logged
+    before uvicorn replaces its handlers; a handler's filter also sees records
+    propagated from child loggers such as argscape.backend.*.
+    """
+    global _installed
+    with _lock:
+        if _installed:
+            return
+        _installed = True
+
+    log_filter = SensitiveDataFilter()
+    for name in SCRUBBED_LOGGERS:
+        target = logging.getLogger(name)
+        target.addFilter(log_filter)
+        for handler in target.handlers:
+            handler.addFilter(log_filter)
+    logging.getLogger(__name__).info("Protected data handling active: file paths are scrubbed from logs")
+
+
+def register_sensitive_name(name: str):
+    """Scrub a protected filename (and its stem) from all future log output."""
+    with _lock:
+        _sensitive_names.add(name)
+        stem = name.rsplit(".", 1)[0]
+        if len(stem) >= 3:
+            _sensitive_names.add(stem)
+    install_log_scrubber()
@@ -0,0 +1,230 @@
+"""
+Recombination and mutation rate maps.
+Recombination maps are read from HapMap-format files (position, rate in cM/Mb,
+optionally the genetic map position) and mutation rate maps from BEDgraph files
+(per-base, per-generation rates). Both are indexed per contig as step functions
+in per-base, per-generation units, so rates can be drawn as tracks and averaged
+over windows by statistics that need rate normalization.
+"""
+
+import bisect
+import gzip
+import logging
+import threading
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+import numpy as np
+
+logger = logging.getLogger(__name__)
+
+RATE_MAP_KINDS = ("recombination", "mutation")
+
+# HapMap rates are in centimorgans per megabase; 1 cM/Mb is 1e-8 per base per generation
+CM_PER_MB = 1e-8
+
+DEFAULT_NUM_WINDOWS = 100
+MAX_NUM_WINDOWS = 5000
+
+
+class ContigRates:
+    """Piecewise-constant rates on one contig; NaN where the map has no data."""
+
+    def __init__(self, breaks: List[float], rates: List[float]):
+        self.breaks = np.asarray(breaks, dtype=float)
+        self._break_list = self.breaks.tolist()
+        self.rates = np.asarray(rates, dtype=float)
+        known = np.where(np.isnan(self.rates), 0.0, self.rates) * np.diff(self.breaks)
+        self._mass = np.concatenate([[0.0], np.cumsum(known)])
+        self._covered = np.concatenate([[0.0], np.cumsum(np.where(np.isnan(self.rates), 0.0, np.diff(self.breaks)))])
+
+    def _cumulative(self, cumulative: np.ndarray, position: float) -> float:
+        """Integral of a per-interval quantity from the first break to `position`."""
+        i = bisect.bisect_right(self._break_list, position) - 1
+        if i < 0:
+            return 0.0
+        if i >= len(self.rates):
+            return float(cumulative[-1])
+        fraction = (position - self.breaks[i]) / (self.breaks[i + 1] - self.breaks[i])
+        return float(cumulative[i] + fraction * (cumulative[i + 1] - cumulative[i]))
+
+    def rate_at(self, position: float) -> Optional[float]:
+        i = bisect.bisect_right(self._break_list, position) - 1
+        if i < 0 or i >= len(self.rates) or np.isnan(self.rates[i]):
+            return None
+        return float(self.rates[i])
+
+    def mean_rate(self, left: float, right: float) -> Optional[float]:
+        """Mean rate over the part of [left, right) that the map covers."""
+        covered = self._cumulative(self._covered, right) - self._cumulative(self._covered, left)
+        if covered <= 0:
+            return None
+        return (self._cumulative(self._mass, right) - self._cumulative(self._mass, left)) / covered
+
+    @property
+    def span(self) -> List[float]:
+        return [float(self.breaks[0]), float(self.breaks[-1])]
+
+
+class RateMap:
+    """A recombination or mutation rate map, indexed by contig."""
+
+    def __init__(self, kind: str, contigs: Dict[str, ContigRates], name: str):
+        if kind not in RATE_MAP_KINDS:
+            raise ValueError(f"Unknown rate map kind '{kind}'. Expected one of {RATE_MAP_KINDS}")
+        if not contigs:
+            raise ValueError("Rate map contains no intervals")
+        self.kind = kind
+        self.contigs = contigs
+        self.name = name
+
+    @classmethod
+    def from_bytes(cls, kind: str, contents: bytes, name: str) -> "RateMap":
+        if contents[:2] == b"\x1f\x8b":
+            contents = gzip.decompress(contents)
+        text = contents.decode("utf-8")
+        parser = parse_hapmap if kind == "recombination" else parse_bedgraph
+        return cls(kind, parser(text), name)
+
+    @classmethod
+    def from_path(cls, kind: str, path: Path) -> "RateMap":
+        return cls.from_bytes(kind, Path(path).read_bytes(), Path(path).name)
+
+    def contig(self, name: Optional[str] = None) -> ContigRates:
+        """The named contig, or the only/first one when no name is given."""
+        if name is None:
+            return next(iter(self.contigs.values()))
+        if name not in self.contigs:
+            raise ValueError(f"Contig '{name}' is not in the rate map. Available: {sorted(self.contigs)}")
+        return self.contigs[name]
+
+    def window_rates(self, start: float, end: float, num_windows: int = DEFAULT_NUM_WINDOWS, contig: Optional[str] = None) -> Dict[str, Any]:
+        """Mean rate in each of `num_windows` equal windows over [start, end)."""
+        if end <= start:
+            raise ValueError("Window end must be after its start")
+        if not 1 <= num_windows <= MAX_NUM_WINDOWS:
+            raise ValueError(f"num_windows must be between 1 and {MAX_NUM_WINDOWS}")
+        rates = self.contig(contig)
+        edges = np.linspace(start, end, num_windows + 1)
+        return {
+            "kind": self.kind,
+            "contig": contig or next(iter(self.contigs)),
+            "units": "per base per generation",
+            "windows": [
+                {"left": float(left), "right": float(right), "rate": rates.mean_rate(left, right)}
+                for left, right in zip(edges[:-1], edges[1:])
+            ],
+        }
+
+    def summary(self) -> Dict[str, Any]:
+        return {
+            "kind": self.kind,
+            "name": self.name,
+            "contigs": {
+                name: {"span": rates.span, "num_intervals": len(rates.rates), "mean_rate": rates.mean_rate(*rates.span)}
+                for name, rates in self.contigs.items()
+            },
+        }
+
+
+def _is_number(text: str) -> bool:
+    try:
+        float(text)
+        return True
+    except ValueError:
+        return False
+
+
+def parse_hapmap(text: str) -> Dict[str, ContigRates]:
+    """HapMap recombination map: chromosome, position, rate (cM/Mb)[, map position (cM)].
+
+    Each row's rate applies up to the next row's position; the region before
+    the first row is unknown, and the last row ends the map.
+    """
+    rows: Dict[str, List[tuple]] = {}
+    for line_number, line in enumerate(text.splitlines(), 1):
+        fields = line.split()
+        if not fields or line.startswith("#"):
+            continue
+        if len(fields) < 3:
+            raise ValueError(f"Expected at least 3 columns on line {line_number}")
+        if not _is_number(fields[1]):
+            if line_number == 1 or not rows:
+                continue  # Header
+            raise ValueError(f"Invalid position on line {line_number}")
+        rows.setdefault(fields[0], []).append((float(fields[1]), float(fields[2]) * CM_PER_MB))
+
+    contigs = {}
+    for contig, entries in rows.items():
+        entries.sort()
+        positions = [p for p, _ in entries]
+        if len(set(positions)) != len(positions):
+            raise ValueError(f"Duplicate positions in the map for {contig}")
+        if len(entries) < 2:
+            raise ValueError(f"The map for {contig} needs at least two positions")
+        breaks = [0.0] + positions if positions[0] > 0 else positions
+        rates = ([np.nan] if positions[0] > 0 else []) + [r for _, r in entries[:-1]]
+        contigs[contig] = ContigRates(breaks, rates)
+    return contigs
+
+
+def parse_bedgraph(text: str) -> Dict[str, ContigRates]:
+    """BEDgraph of per-base, per-generation rates: chromosome, start, end, rate (0-based, half-open)."""
+    rows: Dict[str, List[tuple]] = {}
+    for line_number, line in enumerate(text.splitlines(), 1):
+        fields = line.split()
+        if not fields or fields[0] in ("track", "browser") or line.startswith("#"):
+            continue
+        if len(fields) < 4:
+            raise ValueError(f"Expected 4 columns on line {line_number}")
+        try:
+            start, end, rate = float(fields[1]), float(fields[2]), float(fields[3])
+        except ValueError:
+            raise ValueError(f"Invalid interval on line {line_number}")
+        if end <= start or rate < 0:
+            raise ValueError(f"Invalid interval or negative rate on line {line_number}")
+        rows.setdefault(fields[0], []).append((start, end, rate))
+
+    contigs = {}
+    for contig, intervals in rows.items():
+        intervals.sort()
+        breaks: List[float] = [0.0]
+        rates: List[float] = []
+        for start, end, rate in intervals:
+            if start < breaks[-1]:
+                raise ValueError(f"Overlapping intervals in the map for {contig} at {start:g}")
+            if start > breaks[-1]:
+                breaks.append(start)
+                rates.append(np.nan)  # Gap
+            breaks.append(end)
+            rates.append(rate)
+        contigs[contig] = ContigRates(breaks, rates)
+    return contigs
+
+
+class RateMapRegistry:
+    """Rate maps loaded by each session, one per kind."""
+
+    def __init__(self):
+        self._maps: Dict[str, Dict[str, RateMap]] = {}
+        self._lock = threading.Lock()
+
+    def set(self, session_id: str, rate_map: RateMap):
+        with self._lock:
+            self._maps.setdefault(session_id, {})[rate_map.kind] = rate_map
+        logger.info(f"Loaded {rate_map.kind} map {rate_map.name} ({len(rate_map.contigs)} contigs) for session {session_id}")
+
+    def get(self, session_id: str, kind: str) -> Optional[RateMap]:
+        with self._lock:
+            return self._maps.get(session_id, {}).get(kind)
+
+    def get_all(self, session_id: str) -> Dict[str, RateMap]:
+        with self._lock:
+            return dict(self._maps.get(session_id, {}))
+
+    def remove(self, session_id: str, kind: str) -> bool:
+        with self._lock:
+            return self._maps.get(session_id, {}).pop(kind, None) is not None
+
+
+rate_map_registry = RateMapRegistry()
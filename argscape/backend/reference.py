@@ -0,0 +1,318 @@
+"""
+Reference genome support: indexed FASTA access and GFF3 coding annotations.
+Sequence for the current genomic window is read directly from the FASTA using
+its .fai index (built on load if missing), and mutation positions are mapped to
+reference bases and, for coding sites, codons and amino acid changes.
+"""
+
+import logging
+import threading
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+from urllib.parse import unquote
+
+import tskit
+
+logger = logging.getLogger(__name__)
+
+# Largest window returned by a single sequence request
+MAX_FETCH_BASES = 1_000_000
+
+COMPLEMENT = str.maketrans("ACGTNacgtn", "TGCANtgcan")
+
+BASES = "TCAG"
+AMINO_ACIDS = "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG"
+CODON_TABLE = {
+    a + b + c: AMINO_ACIDS[16 * i + 4 * j + k]
+    for i, a in enumerate(BASES) for j, b in enumerate(BASES) for k, c in enumerate(BASES)
+}
+
+
+@dataclass
+class FaiEntry:
+    length: int
+    offset: int
+    line_bases: int
+    line_width: int
+
+
+@dataclass
+class CodingTranscript:
+    transcript_id: str
+    seqid: str
+    strand: str
+    gene: Optional[str] = None
+    # CDS segments as 0-based half-open (start, end, phase), sorted by start
+    segments: List[Tuple[int, int, int]] = field(default_factory=list)
+
+
+def read_fai(text: str) -> Dict[str, FaiEntry]:
+    """Parse a samtools .fai index."""
+    index = {}
+    for line in text.splitlines():
+        fields = line.split("\t")
+        if len(fields) < 5:
+            continue
+        index[fields[0]] = FaiEntry(int(fields[1]), int(fields[2]), int(fields[3]), int(fields[4]))
+    if not index:
+        raise ValueError("FASTA index (.fai) is empty or malformed")
+    return index
+
+
+def build_fai(fasta_path: Path) -> Dict[str, FaiEntry]:
+    """Index an uncompressed FASTA file, as `samtools faidx` would."""
+    with open(fasta_path, "rb") as f:
+        if f.read(2) == b"\x1f\x8b":
+            raise ValueError("Compressed FASTA files need an existing .fai index and are not supported")
+        f.seek(0)
+
+        index: Dict[str, FaiEntry] = {}
+        name = None
+        offset = 0
+        for line in iter(f.readline, b""):
+            if line.startswith(b">"):
+                name = line[1:].split()[0].decode()
+                index[name] = FaiEntry(0, offset + len(line), 0, 0)
+            elif name is not None and line.strip():
+                entry = index[name]
+                if entry.line_bases == 0:
+                    entry.line_bases = len(line.rstrip(b"\r\n"))
+                    entry.line_width = len(line)
+                entry.length += len(line.rstrip(b"\r\n"))
+            offset += len(line)
+    if not index:
+        raise ValueError("No sequences found in FASTA file")
+    return index
+
+
+def _parse_gff_attributes(text: str) -> Dict[str, str]:
+    attributes = {}
+    for item in text.strip().split(";"):
+        if "=" in item:
+            key, _, value = item.partition("=")
+            attributes[key.strip()] = unquote(value.strip())
+    return attributes
+
+
+def read_gff_cds(path: Path) -> Dict[str, CodingTranscript]:
+    """Read CDS features from a GFF3 file, grouped by parent transcript."""
+    transcripts: Dict[str, CodingTranscript] = {}
+    genes: Dict[str, str] = {}
+    with open(path, "r") as f:
+        for line in f:
+            if line.startswith("#") or not line.strip():
+                if line.startswith("##FASTA"):
+                    break
+                continue
+            fields = line.rstrip("\n").split("\t")
+            if len(fields) < 9:
+                continue
+            seqid, _, feature, start, end, _, strand, phase, attribute_text = fields[:9]
+            attributes = _parse_gff_attributes(attribute_text)
+            if feature in ("mRNA", "transcript") and "ID" in attributes:
+                genes[attributes["ID"]] = attributes.get("Parent", attributes.get("gene", ""))
+            if feature != "CDS":
+                continue
+            for parent in attributes.get("Parent", attributes.get("ID", "")).split(","):
+                transcript = transcripts.setdefault(parent, CodingTranscript(parent, seqid, strand))
+                transcript.segments.append((int(start) - 1, int(end), int(phase) if phase.isdigit() else 0))
+
+    for transcript in transcripts.values():
+        transcript.segments.sort()
+        transcript.gene = genes.get(transcript.transcript_id) or None
+    return transcripts
+
+
+class ReferenceGenome:
+    """An indexed FASTA file with optional coding annotations."""
+
+    def __init__(self, fasta_path: Path, fai_path: Optional[Path] = None, gff_path: Optional[Path] = None):
+        self.fasta_path = Path(fasta_path)
+        if not self.fasta_path.is_file():
+            raise ValueError(f"FASTA file not found: {self.fasta_path.name}")
+
+        fai_path = Path(fai_path) if fai_path else Path(f"{self.fasta_path}.fai")
+        if fai_path.is_file():
+            self.index = read_fai(fai_path.read_text())
+            self.index_source = "fai"
+        else:
+            self.index = build_fai(self.fasta_path)
+            self.index_source = "built"
+
+        self.gff_path = Path(gff_path) if gff_path else None
+        self.transcripts = read_gff_cds(self.gff_path) if self.gff_path else {}
+        self._lock = threading.Lock()
+
+    def summary(self) -> Dict[str, Any]:
+        return {
+            "fasta": self.fasta_path.name,
+            "index_source": self.index_source,
+            "contigs": [{"name": name, "length": entry.length} for name, entry in self.index.items()],
+            "annotation": self.gff_path.name if self.gff_path else None,
+            "num_coding_transcripts": len(self.transcripts),
+        }
+
+    def default_contig(self) -> str:
+        return next(iter(self.index))
+
+    def fetch(self, contig: str, start: int, end: int) -> str:
+        """Reference sequence for the 0-based half-open interval [start, end)."""
+        entry = self.index.get(contig)
+        if entry is None:
+            raise ValueError(f"Contig '{contig}' is not in the reference")
+        start, end = max(0, start), min(end, entry.length)
+        if start >= end:
+            return ""
+
+        def byte_offset(pos: int) -> int:
+            return entry.offset + (pos // entry.line_bases) * entry.line_width + pos % entry.line_bases
+
+        first, last = byte_offset(start), byte_offset(end - 1) + 1
+        with self._lock, open(self.fasta_path, "rb") as f:
+            f.seek(first)
+            raw = f.read(last - first)
+        return raw.replace(b"\n", b"").replace(b"\r", b"").decode("ascii").upper()
+
+    def coding_context(self, contig: str, position: int, derived_state: Optional[str] = None) -> List[Dict[str, Any]]:
+        """Codon context of a 0-based position in every transcript whose CDS covers it."""
+        contexts = []
+        for transcript in self.transcripts.values():
+            if transcript.seqid != contig:
+                continue
+            context = self._codon_at(transcript, position, derived_state)
+            if context is not None:
+                contexts.append(context)
+        return contexts
+
+    def _codon_at(self, transcript: CodingTranscript, position: int, derived_state: Optional[str]) -> Optional[Dict[str, Any]]:
+        segments = transcript.segments if transcript.strand != "-" else transcript.segments[::-1]
+        if not any(start <= position < end for start, end, _ in segments):
+            return None
+
+        # Genomic positions of coding bases in transcript order, skipping the initial phase
+        coding_positions: List[int] = []
+        for start, end, _ in segments:
+            coding_positions.extend(range(start, end) if transcript.strand != "-" else range(end - 1, start - 1, -1))
+        coding_positions = coding_positions[segments[0][2]:]
+        if position not in coding_positions:
+            return None
+
+        coding_index = coding_positions.index(position)
+        codon_start = coding_index - coding_index % 3
+        codon_positions = coding_positions[codon_start:codon_start + 3]
+        if len(codon_positions) < 3:
+            return None
+
+        bases = [self.fetch(transcript.seqid, p, p + 1) for p in codon_positions]
+        if transcript.strand == "-":
+            bases = [b.translate(COMPLEMENT) for b in bases]
+        ref_codon = "".join(bases)
+
+        context = {
+            "transcript": transcript.transcript_id,
+            "gene": transcript.gene,
+            "strand": transcript.strand,
+            "codon_number": codon_start // 3 + 1,
+            "codon_position": coding_index % 3 + 1,
+            "ref_codon": ref_codon,
+            "ref_amino_acid": CODON_TABLE.get(ref_codon),
+        }
+        if derived_state and len(derived_state) == 1:
+            alt = derived_state.upper()
+            if transcript.strand == "-":
+                alt = alt.translate(COMPLEMENT)
+            alt_codon = ref_codon[:coding_index % 3] + alt + ref_codon[coding_index % 3 + 1:]
+            alt_amino_acid = CODON_TABLE.get(alt_codon)
+            context.update({
+                "alt_codon": alt_codon,
+                "alt_amino_acid": alt_amino_acid,
+                "synonymous": alt_amino_acid == context["ref_amino_acid"] if alt_amino_acid else None,
+            })
+        return context
+
+
+def annotate_mutations(
+    ts: tskit.TreeSequence,
+    reference: ReferenceGenome,
+    contig: Optional[str] = None,
+    start: float = 0,
+    end: Optional[float] = None,
+    offset: int = 0
+) -> Dict[str, Any]:
+    """Map mutations in a genomic window to reference and derived alleles.
+
+    Args:
+        ts: Tree sequence
+        reference: Loaded reference genome
+        contig: Reference contig matching the tree sequence (default: first contig)
+        start, end: Window in tree sequence coordinates
+        offset: Added to tree sequence positions to get 0-based reference positions
+    """
+    contig = contig or reference.default_contig()
+    end = ts.sequence_length if end is None else end
+    if end - start > MAX_FETCH_BASES:
+        raise ValueError(f"Window is larger than {MAX_FETCH_BASES} bases")
+
+    sites = []
+    num_mismatches = 0
+    for site in ts.sites():
+        if not start <= site.position < end:
+            continue
+        ref_position = int(site.position) + offset
+        ref_base = reference.fetch(contig, ref_position, ref_position + 1) or None
+        matches = ref_base is not None and site.ancestral_state.upper() == ref_base
+        if ref_base is not None and len(site.ancestral_state) == 1 and not matches:
+            num_mismatches += 1
+        sites.append({
+            "site_id": site.id,
+            "position": site.position,
+            "reference_position": ref_position,
+            "reference_base": ref_base,
+            "ancestral_state": site.ancestral_state,
+            "ancestral_matches_reference": matches,
+            "mutations": [
+                {
+                    "mutation_id": mutation.id,
+                    "node": mutation.node,
+                    "derived_state": mutation.derived_state,
+                    "coding": reference.coding_context(contig, ref_position, mutation.derived_state),
+                }
+                for mutation in site.mutations
+            ],
+        })
+
+    return {
+        "contig": contig,
+        "window": [start, end],
+        "offset": offset,
+        "num_sites": len(sites),
+        "num_ancestral_mismatches": num_mismatches,
+        "sites": sites,
+    }
+
+
+class ReferenceRegistry:
+    """Reference genome loaded by each session."""
+
+    def __init__(self):
+        self._references: Dict[str, ReferenceGenome] = {}
+        self._lock = threading.Lock()
+
+    def load(self, session_id: str, fasta_path: Path, fai_path: Optional[Path] = None, gff_path: Optional[Path] = None) -> ReferenceGenome:
+        reference = ReferenceGenome(fasta_path, fai_path, gff_path)
+        with self._lock:
+            self._references[session_id] = reference
+        logger.info(f"Loaded reference {reference.fasta_path.name} with {len(reference.index)} contigs for session {session_id}")
+        return reference
+
+    def get(self, session_id: str) -> Optional[ReferenceGenome]:
+        with self._lock:
+            return self._references.get(session_id)
+
+    def remove(self, session_id: str) -> bool:
+        with self._lock:
+            return self._references.pop(session_id, None) is not None
+
+
+reference_registry = ReferenceRegistry()
@@ -0,0 +1,309 @@
+"""
+Opening tree sequences straight from S3, GCS or HTTPS.
+Remote files are read with HTTP range requests in fixed-size blocks, fetched
+in parallel and kept on disk until the transfer completes, so an interrupted
+transfer resumes where it stopped. Finished files go into the shared download
+cache, so reopening the same file (same URL and ETag) needs no transfer, and
+without network access the last cached copy of a URL is used. tskit loads
+whole files, so every block is still fetched once, but nobody has to copy a
+10 GB cluster output to their laptop by hand first.
+
+Credentials are never stored, and only those given with the request are
+used: S3 requests are signed (SigV4) with its keys, GCS and HTTPS requests
+send its bearer token. The server's own AWS keys, profiles and tokens are
+never picked up, so a request can't borrow them. Without credentials, public
+objects still work.
+
+Remote files must be on the public internet: every request, and every
+redirect, is refused if its host resolves to a private, loopback,
+link-local or otherwise non-global address (cloud metadata services,
+services on the server's network).
+"""
+
+import hashlib
+import hmac
+import ipaddress
+import logging
+import os
+import shutil
+import socket
+import time
+import urllib.error
+import urllib.parse
+import urllib.request
+from concurrent.futures import ThreadPoolExecutor
+from pathlib import Path
+from typing import Any, Callable, Dict, Optional
+
+from argscape.backend.download_cache import download_cache
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.network import NetworkDisabledError, http_open
+from argscape.backend.settings import get_cache_dir
+
+logger = logging.getLogger(__name__)
+
+REMOTE_SCHEMES = ("s3", "gs", "https", "http")
+BLOCK_SIZE = 8 * 1024 * 1024
+MAX_PARALLEL_BLOCKS = 4
+MAX_BLOCK_ATTEMPTS = 3
+BLOCK_CACHE_DIRNAME = "remote_blocks"
+# Oldest unfinished transfers are dropped once their blocks grow past this
+BLOCK_CACHE_LIMIT_BYTES = 4 * 1024 ** 3
+
+GCS_ENDPOINT = "https://storage.googleapis.com"
+DEFAULT_S3_REGION = "us-east-1"
+
+
+def _sha256(data: bytes) -> str:
+    return hashlib.sha256(data).hexdigest()
+
+
+def _hmac(key: bytes, message: str) -> bytes:
+    return hmac.new(key, message.encode("utf-8"), hashlib.sha256).digest()
+
+
+def find_aws_credentials(credentials: Optional[Dict[str, str]] = None) -> Optional[Dict[str, str]]:
+    """The request's explicit S3 keys, if both halves were given."""
+    credentials = credentials or {}
+    if credentials.get("access_key_id") and credentials.get("secret_access_key"):
+        return {key: credentials.get(key) for key in ("access_key_id", "secret_access_key", "session_token")}
+    return None
+
+
+def check_public_host(url: str):
+    """Raise PermissionError unless every address the URL's host resolves to is globally routable."""
+    host = urllib.parse.urlsplit(url).hostname
+    if not host:
+        raise ValueError(f"No host in {url}")
+    try:
+        addresses = {info[4][0] for info in socket.getaddrinfo(host, None)}
+    except socket.gaierror as e:
+        raise urllib.error.URLError(f"Could not resolve {host}: {e}") from None
+    for text in addresses:
+        address = ipaddress.ip_address(text.split("%")[0])
+        if address.version == 6 and address.ipv4_mapped is not None:
+            address = address.ipv4_mapped
+        if not address.is_global:
+            raise PermissionError(f"{host} resolves to a non-public address ({address}); remote files must be on the internet")
+
+
+class _PublicRedirectHandler(urllib.request.HTTPRedirectHandler):
+    """Follows redirects only to public hosts."""
+
+    def redirect_request(self, req, fp, code, msg, headers, newurl):
+        check_public_host(newurl)
+        return super().redirect_request(req, fp, code, msg, headers, newurl)
+
+
+def sign_s3_request(
+    method: str,
+    url: str,
+    region: str,
+    credentials: Dict[str, str],
+    headers: Optional[Dict[str, str]] = None
+) -> Dict[str, str]:
+    """Headers for an AWS Signature Version 4 signed S3 request (range headers stay unsigned)."""
+    parts = urllib.parse.urlsplit(url)
+    amz_date = time.strftime("%Y%m%dT%H%M%SZ", time.gmtime())
+    date = amz_date[:8]
+    payload_hash = "UNSIGNED-PAYLOAD"
+    signed = {"host": parts.netloc, "x-amz-content-sha256": payload_hash, "x-amz-date": amz_date}
+    if credentials.get("session_token"):
+        signed["x-amz-security-token"] = credentials["session_token"]
+
+    query = urllib.parse.parse_qsl(parts.query, keep_blank_values=True)
+    canonical_query = "&".join(
+        f"{urllib.parse.quote(k, safe='-_.~')}={urllib.parse.quote(v, safe='-_.~')}" for k, v in sorted(query)
+    )
+    signed_headers = ";".join(sorted(signed))
+    canonical_request = "\n".join([
+        method,
+        parts.path or "/",
+        canonical_query,
+        "".join(f"{k}:{signed[k].strip()}\n" for k in sorted(signed)),
+        signed_headers,
+        payload_hash,
+    ])
+    scope = f"{date}/{region}/s3/aws4_request"
+    string_to_sign = "\n".join(["AWS4-HMAC-SHA256", amz_date, scope, _sha256(canonical_request.encode())])
+    key = _hmac(("AWS4" + credentials["secret_access_key"]).encode(), date)
+    for part in (region, "s3", "aws4_request"):
+        key = _hmac(key, part)
+    signature = hmac.new(key, string_to_sign.encode(), hashlib.sha256).hexdigest()
+
+    return {
+        **(headers or {}),
+        **{k: v for k, v in signed.items() if k != "host"},
+        "Authorization": (
+            f"AWS4-HMAC-SHA256 Credential={credentials['access_key_id']}/{scope}, "
+            f"SignedHeaders={signed_headers}, Signature={signature}"
+        ),
+    }
+
+
+class RemoteFile:
+    """A remote object read by byte ranges through the block cache."""
+
+    def __init__(self, url: str, credentials: Optional[Dict[str, str]] = None):
+        parts = urllib.parse.urlsplit(url)
+        if parts.scheme not in REMOTE_SCHEMES:
+            raise ValueError(f"Unsupported URL scheme '{parts.scheme}'. Expected one of {REMOTE_SCHEMES}")
+        if not parts.netloc or not parts.path.strip("/"):
+            raise ValueError("Remote URLs must name a bucket or host and a file")
+        self.url = url
+        self.scheme = parts.scheme
+        self.name = Path(urllib.parse.unquote(parts.path)).name
+        credentials = credentials or {}
+        self._s3_credentials = None
+        self._token = credentials.get("token")
+        key = urllib.parse.quote(urllib.parse.unquote(parts.path.lstrip("/")), safe="/~")
+
+        if self.scheme == "s3":
+            self.region = credentials.get("region") or DEFAULT_S3_REGION
+            endpoint = credentials.get("endpoint_url")
+            # Custom endpoints (MinIO, Ceph and other cluster stores) use path-style addressing
+            self.http_url = (
+                f"{endpoint.rstrip('/')}/{parts.netloc}/{key}" if endpoint
+                else f"https://{parts.netloc}.s3.{self.region}.amazonaws.com/{key}"
+            )
+            self._s3_credentials = find_aws_credentials(credentials)
+        elif self.scheme == "gs":
+            self.http_url = f"{GCS_ENDPOINT}/{parts.netloc}/{key}"
+        else:
+            self.http_url = url
+        self.size: Optional[int] = None
+        self.etag: Optional[str] = None
+
+    def _headers(self, method: str, extra: Optional[Dict[str, str]] = None) -> Dict[str, str]:
+        headers = dict(extra or {})
+        if self._s3_credentials:
+            return sign_s3_request(method, self.http_url, self.region, self._s3_credentials, headers)
+        if self._token:
+            headers["Authorization"] = f"Bearer {self._token}"
+        return headers
+
+    def _open(self, headers: Dict[str, str], timeout: float = 60):
+        # Resolved again for every request, so a host can't switch to a private address mid-transfer
+        check_public_host(self.http_url)
+        return http_open(
+            self.http_url, f"remote file ({self.scheme})", headers=self._headers("GET", headers),
+            timeout=timeout, handlers=[_PublicRedirectHandler()]
+        )
+
+    def probe(self) -> Dict[str, Any]:
+        """Size, ETag and range support, from a one-byte range request."""
+        try:
+            with self._open({"Range": "bytes=0-0"}) as response:
+                content_range = response.headers.get("Content-Range", "")
+                supports_ranges = response.status == 206 and "/" in content_range
+                self.size = int(content_range.rsplit("/", 1)[1]) if supports_ranges else int(response.headers.get("Content-Length", 0)) or None
+                self.etag = (response.headers.get("ETag") or "").strip('"') or None
+        except urllib.error.HTTPError as e:
+            if e.code in (401, 403):
+                raise PermissionError(f"Access to {self.url} was denied ({e.code}); check the credentials") from None
+            if e.code == 404:
+                raise FileNotFoundError(f"{self.url} does not exist") from None
+            raise
+        if not supports_ranges:
+            logger.info(f"{self.url} does not support range requests; it will be fetched in one piece")
+        return {
+            "url": self.url,
+            "name": self.name,
+            "size": self.size,
+            "etag": self.etag,
+            "supports_ranges": supports_ranges,
+            "signed": self._s3_credentials is not None or self._token is not None,
+        }
+
+    def _cache_dir(self) -> Path:
+        identity = f"{self.url}\n{self.etag or ''}\n{self.size or ''}"
+        return get_cache_dir() / BLOCK_CACHE_DIRNAME / _sha256(identity.encode())[:32]
+
+    def _fetch_block(self, directory: Path, index: int, cancel_token: Optional[CancellationToken]) -> int:
+        path = directory / f"{index:06d}.block"
+        start = index * BLOCK_SIZE
+        end = min(start + BLOCK_SIZE, self.size) - 1
+        if path.exists() and path.stat().st_size == end - start + 1:
+            return 0
+        for attempt in range(1, MAX_BLOCK_ATTEMPTS + 1):
+            check_cancelled(cancel_token)
+            try:
+                with self._open({"Range": f"bytes={start}-{end}"}) as response:
+                    data = response.read()
+                if len(data) != end - start + 1:
+                    raise OSError(f"Short read for bytes {start}-{end}")
+                temp_path = path.with_suffix(".tmp")
+                temp_path.write_bytes(data)
+                temp_path.replace(path)
+                return len(data)
+            except (urllib.error.URLError, OSError) as e:
+                if attempt == MAX_BLOCK_ATTEMPTS:
+                    raise
+                logger.warning(f"Retrying block {index} of {self.name}: {e}")
+        return 0
+
+    def read_all(
+        self,
+        progress: Optional[Callable[[float], None]] = None,
+        cancel_token: Optional[CancellationToken] = None
+    ) -> bytes:
+        """The whole file, from the download cache or by fetching the blocks not yet on disk."""
+        try:
+            info = self.probe()
+        except (urllib.error.URLError, NetworkDisabledError) as e:
+            cached = download_cache.lookup(f"remote:{self.url}")
+            if cached is None:
+                raise
+            logger.info(f"Using the cached copy of {self.url} ({e})")
+            return cached.read_bytes()
+        keys = [f"remote:{self.url}", f"remote:{self.url}#{self.etag or ''}:{self.size or ''}"]
+        if self.etag:
+            cached = download_cache.lookup(keys[1])
+            if cached is not None:
+                return cached.read_bytes()
+
+        if not info["supports_ranges"] or not self.size:
+            with self._open({}) as response:
+                contents = response.read()
+            download_cache.add_bytes(contents, self.name, keys, source=self.url)
+            return contents
+
+        directory = self._cache_dir()
+        directory.mkdir(parents=True, exist_ok=True)
+        os.utime(directory)
+        num_blocks = (self.size + BLOCK_SIZE - 1) // BLOCK_SIZE
+        done = 0
+        with ThreadPoolExecutor(max_workers=MAX_PARALLEL_BLOCKS, thread_name_prefix="argscape-remote") as executor:
+            futures = [executor.submit(self._fetch_block, directory, i, cancel_token) for i in range(num_blocks)]
+            for future in futures:
+                future.result()
+                done += 1
+                if progress:
+                    progress(done / num_blocks)
+        contents = b"".join((directory / f"{i:06d}.block").read_bytes() for i in range(num_blocks))
+        download_cache.add_bytes(contents, self.name, keys, source=self.url)
+        shutil.rmtree(directory, ignore_errors=True)
+        prune_block_cache()
+        return contents
+
+
+def prune_block_cache(limit: int = BLOCK_CACHE_LIMIT_BYTES, keep: Optional[Path] = None) -> int:
+    """Drop the least recently started unfinished transfers past `limit` bytes; returns bytes freed."""
+    root = get_cache_dir() / BLOCK_CACHE_DIRNAME
+    if not root.is_dir():
+        return 0
+    entries = []
+    for directory in root.iterdir():
+        if directory.is_dir():
+            size = sum(p.stat().st_size for p in directory.iterdir() if p.is_file())
+            entries.append((directory.stat().st_mtime, directory, size))
+    total = sum(size for _, _, size in entries)
+    freed = 0
+    for _, directory, size in sorted(entries, key=lambda entry: entry[0]):
+        if total - freed <= limit:
+            break
+        if directory == keep:
+            continue
+        shutil.rmtree(directory, ignore_errors=True)
+        freed += size
+    return freed
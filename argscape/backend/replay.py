@@ -0,0 +1,164 @@
+"""
+Session replay across backend restarts.
+Each file that can be rebuilt without its contents — loaded from a local path,
+simulated, or derived from another file by a deterministic operation — gets a
+small recipe in the config directory. Session files live in the temp
+directory, so after a reboot, a temp cleanup or a quota eviction the recipes
+rebuild what is missing, at startup or the first time a file is asked for.
+Recipes never hold genetic data, and protected files get none.
+"""
+
+import json
+import logging
+import threading
+import time
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional
+
+import tskit
+
+from argscape.backend.settings import get_config_dir
+
+logger = logging.getLogger(__name__)
+
+REPLAY_FILENAME = "session_replay.json"
+
+# Recipe kinds
+RECIPE_PATH = "path"  # {"path", "preprocess"}
+RECIPE_SIMULATION = "simulation"  # {"params"}
+RECIPE_SAMPLE = "sample"  # {} (the tour's fixed example)
+RECIPE_FIXTURE = "fixture"  # {"name"} (a synthetic test ARG)
+RECIPE_DERIVED = "derived"  # {"parent", "operation", "params"}
+
+# kind -> function(recipe, parent tree sequence or None) -> TreeSequence
+Replayer = Callable[[Dict[str, Any], Optional[tskit.TreeSequence]], tskit.TreeSequence]
+
+
+class SessionReplay:
+    """Recipes per session, persisted as JSON, and the replayers that use them."""
+
+    def __init__(self, path: Optional[Path] = None):
+        self.path = path or get_config_dir() / REPLAY_FILENAME
+        self._lock = threading.RLock()
+        self._sessions: Dict[str, Dict[str, Any]] = {}
+        self._replayers: Dict[str, Replayer] = {}
+        self._storage = None
+        self._building = set()  # (session_id, filename) being rebuilt, to stop recipe cycles
+        self.last_report: Optional[Dict[str, Any]] = None
+        self._load()
+
+    def _load(self):
+        if not self.path.exists():
+            return
+        try:
+            with open(self.path, "r") as f:
+                self._sessions = json.load(f)
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable session replay file {self.path}: {e}")
+
+    def _save(self):
+        try:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            with open(self.path, "w") as f:
+                json.dump(self._sessions, f, indent=2)
+        except Exception as e:
+            logger.warning(f"Failed to save session replay recipes to {self.path}: {e}")
+
+    def attach(self, storage):
+        """Rebuild files for `storage` when they are missing."""
+        self._storage = storage
+        storage.missing_file_handler = self.rebuild
+        storage.file_deleted_listener = self.forget
+
+    def register_replayer(self, kind: str, replayer: Replayer):
+        self._replayers[kind] = replayer
+
+    def record(self, session_id: str, filename: str, kind: str, **recipe):
+        """Remember how `filename` was made. Skipped for protected files."""
+        if self._storage is not None and self._storage.is_file_protected(session_id, filename):
+            return
+        if kind == RECIPE_DERIVED and self._storage is not None and self._storage.is_file_protected(session_id, recipe["parent"]):
+            return
+        session = self._storage.sessions.get(session_id) if self._storage is not None else None
+        with self._lock:
+            entry = self._sessions.setdefault(session_id, {"client_ip": None, "files": {}})
+            if session is not None:
+                entry["client_ip"] = session.client_ip
+            entry["files"][filename] = {"kind": kind, "recorded_at": time.time(), **recipe}
+            self._save()
+
+    def forget(self, session_id: str, filename: str):
+        with self._lock:
+            entry = self._sessions.get(session_id)
+            if entry and entry["files"].pop(filename, None) is not None:
+                self._save()
+
+    def get_recipe(self, session_id: str, filename: str) -> Optional[Dict[str, Any]]:
+        with self._lock:
+            return self._sessions.get(session_id, {}).get("files", {}).get(filename)
+
+    def rebuild(self, session_id: str, filename: str) -> Optional[tskit.TreeSequence]:
+        """Rebuild a missing file (and any missing parents) from its recipe and store it again."""
+        recipe = self.get_recipe(session_id, filename)
+        key = (session_id, filename)
+        if recipe is None or key in self._building:
+            return None
+        self._building.add(key)
+        try:
+            return self._rebuild(session_id, filename, recipe)
+        finally:
+            self._building.discard(key)
+
+    def _rebuild(self, session_id: str, filename: str, recipe: Dict[str, Any]) -> Optional[tskit.TreeSequence]:
+        replayer = self._replayers.get(recipe["kind"])
+        if replayer is None:
+            logger.warning(f"No replayer for {recipe['kind']} recipes; cannot rebuild {filename}")
+            return None
+
+        parent_ts = None
+        if recipe["kind"] == RECIPE_DERIVED:
+            parent_ts = self._storage.get_tree_sequence(session_id, recipe["parent"])
+            if parent_ts is None:
+                return None
+
+        logger.info(f"Rebuilding {filename} from its {recipe['kind']} recipe")
+        ts = replayer(recipe, parent_ts)
+        derived_from = recipe["parent"] if recipe["kind"] == RECIPE_DERIVED else None
+        self._storage.store_tree_sequence(session_id, filename, ts, derived_from=derived_from)
+        return ts
+
+    def replay_all(self) -> Dict[str, Any]:
+        """Rebuild originals (not derived files, which are rebuilt on demand) missing after a restart."""
+        restored: List[str] = []
+        failed: Dict[str, str] = {}
+        with self._lock:
+            sessions = {sid: dict(entry) for sid, entry in self._sessions.items()}
+        for session_id, entry in sessions.items():
+            if entry.get("client_ip") is None:
+                continue
+            self._storage.get_or_create_session(entry["client_ip"])
+            present = set(self._storage.get_file_list(session_id))
+            for filename, recipe in entry["files"].items():
+                if filename in present or recipe["kind"] == RECIPE_DERIVED:
+                    continue
+                try:
+                    if self.rebuild(session_id, filename) is not None:
+                        restored.append(filename)
+                except Exception as e:
+                    failed[filename] = str(e)
+                    logger.warning(f"Could not rebuild {filename}: {e}")
+        self.last_report = {"finished_at": time.time(), "restored": restored, "failed": failed}
+        if restored or failed:
+            logger.info(f"Session replay restored {len(restored)} file(s), {len(failed)} failed")
+        return self.last_report
+
+    def status(self, session_id: str) -> Dict[str, Any]:
+        with self._lock:
+            files = dict(self._sessions.get(session_id, {}).get("files", {}))
+        return {
+            "recipes": {name: {"kind": recipe["kind"], "parent": recipe.get("parent")} for name, recipe in files.items()},
+            "last_replay": self.last_report,
+        }
+
+
+session_replay = SessionReplay()
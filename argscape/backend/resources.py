@@ -0,0 +1,201 @@
+"""
+CPU and memory resource controls for ARGscape.
+Sizes the native thread pools (BLAS/OpenMP) and our own worker pools, lowers
+the process priority, implements a low-power mode that trades speed for
+battery life, and enforces an optional memory ceiling so a runaway job can't
+freeze the machine.
+"""
+
+import logging
+import os
+import sys
+import threading
+import time
+from typing import Callable, Dict, Optional
+
+logger = logging.getLogger(__name__)
+
+# Environment variables read by the BLAS/OpenMP runtimes at import time
+THREAD_ENV_VARS = (
+    "OMP_NUM_THREADS",
+    "OPENBLAS_NUM_THREADS",
+    "MKL_NUM_THREADS",
+    "NUMEXPR_NUM_THREADS",
+    "VECLIB_MAXIMUM_THREADS",
+)
+LOW_POWER_NICE_INCREMENT = 10
+
+# Priority level -> (nice increment on POSIX, Windows priority class)
+PRIORITY_LEVELS = {
+    "normal": (0, 0x00000020),  # NORMAL_PRIORITY_CLASS
+    "low": (LOW_POWER_NICE_INCREMENT, 0x00004000),  # BELOW_NORMAL_PRIORITY_CLASS
+    "idle": (19, 0x00000040),  # IDLE_PRIORITY_CLASS
+}
+
+MEMORY_CHECK_INTERVAL_SECONDS = 2.0
+# After cancelling jobs, how long memory may stay above the limit before the process is ended
+MEMORY_GRACE_SECONDS = 15.0
+
+_applied_nice_increment = 0
+_applied_priority = "normal"
+
+
+def get_cpu_count() -> int:
+    """Number of CPUs available to this process."""
+    if hasattr(os, "sched_getaffinity"):
+        return len(os.sched_getaffinity(0))
+    return os.cpu_count() or 1
+
+
+def get_worker_count(num_threads: Optional[int] = None, low_power_mode: bool = False) -> int:
+    """Number of worker threads to use for parallel computations."""
+    if low_power_mode:
+        return 1
+    if num_threads is not None:
+        return max(1, num_threads)
+    from argscape.backend.hardware import recommended_worker_count
+    return recommended_worker_count()
+
+
+def set_thread_environment(num_threads: int):
+    """Set thread-count environment variables before numpy/scipy are imported."""
+    for var in THREAD_ENV_VARS:
+        os.environ[var] = str(num_threads)
+
+
+def set_process_priority(level: str) -> bool:
+    """Lower the process priority to `level`. Returns False if the OS refused.
+
+    On POSIX an unprivileged process can't raise its priority again, so moving
+    back towards "normal" only takes effect after a restart.
+    """
+    global _applied_nice_increment, _applied_priority
+    if level not in PRIORITY_LEVELS:
+        raise ValueError(f"process_priority must be one of {list(PRIORITY_LEVELS)}")
+    increment, priority_class = PRIORITY_LEVELS[level]
+    try:
+        if sys.platform == "win32":
+            import ctypes
+            kernel32 = ctypes.windll.kernel32
+            if not kernel32.SetPriorityClass(kernel32.GetCurrentProcess(), priority_class):
+                raise OSError("SetPriorityClass failed")
+        elif increment > _applied_nice_increment:
+            os.nice(increment - _applied_nice_increment)
+            _applied_nice_increment = increment
+    except OSError as e:
+        logger.warning(f"Could not set process priority to {level}: {e}")
+        return False
+    _applied_priority = level
+    return True
+
+
+def get_memory_usage_bytes() -> Optional[int]:
+    """Current resident memory of this process, or None if it can't be measured."""
+    try:
+        import psutil
+        return psutil.Process().memory_info().rss
+    except ImportError:
+        pass
+    try:
+        with open("/proc/self/statm") as f:
+            return int(f.read().split()[1]) * os.sysconf("SC_PAGE_SIZE")
+    except (OSError, ValueError, AttributeError):
+        return None
+
+
+class MemoryWatchdog:
+    """Enforces a resident memory ceiling for the backend process.
+
+    Above the limit, `on_exceeded` runs (e.g. cancel running jobs). If memory
+    stays above the limit for MEMORY_GRACE_SECONDS, `on_kill` ends the process.
+    """
+
+    def __init__(self, on_exceeded: Callable[[int, int], None], on_kill: Callable[[int, int], None]):
+        self.on_exceeded = on_exceeded
+        self.on_kill = on_kill
+        self.limit_bytes: Optional[int] = None
+        self.exceeded_since: Optional[float] = None
+        self.last_usage: Optional[int] = None
+        self._thread: Optional[threading.Thread] = None
+        self._stop = threading.Event()
+
+    def set_limit(self, limit_mb: Optional[int]):
+        self.limit_bytes = limit_mb * 1024 * 1024 if limit_mb else None
+        self.exceeded_since = None
+        if self.limit_bytes is None:
+            self._stop.set()
+            return
+        if get_memory_usage_bytes() is None:
+            logger.warning("Memory usage can't be measured on this platform (install psutil); memory limit not enforced")
+            return
+        # A thread that was told to stop may still be waiting out its interval;
+        # it keeps its own event, so a fresh thread can start alongside it
+        if self._thread is None or not self._thread.is_alive() or self._stop.is_set():
+            self._stop = threading.Event()
+            self._thread = threading.Thread(target=self._run, args=(self._stop,), name="argscape-memory-watchdog", daemon=True)
+            self._thread.start()
+        logger.info(f"Memory limit set to {limit_mb} MB")
+
+    def check(self):
+        usage = get_memory_usage_bytes()
+        self.last_usage = usage
+        limit = self.limit_bytes
+        if usage is None or limit is None or usage <= limit:
+            self.exceeded_since = None
+            return
+        if self.exceeded_since is None:
+            self.exceeded_since = time.time()
+            logger.warning(f"Memory usage {usage // 2**20} MB exceeds the {limit // 2**20} MB limit; cancelling jobs")
+            self.on_exceeded(usage, limit)
+        elif time.time() - self.exceeded_since > MEMORY_GRACE_SECONDS:
+            self.on_kill(usage, limit)
+
+    def _run(self, stop: threading.Event):
+        while not stop.wait(MEMORY_CHECK_INTERVAL_SECONDS):
+            self.check()
+
+    def status(self) -> Dict[str, object]:
+        return {
+            "memory_limit_mb": self.limit_bytes // 2**20 if self.limit_bytes else None,
+            "memory_usage_mb": self.last_usage // 2**20 if self.last_usage else None,
+            "memory_limit_exceeded": self.exceeded_since is not None,
+        }
+
+
+def apply_resource_settings(
+    num_threads: Optional[int],
+    low_power_mode: bool,
+    process_priority: str = "normal"
+) -> Dict[str, object]:
+    """Apply thread limits to already-loaded native libraries and the process priority."""
+
+    workers = get_worker_count(num_threads, low_power_mode)
+    limited = num_threads is not None or low_power_mode
+
+    threadpool_info = None
+    try:
+        from threadpoolctl import threadpool_limits, threadpool_info as get_threadpool_info
+        threadpool_limits(limits=workers if limited else None)
+        threadpool_info = [
+            {"api": pool.get("internal_api"), "num_threads": pool.get("num_threads")}
+            for pool in get_threadpool_info()
+        ]
+    except ImportError:
+        logger.warning("threadpoolctl not available - native thread pools can only be sized at startup")
+
+    # Low-power mode implies at least "low" priority
+    if low_power_mode and process_priority == "normal":
+        process_priority = "low"
+    if process_priority != _applied_priority:
+        set_process_priority(process_priority)
+
+    logger.info(f"Resource settings applied: {workers} worker threads, low_power_mode={low_power_mode}")
+
+    return {
+        "cpu_count": get_cpu_count(),
+        "worker_threads": workers,
+        "low_power_mode": low_power_mode,
+        "priority_lowered": _applied_priority != "normal",
+        "process_priority": _applied_priority,
+        "native_thread_pools": threadpool_info,
+    }
@@ -0,0 +1,210 @@
+"""
+Named sample sets and set algebra.
+A set is defined rather than stored as IDs: by population, by a metadata
+filter on sample nodes or their individuals, by a manual selection, or by an
+imported list of node IDs or individual names, and sets combine with union,
+intersection and difference (also of other named sets). Definitions are kept
+per file in the session, so they survive reloads, and are resolved to sample
+IDs whenever a statistic, simplification or embedding asks for them.
+"""
+
+import json
+import logging
+import re
+from typing import Any, Dict, List, Optional, Set
+
+import tskit
+
+from argscape.backend.palettes import population_names
+
+logger = logging.getLogger(__name__)
+
+SET_KINDS = ("all", "population", "metadata", "manual", "ids", "set", "union", "intersection", "difference")
+METADATA_OPERATORS = ("==", "!=", "<", "<=", ">", ">=", "in", "contains")
+METADATA_TABLES = ("nodes", "individuals")
+ID_TYPES = ("node", "individual", "name")
+# Individual metadata keys that can hold a sample's name in an imported list
+NAME_KEYS = ("name", "id", "sample_id", "individual_name")
+MAX_SET_DEPTH = 32
+
+
+def parse_id_list(text: str) -> List[str]:
+    """IDs from an imported list: one per line or separated by commas, tabs or spaces; # starts a comment."""
+    ids = []
+    for line in text.splitlines():
+        line = line.split("#", 1)[0]
+        ids.extend(token for token in re.split(r"[\s,]+", line) if token)
+    return ids
+
+
+def _decoded(row_metadata: Any) -> Dict[str, Any]:
+    if isinstance(row_metadata, bytes):
+        try:
+            row_metadata = json.loads(row_metadata.decode("utf-8")) if row_metadata else {}
+        except (UnicodeDecodeError, ValueError):
+            return {}
+    return row_metadata if isinstance(row_metadata, dict) else {}
+
+
+def _matches(value: Any, operator: str, target: Any) -> bool:
+    try:
+        if operator == "==":
+            return value == target
+        if operator == "!=":
+            return value != target
+        if operator == "in":
+            return value in target
+        if operator == "contains":
+            return target in value
+        if value is None:
+            return False
+        return {"<": value < target, "<=": value <= target, ">": value > target, ">=": value >= target}[operator]
+    except TypeError:
+        # Mismatched types (a string field compared with a number) are simply not a match
+        return False
+
+
+def _population_samples(ts: tskit.TreeSequence, population: Any) -> Set[int]:
+    names = population_names(ts)
+    if isinstance(population, str) and not population.isdigit():
+        if population not in names:
+            raise ValueError(f"Unknown population '{population}'. Expected one of {names}")
+        population = names.index(population)
+    population = int(population)
+    if not 0 <= population < ts.num_populations:
+        raise ValueError(f"Population {population} does not exist")
+    return {int(u) for u in ts.samples(population=population)}
+
+
+def _metadata_samples(ts: tskit.TreeSequence, definition: Dict[str, Any]) -> Set[int]:
+    table = definition.get("table", "individuals")
+    operator = definition.get("op", "==")
+    if table not in METADATA_TABLES:
+        raise ValueError(f"Metadata filters apply to {METADATA_TABLES}, not '{table}'")
+    if operator not in METADATA_OPERATORS:
+        raise ValueError(f"Unknown operator '{operator}'. Expected one of {METADATA_OPERATORS}")
+    if not definition.get("key"):
+        raise ValueError("A metadata filter needs a key")
+    path = str(definition["key"]).split(".")
+    selected = set()
+    for u in ts.samples():
+        node = ts.node(u)
+        if table == "individuals" and node.individual == tskit.NULL:
+            continue
+        value: Any = _decoded(node.metadata if table == "nodes" else ts.individual(node.individual).metadata)
+        # Dotted keys reach into nested metadata, e.g. "location.region"
+        for part in path:
+            value = value.get(part) if isinstance(value, dict) else None
+        if _matches(value, operator, definition.get("value")):
+            selected.add(int(u))
+    return selected
+
+
+def _listed_samples(ts: tskit.TreeSequence, ids: List[Any], id_type: str) -> Set[int]:
+    if id_type not in ID_TYPES:
+        raise ValueError(f"Unknown ID type '{id_type}'. Expected one of {ID_TYPES}")
+    samples = {int(u) for u in ts.samples()}
+    if id_type == "node":
+        listed = {int(i) for i in ids}
+        unknown = sorted(listed - samples)
+        if unknown:
+            raise ValueError(f"Not sample nodes: {unknown[:20]}")
+        return listed
+    if id_type == "individual":
+        wanted = {int(i) for i in ids}
+    else:
+        names = {str(i) for i in ids}
+        wanted = set()
+        for individual in ts.individuals():
+            metadata = _decoded(individual.metadata)
+            if any(str(metadata.get(key)) in names for key in NAME_KEYS if key in metadata):
+                wanted.add(individual.id)
+        if not wanted:
+            raise ValueError(f"No individual has one of the listed names (looked in metadata keys {NAME_KEYS})")
+    return {u for u in samples if ts.node(u).individual in wanted}
+
+
+def evaluate(
+    ts: tskit.TreeSequence,
+    definition: Dict[str, Any],
+    named: Optional[Dict[str, Dict[str, Any]]] = None,
+    _stack: tuple = ()
+) -> Set[int]:
+    """The sample IDs a definition selects; `named` holds the definitions "set" can refer to."""
+    named = named or {}
+    if len(_stack) > MAX_SET_DEPTH:
+        raise ValueError("Sample set definitions are nested too deeply")
+    kind = definition.get("kind")
+    if kind not in SET_KINDS:
+        raise ValueError(f"Unknown sample set kind '{kind}'. Expected one of {SET_KINDS}")
+    if kind == "all":
+        return {int(u) for u in ts.samples()}
+    if kind == "population":
+        return _population_samples(ts, definition.get("population"))
+    if kind == "metadata":
+        return _metadata_samples(ts, definition)
+    if kind == "manual":
+        return _listed_samples(ts, definition.get("samples", []), "node")
+    if kind == "ids":
+        ids = definition.get("ids")
+        if isinstance(ids, str):
+            ids = parse_id_list(ids)
+        return _listed_samples(ts, ids or [], definition.get("id_type", "node"))
+    if kind == "set":
+        name = definition.get("name")
+        if name in _stack:
+            raise ValueError(f"Sample set '{name}' refers to itself")
+        if name not in named:
+            raise ValueError(f"Unknown sample set '{name}'")
+        return evaluate(ts, named[name], named, _stack + (name,))
+
+    operands = [evaluate(ts, operand, named, _stack) for operand in definition.get("sets", [])]
+    if not operands:
+        raise ValueError(f"A {kind} needs at least one set")
+    result = set(operands[0])
+    for operand in operands[1:]:
+        if kind == "union":
+            result |= operand
+        elif kind == "intersection":
+            result &= operand
+        else:
+            result -= operand
+    return result
+
+
+def referenced_sets(definition: Dict[str, Any]) -> Set[str]:
+    """Names of the other sets a definition refers to, directly or inside set operations."""
+    if definition.get("kind") == "set":
+        return {definition.get("name")}
+    return set().union(*(referenced_sets(operand) for operand in definition.get("sets", []) if isinstance(operand, dict)))
+
+
+def resolve_sample_sets(
+    ts: tskit.TreeSequence,
+    definitions: Dict[str, Dict[str, Any]],
+    names: Optional[List[str]] = None
+) -> Dict[str, List[int]]:
+    """Named sets (default: all of them) as sorted sample IDs; raises ValueError for an unknown or empty set."""
+    resolved = {}
+    for name in names if names is not None else list(definitions):
+        if name not in definitions:
+            raise ValueError(f"Unknown sample set '{name}'. Defined: {sorted(definitions)}")
+        samples = sorted(evaluate(ts, definitions[name], definitions, (name,)))
+        if not samples:
+            raise ValueError(f"Sample set '{name}' is empty for this tree sequence")
+        resolved[name] = samples
+    return resolved
+
+
+def describe_sample_sets(ts: tskit.TreeSequence, definitions: Dict[str, Dict[str, Any]]) -> List[Dict[str, Any]]:
+    """Each definition with its size, or the error that stops it resolving here."""
+    described = []
+    for name, definition in definitions.items():
+        entry = {"name": name, "definition": definition}
+        try:
+            samples = sorted(evaluate(ts, definition, definitions, (name,)))
+            entry.update({"num_samples": len(samples), "samples": samples, "error": None})
+        except ValueError as e:
+            entry.update({"num_samples": 0, "samples": [], "error": str(e)})
+        described.append(entry)
+    return described
@@ -0,0 +1,199 @@
+"""
+Optional restricted mode for the ARGscape backend.
+When enabled, the process keeps only an allowlist of environment variables,
+runs from its storage directory, may only write inside its storage, temp and
+config directories, may only read those plus its own installation, and can
+only open network connections to loopback addresses.
+Enforcement is best-effort and Python-level only: it uses audit hooks, which
+see file and socket calls made through Python but not I/O done inside C
+extensions (tskit, numpy, h5py) or by code that deliberately avoids them. It
+stops mistakes and ordinary library behaviour from leaving its limits, not a
+determined attacker; an OS-level boundary (container, VM, seccomp) is needed
+for that.
+"""
+
+import ipaddress
+import logging
+import os
+import site
+import socket
+import sys
+import sysconfig
+import tempfile
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+# Environment variables kept when the environment is cleared
+SANDBOX_ENV_ALLOWLIST = (
+    "PATH", "HOME", "USERPROFILE", "APPDATA", "LOCALAPPDATA", "SYSTEMROOT", "TEMP", "TMP", "TMPDIR",
+    "LANG", "LC_ALL", "TZ", "PYTHONPATH", "VIRTUAL_ENV", "CONDA_PREFIX", "XDG_CONFIG_HOME",
+    "PERSISTENT_SESSION_PATH", "DISABLE_TSDATE", "MAX_SESSION_AGE_HOURS", "MAX_FILES_PER_SESSION",
+    "MAX_FILE_SIZE_MB", "CLEANUP_INTERVAL_MINUTES",
+)
+SANDBOX_ENV_PREFIXES = ("ARGSCAPE_", "OMP_", "OPENBLAS_", "MKL_", "NUMEXPR_", "VECLIB_")
+
+# Pseudo-filesystems needed by numeric libraries for CPU and memory detection, plus the
+# few /etc files Python reads itself (TLS certificates, time zone, MIME types)
+SYSTEM_READ_ROOTS = (
+    "/proc", "/sys", "/dev",
+    "/etc/ssl", "/etc/pki", "/etc/ca-certificates", "/etc/localtime", "/etc/mime.types",
+    "/usr/share/zoneinfo",
+)
+
+WRITE_EVENTS = ("os.remove", "os.rename", "os.mkdir", "os.rmdir", "os.truncate", "shutil.rmtree", "shutil.move")
+
+_state: Dict[str, Any] = {"enabled": False}
+
+
+def is_sandbox_enabled() -> bool:
+    return _state["enabled"]
+
+
+def _is_within(path: Path, roots: List[Path]) -> bool:
+    return any(path == root or root in path.parents for root in roots)
+
+
+def _normalize(path: Any) -> Optional[Path]:
+    if isinstance(path, int):  # already-open file descriptor
+        return None
+    if isinstance(path, bytes):
+        path = os.fsdecode(path)
+    try:
+        return Path(os.path.abspath(os.fspath(path)))
+    except TypeError:
+        return None
+
+
+def is_loopback_host(host: Any) -> bool:
+    """Whether a host name or address refers to this machine."""
+    if host is None:
+        return True  # AI_PASSIVE lookups for binding
+    if isinstance(host, bytes):
+        host = host.decode()
+    if host in ("localhost", ""):
+        return True
+    try:
+        return ipaddress.ip_address(host.split("%")[0]).is_loopback
+    except ValueError:
+        return False
+
+
+def _is_write_open(mode: Any, flags: Any) -> bool:
+    if isinstance(mode, str) and any(c in mode for c in "wax+"):
+        return True
+    if isinstance(flags, int) and flags & (os.O_WRONLY | os.O_RDWR | os.O_CREAT | os.O_APPEND | os.O_TRUNC):
+        return True
+    return False
+
+
+def _audit_hook(event: str, args: tuple):
+    if event == "open":
+        path = _normalize(args[0])
+        if path is None:
+            return
+        mode = args[1] if len(args) > 1 else None
+        flags = args[2] if len(args) > 2 else None
+        if _is_write_open(mode, flags):
+            if not _is_within(path, _state["write_roots"]):
+                raise PermissionError(f"Sandbox: writing outside the allowed directories is blocked ({path.name})")
+        elif not _is_within(path, _state["read_roots"]):
+            raise PermissionError(f"Sandbox: reading outside the allowed directories is blocked ({path.name})")
+
+    elif event in WRITE_EVENTS:
+        for arg in args[:2]:
+            path = _normalize(arg) if isinstance(arg, (str, bytes, os.PathLike)) else None
+            if path is not None and not _is_within(path, _state["write_roots"]):
+                raise PermissionError(f"Sandbox: modifying files outside the allowed directories is blocked ({path.name})")
+
+    elif event == "socket.connect":
+        sock, address = args[0], args[1]
+        if sock.family in (socket.AF_INET, socket.AF_INET6) and not is_loopback_host(address[0]):
+            raise PermissionError("Sandbox: outbound network connections are blocked (loopback only)")
+
+    elif event == "socket.getaddrinfo":
+        if not is_loopback_host(args[0]):
+            raise PermissionError("Sandbox: name resolution for non-loopback hosts is blocked")
+
+
+def _installation_roots() -> List[Path]:
+    roots = {sys.prefix, sys.base_prefix, sys.exec_prefix, sysconfig.get_paths()["stdlib"]}
+    roots.update(site.getsitepackages() if hasattr(site, "getsitepackages") else [])
+    user_site = site.getusersitepackages() if hasattr(site, "getusersitepackages") else None
+    if user_site:
+        roots.add(user_site)
+    roots.update(p for p in sys.path if p)
+    roots.add(str(Path(__file__).resolve().parent.parent))  # the argscape package
+    return [Path(os.path.abspath(r)) for r in roots]
+
+
+def apply_sandbox(storage_dir: Path, config_dir: Path, extra_dirs: Optional[List[Path]] = None):
+    """Restrict this process. Irreversible: audit hooks cannot be removed once added.
+
+    `extra_dirs` are other folders the backend writes to (data, cache and scratch folders).
+    """
+    if _state["enabled"]:
+        return
+
+    storage_dir = Path(os.path.abspath(storage_dir))
+    config_dir = Path(os.path.abspath(config_dir))
+    temp_dir = Path(os.path.abspath(tempfile.gettempdir()))
+    storage_dir.mkdir(parents=True, exist_ok=True)
+
+    removed_env = [
+        name for name in list(os.environ)
+        if name not in SANDBOX_ENV_ALLOWLIST and not name.startswith(SANDBOX_ENV_PREFIXES)
+    ]
+    for name in removed_env:
+        del os.environ[name]
+
+    os.chdir(storage_dir)
+
+    write_roots = [storage_dir, temp_dir, config_dir] + [Path(os.path.abspath(d)) for d in extra_dirs or []]
+    _state.update({
+        "enabled": True,
+        "storage_dir": storage_dir,
+        "write_roots": write_roots,
+        "read_roots": write_roots + _installation_roots() + [Path(p) for p in SYSTEM_READ_ROOTS],
+        "num_env_removed": len(removed_env),
+    })
+    sys.addaudithook(_audit_hook)
+
+    logger.info(f"Sandbox enabled: working dir {storage_dir}, {len(removed_env)} environment variables cleared, "
+                "network restricted to loopback")
+
+
+def get_capabilities_report() -> Dict[str, Any]:
+    """Describe the boundary currently enforced on this backend process."""
+    if not _state["enabled"]:
+        return {
+            "sandbox_enabled": False,
+            "filesystem": {"read": "unrestricted", "write": "unrestricted"},
+            "network": {"outbound": "unrestricted"},
+            "environment": {"cleared": False},
+        }
+
+    return {
+        "sandbox_enabled": True,
+        "working_directory": str(_state["storage_dir"]),
+        "filesystem": {
+            "write": [str(p) for p in _state["write_roots"]],
+            "read": sorted({str(p) for p in _state["read_roots"]}),
+        },
+        "network": {
+            "outbound": "loopback only",
+            "dns": "loopback names only",
+        },
+        "environment": {
+            "cleared": True,
+            "allowlist": list(SANDBOX_ENV_ALLOWLIST),
+            "allowed_prefixes": list(SANDBOX_ENV_PREFIXES),
+            "num_removed": _state["num_env_removed"],
+        },
+        "enforcement": "Best-effort Python audit hooks (Python-level file and socket calls only)",
+        "limitations": [
+            "I/O done inside C extensions such as tskit, numpy and h5py is not checked",
+            "Code that avoids Python's audited calls can bypass it; use a container or VM for a hard boundary",
+        ],
+    }
@@ -12,11 +12,17 @@ import threading
 import shutil
 import pickle
 import json
-from typing import Dict, List, Optional, Set
+import io
+from typing import Any, Callable, Dict, List, Optional, Set
 from datetime import datetime, timedelta
 from dataclasses import dataclass, field
 from pathlib import Path
 
+from argscape.backend.protected import is_protected_mode, register_sensitive_name
+from argscape.backend.settings import settings
+from argscape.backend.temp_files import CATEGORY_CACHE, CATEGORY_DERIVED, LEDGER_FILENAME, TempFileManager
+from argscape.backend.ts_cache import evict_tree_sequence
+
 try:
     import tskit
 except ImportError:
@@ -40,6 +46,8 @@ class UserSession:
     client_ip: str
     uploaded_files: Dict[str, bytes] = field(default_factory=dict)
     tree_sequences: Dict[str, tskit.TreeSequence] = field(default_factory=dict)
+    session_data: Dict[str, Any] = field(default_factory=dict)
+    protected_files: Set[str] = field(default_factory=set)  # Kept in memory only, never written to disk
     temp_dir: Optional[str] = None
     
     def update_access_time(self):
@@ -93,6 +101,21 @@ class PersistentSessionStorage:
         self.cleanup_interval_minutes = cleanup_interval_minutes
         self._lock = threading.RLock()
         
+        # Set by argscape.backend.replay: rebuilds a file that is neither in memory nor on disk
+        self.missing_file_handler: Optional[Callable[[str, str], Optional[tskit.TreeSequence]]] = None
+        self.file_deleted_listener: Optional[Callable[[str, str], None]] = None
+        # Set by main: records stored files in the audit trail (not called for protected files)
+        self.file_stored_listener: Optional[Callable[[str, str, tskit.TreeSequence, Optional[str]], None]] = None
+        
+        # Derived files and caches are tracked so a disk quota can be enforced
+        self.temp_files = TempFileManager(
+            self.storage_base_path / LEDGER_FILENAME,
+            get_quota_bytes=self._get_derived_quota_bytes,
+            on_evict=self._on_derived_file_evicted,
+            get_scratch_dirs=self.get_scratch_dirs,
+            get_min_free_bytes=lambda: settings.get("scratch_min_free_mb") * 1024 * 1024
+        )
+        
         # Load existing sessions from disk
         self._load_sessions_from_disk()
         
@@ -127,6 +150,75 @@ class PersistentSessionStorage:
         """Get the directory path for a session."""
         return self.storage_base_path / f"session_{session_id}"
     
+    def get_scratch_dirs(self) -> List[Path]:
+        """Folders derived tree sequences are written to, from the scratch_dirs setting."""
+        configured = settings.get("scratch_dirs")
+        return [Path(d) for d in configured] if configured else [self.storage_base_path]
+    
+    def _session_dirs(self, session_id: str) -> List[Path]:
+        """The session directory and the session's folder on each scratch location."""
+        dirs = [self._get_session_dir(session_id)]
+        for scratch in self.get_scratch_dirs():
+            scratch_dir = scratch / f"session_{session_id}"
+            if scratch_dir not in dirs:
+                dirs.append(scratch_dir)
+        return dirs
+    
+    def _tree_sequence_path(self, session_id: str, filename: str) -> Path:
+        """Where a stored tree sequence is on disk (in the session directory if it isn't anywhere yet)."""
+        for directory in self._session_dirs(session_id):
+            path = directory / f"{filename}.trees"
+            if path.exists():
+                return path
+        return self._get_session_dir(session_id) / f"{filename}.trees"
+    
+    def tree_sequence_path(self, session_id: str, filename: str) -> Optional[Path]:
+        """The stored copy of a file on disk, or None if it is only in memory."""
+        path = self._tree_sequence_path(session_id, filename)
+        return path if path.exists() else None
+    
+    def _get_derived_quota_bytes(self) -> Optional[int]:
+        """Disk quota for derived files and caches, from settings."""
+        quota_mb = settings.get("derived_data_quota_mb")
+        return None if quota_mb is None else quota_mb * 1024 * 1024
+    
+    def is_file_protected(self, session_id: str, filename: str) -> bool:
+        """Whether a file falls under protected data handling."""
+        if is_protected_mode():
+            return True
+        session = self.sessions.get(session_id)
+        return session is not None and filename in session.protected_files
+    
+    def set_file_protected(self, session_id: str, filename: str, protected: bool = True) -> bool:
+        """Mark a file as protected; data derived from it will not be written to disk."""
+        session = self.get_session(session_id)
+        if not session:
+            return False
+        
+        with self._lock:
+            if protected:
+                session.protected_files.add(filename)
+                register_sensitive_name(filename)
+            else:
+                session.protected_files.discard(filename)
+        return True
+    
+    def _on_derived_file_evicted(self, entry: Dict[str, Any]):
+        """Drop in-memory copies of a derived file the temp file manager is removing."""
+        session = self.sessions.get(entry["session_id"])
+        if session is None:
+            return
+        with self._lock:
+            if entry["category"] == CATEGORY_DERIVED:
+                ts = session.tree_sequences.pop(entry["name"], None)
+                if ts is not None:
+                    evict_tree_sequence(ts)
+                session.uploaded_files.pop(entry["name"], None)
+                (self._get_session_dir(session.session_id) / f"{entry['name']}.data").unlink(missing_ok=True)
+                self._save_session_metadata(session)
+            elif entry["category"] == CATEGORY_CACHE:
+                session.session_data.pop(entry["name"], None)
+    
     def _save_session_metadata(self, session: UserSession):
         """Save session metadata to disk."""
         session_dir = self._get_session_dir(session.session_id)
@@ -137,7 +229,8 @@ class PersistentSessionStorage:
             "created_at": session.created_at.isoformat(),
             "last_accessed": session.last_accessed.isoformat(),
             "client_ip": session.client_ip,
-            "file_list": list(session.tree_sequences.keys())
+            # Protected files only live in memory, so they can't be restored from disk
+            "file_list": [f for f in session.tree_sequences if f not in session.protected_files]
         }
         
         metadata_file = session_dir / "metadata.json"
@@ -166,7 +259,7 @@ class PersistentSessionStorage:
             
             # Load tree sequences
             for filename in metadata["file_list"]:
-                ts_file = session_dir / f"{filename}.trees"
+                ts_file = self._tree_sequence_path(session_id, filename)
                 file_data_file = session_dir / f"{filename}.data"
                 
                 if ts_file.exists():
@@ -275,8 +368,8 @@ class PersistentSessionStorage:
                 return None
             return None
     
-    def store_file(self, session_id: str, filename: str, contents: bytes) -> bool:
-        """Store a file in the session."""
+    def store_file(self, session_id: str, filename: str, contents: bytes, protected: bool = False) -> bool:
+        """Store a file in the session. Protected files are kept in memory only."""
         if len(contents) > self.max_file_size_bytes:
             raise ValueError(f"File too large: {len(contents)} bytes (max: {self.max_file_size_bytes})")
         
@@ -287,9 +380,16 @@ class PersistentSessionStorage:
         if session.get_file_count() >= self.max_files_per_session:
             raise ValueError(f"Too many files in session (max: {self.max_files_per_session})")
         
+        if protected or is_protected_mode():
+            self.set_file_protected(session_id, filename)
+        
         with self._lock:
             session.uploaded_files[filename] = contents
             
+            if filename in session.protected_files:
+                logger.info(f"Stored protected file {filename} in memory only")
+                return True
+            
             # Save file data to disk
             session_dir = self._get_session_dir(session_id)
             file_data_path = session_dir / f"{filename}.data"
@@ -301,12 +401,46 @@ class PersistentSessionStorage:
         
         return True
     
-    def store_tree_sequence(self, session_id: str, filename: str, ts: tskit.TreeSequence) -> bool:
-        """Store a tree sequence in the session."""
+    def store_tree_sequence(
+        self,
+        session_id: str,
+        filename: str,
+        ts: tskit.TreeSequence,
+        derived_from: Optional[str] = None,
+        protected: bool = False
+    ) -> bool:
+        """Store a tree sequence in the session.
+        
+        Derived tree sequences (produced from the file `derived_from`) count
+        towards the derived data quota and may be evicted when it is exceeded.
+        Protected tree sequences, including anything derived from one, are
+        kept in memory only.
+        """
         session = self.get_session(session_id)
         if not session:
             raise ValueError("Invalid or expired session")
         
+        if protected or self.is_file_protected(session_id, filename) or (
+            derived_from is not None and self.is_file_protected(session_id, derived_from)
+        ):
+            self.set_file_protected(session_id, filename)
+            with self._lock:
+                session.tree_sequences[filename] = ts
+                self._save_session_metadata(session)
+            logger.info(f"Stored protected tree sequence {filename} in memory only")
+            return True
+        
+        # Derived files go to the first scratch folder with room; raises if none has
+        previous_path = self._tree_sequence_path(session_id, filename)
+        if derived_from is not None:
+            scratch_dir = self.temp_files.choose_scratch_dir(getattr(ts, "nbytes", 0))
+            ts_file_path = scratch_dir / f"session_{session_id}" / f"{filename}.trees"
+        else:
+            ts_file_path = self._get_session_dir(session_id) / f"{filename}.trees"
+        if previous_path != ts_file_path and previous_path.exists():
+            self.temp_files.unregister(previous_path)
+            previous_path.unlink(missing_ok=True)
+        
         with self._lock:
             # Log mutation count before storing
             logger.info(f"Storing tree sequence {filename} with {ts.num_mutations} mutations")
@@ -314,8 +448,7 @@ class PersistentSessionStorage:
             session.tree_sequences[filename] = ts
             
             # Save tree sequence to disk
-            session_dir = self._get_session_dir(session_id)
-            ts_file_path = session_dir / f"{filename}.trees"
+            ts_file_path.parent.mkdir(parents=True, exist_ok=True)
             ts.dump(str(ts_file_path))
             
             # Verify mutations after dump
@@ -328,6 +461,12 @@ class PersistentSessionStorage:
             self._save_session_metadata(session)
             logger.info(f"Stored tree sequence {filename} in persistent session {session_id}")
         
+        if derived_from is not None:
+            self.temp_files.register(ts_file_path, session_id, filename, CATEGORY_DERIVED)
+        
+        if self.file_stored_listener is not None:
+            self.file_stored_listener(session_id, filename, ts, derived_from)
+        
         return True
     
     def get_tree_sequence(self, session_id: str, filename: str) -> Optional[tskit.TreeSequence]:
@@ -336,6 +475,9 @@ class PersistentSessionStorage:
         if not session:
             return None
         
+        ts_file_path = self._tree_sequence_path(session_id, filename)
+        self.temp_files.touch(ts_file_path)
+        
         # Try to get from memory first
         ts = session.tree_sequences.get(filename)
         if ts is not None:
@@ -343,9 +485,6 @@ class PersistentSessionStorage:
             return ts
         
         # If not in memory, try to load from disk
-        session_dir = self._get_session_dir(session_id)
-        ts_file_path = session_dir / f"{filename}.trees"
-        
         if ts_file_path.exists():
             try:
                 ts = tskit.load(str(ts_file_path))
@@ -356,6 +495,12 @@ class PersistentSessionStorage:
             except Exception as e:
                 logger.error(f"Failed to load tree sequence {filename} from disk: {e}")
         
+        if self.missing_file_handler is not None:
+            try:
+                return self.missing_file_handler(session_id, filename)
+            except Exception as e:
+                logger.error(f"Failed to rebuild tree sequence {filename}: {e}")
+        
         return None
     
     def get_file_list(self, session_id: str) -> List[str]:
@@ -372,14 +517,19 @@ class PersistentSessionStorage:
         if not session:
             return False
         
+        # Untrack before taking the session lock; eviction takes the locks in the opposite order
+        session_dir = self._get_session_dir(session_id)
+        ts_file_path = self._tree_sequence_path(session_id, filename)
+        self.temp_files.unregister(ts_file_path)
+        
         with self._lock:
             session.uploaded_files.pop(filename, None)
-            session.tree_sequences.pop(filename, None)
+            ts = session.tree_sequences.pop(filename, None)
+            session.protected_files.discard(filename)
             
             # Delete files from disk
-            session_dir = self._get_session_dir(session_id)
             try:
-                (session_dir / f"{filename}.trees").unlink(missing_ok=True)
+                ts_file_path.unlink(missing_ok=True)
                 (session_dir / f"{filename}.data").unlink(missing_ok=True)
             except Exception as e:
                 logger.warning(f"Failed to delete disk files for {filename}: {e}")
@@ -387,6 +537,13 @@ class PersistentSessionStorage:
             self._save_session_metadata(session)
             logger.info(f"Deleted file {filename} from persistent session {session_id}")
         
+        # Other requests may still hold it, so don't wait for it to be collected
+        if ts is not None:
+            evict_tree_sequence(ts)
+        
+        if self.file_deleted_listener is not None:
+            self.file_deleted_listener(session_id, filename)
+        
         return True
     
     def get_file_data(self, session_id: str, filename: str) -> Optional[bytes]:
@@ -417,6 +574,11 @@ class PersistentSessionStorage:
         # If no .data file exists, check if we have a tree sequence that we can generate file data from
         # This handles simulated files that don't have original file data
         ts = session.tree_sequences.get(filename)
+        if ts is not None and filename in session.protected_files:
+            buffer = io.BytesIO()
+            ts.dump(buffer)
+            session.uploaded_files[filename] = buffer.getvalue()
+            return session.uploaded_files[filename]
         if ts is not None:
             try:
                 import tempfile
@@ -459,6 +621,72 @@ class PersistentSessionStorage:
         
         return None
     
+    def _get_session_data_path(self, session_id: str, key: str) -> Path:
+        """Get the on-disk path for a session data entry."""
+        key_hash = hashlib.sha1(key.encode()).hexdigest()
+        return self._get_session_dir(session_id) / "data" / f"{key_hash}.json"
+    
+    def store_session_data(self, session_id: str, key: str, value: Any, filename: Optional[str] = None) -> bool:
+        """Store a JSON-serializable value (layouts, settings, ...) in the session.
+        
+        Values about a protected `filename` are kept in memory only.
+        """
+        session = self.get_session(session_id)
+        if not session:
+            raise ValueError("Invalid or expired session")
+        
+        with self._lock:
+            session.session_data[key] = value
+            if is_protected_mode() or (filename is not None and filename in session.protected_files):
+                return True
+            
+            data_path = self._get_session_data_path(session_id, key)
+            data_path.parent.mkdir(parents=True, exist_ok=True)
+            with open(data_path, 'w') as f:
+                json.dump({"key": key, "value": value}, f)
+        
+        self.temp_files.register(data_path, session_id, key, CATEGORY_CACHE)
+        
+        return True
+    
+    def get_session_data(self, session_id: str, key: str, default: Any = None) -> Any:
+        """Get a value stored with store_session_data."""
+        session = self.get_session(session_id)
+        if not session:
+            return default
+        
+        data_path = self._get_session_data_path(session_id, key)
+        self.temp_files.touch(data_path)
+        
+        if key in session.session_data:
+            return session.session_data[key]
+        
+        if data_path.exists():
+            try:
+                with open(data_path, 'r') as f:
+                    value = json.load(f)["value"]
+                session.session_data[key] = value
+                return value
+            except Exception as e:
+                logger.error(f"Failed to load session data {key} for session {session_id}: {e}")
+        
+        return default
+    
+    def delete_session_data(self, session_id: str, key: str) -> bool:
+        """Delete a value stored with store_session_data."""
+        session = self.get_session(session_id)
+        if not session:
+            return False
+        
+        data_path = self._get_session_data_path(session_id, key)
+        self.temp_files.unregister(data_path)
+        
+        with self._lock:
+            session.session_data.pop(key, None)
+            data_path.unlink(missing_ok=True)
+        
+        return True
+    
     def get_session_stats(self, session_id: str) -> Optional[Dict]:
         """Get session statistics."""
         session = self.get_session(session_id)
@@ -476,13 +704,40 @@ class PersistentSessionStorage:
             "persistent": True
         }
     
+    def clear_derived_data(self, session_id: str) -> Dict[str, int]:
+        """Delete all derived tree sequences and caches of a session."""
+        return self.temp_files.clear(session_id=session_id)
+    
+    def get_derived_data_usage(self, session_id: str) -> Dict[str, Any]:
+        """Disk usage of a session's derived files against the global quota."""
+        return self.temp_files.get_usage(session_id)
+    
+    def get_auxiliary_dir(self, session_id: str, category: str) -> Path:
+        """Directory holding a session's supporting files of one category."""
+        return self._get_session_dir(session_id) / Path(category).name
+    
+    def store_auxiliary_file(self, session_id: str, category: str, filename: str, contents: bytes) -> Path:
+        """Write a supporting file (reference genome, annotation, ...) into the session directory."""
+        if not self.get_session(session_id):
+            raise ValueError("Invalid or expired session")
+        if is_protected_mode():
+            raise ValueError("Supporting files cannot be written to disk in protected mode")
+        
+        path = self.get_auxiliary_dir(session_id, category) / Path(filename).name
+        path.parent.mkdir(parents=True, exist_ok=True)
+        with open(path, 'wb') as f:
+            f.write(contents)
+        return path
+    
     def _cleanup_session_files(self, session_id: str):
         """Clean up session files on disk."""
-        session_dir = self._get_session_dir(session_id)
-        if session_dir.exists():
+        self.temp_files.forget_session(session_id)
+        for session_dir in self._session_dirs(session_id):
+            if not session_dir.exists():
+                continue
             try:
                 shutil.rmtree(session_dir)
-                logger.info(f"Cleaned up session files for {session_id}")
+                logger.info(f"Cleaned up session files for {session_id} in {session_dir.parent}")
             except Exception as e:
                 logger.error(f"Error cleaning up session files for {session_id}: {e}")
     
@@ -554,6 +809,12 @@ class PersistentSessionStorage:
             "persistent": True
         }
     
+    def flush(self):
+        """Write all session metadata to disk."""
+        with self._lock:
+            for session in self.sessions.values():
+                self._save_session_metadata(session)
+    
     def shutdown(self):
         """Shutdown the storage system."""
         logger.info("Shutting down PersistentSessionStorage...")
@@ -562,9 +823,7 @@ class PersistentSessionStorage:
             self.scheduler.shutdown()
         
         # Save all session metadata before shutdown
-        with self._lock:
-            for session in self.sessions.values():
-                self._save_session_metadata(session)
+        self.flush()
         
         logger.info("PersistentSessionStorage shutdown complete")
 
@@ -0,0 +1,411 @@
+"""
+Persistent user settings for ARGscape.
+Settings are stored as JSON in the user's config directory; environment
+variables (set by the CLI) override stored values for the current process.
+Values missing from both fall back to argscape.toml files (see
+argscape.backend.config) and then to the built-in defaults.
+"""
+
+import json
+import logging
+import os
+import sys
+import threading
+from pathlib import Path
+from typing import Any, Callable, Dict, Optional
+
+from argscape.backend.portable import get_portable_dir
+
+logger = logging.getLogger(__name__)
+
+SETTINGS_FILENAME = "settings.json"
+
+
+def get_config_dir() -> Path:
+    """Get the per-user configuration directory for ARGscape."""
+    env_dir = os.getenv("ARGSCAPE_CONFIG_DIR")
+    if env_dir:
+        return Path(env_dir)
+    portable_dir = get_portable_dir()
+    if portable_dir is not None:
+        return portable_dir
+    if sys.platform == "win32":
+        base = Path(os.getenv("APPDATA", Path.home() / "AppData" / "Roaming"))
+    elif sys.platform == "darwin":
+        base = Path.home() / "Library" / "Application Support"
+    else:
+        base = Path(os.getenv("XDG_CONFIG_HOME", Path.home() / ".config"))
+    return base / "argscape"
+
+
+def get_data_dir() -> Path:
+    """Where audit logs and diagnostic bundles are kept (the data_dir setting, else the config folder)."""
+    configured = settings.get("data_dir")
+    return Path(configured) if configured else get_config_dir()
+
+
+def get_cache_dir() -> Path:
+    """Where downloads, remote file blocks and archived results are kept (cache_dir, else the data folder)."""
+    configured = settings.get("cache_dir")
+    return Path(configured) if configured else get_data_dir()
+
+
+def _validate_num_threads(value: Any) -> Optional[int]:
+    if value is None:
+        return None
+    value = int(value)
+    if value < 1:
+        raise ValueError("num_threads must be at least 1")
+    return value
+
+
+def _validate_stat_workers(value: Any) -> int:
+    value = int(value)
+    if value < 1:
+        raise ValueError("stat_workers must be at least 1")
+    return value
+
+
+def _positive_mb_validator(name: str) -> Callable[[Any], Optional[int]]:
+    """A validator for an optional size in megabytes that must not be negative."""
+    def validate(value: Any) -> Optional[int]:
+        if value is None:
+            return None
+        value = int(value)
+        if value < 0:
+            raise ValueError(f"{name} must not be negative")
+        return value
+    return validate
+
+
+def _validate_rasterize_above(value: Any) -> Optional[int]:
+    if value is None or value == "":
+        return None
+    value = int(value)
+    if value < 1:
+        raise ValueError("export_rasterize_above must be at least 1 (or empty to never rasterize)")
+    return value
+
+
+def _validate_export_warn_mb(value: Any) -> Optional[int]:
+    if value is None or value == "":
+        return None
+    value = int(value)
+    if value < 1:
+        raise ValueError("export_warn_mb must be at least 1 (or empty for no warning)")
+    return value
+
+
+def _validate_target_fps(value: Any) -> int:
+    value = int(value)
+    if not 5 <= value <= 240:
+        raise ValueError("render_target_fps must be between 5 and 240")
+    return value
+
+
+def _validate_proxy_url(value: Any) -> Optional[str]:
+    if value in (None, ""):
+        return None
+    value = str(value)
+    if "://" not in value:
+        raise ValueError("proxy_url must include a scheme, e.g. http://proxy.example.org:3128")
+    return value
+
+
+def _validate_report_url(value: Any) -> Optional[str]:
+    if value in (None, ""):
+        return None
+    value = str(value)
+    if not value.startswith(("http://", "https://")):
+        raise ValueError("diagnostics_endpoint must be an http(s) URL")
+    return value
+
+
+def _validate_ca_bundle(value: Any) -> Optional[str]:
+    if value in (None, ""):
+        return None
+    path = Path(str(value)).expanduser()
+    if not path.is_file():
+        raise ValueError(f"CA bundle not found: {path}")
+    return str(path)
+
+
+def _validate_inbox_dir(value: Any) -> Optional[str]:
+    if value in (None, ""):
+        return None
+    path = Path(str(value)).expanduser()
+    if not path.is_dir():
+        raise ValueError(f"Inbox folder not found: {path}")
+    return str(path)
+
+
+def _validate_interpreter(value: Any) -> Optional[str]:
+    if value in (None, ""):
+        return None
+    path = Path(str(value)).expanduser()
+    if not path.exists():
+        raise ValueError(f"Python interpreter or environment not found: {path}")
+    return str(path)
+
+
+def _validate_storage_dir(value: Any) -> Optional[str]:
+    if value in (None, ""):
+        return None
+    path = Path(str(value)).expanduser()
+    if not path.is_absolute():
+        raise ValueError(f"Storage folder must be an absolute path: {path}")
+    if path.exists() and not path.is_dir():
+        raise ValueError(f"Not a folder: {path}")
+    # The folder may not exist yet; it will be created under its nearest existing parent
+    parent = path
+    while not parent.exists() and parent.parent != parent:
+        parent = parent.parent
+    if not os.access(parent, os.W_OK | os.X_OK):
+        raise ValueError(f"No write permission for {parent}")
+    return str(path)
+
+
+def _validate_scratch_dirs(value: Any) -> Optional[list]:
+    if value in (None, "", []):
+        return None
+    if isinstance(value, str):
+        value = [part for part in value.split(os.pathsep) if part]
+    if not isinstance(value, (list, tuple)):
+        raise ValueError("scratch_dirs must be a list of folders")
+    return [_validate_storage_dir(path) for path in value]
+
+
+def _validate_min_free_mb(value: Any) -> int:
+    value = int(value)
+    if value < 0:
+        raise ValueError("scratch_min_free_mb must not be negative")
+    return value
+
+
+def _validate_optional_bool(value: Any) -> Optional[bool]:
+    if value is None or value == "":
+        return None
+    return _validate_bool(value)
+
+
+def _choice(name: str, choices: tuple) -> Callable[[Any], str]:
+    def validate(value: Any) -> str:
+        if value not in choices:
+            raise ValueError(f"{name} must be one of {list(choices)}")
+        return value
+    return validate
+
+
+def _validate_memory_limit(value: Any) -> Optional[int]:
+    if value is None or value == "":
+        return None
+    value = int(value)
+    if value < 256:
+        raise ValueError("memory_limit_mb must be at least 256 (or empty for no limit)")
+    return value
+
+
+def _validate_locale(value: Any) -> str:
+    from argscape.backend.formatting import LOCALE_SEPARATORS
+    if value not in LOCALE_SEPARATORS:
+        raise ValueError(f"locale must be one of {list(LOCALE_SEPARATORS)}")
+    return value
+
+
+def _validate_generation_time(value: Any) -> Optional[float]:
+    if value is None or value == "":
+        return None
+    value = float(value)
+    if value <= 0:
+        raise ValueError("generation_time_years must be positive")
+    return value
+
+
+def _validate_bool(value: Any) -> bool:
+    if isinstance(value, str):
+        return value.lower() in ("1", "true", "yes", "on")
+    return bool(value)
+
+
+# Setting name -> (default value, validator, overriding environment variable)
+SETTINGS_SCHEMA: Dict[str, tuple] = {
+    "num_threads": (None, _validate_num_threads, "ARGSCAPE_NUM_THREADS"),
+    "low_power_mode": (False, _validate_bool, "ARGSCAPE_LOW_POWER"),
+    # Run each heavy code path once after launch so the first real action isn't slow
+    "warmup_on_launch": (True, _validate_bool, "ARGSCAPE_WARMUP"),
+    "process_priority": ("normal", _choice("process_priority", ("normal", "low", "idle")), "ARGSCAPE_PRIORITY"),
+    # Resident memory ceiling for the backend; jobs are cancelled, then the process is ended
+    "memory_limit_mb": (None, _validate_memory_limit, "ARGSCAPE_MEMORY_LIMIT_MB"),
+    "derived_data_quota_mb": (2048, _positive_mb_validator("derived_data_quota_mb"), "ARGSCAPE_DERIVED_QUOTA_MB"),
+    # Derived-result archive (see argscape.backend.archive); least recently opened results go first
+    "archive_quota_mb": (1024, _positive_mb_validator("archive_quota_mb"), "ARGSCAPE_ARCHIVE_QUOTA_MB"),
+    # Downloaded datasets and remote files (see argscape.backend.download_cache); pinned files are kept
+    "download_cache_mb": (4096, _positive_mb_validator("download_cache_mb"), "ARGSCAPE_DOWNLOAD_CACHE_MB"),
+    # Vector exports with more elements than this are written as PNG (see argscape.backend.export_estimates)
+    "export_rasterize_above": (200_000, _validate_rasterize_above, "ARGSCAPE_EXPORT_RASTERIZE_ABOVE"),
+    "export_warn_mb": (512, _validate_export_warn_mb, "ARGSCAPE_EXPORT_WARN_MB"),
+    # Frame rate the viewer's adaptive level of detail tries to hold (see argscape.backend.frame_budget)
+    "render_target_fps": (30, _validate_target_fps, "ARGSCAPE_RENDER_TARGET_FPS"),
+    "protected_mode": (False, _validate_bool, "ARGSCAPE_PROTECTED_MODE"),
+    "offline_mode": (False, _validate_bool, "ARGSCAPE_OFFLINE"),
+    # None uses the system proxy configuration (environment, registry or macOS settings)
+    "proxy_url": (None, _validate_proxy_url, "ARGSCAPE_PROXY"),
+    "ca_bundle_path": (None, _validate_ca_bundle, "ARGSCAPE_CA_BUNDLE"),
+    # Watched folder for pipeline outputs (see argscape.backend.inbox)
+    "inbox_dir": (None, _validate_inbox_dir, "ARGSCAPE_INBOX_DIR"),
+    "inbox_preprocess": ("none", _choice("inbox_preprocess", ("none", "validate", "simplify")), "ARGSCAPE_INBOX_PREPROCESS"),
+    "inbox_action": ("notify", _choice("inbox_action", ("notify", "open")), "ARGSCAPE_INBOX_ACTION"),
+    # Localhost WebSocket for external tools to follow or drive the viewer (see argscape.backend.event_bridge)
+    "event_bridge": (False, _validate_bool, "ARGSCAPE_EVENT_BRIDGE"),
+    # Run the external commands in hooks.json on file open and export (see argscape.backend.hooks)
+    "hooks": (False, _validate_bool, "ARGSCAPE_HOOKS"),
+    # Experimental rooms where LAN followers watch a presenter's view (see argscape.backend.collaboration)
+    "collaboration": (False, _validate_bool, "ARGSCAPE_COLLABORATION"),
+    # Desktop notifications for finished background jobs: only while the window is hidden, always, or never
+    "job_notifications": ("background", _choice("job_notifications", ("background", "always", "off")), "ARGSCAPE_JOB_NOTIFICATIONS"),
+    # Where branch-mode diversity and TMRCA run when a request doesn't say (see argscape.backend.gpu_stats)
+    "stat_accelerator": ("cpu", _choice("stat_accelerator", ("cpu", "auto", "gpu")), "ARGSCAPE_STAT_ACCELERATOR"),
+    # Processes that windowed statistics split their windows across (see argscape.backend.windowed_stats)
+    "stat_workers": (1, _validate_stat_workers, "ARGSCAPE_STAT_WORKERS"),
+    # Number formatting and UI language (see argscape.backend.formatting)
+    "locale": ("en", _validate_locale, "ARGSCAPE_LOCALE"),
+    # Years per generation; when set, times in generations are also shown in years
+    "generation_time_years": (None, _validate_generation_time, "ARGSCAPE_GENERATION_TIME"),
+    # Where "report" sends diagnostic bundles; None only allows saving them locally
+    "diagnostics_endpoint": (None, _validate_report_url, "ARGSCAPE_DIAGNOSTICS_ENDPOINT"),
+    # Interpreter, virtualenv or conda environment for the backend (see argscape.backend.interpreter)
+    "python_interpreter": (None, _validate_interpreter, "ARGSCAPE_PYTHON"),
+    "backend_runtime": ("auto", _choice("backend_runtime", ("auto", "bundled", "system")), "ARGSCAPE_BACKEND_RUNTIME"),
+    # Audit logs and diagnostic bundles, and re-creatable caches; None keeps them in the config folder
+    "data_dir": (None, _validate_storage_dir, "ARGSCAPE_DATA_DIR"),
+    "cache_dir": (None, _validate_storage_dir, "ARGSCAPE_CACHE_DIR"),
+    # Large derived files go to the first of these with enough free space; None uses session storage
+    "scratch_dirs": (None, _validate_scratch_dirs, "ARGSCAPE_SCRATCH_DIRS"),
+    "scratch_min_free_mb": (1024, _validate_min_free_mb, "ARGSCAPE_SCRATCH_MIN_FREE_MB"),
+    # None until the first-run wizard asks; no usage data is collected without True
+    "telemetry_consent": (None, _validate_optional_bool, "ARGSCAPE_TELEMETRY"),
+    # Sample ordering the 2D view opens with
+    "default_layout": ("degree", _choice("default_layout", ("degree", "center_minlex", "first_tree", "custom", "numeric", "dagre")), "ARGSCAPE_DEFAULT_LAYOUT"),
+    "setup_completed": (False, _validate_bool, None),
+}
+
+# Never taken from the app or settings.json, as the CLI executes them: set them with a flag, the environment or argscape.toml
+CONFIG_ONLY_SETTINGS = ("python_interpreter",)
+
+
+class SettingsStore:
+    """JSON-backed settings with validation and environment overrides."""
+
+    def __init__(self, path: Optional[Path] = None):
+        self.path = path or get_config_dir() / SETTINGS_FILENAME
+        self._lock = threading.RLock()
+        self._values: Dict[str, Any] = {}
+        self._listeners: list = []
+        self._config_layers: list = []
+        self._load()
+        self.reload_config()
+
+    def _load(self):
+        if not self.path.exists():
+            return
+        try:
+            with open(self.path, 'r') as f:
+                stored = json.load(f)
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable settings file {self.path}: {e}")
+            return
+        # One bad value (e.g. a folder on an unplugged drive) shouldn't drop the others
+        for key, value in stored.items():
+            if key not in SETTINGS_SCHEMA:
+                continue
+            if key in CONFIG_ONLY_SETTINGS:
+                logger.warning(f"Ignoring {key} in {self.path}; set it with a CLI flag, environment variable or argscape.toml")
+                continue
+            try:
+                self._values[key] = SETTINGS_SCHEMA[key][1](value)
+            except (TypeError, ValueError) as e:
+                logger.warning(f"Ignoring stored setting {key}: {e}")
+
+    def _save(self):
+        try:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            with open(self.path, 'w') as f:
+                json.dump(self._values, f, indent=2)
+        except Exception as e:
+            logger.warning(f"Failed to save settings to {self.path}: {e}")
+
+    def reload_config(self):
+        """Re-read argscape.toml layers; broken files are logged and skipped."""
+        from argscape.backend.config import load_config_layers
+
+        layers = load_config_layers(strict=False)
+        for layer in layers:
+            if "error" in layer:
+                logger.warning(f"Ignoring config file: {layer['error']}")
+        with self._lock:
+            self._config_layers = layers
+
+    @property
+    def config_layers(self) -> list:
+        with self._lock:
+            return list(self._config_layers)
+
+    def get_source(self, key: str) -> Dict[str, Any]:
+        """Where a setting's effective value comes from: environment, app, a config layer or default."""
+        default, validator, env_var = SETTINGS_SCHEMA[key]
+        if env_var and os.getenv(env_var):
+            return {"source": "environment", "env_var": env_var}
+        with self._lock:
+            if key in self._values:
+                return {"source": "app", "path": str(self.path)}
+            for layer in reversed(self._config_layers):
+                if key in layer["settings"]:
+                    return {"source": layer["layer"], "path": layer["path"]}
+        return {"source": "default"}
+
+    def get(self, key: str) -> Any:
+        """Get a setting, honoring environment overrides."""
+        default, validator, env_var = SETTINGS_SCHEMA[key]
+        env_value = os.getenv(env_var) if env_var else None
+        if env_value:
+            return validator(env_value)
+        with self._lock:
+            if key in self._values:
+                return self._values[key]
+            for layer in reversed(self._config_layers):
+                if key in layer["settings"]:
+                    return layer["settings"][key]
+            return default
+
+    def get_all(self) -> Dict[str, Any]:
+        """Get all settings with their effective values."""
+        return {key: self.get(key) for key in SETTINGS_SCHEMA}
+
+    def update(self, updates: Dict[str, Any]) -> Dict[str, Any]:
+        """Validate and persist setting updates, then notify listeners."""
+        unknown = [key for key in updates if key not in SETTINGS_SCHEMA]
+        if unknown:
+            raise ValueError(f"Unknown settings: {unknown}")
+        locked = [key for key in updates if key in CONFIG_ONLY_SETTINGS]
+        if locked:
+            raise ValueError(f"{locked} can only be set with a CLI flag, environment variable or argscape.toml")
+
+        validated = {key: SETTINGS_SCHEMA[key][1](value) for key, value in updates.items()}
+        with self._lock:
+            self._values.update(validated)
+            self._save()
+
+        for listener in self._listeners:
+            try:
+                listener(self)
+            except Exception as e:
+                logger.error(f"Settings listener failed: {e}")
+
+        return self.get_all()
+
+    def add_listener(self, listener: Callable[["SettingsStore"], None]):
+        """Register a callback run after every settings update."""
+        self._listeners.append(listener)
+
+
+settings = SettingsStore()
@@ -0,0 +1,242 @@
+"""
+Lifecycle tracking for derived files (simplified/inferred tree sequences, caches).
+Every derived file written by session storage is registered here; a configurable
+disk quota is enforced by evicting the least recently used files first.
+Derived files can be placed on separate scratch folders (e.g. a large HPC
+scratch disk): each is written to the first folder with enough free space,
+and usage is reported per folder.
+"""
+
+import json
+import logging
+import shutil
+import threading
+import time
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+LEDGER_FILENAME = "temp_files.json"
+
+# File categories
+CATEGORY_DERIVED = "derived"  # Tree sequences produced from another file
+CATEGORY_CACHE = "cache"  # Cached computation results (layouts, ...)
+
+
+class InsufficientScratchSpace(ValueError):
+    """No scratch folder has room for a file; the message lists the free space of each."""
+
+
+def free_bytes(path: Path) -> Optional[int]:
+    """Free space on the drive holding `path` (or its nearest existing parent)."""
+    probe = Path(path)
+    while not probe.exists() and probe.parent != probe:
+        probe = probe.parent
+    try:
+        return shutil.disk_usage(probe).free
+    except OSError:
+        return None
+
+
+class TempFileManager:
+    """Tracks derived files on disk and enforces a disk quota with LRU cleanup."""
+
+    def __init__(
+        self,
+        ledger_path: Path,
+        get_quota_bytes: Callable[[], Optional[int]],
+        on_evict: Optional[Callable[[Dict[str, Any]], None]] = None,
+        get_scratch_dirs: Optional[Callable[[], List[Path]]] = None,
+        get_min_free_bytes: Optional[Callable[[], int]] = None
+    ):
+        """
+        Args:
+            ledger_path: JSON file recording tracked files across restarts
+            get_quota_bytes: Returns the current quota in bytes (None for unlimited)
+            on_evict: Called with the file entry before a file is removed, so the
+                owner can drop in-memory copies; it may delete the file itself
+            get_scratch_dirs: Returns the folders derived files may be written to,
+                in order of preference (default: the ledger's folder)
+            get_min_free_bytes: Free space to leave on a scratch folder's drive
+        """
+        self.ledger_path = ledger_path
+        self.get_quota_bytes = get_quota_bytes
+        self.on_evict = on_evict
+        self.get_scratch_dirs = get_scratch_dirs or (lambda: [ledger_path.parent])
+        self.get_min_free_bytes = get_min_free_bytes or (lambda: 0)
+        self._entries: Dict[str, Dict[str, Any]] = {}
+        self._lock = threading.RLock()
+        self._load()
+
+    def _load(self):
+        if not self.ledger_path.exists():
+            return
+        try:
+            with open(self.ledger_path, 'r') as f:
+                entries = json.load(f)
+            # Forget files that were removed behind our back
+            self._entries = {path: entry for path, entry in entries.items() if Path(path).exists()}
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable temp file ledger {self.ledger_path}: {e}")
+
+    def _save(self):
+        try:
+            self.ledger_path.parent.mkdir(parents=True, exist_ok=True)
+            with open(self.ledger_path, 'w') as f:
+                json.dump(self._entries, f)
+        except Exception as e:
+            logger.warning(f"Failed to save temp file ledger: {e}")
+
+    def register(self, path: Path, session_id: str, name: str, category: str = CATEGORY_DERIVED) -> int:
+        """Track a newly written file and enforce the quota. Returns bytes freed by eviction."""
+        path = Path(path)
+        with self._lock:
+            self._entries[str(path)] = {
+                "session_id": session_id,
+                "name": name,
+                "category": category,
+                "size": path.stat().st_size if path.exists() else 0,
+                "last_access": time.time(),
+            }
+            self._save()
+        return self.enforce_quota(exclude={str(path)})
+
+    def choose_scratch_dir(self, needed_bytes: int) -> Path:
+        """The first scratch folder that keeps its minimum free space after writing `needed_bytes`."""
+        reserve = self.get_min_free_bytes()
+        report = []
+        for directory in self.get_scratch_dirs():
+            free = free_bytes(directory)
+            if free is None or free - needed_bytes >= reserve:
+                return Path(directory)
+            report.append(f"{directory}: {free // 2**20} MB free")
+        raise InsufficientScratchSpace(
+            f"Not enough disk space for a {needed_bytes // 2**20} MB file "
+            f"(keeping {reserve // 2**20} MB free): " + "; ".join(report)
+            + ". Free some space, clear derived data or add a scratch folder"
+        )
+
+    def touch(self, path: Path):
+        """Mark a tracked file as recently used."""
+        with self._lock:
+            entry = self._entries.get(str(path))
+            if entry is not None:
+                entry["last_access"] = time.time()
+
+    def unregister(self, path: Path):
+        """Stop tracking a file that its owner deleted."""
+        with self._lock:
+            if self._entries.pop(str(path), None) is not None:
+                self._save()
+
+    def forget_session(self, session_id: str):
+        """Stop tracking all files of a session whose directory was removed."""
+        with self._lock:
+            self._entries = {p: e for p, e in self._entries.items() if e["session_id"] != session_id}
+            self._save()
+
+    def total_bytes(self) -> int:
+        with self._lock:
+            return sum(entry["size"] for entry in self._entries.values())
+
+    def _dispose(self, path: str, entry: Dict[str, Any]):
+        # Called without holding our lock, since the callback takes the owner's locks
+        if self.on_evict is not None:
+            try:
+                self.on_evict(entry)
+            except Exception as e:
+                logger.warning(f"Eviction callback failed for {entry['name']}: {e}")
+        try:
+            Path(path).unlink(missing_ok=True)
+        except OSError as e:
+            logger.warning(f"Failed to delete derived file {path}: {e}")
+
+    def enforce_quota(self, exclude: Optional[set] = None) -> int:
+        """Evict least recently used files until usage fits the quota. Returns bytes freed."""
+        quota = self.get_quota_bytes()
+        if quota is None:
+            return 0
+
+        evicted = []
+        with self._lock:
+            excess = self.total_bytes() - quota
+            if excess <= 0:
+                return 0
+            candidates = sorted(
+                (p for p in self._entries if not exclude or p not in exclude),
+                key=lambda p: self._entries[p]["last_access"]
+            )
+            for path in candidates:
+                if excess <= 0:
+                    break
+                entry = self._entries.pop(path)
+                excess -= entry["size"]
+                evicted.append((path, entry))
+            self._save()
+
+        for path, entry in evicted:
+            self._dispose(path, entry)
+            logger.info(f"Evicted derived file {entry['name']} to stay within disk quota")
+
+        freed = sum(entry["size"] for _, entry in evicted)
+        if freed:
+            logger.info(f"Disk quota enforcement freed {freed / (1024 * 1024):.1f} MB")
+        return freed
+
+    def clear(self, session_id: Optional[str] = None, category: Optional[str] = None) -> Dict[str, int]:
+        """Remove all tracked files, optionally limited to a session and/or category."""
+        with self._lock:
+            removed = [
+                (p, e) for p, e in self._entries.items()
+                if (session_id is None or e["session_id"] == session_id)
+                and (category is None or e["category"] == category)
+            ]
+            for path, _ in removed:
+                del self._entries[path]
+            self._save()
+
+        for path, entry in removed:
+            self._dispose(path, entry)
+
+        freed = sum(entry["size"] for _, entry in removed)
+        logger.info(f"Cleared {len(removed)} derived files ({freed} bytes)")
+        return {"files_removed": len(removed), "bytes_freed": freed}
+
+    def _usage_by_location(self) -> List[Dict[str, Any]]:
+        """Tracked bytes (all sessions) and free space for each scratch folder."""
+        locations = [Path(d) for d in self.get_scratch_dirs()]
+        usage = {str(d): {"path": str(d), "num_files": 0, "bytes": 0} for d in locations}
+        for path, entry in self._entries.items():
+            for directory in locations:
+                if directory in Path(path).parents:
+                    usage[str(directory)]["num_files"] += 1
+                    usage[str(directory)]["bytes"] += entry["size"]
+                    break
+        for directory in locations:
+            usage[str(directory)]["free_bytes"] = free_bytes(directory)
+        return list(usage.values())
+
+    def get_usage(self, session_id: Optional[str] = None) -> Dict[str, Any]:
+        """Disk usage of tracked files, overall and per category."""
+        with self._lock:
+            entries = [e for e in self._entries.values() if session_id is None or e["session_id"] == session_id]
+            by_category: Dict[str, Dict[str, int]] = {}
+            for entry in entries:
+                stats = by_category.setdefault(entry["category"], {"num_files": 0, "bytes": 0})
+                stats["num_files"] += 1
+                stats["bytes"] += entry["size"]
+            return {
+                "num_files": len(entries),
+                "bytes": sum(e["size"] for e in entries),
+                "total_bytes_all_sessions": self.total_bytes(),
+                "quota_bytes": self.get_quota_bytes(),
+                "by_category": by_category,
+                "by_location": self._usage_by_location(),
+                "files": sorted(
+                    ({"name": e["name"], "category": e["category"], "size": e["size"], "last_access": e["last_access"]}
+                     for e in entries),
+                    key=lambda e: e["last_access"],
+                    reverse=True
+                ),
+            }
@@ -1,11 +1,12 @@
 """
 Temporal inference functionality for ARGscape.
-Handles tsdate-based temporal inference.
+Handles tsdate-based temporal inference, checks of its prerequisites before a
+re-dating job starts, and before/after comparison of node times afterwards.
 """
 
 import logging
 import os
-from typing import Dict, Tuple, Optional, Any
+from typing import Any, Callable, Dict, List, Optional, Tuple
 
 import numpy as np
 import tskit
@@ -30,6 +31,20 @@ else:
     logger.info("tsdate import skipped - temporal inference disabled by configuration")
 
 
+DATING_METHODS = ("variational_gamma", "inside_outside", "maximization")
+
+# Methods that need a population size prior
+NE_PRIOR_METHODS = ("inside_outside", "maximization")
+
+# Per-base, per-generation mutation rates outside this range are probably mistyped
+PLAUSIBLE_MUTATION_RATE = (1e-11, 1e-6)
+
+# Bins of log10(new time / old time) in the comparison histogram
+LOG_RATIO_BINS = np.linspace(-3, 3, 25)
+
+MAX_LISTED_CHANGES = 20
+
+
 def check_mutations_present(ts: tskit.TreeSequence) -> bool:
     """Return True if the tree sequence contains mutations."""
     return ts.num_mutations > 0
@@ -70,11 +85,17 @@ def run_tsdate_inference(
     split_disjoint: bool = True,
     filter_populations: bool = False,
     filter_individuals: bool = False,
-    filter_sites: bool = False
+    filter_sites: bool = False,
+    method: Optional[str] = None,
+    population_size: Optional[float] = None,
+    progress_callback: Optional[Callable[[float], None]] = None
 ) -> Tuple[tskit.TreeSequence, Dict[str, Any]]:
     """
     Run tsdate inference on a tree sequence.
 
+    `method` and `population_size` are passed to tsdate.date when given;
+    `progress_callback` receives the fraction done after each stage.
+
     Returns:
         A tuple of (dated TreeSequence, inference metadata).
     """
@@ -99,12 +120,20 @@ def run_tsdate_inference(
             filter_sites
         )
         logger.info("Preprocessing complete.")
+    if progress_callback:
+        progress_callback(0.2)
 
+    date_options = {}
+    if method is not None:
+        date_options["method"] = method
+    if population_size is not None:
+        date_options["population_size"] = population_size
     try:
         ts_with_times = tsdate.date(
             ts_copy,
             mutation_rate=mutation_rate,
-            progress=progress
+            progress=progress,
+            **date_options
         )
         logger.info("tsdate inference complete.")
     except Exception as e:
@@ -117,6 +146,8 @@ def run_tsdate_inference(
         "num_inferred_times": num_inferred,
         "total_nodes": ts_with_times.num_nodes,
         "mutation_rate": mutation_rate,
+        "method": method,
+        "population_size": population_size,
         "preprocessing": {
             "enabled": preprocess,
             "remove_telomeres": remove_telomeres if preprocess else None,
@@ -135,3 +166,99 @@ def run_tsdate_inference(
 
     logger.info(f"Inferred times for {num_inferred} internal nodes.")
     return ts_with_times, inference_info
+
+
+def validate_redating(
+    ts: tskit.TreeSequence,
+    mutation_rate: Optional[float],
+    method: Optional[str] = None,
+    population_size: Optional[float] = None
+) -> Dict[str, Any]:
+    """Check that a re-dating job can run before it is dispatched.
+
+    Errors block the job; warnings are shown for confirmation.
+    """
+    errors: List[str] = []
+    warnings: List[str] = []
+    if DISABLE_TSDATE:
+        errors.append("Temporal inference is disabled. Set DISABLE_TSDATE=0 to enable.")
+    elif not TSDATE_AVAILABLE:
+        errors.append("tsdate package is not available")
+    if not check_mutations_present(ts):
+        errors.append("tsdate inference requires tree sequences with mutations")
+    if method is not None and method not in DATING_METHODS:
+        errors.append(f"method must be one of {DATING_METHODS}")
+
+    if mutation_rate is None or mutation_rate <= 0:
+        errors.append("A positive mutation rate is required")
+    elif not PLAUSIBLE_MUTATION_RATE[0] <= mutation_rate <= PLAUSIBLE_MUTATION_RATE[1]:
+        warnings.append(
+            f"Mutation rate {mutation_rate:g} is outside the usual range "
+            f"{PLAUSIBLE_MUTATION_RATE[0]:g}-{PLAUSIBLE_MUTATION_RATE[1]:g} per base per generation"
+        )
+
+    if population_size is not None and population_size <= 0:
+        errors.append("The population size prior must be positive")
+    elif population_size is None and method in NE_PRIOR_METHODS:
+        errors.append(f"The {method} method needs a population size prior")
+
+    if ts.time_units not in ("generations", tskit.TIME_UNITS_UNKNOWN, "uncalibrated"):
+        warnings.append(f"Node times are in {ts.time_units}; tsdate writes times in generations")
+    if ts.num_sites and ts.num_mutations > 2 * ts.num_sites:
+        warnings.append("Many sites have recurrent mutations, which tsdate handles poorly")
+
+    return {
+        "valid": not errors,
+        "errors": errors,
+        "warnings": warnings,
+        "num_mutations": ts.num_mutations,
+        "num_sites": ts.num_sites,
+    }
+
+
+def compare_node_times(before: tskit.TreeSequence, after: tskit.TreeSequence) -> Dict[str, Any]:
+    """Diff node times of a tree sequence before and after re-dating.
+
+    Nodes are matched by ID, which preprocessing keeps unless it removes nodes;
+    in that case only the summary of each side is returned.
+    """
+    old_times = before.tables.nodes.time
+    new_times = after.tables.nodes.time
+
+    def summary(times: np.ndarray) -> Dict[str, float]:
+        return {"min": float(times.min()), "max": float(times.max()), "mean": float(times.mean())} if len(times) else {}
+
+    result: Dict[str, Any] = {
+        "before": {"time_units": before.time_units, **summary(old_times)},
+        "after": {"time_units": after.time_units, **summary(new_times)},
+        "comparable": before.num_nodes == after.num_nodes,
+    }
+    if not result["comparable"]:
+        return result
+
+    internal = np.ones(before.num_nodes, dtype=bool)
+    internal[before.samples()] = False
+    nodes = np.flatnonzero(internal)
+    old, new = old_times[nodes], new_times[nodes]
+    difference = new - old
+    positive = (old > 0) & (new > 0)
+    log_ratio = np.log10(new[positive] / old[positive])
+    counts, _ = np.histogram(np.clip(log_ratio, LOG_RATIO_BINS[0], LOG_RATIO_BINS[-1]), bins=LOG_RATIO_BINS)
+    correlation = float(np.corrcoef(old, new)[0, 1]) if len(nodes) > 1 and old.std() > 0 and new.std() > 0 else None
+    largest = np.argsort(-np.abs(difference))[:MAX_LISTED_CHANGES]
+
+    result.update({
+        "num_compared": int(len(nodes)),
+        "mean_absolute_change": float(np.abs(difference).mean()) if len(nodes) else 0.0,
+        "median_log10_ratio": float(np.median(log_ratio)) if len(log_ratio) else None,
+        "correlation": correlation,
+        "num_older": int((difference > 0).sum()),
+        "num_younger": int((difference < 0).sum()),
+        "log10_ratio_histogram": {"bin_edges": LOG_RATIO_BINS.tolist(), "counts": counts.tolist()},
+        "largest_changes": [
+            {"node_id": int(nodes[i]), "before": float(old[i]), "after": float(new[i])}
+            for i in largest
+        ],
+        "points": [[float(a), float(b)] for a, b in zip(old, new)],
+    })
+    return result
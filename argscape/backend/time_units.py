@@ -0,0 +1,175 @@
+"""
+Time units of node times, and conversion between them.
+A tree sequence's `time_units` says what its node times mean, but many files
+say "unknown" and tsinfer writes "uncalibrated" times that are not in any
+unit at all. A TimeCalibration reads the declared unit and, with a
+generation time and (for uncalibrated times) a mutation rate from the user,
+gives the factor from native times to generations or years. Statistics,
+axes and labels all convert through it, so a value is never shown in a unit
+it isn't in.
+"""
+
+import logging
+from typing import Any, Dict, List, Optional
+
+import tskit
+
+logger = logging.getLogger(__name__)
+
+UNIT_GENERATIONS = "generations"
+UNIT_YEARS = "years"
+UNIT_UNCALIBRATED = "uncalibrated"
+UNIT_UNKNOWN = tskit.TIME_UNITS_UNKNOWN
+
+# What values may be shown in: as stored, or converted
+DISPLAY_UNITS = ("native", UNIT_GENERATIONS, UNIT_YEARS)
+
+# Spellings seen in the wild; SLiM writes "ticks", which are generations in a WF model
+TIME_UNIT_ALIASES = {
+    "generation": UNIT_GENERATIONS,
+    "gens": UNIT_GENERATIONS,
+    "ticks": UNIT_GENERATIONS,
+    "year": UNIT_YEARS,
+    "yr": UNIT_YEARS,
+    "yrs": UNIT_YEARS,
+    "years ago": UNIT_YEARS,
+}
+
+# Branch-mode statistics in time units (sums of branch lengths); others are counts or ratios
+TIME_SCALED_STATISTICS = ("diversity", "segregating_sites", "sfs", "tmrca")
+
+
+def parse_time_units(ts: tskit.TreeSequence) -> str:
+    """The tree sequence's time units, with common spellings folded together."""
+    units = (ts.time_units or UNIT_UNKNOWN).strip().lower()
+    return TIME_UNIT_ALIASES.get(units, units)
+
+
+def mutation_rate_scale(ts: tskit.TreeSequence, mutation_rate: float) -> float:
+    """Generations per native time unit, from site diversity = mutation rate x branch diversity."""
+    if mutation_rate <= 0:
+        raise ValueError("mutation_rate must be positive")
+    if ts.num_sites == 0:
+        raise ValueError("Calibrating with a mutation rate needs sites")
+    site = float(ts.diversity(mode="site"))
+    branch = float(ts.diversity(mode="branch"))
+    if site <= 0 or branch <= 0:
+        raise ValueError("Calibrating with a mutation rate needs variable sites and non-zero branch lengths")
+    return site / (mutation_rate * branch)
+
+
+class TimeCalibration:
+    """Factors from a tree sequence's native times to generations and years."""
+
+    def __init__(
+        self,
+        native: str,
+        generation_time: Optional[float] = None,
+        mutation_rate: Optional[float] = None,
+        generations_per_unit: Optional[float] = None,
+        warnings: Optional[List[str]] = None
+    ):
+        if generation_time is not None and generation_time <= 0:
+            raise ValueError("generation_time must be positive")
+        self.native = native
+        self.generation_time = generation_time
+        self.mutation_rate = mutation_rate
+        self.generations_per_unit = generations_per_unit
+        self.warnings = warnings or []
+
+    @property
+    def calibrated(self) -> bool:
+        return self.generations_per_unit is not None
+
+    def factor(self, display: str) -> float:
+        """Multiplier from native times to `display` units; raises ValueError naming what is missing."""
+        if display not in DISPLAY_UNITS:
+            raise ValueError(f"Unknown time units '{display}'. Expected one of {DISPLAY_UNITS}")
+        if display == "native":
+            return 1.0
+        if self.generations_per_unit is None:
+            raise ValueError(
+                f"Times are {self.native}; give a mutation_rate to convert them to {display}"
+            )
+        if display == UNIT_GENERATIONS:
+            return self.generations_per_unit
+        if self.generation_time is None:
+            raise ValueError("Converting to years needs a generation_time (years per generation)")
+        return self.generations_per_unit * self.generation_time
+
+    def label(self, display: str) -> str:
+        if display != "native":
+            return display
+        # Unknown units are not labelled as if they were known
+        return "time" if self.native == UNIT_UNKNOWN else self.native
+
+    def default_display(self) -> str:
+        """Years whenever they can be computed, as setting a generation time promises."""
+        if self.generation_time is not None and self.calibrated and self.native != UNIT_YEARS:
+            return UNIT_YEARS
+        return "native"
+
+    def to_dict(self) -> Dict[str, Any]:
+        available = ["native"]
+        for display in (UNIT_GENERATIONS, UNIT_YEARS):
+            try:
+                self.factor(display)
+                available.append(display)
+            except ValueError:
+                pass
+        return {
+            "native": self.native,
+            "calibrated": self.calibrated,
+            "generation_time": self.generation_time,
+            "mutation_rate": self.mutation_rate,
+            "generations_per_unit": self.generations_per_unit,
+            "available": available,
+            "default_display": self.default_display(),
+            "warnings": self.warnings,
+        }
+
+
+def resolve_calibration(
+    ts: tskit.TreeSequence,
+    generation_time: Optional[float] = None,
+    mutation_rate: Optional[float] = None,
+    scale: Optional[float] = None
+) -> TimeCalibration:
+    """A calibration from the declared units plus whatever the user has supplied.
+
+    `scale` is a mutation_rate_scale already computed for this mutation rate.
+    """
+    native = parse_time_units(ts)
+    warnings: List[str] = []
+    generations_per_unit: Optional[float] = None
+    if native == UNIT_GENERATIONS:
+        generations_per_unit = 1.0
+    elif native == UNIT_YEARS:
+        if generation_time is not None:
+            generations_per_unit = 1.0 / generation_time
+        else:
+            warnings.append("Times are in years; set a generation time to show them in generations")
+    elif mutation_rate is not None:
+        generations_per_unit = scale if scale is not None else mutation_rate_scale(ts, mutation_rate)
+        warnings.append(
+            f"Times ({native}) were calibrated to generations with mutation rate {mutation_rate:g} "
+            f"({generations_per_unit:.4g} generations per unit)"
+        )
+    elif native == UNIT_UNKNOWN:
+        # What the views have always assumed; said out loud now
+        generations_per_unit = 1.0
+        warnings.append("Time units are unknown and assumed to be generations; give a mutation rate to calibrate instead")
+    else:
+        warnings.append(f"Times are {native}; give a mutation rate to convert them to generations or years")
+    return TimeCalibration(native, generation_time, mutation_rate, generations_per_unit, warnings)
+
+
+def scale_statistic(result: Dict[str, Any], calibration: TimeCalibration, display: str) -> Dict[str, Any]:
+    """Convert a compute_statistic result's values to `display` units where they are in time units."""
+    in_time = result["mode"] == "branch" and result["statistic"] in TIME_SCALED_STATISTICS
+    if in_time:
+        factor = calibration.factor(display)
+        if factor != 1.0:
+            result["columns"]["value"] = [None if v is None else v * factor for v in result["columns"]["value"]]
+    result["time_units"] = calibration.label(display) if in_time else None
+    return result
@@ -0,0 +1,125 @@
+"""
+Per-operation timing metrics.
+Requests and the expensive steps inside them (parsing, graph conversion,
+layout, metrics, rendering) are timed as spans and aggregated per session and
+overall, so the frontend's performance HUD can show where time goes.
+"""
+
+import collections
+import contextvars
+import functools
+import threading
+import time
+from contextlib import contextmanager
+from typing import Any, Callable, Dict, List, Optional
+
+# Span categories, in the order the HUD shows them
+CATEGORIES = ("request", "parse", "graph", "layout", "analysis", "render")
+
+MAX_RECENT_SPANS = 200
+
+# Session the current request belongs to, set by the timing middleware
+current_session: contextvars.ContextVar[Optional[str]] = contextvars.ContextVar("argscape_timing_session", default=None)
+
+
+class _Aggregate:
+    __slots__ = ("count", "total", "max", "last")
+
+    def __init__(self):
+        self.count = 0
+        self.total = 0.0
+        self.max = 0.0
+        self.last = 0.0
+
+    def add(self, seconds: float):
+        self.count += 1
+        self.total += seconds
+        self.max = max(self.max, seconds)
+        self.last = seconds
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "count": self.count,
+            "total_ms": round(self.total * 1000, 3),
+            "mean_ms": round(self.total / self.count * 1000, 3) if self.count else 0.0,
+            "max_ms": round(self.max * 1000, 3),
+            "last_ms": round(self.last * 1000, 3),
+        }
+
+
+class TimingRegistry:
+    """Aggregated spans per session (and overall), with the most recent spans kept in order."""
+
+    def __init__(self):
+        self._lock = threading.Lock()
+        self._aggregates: Dict[Optional[str], Dict[tuple, _Aggregate]] = collections.defaultdict(dict)
+        self._recent: Dict[Optional[str], collections.deque] = collections.defaultdict(
+            lambda: collections.deque(maxlen=MAX_RECENT_SPANS)
+        )
+
+    def record(self, category: str, name: str, seconds: float, session: Optional[str] = None):
+        session = session if session is not None else current_session.get()
+        span = {"category": category, "name": name, "ms": round(seconds * 1000, 3), "at": time.time()}
+        with self._lock:
+            for key in {None, session}:
+                self._aggregates[key].setdefault((category, name), _Aggregate()).add(seconds)
+                self._recent[key].append(span)
+
+    def get_metrics(self, session: Optional[str] = None) -> Dict[str, Any]:
+        """Totals per category and per operation for `session` (or all sessions with None)."""
+        with self._lock:
+            aggregates = dict(self._aggregates.get(session, {}))
+            recent = list(self._recent.get(session, []))
+        by_category = {category: _Aggregate() for category in CATEGORIES}
+        operations: List[Dict[str, Any]] = []
+        for (category, name), aggregate in sorted(aggregates.items(), key=lambda item: -item[1].total):
+            summary = by_category.setdefault(category, _Aggregate())
+            summary.count += aggregate.count
+            summary.total += aggregate.total
+            summary.max = max(summary.max, aggregate.max)
+            summary.last = aggregate.last
+            operations.append({"category": category, "name": name, **aggregate.to_dict()})
+        return {
+            "categories": {category: aggregate.to_dict() for category, aggregate in by_category.items()},
+            "operations": operations,
+            "recent": recent,
+        }
+
+    def reset(self, session: Optional[str] = None):
+        with self._lock:
+            for key in (session,) if session is not None else list(self._aggregates):
+                self._aggregates.pop(key, None)
+                self._recent.pop(key, None)
+
+
+timing_registry = TimingRegistry()
+
+
+@contextmanager
+def span(category: str, name: str):
+    """Time a block of code."""
+    start = time.perf_counter()
+    try:
+        yield
+    finally:
+        timing_registry.record(category, name, time.perf_counter() - start)
+
+
+def timed(category: str, name: Optional[str] = None) -> Callable:
+    """Decorator timing every call of a function."""
+    def decorator(func: Callable) -> Callable:
+        label = name or func.__name__
+
+        @functools.wraps(func)
+        def wrapper(*args, **kwargs):
+            with span(category, label):
+                return func(*args, **kwargs)
+        return wrapper
+    return decorator
+
+
+def get_performance_metrics(session: Optional[str] = None) -> Dict[str, Any]:
+    return {
+        "session": timing_registry.get_metrics(session) if session is not None else None,
+        "overall": timing_registry.get_metrics(None),
+    }
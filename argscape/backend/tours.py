@@ -0,0 +1,202 @@
+"""
+Guided tours for first-run onboarding.
+Tour definitions live here and each user's progress is stored in their config
+directory, so a tour continues where it left off across windows and restarts.
+Tours can also be suggested for a loaded file, e.g. the simplification tour
+when an ARG is too big to draw comfortably.
+"""
+
+import json
+import logging
+import threading
+import time
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+import tskit
+
+from argscape.backend.settings import get_config_dir
+
+logger = logging.getLogger(__name__)
+
+TOURS_FILENAME = "tours.json"
+SAMPLE_FILENAME = "tutorial_sample.trees"
+
+# ARGs larger than this trigger the "large_arg" tour
+LARGE_ARG_NODE_THRESHOLD = 10_000
+
+# Tour states
+TOUR_NOT_STARTED = "not_started"
+TOUR_ACTIVE = "active"
+TOUR_COMPLETED = "completed"
+TOUR_DISMISSED = "dismissed"
+
+# Step "action" values tell the frontend what the step's button does
+TOURS: Dict[str, Dict[str, Any]] = {
+    "first_run": {
+        "title": "Welcome to ARGscape",
+        "auto_start": True,
+        "steps": [
+            {
+                "id": "welcome",
+                "title": "Welcome",
+                "body": "ARGscape visualizes ancestral recombination graphs stored as tskit tree sequences.",
+            },
+            {
+                "id": "load_sample",
+                "title": "Load an example",
+                "body": "Load a small simulated ARG to explore, or upload your own .trees file.",
+                "action": "load_sample",
+                "route": "/",
+            },
+            {
+                "id": "graph_view",
+                "title": "The graph view",
+                "body": "Samples sit at the bottom and ancestors above them by time. Click a node to see its details.",
+                "route": "/visualize/{filename}",
+            },
+            {
+                "id": "genome_navigation",
+                "title": "Move along the genome",
+                "body": "Use the genomic range controls to show the local trees in a region of the sequence.",
+                "route": "/visualize/{filename}",
+            },
+            {
+                "id": "done",
+                "title": "You're set",
+                "body": "Tutorials explain the concepts behind ARGs in more depth whenever you want them.",
+                "route": "/tutorials",
+            },
+        ],
+    },
+    "large_arg": {
+        "title": "Working with a large ARG",
+        "auto_start": False,
+        "steps": [
+            {
+                "id": "too_big",
+                "title": "This ARG is large",
+                "body": "With more than {threshold} nodes, drawing everything at once is slow and hard to read.",
+            },
+            {
+                "id": "simplify",
+                "title": "Simplify to fewer samples",
+                "body": "Simplifying to a subset of samples keeps only their ancestry and shrinks the graph.",
+                "action": "simplify",
+            },
+            {
+                "id": "subset_region",
+                "title": "Or look at a region",
+                "body": "Restricting the genomic range shows only the trees in that region.",
+            },
+        ],
+    },
+}
+
+
+def _public_definition(tour_id: str) -> Dict[str, Any]:
+    tour = TOURS[tour_id]
+    steps = [
+        {**step, "body": step["body"].replace("{threshold}", f"{LARGE_ARG_NODE_THRESHOLD:,}")}
+        for step in tour["steps"]
+    ]
+    return {"id": tour_id, "title": tour["title"], "auto_start": tour["auto_start"], "steps": steps}
+
+
+class TourStore:
+    """Per-user tour progress, persisted as JSON."""
+
+    def __init__(self, path: Optional[Path] = None):
+        self.path = path or get_config_dir() / TOURS_FILENAME
+        self._lock = threading.Lock()
+        self._progress: Dict[str, Dict[str, Any]] = {}
+        self._load()
+
+    def _load(self):
+        if not self.path.exists():
+            return
+        try:
+            with open(self.path, "r") as f:
+                stored = json.load(f)
+            self._progress = {k: v for k, v in stored.items() if k in TOURS}
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable tour progress {self.path}: {e}")
+
+    def _save(self):
+        try:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            with open(self.path, "w") as f:
+                json.dump(self._progress, f, indent=2)
+        except Exception as e:
+            logger.warning(f"Failed to save tour progress to {self.path}: {e}")
+
+    def _state(self, tour_id: str) -> Dict[str, Any]:
+        return self._progress.get(tour_id, {"status": TOUR_NOT_STARTED, "step": 0, "updated_at": None})
+
+    def get(self, tour_id: str) -> Dict[str, Any]:
+        if tour_id not in TOURS:
+            raise ValueError(f"Unknown tour '{tour_id}'. Expected one of {tuple(TOURS)}")
+        with self._lock:
+            state = dict(self._state(tour_id))
+        return {**_public_definition(tour_id), **state}
+
+    def get_all(self) -> List[Dict[str, Any]]:
+        return [self.get(tour_id) for tour_id in TOURS]
+
+    def apply(self, tour_id: str, action: str) -> Dict[str, Any]:
+        """Apply "start", "advance", "back", "dismiss" or "reset" to a tour."""
+        if tour_id not in TOURS:
+            raise ValueError(f"Unknown tour '{tour_id}'. Expected one of {tuple(TOURS)}")
+        num_steps = len(TOURS[tour_id]["steps"])
+        with self._lock:
+            state = dict(self._state(tour_id))
+            if action == "start":
+                state.update(status=TOUR_ACTIVE, step=0)
+            elif action == "advance":
+                if state["status"] != TOUR_ACTIVE:
+                    raise ValueError(f"Tour '{tour_id}' is not active")
+                if state["step"] + 1 >= num_steps:
+                    state["status"] = TOUR_COMPLETED
+                else:
+                    state["step"] += 1
+            elif action == "back":
+                if state["status"] != TOUR_ACTIVE:
+                    raise ValueError(f"Tour '{tour_id}' is not active")
+                state["step"] = max(0, state["step"] - 1)
+            elif action == "dismiss":
+                state["status"] = TOUR_DISMISSED
+            elif action == "reset":
+                state.update(status=TOUR_NOT_STARTED, step=0)
+            else:
+                raise ValueError("action must be one of start, advance, back, dismiss, reset")
+            state["updated_at"] = time.time()
+            self._progress[tour_id] = state
+            self._save()
+        return self.get(tour_id)
+
+    def suggestions(self, ts: Optional[tskit.TreeSequence] = None) -> List[str]:
+        """Tours worth offering now: unstarted auto-start tours, and tours triggered by the loaded file."""
+        suggested = [
+            tour_id for tour_id, tour in TOURS.items()
+            if tour["auto_start"] and self._state(tour_id)["status"] == TOUR_NOT_STARTED
+        ]
+        if ts is not None and ts.num_nodes > LARGE_ARG_NODE_THRESHOLD:
+            if self._state("large_arg")["status"] not in (TOUR_ACTIVE, TOUR_DISMISSED):
+                suggested.append("large_arg")
+        return suggested
+
+
+def build_sample_tree_sequence() -> tskit.TreeSequence:
+    """Small, fixed example ARG for the first-run tour."""
+    import msprime
+    ts = msprime.sim_ancestry(
+        samples=10,
+        sequence_length=100_000,
+        recombination_rate=1e-8,
+        population_size=10_000,
+        random_seed=42,
+    )
+    return msprime.sim_mutations(ts, rate=1e-8, random_seed=42)
+
+
+tour_store = TourStore()
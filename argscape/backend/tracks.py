@@ -0,0 +1,385 @@
+"""
+Genome tracks: one shape for every value along the sequence.
+A windowed statistic, a missingness or selection scan, or an imported BED or
+BEDgraph file is turned into a track: sorted intervals with one or more
+series of values (one per sample set, sample or score), its value range and
+how finely it is binned. Tracks are registered per file in the session, so
+any analysis that returns window_left/window_right/value columns can be
+drawn, zoomed, merged with other tracks and exported without code of its own.
+"""
+
+import csv
+import io
+import logging
+import re
+from typing import Any, Dict, List, Optional, Sequence
+
+import numpy as np
+
+from argscape.backend.liftover import parse_bed
+
+logger = logging.getLogger(__name__)
+
+TRACK_SOURCES = ("statistic", "missingness", "sweep_scan", "bed", "bedgraph", "merged")
+# How a viewport with more intervals than bins reduces them
+BINNING_METHODS = ("mean", "max", "min")
+MERGE_OPERATIONS = ("stack", "mean", "sum", "difference", "ratio")
+TRACK_EXPORT_FORMATS = ("bedgraph", "csv")
+# Table columns that split a statistic's rows into series rather than hold values
+SERIES_COLUMNS = ("sample_set", "reference_set", "sample", "frequency")
+SWEEP_SCAN_SERIES = ("scores", "haplotype_homozygosity", "relative_tmrca", "tajimas_d")
+DEFAULT_MAX_BINS = 1000
+MAX_BINS = 20000
+MAX_TRACK_INTERVALS = 1_000_000
+TRACK_NAME_PATTERN = re.compile(r"^[A-Za-z0-9_.\- ]{1,64}$")
+
+
+def validate_track_name(name: str) -> str:
+    if not isinstance(name, str) or not TRACK_NAME_PATTERN.match(name.strip()):
+        raise ValueError("Track names are 1-64 letters, digits, spaces, '.', '_' or '-'")
+    return name.strip()
+
+
+def _values(array: np.ndarray) -> List[Optional[float]]:
+    return [float(v) if np.isfinite(v) else None for v in array]
+
+
+def _as_array(values: Sequence[Optional[float]]) -> np.ndarray:
+    return np.array([np.nan if v is None else v for v in values], dtype=float)
+
+
+def make_track(
+    name: str,
+    source: str,
+    left: Sequence[float],
+    right: Sequence[float],
+    series: Dict[str, Sequence[Optional[float]]],
+    unit: Optional[str] = None,
+    params: Optional[Dict[str, Any]] = None,
+    labels: Optional[Sequence[Optional[str]]] = None,
+    binning: str = "mean"
+) -> Dict[str, Any]:
+    """A track from intervals and their series of values, sorted by position, with its range and binning.
+
+    Raises:
+        ValueError: If lengths differ, an interval is empty or there are too many intervals
+    """
+    if source not in TRACK_SOURCES:
+        raise ValueError(f"Unknown track source '{source}'. Expected one of {TRACK_SOURCES}")
+    if binning not in BINNING_METHODS:
+        raise ValueError(f"Unknown binning method '{binning}'. Expected one of {BINNING_METHODS}")
+    left, right = np.asarray(left, dtype=float), np.asarray(right, dtype=float)
+    if len(left) != len(right) or any(len(values) != len(left) for values in series.values()):
+        raise ValueError("A track needs one value per interval in every series")
+    if len(left) > MAX_TRACK_INTERVALS:
+        raise ValueError(f"Tracks hold at most {MAX_TRACK_INTERVALS} intervals, got {len(left)}")
+    if not series:
+        raise ValueError("A track needs at least one series")
+    if np.any(right <= left):
+        raise ValueError("Every track interval needs left < right")
+
+    order = np.lexsort((right, left))
+    arrays = {label: _as_array(values)[order] for label, values in series.items()}
+    finite = np.concatenate([a[np.isfinite(a)] for a in arrays.values()])
+    widths = (right - left)[order]
+    return {
+        "name": validate_track_name(name),
+        "source": source,
+        "unit": unit,
+        "params": params or {},
+        "left": [float(x) for x in left[order]],
+        "right": [float(x) for x in right[order]],
+        "series": {label: _values(a) for label, a in arrays.items()},
+        "labels": [labels[i] for i in order] if labels is not None else None,
+        "value_range": [float(finite.min()), float(finite.max())] if len(finite) else None,
+        "binning": {
+            "method": binning,
+            "num_intervals": int(len(left)),
+            "min_width": float(widths.min()) if len(widths) else None,
+            "max_width": float(widths.max()) if len(widths) else None,
+            # Overlapping intervals (BED features) are averaged where they stack up
+            "overlapping": bool(np.any(left[order][1:] < right[order][:-1])),
+        },
+    }
+
+
+def from_columns(
+    name: str,
+    source: str,
+    columns: Dict[str, List[Any]],
+    unit: Optional[str] = None,
+    params: Optional[Dict[str, Any]] = None
+) -> Dict[str, Any]:
+    """A track from a windowed table; rows are split into series by any of SERIES_COLUMNS present."""
+    if not {"window_left", "window_right", "value"} <= set(columns):
+        raise ValueError("A track table needs window_left, window_right and value columns")
+    split_by = [c for c in SERIES_COLUMNS if c in columns]
+    windows: Dict[tuple, int] = {}
+    series: Dict[str, Dict[int, Optional[float]]] = {}
+    for row, (left, right) in enumerate(zip(columns["window_left"], columns["window_right"])):
+        index = windows.setdefault((float(left), float(right)), len(windows))
+        label = "/".join(str(columns[c][row]) for c in split_by) if split_by else "value"
+        series.setdefault(label, {})[index] = columns["value"][row]
+    bounds = list(windows)
+    return make_track(
+        name, source,
+        [b[0] for b in bounds], [b[1] for b in bounds],
+        {label: [values.get(i) for i in range(len(bounds))] for label, values in series.items()},
+        unit=unit, params=params,
+    )
+
+
+def from_sweep_scan(name: str, result: Dict[str, Any], params: Optional[Dict[str, Any]] = None) -> Dict[str, Any]:
+    """The sweep scan's combined score and its components as one track."""
+    return make_track(
+        name, "sweep_scan",
+        [w["left"] for w in result["windows"]], [w["right"] for w in result["windows"]],
+        {key: result[key] for key in SWEEP_SCAN_SERIES},
+        params={**(params or {}), "candidates": result.get("candidates", [])},
+        binning="max",
+    )
+
+
+def parse_track_file(
+    text: str,
+    kind: str,
+    name: str,
+    sequence_length: float,
+    contig_map: Optional[Any] = None
+) -> Dict[str, Any]:
+    """A track from BED features (value 1 over each, names kept as labels) or a BEDgraph's values.
+
+    Chromosome names are placed with the file's contig map when it has one;
+    otherwise the file must name a single chromosome. Intervals beyond the
+    sequence are clipped, and those wholly outside it are dropped.
+    """
+    if kind == "bed":
+        rows = [(r["chrom"], float(r["start"]), float(r["end"]), 1.0, r.get("name")) for r in parse_bed(text)]
+    elif kind == "bedgraph":
+        rows = []
+        for line_number, line in enumerate(text.splitlines(), 1):
+            fields = line.split()
+            if not fields or fields[0] in ("track", "browser") or line.startswith("#"):
+                continue
+            if len(fields) < 4:
+                raise ValueError(f"Expected 4 columns on line {line_number}")
+            try:
+                rows.append((fields[0], float(fields[1]), float(fields[2]), float(fields[3]), None))
+            except ValueError:
+                raise ValueError(f"Invalid interval on line {line_number}")
+    else:
+        raise ValueError(f"Unknown track file kind '{kind}'. Expected bed or bedgraph")
+    if not rows:
+        raise ValueError(f"The {kind} file has no intervals")
+
+    chroms = sorted({row[0] for row in rows})
+    if contig_map is None and len(chroms) > 1:
+        raise ValueError(f"The file names {len(chroms)} chromosomes ({', '.join(chroms[:5])}); load a contig map to place them")
+    left, right, values, labels = [], [], [], []
+    dropped = 0
+    for chrom, start, end, value, label in rows:
+        if contig_map is not None:
+            start, end = contig_map.to_global(chrom, start), contig_map.to_global(chrom, end)
+        start, end = max(start, 0.0), min(end, sequence_length)
+        if end <= start:
+            dropped += 1
+            continue
+        left.append(start)
+        right.append(end)
+        values.append(value)
+        labels.append(label)
+    if not left:
+        raise ValueError(f"No {kind} interval overlaps the sequence (length {sequence_length:g})")
+    if dropped:
+        logger.info(f"Dropped {dropped} {kind} intervals outside the sequence for track {name}")
+    return make_track(
+        name, kind, left, right, {"value": values},
+        params={"chromosomes": chroms, "dropped": dropped},
+        labels=labels if kind == "bed" else None,
+    )
+
+
+def _integrals(left: np.ndarray, right: np.ndarray, values: np.ndarray, points: np.ndarray) -> tuple:
+    """The integral of the track's values, and the length it covers, from 0 to each of `points`."""
+    present = np.isfinite(values)
+    left, right, values = left[present], right[present], values[present]
+    events = np.concatenate([left, right])
+    order = np.argsort(events, kind="stable")
+    events = events[order]
+    # Integrals of a sum of boxes are piecewise linear, with slope changes at each box edge
+    integrals = []
+    for weights in (values, np.ones_like(values)):
+        slopes = np.cumsum(np.concatenate([weights, -weights])[order])
+        cumulative = np.concatenate([[0.0], np.cumsum(slopes[:-1] * np.diff(events))]) if len(events) else np.zeros(0)
+        integrals.append(np.interp(points, events, cumulative) if len(events) else np.zeros(len(points)))
+    return integrals[0], integrals[1]
+
+
+def _segment_means(left: np.ndarray, right: np.ndarray, values: np.ndarray, edges: np.ndarray) -> np.ndarray:
+    """Coverage-weighted mean of the values over each segment between `edges`; NaN where nothing is covered."""
+    integral, covered = _integrals(left, right, values, edges)
+    length = np.diff(covered)
+    with np.errstate(divide="ignore", invalid="ignore"):
+        return np.where(length > 0, np.diff(integral) / length, np.nan)
+
+
+def _segment_extremes(left: np.ndarray, right: np.ndarray, values: np.ndarray, edges: np.ndarray, method: str) -> np.ndarray:
+    present = np.isfinite(values)
+    left, right, values = left[present], right[present], values[present]
+    num_bins = len(edges) - 1
+    first = np.clip(np.searchsorted(edges, left, side="right") - 1, 0, num_bins - 1)
+    last = np.clip(np.searchsorted(edges, right, side="left") - 1, 0, num_bins - 1)
+    inside = (right > edges[0]) & (left < edges[-1])
+    first, last, values = first[inside], last[inside], values[inside]
+    # Expand each interval to every bin it touches
+    counts = last - first + 1
+    offsets = np.arange(counts.sum()) - np.repeat(np.cumsum(counts) - counts, counts)
+    out = np.full(num_bins, np.nan)
+    (np.fmax if method == "max" else np.fmin).at(out, np.repeat(first, counts) + offsets, np.repeat(values, counts))
+    return out
+
+
+def query_track(
+    track: Dict[str, Any],
+    start: float = 0.0,
+    end: Optional[float] = None,
+    max_bins: int = DEFAULT_MAX_BINS,
+    method: Optional[str] = None,
+    series: Optional[List[str]] = None
+) -> Dict[str, Any]:
+    """The track's intervals over [start, end): as stored when there are at most `max_bins`, else binned."""
+    method = method or track["binning"]["method"]
+    if method not in BINNING_METHODS:
+        raise ValueError(f"Unknown binning method '{method}'. Expected one of {BINNING_METHODS}")
+    if not 1 <= max_bins <= MAX_BINS:
+        raise ValueError(f"max_bins must be between 1 and {MAX_BINS}")
+    left, right = np.asarray(track["left"]), np.asarray(track["right"])
+    end = float(right.max()) if end is None else end
+    if end <= start:
+        raise ValueError("The viewport needs start < end")
+    labels = series or list(track["series"])
+    unknown = [label for label in labels if label not in track["series"]]
+    if unknown:
+        raise ValueError(f"Unknown series {unknown}. Track {track['name']} has {list(track['series'])}")
+
+    visible = np.flatnonzero((right > start) & (left < end))
+    binned = len(visible) > max_bins
+    if binned:
+        edges = np.linspace(start, end, max_bins + 1)
+        reduce = _segment_means if method == "mean" else lambda l, r, v, e: _segment_extremes(l, r, v, e, method)
+        values = {label: reduce(left, right, _as_array(track["series"][label]), edges) for label in labels}
+        bin_left, bin_right = edges[:-1], edges[1:]
+    else:
+        values = {label: _as_array(track["series"][label])[visible] for label in labels}
+        bin_left, bin_right = np.maximum(left[visible], start), np.minimum(right[visible], end)
+    finite = np.concatenate([v[np.isfinite(v)] for v in values.values()]) if values else np.zeros(0)
+    return {
+        "name": track["name"],
+        "start": float(start),
+        "end": float(end),
+        "binned": bool(binned),
+        "method": method if binned else None,
+        "num_intervals": int(len(visible)),
+        "left": [float(x) for x in bin_left],
+        "right": [float(x) for x in bin_right],
+        "series": {label: _values(v) for label, v in values.items()},
+        "labels": [track["labels"][i] for i in visible] if track.get("labels") and not binned else None,
+        "value_range": [float(finite.min()), float(finite.max())] if len(finite) else None,
+        "track_value_range": track["value_range"],
+        "unit": track["unit"],
+    }
+
+
+def merge_tracks(name: str, tracks: List[Dict[str, Any]], operation: str = "stack") -> Dict[str, Any]:
+    """Tracks on a shared grid of all their breakpoints: side by side ("stack") or combined into one series.
+
+    "difference" and "ratio" take exactly two series (the first minus, or
+    over, the second); "mean" and "sum" combine every series, skipping
+    missing values.
+    """
+    if operation not in MERGE_OPERATIONS:
+        raise ValueError(f"Unknown merge operation '{operation}'. Expected one of {MERGE_OPERATIONS}")
+    if len(tracks) < 2:
+        raise ValueError("Merging needs at least two tracks")
+    edges = np.unique(np.concatenate([np.concatenate([t["left"], t["right"]]) for t in tracks]))
+    columns = {}
+    for track in tracks:
+        left, right = np.asarray(track["left"]), np.asarray(track["right"])
+        for label, values in track["series"].items():
+            columns[f"{track['name']}:{label}"] = _segment_means(left, right, _as_array(values), edges)
+    if operation in ("difference", "ratio") and len(columns) != 2:
+        raise ValueError(f"A {operation} needs exactly two series, got {len(columns)}: {list(columns)}")
+
+    stacked = np.vstack(list(columns.values()))
+    with np.errstate(divide="ignore", invalid="ignore"):
+        if operation == "stack":
+            series = columns
+        elif operation == "difference":
+            series = {"difference": stacked[0] - stacked[1]}
+        elif operation == "ratio":
+            series = {"ratio": np.where(stacked[1] != 0, stacked[0] / stacked[1], np.nan)}
+        else:
+            present = np.isfinite(stacked).any(axis=0)
+            total = np.nansum(stacked, axis=0)
+            if operation == "mean":
+                total = total / np.maximum(np.isfinite(stacked).sum(axis=0), 1)
+            series = {operation: np.where(present, total, np.nan)}
+    # Grid segments no track covers are gaps, not intervals
+    keep = np.isfinite(stacked).any(axis=0)
+    units = {t["unit"] for t in tracks}
+    return make_track(
+        name, "merged", edges[:-1][keep], edges[1:][keep],
+        {label: values[keep] for label, values in series.items()},
+        unit=units.pop() if len(units) == 1 and operation != "ratio" else None,
+        params={"tracks": [t["name"] for t in tracks], "operation": operation},
+        binning=tracks[0]["binning"]["method"],
+    )
+
+
+def describe_track(track: Dict[str, Any]) -> Dict[str, Any]:
+    """A track without its values, for listings."""
+    return {
+        "name": track["name"],
+        "source": track["source"],
+        "unit": track["unit"],
+        "params": track["params"],
+        "series": list(track["series"]),
+        "value_range": track["value_range"],
+        "binning": track["binning"],
+        "extent": [track["left"][0], max(track["right"])] if track["left"] else None,
+    }
+
+
+def export_track(
+    track: Dict[str, Any],
+    table_format: str,
+    series: Optional[str] = None,
+    chrom: str = "1",
+    contig_map: Optional[Any] = None
+) -> bytes:
+    """A track as BEDgraph (one series; contig coordinates when there is a contig map) or CSV (every series)."""
+    if table_format not in TRACK_EXPORT_FORMATS:
+        raise ValueError(f"format must be one of {TRACK_EXPORT_FORMATS}")
+    out = io.StringIO()
+    if table_format == "csv":
+        writer = csv.writer(out)
+        labels = list(track["series"])
+        writer.writerow(["left", "right", *labels] + (["label"] if track.get("labels") else []))
+        for i, (left, right) in enumerate(zip(track["left"], track["right"])):
+            values = ["" if track["series"][label][i] is None else track["series"][label][i] for label in labels]
+            writer.writerow([left, right, *values] + ([track["labels"][i] or ""] if track.get("labels") else []))
+        return out.getvalue().encode("utf-8")
+
+    if series is None and len(track["series"]) > 1:
+        raise ValueError(f"BEDgraph holds one series; choose one of {list(track['series'])}")
+    label = series or next(iter(track["series"]))
+    if label not in track["series"]:
+        raise ValueError(f"Unknown series '{label}'. Track {track['name']} has {list(track['series'])}")
+    out.write(f'track type=bedGraph name="{track["name"]} {label}"\n')
+    for left, right, value in zip(track["left"], track["right"], track["series"][label]):
+        if value is None:
+            continue
+        parts = contig_map.split_interval(left, right) if contig_map is not None else [{"contig": chrom, "start": left, "end": right}]
+        for part in parts:
+            out.write(f"{part['contig']}\t{int(part['start'])}\t{int(np.ceil(part['end']))}\t{value:g}\n")
+    return out.getvalue().encode("utf-8")
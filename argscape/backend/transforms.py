@@ -0,0 +1,152 @@
+"""
+Declarative transform stack for the display tree sequence.
+A file's stack is an ordered list of steps (trim, simplify, resolve
+polytomies, recalibrate times), each with parameters and an enabled flag.
+The display tree sequence is always rebuilt from the loaded original, so
+steps can be reordered, toggled or edited without losing anything. Results
+after each step are cached, so editing a late step only reruns the steps
+from there on.
+"""
+
+import json
+import logging
+import time
+from typing import Any, Dict, List, Optional, Tuple
+
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.ts_cache import TreeSequenceCache
+from argscape.backend.tskit_utils.polytomies import resolve_polytomies
+
+logger = logging.getLogger(__name__)
+
+TRANSFORM_OPERATIONS = ("trim", "simplify", "resolve_polytomies", "recalibrate_times")
+RECALIBRATION_METHODS = ("scale", "tsdate")
+
+# Accepted parameters per operation, with defaults
+TRANSFORM_PARAMS: Dict[str, Dict[str, Any]] = {
+    "trim": {"left": None, "right": None},
+    "simplify": {"samples": None, "keep_unary": False, "filter_sites": True},
+    "resolve_polytomies": {"method": "random", "random_seed": None},
+    "recalibrate_times": {"method": "scale", "factor": 1.0, "mutation_rate": None},
+}
+
+# Intermediate results kept across stack edits (each is a full tree sequence)
+MAX_CACHED_STAGES = 16
+
+_cache = TreeSequenceCache("transform_stages", MAX_CACHED_STAGES)
+
+
+def normalize_stack(steps: List[Dict[str, Any]]) -> List[Dict[str, Any]]:
+    """Fill in defaults and check each step; raises ValueError naming the bad step."""
+    normalized = []
+    for index, step in enumerate(steps):
+        operation = step.get("operation")
+        if operation not in TRANSFORM_OPERATIONS:
+            raise ValueError(f"Step {index + 1}: unknown operation '{operation}'. Expected one of {TRANSFORM_OPERATIONS}")
+        params = dict(step.get("params") or {})
+        unknown = sorted(set(params) - set(TRANSFORM_PARAMS[operation]))
+        if unknown:
+            raise ValueError(f"Step {index + 1} ({operation}): unknown parameters {unknown}. Expected some of {list(TRANSFORM_PARAMS[operation])}")
+        params = {**TRANSFORM_PARAMS[operation], **params}
+        if operation == "trim" and params["left"] is not None and params["right"] is not None and params["left"] >= params["right"]:
+            raise ValueError(f"Step {index + 1} (trim): left must be less than right")
+        if operation == "recalibrate_times":
+            if params["method"] not in RECALIBRATION_METHODS:
+                raise ValueError(f"Step {index + 1} (recalibrate_times): method must be one of {RECALIBRATION_METHODS}")
+            if params["method"] == "scale" and not params["factor"] > 0:
+                raise ValueError(f"Step {index + 1} (recalibrate_times): factor must be positive")
+            if params["method"] == "tsdate" and not (params["mutation_rate"] or 0) > 0:
+                raise ValueError(f"Step {index + 1} (recalibrate_times): tsdate needs a positive mutation_rate")
+        normalized.append({"operation": operation, "params": params, "enabled": bool(step.get("enabled", True))})
+    return normalized
+
+
+def _trim(ts: tskit.TreeSequence, left: Optional[float], right: Optional[float]) -> tskit.TreeSequence:
+    left = 0 if left is None else left
+    right = ts.sequence_length if right is None else right
+    if not 0 <= left < right <= ts.sequence_length:
+        raise ValueError(f"Trim interval [{left}, {right}) is outside the sequence (length {ts.sequence_length})")
+    if left > 0 or right < ts.sequence_length:
+        ts = ts.keep_intervals([[left, right]], simplify=False)
+    return ts.trim()
+
+
+def _scale_times(ts: tskit.TreeSequence, factor: float) -> tskit.TreeSequence:
+    tables = ts.dump_tables()
+    tables.nodes.time = tables.nodes.time * factor
+    # Unknown mutation times are NaN and stay unknown
+    tables.mutations.time = tables.mutations.time * factor
+    tables.migrations.time = tables.migrations.time * factor
+    return tables.tree_sequence()
+
+
+def run_step(ts: tskit.TreeSequence, step: Dict[str, Any], cancel_token: Optional[CancellationToken] = None) -> tskit.TreeSequence:
+    params = step["params"]
+    operation = step["operation"]
+    if operation == "trim":
+        return _trim(ts, params["left"], params["right"])
+    if operation == "simplify":
+        return ts.simplify(samples=params["samples"], keep_unary=params["keep_unary"], filter_sites=params["filter_sites"])
+    if operation == "resolve_polytomies":
+        return resolve_polytomies(ts, method=params["method"], random_seed=params["random_seed"], cancel_token=cancel_token)[0]
+    if params["method"] == "scale":
+        return _scale_times(ts, params["factor"])
+    # Imported here because tsdate is optional and slow to import
+    from argscape.backend.temporal_inference import run_tsdate_inference
+    return run_tsdate_inference(ts, mutation_rate=params["mutation_rate"], progress=False)[0]
+
+
+def _stage_key(steps: List[Dict[str, Any]]) -> str:
+    return json.dumps(steps, sort_keys=True)
+
+
+def apply_transform_stack(
+    ts: tskit.TreeSequence,
+    steps: List[Dict[str, Any]],
+    cancel_token: Optional[CancellationToken] = None
+) -> Tuple[tskit.TreeSequence, List[Dict[str, Any]]]:
+    """Run the enabled steps in order, starting from the longest cached prefix.
+
+    Args:
+        ts: The loaded original
+        steps: A normalized stack, see normalize_stack
+
+    Returns:
+        The display tree sequence and a per-step report
+    """
+    enabled = [step for step in steps if step["enabled"]]
+    current, start = ts, 0
+    for length in range(len(enabled), 0, -1):
+        cached = _cache.get(ts, _stage_key(enabled[:length]))
+        if cached is not None:
+            current, start = cached, length
+            break
+
+    report = []
+    position = 0
+    for step in steps:
+        entry = {"operation": step["operation"], "enabled": step["enabled"]}
+        if step["enabled"]:
+            if position >= start:
+                check_cancelled(cancel_token)
+                started = time.perf_counter()
+                try:
+                    current = run_step(current, step, cancel_token)
+                except ValueError as e:
+                    raise ValueError(f"Step {len(report) + 1} ({step['operation']}): {e}")
+                entry.update(cached=False, seconds=round(time.perf_counter() - started, 3))
+                _cache.put(ts, current, _stage_key(enabled[:position + 1]))
+            else:
+                entry["cached"] = True
+            position += 1
+            entry.update(num_nodes=current.num_nodes, num_edges=current.num_edges, num_trees=current.num_trees)
+        report.append(entry)
+
+    logger.info(f"Applied transform stack ({len(enabled)} of {len(steps)} steps enabled, {start} from cache)")
+    return current, report
+
+
+def clear_transform_cache(ts: tskit.TreeSequence):
+    _cache.evict(ts)
@@ -0,0 +1,85 @@
+"""
+Caches of results derived from loaded tree sequences.
+Display times, edge indexes, GPU branch segments, node attributes and
+transform stages are all expensive to compute and only valid for the tree
+sequence they came from. Each TreeSequenceCache keeps them least recently
+used first, keyed by the tree sequence object through a weak reference: the
+cache never keeps a tree sequence alive, its entries go when the tree
+sequence is garbage collected, and they can't be served to a later object
+that reuses the same id(). Deleting a file evicts its entries at once (see
+evict_tree_sequence), without waiting for the collector.
+"""
+
+import threading
+import weakref
+from collections import OrderedDict
+from typing import Any, Dict, Hashable, Optional, Tuple
+
+import tskit
+
+# Every cache, so a deleted file's entries can be evicted from all of them
+_caches: "weakref.WeakSet[TreeSequenceCache]" = weakref.WeakSet()
+
+
+class TreeSequenceCache:
+    """A least-recently-used map from (tree sequence, key) to a derived value.
+
+    Values must not refer back to their tree sequence, or it is never collected.
+    """
+
+    def __init__(self, name: str, max_entries: int):
+        self.name = name
+        self.max_entries = max_entries
+        self._entries: "OrderedDict[Tuple[int, Hashable], Tuple[weakref.ref, Any]]" = OrderedDict()
+        self._finalizers: Dict[int, weakref.finalize] = {}
+        # Reentrant: a finalizer can run during garbage collection while this thread holds the lock
+        self._lock = threading.RLock()
+        _caches.add(self)
+
+    def get(self, ts: tskit.TreeSequence, key: Hashable = None) -> Optional[Any]:
+        """The value stored for `ts` and `key`, or None."""
+        entry_key = (id(ts), key)
+        with self._lock:
+            entry = self._entries.get(entry_key)
+            if entry is None or entry[0]() is not ts:
+                return None
+            self._entries.move_to_end(entry_key)
+            return entry[1]
+
+    def put(self, ts: tskit.TreeSequence, value: Any, key: Hashable = None):
+        ts_id = id(ts)
+        with self._lock:
+            self._entries[(ts_id, key)] = (weakref.ref(ts), value)
+            self._entries.move_to_end((ts_id, key))
+            if ts_id not in self._finalizers:
+                self._finalizers[ts_id] = weakref.finalize(ts, self._forget, ts_id)
+            while len(self._entries) > self.max_entries:
+                (evicted_id, _), _ = self._entries.popitem(last=False)
+                if not any(entry_id == evicted_id for entry_id, _ in self._entries):
+                    self._detach(evicted_id)
+
+    def evict(self, ts: tskit.TreeSequence):
+        """Drop every value stored for `ts`."""
+        with self._lock:
+            self._detach(id(ts))
+            self._forget(id(ts))
+
+    def __len__(self) -> int:
+        return len(self._entries)
+
+    def _forget(self, ts_id: int):
+        with self._lock:
+            for entry_key in [entry_key for entry_key in self._entries if entry_key[0] == ts_id]:
+                del self._entries[entry_key]
+            self._finalizers.pop(ts_id, None)
+
+    def _detach(self, ts_id: int):
+        finalizer = self._finalizers.pop(ts_id, None)
+        if finalizer is not None:
+            finalizer.detach()
+
+
+def evict_tree_sequence(ts: tskit.TreeSequence):
+    """Drop everything any cache derived from `ts`, e.g. when its file is deleted."""
+    for cache in list(_caches):
+        cache.evict(ts)
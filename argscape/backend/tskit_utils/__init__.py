@@ -2,8 +2,66 @@
 Tree Sequence utilities for ARGscape.
 """
 
-from .io import load_tree_sequence_from_file
+from .io import load_tree_sequence_from_file, tree_sequence_fingerprint, TreeSequenceLoadError
+from .polytomies import get_polytomy_stats, resolve_polytomies
+from .time_scales import get_display_times, TIME_SCALE_MODES
+from .node_metrics import compute_node_metrics
+from .provenance import get_simulation_context
+from .legacy import upgrade_legacy_tree_sequence
+from .metadata import get_metadata_schemas, decode_metadata, get_node_metadata, apply_metadata_edits, METADATA_TABLES, TOP_LEVEL_METADATA
+from .populations import list_populations, apply_population_edits
+from .formats import detect_format, convert_arg_files, CONVERTIBLE_FORMATS
+from .writers import write_tree_sequence, format_from_path, WRITE_FORMATS
+from .descriptions import describe_node, describe_tree, describe_breakpoint, navigation_order
+from .topology import topology_blocks, topology_hash, unique_topologies
+from .sweeps import sweep_scan
+from .introgression import detect_introgression, tracts_to_csv
+from .coalescence import estimate_ne
+from .tmrca import compute_tmrca_matrix
+from .embedding import sample_embedding
+from .traversal import get_edge_index, neighborhood, node_coverage, node_gaps, simd_features
 
 __all__ = [
-    'load_tree_sequence_from_file'
-]
\ No newline at end of file
+    'load_tree_sequence_from_file',
+    'tree_sequence_fingerprint',
+    'TreeSequenceLoadError',
+    'upgrade_legacy_tree_sequence',
+    'get_polytomy_stats',
+    'resolve_polytomies',
+    'get_display_times',
+    'TIME_SCALE_MODES',
+    'compute_node_metrics',
+    'detect_format',
+    'convert_arg_files',
+    'CONVERTIBLE_FORMATS',
+    'write_tree_sequence',
+    'format_from_path',
+    'WRITE_FORMATS',
+    'get_simulation_context',
+    'get_metadata_schemas',
+    'decode_metadata',
+    'get_node_metadata',
+    'apply_metadata_edits',
+    'METADATA_TABLES',
+    'TOP_LEVEL_METADATA',
+    'list_populations',
+    'apply_population_edits',
+    'describe_node',
+    'describe_tree',
+    'describe_breakpoint',
+    'navigation_order',
+    'topology_blocks',
+    'topology_hash',
+    'unique_topologies',
+    'sweep_scan',
+    'detect_introgression',
+    'tracts_to_csv',
+    'estimate_ne',
+    'compute_tmrca_matrix',
+    'sample_embedding',
+    'get_edge_index',
+    'neighborhood',
+    'node_coverage',
+    'node_gaps',
+    'simd_features'
+]
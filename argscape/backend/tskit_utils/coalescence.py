@@ -0,0 +1,171 @@
+"""
+Effective population size through time from coalescence rates in the ARG.
+Every internal node is where pairs of sample lineages coalesce. Counting those
+pairs (weighted by tree span) in piecewise-constant epochs gives the maximum
+likelihood coalescence rate per epoch, and Ne(t) = 1 / (ploidy * rate).
+Curves are computed for all samples and for each population, with bootstrap
+confidence intervals from resampling local trees.
+"""
+
+import warnings
+from typing import Any, Dict, List, Optional
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+
+DEFAULT_NUM_EPOCHS = 20
+MAX_NUM_EPOCHS = 200
+DEFAULT_NUM_BOOTSTRAP = 100
+MAX_NUM_BOOTSTRAP = 1000
+CONFIDENCE_LEVEL = 0.95
+
+
+def default_time_breaks(ts: tskit.TreeSequence, num_epochs: int) -> np.ndarray:
+    """Epoch start times: 0, then log-spaced up to the oldest node (the last epoch is open-ended)."""
+    node_times = ts.tables.nodes.time
+    internal = node_times[node_times > 0]
+    if len(internal) == 0 or num_epochs == 1:
+        return np.array([0.0])
+    return np.concatenate([[0.0], np.geomspace(internal.min(), internal.max(), num_epochs - 1)])
+
+
+def _pair_coalescences(
+    ts: tskit.TreeSequence,
+    groups: List[np.ndarray],
+    breaks: np.ndarray,
+    cancel_token: Optional[CancellationToken] = None
+):
+    """Span-weighted coalescing pairs and their time at risk within the epoch, per tree, epoch and group."""
+    num_groups = len(groups)
+    shape = (ts.num_trees, len(breaks), num_groups)
+    pairs = np.zeros(shape)
+    exposure = np.zeros(shape)
+    membership: Dict[int, np.ndarray] = {}
+    for g, samples in enumerate(groups):
+        for u in samples:
+            membership.setdefault(int(u), np.zeros(num_groups))[g] = 1
+    node_times = ts.tables.nodes.time
+
+    for tree in ts.trees():
+        check_cancelled(cancel_token)
+        span = tree.span
+        below: Dict[int, np.ndarray] = {}
+        for u in tree.nodes(order="postorder"):
+            components = [below.pop(c) for c in tree.children(u)]
+            if u in membership:
+                components.append(membership[u])
+            if not components:
+                continue
+            total = np.sum(components, axis=0)
+            below[u] = total
+            if len(components) < 2:
+                continue
+            coalescing = (total ** 2 - np.sum(np.square(components), axis=0)) / 2
+            epoch = int(np.searchsorted(breaks, node_times[u], side="right")) - 1
+            pairs[tree.index, epoch] += coalescing * span
+            exposure[tree.index, epoch] += coalescing * (node_times[u] - breaks[epoch]) * span
+    return pairs, exposure
+
+
+def _rates(pairs: np.ndarray, exposure: np.ndarray, breaks: np.ndarray) -> np.ndarray:
+    """MLE coalescence rate per epoch and group from (epoch, group) sums."""
+    durations = np.diff(breaks)
+    # Pairs still uncoalesced at the end of each finite epoch were at risk for all of it
+    surviving = pairs[::-1].cumsum(axis=0)[::-1]
+    at_risk = exposure.copy()
+    at_risk[:-1] += surviving[1:] * durations[:, None]
+    with np.errstate(divide="ignore", invalid="ignore"):
+        return np.where(at_risk > 0, pairs / at_risk, np.nan)
+
+
+def estimate_ne(
+    ts: tskit.TreeSequence,
+    num_epochs: int = DEFAULT_NUM_EPOCHS,
+    time_breaks: Optional[List[float]] = None,
+    num_bootstrap: int = DEFAULT_NUM_BOOTSTRAP,
+    ploidy: int = 2,
+    random_seed: Optional[int] = None,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Piecewise-constant Ne(t) for all samples and each population with at least two samples.
+
+    Args:
+        num_epochs: Number of epochs for the default log-spaced time breaks
+        time_breaks: Explicit epoch start times (must start at 0 and increase)
+        num_bootstrap: Resamples of local trees for confidence intervals (0 to skip)
+        ploidy: Pairs coalesce at rate 1 / (ploidy * Ne)
+    """
+    if not 1 <= num_epochs <= MAX_NUM_EPOCHS:
+        raise ValueError(f"num_epochs must be between 1 and {MAX_NUM_EPOCHS}")
+    if not 0 <= num_bootstrap <= MAX_NUM_BOOTSTRAP:
+        raise ValueError(f"num_bootstrap must be between 0 and {MAX_NUM_BOOTSTRAP}")
+    if ploidy < 1:
+        raise ValueError("ploidy must be at least 1")
+    if time_breaks is not None:
+        breaks = np.asarray(time_breaks, dtype=float)
+        if len(breaks) == 0 or breaks[0] != 0 or np.any(np.diff(breaks) <= 0):
+            raise ValueError("time_breaks must start at 0 and be strictly increasing")
+    else:
+        breaks = default_time_breaks(ts, num_epochs)
+
+    groups = [ts.samples()]
+    labels = [{"population": None, "label": "All samples"}]
+    for pop in ts.populations():
+        samples = ts.samples(population=pop.id)
+        if len(samples) >= 2:
+            metadata = pop.metadata if isinstance(pop.metadata, dict) else {}
+            groups.append(samples)
+            labels.append({"population": pop.id, "label": metadata.get("name") or f"Population {pop.id}"})
+    if len(groups[0]) < 2:
+        raise ValueError("Estimating Ne needs at least two samples")
+
+    pairs, exposure = _pair_coalescences(ts, groups, breaks, cancel_token)
+    rates = _rates(pairs.sum(axis=0), exposure.sum(axis=0), breaks)
+
+    lower = upper = None
+    if num_bootstrap and ts.num_trees > 1:
+        rng = np.random.default_rng(random_seed)
+        replicates = np.empty((num_bootstrap,) + rates.shape)
+        for b in range(num_bootstrap):
+            check_cancelled(cancel_token)
+            trees = rng.integers(0, ts.num_trees, ts.num_trees)
+            replicates[b] = _rates(pairs[trees].sum(axis=0), exposure[trees].sum(axis=0), breaks)
+        tail = (1 - CONFIDENCE_LEVEL) / 2 * 100
+        with np.errstate(divide="ignore", invalid="ignore"):
+            ne_replicates = 1 / (ploidy * replicates)
+        # Epochs without coalescences have no finite Ne in some replicates
+        ne_replicates[~np.isfinite(ne_replicates)] = np.nan
+        with warnings.catch_warnings():
+            warnings.simplefilter("ignore", RuntimeWarning)
+            lower = np.nanpercentile(ne_replicates, tail, axis=0)
+            upper = np.nanpercentile(ne_replicates, 100 - tail, axis=0)
+
+    with np.errstate(divide="ignore"):
+        ne = 1 / (ploidy * rates)
+
+    def values(array: Optional[np.ndarray], g: int) -> Optional[List[Optional[float]]]:
+        if array is None:
+            return None
+        return [float(v) if np.isfinite(v) else None for v in array[:, g]]
+
+    ends = list(breaks[1:]) + [None]
+    return {
+        "ploidy": ploidy,
+        "num_bootstrap": num_bootstrap if lower is not None else 0,
+        "confidence_level": CONFIDENCE_LEVEL,
+        "time_units": ts.time_units,
+        "epochs": [{"start": float(start), "end": float(end) if end is not None else None} for start, end in zip(breaks, ends)],
+        "curves": [
+            {
+                **label,
+                "num_samples": len(groups[g]),
+                "coalescence_rate": values(rates, g),
+                "ne": values(ne, g),
+                "ne_lower": values(lower, g),
+                "ne_upper": values(upper, g),
+            }
+            for g, label in enumerate(labels)
+        ],
+    }
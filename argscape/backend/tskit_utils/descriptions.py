@@ -0,0 +1,217 @@
+"""
+Screen-reader descriptions and keyboard navigation order for ARG views.
+Nodes, local trees and the topology changes at each breakpoint are described
+in plain sentences, and every displayed node gets a deterministic position in
+the keyboard traversal with its neighbors for arrow-key navigation.
+"""
+
+from typing import Any, Dict, List, Optional
+
+import tskit
+
+from argscape.backend.formatting import DEFAULT_LOCALE, format_number, format_time
+from .node_metrics import NODE_IS_RE_EVENT
+
+# Longest list of node IDs spelled out in a sentence before summarizing
+MAX_LISTED_NODES = 8
+
+
+def _node_list(nodes: List[int]) -> str:
+    nodes = sorted(nodes)
+    if len(nodes) <= MAX_LISTED_NODES:
+        listed = ", ".join(str(u) for u in nodes)
+    else:
+        listed = ", ".join(str(u) for u in nodes[:MAX_LISTED_NODES]) + f" and {len(nodes) - MAX_LISTED_NODES} more"
+    return f"node {listed}" if len(nodes) == 1 else f"nodes {listed}"
+
+
+def _time_units(ts: tskit.TreeSequence) -> str:
+    from argscape.backend.time_units import parse_time_units
+
+    units = parse_time_units(ts)
+    return units if units != tskit.TIME_UNITS_UNKNOWN else "generations"
+
+
+def _population_label(ts: tskit.TreeSequence, population_id: int) -> Optional[str]:
+    if population_id == tskit.NULL:
+        return None
+    metadata = ts.population(population_id).metadata
+    name = metadata.get("name") if isinstance(metadata, dict) else None
+    return f"population {name}" if name else f"population {population_id}"
+
+
+def describe_node(
+    ts: tskit.TreeSequence,
+    node_id: int,
+    locale: str = DEFAULT_LOCALE,
+    generation_time: Optional[float] = None
+) -> Dict[str, Any]:
+    """One-paragraph summary of a node: kind, time, population, parents, children and mutations."""
+    if not 0 <= node_id < ts.num_nodes:
+        raise ValueError(f"Node {node_id} does not exist")
+    node = ts.node(node_id)
+    tables = ts.tables
+    edges = tables.edges
+    parents = sorted(set(int(p) for p in edges.parent[edges.child == node_id]))
+    children = sorted(set(int(c) for c in edges.child[edges.parent == node_id]))
+    num_mutations = int((tables.mutations.node == node_id).sum())
+
+    if node.is_sample():
+        kind = "Sample node"
+    elif len(parents) > 1 or node.flags & NODE_IS_RE_EVENT:
+        kind = "Recombination node"
+    else:
+        kind = "Ancestral node" if parents else "Root node"
+    when = format_time(node.time, _time_units(ts), generation_time, locale)
+    sentences = [f"{kind} {node_id} at time {when}"]
+    population = _population_label(ts, node.population)
+    if population:
+        sentences[0] += f" in {population}"
+    if parents:
+        sentences.append(f"Child of {_node_list(parents)}")
+    if children:
+        sentences.append(f"Parent of {_node_list(children)}")
+    if num_mutations:
+        sentences.append(f"Carries {format_number(num_mutations, 0, locale)} mutation{'s' if num_mutations != 1 else ''}")
+
+    return {
+        "node_id": node_id,
+        "description": ". ".join(sentences) + ".",
+        "parents": parents,
+        "children": children,
+        "num_mutations": num_mutations,
+    }
+
+
+def describe_tree(ts: tskit.TreeSequence, tree_index: int, locale: str = DEFAULT_LOCALE) -> Dict[str, Any]:
+    """Summary of one local tree: interval, roots, depth and mutations."""
+    if not 0 <= tree_index < ts.num_trees:
+        raise ValueError(f"Tree index must be between 0 and {ts.num_trees - 1}")
+    tree = ts.at_index(tree_index)
+    left, right = tree.interval
+    roots = list(tree.roots)
+    root_times = [ts.node(r).time for r in roots]
+    num_mutations = tree.num_mutations
+
+    sentences = [
+        f"Tree {tree_index + 1} of {format_number(ts.num_trees, 0, locale)} covers positions "
+        f"{format_number(left, 0, locale)} to {format_number(right, 0, locale)}",
+        f"It joins {format_number(tree.num_samples(), 0, locale)} samples under "
+        + (f"one root, node {roots[0]}" if len(roots) == 1 else f"{len(roots)} roots"),
+    ]
+    if roots:
+        sentences.append(f"The oldest root is at time {format_number(max(root_times), 2, locale)} {_time_units(ts)}")
+    sentences.append(f"It carries {format_number(num_mutations, 0, locale)} mutation{'s' if num_mutations != 1 else ''}")
+
+    return {
+        "tree_index": tree_index,
+        "interval": [left, right],
+        "description": ". ".join(sentences) + ".",
+        "num_roots": len(roots),
+        "num_mutations": num_mutations,
+    }
+
+
+def describe_breakpoint(ts: tskit.TreeSequence, tree_index: int, locale: str = DEFAULT_LOCALE) -> Dict[str, Any]:
+    """What changes between tree `tree_index - 1` and tree `tree_index`."""
+    if not 1 <= tree_index < ts.num_trees:
+        raise ValueError(f"Breakpoints are between trees, so tree_index must be between 1 and {ts.num_trees - 1}")
+
+    for index, (interval, edges_out, edges_in) in enumerate(ts.edge_diffs()):
+        if index == tree_index:
+            break
+    old_parent = {e.child: e.parent for e in edges_out}
+    new_parent = {e.child: e.parent for e in edges_in}
+
+    moves = []
+    for child in sorted(set(old_parent) & set(new_parent)):
+        if old_parent[child] != new_parent[child]:
+            moves.append({"node": int(child), "from": int(old_parent[child]), "to": int(new_parent[child])})
+    detached = sorted(set(old_parent) - set(new_parent))
+    attached = sorted(set(new_parent) - set(old_parent))
+
+    sentences = [f"At position {format_number(interval.left, 0, locale)} the genealogy changes"]
+    for move in moves[:MAX_LISTED_NODES]:
+        sentences.append(f"Node {move['node']} moves from parent {move['from']} to parent {move['to']}")
+    if len(moves) > MAX_LISTED_NODES:
+        sentences.append(f"{len(moves) - MAX_LISTED_NODES} more nodes change parent")
+    if detached:
+        sentences.append(f"{_node_list(detached).capitalize()} {'loses its' if len(detached) == 1 else 'lose their'} parent edge")
+    if attached:
+        sentences.append(f"{_node_list(attached).capitalize()} {'gains' if len(attached) == 1 else 'gain'} a parent edge")
+    if not (moves or detached or attached):
+        sentences.append("Only edge intervals change; the topology is the same")
+
+    return {
+        "tree_index": tree_index,
+        "position": interval.left,
+        "description": ". ".join(sentences) + ".",
+        "moves": moves,
+        "detached": [int(u) for u in detached],
+        "attached": [int(u) for u in attached],
+        "num_edges_out": len(edges_out),
+        "num_edges_in": len(edges_in),
+    }
+
+
+def navigation_order(
+    ts: tskit.TreeSequence,
+    tree_index: Optional[int] = None,
+    positions: Optional[Dict[int, List[float]]] = None
+) -> Dict[str, Any]:
+    """Deterministic keyboard traversal of the displayed nodes.
+
+    With `tree_index`, nodes of that local tree are visited in preorder from the
+    roots. Otherwise the whole graph is visited from the oldest nodes down, left
+    to right by layout x (or node ID without a layout). Each node gets its
+    neighbors for arrow keys: up (first parent), down (first child), and
+    left/right (previous/next node at the same level of the order).
+    """
+    neighbors: Dict[int, Dict[str, Optional[int]]] = {}
+
+    if tree_index is not None:
+        if not 0 <= tree_index < ts.num_trees:
+            raise ValueError(f"Tree index must be between 0 and {ts.num_trees - 1}")
+        tree = ts.at_index(tree_index)
+        order = [int(u) for root in tree.roots for u in tree.nodes(root, order="preorder")]
+        for u in order:
+            children = list(tree.children(u))
+            parent = tree.parent(u)
+            siblings = list(tree.children(parent)) if parent != tskit.NULL else list(tree.roots)
+            i = siblings.index(u)
+            neighbors[u] = {
+                "up": int(parent) if parent != tskit.NULL else None,
+                "down": int(children[0]) if children else None,
+                "left": int(siblings[i - 1]) if i > 0 else None,
+                "right": int(siblings[i + 1]) if i + 1 < len(siblings) else None,
+            }
+        return {"mode": "tree", "tree_index": tree_index, "order": order, "neighbors": neighbors}
+
+    nodes = [int(u) for u in positions if 0 <= int(u) < ts.num_nodes] if positions else list(range(ts.num_nodes))
+    times = ts.tables.nodes.time
+
+    def x_of(u: int) -> float:
+        return positions[u][0] if positions else float(u)
+
+    order = sorted(nodes, key=lambda u: (-times[u], x_of(u), u))
+    edges = ts.tables.edges
+    parents: Dict[int, List[int]] = {}
+    children: Dict[int, List[int]] = {}
+    for parent, child in sorted(set(zip(edges.parent.tolist(), edges.child.tolist()))):
+        parents.setdefault(child, []).append(parent)
+        children.setdefault(parent, []).append(child)
+
+    # Nodes at the same time form a level for left/right movement
+    index = {u: i for i, u in enumerate(order)}
+    for i, u in enumerate(order):
+        same_level_left = order[i - 1] if i > 0 and times[order[i - 1]] == times[u] else None
+        same_level_right = order[i + 1] if i + 1 < len(order) and times[order[i + 1]] == times[u] else None
+        displayed_parents = sorted((p for p in parents.get(u, []) if p in index), key=index.get)
+        displayed_children = sorted((c for c in children.get(u, []) if c in index), key=index.get)
+        neighbors[u] = {
+            "up": displayed_parents[0] if displayed_parents else None,
+            "down": displayed_children[0] if displayed_children else None,
+            "left": same_level_left,
+            "right": same_level_right,
+        }
+    return {"mode": "graph", "order": order, "neighbors": neighbors}
@@ -0,0 +1,84 @@
+"""
+PCA-style embeddings of samples from ARG-derived distances.
+Classical multidimensional scaling turns a pairwise distance matrix into 2D or
+3D coordinates: with genetic (site) divergence this matches PCA of the
+genotypes, and with branch divergence it embeds the genealogical distances
+(twice the TMRCA for contemporary samples). Each point carries its sample's
+node ID and population, so the plot can be linked with the ARG view and map.
+"""
+
+from typing import Any, Dict, List, Optional
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken
+from .tmrca import DEFAULT_MAX_SAMPLES, pairwise_divergence, select_samples
+
+# Distance name -> tskit statistic mode
+DISTANCE_MODES = {"tmrca": "branch", "genetic": "site"}
+
+
+def classical_mds(distances: np.ndarray, dimensions: int) -> Dict[str, Any]:
+    """Coordinates whose Euclidean distances best match `distances`, with explained variance."""
+    n = distances.shape[0]
+    centering = np.eye(n) - np.ones((n, n)) / n
+    gram = -0.5 * centering @ (distances ** 2) @ centering
+    eigenvalues, eigenvectors = np.linalg.eigh(gram)
+    order = np.argsort(eigenvalues)[::-1]
+    eigenvalues, eigenvectors = eigenvalues[order], eigenvectors[:, order]
+    positive = np.clip(eigenvalues, 0, None)
+    top = positive[:dimensions]
+    coordinates = eigenvectors[:, :dimensions] * np.sqrt(top)
+    total = positive.sum()
+    return {
+        "coordinates": coordinates,
+        "eigenvalues": top,
+        "explained_variance": top / total if total > 0 else np.zeros_like(top),
+    }
+
+
+def sample_embedding(
+    ts: tskit.TreeSequence,
+    distance: str = "tmrca",
+    dimensions: int = 2,
+    samples: Optional[List[int]] = None,
+    max_samples: int = DEFAULT_MAX_SAMPLES,
+    random_seed: Optional[int] = None,
+    workers: int = 1,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Embed samples in 2D or 3D from genealogical ("tmrca") or genetic distances."""
+    if distance not in DISTANCE_MODES:
+        raise ValueError(f"distance must be one of {tuple(DISTANCE_MODES)}")
+    if dimensions not in (2, 3):
+        raise ValueError("dimensions must be 2 or 3")
+    if distance == "genetic" and ts.num_sites == 0:
+        raise ValueError("Genetic distances need sites; use the TMRCA distance instead")
+    samples, used = select_samples(ts, samples, max_samples, random_seed)
+
+    pairs, divergences = pairwise_divergence(ts, used, mode=DISTANCE_MODES[distance], workers=workers, cancel_token=cancel_token)
+    matrix = np.zeros((len(used), len(used)))
+    index_i = np.array([i for i, _ in pairs])
+    index_j = np.array([j for _, j in pairs])
+    matrix[index_i, index_j] = divergences[0]
+    matrix[index_j, index_i] = divergences[0]
+    embedding = classical_mds(matrix, dimensions)
+
+    populations = ts.tables.nodes.population
+    return {
+        "distance": distance,
+        "dimensions": dimensions,
+        "num_requested": len(samples),
+        "downsampled": len(used) < len(samples),
+        "random_seed": random_seed,
+        "explained_variance": [float(v) for v in embedding["explained_variance"]],
+        "points": [
+            {
+                "node_id": u,
+                "population": int(populations[u]) if populations[u] != tskit.NULL else None,
+                "coordinates": [float(x) for x in embedding["coordinates"][i]],
+            }
+            for i, u in enumerate(used)
+        ],
+    }
@@ -0,0 +1,412 @@
+"""
+Import adapters for ARG formats other than tskit.
+Supports Relate (.anc with optional .mut) and ARGweaver / ARGweaver-D (.smc)
+output. Local trees are converted into a single tree sequence, sharing a node
+across adjacent trees whenever it subtends the same samples at the same time.
+"""
+
+import gzip
+import logging
+import re
+from typing import Any, Dict, List, Optional, Tuple
+
+import tskit
+
+logger = logging.getLogger(__name__)
+
+# Format identifiers
+FORMAT_TSKIT = "tskit"
+FORMAT_TSZIP = "tszip"
+FORMAT_RELATE = "relate"
+FORMAT_ARGWEAVER = "argweaver"
+
+CONVERTIBLE_FORMATS = (FORMAT_RELATE, FORMAT_ARGWEAVER)
+
+# Node times closer than this (relative) are treated as equal when sharing nodes
+TIME_MATCH_DIGITS = 9
+
+# A local tree: (left, right, parent map, node times, leaf -> sample index)
+LocalTree = Tuple[float, float, Dict[int, int], Dict[int, float], Dict[int, int]]
+
+
+def _decompress(contents: bytes) -> bytes:
+    if contents[:2] == b"\x1f\x8b":
+        return gzip.decompress(contents)
+    return contents
+
+
+def _strip_compression_suffix(filename: str) -> str:
+    return filename[:-3] if filename.lower().endswith(".gz") else filename
+
+
+def detect_format(filename: str, contents: bytes) -> str:
+    """Detect the format of an uploaded ARG file from its name and content."""
+    name = _strip_compression_suffix(filename.lower())
+    if name.endswith(".tsz"):
+        return FORMAT_TSZIP
+    if name.endswith(".anc"):
+        return FORMAT_RELATE
+    if name.endswith(".smc"):
+        return FORMAT_ARGWEAVER
+    if name.endswith(".trees"):
+        return FORMAT_TSKIT
+
+    head = _decompress(contents)[:4096]
+    try:
+        text = head.decode("utf-8")
+    except UnicodeDecodeError:
+        return FORMAT_TSKIT
+    if text.startswith("NUM_HAPLOTYPES"):
+        return FORMAT_RELATE
+    if text.startswith("NAMES") or "\nTREE\t" in text:
+        return FORMAT_ARGWEAVER
+    return FORMAT_TSKIT
+
+
+def _build_tree_sequence(
+    local_trees: List[LocalTree],
+    sample_names: List[str],
+    sequence_length: float
+) -> Tuple[tskit.TableCollection, Dict[Tuple[int, int], int], Dict[str, Any]]:
+    """Assemble local trees into tables, sharing identical nodes across trees.
+
+    Returns the tables, a map from (tree index, local node) to node ID, and stats.
+    """
+    tables = tskit.TableCollection(sequence_length=sequence_length)
+    tables.nodes.metadata_schema = tskit.MetadataSchema.permissive_json()
+    for name in sample_names:
+        tables.nodes.add_row(flags=tskit.NODE_IS_SAMPLE, time=0.0, metadata={"name": name})
+
+    node_ids: Dict[Tuple[int, float], int] = {}
+    local_to_global: Dict[Tuple[int, int], int] = {}
+    warnings: List[str] = []
+    num_local_nodes = 0
+
+    for tree_index, (left, right, parent, times, leaf_samples) in enumerate(local_trees):
+        children: Dict[int, List[int]] = {}
+        for child, p in parent.items():
+            if p != -1:
+                children.setdefault(p, []).append(child)
+
+        # Post-order: a node's sample bitmask is the union of its children's
+        masks: Dict[int, int] = {}
+        roots = [u for u, p in parent.items() if p == -1]
+        stack = [(root, False) for root in roots]
+        while stack:
+            u, visited = stack.pop()
+            if u in leaf_samples:
+                masks[u] = 1 << leaf_samples[u]
+            elif visited:
+                masks[u] = 0
+                for c in children.get(u, []):
+                    masks[u] |= masks[c]
+            else:
+                stack.append((u, True))
+                stack.extend((c, False) for c in children.get(u, []))
+
+        for u, mask in masks.items():
+            num_local_nodes += 1
+            if u in leaf_samples:
+                local_to_global[(tree_index, u)] = leaf_samples[u]
+                continue
+            key = (mask, round(times[u], TIME_MATCH_DIGITS))
+            if key not in node_ids:
+                node_ids[key] = tables.nodes.add_row(flags=0, time=times[u], metadata={})
+            local_to_global[(tree_index, u)] = node_ids[key]
+
+        for child, p in parent.items():
+            if p == -1 or child not in masks:
+                continue
+            child_id = local_to_global[(tree_index, child)]
+            parent_id = local_to_global[(tree_index, p)]
+            if tables.nodes.time[parent_id] <= tables.nodes.time[child_id]:
+                warnings.append(f"Tree {tree_index}: parent {p} is not older than child {child}; edge dropped")
+                continue
+            tables.edges.add_row(left=left, right=right, parent=parent_id, child=child_id)
+
+    tables.sort()
+    tables.edges.squash()
+    tables.sort()
+
+    stats = {
+        "num_local_trees": len(local_trees),
+        "num_local_nodes": num_local_nodes,
+        "num_nodes_after_sharing": tables.nodes.num_rows,
+        "warnings": warnings[:20] + ([f"... and {len(warnings) - 20} more"] if len(warnings) > 20 else []),
+    }
+    return tables, local_to_global, stats
+
+
+#### Relate ####
+
+RELATE_BRANCH_PATTERN = re.compile(r"(-?\d+):\(([^)]*)\)")
+
+
+def _parse_relate_mut(text: str) -> List[Dict[str, Any]]:
+    rows = []
+    lines = [line for line in text.splitlines() if line.strip()]
+    for line in lines[1:]:  # header
+        fields = line.split(";")
+        if len(fields) < 11:
+            continue
+        ancestral, _, derived = fields[10].partition("/")
+        rows.append({
+            "snp": int(fields[0]),
+            "position": float(fields[1]),
+            "tree_index": int(fields[4]),
+            "branches": [int(b) for b in fields[5].split()] if fields[5].strip() else [],
+            "is_not_mapping": fields[6].strip() == "1",
+            "ancestral_state": ancestral,
+            "derived_state": derived,
+        })
+    return rows
+
+
+def convert_relate(
+    anc_contents: bytes,
+    mut_contents: Optional[bytes] = None
+) -> Tuple[tskit.TreeSequence, Dict[str, Any]]:
+    """Convert Relate .anc (and optionally .mut) output into a tree sequence.
+
+    Without a .mut file SNP indices are used as genomic positions.
+    """
+    lines = [line for line in _decompress(anc_contents).decode("utf-8").splitlines() if line.strip()]
+    if not lines or not lines[0].startswith("NUM_HAPLOTYPES"):
+        raise ValueError("Not a Relate .anc file: missing NUM_HAPLOTYPES header")
+    num_haplotypes = int(lines[0].split()[1])
+    tree_lines = [line for line in lines[2:] if ":" in line]
+
+    mutations = _parse_relate_mut(_decompress(mut_contents).decode("utf-8")) if mut_contents else []
+    positions = {m["snp"]: m["position"] for m in mutations}
+
+    tree_starts = []
+    parsed = []
+    for line in tree_lines:
+        snp_text, _, rest = line.partition(":")
+        tree_starts.append(int(snp_text))
+        branches = RELATE_BRANCH_PATTERN.findall(rest)
+        if len(branches) != 2 * num_haplotypes - 1:
+            raise ValueError(f"Relate tree at SNP {snp_text} has {len(branches)} nodes, expected {2 * num_haplotypes - 1}")
+        parsed.append([(int(p), float(values.split()[0])) for p, values in branches])
+
+    def snp_position(snp: int) -> float:
+        return positions.get(snp, float(snp))
+
+    last_snp = max(positions) if positions else (tree_starts[-1] if tree_starts else 0)
+    sequence_length = snp_position(last_snp) + 1.0
+
+    local_trees: List[LocalTree] = []
+    local_index: Dict[int, int] = {}  # Relate tree index -> index in local_trees
+    for i, branches in enumerate(parsed):
+        left = 0.0 if i == 0 else snp_position(tree_starts[i])
+        right = sequence_length if i == len(parsed) - 1 else snp_position(tree_starts[i + 1])
+        parent = {u: p for u, (p, _) in enumerate(branches)}
+        times = {u: 0.0 for u in range(num_haplotypes)}
+        # Relate numbers internal nodes so parents always follow their children
+        for u in range(len(branches)):
+            p, length = branches[u]
+            if p != -1:
+                times[p] = max(times.get(p, 0.0), times[u] + length)
+        if left < right:
+            local_index[i] = len(local_trees)
+            local_trees.append((left, right, parent, times, {u: u for u in range(num_haplotypes)}))
+
+    sample_names = [f"haplotype_{i}" for i in range(num_haplotypes)]
+    tables, local_to_global, stats = _build_tree_sequence(local_trees, sample_names, sequence_length)
+
+    num_mutations = 0
+    skipped = 0
+    site_positions = set()
+    for m in mutations:
+        tree_index = local_index.get(m["tree_index"])
+        if m["is_not_mapping"] or len(m["branches"]) != 1 or tree_index is None or m["position"] in site_positions:
+            skipped += 1
+            continue
+        node = local_to_global.get((tree_index, m["branches"][0]))
+        if node is None:
+            skipped += 1
+            continue
+        site_positions.add(m["position"])
+        site = tables.sites.add_row(position=m["position"], ancestral_state=m["ancestral_state"])
+        tables.mutations.add_row(site=site, node=node, derived_state=m["derived_state"])
+        num_mutations += 1
+    if skipped:
+        stats["warnings"].append(f"{skipped} mutations could not be mapped to a single branch and were skipped")
+
+    tables.sort()
+    tables.build_index()
+    tables.compute_mutation_parents()
+    ts = tables.tree_sequence()
+
+    return ts, {
+        "format": FORMAT_RELATE,
+        "num_haplotypes": num_haplotypes,
+        "has_mutation_file": mut_contents is not None,
+        "num_mutations": num_mutations,
+        **stats,
+    }
+
+
+#### ARGweaver ####
+
+NHX_AGE_PATTERN = re.compile(r"age=([-+0-9.eE]+)")
+
+
+def _parse_newick(newick: str) -> Tuple[Dict[int, int], Dict[int, float], Dict[int, str], Dict[int, Optional[float]]]:
+    """Parse a Newick string with optional NHX comments.
+
+    Returns parent map, branch lengths, labels and NHX ages keyed by local node index.
+    """
+    parent: Dict[int, int] = {}
+    lengths: Dict[int, float] = {}
+    labels: Dict[int, str] = {}
+    ages: Dict[int, Optional[float]] = {}
+    stack: List[int] = []
+    current = -1
+    next_id = 0
+    i = 0
+    text = newick.strip().rstrip(";")
+
+    def new_node() -> int:
+        nonlocal next_id
+        node = next_id
+        next_id += 1
+        parent[node] = stack[-1] if stack else -1
+        return node
+
+    while i < len(text):
+        c = text[i]
+        if c == "(":
+            node = new_node()
+            stack.append(node)
+            current = -1
+            i += 1
+        elif c == ",":
+            current = -1
+            i += 1
+        elif c == ")":
+            current = stack.pop()
+            i += 1
+        elif c == ":":
+            j = i + 1
+            while j < len(text) and text[j] not in ",()[;":
+                j += 1
+            lengths[current] = float(text[i + 1:j])
+            i = j
+        elif c == "[":
+            j = text.index("]", i)
+            match = NHX_AGE_PATTERN.search(text[i:j])
+            if match:
+                ages[current] = float(match.group(1))
+            i = j + 1
+        else:
+            j = i
+            while j < len(text) and text[j] not in ",():[;":
+                j += 1
+            if current == -1:
+                current = new_node()
+            labels[current] = text[i:j].strip()
+            i = j
+    return parent, lengths, labels, ages
+
+
+def convert_argweaver(smc_contents: bytes) -> Tuple[tskit.TreeSequence, Dict[str, Any]]:
+    """Convert an ARGweaver / ARGweaver-D .smc file into a tree sequence."""
+    names: List[str] = []
+    region_end: Optional[float] = None
+    local_trees: List[LocalTree] = []
+    age_source = "nhx"
+
+    for line in _decompress(smc_contents).decode("utf-8").splitlines():
+        fields = line.rstrip("\n").split("\t")
+        if fields[0] == "NAMES":
+            names = fields[1:]
+        elif fields[0] == "REGION" and len(fields) >= 4:
+            region_end = float(fields[3])
+        elif fields[0] == "TREE" and len(fields) >= 4:
+            if not names:
+                raise ValueError("ARGweaver .smc file has a TREE line before NAMES")
+            # SMC coordinates are 1-based inclusive
+            left, right = float(fields[1]) - 1, float(fields[2])
+            parent, lengths, labels, ages = _parse_newick(fields[3])
+
+            leaf_samples = {}
+            for u, label in labels.items():
+                if u not in parent.values():
+                    index = int(label)
+                    if not 0 <= index < len(names):
+                        raise ValueError(f"Leaf label {label} is not a valid sample index")
+                    leaf_samples[u] = index
+
+            times: Dict[int, float] = {}
+            if all(ages.get(u) is not None for u in parent):
+                times = {u: float(ages[u]) for u in parent}
+            else:
+                age_source = "branch_lengths"
+                # Ages from branch lengths, measured up from the leaves
+                order = sorted(parent, key=lambda u: -u)  # children were created after parents
+                for u in order:
+                    times.setdefault(u, 0.0)
+                    p = parent[u]
+                    if p != -1:
+                        times[p] = max(times.get(p, 0.0), times[u] + lengths.get(u, 0.0))
+            for u in leaf_samples:
+                times[u] = 0.0
+            local_trees.append((left, right, parent, times, leaf_samples))
+
+    if not local_trees:
+        raise ValueError("No TREE lines found in ARGweaver .smc file")
+
+    sequence_length = region_end if region_end is not None else max(t[1] for t in local_trees)
+    offset = local_trees[0][0] if region_end is None else 0.0
+    tables, _, stats = _build_tree_sequence(local_trees, names, sequence_length)
+    tables.build_index()
+    ts = tables.tree_sequence()
+
+    return ts, {
+        "format": FORMAT_ARGWEAVER,
+        "num_samples": len(names),
+        "node_time_source": age_source,
+        "region_start_offset": offset,
+        **stats,
+    }
+
+
+def convert_arg_files(files: Dict[str, bytes]) -> Tuple[tskit.TreeSequence, str, Dict[str, Any]]:
+    """Convert uploaded non-tskit ARG files, auto-detecting the format.
+
+    Args:
+        files: Filename -> contents. Relate needs the .anc file and accepts a
+            matching .mut file; ARGweaver needs a single .smc file.
+
+    Returns:
+        Tuple of (tree sequence, output filename, conversion report)
+    """
+    detected = {name: detect_format(name, contents) for name, contents in files.items()}
+    mut_files = {n: c for n, c in files.items() if _strip_compression_suffix(n.lower()).endswith(".mut")}
+
+    anc_files = [n for n, f in detected.items() if f == FORMAT_RELATE]
+    smc_files = [n for n, f in detected.items() if f == FORMAT_ARGWEAVER]
+
+    if anc_files:
+        name = anc_files[0]
+        mut_contents = next(iter(mut_files.values()), None)
+        ts, report = convert_relate(files[name], mut_contents)
+    elif smc_files:
+        name = smc_files[0]
+        ts, report = convert_argweaver(files[name])
+    else:
+        raise ValueError(f"No convertible ARG file found (detected: {detected})")
+
+    base = _strip_compression_suffix(name).rsplit(".", 1)[0]
+    output_filename = f"{base}_{report['format']}.trees"
+    report.update({
+        "source_files": sorted(files),
+        "detected_formats": detected,
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_trees": ts.num_trees,
+        "sequence_length": ts.sequence_length,
+    })
+    logger.info(f"Converted {name} ({report['format']}) to tree sequence: {ts.num_nodes} nodes, {ts.num_trees} trees")
+    return ts, output_filename, report
@@ -0,0 +1,157 @@
+"""
+Detection of candidate introgressed tracts between two populations.
+For every sample in the recipient population, each local tree gives the time at
+which it first coalesces with any sample of the donor population. Trees where
+that time is far below the sample's genome-wide average (or below an absolute
+cutoff) are flagged, and consecutive flagged trees merge into tracts, which the
+genome navigator shows as an annotation track and which export as CSV.
+"""
+
+import csv
+import io
+from typing import Any, Dict, List, Optional, Union
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+
+DEFAULT_RELATIVE_THRESHOLD = 0.5
+DEFAULT_MIN_LENGTH = 0.0
+
+TRACT_CSV_COLUMNS = ("sample", "recipient", "donor", "left", "right", "length", "num_trees", "min_time", "mean_time", "relative_time")
+
+
+def resolve_population(ts: tskit.TreeSequence, population: Union[int, str]) -> int:
+    """A population ID from an ID or a name in the population metadata."""
+    if isinstance(population, str) and not population.isdigit():
+        for pop in ts.populations():
+            metadata = pop.metadata if isinstance(pop.metadata, dict) else {}
+            if metadata.get("name") == population:
+                return pop.id
+        raise ValueError(f"No population named '{population}'")
+    population_id = int(population)
+    if not 0 <= population_id < ts.num_populations:
+        raise ValueError(f"Population {population_id} does not exist")
+    return population_id
+
+
+def donor_coalescence_times(
+    ts: tskit.TreeSequence,
+    recipients: np.ndarray,
+    donors: np.ndarray,
+    cancel_token: Optional[CancellationToken] = None
+) -> np.ndarray:
+    """Per tree and recipient sample, the time of the first ancestor shared with a donor sample.
+
+    NaN where the sample does not coalesce with any donor in that tree.
+    """
+    times = np.full((ts.num_trees, len(recipients)), np.nan)
+    node_times = ts.tables.nodes.time
+    for tree in ts.trees(tracked_samples=donors):
+        check_cancelled(cancel_token)
+        for j, sample in enumerate(recipients):
+            u = tree.parent(sample)
+            while u != tskit.NULL and tree.num_tracked_samples(u) == 0:
+                u = tree.parent(u)
+            if u != tskit.NULL:
+                times[tree.index, j] = node_times[u]
+    return times
+
+
+def detect_introgression(
+    ts: tskit.TreeSequence,
+    recipient: Union[int, str],
+    donor: Union[int, str],
+    relative_threshold: float = DEFAULT_RELATIVE_THRESHOLD,
+    max_time: Optional[float] = None,
+    min_length: float = DEFAULT_MIN_LENGTH,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Find tracts where recipient samples coalesce unexpectedly recently with the donor population.
+
+    Args:
+        recipient, donor: Population IDs or names
+        relative_threshold: Flag trees where the donor coalescence time is at
+            most this fraction of the sample's span-weighted genome-wide mean
+        max_time: Additionally require the coalescence time to be at most this
+        min_length: Drop tracts shorter than this many base pairs
+    """
+    if not 0 < relative_threshold <= 1:
+        raise ValueError("relative_threshold must be in (0, 1]")
+    if min_length < 0:
+        raise ValueError("min_length must not be negative")
+    recipient_id = resolve_population(ts, recipient)
+    donor_id = resolve_population(ts, donor)
+    if recipient_id == donor_id:
+        raise ValueError("Recipient and donor populations must differ")
+    recipients = ts.samples(population=recipient_id)
+    donors = ts.samples(population=donor_id)
+    if len(recipients) == 0 or len(donors) == 0:
+        raise ValueError("Both populations need at least one sample")
+
+    times = donor_coalescence_times(ts, recipients, donors, cancel_token)
+    breakpoints = np.asarray(ts.breakpoints(as_array=True))
+    spans = np.diff(breakpoints)
+    coalesced = np.isfinite(times)
+    weights = spans[:, None] * coalesced
+    with np.errstate(invalid="ignore", divide="ignore"):
+        mean_times = np.nansum(times * spans[:, None], axis=0) / weights.sum(axis=0)
+        relative = times / mean_times[None, :]
+    flagged = coalesced & (relative <= relative_threshold)
+    if max_time is not None:
+        flagged &= times <= max_time
+
+    tracts: List[Dict[str, Any]] = []
+    samples_summary = []
+    for j, sample in enumerate(recipients):
+        start = None
+        sample_tracts = []
+        for i in range(ts.num_trees + 1):
+            is_flagged = i < ts.num_trees and flagged[i, j]
+            if is_flagged and start is None:
+                start = i
+            elif not is_flagged and start is not None:
+                left, right = float(breakpoints[start]), float(breakpoints[i])
+                if right - left >= min_length:
+                    segment = times[start:i, j]
+                    sample_tracts.append({
+                        "sample": int(sample),
+                        "recipient": recipient_id,
+                        "donor": donor_id,
+                        "left": left,
+                        "right": right,
+                        "length": right - left,
+                        "num_trees": i - start,
+                        "min_time": float(segment.min()),
+                        "mean_time": float(np.average(segment, weights=spans[start:i])),
+                        "relative_time": float(np.average(relative[start:i, j], weights=spans[start:i])),
+                    })
+                start = None
+        tracts.extend(sample_tracts)
+        samples_summary.append({
+            "sample": int(sample),
+            "mean_donor_time": float(mean_times[j]) if np.isfinite(mean_times[j]) else None,
+            "num_tracts": len(sample_tracts),
+            "introgressed_fraction": sum(t["length"] for t in sample_tracts) / ts.sequence_length,
+        })
+
+    return {
+        "recipient": recipient_id,
+        "donor": donor_id,
+        "relative_threshold": relative_threshold,
+        "max_time": max_time,
+        "min_length": min_length,
+        "num_tracts": len(tracts),
+        "tracts": tracts,
+        "samples": samples_summary,
+    }
+
+
+def tracts_to_csv(tracts: List[Dict[str, Any]]) -> str:
+    text = io.StringIO()
+    writer = csv.writer(text)
+    writer.writerow(TRACT_CSV_COLUMNS)
+    for tract in tracts:
+        writer.writerow([tract[column] for column in TRACT_CSV_COLUMNS])
+    return text.getvalue()
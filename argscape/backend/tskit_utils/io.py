@@ -1,14 +1,173 @@
+import hashlib
+import io
+import struct
 import tskit
 import tempfile
 import os
 import tszip
+import zipfile
+from typing import Any, Dict, Optional
 
+from argscape.backend.timing import timed
+
+# .trees files are kastore containers: a 64-byte header, then 64-byte item descriptors
+KASTORE_MAGIC = b"\x89KAS\r\n\x1a\n"
+KASTORE_HEADER = struct.Struct("<8sHHIQ")
+KASTORE_HEADER_SIZE = 64
+KASTORE_DESCRIPTOR = struct.Struct("<B7xQQQQ")
+KASTORE_DESCRIPTOR_SIZE = 64
+KASTORE_VERSION_MAJOR = 1
+# Bytes per element of each kastore type code (int8 ... float64)
+KASTORE_TYPE_SIZES = (1, 1, 2, 2, 4, 4, 8, 8, 4, 8)
+# tskit writes a few dozen columns; far more means the header is garbage
+MAX_KASTORE_ITEMS = 4096
+# tskit reads file format 12.x; older kastore files (msprime 0.6/0.7) need upgrading
+TSKIT_FORMAT_MAJOR = 12
+
+ZIP_MAGIC = b"PK\x03\x04"
+GZIP_MAGIC = b"\x1f\x8b"
+HDF5_MAGIC = b"\x89HDF\r\n\x1a\n"
+
+
+class TreeSequenceLoadError(ValueError):
+    """A file that can't be loaded as a tree sequence; `hint` says what the user can do about it."""
+
+    kind = "invalid"
+
+    def __init__(self, message: str, hint: Optional[str] = None, upgradable: bool = False):
+        super().__init__(message)
+        self.message = message
+        self.hint = hint
+        # Legacy formats that tskit_utils.legacy can convert
+        self.upgradable = upgradable
+
+    def __str__(self) -> str:
+        return f"{self.message}. {self.hint}" if self.hint else self.message
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"kind": self.kind, "message": self.message, "hint": self.hint, "upgradable": self.upgradable}
+
+
+class TruncatedFileError(TreeSequenceLoadError):
+    kind = "truncated"
+
+
+class BadMagicError(TreeSequenceLoadError):
+    kind = "bad_magic"
+
+
+class VersionUnsupportedError(TreeSequenceLoadError):
+    kind = "version_unsupported"
+
+
+class TableInconsistentError(TreeSequenceLoadError):
+    kind = "table_inconsistent"
+
+
+TRUNCATED_HINT = "The file is incomplete, usually from an interrupted download or copy; fetch it again"
+
+
+def _read_format_version(contents: bytes, descriptors) -> Optional[tuple]:
+    for type_code, key_start, key_len, array_start, array_len in descriptors:
+        if contents[key_start:key_start + key_len] == b"format/version" and type_code == 5 and array_len == 2:
+            return struct.unpack_from("<II", contents, array_start)
+    return None
+
+
+def check_kastore(contents: bytes) -> Dict[str, Any]:
+    """
+    Bounds-check a .trees file's container before tskit parses it, so a
+    truncated or corrupt file fails fast with a typed error instead of a
+    crash or a huge allocation.
+    """
+    if len(contents) < KASTORE_HEADER_SIZE:
+        raise TruncatedFileError(f"File is only {len(contents)} bytes, shorter than a tree sequence header", TRUNCATED_HINT)
+    magic, version_major, version_minor, num_items, file_size = KASTORE_HEADER.unpack_from(contents)
+    if magic != KASTORE_MAGIC:
+        raise BadMagicError("Not a tskit .trees file (unrecognised header)")
+    if version_major != KASTORE_VERSION_MAJOR:
+        raise VersionUnsupportedError(
+            f"Container version {version_major}.{version_minor} is not supported",
+            "Re-save the file with a current tskit, or upgrade ARGscape"
+        )
+    if file_size > len(contents):
+        raise TruncatedFileError(f"File is {len(contents)} bytes but its header says {file_size}", TRUNCATED_HINT)
+    if num_items > MAX_KASTORE_ITEMS or KASTORE_HEADER_SIZE + num_items * KASTORE_DESCRIPTOR_SIZE > file_size:
+        raise TableInconsistentError(f"Header declares {num_items} columns, which don't fit in the file", "The file is corrupt")
+
+    descriptors = []
+    for i in range(num_items):
+        type_code, key_start, key_len, array_start, array_len = KASTORE_DESCRIPTOR.unpack_from(
+            contents, KASTORE_HEADER_SIZE + i * KASTORE_DESCRIPTOR_SIZE
+        )
+        if type_code >= len(KASTORE_TYPE_SIZES):
+            raise TableInconsistentError(f"Column {i} has unknown type code {type_code}", "The file is corrupt")
+        if key_start + key_len > file_size or array_start + array_len * KASTORE_TYPE_SIZES[type_code] > file_size:
+            raise TruncatedFileError(f"Column {i} extends past the end of the file", TRUNCATED_HINT)
+        descriptors.append((type_code, key_start, key_len, array_start, array_len))
+    return {
+        "num_items": num_items,
+        "file_size": file_size,
+        "format_version": _read_format_version(contents, descriptors),
+        "descriptors": descriptors,
+    }
+
+
+def check_tree_sequence_bytes(contents: bytes, filename: str = ""):
+    """Raise a TreeSequenceLoadError for files that are recognisably not loadable."""
+    if not contents:
+        raise TruncatedFileError("File is empty", TRUNCATED_HINT)
+    if contents.startswith(ZIP_MAGIC):
+        try:
+            zipfile.ZipFile(io.BytesIO(contents))
+        except zipfile.BadZipFile:
+            raise TruncatedFileError("Compressed .tsz file is damaged or incomplete", TRUNCATED_HINT)
+        return
+    if contents.startswith(GZIP_MAGIC):
+        raise BadMagicError("File is gzip-compressed", "Decompress it (gunzip) and upload the .trees file")
+    if contents.startswith(HDF5_MAGIC):
+        raise VersionUnsupportedError(
+            "File uses the HDF5 format of msprime before 0.6 (2018)",
+            "It can be upgraded to the current format (needs h5py)",
+            upgradable=True
+        )
+    if not contents.startswith(KASTORE_MAGIC):
+        raise BadMagicError(
+            f"{filename or 'File'} is not a .trees or .tsz file",
+            "Relate .anc and ARGweaver .smc files can be imported; other formats need converting with tskit first"
+        )
+    version = check_kastore(contents)["format_version"]
+    if version is not None and version[0] < TSKIT_FORMAT_MAJOR:
+        raise VersionUnsupportedError(
+            f"File uses tskit file format {version[0]}.{version[1]}, older than this tskit reads ({TSKIT_FORMAT_MAJOR}.x)",
+            "It can be upgraded to the current format",
+            upgradable=True
+        )
+
+
+def _typed_tskit_error(e: Exception) -> Optional[TreeSequenceLoadError]:
+    if isinstance(e, (tskit.VersionTooOldError, tskit.VersionTooNewError)):
+        return VersionUnsupportedError(str(e), "Re-save the file with a matching tskit version (`tskit upgrade` for old files)")
+    if isinstance(e, tskit.FileFormatError):
+        return TableInconsistentError(f"File structure is invalid: {e}", "The file is corrupt; regenerate it from its source")
+    if isinstance(e, tskit.LibraryError):
+        return TableInconsistentError(f"Tables are inconsistent: {e}", "Fix the tables (e.g. sort and deduplicate) in tskit and save again")
+    return None
+
+
+@timed("parse")
 def load_tree_sequence_from_file(contents: bytes, filename: str) -> tuple[tskit.TreeSequence, str]:
     """Load tree sequence from file contents.
     
     Returns:
         tuple: (TreeSequence object, updated filename with correct extension)
+    
+    Raises:
+        TreeSequenceLoadError: Truncated, BadMagic, VersionUnsupported or
+            TableInconsistent, with a hint for the user
     """
+    check_tree_sequence_bytes(contents, filename)
+    
     # Create temporary file that will persist until explicitly deleted
     suffix = ".trees.tsz" if filename.endswith(".tsz") else ".trees"
     temp_file = tempfile.NamedTemporaryFile(delete=False, suffix=suffix)
@@ -18,7 +177,13 @@ def load_tree_sequence_from_file(contents: bytes, filename: str) -> tuple[tskit.
         temp_file.close()
         
         # Use tszip.load which handles both compressed and uncompressed files
-        ts = tszip.load(temp_file.name)
+        try:
+            ts = tszip.load(temp_file.name)
+        except Exception as e:
+            typed = _typed_tskit_error(e)
+            if typed is None:
+                raise
+            raise typed from e
         
         # Update filename to .trees since we've loaded it into memory
         updated_filename = filename
@@ -31,4 +196,25 @@ def load_tree_sequence_from_file(contents: bytes, filename: str) -> tuple[tskit.
         try:
             os.unlink(temp_file.name)
         except:
-            pass
\ No newline at end of file
+            pass
+
+
+def tree_sequence_fingerprint(ts: tskit.TreeSequence) -> str:
+    """Content hash identifying a tree sequence's topology and node times.
+
+    Used to check that sidecar files (layouts, view states) belong to the
+    tree sequence they are applied to.
+    """
+    hasher = hashlib.sha256()
+    hasher.update(repr(ts.sequence_length).encode())
+    tables = ts.tables
+    for column in (
+        tables.nodes.flags,
+        tables.nodes.time,
+        tables.edges.left,
+        tables.edges.right,
+        tables.edges.parent,
+        tables.edges.child,
+    ):
+        hasher.update(column.tobytes())
+    return hasher.hexdigest()
@@ -0,0 +1,170 @@
+"""
+Upgrade of tree sequences saved in older file formats.
+Handles the HDF5 files of msprime before 0.6 (through tskit's own legacy
+reader) and kastore files older than format 12 (msprime 0.6/0.7), whose
+columns are read directly and rebuilt into current tables. The report lists
+every change made along the way, so users can judge whether the upgraded
+file still means the same thing.
+"""
+
+import json
+import logging
+import os
+import tempfile
+from typing import Any, Dict, List, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.tskit_utils.io import (
+    HDF5_MAGIC,
+    KASTORE_MAGIC,
+    TableInconsistentError,
+    VersionUnsupportedError,
+    check_kastore,
+)
+
+logger = logging.getLogger(__name__)
+
+# numpy dtype of each kastore type code
+KASTORE_DTYPES = (np.int8, np.uint8, np.int16, np.uint16, np.int32, np.uint32, np.int64, np.uint64, np.float32, np.float64)
+
+# Columns of format 12; any a legacy file lacks are left to their tskit defaults
+TABLE_COLUMNS = {
+    "individuals": ("flags", "location", "location_offset", "parents", "parents_offset", "metadata", "metadata_offset"),
+    "nodes": ("flags", "time", "population", "individual", "metadata", "metadata_offset"),
+    "edges": ("left", "right", "parent", "child", "metadata", "metadata_offset"),
+    "migrations": ("left", "right", "node", "source", "dest", "time", "metadata", "metadata_offset"),
+    "sites": ("position", "ancestral_state", "ancestral_state_offset", "metadata", "metadata_offset"),
+    "mutations": ("site", "node", "time", "derived_state", "derived_state_offset", "parent", "metadata", "metadata_offset"),
+    "populations": ("metadata", "metadata_offset"),
+    "provenances": ("timestamp", "timestamp_offset", "record", "record_offset"),
+}
+# What leaving out each notable column means for the data
+MISSING_COLUMN_NOTES = {
+    "mutations/time": "Mutation times were not recorded; they are marked unknown",
+    "mutations/parent": "Mutation parents were recomputed from the trees",
+    "individuals/parents": "Individuals have no recorded parents",
+    "edges/metadata": "Edges have no metadata",
+    "migrations/metadata": "Migrations have no metadata",
+}
+# Container keys that are rebuilt rather than copied
+REBUILT_KEYS = ("format/name", "format/version", "sequence_length", "uuid", "indexes/edge_insertion_order", "indexes/edge_removal_order")
+
+
+def _read_columns(contents: bytes) -> Tuple[Dict[str, np.ndarray], Tuple[int, int]]:
+    info = check_kastore(contents)
+    columns = {}
+    for type_code, key_start, key_len, array_start, array_len in info["descriptors"]:
+        key = contents[key_start:key_start + key_len].decode("utf-8", errors="replace")
+        columns[key] = np.frombuffer(contents, dtype=KASTORE_DTYPES[type_code], count=array_len, offset=array_start)
+    return columns, info["format_version"] or (0, 0)
+
+
+def _upgrade_kastore(contents: bytes) -> Tuple[tskit.TableCollection, Dict[str, Any]]:
+    columns, version = _read_columns(contents)
+    if "sequence_length" not in columns or not len(columns["sequence_length"]):
+        raise TableInconsistentError("Legacy file has no sequence length", "The file is corrupt")
+
+    tables_dict: Dict[str, Any] = {"sequence_length": float(columns["sequence_length"][0])}
+    changes: List[str] = []
+    missing = []
+    for table, names in TABLE_COLUMNS.items():
+        table_dict = {name: columns[f"{table}/{name}"] for name in names if f"{table}/{name}" in columns}
+        missing += [f"{table}/{name}" for name in names if f"{table}/{name}" not in columns]
+        tables_dict[table] = table_dict
+    known = {f"{table}/{name}" for table, names in TABLE_COLUMNS.items() for name in names}
+    dropped = sorted(key for key in columns if key not in known and key not in REBUILT_KEYS)
+
+    # A missing column only changes anything when its table has rows
+    for key in missing:
+        table = key.split("/")[0]
+        if key in MISSING_COLUMN_NOTES and len(tables_dict[table].get(TABLE_COLUMNS[table][0], [])):
+            changes.append(MISSING_COLUMN_NOTES[key])
+    if dropped:
+        changes.append(f"Columns unknown to the current format were dropped: {', '.join(dropped)}")
+
+    try:
+        tables = tskit.TableCollection.fromdict(tables_dict)
+    except Exception as e:
+        raise TableInconsistentError(f"Legacy tables could not be rebuilt: {e}", "The file may be corrupt")
+    return tables, {
+        "from_format": f"tskit file format {version[0]}.{version[1]}",
+        "changes": changes,
+        "dropped_columns": dropped,
+        "recompute_mutation_parents": "mutations/parent" not in columns,
+    }
+
+
+def _upgrade_hdf5(contents: bytes) -> Tuple[tskit.TableCollection, Dict[str, Any]]:
+    try:
+        import h5py  # noqa: F401  (tskit's legacy reader needs it)
+    except ImportError:
+        raise VersionUnsupportedError("Reading the pre-0.6 HDF5 format needs h5py", "Install it with `pip install h5py` and try again")
+    changes = []
+    temp_file = tempfile.NamedTemporaryFile(delete=False, suffix=".hdf5")
+    try:
+        temp_file.write(contents)
+        temp_file.close()
+        try:
+            ts = tskit.load_legacy(temp_file.name)
+        except tskit.LibraryError:
+            # Old msprime allowed several sites at one position; tskit doesn't
+            ts = tskit.load_legacy(temp_file.name, remove_duplicate_positions=True)
+            changes.append("Sites sharing a position were merged (only the first was kept)")
+    except (OSError, ValueError) as e:
+        raise TableInconsistentError(f"Legacy HDF5 file could not be read: {e}", "The file may be corrupt")
+    finally:
+        try:
+            os.unlink(temp_file.name)
+        except OSError:
+            pass
+    return ts.dump_tables(), {"from_format": "HDF5 (msprime before 0.6)", "changes": changes, "dropped_columns": [], "recompute_mutation_parents": False}
+
+
+def upgrade_legacy_tree_sequence(contents: bytes) -> Tuple[tskit.TreeSequence, Dict[str, Any]]:
+    """Convert an old-format file to a current tree sequence; returns it and a report of what changed.
+
+    Raises:
+        VersionUnsupportedError: If the format isn't one this can upgrade
+        TableInconsistentError: If the old file's tables don't form a valid tree sequence
+    """
+    if contents.startswith(HDF5_MAGIC):
+        tables, report = _upgrade_hdf5(contents)
+    elif contents.startswith(KASTORE_MAGIC):
+        tables, report = _upgrade_kastore(contents)
+    else:
+        raise VersionUnsupportedError("File is not in a legacy tskit or msprime format")
+
+    changes = report.pop("changes")
+    try:
+        tables.tree_sequence()
+    except tskit.LibraryError:
+        # Older writers didn't always keep tables in the order tskit now requires
+        tables.sort()
+        changes.append("Tables were re-sorted into the order the current format requires")
+    tables.build_index()
+    if report.pop("recompute_mutation_parents") and tables.mutations.num_rows:
+        tables.compute_mutation_parents()
+    tables.provenances.add_row(record=json.dumps({
+        "schema_version": "1.0.0",
+        "software": {"name": "argscape"},
+        "parameters": {"command": "upgrade", "from_format": report["from_format"]},
+        "environment": {"tskit": tskit.__version__},
+    }))
+    changes.append("A provenance record of the upgrade was added")
+
+    try:
+        ts = tables.tree_sequence()
+    except tskit.LibraryError as e:
+        raise TableInconsistentError(f"Upgraded tables are inconsistent: {e}", "The file may be corrupt")
+    logger.info(f"Upgraded tree sequence from {report['from_format']}: {len(changes)} changes")
+    return ts, {
+        **report,
+        "to_format": f"tskit {tskit.__version__}",
+        "changes": changes,
+        "num_nodes": ts.num_nodes,
+        "num_edges": ts.num_edges,
+        "num_sites": ts.num_sites,
+        "num_mutations": ts.num_mutations,
+    }
@@ -0,0 +1,318 @@
+"""
+Inspection and editing of tskit metadata.
+Reads metadata schemas, decodes struct/JSON metadata into typed values for the
+metadata editor, and writes edited metadata (and schemas) into a derived tree
+sequence. Node metadata is decoded lazily, one node at a time, with large
+values cut down to a preview unless the full value is asked for.
+"""
+
+import base64
+import json
+import logging
+from typing import Any, Dict, List, Optional
+
+import numpy as np
+import tskit
+
+logger = logging.getLogger(__name__)
+
+METADATA_TABLES = ("nodes", "edges", "sites", "mutations", "individuals", "populations", "migrations")
+TOP_LEVEL_METADATA = "tree_sequence"
+
+MAX_ROWS_PER_REQUEST = 5000
+
+# Metadata up to this size is returned whole; larger values get a preview
+PREVIEW_MAX_BYTES = 16 * 1024
+# Preview limits: items kept per list/dict and characters kept per string
+PREVIEW_MAX_ITEMS = 20
+PREVIEW_MAX_CHARS = 500
+
+
+def _json_safe(value: Any) -> Any:
+    if isinstance(value, dict):
+        return {str(k): _json_safe(v) for k, v in value.items()}
+    if isinstance(value, (list, tuple)):
+        return [_json_safe(v) for v in value]
+    if isinstance(value, np.ndarray):
+        return value.tolist()
+    if isinstance(value, np.generic):
+        return value.item()
+    if isinstance(value, bytes):
+        return {"__bytes__": base64.b64encode(value).decode("ascii")}
+    return value
+
+
+def _from_json_safe(value: Any) -> Any:
+    if isinstance(value, dict):
+        if set(value) == {"__bytes__"}:
+            return base64.b64decode(value["__bytes__"])
+        return {k: _from_json_safe(v) for k, v in value.items()}
+    if isinstance(value, list):
+        return [_from_json_safe(v) for v in value]
+    return value
+
+
+def _get_table(tables: tskit.TableCollection, table: str):
+    if table not in METADATA_TABLES:
+        raise ValueError(f"Unknown table '{table}'. Expected one of {METADATA_TABLES} or '{TOP_LEVEL_METADATA}'")
+    return getattr(tables, table)
+
+
+def _schema_properties(schema: Dict[str, Any]) -> List[Dict[str, Any]]:
+    properties = []
+    required = set(schema.get("required", []))
+    for name, spec in (schema.get("properties") or {}).items():
+        properties.append({
+            "name": name,
+            "type": spec.get("type"),
+            "binary_format": spec.get("binaryFormat"),
+            "description": spec.get("description"),
+            "required": name in required,
+            "default": _json_safe(spec.get("default")),
+        })
+    return properties
+
+
+def describe_schema(schema: tskit.MetadataSchema) -> Dict[str, Any]:
+    """Codec, JSON schema and flattened property list of a metadata schema."""
+    schema_dict = schema.schema
+    if schema_dict is None:
+        return {"codec": None, "schema": None, "properties": []}
+    return {
+        "codec": schema_dict.get("codec"),
+        "schema": _json_safe(schema_dict),
+        "properties": _schema_properties(schema_dict),
+    }
+
+
+def _decode_raw(raw: bytes) -> Dict[str, Any]:
+    """Best-effort view of metadata stored without a schema."""
+    if not raw:
+        return {"value": None, "encoding": "empty"}
+    try:
+        return {"value": json.loads(raw.decode("utf-8")), "encoding": "json (no schema)"}
+    except (UnicodeDecodeError, ValueError):
+        pass
+    try:
+        return {"value": raw.decode("utf-8"), "encoding": "text (no schema)"}
+    except UnicodeDecodeError:
+        return {"value": _json_safe(raw), "encoding": "binary (no schema)"}
+
+
+def _row_bytes(table, row_id: int) -> bytes:
+    return table.metadata[table.metadata_offset[row_id]:table.metadata_offset[row_id + 1]].tobytes()
+
+
+def _decode_row(table, raw: bytes, schema: Optional[tskit.MetadataSchema] = None) -> Dict[str, Any]:
+    """Decode one row with the table's schema, or `schema` for raw rows (e.g. struct bytes written without one)."""
+    if table.metadata_schema.schema is not None:
+        schema, encoding = table.metadata_schema, "schema"
+    elif schema is not None:
+        encoding = "supplied schema"
+    else:
+        return _decode_raw(raw)
+    try:
+        return {"value": _json_safe(schema.decode_row(raw)), "encoding": encoding}
+    except Exception as e:
+        return {"value": _json_safe(raw), "encoding": "undecodable", "error": str(e)}
+
+
+def _preview(value: Any) -> Any:
+    """Shorten long strings, lists and dicts; shortened parts are marked with an "__truncated__" entry."""
+    if isinstance(value, dict):
+        if set(value) == {"__bytes__"} and len(value["__bytes__"]) > PREVIEW_MAX_CHARS:
+            return {"__bytes__": value["__bytes__"][:PREVIEW_MAX_CHARS], "__truncated__": len(value["__bytes__"])}
+        items = list(value.items())
+        preview = {k: _preview(v) for k, v in items[:PREVIEW_MAX_ITEMS]}
+        if len(items) > PREVIEW_MAX_ITEMS:
+            preview["__truncated__"] = len(items)
+        return preview
+    if isinstance(value, list):
+        preview = [_preview(v) for v in value[:PREVIEW_MAX_ITEMS]]
+        if len(value) > PREVIEW_MAX_ITEMS:
+            preview.append({"__truncated__": len(value)})
+        return preview
+    if isinstance(value, str) and len(value) > PREVIEW_MAX_CHARS:
+        return value[:PREVIEW_MAX_CHARS] + "…"
+    return value
+
+
+def _inspect_row(table, row_id: int, full: bool, schema: Optional[tskit.MetadataSchema]) -> Dict[str, Any]:
+    raw = _row_bytes(table, row_id)
+    decoded = _decode_row(table, raw, schema)
+    truncated = not full and len(raw) > PREVIEW_MAX_BYTES
+    if truncated:
+        decoded["value"] = _preview(decoded["value"])
+    return {"id": row_id, "bytes": len(raw), "truncated": truncated, **decoded}
+
+
+def get_node_metadata(
+    ts: tskit.TreeSequence,
+    node_id: int,
+    full: bool = False,
+    schema: Optional[Dict[str, Any]] = None
+) -> Dict[str, Any]:
+    """Decode one node's metadata and that of its individual.
+
+    Args:
+        ts: Source tree sequence
+        node_id: Node to inspect
+        full: Return the whole value even when it is larger than PREVIEW_MAX_BYTES
+        schema: JSON metadata schema (e.g. a "struct" codec) used to decode
+            tables that have no schema of their own
+
+    Raises:
+        ValueError: If the node is out of range or the schema is invalid
+    """
+    if not 0 <= node_id < ts.num_nodes:
+        raise ValueError(f"Node {node_id} is out of range (tree sequence has {ts.num_nodes} nodes)")
+    supplied = None
+    if schema is not None:
+        try:
+            supplied = tskit.MetadataSchema(schema)
+        except Exception as e:
+            raise ValueError(f"Invalid metadata schema: {e}")
+
+    tables = ts.tables
+    result = {
+        "node": _inspect_row(tables.nodes, node_id, full, supplied),
+        "individual": None,
+    }
+    individual = int(tables.nodes.individual[node_id])
+    if individual != tskit.NULL:
+        result["individual"] = _inspect_row(tables.individuals, individual, full, supplied)
+    return result
+
+
+def preview_node_metadata(ts: tskit.TreeSequence, node_ids: List[int]) -> Dict[int, Any]:
+    """Decoded metadata of the nodes in `node_ids` that have any, shortened as in previews."""
+    nodes = ts.tables.nodes
+    sizes = np.diff(nodes.metadata_offset)
+    return {
+        u: _preview(_decode_row(nodes, _row_bytes(nodes, u))["value"])
+        for u in node_ids if sizes[u]
+    }
+
+
+def get_metadata_schemas(ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """Schemas for the top-level metadata and every table with metadata."""
+    tables = ts.tables
+    result = {TOP_LEVEL_METADATA: describe_schema(ts.metadata_schema)}
+    for name in METADATA_TABLES:
+        table = getattr(tables, name)
+        offsets = table.metadata_offset
+        result[name] = {
+            **describe_schema(table.metadata_schema),
+            "num_rows": table.num_rows,
+            "num_rows_with_metadata": int(np.count_nonzero(np.diff(offsets))) if table.num_rows else 0,
+        }
+    return result
+
+
+def decode_metadata(
+    ts: tskit.TreeSequence,
+    table: str,
+    row_ids: Optional[List[int]] = None,
+    offset: int = 0,
+    limit: int = 100
+) -> Dict[str, Any]:
+    """Decode metadata rows of a table (or the top-level metadata) into typed values."""
+    if table == TOP_LEVEL_METADATA:
+        if ts.metadata_schema.schema is None:
+            return {"table": TOP_LEVEL_METADATA, "rows": [{"id": 0, **_decode_raw(ts.tables.metadata)}]}
+        return {"table": TOP_LEVEL_METADATA, "rows": [{"id": 0, "value": _json_safe(ts.metadata), "encoding": "schema"}]}
+
+    tables = ts.tables
+    source = _get_table(tables, table)
+    if row_ids is None:
+        row_ids = list(range(max(offset, 0), min(offset + min(limit, MAX_ROWS_PER_REQUEST), source.num_rows)))
+    elif len(row_ids) > MAX_ROWS_PER_REQUEST:
+        raise ValueError(f"At most {MAX_ROWS_PER_REQUEST} rows can be decoded per request")
+
+    rows = []
+    for row_id in row_ids:
+        if not 0 <= row_id < source.num_rows:
+            raise ValueError(f"Row {row_id} is out of range for table '{table}'")
+        rows.append({"id": row_id, **_decode_row(source, _row_bytes(source, row_id))})
+
+    return {"table": table, "num_rows": source.num_rows, "rows": rows}
+
+
+def _encode_rows(schema: tskit.MetadataSchema, values: List[Any], table: str) -> List[bytes]:
+    encoded = []
+    for row_id, value in enumerate(values):
+        try:
+            if schema.schema is None:
+                if isinstance(value, (bytes, bytearray)):
+                    encoded.append(bytes(value))
+                elif value is None:
+                    encoded.append(b"")
+                else:
+                    encoded.append(value.encode("utf-8") if isinstance(value, str) else json.dumps(value).encode("utf-8"))
+            else:
+                encoded.append(schema.validate_and_encode_row(value))
+        except tskit.MetadataValidationError as e:
+            raise ValueError(f"Metadata for {table} row {row_id} does not match the schema: {e}")
+    return encoded
+
+
+def apply_metadata_edits(
+    ts: tskit.TreeSequence,
+    edits: Optional[Dict[str, Dict[int, Any]]] = None,
+    schemas: Optional[Dict[str, Optional[Dict[str, Any]]]] = None
+) -> tskit.TreeSequence:
+    """Write edited metadata and/or schemas into a new tree sequence.
+
+    Args:
+        ts: Source tree sequence
+        edits: Table name -> {row ID: new metadata value}; the top-level
+            metadata is table "tree_sequence", row 0
+        schemas: Table name -> new JSON schema (None removes the schema).
+            Existing rows are decoded with the old schema and re-encoded with
+            the new one, so they must be valid under it.
+
+    Raises:
+        ValueError: If a row is out of range or metadata fails schema validation
+    """
+    edits = edits or {}
+    schemas = schemas or {}
+    tables = ts.dump_tables()
+
+    for table in set(edits) | set(schemas):
+        new_schema = None
+        if table in schemas:
+            try:
+                new_schema = tskit.MetadataSchema(schemas[table])
+            except Exception as e:
+                raise ValueError(f"Invalid metadata schema for '{table}': {e}")
+        table_edits = {k: _from_json_safe(v) for k, v in edits.get(table, {}).items()}
+
+        if table == TOP_LEVEL_METADATA:
+            value = table_edits.get(0, ts.metadata)
+            if 0 not in table_edits and new_schema is not None and ts.metadata_schema.schema is None:
+                value = _decode_raw(value)["value"]
+            schema = new_schema or tables.metadata_schema
+            [encoded] = _encode_rows(schema, [value], TOP_LEVEL_METADATA)
+            tables.metadata_schema = schema
+            tables.metadata = value if schema.schema is not None else encoded
+            continue
+
+        target = _get_table(tables, table)
+        source = _get_table(ts.tables, table)
+        for row_id in table_edits:
+            if not 0 <= row_id < source.num_rows:
+                raise ValueError(f"Row {row_id} is out of range for table '{table}'")
+
+        # Without a schema, unedited rows are raw bytes and are kept verbatim
+        values = [table_edits[row_id] if row_id in table_edits else source[row_id].metadata for row_id in range(source.num_rows)]
+        schema = new_schema or target.metadata_schema
+        if new_schema is not None and source.metadata_schema.schema is None:
+            # Raw bytes are only meaningful to a new schema if they are JSON-encoded
+            values = [v if row_id in table_edits else _decode_raw(v)["value"] for row_id, v in enumerate(values)]
+
+        encoded = _encode_rows(schema, values, table)
+        target.metadata_schema = schema
+        target.packset_metadata(encoded)
+
+    logger.info(f"Applied metadata edits to {sorted(edits)} with schema changes to {sorted(schemas)}")
+    return tables.tree_sequence()
@@ -0,0 +1,151 @@
+"""
+Per-node structural metrics for ARG nodes.
+Used by the frontend to size and color nodes by structural importance.
+"""
+
+import logging
+import random
+from collections import deque
+from typing import Any, Dict, List, Optional, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.timing import timed
+from argscape.backend.tskit_utils.traversal import adjacency_pairs
+
+logger = logging.getLogger(__name__)
+
+# msprime flags nodes created by recombination events when record_full_arg=True
+NODE_IS_RE_EVENT = 1 << 17
+
+# Above this many nodes betweenness is estimated from a sample of source nodes
+BETWEENNESS_EXACT_NODE_LIMIT = 2000
+BETWEENNESS_DEFAULT_PIVOTS = 500
+
+
+def compute_betweenness(
+    num_nodes: int,
+    pairs: np.ndarray,
+    max_pivots: Optional[int] = None,
+    random_seed: Optional[int] = None,
+    cancel_token: Optional[CancellationToken] = None
+) -> Tuple[np.ndarray, bool]:
+    """Normalised betweenness centrality on the undirected parent-child graph.
+
+    Uses Brandes' algorithm. When max_pivots is smaller than the number of
+    connected nodes, shortest paths are only accumulated from a random sample
+    of them as sources and the result is rescaled (Brandes & Pich, 2007).
+
+    Returns:
+        Tuple of (betweenness per node, whether it is exact rather than sampled)
+    """
+    adjacency: List[List[int]] = [[] for _ in range(num_nodes)]
+    for parent, child in pairs:
+        adjacency[int(parent)].append(int(child))
+        adjacency[int(child)].append(int(parent))
+
+    sources = [u for u in range(num_nodes) if adjacency[u]]
+    scale = 1.0
+    sampled = max_pivots is not None and max_pivots < len(sources)
+    if sampled:
+        rng = random.Random(random_seed)
+        scale = len(sources) / max_pivots
+        sources = rng.sample(sources, max_pivots)
+
+    betweenness = np.zeros(num_nodes)
+    for s in sources:
+        check_cancelled(cancel_token)
+        stack = []
+        predecessors: Dict[int, List[int]] = {s: []}
+        sigma = {s: 1.0}
+        distance = {s: 0}
+        queue = deque([s])
+        while queue:
+            v = queue.popleft()
+            stack.append(v)
+            for w in adjacency[v]:
+                if w not in distance:
+                    distance[w] = distance[v] + 1
+                    sigma[w] = 0.0
+                    predecessors[w] = []
+                    queue.append(w)
+                if distance[w] == distance[v] + 1:
+                    sigma[w] += sigma[v]
+                    predecessors[w].append(v)
+        delta = {v: 0.0 for v in stack}
+        while stack:
+            w = stack.pop()
+            for v in predecessors[w]:
+                delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w])
+            if w != s:
+                betweenness[w] += delta[w]
+
+    # Each undirected path is counted from both ends
+    betweenness *= scale / 2.0
+    n = len([u for u in range(num_nodes) if adjacency[u]])
+    if n > 2:
+        betweenness /= (n - 1) * (n - 2) / 2.0
+    return betweenness, not sampled
+
+
+@timed("analysis")
+def compute_node_metrics(
+    ts: tskit.TreeSequence,
+    include_betweenness: bool = True,
+    max_pivots: Optional[int] = None,
+    random_seed: Optional[int] = None,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Compute per-node structural metrics, returned as arrays indexed by node ID.
+
+    Metrics:
+        descendant_samples: span-weighted mean number of samples below the node
+        genomic_span: length of genome over which the node is ancestral to a sample
+        num_parents / num_children / degree: distinct neighbours in the ARG
+        betweenness: normalised betweenness centrality in the ARG graph
+        is_recombination: node has more than one parent (or carries msprime's RE flag)
+    """
+    logger.info(f"Computing node metrics for {ts.num_nodes} nodes, {ts.num_edges} edges")
+
+    samples = ts.samples()
+    if len(samples) > 0:
+        total_descendants = ts.sample_count_stat(
+            [samples], lambda x: x, 1, mode="node", span_normalise=False, polarised=True
+        ).reshape(ts.num_nodes)
+        genomic_span = ts.sample_count_stat(
+            [samples], lambda x: (x > 0).astype(float), 1, mode="node", span_normalise=False, polarised=True
+        ).reshape(ts.num_nodes)
+    else:
+        total_descendants = np.zeros(ts.num_nodes)
+        genomic_span = np.zeros(ts.num_nodes)
+
+    with np.errstate(divide="ignore", invalid="ignore"):
+        descendant_samples = np.where(genomic_span > 0, total_descendants / genomic_span, 0.0)
+
+    check_cancelled(cancel_token)
+    pairs = adjacency_pairs(ts)
+    num_parents = np.bincount(pairs[:, 1], minlength=ts.num_nodes) if len(pairs) else np.zeros(ts.num_nodes, dtype=int)
+    num_children = np.bincount(pairs[:, 0], minlength=ts.num_nodes) if len(pairs) else np.zeros(ts.num_nodes, dtype=int)
+    is_recombination = (num_parents > 1) | ((ts.tables.nodes.flags & NODE_IS_RE_EVENT) != 0)
+
+    result = {
+        "num_nodes": ts.num_nodes,
+        "descendant_samples": descendant_samples.tolist(),
+        "genomic_span": genomic_span.tolist(),
+        "num_parents": num_parents.tolist(),
+        "num_children": num_children.tolist(),
+        "degree": (num_parents + num_children).tolist(),
+        "is_recombination": is_recombination.tolist(),
+        "num_recombination_nodes": int(is_recombination.sum()),
+    }
+
+    if include_betweenness:
+        if max_pivots is None and ts.num_nodes > BETWEENNESS_EXACT_NODE_LIMIT:
+            max_pivots = BETWEENNESS_DEFAULT_PIVOTS
+        betweenness, exact = compute_betweenness(ts.num_nodes, pairs, max_pivots, random_seed, cancel_token)
+        result["betweenness"] = betweenness.tolist()
+        result["betweenness_exact"] = exact
+
+    return result
@@ -0,0 +1,231 @@
+"""
+Polytomy detection and resolution for tree sequences.
+Inferred ARGs (e.g. from tsinfer) often contain large polytomies that make
+layouts unreadable; these helpers quantify them and produce binary derived
+tree sequences.
+"""
+
+import logging
+import random
+from collections import defaultdict
+from typing import Any, Dict, List, Optional, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+from argscape.backend.tskit_utils.traversal import edges_by_node, get_edge_index
+
+logger = logging.getLogger(__name__)
+
+RESOLUTION_METHODS = ("random", "zero_length")
+
+
+def get_polytomy_stats(
+    ts: tskit.TreeSequence,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Quantify polytomy sizes across all local trees.
+
+    A polytomy is any node with more than two children in a local tree.
+
+    Returns:
+        Dictionary with a size histogram, per-tree maxima and summary counts.
+    """
+    size_histogram: Dict[int, int] = defaultdict(int)
+    per_tree_max_size: List[int] = []
+    polytomic_nodes = set()
+    polytomic_span = 0.0
+
+    for tree in ts.trees():
+        check_cancelled(cancel_token)
+        num_children = tree.num_children_array[:-1]  # drop the virtual root
+        polytomy_ids = np.where(num_children > 2)[0]
+        if len(polytomy_ids) == 0:
+            per_tree_max_size.append(int(num_children.max()) if len(num_children) else 0)
+            continue
+
+        sizes = num_children[polytomy_ids]
+        for size in sizes:
+            size_histogram[int(size)] += 1
+        polytomic_nodes.update(int(u) for u in polytomy_ids)
+        polytomic_span += tree.span
+        per_tree_max_size.append(int(sizes.max()))
+
+    max_size = max(per_tree_max_size) if per_tree_max_size else 0
+
+    return {
+        "is_binary": len(polytomic_nodes) == 0,
+        "num_trees": ts.num_trees,
+        "num_polytomic_nodes": len(polytomic_nodes),
+        "max_polytomy_size": max_size if polytomic_nodes else 0,
+        "polytomic_span_fraction": polytomic_span / ts.sequence_length if ts.sequence_length > 0 else 0.0,
+        "size_histogram": dict(sorted(size_histogram.items())),
+        "per_tree_max_size": per_tree_max_size,
+    }
+
+
+def _child_segments(parent_edges: List[tskit.Edge]) -> List[Tuple[float, float, List[int]]]:
+    """Split a parent's edges into maximal intervals with a constant child set."""
+    breakpoints = sorted({e.left for e in parent_edges} | {e.right for e in parent_edges})
+    segments: List[Tuple[float, float, List[int]]] = []
+    for left, right in zip(breakpoints[:-1], breakpoints[1:]):
+        children = sorted(e.child for e in parent_edges if e.left <= left and e.right >= right)
+        if not children:
+            continue
+        # Merge with the previous segment when the child set is unchanged
+        if segments and segments[-1][1] == left and segments[-1][2] == children:
+            segments[-1] = (segments[-1][0], right, children)
+        else:
+            segments.append((left, right, children))
+    return segments
+
+
+def _resolution_times(parent_time: float, lower_bound: float, num_new_nodes: int, method: str) -> List[float]:
+    """Times for the intermediate nodes, increasing and strictly between the children and the parent.
+
+    When the interval is too narrow for the spacing to survive rounding, the
+    nodes take the representable times just below the parent instead.
+
+    Raises:
+        ValueError: If there aren't that many representable times in the interval
+    """
+    if method == "zero_length":
+        # Nearly zero-length branches just below the parent
+        eps = (parent_time - lower_bound) * 1e-6 / (num_new_nodes + 1)
+        times = [parent_time - eps * (num_new_nodes - k) for k in range(num_new_nodes)]
+    else:
+        # Evenly spread the new nodes between the oldest child and the parent
+        step = (parent_time - lower_bound) / (num_new_nodes + 1)
+        times = [lower_bound + step * (k + 1) for k in range(num_new_nodes)]
+    bounds = [lower_bound, *times, parent_time]
+    if all(a < b for a, b in zip(bounds[:-1], bounds[1:])):
+        return times
+
+    times = []
+    time = parent_time
+    for _ in range(num_new_nodes):
+        time = float(np.nextafter(time, -np.inf))
+        times.append(time)
+    if times[-1] <= lower_bound:
+        raise ValueError(
+            f"No room for {num_new_nodes} nodes between time {lower_bound} and a parent at time {parent_time}"
+        )
+    return times[::-1]
+
+
+def resolve_polytomies(
+    ts: tskit.TreeSequence,
+    method: str = "random",
+    random_seed: Optional[int] = None,
+    cancel_token: Optional[CancellationToken] = None
+) -> Tuple[tskit.TreeSequence, Dict[str, Any]]:
+    """Resolve every polytomy into a random binary subtree.
+
+    Args:
+        ts: Input tree sequence
+        method: "random" spreads the new nodes between the oldest child and the
+            parent; "zero_length" places them just below the parent so the
+            original node times are visually preserved
+        random_seed: Seed for the random binary topologies
+        cancel_token: Optional token checked once per polytomic parent
+
+    Returns:
+        Tuple of (resolved tree sequence, resolution info dict)
+    """
+    if method not in RESOLUTION_METHODS:
+        raise ValueError(f"Unknown resolution method '{method}'. Expected one of {RESOLUTION_METHODS}")
+
+    rng = random.Random(random_seed)
+    tables = ts.dump_tables()
+    node_times = ts.tables.nodes.time
+
+    # Only polytomic parents' edges are needed as objects; the rest are found from the columns
+    polytomic_parents = {int(u) for u in np.flatnonzero(get_edge_index(ts).num_children() > 2)}
+    edges_by_parent = edges_by_node(ts, sorted(polytomic_parents), "children")
+
+    if not polytomic_parents:
+        logger.info("Tree sequence is already binary, nothing to resolve")
+        return ts, {"num_polytomies_resolved": 0, "num_nodes_added": 0, "method": method, "random_seed": random_seed}
+
+    # Keep every other parent's edges as they are, then append the resolved ones
+    edges = tables.edges
+    keep = ~np.isin(edges.parent, np.fromiter(polytomic_parents, dtype=np.int32))
+    metadata_lengths = np.diff(edges.metadata_offset)
+    edges.set_columns(
+        left=edges.left[keep],
+        right=edges.right[keep],
+        parent=edges.parent[keep],
+        child=edges.child[keep],
+        metadata=edges.metadata[np.repeat(keep, metadata_lengths)],
+        metadata_offset=np.concatenate([[0], np.cumsum(metadata_lengths[keep])]).astype(edges.metadata_offset.dtype)
+    )
+
+    new_edges: Dict[str, List[Any]] = {"left": [], "right": [], "parent": [], "child": []}
+
+    def add_edge(left: float, right: float, parent: int, child: int):
+        new_edges["left"].append(left)
+        new_edges["right"].append(right)
+        new_edges["parent"].append(parent)
+        new_edges["child"].append(child)
+
+    num_resolved = 0
+    num_nodes_added = 0
+    for parent in sorted(polytomic_parents):
+        check_cancelled(cancel_token)
+        parent_node = ts.node(parent)
+        resolved = False
+        for left, right, children in _child_segments(edges_by_parent[parent]):
+            if len(children) <= 2:
+                for child in children:
+                    add_edge(left, right, parent, child)
+                continue
+
+            lineages = list(children)
+            rng.shuffle(lineages)
+            times = _resolution_times(
+                parent_node.time,
+                float(max(node_times[c] for c in children)),
+                len(children) - 2,
+                method
+            )
+            for new_time in times:
+                a = lineages.pop(rng.randrange(len(lineages)))
+                b = lineages.pop(rng.randrange(len(lineages)))
+                new_node = tables.nodes.add_row(
+                    flags=0,
+                    time=new_time,
+                    population=parent_node.population,
+                    metadata=parent_node.metadata
+                )
+                add_edge(left, right, new_node, a)
+                add_edge(left, right, new_node, b)
+                lineages.append(new_node)
+                num_nodes_added += 1
+            for child in lineages:
+                add_edge(left, right, parent, child)
+            resolved = True
+        # A parent counts once however many of its intervals were polytomic
+        num_resolved += resolved
+
+    edges.append_columns(
+        left=np.array(new_edges["left"], dtype=np.float64),
+        right=np.array(new_edges["right"], dtype=np.float64),
+        parent=np.array(new_edges["parent"], dtype=np.int32),
+        child=np.array(new_edges["child"], dtype=np.int32)
+    )
+
+    tables.sort()
+    tables.edges.squash()
+    tables.sort()
+    tables.build_index()
+    resolved_ts = tables.tree_sequence()
+
+    logger.info(f"Resolved {num_resolved} polytomies by adding {num_nodes_added} nodes ({method})")
+
+    return resolved_ts, {
+        "num_polytomies_resolved": num_resolved,
+        "num_nodes_added": num_nodes_added,
+        "method": method,
+        "random_seed": random_seed
+    }
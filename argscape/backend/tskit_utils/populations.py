@@ -0,0 +1,157 @@
+"""
+Viewing and editing the populations table.
+Many pipelines leave populations as bare IDs, so every view says pop_0,
+pop_1, pop_2. Names, descriptions, colors and display order are kept in each
+population's JSON metadata ("color" and "display_order" alongside msprime's
+"name" and "description"), where the palette and every view read them, and
+edits are written to a new tree sequence rather than the loaded one.
+"""
+
+import copy
+import logging
+from typing import Any, Dict, List, Optional, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.palettes import population_metadata, resolve_palette, validate_color
+
+logger = logging.getLogger(__name__)
+
+EDITABLE_FIELDS = ("name", "description", "color", "display_order")
+# Schema properties for the editable fields, added to the table's schema when it lists properties
+FIELD_SCHEMAS = {
+    "name": {"type": "string"},
+    "description": {"type": ["string", "null"]},
+    "color": {"type": ["string", "null"]},
+    "display_order": {"type": ["integer", "null"]},
+}
+
+
+def list_populations(ts: tskit.TreeSequence, palette_choice: Optional[Dict[str, Any]] = None) -> Dict[str, Any]:
+    """Each population with its editable fields, resolved color and how many samples and nodes it has."""
+    metadata = population_metadata(ts)
+    palette = resolve_palette(ts, palette_choice)
+    node_population = ts.tables.nodes.population
+    samples = ts.samples()
+    num_nodes = np.bincount(node_population[node_population >= 0], minlength=ts.num_populations)
+    num_samples = np.bincount(node_population[samples][node_population[samples] >= 0], minlength=ts.num_populations)
+    populations = []
+    for resolved in palette["populations"]:
+        i = resolved["id"]
+        populations.append({
+            **resolved,
+            "description": metadata[i].get("description"),
+            "stored_color": metadata[i].get("color"),
+            # False when the name is only the pop_<id> fallback
+            "named": bool(metadata[i].get("name")),
+            "num_samples": int(num_samples[i]),
+            "num_nodes": int(num_nodes[i]),
+        })
+    schema = ts.tables.populations.metadata_schema.schema
+    return {
+        "populations": populations,
+        "num_unnamed": sum(1 for p in populations if not p["named"]),
+        "schema_codec": schema.get("codec") if schema else None,
+    }
+
+
+def _edited_schema(schema: Optional[Dict[str, Any]]) -> Tuple[tskit.MetadataSchema, Optional[str]]:
+    """A JSON schema that accepts the editable fields, and a note when it differs from the old one."""
+    if not schema:
+        return tskit.MetadataSchema.permissive_json(), "The populations table had no metadata schema; a JSON schema was added"
+    if schema.get("codec") != "json":
+        return tskit.MetadataSchema.permissive_json(), f"Population metadata was converted from the {schema.get('codec')} codec to JSON"
+    if "properties" not in schema and schema.get("additionalProperties", True) is not False:
+        return tskit.MetadataSchema(schema), None
+    schema = copy.deepcopy(schema)
+    missing = [field for field in EDITABLE_FIELDS if field not in schema.setdefault("properties", {})]
+    for field in missing:
+        schema["properties"][field] = FIELD_SCHEMAS[field]
+    return tskit.MetadataSchema(schema), f"Added {', '.join(missing)} to the population metadata schema" if missing else None
+
+
+def _validate_edit(population: int, field: str, value: Any) -> Any:
+    if field not in EDITABLE_FIELDS:
+        raise ValueError(f"Population {population}: '{field}' is not editable. Expected one of {EDITABLE_FIELDS}")
+    if value is None:
+        if field == "name":
+            raise ValueError(f"Population {population}: the name cannot be removed")
+        return None
+    if field == "name":
+        if not isinstance(value, str) or not value.strip():
+            raise ValueError(f"Population {population}: names must be non-empty strings")
+        return value.strip()
+    if field == "description":
+        return str(value)
+    if field == "color":
+        return validate_color(value)
+    if isinstance(value, bool) or not isinstance(value, int):
+        raise ValueError(f"Population {population}: display_order must be an integer")
+    return value
+
+
+def apply_population_edits(
+    ts: tskit.TreeSequence,
+    edits: Optional[Dict[Any, Dict[str, Any]]] = None,
+    order: Optional[List[int]] = None
+) -> Tuple[tskit.TreeSequence, Dict[str, Any]]:
+    """Write population edits into a new tree sequence; returns it and a report of what changed.
+
+    Args:
+        edits: Population ID -> {field: value}; a None value removes the field
+            (except the name)
+        order: Every population ID in display order; sets each display_order
+
+    Raises:
+        ValueError: If a population or field is unknown, names clash or the order is incomplete
+    """
+    edits = {int(k): v for k, v in (edits or {}).items()}
+    for population in edits:
+        if not 0 <= population < ts.num_populations:
+            raise ValueError(f"Population {population} does not exist")
+    if order is not None:
+        if sorted(order) != list(range(ts.num_populations)):
+            raise ValueError(f"order must list every population ID (0-{ts.num_populations - 1}) once")
+        for position, population in enumerate(order):
+            edits.setdefault(population, {})["display_order"] = position
+
+    records = [dict(m) for m in population_metadata(ts)]
+    changes = []
+    for population, fields in sorted(edits.items()):
+        for field, value in fields.items():
+            value = _validate_edit(population, field, value)
+            old = records[population].get(field)
+            if value == old:
+                continue
+            if value is None:
+                records[population].pop(field, None)
+            else:
+                records[population][field] = value
+            changes.append({"population": population, "field": field, "old": old, "new": value})
+    names = [m.get("name", f"pop_{i}") for i, m in enumerate(records)]
+    duplicates = sorted({name for name in names if names.count(name) > 1})
+    if duplicates:
+        raise ValueError(f"Population names must be unique: {duplicates}")
+
+    tables = ts.dump_tables()
+    schema, schema_note = _edited_schema(tables.populations.metadata_schema.schema)
+    required = schema.schema.get("required", []) if schema.schema else []
+    for i, record in enumerate(records):
+        # msprime's schema requires both; fill them so untouched rows still validate
+        if "name" in required:
+            record.setdefault("name", f"pop_{i}")
+        if "description" in required:
+            record.setdefault("description", "")
+    try:
+        encoded = [schema.validate_and_encode_row(record) for record in records]
+    except tskit.MetadataValidationError as e:
+        raise ValueError(f"Edited population metadata does not fit the table's schema: {e}")
+    tables.populations.metadata_schema = schema
+    tables.populations.packset_metadata(encoded)
+    logger.info(f"Applied {len(changes)} population edits")
+    return tables.tree_sequence(), {
+        "changes": changes,
+        "schema_changed": schema_note,
+        "num_populations": ts.num_populations,
+    }
@@ -0,0 +1,256 @@
+"""
+Simulation context recovered from tree sequence provenance and metadata.
+Recognises SLiM, stdpopsim and msprime output, so the UI can show the
+demographic model and simulation parameters alongside the ARG.
+"""
+
+import json
+import logging
+from typing import Any, Dict, List, Optional
+
+import tskit
+
+logger = logging.getLogger(__name__)
+
+# Keep model scripts and provenance commands from bloating responses
+MAX_SCRIPT_CHARS = 20000
+
+STDPOPSIM_KEYS = {
+    "species": ("species", "species_id"),
+    "model": ("model", "demographic_model", "model_id"),
+    "contig": ("contig", "chromosome", "chrom"),
+    "genetic_map": ("genetic_map",),
+    "genome_build": ("genome_build", "assembly"),
+    "engine": ("engine",),
+    "samples": ("samples", "population_samples"),
+    "seed": ("seed", "random_seed"),
+    "dfe": ("dfe",),
+}
+
+MSPRIME_PARAMETER_KEYS = (
+    "sequence_length", "recombination_rate", "gene_conversion_rate", "ploidy",
+    "model", "random_seed", "start_time", "end_time", "discrete_genome",
+    "rate", "keep",
+)
+
+
+def _unwrap(value: Any) -> Any:
+    """Strip the msprime/tskit JSON encoding wrappers (__class__, __ndarray__)."""
+    if isinstance(value, dict):
+        if "__ndarray__" in value:
+            return _unwrap(value["__ndarray__"])
+        return {k: _unwrap(v) for k, v in value.items() if k != "__class__"}
+    if isinstance(value, list):
+        return [_unwrap(v) for v in value]
+    return value
+
+
+def _class_name(value: Any) -> Optional[str]:
+    if isinstance(value, dict) and isinstance(value.get("__class__"), str):
+        return value["__class__"].rsplit(".", 1)[-1]
+    return None
+
+
+def _parse_records(ts: tskit.TreeSequence) -> List[Dict[str, Any]]:
+    records = []
+    for provenance in ts.provenances():
+        try:
+            record = json.loads(provenance.record)
+        except (TypeError, ValueError):
+            continue
+        if isinstance(record, dict):
+            record["_timestamp"] = provenance.timestamp
+            records.append(record)
+    return records
+
+
+def _software_name(record: Dict[str, Any]) -> str:
+    software = record.get("software")
+    name = software.get("name", "") if isinstance(software, dict) else str(software or "")
+    return name.lower()
+
+
+def _first(mapping: Dict[str, Any], keys) -> Any:
+    for key in keys:
+        if mapping.get(key) is not None:
+            return mapping[key]
+    return None
+
+
+def _summarize_record(record: Dict[str, Any]) -> Dict[str, Any]:
+    software = record.get("software") if isinstance(record.get("software"), dict) else {}
+    parameters = record.get("parameters") if isinstance(record.get("parameters"), dict) else {}
+    command = parameters.get("command")
+    return {
+        "software": software.get("name"),
+        "version": software.get("version"),
+        "command": " ".join(map(str, command)) if isinstance(command, list) else command,
+        "timestamp": record["_timestamp"],
+    }
+
+
+def _population_table(ts: tskit.TreeSequence) -> List[Dict[str, Any]]:
+    populations = []
+    for population in ts.populations():
+        metadata = population.metadata
+        if isinstance(metadata, bytes):
+            try:
+                metadata = json.loads(metadata.decode()) if metadata else {}
+            except (UnicodeDecodeError, ValueError):
+                metadata = {}
+        if not isinstance(metadata, dict):
+            metadata = {}
+        populations.append({
+            "id": population.id,
+            "name": metadata.get("name"),
+            "description": metadata.get("description"),
+            # Scalar SLiM / msprime population attributes (sex ratio, selfing, ...)
+            "attributes": {
+                k: v for k, v in metadata.items()
+                if k not in ("name", "description") and isinstance(v, (int, float, str, bool))
+            },
+        })
+    return populations
+
+
+def _msprime_demography(record: Dict[str, Any]) -> Optional[Dict[str, Any]]:
+    demography = record.get("parameters", {}).get("demography")
+    if not isinstance(demography, dict):
+        return None
+
+    populations = [
+        {
+            "id": pop.get("id", i),
+            "name": pop.get("name"),
+            "description": pop.get("description"),
+            "initial_size": pop.get("initial_size"),
+            "growth_rate": pop.get("growth_rate"),
+            "default_sampling_time": pop.get("default_sampling_time"),
+            "initially_active": pop.get("initially_active"),
+        }
+        for i, pop in enumerate(_unwrap(demography.get("populations", [])))
+    ]
+    events = []
+    for event in demography.get("events", []):
+        unwrapped = _unwrap(event)
+        events.append({"type": _class_name(event) or "event", **unwrapped})
+    events.sort(key=lambda e: e.get("time", 0) or 0)
+
+    return {
+        "source": "msprime",
+        "populations": populations,
+        "events": events,
+        "migration_matrix": _unwrap(demography.get("migration_matrix")),
+    }
+
+
+def _slim_context(ts: tskit.TreeSequence, records: List[Dict[str, Any]]) -> Optional[Dict[str, Any]]:
+    top = ts.metadata.get("SLiM") if isinstance(ts.metadata, dict) else None
+    slim_records = [r for r in records if _software_name(r) == "slim"]
+    if top is None and not slim_records:
+        return None
+
+    context: Dict[str, Any] = {}
+    if slim_records:
+        last = slim_records[-1]
+        parameters = last.get("parameters", {})
+        slim_info = last.get("slim", {})
+        context.update({
+            "model_type": parameters.get("model_type") or slim_info.get("model_type"),
+            "tick": slim_info.get("tick", slim_info.get("generation")),
+            "seed": parameters.get("seed"),
+            "stage": parameters.get("stage"),
+            "model_file": parameters.get("model_file"),
+            "model_script": parameters["model"][:MAX_SCRIPT_CHARS] if isinstance(parameters.get("model"), str) else None,
+        })
+    if top:
+        context.update({
+            "model_type": top.get("model_type", context.get("model_type")),
+            "tick": top.get("tick", top.get("generation", context.get("tick"))),
+            "cycle": top.get("cycle"),
+            "stage": top.get("stage", context.get("stage")),
+            "spatial_dimensionality": top.get("spatial_dimensionality"),
+            "spatial_periodicity": top.get("spatial_periodicity"),
+            "separate_sexes": top.get("separate_sexes"),
+            "nucleotide_based": top.get("nucleotide_based"),
+            "file_version": top.get("file_version"),
+            "name": top.get("name"),
+            "description": top.get("description"),
+        })
+    return {k: v for k, v in context.items() if v is not None}
+
+
+def _stdpopsim_context(ts: tskit.TreeSequence, records: List[Dict[str, Any]]) -> Optional[Dict[str, Any]]:
+    top = ts.metadata.get("stdpopsim") if isinstance(ts.metadata, dict) else None
+    stdpopsim_records = [r for r in records if _software_name(r) == "stdpopsim"]
+    if top is None and not stdpopsim_records:
+        return None
+
+    sources = []
+    if stdpopsim_records:
+        last = stdpopsim_records[-1]
+        parameters = last.get("parameters", {})
+        sources.append(parameters)
+        if isinstance(parameters.get("args"), dict):
+            sources.append(parameters["args"])
+        sources[0] = {**sources[0], "version": last.get("software", {}).get("version")}
+    if isinstance(top, dict):
+        sources.append(top)
+
+    context: Dict[str, Any] = {}
+    for source in sources:
+        for field, keys in STDPOPSIM_KEYS.items():
+            value = _first(source, keys)
+            if value is not None:
+                context[field] = _unwrap(value)
+        if source.get("version"):
+            context["version"] = source["version"]
+    return context
+
+
+def get_simulation_context(ts: tskit.TreeSequence) -> Dict[str, Any]:
+    """Recover how a tree sequence was simulated.
+
+    Returns:
+        Dict with the detected source ("stdpopsim", "slim", "msprime" or None),
+        per-simulator details, the demographic model (msprime demography when
+        recorded, otherwise the population table), simulation parameters and a
+        summary of every provenance record.
+    """
+    records = _parse_records(ts)
+    slim = _slim_context(ts, records)
+    stdpopsim = _stdpopsim_context(ts, records)
+
+    demography = None
+    parameters: Dict[str, Any] = {}
+    for record in records:
+        if _software_name(record) != "msprime":
+            continue
+        record_parameters = record.get("parameters", {})
+        command = record_parameters.get("command")
+        demography = _msprime_demography(record) or demography
+        parameters[command or "msprime"] = {
+            key: _unwrap(record_parameters[key])
+            for key in MSPRIME_PARAMETER_KEYS if record_parameters.get(key) is not None
+        }
+
+    if demography is None:
+        demography = {"source": "population_table", "populations": _population_table(ts), "events": []}
+
+    if stdpopsim is not None:
+        source = "stdpopsim"
+    elif slim is not None:
+        source = "slim"
+    elif any(_software_name(r) == "msprime" for r in records):
+        source = "msprime"
+    else:
+        source = None
+
+    return {
+        "source": source,
+        "slim": slim,
+        "stdpopsim": stdpopsim,
+        "demography": demography,
+        "parameters": parameters,
+        "provenances": [_summarize_record(r) for r in records],
+    }
@@ -0,0 +1,135 @@
+"""
+Windowed scan for signals of selective sweeps.
+Three statistics are computed in genomic windows: haplotype homozygosity (Garud's
+H1 over the window's sites), depression of the mean pairwise TMRCA relative to
+the genome-wide mean, and branch-mode Tajima's D, which turns negative when
+branch length is skewed toward the tips. Each is z-scored across windows and
+oriented so that sweep-like values are positive, and their mean is the score
+track. Adjacent windows above a threshold merge into candidate regions.
+"""
+
+from typing import Any, Dict, List, Optional
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+
+DEFAULT_NUM_WINDOWS = 100
+MAX_NUM_WINDOWS = 2000
+DEFAULT_SCORE_THRESHOLD = 2.0
+
+# Sites between cancellation checks
+CANCEL_CHECK_INTERVAL = 1000
+
+
+def _zscores(values: np.ndarray) -> np.ndarray:
+    """Z-scores ignoring NaN; windows with no value, or no variation, score 0."""
+    finite = np.isfinite(values)
+    z = np.zeros_like(values, dtype=float)
+    if finite.sum() < 2:
+        return z
+    mean = values[finite].mean()
+    std = values[finite].std()
+    if std > 0:
+        z[finite] = (values[finite] - mean) / std
+    return z
+
+
+def _haplotype_homozygosity(
+    ts: tskit.TreeSequence,
+    windows: np.ndarray,
+    cancel_token: Optional[CancellationToken] = None
+) -> np.ndarray:
+    """Garud's H1 (sum of squared haplotype frequencies) per window; NaN without sites."""
+    num_windows = len(windows) - 1
+    genotypes: List[List[np.ndarray]] = [[] for _ in range(num_windows)]
+    for i, variant in enumerate(ts.variants()):
+        if i % CANCEL_CHECK_INTERVAL == 0:
+            check_cancelled(cancel_token)
+        w = min(int(np.searchsorted(windows, variant.site.position, side="right")) - 1, num_windows - 1)
+        genotypes[w].append(variant.genotypes.copy())
+
+    h1 = np.full(num_windows, np.nan)
+    for w, columns in enumerate(genotypes):
+        if not columns:
+            continue
+        haplotypes = np.column_stack(columns)
+        _, counts = np.unique(haplotypes, axis=0, return_counts=True)
+        frequencies = counts / haplotypes.shape[0]
+        h1[w] = float(np.sum(frequencies ** 2))
+    return h1
+
+
+def _merge_candidates(windows: np.ndarray, scores: np.ndarray, threshold: float) -> List[Dict[str, Any]]:
+    candidates: List[Dict[str, Any]] = []
+    previous = None
+    for w, score in enumerate(scores):
+        if score < threshold:
+            previous = None
+            continue
+        if previous is not None and previous == w - 1:
+            region = candidates[-1]
+            region["right"] = float(windows[w + 1])
+            region["num_windows"] += 1
+            region["max_score"] = max(region["max_score"], float(score))
+        else:
+            candidates.append({
+                "left": float(windows[w]),
+                "right": float(windows[w + 1]),
+                "num_windows": 1,
+                "max_score": float(score),
+            })
+        previous = w
+    return candidates
+
+
+def sweep_scan(
+    ts: tskit.TreeSequence,
+    num_windows: int = DEFAULT_NUM_WINDOWS,
+    threshold: float = DEFAULT_SCORE_THRESHOLD,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Score windows along the genome for sweep-like haplotype and genealogy patterns.
+
+    Returns:
+        Per-window statistics and scores, and the merged candidate regions
+        whose score is at least `threshold`.
+    """
+    if not 1 <= num_windows <= MAX_NUM_WINDOWS:
+        raise ValueError(f"num_windows must be between 1 and {MAX_NUM_WINDOWS}")
+    if ts.num_samples < 2:
+        raise ValueError("A sweep scan needs at least two samples")
+    windows = np.linspace(0, ts.sequence_length, num_windows + 1)
+
+    homozygosity = _haplotype_homozygosity(ts, windows, cancel_token)
+    check_cancelled(cancel_token)
+    # Branch-mode diversity is the mean pairwise branch length, twice the mean TMRCA
+    tmrca = np.asarray(ts.diversity(mode="branch", windows=windows), dtype=float) / 2
+    genome_tmrca = float(ts.diversity(mode="branch")) / 2
+    relative_tmrca = tmrca / genome_tmrca if genome_tmrca > 0 else np.full(num_windows, np.nan)
+    check_cancelled(cancel_token)
+    with np.errstate(divide="ignore", invalid="ignore"):
+        tajimas_d = np.asarray(ts.Tajimas_D(mode="branch", windows=windows), dtype=float)
+
+    # Orient so that sweep-like values (high H1, low TMRCA, negative D) are positive
+    components = np.vstack([_zscores(homozygosity), -_zscores(relative_tmrca), -_zscores(tajimas_d)])
+    scores = components.mean(axis=0)
+
+    def values(array: np.ndarray) -> List[Optional[float]]:
+        return [float(v) if np.isfinite(v) else None for v in array]
+
+    return {
+        "num_windows": num_windows,
+        "threshold": threshold,
+        "genome_mean_tmrca": genome_tmrca,
+        "windows": [
+            {"left": float(windows[w]), "right": float(windows[w + 1])}
+            for w in range(num_windows)
+        ],
+        "haplotype_homozygosity": values(homozygosity),
+        "relative_tmrca": values(relative_tmrca),
+        "tajimas_d": values(tajimas_d),
+        "scores": [float(s) for s in scores],
+        "candidates": _merge_candidates(windows, scores, threshold),
+    }
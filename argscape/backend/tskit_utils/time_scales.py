@@ -0,0 +1,82 @@
+"""
+Alternative display time scales for tree sequence nodes.
+Results are cached so the frontend can switch time scales instantly.
+"""
+
+import logging
+from typing import Any, Dict, Optional
+
+import numpy as np
+import tskit
+
+from argscape.backend.ts_cache import TreeSequenceCache
+
+logger = logging.getLogger(__name__)
+
+TIME_SCALE_MODES = ("time", "log", "rank", "years")
+MAX_CACHED_TIME_SCALES = 32
+
+_cache = TreeSequenceCache("time_scales", MAX_CACHED_TIME_SCALES)
+
+
+def compute_display_times(
+    ts: tskit.TreeSequence,
+    mode: str = "time",
+    generation_time: Optional[float] = None
+) -> np.ndarray:
+    """Compute per-node display times, indexed by node ID.
+
+    Args:
+        ts: Tree sequence
+        mode: "time" (raw node times), "log" (log10(time + 1)), "rank"
+            (dense rank of node times, so equal times share a rank) or
+            "years" (time multiplied by the generation time)
+        generation_time: Years per generation, required for "years"
+    """
+    times = ts.tables.nodes.time
+
+    if mode == "time":
+        return times.copy()
+    if mode == "log":
+        return np.log10(times + 1.0)
+    if mode == "rank":
+        _, ranks = np.unique(times, return_inverse=True)
+        return ranks.astype(np.float64)
+    if mode == "years":
+        if generation_time is None or generation_time <= 0:
+            raise ValueError("A positive generation_time is required for the 'years' time scale")
+        return times * generation_time
+
+    raise ValueError(f"Unknown time scale mode '{mode}'. Expected one of {TIME_SCALE_MODES}")
+
+
+def get_display_times(
+    ts: tskit.TreeSequence,
+    mode: str = "time",
+    generation_time: Optional[float] = None
+) -> Dict[str, Any]:
+    """Get display times for a tree sequence, using the cache when possible.
+
+    Args:
+        ts: Tree sequence
+        mode: Time scale mode, see compute_display_times
+        generation_time: Years per generation, required for "years"
+    """
+    key = (mode, generation_time if mode == "years" else None)
+    cached = _cache.get(ts, key)
+    if cached is not None:
+        return cached
+
+    display_times = compute_display_times(ts, mode, generation_time)
+    result = {
+        "mode": mode,
+        "generation_time": generation_time if mode == "years" else None,
+        "time_units": ts.time_units,
+        "display_times": display_times.tolist(),
+        "min": float(display_times.min()) if len(display_times) else 0.0,
+        "max": float(display_times.max()) if len(display_times) else 0.0,
+    }
+
+    _cache.put(ts, result, key)
+    logger.info(f"Computed '{mode}' display times for {ts.num_nodes} nodes")
+    return result
@@ -0,0 +1,147 @@
+"""
+Pairwise TMRCA matrices for heatmap and MDS views.
+TMRCAs come from branch-mode divergence between single samples, which tskit
+computes in C, so pairs are split into chunks evaluated on worker threads.
+Matrices are either the span-weighted genome-wide mean or the local trees at
+given positions. Large sample sets are downsampled first, since the output
+grows with the square of the number of samples.
+"""
+
+import random
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any, Dict, List, Optional, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+
+DEFAULT_MAX_SAMPLES = 200
+MAX_SAMPLES = 2000
+MAX_POSITIONS = 100
+
+# Sample pairs per divergence call
+PAIR_CHUNK_SIZE = 5000
+
+
+def downsample(samples: List[int], max_samples: int, random_seed: Optional[int] = None) -> List[int]:
+    """A random subset of at most `max_samples` samples, kept in their original order."""
+    if len(samples) <= max_samples:
+        return list(samples)
+    chosen = set(random.Random(random_seed).sample(range(len(samples)), max_samples))
+    return [u for i, u in enumerate(samples) if i in chosen]
+
+
+def select_samples(
+    ts: tskit.TreeSequence,
+    samples: Optional[List[int]],
+    max_samples: int,
+    random_seed: Optional[int] = None
+) -> Tuple[List[int], List[int]]:
+    """Check requested samples (default: all) and downsample them; returns (requested, used)."""
+    if not 2 <= max_samples <= MAX_SAMPLES:
+        raise ValueError(f"max_samples must be between 2 and {MAX_SAMPLES}")
+    all_samples = [int(u) for u in ts.samples()]
+    if samples is None:
+        samples = all_samples
+    else:
+        sample_set = set(all_samples)
+        missing = [u for u in samples if u not in sample_set]
+        if missing:
+            raise ValueError(f"Not sample nodes: {missing[:10]}")
+    used = downsample(samples, max_samples, random_seed)
+    if len(used) < 2:
+        raise ValueError("Pairwise comparisons need at least two samples")
+    return samples, used
+
+
+def sample_pairs(n: int) -> List[Tuple[int, int]]:
+    return [(i, j) for i in range(n) for j in range(i + 1, n)]
+
+
+def pairwise_divergence(
+    ts: tskit.TreeSequence,
+    samples: List[int],
+    mode: str = "branch",
+    windows: Optional[np.ndarray] = None,
+    workers: int = 1,
+    cancel_token: Optional[CancellationToken] = None
+) -> Tuple[List[Tuple[int, int]], np.ndarray]:
+    """Divergence between every pair of samples, shape (windows, pairs), computed in chunks on worker threads."""
+    pairs = sample_pairs(len(samples))
+    chunks = [pairs[k:k + PAIR_CHUNK_SIZE] for k in range(0, len(pairs), PAIR_CHUNK_SIZE)]
+    sample_sets = [[u] for u in samples]
+
+    def divergence(chunk):
+        check_cancelled(cancel_token)
+        result = ts.divergence(sample_sets, indexes=chunk, mode=mode, windows=windows)
+        # Without windows the result is one value per index pair
+        return np.atleast_2d(result)
+
+    with ThreadPoolExecutor(max_workers=max(1, workers), thread_name_prefix="argscape-divergence") as executor:
+        results = list(executor.map(divergence, chunks))
+    check_cancelled(cancel_token)
+    return pairs, np.concatenate(results, axis=1)
+
+
+def compute_tmrca_matrix(
+    ts: tskit.TreeSequence,
+    samples: Optional[List[int]] = None,
+    positions: Optional[List[float]] = None,
+    max_samples: int = DEFAULT_MAX_SAMPLES,
+    random_seed: Optional[int] = None,
+    workers: int = 1,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Pairwise TMRCA matrices, genome-wide or at each of `positions`.
+
+    Returns:
+        The samples used (after downsampling), one genome-wide matrix, or one
+        matrix per position with the interval of its local tree. The diagonal
+        holds each sample's own time.
+    """
+    samples, used = select_samples(ts, samples, max_samples, random_seed)
+
+    if positions:
+        if len(positions) > MAX_POSITIONS:
+            raise ValueError(f"At most {MAX_POSITIONS} positions can be requested at once")
+        if any(not 0 <= p < ts.sequence_length for p in positions):
+            raise ValueError(f"Positions must be within [0, {ts.sequence_length})")
+        intervals = [tuple(ts.at(p).interval) for p in positions]
+        edges = sorted({0.0, float(ts.sequence_length)} | {float(x) for interval in intervals for x in interval})
+        windows = np.array(edges)
+        rows = [edges.index(float(left)) for left, _ in intervals]
+    else:
+        windows = None
+
+    pairs, divergences = pairwise_divergence(ts, used, mode="branch", windows=windows, workers=workers, cancel_token=cancel_token)
+
+    times = ts.tables.nodes.time[np.array(used)]
+    index_i = np.array([i for i, _ in pairs])
+    index_j = np.array([j for _, j in pairs])
+
+    def matrix(row: np.ndarray) -> List[List[float]]:
+        result = np.diag(times.astype(float))
+        # Divergence is the branch length from both samples up to their MRCA
+        tmrca = (row + times[index_i] + times[index_j]) / 2
+        result[index_i, index_j] = tmrca
+        result[index_j, index_i] = tmrca
+        return result.tolist()
+
+    response = {
+        "samples": used,
+        "num_requested": len(samples),
+        "downsampled": len(used) < len(samples),
+        "random_seed": random_seed,
+        "time_units": ts.time_units,
+    }
+    if windows is None:
+        response["mode"] = "genome_mean"
+        response["matrix"] = matrix(divergences[0])
+    else:
+        response["mode"] = "positions"
+        response["matrices"] = [
+            {"position": float(p), "interval": [float(left), float(right)], "matrix": matrix(divergences[row])}
+            for p, (left, right), row in zip(positions, intervals, rows)
+        ]
+    return response
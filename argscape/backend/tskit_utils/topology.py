@@ -0,0 +1,140 @@
+"""
+Topology comparison of local trees.
+Each local tree is reduced to its set of clades (the sample sets below its
+internal nodes), so trees that differ only in node IDs or times compare equal.
+Consecutive trees with the same clades, or nearly the same within a
+Robinson-Foulds tolerance, are grouped into blocks: the number of blocks is how
+many effectively distinct genealogies the genome navigator has to show.
+Clade sets also hash to a canonical topology ID, so trees anywhere along the
+genome that share a topology can be found, and rendered only once.
+"""
+
+import hashlib
+from typing import Any, Dict, FrozenSet, List, Optional
+
+import tskit
+
+from argscape.backend.jobs import CancellationToken, check_cancelled
+
+# Trees between cancellation checks
+CANCEL_CHECK_INTERVAL = 100
+
+MAX_TOLERANCE = 1000
+
+
+def tree_clades(tree: tskit.Tree, sample_bits: Dict[int, int]) -> FrozenSet[int]:
+    """Clades of a tree as sample bitmasks, excluding single samples."""
+    below: Dict[int, int] = {}
+    clades = set()
+    for u in tree.nodes(order="postorder"):
+        mask = sample_bits.get(u, 0)
+        for child in tree.children(u):
+            mask |= below.pop(child)
+        below[u] = mask
+        if mask & (mask - 1):  # More than one sample
+            clades.add(mask)
+    return frozenset(clades)
+
+
+def sample_bitmasks(ts: tskit.TreeSequence) -> Dict[int, int]:
+    return {int(u): 1 << i for i, u in enumerate(ts.samples())}
+
+
+def topology_hash(clades: FrozenSet[int]) -> str:
+    """Canonical hash of a labelled topology: equal for trees with the same clades."""
+    digest = hashlib.blake2b(digest_size=16)
+    for mask in sorted(clades):
+        digest.update(mask.to_bytes((mask.bit_length() + 7) // 8, "little"))
+        digest.update(b"|")
+    return digest.hexdigest()
+
+
+def robinson_foulds(clades_a: FrozenSet[int], clades_b: FrozenSet[int]) -> int:
+    """Number of clades found in only one of the two trees."""
+    return len(clades_a ^ clades_b)
+
+
+def topology_blocks(
+    ts: tskit.TreeSequence,
+    tolerance: int = 0,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Group consecutive trees into blocks of identical (tolerance 0) or near-identical topology.
+
+    A tree joins the current block while its Robinson-Foulds distance to the
+    block's first tree is at most `tolerance`, so blocks don't drift.
+    """
+    if not 0 <= tolerance <= MAX_TOLERANCE:
+        raise ValueError(f"tolerance must be between 0 and {MAX_TOLERANCE}")
+    sample_bits = sample_bitmasks(ts)
+    blocks: List[Dict[str, Any]] = []
+    block_clades = None
+
+    for tree in ts.trees():
+        if tree.index % CANCEL_CHECK_INTERVAL == 0:
+            check_cancelled(cancel_token)
+        clades = tree_clades(tree, sample_bits)
+        left, right = tree.interval
+        if block_clades is not None and robinson_foulds(block_clades, clades) <= tolerance:
+            block = blocks[-1]
+            block["end_tree"] = tree.index
+            block["right"] = right
+            block["num_trees"] += 1
+        else:
+            blocks.append({"start_tree": tree.index, "end_tree": tree.index, "left": left, "right": right, "num_trees": 1})
+            block_clades = clades
+
+    spans = [block["right"] - block["left"] for block in blocks]
+    for block, span in zip(blocks, spans):
+        block["span"] = span
+    return {
+        "tolerance": tolerance,
+        "num_trees": ts.num_trees,
+        "num_blocks": len(blocks),
+        "mean_block_span": sum(spans) / len(spans) if spans else 0.0,
+        "max_block_span": max(spans) if spans else 0.0,
+        "blocks": blocks,
+    }
+
+
+def unique_topologies(
+    ts: tskit.TreeSequence,
+    cancel_token: Optional[CancellationToken] = None
+) -> Dict[str, Any]:
+    """Hash every local tree and group the genomic intervals that share a topology.
+
+    Adjacent trees with the same topology are merged into one interval.
+    Topologies are listed by total span, most common first, and `tree_hashes`
+    gives each tree's topology so views can render only one tree per hash.
+    """
+    sample_bits = sample_bitmasks(ts)
+    tree_hashes: List[str] = []
+    topologies: Dict[str, Dict[str, Any]] = {}
+
+    for tree in ts.trees():
+        if tree.index % CANCEL_CHECK_INTERVAL == 0:
+            check_cancelled(cancel_token)
+        key = topology_hash(tree_clades(tree, sample_bits))
+        left, right = tree.interval
+        entry = topologies.get(key)
+        if entry is None:
+            entry = topologies[key] = {"hash": key, "first_tree": tree.index, "trees": [], "intervals": [], "span": 0.0}
+        entry["trees"].append(tree.index)
+        entry["span"] += right - left
+        if entry["intervals"] and entry["intervals"][-1][1] == left:
+            entry["intervals"][-1][1] = right
+        else:
+            entry["intervals"].append([left, right])
+        tree_hashes.append(key)
+
+    ranked = sorted(topologies.values(), key=lambda t: (-t["span"], t["first_tree"]))
+    for entry in ranked:
+        entry["num_trees"] = len(entry["trees"])
+        entry["recurrent"] = len(entry["intervals"]) > 1
+    return {
+        "num_trees": ts.num_trees,
+        "num_unique": len(ranked),
+        "num_recurrent": sum(1 for t in ranked if t["recurrent"]),
+        "topologies": ranked,
+        "tree_hashes": tree_hashes,
+    }
@@ -0,0 +1,223 @@
+"""
+Vectorized edge-table traversal.
+Scanning the edge table one tskit.Edge at a time dominates several analyses
+on chromosome-scale ARGs (millions of edges). These primitives work on the
+edge columns as whole arrays, so the inner loops are numpy's compiled sort,
+bincount, searchsorted and accumulate kernels, which numpy dispatches at
+runtime to the best SIMD instruction set the CPU has (see simd_features).
+The per-node indexes are built once per tree sequence and cached, and are
+shared by missing-data detection, polytomy resolution and neighborhood
+(subgraph) extraction; argscape.backend.benchmark times them against the
+per-edge loops they replace.
+"""
+
+import logging
+from typing import Dict, List, Optional, Sequence, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend.ts_cache import TreeSequenceCache
+
+logger = logging.getLogger(__name__)
+
+MAX_CACHED_INDEXES = 8
+EDGE_DIRECTIONS = ("children", "parents", "both")
+
+_cache = TreeSequenceCache("edge_index", MAX_CACHED_INDEXES)
+
+
+def simd_features() -> List[str]:
+    """SIMD extensions numpy detected on this CPU and dispatches its kernels to."""
+    try:
+        from numpy._core._multiarray_umath import __cpu_features__
+    except ImportError:
+        try:
+            from numpy.core._multiarray_umath import __cpu_features__
+        except ImportError:
+            return []
+    return sorted(name for name, enabled in __cpu_features__.items() if enabled)
+
+
+def _csr(keys: np.ndarray, secondary: np.ndarray, num_nodes: int) -> Tuple[np.ndarray, np.ndarray]:
+    """Edge IDs grouped by node (ordered by `secondary` within a node) and each node's offsets into them."""
+    order = np.lexsort((secondary, keys))
+    offsets = np.zeros(num_nodes + 1, dtype=np.int64)
+    np.cumsum(np.bincount(keys, minlength=num_nodes), out=offsets[1:])
+    return order, offsets
+
+
+def _gather(order: np.ndarray, offsets: np.ndarray, nodes: np.ndarray) -> Tuple[np.ndarray, np.ndarray]:
+    """Every edge indexed under any of `nodes`, with the position in `nodes` it came from."""
+    starts, ends = offsets[nodes], offsets[nodes + 1]
+    counts = ends - starts
+    owner = np.repeat(np.arange(len(nodes)), counts)
+    within = np.arange(counts.sum()) - np.repeat(np.cumsum(counts) - counts, counts)
+    return order[np.repeat(starts, counts) + within], owner
+
+
+class EdgeIndex:
+    """The edge table's columns with every node's edges as parent and as child, in order of left."""
+
+    def __init__(self, ts: tskit.TreeSequence):
+        self.num_nodes = ts.num_nodes
+        self.sequence_length = ts.sequence_length
+        self.parent = ts.edges_parent.astype(np.int64)
+        self.child = ts.edges_child.astype(np.int64)
+        self.left = ts.edges_left
+        self.right = ts.edges_right
+        self.by_parent, self.parent_offsets = _csr(self.parent, self.left, self.num_nodes)
+        self.by_child, self.child_offsets = _csr(self.child, self.left, self.num_nodes)
+
+    def edges_of(self, nodes: Sequence[int], direction: str = "both") -> np.ndarray:
+        """IDs of the edges where any of `nodes` is the parent, the child, or either."""
+        if direction not in EDGE_DIRECTIONS:
+            raise ValueError(f"direction must be one of {EDGE_DIRECTIONS}")
+        nodes = np.asarray(nodes, dtype=np.int64)
+        found = []
+        if direction in ("children", "both"):
+            found.append(_gather(self.by_parent, self.parent_offsets, nodes)[0])
+        if direction in ("parents", "both"):
+            found.append(_gather(self.by_child, self.child_offsets, nodes)[0])
+        return np.unique(np.concatenate(found)) if direction == "both" else found[0]
+
+    def num_children(self) -> np.ndarray:
+        """Distinct children of each node over the whole sequence."""
+        pairs = np.unique(self.parent * self.num_nodes + self.child)
+        return np.bincount(pairs // self.num_nodes, minlength=self.num_nodes)
+
+    def connected(self) -> np.ndarray:
+        """Whether each node is in at least one edge."""
+        mask = np.zeros(self.num_nodes, dtype=bool)
+        mask[self.parent] = True
+        mask[self.child] = True
+        return mask
+
+
+def get_edge_index(ts: tskit.TreeSequence) -> EdgeIndex:
+    """The EdgeIndex for a tree sequence, built once and cached."""
+    index = _cache.get(ts)
+    if index is None:
+        index = EdgeIndex(ts)
+        _cache.put(ts, index)
+    return index
+
+
+def clear_edge_index(ts: tskit.TreeSequence):
+    """Drop the cached EdgeIndex for a tree sequence, if any."""
+    _cache.evict(ts)
+
+
+def merge_grouped_intervals(
+    groups: np.ndarray,
+    left: np.ndarray,
+    right: np.ndarray,
+    sequence_length: float
+) -> Tuple[np.ndarray, np.ndarray, np.ndarray]:
+    """Union of each group's intervals, as (group, left, right) arrays sorted by group then left."""
+    if len(groups) == 0:
+        return groups, left, right
+    order = np.lexsort((left, groups))
+    groups, left, right = groups[order], left[order], right[order]
+    new_group = np.ones(len(groups), dtype=bool)
+    new_group[1:] = groups[1:] != groups[:-1]
+    # Offsetting each group past the previous ones makes a single running max work per group
+    base = (np.cumsum(new_group) - 1) * (sequence_length + 1.0)
+    reach = np.maximum.accumulate(right + base)
+    starts = new_group.copy()
+    starts[1:] |= left[1:] + base[1:] > reach[:-1]
+    first = np.flatnonzero(starts)
+    # Ends are taken from the original columns, so they are exact
+    return groups[first], left[first], np.maximum.reduceat(right, first)
+
+
+def node_coverage(ts: tskit.TreeSequence, nodes: Sequence[int]) -> Tuple[np.ndarray, np.ndarray, np.ndarray]:
+    """Merged intervals over which each of `nodes` is in any edge, as (node, left, right) arrays."""
+    index = get_edge_index(ts)
+    nodes = np.asarray(nodes, dtype=np.int64)
+    as_parent, parent_owner = _gather(index.by_parent, index.parent_offsets, nodes)
+    as_child, child_owner = _gather(index.by_child, index.child_offsets, nodes)
+    edges = np.concatenate([as_parent, as_child])
+    owner = nodes[np.concatenate([parent_owner, child_owner])]
+    return merge_grouped_intervals(owner, index.left[edges], index.right[edges], ts.sequence_length)
+
+
+def node_gaps(
+    ts: tskit.TreeSequence,
+    nodes: Sequence[int]
+) -> Tuple[np.ndarray, np.ndarray, np.ndarray]:
+    """Intervals over which each of `nodes` is in no edge (isolated), as (node, left, right) arrays."""
+    nodes = np.unique(np.asarray(nodes, dtype=np.int64))
+    node, left, right = node_coverage(ts, nodes)
+    first = np.ones(len(node), dtype=bool)
+    first[1:] = node[1:] != node[:-1]
+    last = np.ones(len(node), dtype=bool)
+    last[:-1] = first[1:]
+    leading = first & (left > 0)
+    trailing = last & (right < ts.sequence_length)
+    interior = np.flatnonzero(~first)
+    # Nodes in no edge at all are missing everywhere
+    absent = np.setdiff1d(nodes, node)
+    gap_node = np.concatenate([node[leading], node[interior], node[trailing], absent])
+    gap_left = np.concatenate([np.zeros(leading.sum()), right[interior - 1], right[trailing], np.zeros(len(absent))])
+    gap_right = np.concatenate([left[leading], left[interior], np.full(trailing.sum(), ts.sequence_length), np.full(len(absent), ts.sequence_length)])
+    order = np.lexsort((gap_left, gap_node))
+    return gap_node[order], gap_left[order], gap_right[order]
+
+
+def neighborhood(
+    ts: tskit.TreeSequence,
+    seeds: Sequence[int],
+    hops: int,
+    allowed: Optional[np.ndarray] = None,
+    direction: str = "both"
+) -> np.ndarray:
+    """Nodes within `hops` edges of any seed, expanding a whole frontier per hop.
+
+    Args:
+        allowed: Boolean mask over nodes; paths only pass through allowed nodes
+        direction: Follow edges to children, to parents, or both
+    """
+    if direction not in EDGE_DIRECTIONS:
+        raise ValueError(f"direction must be one of {EDGE_DIRECTIONS}")
+    index = get_edge_index(ts)
+    allowed = np.ones(index.num_nodes, dtype=bool) if allowed is None else allowed
+    frontier = np.unique(np.asarray(seeds, dtype=np.int64))
+    frontier = frontier[allowed[frontier]]
+    reached = np.zeros(index.num_nodes, dtype=bool)
+    reached[frontier] = True
+    for _ in range(hops):
+        if len(frontier) == 0:
+            break
+        found = []
+        if direction in ("children", "both"):
+            found.append(index.child[_gather(index.by_parent, index.parent_offsets, frontier)[0]])
+        if direction in ("parents", "both"):
+            found.append(index.parent[_gather(index.by_child, index.child_offsets, frontier)[0]])
+        candidates = np.unique(np.concatenate(found))
+        frontier = candidates[allowed[candidates] & ~reached[candidates]]
+        reached[frontier] = True
+    return np.flatnonzero(reached)
+
+
+def adjacency_pairs(ts: tskit.TreeSequence) -> np.ndarray:
+    """Distinct (parent, child) node pairs joined by at least one edge, as an (n, 2) array."""
+    index = get_edge_index(ts)
+    keys = np.unique(index.parent * index.num_nodes + index.child)
+    return np.column_stack([keys // index.num_nodes, keys % index.num_nodes])
+
+
+def edges_by_node(ts: tskit.TreeSequence, nodes: Sequence[int], direction: str = "children") -> Dict[int, List[tskit.Edge]]:
+    """tskit.Edge objects for just `nodes` (as parents, or as children), in order of left."""
+    index = get_edge_index(ts)
+    nodes = np.asarray(nodes, dtype=np.int64)
+    if direction == "children":
+        edge_ids, owner = _gather(index.by_parent, index.parent_offsets, nodes)
+    elif direction == "parents":
+        edge_ids, owner = _gather(index.by_child, index.child_offsets, nodes)
+    else:
+        raise ValueError("direction must be children or parents")
+    grouped: Dict[int, List[tskit.Edge]] = {int(u): [] for u in nodes}
+    for edge_id, i in zip(edge_ids, owner):
+        grouped[int(nodes[i])].append(ts.edge(int(edge_id)))
+    return grouped
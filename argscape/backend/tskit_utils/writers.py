@@ -0,0 +1,92 @@
+"""
+Export adapters from tree sequences to other formats.
+tskit (.trees) and tszip (.tsz) keep everything; GraphML keeps the ARG as a
+graph for network tools, CSV keeps the edge table with node times for
+spreadsheets and data frames, and Newick writes each local tree on its own
+line for phylogenetics tools. The last three drop sites and mutations.
+"""
+
+import csv
+import os
+from typing import Optional, TextIO
+from xml.sax.saxutils import quoteattr
+
+import tskit
+import tszip
+
+WRITE_FORMATS = ("trees", "tsz", "graphml", "csv", "newick")
+FORMAT_EXTENSIONS = {"trees": ".trees", "tsz": ".tsz", "graphml": ".graphml", "csv": ".csv", "newick": ".nwk"}
+EXTENSION_FORMATS = {".trees": "trees", ".tsz": "tsz", ".graphml": "graphml", ".csv": "csv", ".nwk": "newick", ".newick": "newick", ".tre": "newick"}
+
+
+def format_from_path(path: str) -> Optional[str]:
+    """The output format implied by a file name, if any."""
+    return EXTENSION_FORMATS.get(os.path.splitext(path)[1].lower())
+
+
+def write_graphml(ts: tskit.TreeSequence, out: TextIO):
+    """Nodes with their time and population; one directed edge (parent -> child) per edge row."""
+    out.write('<?xml version="1.0" encoding="UTF-8"?>\n')
+    out.write('<graphml xmlns="http://graphml.graphdrawing.org/xmlns">\n')
+    for key, target, name, kind in (
+        ("d0", "node", "time", "double"),
+        ("d1", "node", "population", "int"),
+        ("d2", "node", "individual", "int"),
+        ("d3", "node", "is_sample", "boolean"),
+        ("d4", "edge", "left", "double"),
+        ("d5", "edge", "right", "double"),
+    ):
+        out.write(f'  <key id="{key}" for="{target}" attr.name="{name}" attr.type="{kind}"/>\n')
+    out.write(f'  <graph id={quoteattr(f"sequence_length={ts.sequence_length:g}")} edgedefault="directed">\n')
+    nodes = ts.tables.nodes
+    for u in range(ts.num_nodes):
+        out.write(
+            f'    <node id="n{u}"><data key="d0">{nodes.time[u]!r}</data>'
+            f'<data key="d1">{int(nodes.population[u])}</data>'
+            f'<data key="d2">{int(nodes.individual[u])}</data>'
+            f'<data key="d3">{"true" if nodes.flags[u] & tskit.NODE_IS_SAMPLE else "false"}</data></node>\n'
+        )
+    for edge in ts.edges():
+        out.write(
+            f'    <edge id="e{edge.id}" source="n{edge.parent}" target="n{edge.child}">'
+            f'<data key="d4">{edge.left!r}</data><data key="d5">{edge.right!r}</data></edge>\n'
+        )
+    out.write("  </graph>\n</graphml>\n")
+
+
+def write_edges_csv(ts: tskit.TreeSequence, out: TextIO):
+    """The edge table, with each end's node time and population."""
+    writer = csv.writer(out, lineterminator="\n")
+    writer.writerow(["edge", "left", "right", "parent", "child", "parent_time", "child_time", "parent_population", "child_population"])
+    nodes = ts.tables.nodes
+    for edge in ts.edges():
+        writer.writerow([
+            edge.id, edge.left, edge.right, edge.parent, edge.child,
+            nodes.time[edge.parent], nodes.time[edge.child],
+            int(nodes.population[edge.parent]), int(nodes.population[edge.child]),
+        ])
+
+
+def write_newick(ts: tskit.TreeSequence, out: TextIO):
+    """One line per local tree: left, right and the tree; forests list each root's subtree."""
+    for tree in ts.trees():
+        if tree.num_edges == 0:
+            continue
+        newick = " ".join(tree.as_newick(root=root) for root in tree.roots)
+        out.write(f"{tree.interval.left!r}\t{tree.interval.right!r}\t{newick}\n")
+
+
+def write_tree_sequence(ts: tskit.TreeSequence, path: str, output_format: Optional[str] = None) -> str:
+    """Write `ts` to `path` in `output_format` (default: from the extension); returns the format used."""
+    output_format = output_format or format_from_path(path)
+    if output_format not in WRITE_FORMATS:
+        raise ValueError(f"Can't tell the output format of {path}; choose one of {WRITE_FORMATS}")
+    if output_format == "trees":
+        ts.dump(path)
+    elif output_format == "tsz":
+        tszip.compress(ts, path)
+    else:
+        writer = {"graphml": write_graphml, "csv": write_edges_csv, "newick": write_newick}[output_format]
+        with open(path, "w", newline="") as out:
+            writer(ts, out)
+    return output_format
@@ -0,0 +1,102 @@
+"""
+Permalink-style view state encoding.
+A view (which file, genome window, filters, coloring, camera, selection) is
+packed into a short URL-safe string that can be pasted into a lab notebook
+and opened later to restore the view exactly. The file is identified by its
+content fingerprint rather than its name, so a link only applies to the
+tree sequence it was made from, even after renaming or re-uploading.
+Strings are versioned ("v1.") so older links keep decoding.
+"""
+
+import base64
+import json
+import zlib
+from typing import Any, Dict, List, Optional
+
+VIEW_LINK_VERSION = 1
+VIEW_LINK_PREFIX = f"v{VIEW_LINK_VERSION}."
+# Enough of the SHA-256 fingerprint to tell a lab's files apart while keeping links short
+FINGERPRINT_CHARS = 16
+MAX_LINK_LENGTH = 8192
+
+# Field name -> key in the encoded form
+FIELD_KEYS = {
+    "file": "f",
+    "filename": "n",
+    "window": "w",
+    "tree_range": "t",
+    "filter_mode": "m",
+    "filters": "x",
+    "coloring": "c",
+    "camera": "v",
+    "view_mode": "o",
+    "selected_node": "s",
+}
+FIELDS_BY_KEY = {key: field for field, key in FIELD_KEYS.items()}
+
+
+def _range(value: Any, field: str) -> Optional[List[float]]:
+    if value is None:
+        return None
+    try:
+        start, end = (float(v) for v in value)
+    except (TypeError, ValueError):
+        raise ValueError(f"{field} must be [start, end]")
+    if not 0 <= start <= end:
+        raise ValueError(f"{field} must satisfy 0 <= start <= end")
+    return [start, end]
+
+
+def normalize_view_state(state: Dict[str, Any]) -> Dict[str, Any]:
+    """Check a view state and drop empty fields."""
+    unknown = set(state) - set(FIELD_KEYS)
+    if unknown:
+        raise ValueError(f"Unknown view state fields: {sorted(unknown)}. Expected some of {list(FIELD_KEYS)}")
+    normalized = {field: value for field, value in state.items() if value not in (None, {}, [])}
+    if "file" in normalized:
+        if not isinstance(normalized["file"], str):
+            raise ValueError("file must be a tree sequence fingerprint")
+        normalized["file"] = normalized["file"][:FINGERPRINT_CHARS]
+    for field in ("window", "tree_range"):
+        if field in normalized:
+            normalized[field] = _range(normalized[field], field)
+    for field in ("filters", "coloring", "camera"):
+        if field in normalized and not isinstance(normalized[field], dict):
+            raise ValueError(f"{field} must be an object")
+    node = normalized.get("selected_node")
+    if node is not None and (isinstance(node, bool) or not isinstance(node, int) or node < 0):
+        raise ValueError("selected_node must be a non-negative node ID")
+    return normalized
+
+
+def encode_view_state(state: Dict[str, Any]) -> str:
+    """The view state as a compact, URL-safe string."""
+    normalized = normalize_view_state(state)
+    packed = json.dumps({FIELD_KEYS[field]: value for field, value in normalized.items()}, separators=(",", ":"))
+    encoded = base64.urlsafe_b64encode(zlib.compress(packed.encode("utf-8"), 9)).decode("ascii").rstrip("=")
+    return VIEW_LINK_PREFIX + encoded
+
+
+def decode_view_state(code: str) -> Dict[str, Any]:
+    """Inverse of encode_view_state; raises ValueError for malformed or unsupported strings."""
+    code = code.strip()
+    if len(code) > MAX_LINK_LENGTH:
+        raise ValueError("View link is too long")
+    version, _, payload = code.partition(".")
+    if not version.startswith("v") or not payload:
+        raise ValueError("Not an ARGscape view link")
+    if version != VIEW_LINK_PREFIX[:-1]:
+        raise ValueError(f"View links of version {version[1:]} are not supported by this version of ARGscape")
+    try:
+        raw = zlib.decompress(base64.urlsafe_b64decode(payload + "=" * (-len(payload) % 4)))
+        packed = json.loads(raw.decode("utf-8"))
+    except (ValueError, zlib.error) as e:
+        raise ValueError(f"View link is corrupted: {e}")
+    if not isinstance(packed, dict):
+        raise ValueError("View link is corrupted")
+    return normalize_view_state({FIELDS_BY_KEY.get(key, key): value for key, value in packed.items()})
+
+
+def fingerprint_matches(fingerprint: str, state: Dict[str, Any]) -> bool:
+    """Whether a full fingerprint is the file a decoded state refers to."""
+    return "file" in state and fingerprint.startswith(state["file"])
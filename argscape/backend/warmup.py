@@ -0,0 +1,117 @@
+"""
+Backend warm-up after launch.
+Importing the backend loads the heavy libraries, but the first simulation,
+graph conversion and layout still pay for lazy imports and first-call setup.
+A background thread runs each of those once on a tiny tree sequence while the
+frontend shows the readiness state: starting -> warming -> ready.
+"""
+
+import logging
+import threading
+import time
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+logger = logging.getLogger(__name__)
+
+STATE_STARTING = "starting"
+STATE_WARMING = "warming"
+STATE_READY = "ready"
+
+WARMUP_SEED = 1
+
+
+def _simulate():
+    import msprime
+    ts = msprime.sim_ancestry(
+        samples=4, sequence_length=10_000, recombination_rate=1e-8,
+        population_size=1_000, random_seed=WARMUP_SEED
+    )
+    return msprime.sim_mutations(ts, rate=1e-8, random_seed=WARMUP_SEED)
+
+
+def _graph_data(ts):
+    from argscape.backend.graph_utils import convert_to_graph_data
+    convert_to_graph_data(ts)
+
+
+def _node_metrics(ts):
+    from argscape.backend.tskit_utils import compute_node_metrics
+    compute_node_metrics(ts, include_betweenness=False)
+
+
+def _serialization(ts):
+    import io
+    import tskit
+    buffer = io.BytesIO()
+    ts.dump(buffer)
+    buffer.seek(0)
+    tskit.load(buffer)
+
+
+# Steps after the simulation, each given the simulated tree sequence
+WARMUP_STEPS: List[Tuple[str, Callable[[Any], None]]] = [
+    ("graph_data", _graph_data),
+    ("node_metrics", _node_metrics),
+    ("serialization", _serialization),
+]
+
+
+class Readiness:
+    """Readiness state machine, advanced by the warm-up thread."""
+
+    def __init__(self):
+        self.state = STATE_STARTING
+        self.started_at = time.time()
+        self.ready_at: Optional[float] = None
+        self.current_step: Optional[str] = None
+        self.steps: Dict[str, Dict[str, Any]] = {}
+        self._thread: Optional[threading.Thread] = None
+
+    def start(self, enabled: bool = True):
+        """Begin warming up in the background, or go straight to ready."""
+        if self._thread is not None:
+            return
+        if not enabled:
+            self._mark_ready()
+            return
+        self.state = STATE_WARMING
+        self._thread = threading.Thread(target=self._run, name="argscape-warmup", daemon=True)
+        self._thread.start()
+
+    def _step(self, name: str, func: Callable[[], Any]) -> Any:
+        self.current_step = name
+        start = time.time()
+        try:
+            result = func()
+            self.steps[name] = {"ok": True, "seconds": round(time.time() - start, 3)}
+            return result
+        except Exception as e:
+            # A failed step only means that action stays slow the first time
+            self.steps[name] = {"ok": False, "seconds": round(time.time() - start, 3), "error": str(e)}
+            logger.warning(f"Warm-up step {name} failed: {e}")
+            return None
+
+    def _run(self):
+        ts = self._step("simulation", _simulate)
+        if ts is not None:
+            for name, func in WARMUP_STEPS:
+                self._step(name, lambda: func(ts))
+        self._mark_ready()
+
+    def _mark_ready(self):
+        self.current_step = None
+        self.state = STATE_READY
+        self.ready_at = time.time()
+        logger.info(f"Backend ready after {self.ready_at - self.started_at:.1f}s")
+
+    def status(self) -> Dict[str, Any]:
+        return {
+            "state": self.state,
+            "ready": self.state == STATE_READY,
+            "current_step": self.current_step,
+            "steps": dict(self.steps),
+            "elapsed_seconds": round((self.ready_at or time.time()) - self.started_at, 3),
+        }
+
+
+readiness = Readiness()
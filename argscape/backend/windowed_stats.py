@@ -0,0 +1,270 @@
+"""
+Windowed population-genetic statistics as tidy tables.
+One code path serves the statistics endpoint, figure panels and
+`argscape stats`, so a number is the same wherever it is read. Every table
+has one row per window and sample set (and per frequency for the SFS, per
+focal sample for GNN) with the window bounds as the first two columns, and
+serializes to CSV, JSON or Parquet. Many windows can be split across worker
+processes (see argscape.backend.worker_pool).
+"""
+
+import csv
+import io
+import json
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+import numpy as np
+import tskit
+
+from argscape.backend import missing_data as missing
+from argscape.backend.gpu_stats import ACCELERATORS, GPU_STATISTICS, windowed_branch_values
+from argscape.backend.palettes import population_names
+from argscape.backend.worker_pool import map_windows
+
+STATISTICS = ("diversity", "segregating_sites", "tajimas_d", "sfs", "gnn", "tmrca")
+STATISTIC_MODES = ("site", "branch")
+TABLE_FORMATS = ("csv", "json", "parquet")
+SAMPLE_SET_OPTIONS = ("all", "population")
+MAX_WINDOWS = 100000
+# GNN is computed per window on a trimmed copy, so it allows fewer windows
+MAX_GNN_WINDOWS = 1000
+
+
+def make_windows(
+    ts: tskit.TreeSequence,
+    num_windows: Optional[int] = None,
+    window_size: Optional[float] = None,
+    trees: bool = False
+) -> np.ndarray:
+    """Window breakpoints: equal windows by count or size, one window per tree, or the whole genome."""
+    if trees:
+        return np.asarray(ts.breakpoints(as_array=True), dtype=float)
+    if window_size is not None:
+        if window_size <= 0:
+            raise ValueError("window_size must be positive")
+        breakpoints = np.arange(0, ts.sequence_length, window_size, dtype=float)
+        return np.append(breakpoints, ts.sequence_length)
+    if num_windows is not None:
+        if not 1 <= num_windows <= MAX_WINDOWS:
+            raise ValueError(f"num_windows must be between 1 and {MAX_WINDOWS}")
+        return np.linspace(0, ts.sequence_length, num_windows + 1)
+    return np.array([0.0, ts.sequence_length])
+
+
+def sample_sets_for(ts: tskit.TreeSequence, grouping: str = "all") -> Dict[str, List[int]]:
+    """Named sample sets: all samples, or the samples of each population that has any."""
+    if grouping not in SAMPLE_SET_OPTIONS:
+        raise ValueError(f"sample sets must be one of {SAMPLE_SET_OPTIONS}")
+    if grouping == "all":
+        return {"all": [int(u) for u in ts.samples()]}
+    names = population_names(ts)
+    sets = {}
+    for population, name in enumerate(names):
+        samples = [int(u) for u in ts.samples(population=population)]
+        if samples:
+            sets[name] = samples
+    if not sets:
+        raise ValueError("No samples are assigned to populations")
+    return sets
+
+
+def _validate(statistic: str, mode: str):
+    if statistic not in STATISTICS:
+        raise ValueError(f"statistic must be one of {STATISTICS}")
+    if mode not in STATISTIC_MODES:
+        raise ValueError(f"mode must be one of {STATISTIC_MODES}")
+
+
+def _check_gnn(windows: np.ndarray, sets: Dict[str, List[int]]):
+    if len(sets) < 2:
+        raise ValueError("GNN compares sample sets; use population sample sets with at least two populations")
+    if len(windows) - 1 > MAX_GNN_WINDOWS:
+        raise ValueError(f"GNN supports at most {MAX_GNN_WINDOWS} windows")
+
+
+def _gnn_rows(ts: tskit.TreeSequence, windows: np.ndarray, sets: Dict[str, List[int]]) -> Dict[str, List[Any]]:
+    _check_gnn(windows, sets)
+    names = list(sets)
+    focal = sorted(u for samples in sets.values() for u in samples)
+    set_of = {u: name for name, samples in sets.items() for u in samples}
+    columns: Dict[str, List[Any]] = {"window_left": [], "window_right": [], "sample": [], "sample_set": [], "reference_set": [], "value": []}
+    for left, right in zip(windows[:-1], windows[1:]):
+        whole_genome = left == 0 and right == ts.sequence_length
+        window_ts = ts if whole_genome else ts.keep_intervals([[left, right]], simplify=False)
+        values = window_ts.genealogical_nearest_neighbours(focal, [sets[name] for name in names])
+        for i, u in enumerate(focal):
+            for j, name in enumerate(names):
+                columns["window_left"].append(float(left))
+                columns["window_right"].append(float(right))
+                columns["sample"].append(u)
+                columns["sample_set"].append(set_of[u])
+                columns["reference_set"].append(name)
+                columns["value"].append(float(values[i, j]))
+    return columns
+
+
+def _statistic_columns(
+    ts: tskit.TreeSequence,
+    statistic: str,
+    mode: str,
+    windows: np.ndarray,
+    sets: Dict[str, List[int]],
+    polarised: bool,
+    accelerator: str
+) -> Tuple[Dict[str, List[Any]], Optional[Dict[str, Any]]]:
+    """The rows of a statistic before missing-data adjustment, and the GPU report if the GPU path ran."""
+    names = list(sets)
+    lefts, rights = windows[:-1], windows[1:]
+    accelerated = None
+    if statistic == "gnn":
+        return _gnn_rows(ts, windows, sets), None
+    if statistic == "sfs":
+        columns = {"window_left": [], "window_right": [], "sample_set": [], "frequency": [], "value": []}
+        for name in names:
+            spectrum = np.asarray(ts.allele_frequency_spectrum(
+                sample_sets=[sets[name]], windows=windows, mode=mode, polarised=polarised, span_normalise=True
+            ))
+            for w, (left, right) in enumerate(zip(lefts, rights)):
+                for frequency, value in enumerate(spectrum[w]):
+                    columns["window_left"].append(float(left))
+                    columns["window_right"].append(float(right))
+                    columns["sample_set"].append(name)
+                    columns["frequency"].append(frequency)
+                    columns["value"].append(float(value))
+        return columns, None
+
+    if accelerator != "cpu" and (statistic == "tmrca" or (statistic in GPU_STATISTICS and mode == "branch")):
+        values, accelerated = windowed_branch_values(ts, statistic, windows, [sets[n] for n in names], accelerator)
+    elif statistic == "tmrca":
+        values = np.asarray(ts.diversity([sets[n] for n in names], windows=windows, mode="branch"), dtype=float) / 2
+    else:
+        values = np.asarray(getattr(ts, statistic)([sets[n] for n in names], windows=windows, mode=mode), dtype=float)
+    values = values.reshape(len(lefts), len(names))
+    columns = {"window_left": [], "window_right": [], "sample_set": [], "value": []}
+    for w, (left, right) in enumerate(zip(lefts, rights)):
+        for s, name in enumerate(names):
+            columns["window_left"].append(float(left))
+            columns["window_right"].append(float(right))
+            columns["sample_set"].append(name)
+            columns["value"].append(None if np.isnan(values[w, s]) else float(values[w, s]))
+    return columns, accelerated
+
+
+def _chunk_columns(
+    ts: tskit.TreeSequence,
+    windows: np.ndarray,
+    statistic: str,
+    mode: str,
+    sets: Dict[str, List[int]],
+    polarised: bool
+) -> Dict[str, List[Any]]:
+    """Rows for a run of consecutive windows, as computed by a worker process."""
+    if statistic == "gnn":
+        return _gnn_rows(ts, windows, sets)
+    # tskit windows must cover the whole genome, so the run is cut out of the
+    # tree sequence (making the work proportional to its span) and padded with
+    # windows whose rows are dropped
+    left, right = windows[0], windows[-1]
+    trimmed = ts.keep_intervals([[left, right]], simplify=False)
+    padded = np.concatenate([[0.0] if left > 0 else [], windows, [ts.sequence_length] if right < ts.sequence_length else []])
+    columns, _ = _statistic_columns(trimmed, statistic, mode, padded, sets, polarised, "cpu")
+    keep = [i for i, (l, r) in enumerate(zip(columns["window_left"], columns["window_right"])) if l >= left and r <= right]
+    return {name: [values[i] for i in keep] for name, values in columns.items()}
+
+
+def compute_statistic(
+    ts: tskit.TreeSequence,
+    statistic: str,
+    mode: str = "site",
+    windows: Optional[Sequence[float]] = None,
+    sample_sets: Optional[Dict[str, List[int]]] = None,
+    polarised: bool = True,
+    missing_data: str = "ignore",
+    accelerator: str = "cpu",
+    workers: int = 1
+) -> Dict[str, Any]:
+    """A windowed statistic as a column table.
+
+    Args:
+        statistic: One of STATISTICS; "tmrca" is the mean pairwise TMRCA (half
+            the branch-mode diversity) and ignores `mode`
+        windows: Breakpoints from make_windows (default: one genome-wide window)
+        sample_sets: Name -> sample IDs (default: all samples)
+        polarised: SFS only; fold the spectrum when False
+        missing_data: One of MISSING_DATA_OPTIONS; "mask" nulls windows where a
+            sample in the sets is missing, "drop" cuts those regions out and
+            renormalises by the span left
+        accelerator: One of ACCELERATORS; "auto" or "gpu" compute branch-mode
+            diversity and tmrca with argscape.backend.gpu_stats where possible
+        workers: Split the windows across up to this many processes, each
+            holding its own copy of the tree sequence (CPU path only)
+
+    Returns:
+        {"statistic", "mode", "num_windows", "columns": {name: values}, "missing_data", "accelerator", "workers"}
+    """
+    _validate(statistic, mode)
+    if accelerator not in ACCELERATORS:
+        raise ValueError(f"Unknown accelerator '{accelerator}'. Expected one of {ACCELERATORS}")
+    windows = np.asarray(windows if windows is not None else make_windows(ts), dtype=float)
+    sets = sample_sets or sample_sets_for(ts)
+    prepared = missing.prepare(ts, missing_data, sorted({u for samples in sets.values() for u in samples}))
+    ts = prepared["ts"]
+    lefts = windows[:-1]
+    gpu_capable = statistic == "tmrca" or (statistic in GPU_STATISTICS and mode == "branch")
+    accelerated = None
+    if accelerator != "cpu" and not gpu_capable:
+        accelerated = {"requested": accelerator, "used": "cpu", "reason": f"The GPU path covers branch-mode {', '.join(GPU_STATISTICS)}"}
+
+    if workers > 1 and len(lefts) > 1 and not (accelerator != "cpu" and gpu_capable):
+        if statistic == "gnn":
+            _check_gnn(windows, sets)
+        parts = map_windows(ts, _chunk_columns, windows, workers, statistic, mode, sets, polarised)
+        workers = len(parts)
+        columns = {name: [value for part in parts for value in part[name]] for name in parts[0]}
+        if statistic == "sfs":
+            # Each part lists set by set; restore the single-process order (set, then window, then frequency)
+            order = {name: i for i, name in enumerate(sets)}
+            rows = sorted(range(len(columns["sample_set"])), key=lambda i: order[columns["sample_set"][i]])
+            columns = {name: [values[i] for i in rows] for name, values in columns.items()}
+    else:
+        workers = 1
+        columns, used = _statistic_columns(ts, statistic, mode, windows, sets, polarised, accelerator)
+        accelerated = used or accelerated
+
+    missing_summary = missing.adjust_columns(columns, statistic, prepared)
+    return {
+        "statistic": statistic,
+        "mode": "branch" if statistic == "tmrca" else mode,
+        "num_windows": len(lefts),
+        "sample_sets": {name: len(samples) for name, samples in sets.items()},
+        "columns": columns,
+        "missing_data": missing_summary,
+        "accelerator": accelerated,
+        "workers": workers,
+    }
+
+
+def serialize_table(result: Dict[str, Any], table_format: str = "csv") -> bytes:
+    """A compute_statistic result as CSV, JSON or Parquet bytes."""
+    columns = result["columns"]
+    if table_format == "csv":
+        buffer = io.StringIO()
+        writer = csv.writer(buffer, lineterminator="\n")
+        writer.writerow(list(columns))
+        writer.writerows(zip(*columns.values()))
+        return buffer.getvalue().encode("utf-8")
+    if table_format == "json":
+        return json.dumps(result).encode("utf-8")
+    if table_format == "parquet":
+        try:
+            import pyarrow as pa
+            import pyarrow.parquet as pq
+        except ImportError:
+            raise ValueError("Parquet export requires the pyarrow package")
+        table = pa.table(columns)
+        header = {k: v for k, v in result.items() if k != "columns"}
+        table = table.replace_schema_metadata({"argscape_statistic": json.dumps(header)})
+        buffer = io.BytesIO()
+        pq.write_table(table, buffer)
+        return buffer.getvalue()
+    raise ValueError(f"Unknown table format '{table_format}'. Expected one of {TABLE_FORMATS}")
@@ -0,0 +1,143 @@
+"""
+Worker processes for computations over many genome windows.
+The backend answers requests from a single process, so a statistic over
+thousands of windows runs on one core. map_windows() splits the windows into
+runs of consecutive windows and computes each run in its own process. The
+tree sequence is handed over once, through share_tree_sequence(), instead of
+being pickled into each task; each worker then holds its own copy in memory.
+Workers are spawned rather than forked: the server runs threads, and a
+forked child would inherit locks those threads hold.
+"""
+
+import io
+import logging
+import multiprocessing
+import os
+import shutil
+import tempfile
+from concurrent.futures import ProcessPoolExecutor
+from contextlib import contextmanager
+from multiprocessing import shared_memory
+from typing import Any, Callable, Dict, Iterator, List, Sequence
+
+import numpy as np
+import tskit
+
+from argscape.backend.resources import get_cpu_count
+
+logger = logging.getLogger(__name__)
+
+# Linux backs shared memory with this tmpfs; writing past its free space kills the process
+SHARED_MEMORY_DIR = "/dev/shm"
+
+# The tree sequence each worker process loaded when it started
+_worker_state: Dict[str, Any] = {}
+
+
+def split_windows(windows: Sequence[float], num_chunks: int) -> List[np.ndarray]:
+    """Window breakpoints cut into at most `num_chunks` runs of consecutive windows.
+
+    Neighbouring runs share the breakpoint between them, so each run is
+    itself a valid list of breakpoints.
+    """
+    windows = np.asarray(windows, dtype=float)
+    num_windows = len(windows) - 1
+    num_chunks = max(1, min(num_chunks, num_windows))
+    bounds = np.linspace(0, num_windows, num_chunks + 1).round().astype(int)
+    return [windows[a:b + 1] for a, b in zip(bounds[:-1], bounds[1:])]
+
+
+@contextmanager
+def share_tree_sequence(ts: tskit.TreeSequence) -> Iterator[Dict[str, Any]]:
+    """A handle other processes on this machine can pass to load_shared_tree_sequence().
+
+    The encoded tables go into a named shared-memory block, so they never
+    touch the filesystem. If shared memory is unavailable or too small (a
+    container's /dev/shm is often 64 MB), a temporary file is used instead.
+    The block or file is removed when the context exits, so every process
+    must have loaded it by then.
+    """
+    buffer = io.BytesIO()
+    ts.dump(buffer)
+    data = buffer.getbuffer()
+    block = None
+    if os.path.isdir(SHARED_MEMORY_DIR) and shutil.disk_usage(SHARED_MEMORY_DIR).free < len(data):
+        logger.info(f"{SHARED_MEMORY_DIR} is too small; handing the tree sequence over in a temporary file")
+    else:
+        try:
+            block = shared_memory.SharedMemory(create=True, size=len(data))
+        except (OSError, ValueError) as e:
+            logger.info(f"Shared memory unavailable ({e}); handing the tree sequence over in a temporary file")
+
+    if block is None:
+        with tempfile.TemporaryDirectory(prefix="argscape-workers-") as directory:
+            path = os.path.join(directory, "shared.trees")
+            with open(path, "wb") as f:
+                f.write(data)
+            del data
+            yield {"kind": "file", "path": path}
+        return
+
+    try:
+        block.buf[:len(data)] = data
+        yield {"kind": "shared_memory", "name": block.name, "size": len(data)}
+    finally:
+        del data
+        block.close()
+        block.unlink()
+
+
+def _attach(name: str) -> shared_memory.SharedMemory:
+    """Open an existing block without making this process responsible for removing it."""
+    try:
+        return shared_memory.SharedMemory(name=name, track=False)  # Python 3.13+
+    except TypeError:
+        # Older versions register the attachment with the resource tracker, but
+        # spawned workers share their parent's tracker, so the name is already
+        # registered there and is released when the parent unlinks the block
+        return shared_memory.SharedMemory(name=name)
+
+
+def load_shared_tree_sequence(handle: Dict[str, Any]) -> tskit.TreeSequence:
+    """The tree sequence behind a share_tree_sequence() handle."""
+    if handle["kind"] == "file":
+        return tskit.load(handle["path"])
+    block = _attach(handle["name"])
+    try:
+        return tskit.load(io.BytesIO(bytes(block.buf[:handle["size"]])))
+    finally:
+        block.close()
+
+
+def _load_worker_ts(handle: Dict[str, Any]):
+    _worker_state["ts"] = load_shared_tree_sequence(handle)
+
+
+def _run_chunk(function: Callable[..., Any], chunk: List[float], args: tuple) -> Any:
+    return function(_worker_state["ts"], np.asarray(chunk, dtype=float), *args)
+
+
+def map_windows(
+    ts: tskit.TreeSequence,
+    function: Callable[..., Any],
+    windows: Sequence[float],
+    workers: int,
+    *args: Any
+) -> List[Any]:
+    """`function(ts, chunk, *args)` for each run of windows from split_windows, in genome order.
+
+    `function` is sent to the workers by name, so it must be a module-level
+    function. With one worker (or a single window) it runs in this process.
+    The number of processes is capped at the CPUs available.
+    """
+    chunks = split_windows(windows, min(workers, get_cpu_count()))
+    if len(chunks) == 1:
+        return [function(ts, chunks[0], *args)]
+
+    with share_tree_sequence(ts) as handle:
+        context = multiprocessing.get_context("spawn")
+        with ProcessPoolExecutor(max_workers=len(chunks), mp_context=context, initializer=_load_worker_ts, initargs=(handle,)) as pool:
+            futures = [pool.submit(_run_chunk, function, chunk.tolist(), args) for chunk in chunks]
+            results = [future.result() for future in futures]
+    logger.info(f"Computed {len(windows) - 1} windows in {len(chunks)} worker processes")
+    return results
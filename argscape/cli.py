@@ -3,23 +3,95 @@ import webbrowser
 import threading
 import time
 import argparse
+import atexit
 import os
+import subprocess
+import sys
 
+# Set in the environment of a re-launched CLI so it doesn't resolve again
+REEXEC_ENV_VAR = "ARGSCAPE_INTERPRETER_RESOLVED"
 
-def open_browser(host: str, port: int):
+
+def open_browser(url: str):
     time.sleep(1)  # Give the server a moment to start
-    webbrowser.open(f"http://{host}:{port}")
+    webbrowser.open(url)
+
+
+def run_benchmark_command(profile: str, output_path: str = None, progress=None):
+    from argscape.backend.benchmark import format_benchmark_report, run_benchmark, write_benchmark_report
+
+    on_progress = (lambda dataset, percent: progress.stage(dataset, percent, f"Benchmarking {dataset}")) if progress else None
+    report = run_benchmark(profile, on_progress=on_progress)
+    if progress is None or progress.mode != "json":
+        print(format_benchmark_report(report))
+    if output_path:
+        write_benchmark_report(report, output_path)
+    if progress:
+        progress.complete(f"Report written to {output_path}" if output_path else None, output=output_path)
+
+
+def run_in_resolved_interpreter(python: str = None, runtime: str = None, check_only: bool = False):
+    """Re-launch the CLI under the configured interpreter if it isn't this one."""
+    from argscape.backend.interpreter import MissingDependencyError, check_dependencies, resolve_interpreter
+    from argscape.backend.settings import settings
+
+    try:
+        resolved = resolve_interpreter(python or settings.get("python_interpreter"), runtime or settings.get("backend_runtime"))
+    except (MissingDependencyError, ValueError) as e:
+        print(f"Error: {e}", file=sys.stderr)
+        sys.exit(1)
+
+    if check_only:
+        report = check_dependencies(resolved["path"])
+        print(f"Interpreter: {resolved['path']} ({resolved['source']}, Python {report['version']})")
+        print("Missing packages: " + (", ".join(report["missing"]) if report["missing"] else "none"))
+        sys.exit(0 if report["ok"] else 1)
+
+    if not resolved["reexec"]:
+        return
+    print(f"Starting the backend with {resolved['path']}")
+    env = {**os.environ, REEXEC_ENV_VAR: "1"}
+    argv = [str(resolved["path"]), "-m", "argscape.cli", *sys.argv[1:]]
+    if sys.platform == "win32":
+        sys.exit(subprocess.call(argv, env=env))
+    os.execve(argv[0], argv, env)
+
+
+def show_config():
+    """Print each setting's effective value and where it comes from."""
+    from argscape.backend.settings import SETTINGS_SCHEMA, settings
+
+    for layer in settings.config_layers:
+        print(f"Config file ({layer['layer']}): {layer['path']}")
+    for key in SETTINGS_SCHEMA:
+        source = settings.get_source(key)
+        origin = source.get("path") or source.get("env_var")
+        print(f"{key} = {settings.get(key)!r}  [{source['source']}{': ' + origin if origin else ''}]")
 
 
 def main():
-    parser = argparse.ArgumentParser(description="Start the Argscape web application.")
+    from argscape.commands import COMMANDS, apply_config, run_subcommand
+    from argscape.commands.progress import PROGRESS_MODES, ProgressReporter
+
+    # Headless subcommands (`argscape convert ...`); everything else starts the server
+    if len(sys.argv) > 1 and sys.argv[1] in COMMANDS:
+        from argscape.backend.portable import apply_portable_environment
+        apply_portable_environment()
+        if not os.environ.get(REEXEC_ENV_VAR):
+            run_in_resolved_interpreter()
+        sys.exit(run_subcommand(sys.argv[1:]))
+
+    parser = argparse.ArgumentParser(
+        description="Start the Argscape web application.",
+        epilog=f"Headless commands: {', '.join(COMMANDS)} (see argscape <command> --help)"
+    )
     parser.add_argument(
-        "--host", type=str, default="127.0.0.1",
-        help="Host to run the server on (default: 127.0.0.1)"
+        "--host", type=str, default=None,
+        help="Host to run the server on (default: [server] host in argscape.toml, else 127.0.0.1)"
     )
     parser.add_argument(
-        "--port", type=int, default=8000,
-        help="Port to run the server on (default: 8000)"
+        "--port", type=int, default=None,
+        help="Port to run the server on (default: [server] port in argscape.toml, else 8000)"
     )
     parser.add_argument(
         "--reload", action="store_true",
@@ -33,18 +105,193 @@ def main():
         "--no-tsdate", action="store_true",
         help="Disable tsdate temporal inference"
     )
+    parser.add_argument(
+        "--threads", type=int, default=None,
+        help="Number of worker threads for computations (default: all CPUs, fewer on low-memory machines)"
+    )
+    parser.add_argument(
+        "--low-power", action="store_true",
+        help="Cap CPU usage (single thread, lower priority) to save battery"
+    )
+    parser.add_argument(
+        "--priority", choices=["normal", "low", "idle"], default=None,
+        help="CPU scheduling priority of the backend (default: normal)"
+    )
+    parser.add_argument(
+        "--memory-limit", type=int, default=None, metavar="MB",
+        help="Cancel jobs, then stop the backend, if its memory use stays above MB megabytes"
+    )
+    parser.add_argument(
+        "--no-auth", action="store_true",
+        help="Don't require the per-launch access token (any local process can use the API)"
+    )
+    parser.add_argument(
+        "--offline", action="store_true",
+        help="Block all outbound network access from the backend (air-gapped environments)"
+    )
+    parser.add_argument(
+        "--sandbox", action="store_true",
+        help="Run the backend restricted to loopback networking and its own storage directories"
+    )
+    parser.add_argument(
+        "--scratch", action="append", default=None, metavar="DIR",
+        help="Write large derived files to DIR (repeat for more; the first with enough free space is used)"
+    )
+    parser.add_argument(
+        "--inbox", type=str, default=None, metavar="DIR",
+        help="Watch DIR and load new .trees/.tsz files dropped there (e.g. by an inference pipeline)"
+    )
+    parser.add_argument(
+        "--inbox-action", choices=["notify", "open"], default=None,
+        help="What to do when a new inbox file is loaded (default: notify)"
+    )
+    parser.add_argument(
+        "--listen", action="store_true",
+        help="Enable the local event bridge so notebooks and other tools can push files and drive the viewer"
+    )
+    parser.add_argument(
+        "--python", type=str, default=None, metavar="PATH",
+        help="Python interpreter, virtualenv or conda environment to run the backend in"
+    )
+    parser.add_argument(
+        "--runtime", choices=["auto", "bundled", "system"], default=None,
+        help="Use the bundled/current interpreter, a system one, or the configured one when it works (default: auto)"
+    )
+    parser.add_argument(
+        "--check-env", action="store_true",
+        help="Report which interpreter would run the backend and any missing packages, then exit"
+    )
+    parser.add_argument(
+        "--benchmark", nargs="?", const="quick", default=None,
+        choices=["quick", "standard", "full", "chromosome"],
+        help="Run the performance benchmark suite instead of starting the server (default profile: quick)"
+    )
+    parser.add_argument(
+        "--benchmark-output", type=str, default=None,
+        help="Write the benchmark report as JSON to this file"
+    )
+    parser.add_argument(
+        "--progress", choices=PROGRESS_MODES, default="text",
+        help="Progress and startup events on stderr: text (default), json (one event per line) or none"
+    )
+    parser.add_argument(
+        "--config", type=str, default=None, metavar="FILE",
+        help="Read settings from this argscape.toml instead of one in the working directory"
+    )
+    parser.add_argument(
+        "--portable", action="store_true",
+        help="Keep settings, caches, logs and sessions in an argscape-data folder beside the executable"
+    )
+    parser.add_argument(
+        "--show-config", action="store_true",
+        help="Print every setting's effective value and which config layer set it, then exit"
+    )
     args = parser.parse_args()
 
+    from argscape.backend.portable import PORTABLE_ENV_VAR, apply_portable_environment
+    if args.portable and not os.environ.get(PORTABLE_ENV_VAR):
+        os.environ[PORTABLE_ENV_VAR] = "1"
+    portable_dir = apply_portable_environment()
+    if portable_dir is not None:
+        print(f"Portable mode: keeping all state in {portable_dir}")
+
+    # Config files sit below CLI flags, which still override them through the environment
+    from argscape.backend.config import ConfigError, merged_server_defaults
+    try:
+        server_defaults = merged_server_defaults(apply_config(args.config))
+    except ConfigError as e:
+        print(f"Error: {e}", file=sys.stderr)
+        sys.exit(2)
+    args.host = args.host or server_defaults.get("host", "127.0.0.1")
+    args.port = args.port or server_defaults.get("port", 8000)
+    if args.show_config:
+        show_config()
+        return
+
+    if args.check_env or not os.environ.get(REEXEC_ENV_VAR):
+        run_in_resolved_interpreter(args.python, args.runtime, check_only=args.check_env)
+
+    if args.benchmark:
+        progress = ProgressReporter("benchmark", args.progress)
+        progress.start(profile=args.benchmark)
+        run_benchmark_command(args.benchmark, args.benchmark_output, progress)
+        return
+
+    if args.offline:
+        os.environ["ARGSCAPE_OFFLINE"] = "1"
+
+    if args.priority:
+        os.environ["ARGSCAPE_PRIORITY"] = args.priority
+    if args.memory_limit is not None:
+        os.environ["ARGSCAPE_MEMORY_LIMIT_MB"] = str(args.memory_limit)
+
+    if args.scratch:
+        os.environ["ARGSCAPE_SCRATCH_DIRS"] = os.pathsep.join(os.path.abspath(os.path.expanduser(d)) for d in args.scratch)
+
+    if args.inbox:
+        os.environ["ARGSCAPE_INBOX_DIR"] = os.path.abspath(os.path.expanduser(args.inbox))
+    if args.inbox_action:
+        os.environ["ARGSCAPE_INBOX_ACTION"] = args.inbox_action
+
+    if args.sandbox:
+        os.environ["ARGSCAPE_SANDBOX"] = "1"
+        if args.host not in ("127.0.0.1", "localhost", "::1"):
+            print(f"Sandbox mode only listens on loopback; ignoring --host {args.host}")
+            args.host = "127.0.0.1"
+
+    url = f"http://{args.host}:{args.port}"
+    launch_url = url
+    if not args.no_auth:
+        from argscape.backend.auth import AUTH_QUERY_PARAM, AUTH_TOKEN_ENV_VAR, generate_auth_token
+        token = os.environ.get(AUTH_TOKEN_ENV_VAR) or generate_auth_token()
+        os.environ[AUTH_TOKEN_ENV_VAR] = token
+        launch_url = f"{url}/?{AUTH_QUERY_PARAM}={token}"
+        # The tokened URL is shown only on an interactive terminal, never in captured logs
+        if sys.stdout.isatty():
+            print(f"ARGscape is available at {launch_url}")
+        else:
+            print(f"ARGscape is available at {url} (access token not shown as output is not a terminal)")
+
+    if args.listen:
+        from argscape.backend.event_bridge import remove_instance_file, write_instance_file
+        from argscape.backend.auth import AUTH_TOKEN_ENV_VAR
+        os.environ["ARGSCAPE_EVENT_BRIDGE"] = "1"
+        write_instance_file(f"http://{args.host}:{args.port}", os.environ.get(AUTH_TOKEN_ENV_VAR))
+        atexit.register(remove_instance_file)
+        print("Listening for tree sequences from argscape.notebook.show()")
+
     if not args.no_browser:
-        threading.Thread(target=open_browser, args=(args.host, args.port), daemon=True).start()
+        threading.Thread(target=open_browser, args=(launch_url,), daemon=True).start()
+
+    # Lets a supervising pipeline know where the server will answer
+    ProgressReporter("serve", args.progress).stage("starting", message=f"Starting server on {args.host}:{args.port}", url=url)
 
     # Set environment variable for tsdate
     if args.no_tsdate:
         os.environ["DISABLE_TSDATE"] = "1"
 
-    uvicorn.run(
-        "argscape.backend.main:app",
-        host=args.host,
-        port=args.port,
-        reload=args.reload
-    )
+    # Thread pools must be sized before numpy and friends are imported
+    if args.threads is not None or args.low_power:
+        from argscape.backend.resources import get_worker_count, set_thread_environment
+        if args.threads is not None:
+            os.environ["ARGSCAPE_NUM_THREADS"] = str(args.threads)
+        if args.low_power:
+            os.environ["ARGSCAPE_LOW_POWER"] = "1"
+        set_thread_environment(get_worker_count(args.threads, args.low_power))
+
+    if args.reload:
+        # The reloader runs the server in a child process it manages itself
+        uvicorn.run(
+            "argscape.backend.main:app",
+            host=args.host,
+            port=args.port,
+            reload=True
+        )
+        return
+
+    from argscape.backend.lifecycle import backend_manager
+    backend_manager.run("argscape.backend.main:app", host=args.host, port=args.port, url=url)
+
+
+if __name__ == "__main__":
+    main()
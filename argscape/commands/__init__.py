@@ -0,0 +1,98 @@
+"""
+Headless CLI subcommands (`argscape convert ...`), for pipelines and machines
+without a display. Each command module defines `add_parser(subparsers)` and
+`run(args, progress) -> int`, and calls the same backend code as the web
+application. `progress` is a ProgressReporter for the `--progress` option.
+"""
+
+import argparse
+import os
+import sys
+from typing import Any, Dict, List, Optional, Tuple
+
+from argscape.commands import convert, generate_fixture, populations, render, stats, validate
+from argscape.commands.progress import PROGRESS_MODES, ProgressReporter
+
+COMMANDS = {
+    "convert": convert,
+    "stats": stats,
+    "validate": validate,
+    "render": render,
+    "generate-fixture": generate_fixture,
+    "populations": populations,
+}
+
+
+def load_input(path: str, mut_path: Optional[str] = None) -> Tuple[Any, Dict[str, Any]]:
+    """Load a .trees/.tsz file or convert a Relate/ARGweaver one; returns the tree sequence and a report."""
+    from argscape.backend.tskit_utils import (
+        CONVERTIBLE_FORMATS,
+        TreeSequenceLoadError,
+        convert_arg_files,
+        detect_format,
+        load_tree_sequence_from_file,
+        upgrade_legacy_tree_sequence,
+    )
+
+    if not os.path.isfile(path):
+        raise FileNotFoundError(f"No such file: {path}")
+    with open(path, "rb") as f:
+        contents = f.read()
+    name = os.path.basename(path)
+    detected = detect_format(name, contents)
+    if detected in CONVERTIBLE_FORMATS:
+        files = {name: contents}
+        if mut_path:
+            with open(mut_path, "rb") as f:
+                files[os.path.basename(mut_path)] = f.read()
+        ts, _, report = convert_arg_files(files)
+        return ts, report
+    try:
+        ts, _ = load_tree_sequence_from_file(contents, name)
+    except TreeSequenceLoadError as e:
+        if not e.upgradable:
+            raise
+        # Old formats are upgraded on the fly; the changes are reported as warnings
+        ts, upgrade = upgrade_legacy_tree_sequence(contents)
+        warnings = [f"Upgraded from {upgrade['from_format']}"] + upgrade["changes"]
+        return ts, {"format": detected, "upgrade": upgrade, "warnings": warnings}
+    return ts, {"format": detected}
+
+
+def apply_config(path: Optional[str] = None) -> List[Dict[str, Any]]:
+    """Use `path` as the project config, then check every config layer; raises ConfigError."""
+    from argscape.backend.config import CONFIG_ENV_VAR, load_config_layers
+    from argscape.backend.settings import settings
+
+    if path:
+        os.environ[CONFIG_ENV_VAR] = os.path.abspath(os.path.expanduser(path))
+    layers = load_config_layers()
+    settings.reload_config()
+    return layers
+
+
+def run_subcommand(argv: List[str]) -> int:
+    parser = argparse.ArgumentParser(prog="argscape", description="Headless ARGscape commands.")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    for module in COMMANDS.values():
+        subparser = module.add_parser(subparsers)
+        subparser.add_argument(
+            "--progress", choices=PROGRESS_MODES, default="text",
+            help="Progress on stderr: text (default), json (one event per line, for pipelines) or none"
+        )
+        subparser.add_argument(
+            "--config", type=str, default=None, metavar="FILE",
+            help="Read settings from this argscape.toml instead of one in the working directory"
+        )
+    args = parser.parse_args(argv)
+    progress = ProgressReporter(args.command, args.progress)
+    progress.start(argv=argv)
+    try:
+        apply_config(args.config)
+        return COMMANDS[args.command].run(args, progress)
+    except (FileNotFoundError, ValueError) as e:
+        if args.progress == "json":
+            progress.error(e)
+        else:
+            print(f"Error: {e}", file=sys.stderr)
+        return 2
@@ -0,0 +1,44 @@
+"""
+`argscape convert`: translate between ARG formats without starting the server.
+Reads .trees, .tsz, Relate (.anc with an optional .mut) and ARGweaver (.smc);
+writes .trees, .tsz, GraphML, edge CSV or Newick.
+"""
+
+import os
+
+
+def add_parser(subparsers):
+    parser = subparsers.add_parser(
+        "convert",
+        help="Convert between ARG formats",
+        description="Convert an ARG file. The output format comes from the output extension unless --to is given.",
+    )
+    parser.add_argument("input", help="Input .trees, .tsz, Relate .anc or ARGweaver .smc file")
+    parser.add_argument("output", help="Output file (.trees, .tsz, .graphml, .csv, .nwk)")
+    parser.add_argument("--to", choices=["trees", "tsz", "graphml", "csv", "newick"], default=None, help="Output format")
+    parser.add_argument("--mut", default=None, metavar="FILE", help="Relate .mut file to read with an .anc input")
+    parser.add_argument("--simplify", action="store_true", help="Simplify to the samples before writing")
+    parser.add_argument("--force", action="store_true", help="Overwrite the output if it exists")
+    return parser
+
+
+def run(args, progress) -> int:
+    from argscape.backend.tskit_utils import write_tree_sequence
+    from argscape.commands import load_input
+
+    if os.path.exists(args.output) and not args.force:
+        raise ValueError(f"{args.output} exists; use --force to overwrite it")
+    progress.stage("load", 0, f"Loading {args.input}")
+    ts, report = load_input(args.input, args.mut)
+    for warning in report.get("warnings", []):
+        progress.warning(warning)
+    if args.simplify:
+        progress.stage("simplify", 40, "Simplifying")
+        ts = ts.simplify()
+    progress.stage("write", 60, f"Writing {args.output}")
+    output_format = write_tree_sequence(ts, args.output, args.to)
+    progress.complete(
+        f"Wrote {args.output} ({output_format}): {ts.num_nodes} nodes, {ts.num_edges} edges, {ts.num_trees} trees",
+        output=args.output, format=output_format
+    )
+    return 0
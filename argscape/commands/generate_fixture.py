@@ -0,0 +1,86 @@
+"""
+`argscape generate-fixture`: write one of the synthetic test ARGs.
+Fixtures are small, deterministic tree sequences with documented properties
+(see argscape.backend.fixtures). Alongside the .trees file a golden summary
+can be written, and a later run with --check compares a file against it, so
+maintainers and plugin authors can tell when output drifts.
+"""
+
+import json
+import os
+
+EXIT_OK = 0
+EXIT_MISMATCH = 1
+
+
+def add_parser(subparsers):
+    parser = subparsers.add_parser(
+        "generate-fixture",
+        help="Write a small synthetic ARG with known properties",
+        description=(
+            "Write a deterministic test ARG. With --check, compare an existing file against a golden "
+            "summary instead; exits 1 when they differ."
+        ),
+    )
+    parser.add_argument("name", nargs="?", help="Fixture to generate (see --list)")
+    parser.add_argument("output", nargs="?", help="Output file (.trees, .tsz, .graphml, .csv, .nwk)")
+    parser.add_argument("--golden", default=None, metavar="FILE", help="Also write the fixture's summary as JSON to FILE")
+    parser.add_argument("--check", default=None, metavar="GOLDEN", help="Compare the output file (or a fresh fixture) with this golden summary")
+    parser.add_argument("--list", action="store_true", help="List fixtures and their documented properties")
+    parser.add_argument("--force", action="store_true", help="Overwrite existing files")
+    return parser
+
+
+def _write_json(path: str, value, force: bool):
+    if os.path.exists(path) and not force:
+        raise ValueError(f"{path} exists; use --force to overwrite it")
+    with open(path, "w", encoding="utf-8") as f:
+        json.dump(value, f, indent=2)
+        f.write("\n")
+
+
+def run(args, progress) -> int:
+    from argscape.backend.fixtures import check_golden, compare_summaries, generate_fixture, list_fixtures, summarize, FIXTURES
+    from argscape.backend.tskit_utils import write_tree_sequence
+    from argscape.commands import load_input
+
+    if args.list:
+        for fixture in list_fixtures():
+            print(f"{fixture['name']:<14} {fixture['description']}")
+            print(f"{'':<14} {json.dumps(fixture['properties'])}")
+        progress.complete(fixtures=len(FIXTURES))
+        return EXIT_OK
+    if not args.name:
+        raise ValueError("Give a fixture name (see --list)")
+
+    if args.check:
+        if args.output:
+            progress.stage("load", 0, f"Loading {args.output}")
+            ts, _ = load_input(args.output)
+        else:
+            ts = generate_fixture(args.name)
+        differences = check_golden(ts, args.check)
+        for difference in differences:
+            print(f"MISMATCH {difference}")
+        progress.complete(f"{len(differences)} differences from {args.check}", differences=len(differences))
+        return EXIT_MISMATCH if differences else EXIT_OK
+
+    if not args.output:
+        raise ValueError("Give an output file, or use --check")
+    if os.path.exists(args.output) and not args.force:
+        raise ValueError(f"{args.output} exists; use --force to overwrite it")
+    progress.stage("generate", 0, f"Building {args.name}")
+    ts = generate_fixture(args.name)
+    summary = summarize(ts)
+    # The documented properties are the fixture's contract; catch a builder that breaks it
+    broken = compare_summaries(FIXTURES[args.name][2], summary)
+    if broken:
+        raise ValueError(f"Fixture {args.name} does not match its documented properties: {'; '.join(broken)}")
+    output_format = write_tree_sequence(ts, args.output)
+    if args.golden:
+        _write_json(args.golden, summary, args.force)
+    progress.complete(
+        f"Wrote {args.name} to {args.output} ({output_format}): {ts.num_nodes} nodes, {ts.num_trees} trees",
+        output=args.output, golden=args.golden,
+    )
+    return EXIT_OK
@@ -0,0 +1,88 @@
+"""
+`argscape populations`: list or edit a file's populations table.
+Without edit options, prints each population's ID, name, color, display
+order and sample count. With them, writes a copy of the file whose
+population metadata carries the new names, descriptions, colors and order
+(see argscape.backend.tskit_utils.populations), the same edit as in the app.
+"""
+
+import json
+import os
+import sys
+
+
+def add_parser(subparsers):
+    parser = subparsers.add_parser(
+        "populations",
+        help="List or edit population names, descriptions, colors and order",
+        description="List a file's populations, or write a copy with edited population metadata.",
+    )
+    parser.add_argument("input", help="Input .trees, .tsz, Relate .anc or ARGweaver .smc file")
+    parser.add_argument("output", nargs="?", help="Output file for the edited tree sequence")
+    parser.add_argument("--name", action="append", default=[], metavar="ID=NAME", help="Rename a population (repeatable)")
+    parser.add_argument("--description", action="append", default=[], metavar="ID=TEXT", help="Describe a population (repeatable)")
+    parser.add_argument("--color", action="append", default=[], metavar="ID=#RRGGBB", help="Color a population (repeatable)")
+    parser.add_argument("--order", default=None, metavar="IDS", help="Every population ID in display order, comma-separated")
+    parser.add_argument("--format", choices=["text", "json"], default="text", help="Listing format on stdout")
+    parser.add_argument("--force", action="store_true", help="Overwrite the output file")
+    parser.add_argument("--mut", default=None, metavar="FILE", help="Relate .mut file to read with an .anc input")
+    return parser
+
+
+def _collect_edits(args) -> dict:
+    edits = {}
+    for field, values in (("name", args.name), ("description", args.description), ("color", args.color)):
+        for value in values:
+            population, sep, text = value.partition("=")
+            if not sep or not population.strip().isdigit():
+                raise ValueError(f"--{field} takes ID={field.upper()}, got {value!r}")
+            edits.setdefault(int(population), {})[field] = text
+    return edits
+
+
+def _print_listing(listing: dict, table_format: str):
+    if table_format == "json":
+        json.dump(listing, sys.stdout, indent=2)
+        sys.stdout.write("\n")
+        return
+    for population in sorted(listing["populations"], key=lambda p: (p["display_order"], p["id"])):
+        name = population["name"] if population["named"] else f"{population['name']} (unnamed)"
+        print(f"{population['id']:>4}  {name:<24} {population['color']}  {population['num_samples']:>8} samples  {population['description'] or ''}")
+
+
+def run(args, progress) -> int:
+    from argscape.backend.tskit_utils import apply_population_edits, list_populations, write_tree_sequence
+    from argscape.commands import load_input
+
+    edits = _collect_edits(args)
+    order = [int(p) for p in args.order.split(",")] if args.order else None
+
+    progress.stage("load", 0, f"Loading {args.input}")
+    ts, load_report = load_input(args.input, args.mut)
+    for warning in load_report.get("warnings", []):
+        progress.warning(warning)
+
+    if not edits and order is None:
+        listing = list_populations(ts)
+        _print_listing(listing, args.format)
+        if listing["num_unnamed"]:
+            progress.warning(f"{listing['num_unnamed']} populations have no name; set them with --name ID=NAME")
+        progress.complete(populations=ts.num_populations)
+        return 0
+
+    if not args.output:
+        raise ValueError("Give an output file for the edited tree sequence")
+    if os.path.exists(args.output) and not args.force:
+        raise ValueError(f"{args.output} exists; use --force to overwrite it")
+    progress.stage("edit", 30, "Editing populations")
+    edited, report = apply_population_edits(ts, edits, order)
+    if report["schema_changed"]:
+        progress.warning(report["schema_changed"])
+    for change in report["changes"]:
+        print(f"population {change['population']}: {change['field']} {change['old']!r} -> {change['new']!r}")
+    output_format = write_tree_sequence(edited, args.output)
+    progress.complete(
+        f"Wrote {len(report['changes'])} population edits to {args.output} ({output_format})",
+        output=args.output, changes=len(report["changes"]),
+    )
+    return 0
@@ -0,0 +1,83 @@
+"""
+Progress output for CLI commands.
+With `--progress json` each command writes one JSON object per line to
+stderr: a `start` event, a `stage` event as it enters each stage (with
+percent done, ETA and resource usage), then `complete` or `error`. Results
+still go to stdout or the output file, so pipelines can parse both.
+`--progress text` prints short human-readable lines, and `none` is silent.
+"""
+
+import json
+import os
+import sys
+import time
+from typing import Any, Optional, TextIO
+
+PROGRESS_MODES = ("text", "json", "none")
+
+
+def _resource_usage() -> dict:
+    from argscape.backend.resources import get_memory_usage_bytes
+
+    usage: dict = {}
+    memory = get_memory_usage_bytes()
+    if memory is not None:
+        usage["rss_mb"] = round(memory / (1024 * 1024), 1)
+    try:
+        times = os.times()
+        usage["cpu_seconds"] = round(times.user + times.system, 2)
+    except OSError:
+        pass
+    return usage
+
+
+class ProgressReporter:
+    """Stage and percent updates for one command run."""
+
+    def __init__(self, command: str, mode: str = "text", stream: Optional[TextIO] = None):
+        if mode not in PROGRESS_MODES:
+            raise ValueError(f"progress must be one of {PROGRESS_MODES}")
+        self.command = command
+        self.mode = mode
+        self.stream = stream or sys.stderr
+        self.started = time.monotonic()
+        self.current_stage: Optional[str] = None
+
+    def _emit(self, event: str, **fields: Any):
+        elapsed = time.monotonic() - self.started
+        if self.mode == "json":
+            record = {"event": event, "command": self.command, "time": time.time(), "elapsed_seconds": round(elapsed, 3), **fields, **_resource_usage()}
+            self.stream.write(json.dumps(record) + "\n")
+            self.stream.flush()
+        elif self.mode == "text":
+            if event == "stage":
+                percent = fields.get("percent")
+                prefix = f"[{percent:5.1f}%] " if percent is not None else ""
+                self.stream.write(f"{prefix}{fields.get('message') or fields.get('stage')}\n")
+            elif event == "warning":
+                self.stream.write(f"Warning: {fields['message']}\n")
+            elif event == "complete" and fields.get("message"):
+                self.stream.write(f"{fields['message']}\n")
+            self.stream.flush()
+
+    def start(self, **fields: Any):
+        self._emit("start", **fields)
+
+    def stage(self, stage: str, percent: Optional[float] = None, message: Optional[str] = None, **fields: Any):
+        """Enter `stage`; `percent` is overall progress (0-100) and drives the ETA."""
+        self.current_stage = stage
+        eta = None
+        if percent is not None and 0 < percent < 100:
+            elapsed = time.monotonic() - self.started
+            eta = round(elapsed * (100 - percent) / percent, 1)
+        self._emit("stage", stage=stage, percent=None if percent is None else round(percent, 1), eta_seconds=eta, message=message, **fields)
+
+    def warning(self, message: str):
+        self._emit("warning", stage=self.current_stage, message=message)
+
+    def complete(self, message: Optional[str] = None, **fields: Any):
+        """Finish; in text mode `message` is printed as the last line."""
+        self._emit("complete", stage=self.current_stage, percent=100.0, message=message, **fields)
+
+    def error(self, error: BaseException):
+        self._emit("error", stage=self.current_stage, error=type(error).__name__, message=str(error))
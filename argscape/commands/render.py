@@ -0,0 +1,62 @@
+"""
+`argscape render`: draw a figure template to PNG, SVG or PDF without a display.
+Loads the file, applies the template exactly as the web application's figure
+export does (matplotlib's Agg backend) and writes the result, so figures can
+be made on the cluster node where the data lives.
+"""
+
+import json
+import os
+import sys
+
+
+def add_parser(subparsers):
+    from argscape.backend.palettes import CATEGORICAL_PALETTES
+
+    parser = subparsers.add_parser(
+        "render",
+        help="Render a figure template headlessly",
+        description="Render a figure template (JSON or TOML) for an ARG file. The format comes from the output extension unless --format is given.",
+    )
+    parser.add_argument("input", nargs="?", help="Input .trees, .tsz, Relate .anc or ARGweaver .smc file")
+    parser.add_argument("output", nargs="?", help="Output .png, .svg or .pdf file")
+    parser.add_argument("-t", "--template", default=None, metavar="FILE", help="Figure template (default: the built-in template)")
+    parser.add_argument("--format", choices=["png", "svg", "pdf"], default=None)
+    parser.add_argument("--palette", choices=sorted(CATEGORICAL_PALETTES), default=None, help="Categorical palette for populations")
+    parser.add_argument("--simplify", action="store_true", help="Simplify to the samples before rendering")
+    parser.add_argument("--mut", default=None, metavar="FILE", help="Relate .mut file to read with an .anc input")
+    parser.add_argument("--print-template", action="store_true", help="Print the built-in template as a starting point and exit")
+    return parser
+
+
+def run(args, progress) -> int:
+    from argscape.backend.figures import DEFAULT_TEMPLATE, FIGURE_FORMATS, parse_template, render_figure, validate_template
+    from argscape.commands import load_input
+
+    if args.print_template:
+        json.dump(DEFAULT_TEMPLATE, sys.stdout, indent=2)
+        sys.stdout.write("\n")
+        return 0
+    if not args.input or not args.output:
+        raise ValueError("render needs an input file and an output file")
+
+    figure_format = args.format or os.path.splitext(args.output)[1].lower().lstrip(".")
+    if figure_format not in FIGURE_FORMATS:
+        raise ValueError(f"Can't tell the figure format of {args.output}; pass --format ({', '.join(FIGURE_FORMATS)})")
+    if args.template:
+        with open(args.template, "r", encoding="utf-8") as f:
+            template = parse_template(f.read(), os.path.basename(args.template))
+    else:
+        template = validate_template(DEFAULT_TEMPLATE)
+
+    progress.stage("load", 0, f"Loading {args.input}")
+    ts, _ = load_input(args.input, args.mut)
+    if args.simplify:
+        progress.stage("simplify", 20, "Simplifying")
+        ts = ts.simplify()
+    progress.stage("render", 30, f"Rendering {len(template['panels'])} panels")
+    contents = render_figure(ts, template, figure_format, {"categorical": args.palette} if args.palette else None)
+    with open(args.output, "wb") as f:
+        f.write(contents)
+    progress.complete(f"Wrote {args.output} ({figure_format}, {len(contents):,} bytes)", output=args.output, bytes=len(contents))
+    return 0
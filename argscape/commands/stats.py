@@ -0,0 +1,131 @@
+"""
+`argscape stats`: windowed statistics from the command line.
+Uses argscape.backend.windowed_stats, the same code as the statistics
+endpoint and figure panels, so the numbers match the GUI exactly.
+"""
+
+import json
+import os
+import sys
+
+
+def add_parser(subparsers):
+    from argscape.backend.gpu_stats import ACCELERATORS
+    from argscape.backend.missing_data import MISSING_DATA_OPTIONS
+    from argscape.backend.time_units import DISPLAY_UNITS
+    from argscape.backend.windowed_stats import SAMPLE_SET_OPTIONS, STATISTIC_MODES, STATISTICS, TABLE_FORMATS
+
+    parser = subparsers.add_parser(
+        "stats",
+        help="Compute windowed statistics",
+        description="Compute a windowed statistic and write it as a tidy table (one row per window and sample set).",
+    )
+    parser.add_argument("input", help="Input .trees, .tsz, Relate .anc or ARGweaver .smc file")
+    parser.add_argument("statistic", choices=STATISTICS)
+    parser.add_argument("-o", "--output", default=None, help="Output file (default: CSV to stdout)")
+    parser.add_argument("--format", choices=TABLE_FORMATS, default=None, help="Output format (default: from the output extension, else csv)")
+    parser.add_argument("--mode", choices=STATISTIC_MODES, default="site")
+    windows = parser.add_mutually_exclusive_group()
+    windows.add_argument("--num-windows", type=int, default=None, help="Split the genome into N equal windows")
+    windows.add_argument("--window-size", type=float, default=None, help="Windows of this many base pairs")
+    windows.add_argument("--tree-windows", action="store_true", help="One window per local tree")
+    parser.add_argument(
+        "--sample-sets", default="all",
+        help=f"One of {', '.join(SAMPLE_SET_OPTIONS)}, or comma-separated names of sets in --sample-set-file",
+    )
+    parser.add_argument(
+        "--sample-set-file", default=None, metavar="FILE",
+        help="JSON object of named sample set definitions (as saved by the app; see argscape.backend.sample_sets)",
+    )
+    parser.add_argument("--unpolarised", action="store_true", help="Fold the SFS")
+    parser.add_argument(
+        "--missing-data", choices=MISSING_DATA_OPTIONS, default="ignore",
+        help="Windows where a sample is missing (isolated): compute as tskit does, leave empty, or drop the region and renormalise",
+    )
+    parser.add_argument("--time-units", choices=DISPLAY_UNITS, default="native", help="Units of branch-mode values (default: the file's own)")
+    parser.add_argument("--generation-time", type=float, default=None, metavar="YEARS", help="Years per generation, for --time-units years")
+    parser.add_argument("--mutation-rate", type=float, default=None, metavar="RATE", help="Per-base, per-generation rate to calibrate uncalibrated times")
+    parser.add_argument(
+        "--accelerator", choices=ACCELERATORS, default="cpu",
+        help="Run branch-mode diversity and tmrca on the GPU (gpu, or auto when it pays off); falls back to the CPU",
+    )
+    parser.add_argument(
+        "--workers", type=int, default=1, metavar="N",
+        help="Split the windows across N processes (CPU only; each holds its own copy of the tree sequence)",
+    )
+    parser.add_argument("--mut", default=None, metavar="FILE", help="Relate .mut file to read with an .anc input")
+    return parser
+
+
+def run(args, progress) -> int:
+    from argscape.backend.contigs import detect_contigs
+    from argscape.backend.sample_sets import resolve_sample_sets
+    from argscape.backend.time_units import resolve_calibration, scale_statistic
+    from argscape.backend.windowed_stats import SAMPLE_SET_OPTIONS, compute_statistic, make_windows, sample_sets_for, serialize_table
+    from argscape.commands import load_input
+
+    table_format = args.format
+    if table_format is None and args.output:
+        extension = os.path.splitext(args.output)[1].lower().lstrip(".")
+        table_format = extension if extension in ("csv", "json", "parquet") else None
+    table_format = table_format or "csv"
+    if table_format == "parquet" and not args.output:
+        raise ValueError("Parquet output needs a file; pass --output")
+    if args.workers < 1:
+        raise ValueError("--workers must be at least 1")
+
+    progress.stage("load", 0, f"Loading {args.input}")
+    ts, _ = load_input(args.input, args.mut)
+    if args.sample_sets in SAMPLE_SET_OPTIONS:
+        sample_sets = sample_sets_for(ts, args.sample_sets)
+    elif args.sample_set_file:
+        with open(args.sample_set_file, "r", encoding="utf-8") as f:
+            definitions = json.load(f)
+        sample_sets = resolve_sample_sets(ts, definitions, [name.strip() for name in args.sample_sets.split(",") if name.strip()])
+    else:
+        raise ValueError(f"--sample-sets must be one of {SAMPLE_SET_OPTIONS} unless --sample-set-file defines the named sets")
+    windows = make_windows(ts, args.num_windows, args.window_size, args.tree_windows)
+    # Concatenated chromosomes get windows split at contig boundaries and per-contig coordinates
+    contig_map = detect_contigs(ts)
+    if contig_map is not None:
+        windows = contig_map.split_windows(windows)
+        progress.warning(f"{len(contig_map.contigs)} contigs found ({contig_map.source}); adding per-contig columns")
+    progress.stage("compute", 20, f"Computing {args.statistic} over {len(windows) - 1} windows")
+    result = compute_statistic(
+        ts, args.statistic, args.mode, windows, sample_sets, not args.unpolarised, args.missing_data, args.accelerator, args.workers
+    )
+    accelerated = result["accelerator"]
+    if accelerated is not None:
+        if accelerated["used"] == "gpu":
+            check = accelerated["check"]
+            progress.warning(f"Computed on the GPU ({accelerated['device']}); cross-check max relative error {check['max_relative_error']:.1e}")
+        else:
+            progress.warning(f"Computed on the CPU: {accelerated['reason']}")
+    missing = result["missing_data"]
+    if missing["missing_span"] and args.missing_data == "ignore":
+        progress.warning(
+            f"Samples are missing over {missing['missing_span']:g} bp and are counted as ancestral; "
+            "consider --missing-data mask or drop"
+        )
+    elif missing["affected_rows"]:
+        progress.warning(f"{missing['affected_rows']} rows touch missing data ({args.missing_data})")
+    calibration = resolve_calibration(ts, args.generation_time, args.mutation_rate)
+    result = scale_statistic(result, calibration, args.time_units)
+    if result["time_units"] is not None:
+        for warning in calibration.warnings:
+            progress.warning(warning)
+    if contig_map is not None:
+        result["columns"] = contig_map.annotate_table(result["columns"])
+    progress.stage("write", 90, "Writing table")
+    contents = serialize_table(result, table_format)
+
+    if args.output:
+        with open(args.output, "wb") as f:
+            f.write(contents)
+    else:
+        sys.stdout.write(contents.decode("utf-8"))
+    progress.complete(
+        f"Wrote {args.statistic} over {result['num_windows']} windows to {args.output or 'stdout'}",
+        output=args.output, num_windows=result["num_windows"]
+    )
+    return 0
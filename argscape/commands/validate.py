@@ -0,0 +1,69 @@
+"""
+`argscape validate`: check ARG files before archiving or analysing them.
+Runs the same integrity pass as the batch pipeline on each file and reports
+per file, as text or JSON. The exit code lets a pipeline gate on the result.
+"""
+
+import json
+import sys
+
+EXIT_OK = 0
+EXIT_INVALID = 1
+# Exit code 2 is argparse's, for usage errors
+
+
+def add_parser(subparsers):
+    parser = subparsers.add_parser(
+        "validate",
+        help="Check ARG files for integrity problems",
+        description=(
+            "Validate one or more ARG files. Exits 0 when all are valid, 1 when any is invalid "
+            "(or has warnings, with --strict)."
+        ),
+    )
+    parser.add_argument("inputs", nargs="+", help="Input .trees, .tsz, Relate .anc or ARGweaver .smc files")
+    parser.add_argument("--format", choices=["text", "json"], default="text", help="Report format on stdout")
+    parser.add_argument("--strict", action="store_true", help="Treat warnings as failures")
+    return parser
+
+
+def validate_file(path: str) -> dict:
+    from argscape.backend.batch import validate_tree_sequence
+    from argscape.commands import load_input
+
+    try:
+        ts, load_report = load_input(path)
+    except Exception as e:
+        return {"path": path, "status": "invalid", "error": f"Could not load: {e}"}
+    try:
+        report = validate_tree_sequence(ts)
+    except ValueError as e:
+        return {"path": path, "status": "invalid", "error": str(e)}
+    report["warnings"] = list(load_report.get("warnings", [])) + report["warnings"]
+    return {"path": path, "status": "warning" if report["warnings"] else "ok", "report": report}
+
+
+def run(args, progress) -> int:
+    results = []
+    for i, path in enumerate(args.inputs):
+        progress.stage("validate", 100 * i / len(args.inputs), f"Validating {path}", file=path)
+        results.append(validate_file(path))
+    failing = ("invalid", "warning") if args.strict else ("invalid",)
+    summary = {status: sum(1 for r in results if r["status"] == status) for status in ("ok", "warning", "invalid")}
+    passed = not any(r["status"] in failing for r in results)
+
+    if args.format == "json":
+        json.dump({"files": results, "summary": summary, "strict": args.strict, "passed": passed}, sys.stdout, indent=2)
+        sys.stdout.write("\n")
+    else:
+        for result in results:
+            if result["status"] == "invalid":
+                print(f"INVALID  {result['path']}: {result['error']}")
+                continue
+            report = result["report"]
+            print(f"{result['status'].upper():<8} {result['path']}: {report['num_nodes']} nodes, {report['num_trees']} trees, {report['num_samples']} samples")
+            for warning in report["warnings"]:
+                print(f"         - {warning}")
+        print(f"{summary['ok']} ok, {summary['warning']} with warnings, {summary['invalid']} invalid")
+    progress.complete(passed=passed, **summary)
+    return EXIT_OK if passed else EXIT_INVALID
@@ -0,0 +1,92 @@
+"""
+Open tree sequences from a notebook or script in a running ARGscape.
+Start ARGscape with `argscape --listen`, then:
+
+    import argscape.notebook
+    argscape.notebook.show(ts)
+
+The tree sequence is sent to the local instance and opened in its browser
+window (or a new one if none is open). Paths are read by the server directly
+instead of being uploaded.
+"""
+
+import io
+import json
+import os
+import urllib.error
+import urllib.parse
+import urllib.request
+import webbrowser
+from typing import Any, Dict, Optional, Union
+
+DEFAULT_NAME = "notebook.trees"
+PUSH_TIMEOUT_SECONDS = 300
+
+
+def find_instance() -> Dict[str, Any]:
+    """Connection details written by `argscape --listen`."""
+    from argscape.backend.event_bridge import read_instance_file
+    instance = read_instance_file()
+    if not instance:
+        raise RuntimeError("No listening ARGscape instance found; start one with `argscape --listen`")
+    return instance
+
+
+def show(
+    ts: Union[str, os.PathLike, Any],
+    name: Optional[str] = None,
+    focus: bool = True,
+    url: Optional[str] = None,
+    token: Optional[str] = None
+) -> Dict[str, Any]:
+    """Open a tree sequence in ARGscape.
+
+    Args:
+        ts: A tskit.TreeSequence, or the path of a .trees/.tsz file
+        name: Filename to show it under (default: the file's name, or notebook.trees)
+        focus: Switch the ARGscape window to it
+        url, token: Instance to use instead of the one found from `argscape --listen`
+
+    Returns:
+        The stored filename and basic counts
+    """
+    if url is None:
+        instance = find_instance()
+        url, token = instance["url"], token or instance.get("token")
+
+    params: Dict[str, str] = {"focus": str(focus).lower()}
+    body = b""
+    if isinstance(ts, (str, os.PathLike)):
+        params["path"] = os.path.abspath(os.fspath(ts))
+    else:
+        buffer = io.BytesIO()
+        ts.dump(buffer)
+        body = buffer.getvalue()
+        name = name or DEFAULT_NAME
+    if name:
+        params["name"] = name
+
+    request = urllib.request.Request(
+        f"{url}/api/events/push?{urllib.parse.urlencode(params)}",
+        data=body,
+        method="POST",
+        headers={"Content-Type": "application/octet-stream"},
+    )
+    if token:
+        request.add_header("Authorization", f"Bearer {token}")
+    try:
+        with urllib.request.urlopen(request, timeout=PUSH_TIMEOUT_SECONDS) as response:
+            result = json.loads(response.read())
+    except urllib.error.HTTPError as e:
+        detail = json.loads(e.read() or b"{}").get("detail", e.reason)
+        raise RuntimeError(f"ARGscape could not open the tree sequence: {detail}") from None
+    except urllib.error.URLError as e:
+        raise RuntimeError(f"Could not reach ARGscape at {url}: {e.reason}") from None
+
+    # Nothing was open to switch to the file, so open it in a new browser window
+    if focus and not result.get("viewers"):
+        page = f"{url}/visualize/{urllib.parse.quote(result['filename'])}"
+        if token:
+            page += f"?token={urllib.parse.quote(token)}"
+        webbrowser.open(page)
+    return result
@@ -15,6 +15,19 @@ import DocsPage from './components/DocsPage';
 import { isFirstVisit, markVisited } from './utils/session';
 import { api } from './lib/api';
 import { log } from './lib/logger';
+import { useInboxWatcher } from './hooks/useInboxWatcher';
+import { useJobNotifications } from './hooks/useJobNotifications';
+import { useBackendReadiness } from './hooks/useBackendReadiness';
+import { loadFormattingConfig } from './lib/formatting';
+import { loadMessages } from './lib/i18n';
+import AlertModal from './components/ui/AlertModal';
+import PerformanceHud from './components/ui/PerformanceHud';
+
+interface CrashReport {
+  exception_type: string;
+  message: string;
+  emergency_save: { saved: string[]; failed: string[] };
+}
 
 // Layout component that includes the footer
 function Layout({ children }: { children: React.ReactNode }) {
@@ -176,6 +189,40 @@ function Home() {
 }
 
 function App() {
+  useInboxWatcher();
+  useJobNotifications();
+  const readiness = useBackendReadiness();
+  const [crashReport, setCrashReport] = useState<CrashReport | null>(null);
+
+  // Tell the user if the previous run ended in a crash, and what was saved
+  useEffect(() => {
+    api.getCrashReport()
+      .then((response) => {
+        const data = response.data as { crashed: boolean; report: CrashReport | null };
+        if (data.crashed && data.report) {
+          log.warn('Previous session ended in a crash', { component: 'App', data: data.report });
+          setCrashReport(data.report);
+        }
+      })
+      .catch(() => undefined);
+  }, []);
+
+  const dismissCrashReport = () => {
+    setCrashReport(null);
+    api.dismissCrashReport().catch(() => undefined);
+  };
+
+  useEffect(() => {
+    loadFormattingConfig()
+      .then((config) => loadMessages(config.locale))
+      .catch((error) => {
+        log.warn('Using default number formatting', {
+          component: 'App',
+          error: error instanceof Error ? error : new Error(String(error))
+        });
+      });
+  }, []);
+
   return (
     <ColorThemeProvider>
       <TreeSequenceProvider>
@@ -193,6 +240,30 @@ function App() {
             <Route path="/load" element={<Layout><IntermediatePage selectedOption="load" onBack={() => {}} /></Layout>} />
           </Routes>
         </Router>
+        <PerformanceHud />
+        {!readiness.ready && (
+          <div
+            role="status"
+            className="fixed bottom-4 left-1/2 -translate-x-1/2 z-40 px-4 py-2 rounded-xl bg-sp-very-dark-blue border border-sp-pale-green/20 text-sp-white/80 text-sm shadow-lg"
+          >
+            {readiness.state === 'starting' ? 'Starting the ARGscape backend…' : 'Preparing the backend…'}
+          </div>
+        )}
+        {crashReport && (
+          <AlertModal
+            isOpen
+            type="error"
+            title="ARGscape closed unexpectedly"
+            message={
+              `The last session ended with ${crashReport.exception_type}: ${crashReport.message}. ` +
+              (crashReport.emergency_save.saved.length
+                ? 'Your uploaded files and session state were saved before it closed.'
+                : 'Files uploaded before the crash are still available.')
+            }
+            reportContext={{ operation: 'crash' }}
+            onClose={dismissCrashReport}
+          />
+        )}
       </TreeSequenceProvider>
     </ColorThemeProvider>
   );
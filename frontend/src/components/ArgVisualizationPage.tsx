@@ -3,6 +3,7 @@ import { ForceDirectedGraphContainer } from './ForceDirectedGraph/ForceDirectedG
 import { useTreeSequence } from '../context/TreeSequenceContext';
 import { useColorTheme } from '../context/ColorThemeContext';
 import { useRef, useEffect, useState, useMemo } from 'react';
+import * as d3 from 'd3';
 import { api } from '../lib/api';
 import { exportSVGAsImage } from '../lib/imageExport';
 import { ColorThemeDropdown } from './ui/ColorThemeDropdown';
@@ -60,6 +61,34 @@ export default function ArgVisualizationPage() {
 
         try {
             const imageFilename = `${decodedFilename.replace(/\.(trees|tsz)$/, '')}_arg.png`;
+
+            // Warn before exports that would be unexpectedly large or slow
+            const count = (selector: string) => svgElement.querySelectorAll(selector).length;
+            const estimate = await api.estimateExport({
+                filename: decodedFilename,
+                format: 'png',
+                element_counts: {
+                    nodes: count('circle'),
+                    edges: count('line, path'),
+                    mutations: count('rect, polygon'),
+                    labels: count('text'),
+                },
+                width: svgElement.clientWidth,
+                height: svgElement.clientHeight,
+                scale: 3,
+            }).catch((error) => {
+                log.warn('Export estimate unavailable', {
+                    component: 'ArgVisualizationPage',
+                    error: error instanceof Error ? error : new Error(String(error)),
+                });
+                return null;
+            }) as { bytes: number; seconds: number; warnings: string[] } | null;
+            if (estimate && estimate.warnings.length > 0) {
+                const summary = `About ${(estimate.bytes / 1024 / 1024).toFixed(1)} MB, ~${Math.ceil(estimate.seconds)} s`;
+                if (!window.confirm(`${estimate.warnings.join('\n')}\n\n${summary}. Export anyway?`)) {
+                    return;
+                }
+            }
             
             await exportSVGAsImage(svgElement, {
                 filename: imageFilename,
@@ -83,6 +112,60 @@ export default function ArgVisualizationPage() {
         }
     };
 
+    // Node positions as laid out in the view, so exports match what is on screen
+    const viewPositions = (svgElement: SVGSVGElement) => {
+        const positions: Record<number, number[]> = {};
+        d3.select(svgElement)
+            .selectAll<SVGCircleElement, { id: number; x?: number; y?: number; is_combined?: boolean }>('circle')
+            .each((d) => {
+                if (d && !d.is_combined && d.x !== undefined && d.y !== undefined) {
+                    positions[d.id] = [d.x, d.y];
+                }
+            });
+        return positions;
+    };
+
+    const downloadBlob = (blob: Blob, name: string) => {
+        const url = URL.createObjectURL(blob);
+        const link = document.createElement('a');
+        link.href = url;
+        link.setAttribute('download', name);
+        document.body.appendChild(link);
+        link.click();
+        document.body.removeChild(link);
+        URL.revokeObjectURL(url);
+    };
+
+    const handleDownloadSvg = async () => {
+        const svgElement = svgRef.current;
+        if (!svgElement) return;
+
+        try {
+            const positions = viewPositions(svgElement);
+            const blob = await api.exportSvg({ filename: decodedFilename, positions, group_by: 'population' });
+            if (blob === null) {
+                // Too many elements for a usable SVG; fall back to a raster export
+                await handleDownloadImage();
+                return;
+            }
+            downloadBlob(blob, `${decodedFilename.replace(/\.(trees|tsz)$/, '')}_arg.svg`);
+        } catch (error) {
+            console.error('Error downloading ARG SVG:', error);
+        }
+    };
+
+    const handleDownloadHtml = async () => {
+        const svgElement = svgRef.current;
+        if (!svgElement) return;
+
+        try {
+            const blob = await api.exportHtmlBundle({ filename: decodedFilename, positions: viewPositions(svgElement) });
+            downloadBlob(blob, `${decodedFilename.replace(/\.(trees|tsz)$/, '')}_view.html`);
+        } catch (error) {
+            console.error('Error downloading ARG HTML bundle:', error);
+        }
+    };
+
     return (
         <div 
             className="h-screen flex flex-col overflow-hidden font-sans"
@@ -177,6 +260,44 @@ export default function ArgVisualizationPage() {
                             >
                                 Download Image
                             </button>
+                            <button 
+                                className="font-medium px-4 py-2 rounded-lg text-sm transition-colors"
+                                style={{
+                                    backgroundColor: colors.containerBackground,
+                                    color: colors.text
+                                }}
+                                onMouseEnter={(e) => {
+                                    e.currentTarget.style.backgroundColor = colors.accentPrimary;
+                                    e.currentTarget.style.color = colors.background;
+                                }}
+                                onMouseLeave={(e) => {
+                                    e.currentTarget.style.backgroundColor = colors.containerBackground;
+                                    e.currentTarget.style.color = colors.text;
+                                }}
+                                onClick={handleDownloadSvg}
+                                title="Download an editable SVG with layers per population"
+                            >
+                                Download SVG
+                            </button>
+                            <button 
+                                className="font-medium px-4 py-2 rounded-lg text-sm transition-colors"
+                                style={{
+                                    backgroundColor: colors.containerBackground,
+                                    color: colors.text
+                                }}
+                                onMouseEnter={(e) => {
+                                    e.currentTarget.style.backgroundColor = colors.accentPrimary;
+                                    e.currentTarget.style.color = colors.background;
+                                }}
+                                onMouseLeave={(e) => {
+                                    e.currentTarget.style.backgroundColor = colors.containerBackground;
+                                    e.currentTarget.style.color = colors.text;
+                                }}
+                                onClick={handleDownloadHtml}
+                                title="Download an interactive, read-only HTML file that opens without ARGscape"
+                            >
+                                Download HTML
+                            </button>
                             {data && <DownloadDropdown filename={data.filename} />}
                         </div>
                     </div>
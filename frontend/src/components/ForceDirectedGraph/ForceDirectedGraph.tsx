@@ -3,6 +3,8 @@ import * as d3 from 'd3';
 import * as dagre from 'dagre';
 import { ForceDirectedGraphProps, GraphNode, GraphEdge, NodeSizeSettings, TemporalSpacingMode } from './ForceDirectedGraph.types';
 import { useColorTheme } from '../../context/ColorThemeContext';
+import { api, PickHit } from '../../lib/api';
+import { log } from '../../lib/logger';
 
 // Default node sizes - these will be overridden by props
 const DEFAULT_NODE_SIZES: NodeSizeSettings = {
@@ -54,6 +56,14 @@ const GRAPH_CONSTANTS = {
     LAYOUT: {
         BOTTOM_MARGIN_RATIO: 0.15 // Reserve 15% of height for footer/bottom margin
     },
+    LABELS: {
+        PLACEMENT_DELAY_MS: 250 // Wait for zooming/panning to settle before re-placing labels
+    },
+    PICKING: {
+        SERVER_NODE_THRESHOLD: 2000, // Above this, hit-testing goes to the backend quadtree
+        RADIUS_PX: 6,
+        HOVER_INTERVAL_MS: 50
+    },
     PERFORMANCE: {
         TICK_SKIP_DESCENDANT: 3,
         TICK_SKIP_CROSSING: 5,
@@ -975,7 +985,8 @@ export const ForceDirectedGraph = forwardRef<SVGSVGElement, ForceDirectedGraphPr
     nodeSizes = DEFAULT_NODE_SIZES,
     sampleOrder = 'degree',
     edgeThickness = GRAPH_CONSTANTS.EDGE_STROKE_WIDTH,
-    temporalSpacingMode = 'equal'
+    temporalSpacingMode = 'equal',
+    filename
 }, ref: ForwardedRef<SVGSVGElement>) => {
     const { colors } = useColorTheme();
     
@@ -1019,11 +1030,74 @@ export const ForceDirectedGraph = forwardRef<SVGSVGElement, ForceDirectedGraphPr
 
         const g = svg.append("g");
 
+        const currentPositions = () => {
+            const positions: Record<number, number[]> = {};
+            combinedNodes.forEach(d => {
+                positions[d.id] = [d.x ?? 0, calculateYPosition(d.time, setupUniqueTimes, availableHeight, temporalSpacingMode)];
+            });
+            return positions;
+        };
+
+        // With label placement, labels keep a constant on-screen size and are re-placed after each zoom
+        const labelFontSize = nodeSizes.sample * 1.2;
+        let labelSelection: d3.Selection<SVGTextElement, Node, SVGGElement, unknown> | null = null;
+        let labelPlacement = new Map<number, { dx: number; dy: number; anchor: string }>();
+        let labelTimer: ReturnType<typeof setTimeout> | null = null;
+        let labelRequest = 0;
+
+        const applyLabelPlacement = (scale: number) => {
+            if (!labelSelection || !filename) return;
+            labelSelection
+                .attr("font-size", `${labelFontSize / scale}px`)
+                .attr("display", d => labelPlacement.has(d.id) ? null : "none")
+                .attr("text-anchor", d => labelPlacement.get(d.id)?.anchor ?? "middle")
+                .attr("dx", d => (labelPlacement.get(d.id)?.dx ?? 0) / scale)
+                .attr("dy", d => (labelPlacement.get(d.id)?.dy ?? 0) / scale);
+        };
+
+        const scheduleLabelPlacement = () => {
+            if (!filename) return;
+            if (labelTimer) clearTimeout(labelTimer);
+            labelTimer = setTimeout(async () => {
+                const svgElement = svg.node();
+                if (!labelSelection || !svgElement) return;
+                const transform = d3.zoomTransform(svgElement);
+                // Every node is an obstacle; only labelled (sample) nodes are candidates
+                const positions = currentPositions();
+                const candidateNodeIds = labelSelection.data().map(d => d.id);
+                const [minX, minY] = transform.invert([0, 0]);
+                const [maxX, maxY] = transform.invert([actualWidth, actualHeight]);
+                const request = ++labelRequest;
+                try {
+                    const response = await api.placeLabels({
+                        filename,
+                        positions,
+                        zoom: transform.k,
+                        font_size: labelFontSize,
+                        node_radius: nodeSizes.sample,
+                        viewport: [minX, minY, maxX, maxY],
+                        candidate_node_ids: candidateNodeIds
+                    });
+                    if (request !== labelRequest) return;
+                    const placed = (response.data as { labels: { node_id: number; dx: number; dy: number; anchor: string }[] }).labels;
+                    labelPlacement = new Map(placed.map(label => [label.node_id, label]));
+                    applyLabelPlacement(d3.zoomTransform(svgElement).k);
+                } catch (error) {
+                    log.debug('Label placement unavailable, showing all labels', {
+                        component: 'ForceDirectedGraph',
+                        error: error instanceof Error ? error : new Error(String(error))
+                    });
+                }
+            }, GRAPH_CONSTANTS.LABELS.PLACEMENT_DELAY_MS);
+        };
+
         const zoom = d3.zoom<SVGSVGElement, unknown>()
             .scaleExtent([GRAPH_CONSTANTS.ZOOM.MIN_SCALE, GRAPH_CONSTANTS.ZOOM.MAX_SCALE])
             .on("zoom", (event) => {
                 g.attr("transform", event.transform);
-            });
+                if (labelPlacement.size > 0) applyLabelPlacement(event.transform.k);
+            })
+            .on("end", scheduleLabelPlacement);
 
         svg.call(zoom);
 
@@ -1313,6 +1387,85 @@ export const ForceDirectedGraph = forwardRef<SVGSVGElement, ForceDirectedGraphPr
             .attr("text-anchor", "middle")  // Center the text horizontally
             .attr("dx", 0)  // No horizontal offset (centered)
             .attr("dy", d => getNodeRadius(d, nodeSizes, combinedNodes, combinedEdges) + 12);  // Position below node
+        labelSelection = labels;
+
+        // Big graphs: per-element DOM events and the tooltip's edge scans drop frames,
+        // so pointer events are answered by the backend quadtree instead
+        const serverPicking = !!filename && combinedNodes.length > GRAPH_CONSTANTS.PICKING.SERVER_NODE_THRESHOLD;
+        let pickIndexReady = false;
+        let lastHover = 0;
+
+        const pickAt = async (event: MouseEvent): Promise<PickHit | null> => {
+            const svgElement = svg.node();
+            if (!pickIndexReady || !filename || !svgElement) return null;
+            const transform = d3.zoomTransform(svgElement);
+            const [x, y] = transform.invert(d3.pointer(event, svgElement));
+            try {
+                const response = await api.pick(filename, x, y, GRAPH_CONSTANTS.PICKING.RADIUS_PX / transform.k);
+                return response.data.hit;
+            } catch (error) {
+                log.debug('Pick query failed', {
+                    component: 'ForceDirectedGraph',
+                    error: error instanceof Error ? error : new Error(String(error))
+                });
+                return null;
+            }
+        };
+
+        if (serverPicking) {
+            const nodeById = new Map(combinedNodes.map(n => [n.id, n]));
+            const endpointId = (end: number | Node | GraphNode) => typeof end === 'number' ? end : end.id;
+            nodes.style("pointer-events", "none");
+            edges.style("pointer-events", "none");
+
+            svg
+                .on("click.pick", async (event: MouseEvent) => {
+                    const hit = await pickAt(event);
+                    if (hit?.kind === 'node' && nodeById.has(hit.id)) {
+                        onNodeClick?.(nodeById.get(hit.id)!);
+                    } else if (hit?.kind === 'edge') {
+                        const edge = combinedEdges.find(e => endpointId(e.source) === hit.parent && endpointId(e.target) === hit.child);
+                        if (edge) onEdgeClick?.(edge);
+                    }
+                })
+                .on("contextmenu.pick", async (event: MouseEvent) => {
+                    event.preventDefault();
+                    const hit = await pickAt(event);
+                    if (hit?.kind === 'node' && nodeById.has(hit.id)) onNodeRightClick?.(nodeById.get(hit.id)!);
+                })
+                .on("mousemove.pick", async (event: MouseEvent) => {
+                    const now = performance.now();
+                    if (now - lastHover < GRAPH_CONSTANTS.PICKING.HOVER_INTERVAL_MS) return;
+                    lastHover = now;
+                    const hit = await pickAt(event);
+                    if (hit?.kind !== 'node') {
+                        tooltip.style("visibility", "hidden");
+                        return;
+                    }
+                    let tooltipContent = `${hit.is_sample ? 'Sample node' : 'Node'} ${hit.id}<br>Time: ${hit.time}`;
+                    if (hit.parents.length > 0) tooltipContent += `<br>Parents: ${hit.parents.join(", ")}`;
+                    if (hit.children.length > 0) tooltipContent += `<br>Children: ${hit.children.join(", ")}`;
+                    if (hit.individual !== -1) tooltipContent += `<br>Individual: ${hit.individual}`;
+                    tooltip
+                        .style("visibility", "visible")
+                        .html(tooltipContent)
+                        .style("left", (event.pageX + 10) + "px")
+                        .style("top", (event.pageY - 10) + "px");
+                })
+                .on("mouseleave.pick", () => tooltip.style("visibility", "hidden"));
+        }
+
+        simulation.on("end", () => {
+            scheduleLabelPlacement();
+            if (serverPicking && filename) {
+                api.buildPickIndex(filename, currentPositions())
+                    .then(() => { pickIndexReady = true; })
+                    .catch(error => log.warn('Could not build pick index; hover and click are unavailable', {
+                        component: 'ForceDirectedGraph',
+                        error: error instanceof Error ? error : new Error(String(error))
+                    }));
+            }
+        });
 
         simulation.on("tick", () => {
             if (!stableData) return;
@@ -1355,8 +1508,9 @@ export const ForceDirectedGraph = forwardRef<SVGSVGElement, ForceDirectedGraphPr
         return () => {
             if (simulation) simulation.stop();
             if (tooltip) tooltip.remove();
+            if (labelTimer) clearTimeout(labelTimer);
         };
-    }, [stableData, width, height, onNodeClick, onNodeRightClick, onEdgeClick, focalNode, nodeSizes, ref, sampleOrder, edgeThickness, temporalSpacingMode]);
+    }, [stableData, width, height, onNodeClick, onNodeRightClick, onEdgeClick, focalNode, nodeSizes, ref, sampleOrder, edgeThickness, temporalSpacingMode, filename]);
 
     return (
         <div className="w-full h-full">
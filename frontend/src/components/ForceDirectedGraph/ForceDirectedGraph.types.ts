@@ -108,4 +108,5 @@ export interface ForceDirectedGraphProps {
     sampleOrder?: string;  // The ordering method for sample nodes
     edgeThickness?: number;  // Edge thickness setting
     temporalSpacingMode?: TemporalSpacingMode;
+    filename?: string;  // Enables backend label placement for this file
 } 
\ No newline at end of file
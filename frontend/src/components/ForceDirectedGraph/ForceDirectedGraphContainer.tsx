@@ -7,9 +7,14 @@ import { RangeSlider } from '../ui/range-slider';
 import { TreeRangeSlider } from '../ui/tree-range-slider';
 import { SampleOrderControl, SampleOrderType } from '../ui/sample-order-control';
 import { ArgStatsData } from '../ui/arg-stats-display';
-import { api } from '../../lib/api';
+import { api, ViewLinkState } from '../../lib/api';
 import { useColorTheme } from '../../context/ColorThemeContext';
 import { useTreeSequence } from '../../context/TreeSequenceContext';
+import { useLinkedViews } from '../../hooks/useLinkedViews';
+import { useEventBridge } from '../../hooks/useEventBridge';
+import { useCollaboration, CollabState } from '../../hooks/useCollaboration';
+import { useViewLink } from '../../hooks/useViewLink';
+import { useFrameBudget } from '../../hooks/useFrameBudget';
 
 // Define view modes for the graph
 type ViewMode = 'full' | 'subgraph' | 'ancestors';
@@ -91,6 +96,7 @@ export const ForceDirectedGraphContainer = forwardRef<SVGSVGElement, ForceDirect
 }, ref: ForwardedRef<SVGSVGElement>) => {
     const { colors } = useColorTheme();
     const { treeSequence } = useTreeSequence();
+    const frameBudget = useFrameBudget(filename);
     const [data, setData] = useState<GraphData | null>(null);
     const [subArgData, setSubArgData] = useState<GraphData | null>(null); // Rename to clarify this is the SubARG
     const [error, setError] = useState<string | null>(null);
@@ -130,6 +136,16 @@ export const ForceDirectedGraphContainer = forwardRef<SVGSVGElement, ForceDirect
         }));
     }, []);
 
+    // Open with the sample ordering chosen in setup
+    useEffect(() => {
+        api.getSettings()
+            .then(response => {
+                const layout = (response.data as { default_layout?: SampleOrderType }).default_layout;
+                if (layout) setSampleOrder(layout);
+            })
+            .catch(() => undefined);
+    }, []);
+
     // Debounce genomic range changes to prevent excessive API calls
     useEffect(() => {
         if (isFilterActive && filterMode === 'genomic') {
@@ -277,6 +293,16 @@ export const ForceDirectedGraphContainer = forwardRef<SVGSVGElement, ForceDirect
                 
                 setData(graphData);
                 setError(null);
+
+                // Prepare the neighboring tree windows so scrubbing doesn't stall at each step
+                if (treeParams) {
+                    api.prefetchGraphData(filename, {
+                        treeStartIdx: treeParams.tree_start_idx,
+                        treeEndIdx: treeParams.tree_end_idx,
+                        maxSamples: max_samples,
+                        sampleOrder,
+                    }).catch(() => undefined);
+                }
             } catch (e) {
                 console.error('Error fetching graph data:', e);
                 setError(e instanceof Error ? e.message : 'An error occurred while fetching graph data');
@@ -399,6 +425,120 @@ export const ForceDirectedGraphContainer = forwardRef<SVGSVGElement, ForceDirect
         setSelectedNode(null);
     };
 
+    // Position and selection changes requested by linked windows or external tools
+    const showGenomicRange = ([start, end]: [number, number]) => {
+        if (sequenceLength <= 0) return;
+        const range: [number, number] = [Math.max(0, Math.min(start, sequenceLength)), Math.max(0, Math.min(end, sequenceLength))];
+        setFilterMode('genomic');
+        setIsFilterActive(true);
+        setGenomicRange(range);
+    };
+
+    const selectNodeById = (nodeId: number | null) => {
+        const node = nodeId === null ? undefined : data?.nodes.find(n => n.id === nodeId);
+        if (node) {
+            setSelectedNode(node);
+            setViewMode(mode => mode === 'full' ? 'subgraph' : mode);
+        } else {
+            setSelectedNode(null);
+            setViewMode('full');
+        }
+    };
+
+    const linkedViews = useLinkedViews({ position: showGenomicRange, selection: selectNodeById });
+    const { publish: publishLinkedView } = linkedViews;
+    const { report: reportToBridge } = useEventBridge(filename, { navigate: showGenomicRange, select: selectNodeById });
+
+    // Genome browser hand-off: pasted regions navigate here, the viewed interval opens there
+    const [regionInput, setRegionInput] = useState('');
+    const [regionError, setRegionError] = useState<string | null>(null);
+    const [browserContig, setBrowserContig] = useState<string | undefined>(undefined);
+
+    const handleGoToRegion = async () => {
+        if (!regionInput.trim()) return;
+        try {
+            const result = (await api.parseGenomeRegion({ filename, region: regionInput, contig: browserContig })).data as { contig: string | null; start: number; end: number };
+            if (result.contig) setBrowserContig(result.contig);
+            setRegionError(null);
+            showGenomicRange([result.start, result.end]);
+        } catch (err) {
+            setRegionError(err instanceof Error ? err.message : 'Invalid region');
+        }
+    };
+
+    const handleOpenInBrowser = async (browser: 'ucsc' | 'igv_web') => {
+        const [start, end] = isFilterActive && filterMode === 'genomic' ? genomicRange : [0, sequenceLength];
+        try {
+            const result = (await api.getGenomeBrowserLinks({ filename, start, end, contig: browserContig })).data as { links: Record<string, string> };
+            setRegionError(null);
+            window.open(result.links[browser], '_blank', 'noopener');
+        } catch (err) {
+            setRegionError(err instanceof Error ? err.message : 'Could not build the browser link');
+        }
+    };
+
+    useEffect(() => {
+        if (isFilterActive && filterMode === 'genomic' && sequenceLength > 0) {
+            publishLinkedView('position', debouncedGenomicRange);
+            reportToBridge({ type: 'position_changed', start: debouncedGenomicRange[0], end: debouncedGenomicRange[1] });
+        }
+    }, [debouncedGenomicRange, isFilterActive, filterMode, sequenceLength, publishLinkedView, reportToBridge]);
+
+    // Collaborative viewing: presenters share position, selection and view mode; followers mirror them
+    const [collabCode, setCollabCode] = useState('');
+    const followPresenter = (state: CollabState) => {
+        const range = state.genomic_range as [number, number] | null | undefined;
+        if (range) {
+            showGenomicRange(range);
+        } else {
+            setIsFilterActive(false);
+        }
+        selectNodeById(typeof state.selected_node === 'number' ? state.selected_node : null);
+        if (typeof state.view_mode === 'string') setViewMode(state.view_mode as ViewMode);
+    };
+    const collaboration = useCollaboration(filename, {
+        filename,
+        genomic_range: isFilterActive && filterMode === 'genomic' ? debouncedGenomicRange : null,
+        selected_node: selectedNode?.id ?? null,
+        view_mode: viewMode,
+    }, followPresenter);
+
+    // View links restore the window, filters, selection and view mode
+    const applyLinkedView = (state: ViewLinkState) => {
+        if (state.window) {
+            showGenomicRange(state.window);
+        } else if (state.tree_range) {
+            setFilterMode('tree');
+            setIsFilterActive(true);
+            setTreeRange(state.tree_range);
+        }
+        const linkedOrder = state.filters?.sample_order;
+        if (typeof linkedOrder === 'string') setSampleOrder(linkedOrder as SampleOrderType);
+        selectNodeById(state.selected_node ?? null);
+        if (state.view_mode) setViewMode(state.view_mode as ViewMode);
+    };
+    const viewLink = useViewLink(filename, {
+        window: isFilterActive && filterMode === 'genomic' ? debouncedGenomicRange : null,
+        tree_range: isFilterActive && filterMode === 'tree' ? debouncedTreeRange : null,
+        filter_mode: filterMode,
+        filters: { sample_order: sampleOrder },
+        view_mode: viewMode,
+        selected_node: selectedNode?.id ?? null,
+    }, applyLinkedView, Boolean(data) && sequenceLength > 0);
+    const [viewLinkCopied, setViewLinkCopied] = useState(false);
+
+    const handleCopyViewLink = async () => {
+        if (await viewLink.copyLink()) {
+            setViewLinkCopied(true);
+            setTimeout(() => setViewLinkCopied(false), 2000);
+        }
+    };
+
+    useEffect(() => {
+        publishLinkedView('selection', selectedNode?.id ?? null);
+        reportToBridge({ type: 'selection_changed', node_id: selectedNode?.id ?? null });
+    }, [selectedNode, publishLinkedView, reportToBridge]);
+
     // Genomic range control handlers
     const handleGenomicRangeChange = useCallback((newRange: [number, number]) => {
         // Only update if the range actually changed to avoid unnecessary rerenders
@@ -543,6 +683,115 @@ export const ForceDirectedGraphContainer = forwardRef<SVGSVGElement, ForceDirect
                                                 Filter Genomic Range
                                             </label>
                                         )}
+                                        {linkedViews.available && (['position', 'selection'] as const).map(aspect => (
+                                            <label
+                                                key={aspect}
+                                                className="flex items-center gap-2 text-sm cursor-pointer"
+                                                style={{ color: colors.headerText }}
+                                                title="Follow this in other ARGscape windows with the same option on"
+                                            >
+                                                <input
+                                                    type="checkbox"
+                                                    checked={linkedViews.links[aspect]}
+                                                    onChange={(e) => linkedViews.setLinked(aspect, e.target.checked)}
+                                                    className="w-4 h-4 rounded focus:ring-2"
+                                                    style={{
+                                                        accentColor: colors.accentPrimary
+                                                    }}
+                                                />
+                                                {aspect === 'position' ? 'Link Position' : 'Link Selection'}
+                                            </label>
+                                        ))}
+                                        {sequenceLength > 0 && (
+                                            <div className="flex items-center gap-2 text-sm" style={{ color: colors.headerText }}>
+                                                <input
+                                                    type="text"
+                                                    value={regionInput}
+                                                    onChange={(e) => setRegionInput(e.target.value)}
+                                                    onKeyDown={(e) => { if (e.key === 'Enter') handleGoToRegion(); }}
+                                                    placeholder="chr2:136,545,410-136,617,158"
+                                                    aria-label="Go to region"
+                                                    className="px-2 py-1 rounded border text-sm w-56"
+                                                    style={{
+                                                        backgroundColor: colors.containerBackground,
+                                                        color: colors.text,
+                                                        borderColor: `${colors.accentPrimary}33`
+                                                    }}
+                                                />
+                                                <button
+                                                    onClick={() => handleOpenInBrowser('ucsc')}
+                                                    className="px-2 py-1 rounded border text-sm"
+                                                    style={{ borderColor: `${colors.accentPrimary}33`, color: colors.headerText }}
+                                                    title="Open the viewed interval in the UCSC Genome Browser"
+                                                >
+                                                    UCSC
+                                                </button>
+                                                <button
+                                                    onClick={() => handleOpenInBrowser('igv_web')}
+                                                    className="px-2 py-1 rounded border text-sm"
+                                                    style={{ borderColor: `${colors.accentPrimary}33`, color: colors.headerText }}
+                                                    title="Open the viewed interval in igv.js"
+                                                >
+                                                    IGV
+                                                </button>
+                                                {regionError && <span className="text-xs text-red-500">{regionError}</span>}
+                                            </div>
+                                        )}
+                                        <div className="flex items-center gap-2 text-sm" style={{ color: colors.headerText }}>
+                                            <button
+                                                onClick={handleCopyViewLink}
+                                                className="px-2 py-1 rounded border text-sm"
+                                                style={{ borderColor: `${colors.accentPrimary}33`, color: colors.headerText }}
+                                                title="Copy a link that restores this view of this file"
+                                            >
+                                                {viewLinkCopied ? 'Link Copied' : 'Copy View Link'}
+                                            </button>
+                                            {viewLink.error && <span className="text-xs text-red-500">{viewLink.error}</span>}
+                                        </div>
+                                        <div className="flex items-center gap-2 text-sm" style={{ color: colors.headerText }}>
+                                            {collaboration.roomId ? (
+                                                <>
+                                                    <span title={collaboration.isPresenter ? 'Followers join with this code' : 'Following the presenter of this room'}>
+                                                        {collaboration.isPresenter
+                                                            ? `Presenting ${collaboration.roomId} · ${collaboration.followers} following`
+                                                            : `Following ${collaboration.roomId}${collaboration.presenterConnected ? '' : ' (presenter away)'}`}
+                                                    </span>
+                                                    <button
+                                                        onClick={collaboration.leave}
+                                                        className="px-2 py-1 rounded border text-sm"
+                                                        style={{ borderColor: `${colors.accentPrimary}33`, color: colors.headerText }}
+                                                    >
+                                                        {collaboration.isPresenter ? 'End' : 'Leave'}
+                                                    </button>
+                                                </>
+                                            ) : (
+                                                <>
+                                                    <button
+                                                        onClick={collaboration.host}
+                                                        className="px-2 py-1 rounded border text-sm"
+                                                        style={{ borderColor: `${colors.accentPrimary}33`, color: colors.headerText }}
+                                                        title="Let others on the network follow this view (experimental)"
+                                                    >
+                                                        Present
+                                                    </button>
+                                                    <input
+                                                        type="text"
+                                                        value={collabCode}
+                                                        onChange={(e) => setCollabCode(e.target.value)}
+                                                        onKeyDown={(e) => { if (e.key === 'Enter' && collabCode.trim()) collaboration.join(collabCode); }}
+                                                        placeholder="Room code"
+                                                        aria-label="Follow a presenter"
+                                                        className="px-2 py-1 rounded border text-sm w-24"
+                                                        style={{
+                                                            backgroundColor: colors.containerBackground,
+                                                            color: colors.text,
+                                                            borderColor: `${colors.accentPrimary}33`
+                                                        }}
+                                                    />
+                                                </>
+                                            )}
+                                            {collaboration.error && <span className="text-xs text-red-500">{collaboration.error}</span>}
+                                        </div>
                                     </div>
                                 )}
                                 
@@ -761,7 +1010,21 @@ export const ForceDirectedGraphContainer = forwardRef<SVGSVGElement, ForceDirect
             )}
 
             <div className="flex-1 overflow-hidden">
-                <div className="w-full h-full relative">
+                <div
+                    className="w-full h-full relative"
+                    onWheel={frameBudget.markActivity}
+                    onPointerMove={(event) => { if (event.buttons) frameBudget.markActivity(); }}
+                >
+                    {frameBudget.lod && frameBudget.lod.level > 0 && (
+                        <button
+                            onClick={frameBudget.reset}
+                            className="absolute bottom-2 left-2 z-10 px-2 py-1 rounded text-xs border"
+                            style={{ backgroundColor: colors.containerBackground, color: colors.text, borderColor: colors.border }}
+                            title="Streamed and tiled views use less detail to hold the target frame rate. Click to restore full detail."
+                        >
+                            Detail reduced to {Math.round(frameBudget.lod.detail_fraction * 100)}% for {frameBudget.lod.target_fps} fps
+                        </button>
+                    )}
                     <ForceDirectedGraph 
                         ref={ref}
                         data={getFilteredData()}
@@ -773,6 +1036,7 @@ export const ForceDirectedGraphContainer = forwardRef<SVGSVGElement, ForceDirect
                         sampleOrder={sampleOrder}
                         edgeThickness={edgeThickness}
                         temporalSpacingMode={visualSettings.temporalSpacingMode}
+                        filename={filename}
                     />
                     
                     <ForceDirectedGraphControlPanel
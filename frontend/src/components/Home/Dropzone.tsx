@@ -1,6 +1,6 @@
 import { useCallback, useState } from 'react';
 import { useDropzone } from 'react-dropzone';
-import { api } from '../../lib/api';
+import { api, TreeSequenceLoadError } from '../../lib/api';
 import { log } from '../../lib/logger';
 import { FILE_TYPES } from '../../config/constants';
 
@@ -90,13 +90,33 @@ export default function Dropzone({ onUploadComplete, setLoading }: DropzoneProps
           error: err instanceof Error ? err : new Error(String(err)),
           data: { filename: file.name }
         });
-        alert(`Upload failed: ${err instanceof Error ? err.message : 'Unknown error'}`);
+        const loadError = (err as { loadError?: TreeSequenceLoadError }).loadError;
+        if (loadError?.upgradable) {
+          await offerUpgrade(file.name, loadError);
+        } else {
+          alert(`Upload failed: ${err instanceof Error ? err.message : 'Unknown error'}`);
+        }
       } finally {
         setLoading(false);
       }
     }
   };
 
+  const offerUpgrade = async (filename: string, loadError: TreeSequenceLoadError) => {
+    if (!window.confirm(`${loadError.message}.\n\nUpgrade it to the current format? The original file is not changed.`)) {
+      return;
+    }
+    try {
+      const result = await api.upgradeTreeSequence(filename);
+      const upgrade = (result.data as { upgrade: { from_format: string; changes: string[] } }).upgrade;
+      alert(`Upgraded from ${upgrade.from_format}:\n\n${upgrade.changes.map((change) => `• ${change}`).join('\n')}`);
+      onUploadComplete?.(result.data);
+    } catch (err) {
+      const details = (err as { details?: unknown }).details;
+      alert(`Upgrade failed: ${typeof details === 'string' ? details : 'Unknown error'}`);
+    }
+  };
+
   const handleAddLocations = () => {
     setMode('add-locations');
   };
@@ -1010,10 +1010,19 @@ export default function ResultPage() {
           ].filter(Boolean).join(', ')}`
         : '\nNo preprocessing applied';
 
+      // Before/after summary of node times; the dating itself succeeded either way
+      const comparison = await api.compareRedatedTimes(data.filename, resultData.new_filename)
+        .then((response) => response.data as any)
+        .catch(() => null);
+      const comparisonInfo = comparison?.comparable && comparison.median_log10_ratio !== null
+        ? `\nNode times changed by a median factor of ${Math.pow(10, comparison.median_log10_ratio).toFixed(2)}` +
+          `${comparison.correlation !== null ? ` (correlation with previous times ${comparison.correlation.toFixed(2)})` : ''}.`
+        : '';
+
       setAlertModal({
         isOpen: true,
         title: 'Success!',
-        message: `tsdate inference completed successfully!${preprocessingInfo}\nInferred times for ${resultData.num_inferred_times} nodes.\nNew file: ${resultData.new_filename}`,
+        message: `tsdate inference completed successfully!${preprocessingInfo}\nInferred times for ${resultData.num_inferred_times} nodes.${comparisonInfo}\nNew file: ${resultData.new_filename}`,
         type: 'success'
       });
 
@@ -1351,6 +1360,7 @@ export default function ResultPage() {
             title={alertModal.title}
             message={alertModal.message}
             type={alertModal.type}
+            reportContext={{ operation: alertModal.title, filename: data?.filename }}
             onClose={() => setAlertModal({ ...alertModal, isOpen: false })}
           />
         </div>
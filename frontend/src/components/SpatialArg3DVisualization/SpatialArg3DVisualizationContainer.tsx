@@ -12,6 +12,7 @@ import { api } from '../../lib/api';
 import { useColorTheme } from '../../context/ColorThemeContext';
 import { useTreeSequence } from '../../context/TreeSequenceContext';
 import { TemporalSpacingMode } from './SpatialArg3DVisualization.types';
+import { useLayout3D } from '../../hooks/useLayout3D';
 
 type ViewMode = 'full' | 'subgraph' | 'ancestors';
 type FilterMode = 'genomic' | 'tree';
@@ -32,7 +33,8 @@ const CONTAINER_CONSTANTS = {
   TIME_PRECISION: 3,
   TEMPORAL_STEP_DIVISOR: 1000,
   TEMPORAL_SLIDER_HEIGHT: 350,
-  UNIT_GRID_SIZE: 10
+  UNIT_GRID_SIZE: 10,
+  CAMERA_FRAME_MS: 33
 };
 
 const DEFAULT_VISUAL_SETTINGS = {
@@ -188,6 +190,15 @@ const filterDataByViewMode = (
   }
 };
 
+// Replace node x/y with the time-as-z layout; nodes it did not place keep their own location
+const applyLayout3D = (data: GraphData, positions: Record<number, number[]>): GraphData => ({
+  ...data,
+  nodes: data.nodes.map(node => {
+    const position = positions[node.id];
+    return position ? { ...node, location: { x: position[0], y: position[1] } } : node;
+  })
+});
+
 const applyTemporalFiltering = (data: GraphData, temporalState: any): GraphData => {
   if (!temporalState.isActive || temporalState.mode !== 'hide') return data;
   
@@ -389,6 +400,14 @@ const SpatialArg3DVisualizationContainer: React.FC<SpatialArg3DVisualizationCont
   });
   
   const [visualSettings, setVisualSettings] = useState(DEFAULT_VISUAL_SETTINGS);
+
+  const layout3D = useLayout3D(filename);
+  const [useTimeZLayout, setUseTimeZLayout] = useState(false);
+  const flyTimerRef = useRef<number | null>(null);
+
+  useEffect(() => () => {
+    if (flyTimerRef.current !== null) window.clearInterval(flyTimerRef.current);
+  }, []);
   
   const [viewState, setViewState] = useState({
     target: [0, 0, 0] as [number, number, number], // Temporary, will be updated by auto-center
@@ -545,7 +564,51 @@ const SpatialArg3DVisualizationContainer: React.FC<SpatialArg3DVisualizationCont
     if (!data) return data;
 
     const temporalFilteredData = applyTemporalFiltering(data, temporalState);
-    return filterDataByViewMode(temporalFilteredData, viewMode, selectedNode);
+    const viewData = filterDataByViewMode(temporalFilteredData, viewMode, selectedNode);
+    return useTimeZLayout && layout3D.positions && viewData ? applyLayout3D(viewData, layout3D.positions) : viewData;
+  };
+
+  const handleTimeZLayoutToggle = () => {
+    if (!useTimeZLayout && !layout3D.positions) {
+      layout3D.start();
+    }
+    setUseTimeZLayout(prev => !prev);
+  };
+
+  const handleFlyCamera = () => {
+    if (flyTimerRef.current !== null) {
+      window.clearInterval(flyTimerRef.current);
+      flyTimerRef.current = null;
+      return;
+    }
+    const keyframes = layout3D.camera;
+    if (!keyframes || keyframes.length === 0) return;
+    const started = performance.now();
+    const duration = keyframes[keyframes.length - 1].time * 1000;
+    flyTimerRef.current = window.setInterval(() => {
+      const elapsed = performance.now() - started;
+      const index = Math.min(keyframes.length - 1, keyframes.findIndex(k => k.time * 1000 >= elapsed));
+      const keyframe = keyframes[index < 0 ? keyframes.length - 1 : index];
+      setViewState(prev => ({ ...prev, rotationOrbit: keyframe.rotation_orbit, rotationX: keyframe.rotation_x }));
+      if (elapsed >= duration && flyTimerRef.current !== null) {
+        window.clearInterval(flyTimerRef.current);
+        flyTimerRef.current = null;
+      }
+    }, CONTAINER_CONSTANTS.CAMERA_FRAME_MS);
+  };
+
+  const handleExportMesh = async (format: 'gltf' | 'obj') => {
+    try {
+      const blob = await api.downloadLayout3D(filename, { format });
+      const url = URL.createObjectURL(blob);
+      const link = document.createElement('a');
+      link.href = url;
+      link.download = `${filename.replace(/\.[^.]+$/, '')}_3d.${format}`;
+      link.click();
+      URL.revokeObjectURL(url);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : `${format} export failed`);
+    }
   };
 
   const handleNodeClick = (node: GraphNode) => {
@@ -724,6 +787,54 @@ const SpatialArg3DVisualizationContainer: React.FC<SpatialArg3DVisualizationCont
                     />
                     Filter Temporal Range
                   </label>
+
+                  <label className="flex items-center gap-2 text-sm cursor-pointer" style={{ color: colors.headerText }}>
+                    <input
+                      type="checkbox"
+                      checked={useTimeZLayout}
+                      onChange={handleTimeZLayoutToggle}
+                      className="w-4 h-4 rounded focus:ring-2"
+                      style={{
+                        accentColor: colors.accentPrimary
+                      }}
+                    />
+                    Time-z Layout
+                    {layout3D.running && layout3D.progress && (
+                      <span className="text-xs" style={{ color: `${colors.headerText}B3` }}>
+                        {Math.round((layout3D.progress.iteration / layout3D.progress.numIterations) * 100)}%
+                      </span>
+                    )}
+                  </label>
+
+                  {useTimeZLayout && layout3D.camera && (
+                    <>
+                      <button
+                        onClick={handleFlyCamera}
+                        className="font-medium px-3 py-1 rounded text-sm transition-colors border"
+                        style={{
+                          backgroundColor: colors.containerBackground,
+                          color: colors.text,
+                          borderColor: `${colors.accentPrimary}33`
+                        }}
+                      >
+                        Fly Camera
+                      </button>
+                      {(['gltf', 'obj'] as const).map(format => (
+                        <button
+                          key={format}
+                          onClick={() => handleExportMesh(format)}
+                          className="font-medium px-3 py-1 rounded text-sm transition-colors border"
+                          style={{
+                            backgroundColor: colors.containerBackground,
+                            color: colors.text,
+                            borderColor: `${colors.accentPrimary}33`
+                          }}
+                        >
+                          Export {format === 'gltf' ? 'glTF' : 'OBJ'}
+                        </button>
+                      ))}
+                    </>
+                  )}
                 </div>
               )}
               
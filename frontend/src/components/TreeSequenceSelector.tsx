@@ -539,6 +539,7 @@ export default function TreeSequenceSelector({ onSelect, className = '' }: TreeS
         title={alertModal.title}
         message={alertModal.message}
         type={alertModal.type}
+        reportContext={{ operation: alertModal.title }}
         onClose={() => setAlertModal({ ...alertModal, isOpen: false })}
       />
     </div>
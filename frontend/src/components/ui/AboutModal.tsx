@@ -0,0 +1,90 @@
+import { useEffect, useState } from 'react';
+import { api, SystemCapabilities } from '../../lib/api';
+
+interface AboutModalProps {
+  isOpen: boolean;
+  onClose: () => void;
+}
+
+function formatMb(mb: number | null | undefined) {
+  if (mb === null || mb === undefined) return 'unknown';
+  return mb >= 1024 ? `${(mb / 1024).toFixed(1)} GB` : `${mb} MB`;
+}
+
+/**
+ * About/diagnostics panel: what the backend found on this machine (GPU
+ * adapter, CPU, memory, free disk) and the defaults it picked from that.
+ */
+export default function AboutModal({ isOpen, onClose }: AboutModalProps) {
+  const [capabilities, setCapabilities] = useState<SystemCapabilities | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!isOpen) return;
+    const handleEsc = (event: KeyboardEvent) => {
+      if (event.key === 'Escape') onClose();
+    };
+    document.addEventListener('keydown', handleEsc);
+    api.getSystemCapabilities()
+      .then((response) => setCapabilities(response.data as SystemCapabilities))
+      .catch((e) => setError(e instanceof Error ? e.message : String(e)));
+    return () => document.removeEventListener('keydown', handleEsc);
+  }, [isOpen, onClose]);
+
+  if (!isOpen) return null;
+
+  const rows: [string, string][] = capabilities ? [
+    ['Platform', `${capabilities.platform} · Python ${capabilities.python}`],
+    ['CPU', `${capabilities.cpu.model ?? capabilities.cpu.architecture} · ${capabilities.cpu.available_cores} cores available`
+      + (capabilities.cpu.physical_cores ? ` (${capabilities.cpu.physical_cores} physical)` : '')],
+    ['CPU features', capabilities.cpu.features ? (capabilities.cpu.features.join(', ') || 'none of note') : 'not reported by this OS'],
+    ['Memory', `${formatMb(capabilities.memory.available_mb)} free of ${formatMb(capabilities.memory.total_mb)}`],
+    ...capabilities.disks.map((disk): [string, string] => [
+      `Disk (${disk.location})`,
+      disk.error ? `unavailable: ${disk.error}` : `${formatMb(disk.free_mb)} free of ${formatMb(disk.total_mb)}`
+    ]),
+    ['GPU', capabilities.gpu.available
+      ? [capabilities.gpu.device, capabilities.gpu.vendor, capabilities.gpu.backend].filter(Boolean).join(' · ')
+      : `not used: ${capabilities.gpu.reason ?? 'unavailable'}`],
+    ['Worker threads', `${capabilities.recommended.num_threads} by default`],
+  ] : [];
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center p-4">
+      <div className="absolute inset-0 bg-black/50 backdrop-blur-sm" onClick={onClose} />
+      <div className="relative bg-sp-very-dark-blue border border-sp-pale-green/20 rounded-2xl shadow-2xl max-w-2xl w-full mx-4">
+        <div className="p-6">
+          <h3 className="text-lg font-semibold text-sp-white mb-4">About this machine</h3>
+          {error && <p className="text-red-400 text-sm mb-4">Could not read system information: {error}</p>}
+          {!capabilities && !error && <p className="text-sp-white/70 text-sm mb-4">Checking hardware…</p>}
+          {capabilities && (
+            <dl className="grid grid-cols-[max-content_1fr] gap-x-4 gap-y-2 text-sm mb-6">
+              {rows.map(([label, value]) => (
+                <div key={label} className="contents">
+                  <dt className="text-sp-white/60">{label}</dt>
+                  <dd className="text-sp-white break-words">{value}</dd>
+                </div>
+              ))}
+            </dl>
+          )}
+          <div className="flex justify-end gap-3">
+            {capabilities && (
+              <button
+                onClick={() => navigator.clipboard.writeText(JSON.stringify(capabilities, null, 2))}
+                className="py-3 px-6 rounded-xl text-sp-white/70 hover:text-sp-white transition-colors"
+              >
+                Copy details
+              </button>
+            )}
+            <button
+              onClick={onClose}
+              className="font-bold py-3 px-6 rounded-xl transition-all duration-200 bg-sp-pale-green hover:bg-sp-very-pale-green text-sp-very-dark-blue"
+            >
+              Close
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
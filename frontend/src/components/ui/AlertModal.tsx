@@ -1,4 +1,5 @@
-import { useEffect } from 'react';
+import { useEffect, useState } from 'react';
+import ErrorReportModal, { ErrorReportContext } from './ErrorReportModal';
 
 interface AlertModalProps {
   isOpen: boolean;
@@ -7,6 +8,7 @@ interface AlertModalProps {
   buttonText?: string;
   onClose: () => void;
   type?: 'success' | 'error' | 'info';
+  reportContext?: ErrorReportContext;  // For errors: offers a "Report" button with a diagnostic bundle
 }
 
 export default function AlertModal({ 
@@ -15,8 +17,11 @@ export default function AlertModal({
   message, 
   buttonText = 'OK',
   onClose,
-  type = 'info'
+  type = 'info',
+  reportContext
 }: AlertModalProps) {
+  const [showReport, setShowReport] = useState(false);
+
   // Handle ESC key
   useEffect(() => {
     const handleEsc = (event: KeyboardEvent) => {
@@ -100,7 +105,15 @@ export default function AlertModal({
           </div>
           
           {/* Action */}
-          <div className="flex justify-end">
+          <div className="flex justify-end gap-3">
+            {type === 'error' && reportContext && (
+              <button
+                onClick={() => setShowReport(true)}
+                className="py-3 px-6 rounded-xl text-sp-white/70 hover:text-sp-white transition-colors"
+              >
+                Report
+              </button>
+            )}
             <button
               onClick={onClose}
               className={`font-bold py-3 px-6 rounded-xl transition-all duration-200 ${buttonColor}`}
@@ -110,6 +123,13 @@ export default function AlertModal({
           </div>
         </div>
       </div>
+
+      <ErrorReportModal
+        isOpen={showReport}
+        error={message}
+        context={reportContext}
+        onClose={() => setShowReport(false)}
+      />
     </div>
   );
 } 
\ No newline at end of file
@@ -0,0 +1,117 @@
+import { useEffect, useState } from 'react';
+import { api } from '../../lib/api';
+
+export interface ErrorReportContext {
+  operation?: string;
+  filename?: string;
+}
+
+interface ErrorReportModalProps {
+  isOpen: boolean;
+  error: string;
+  context?: ErrorReportContext;
+  onClose: () => void;
+}
+
+/**
+ * Preview of the diagnostic bundle for a failed operation, which the user can
+ * save locally or send to the configured report endpoint. The bundle holds
+ * sanitized logs, system information and table sizes, never genetic data.
+ */
+export default function ErrorReportModal({ isOpen, error, context, onClose }: ErrorReportModalProps) {
+  const [preview, setPreview] = useState<string>('');
+  const [comment, setComment] = useState('');
+  const [canSubmit, setCanSubmit] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  const request = {
+    error,
+    operation: context?.operation,
+    filename: context?.filename,
+    user_comment: comment || undefined,
+  };
+
+  useEffect(() => {
+    if (!isOpen) return;
+    setStatus(null);
+    api.previewDiagnostics(request)
+      .then((response) => setPreview(JSON.stringify(response.data, null, 2)))
+      .catch((e) => setPreview(`Could not assemble the report: ${e instanceof Error ? e.message : String(e)}`));
+    api.getSettings()
+      .then((response) => setCanSubmit(Boolean((response.data as { diagnostics_endpoint?: string | null }).diagnostics_endpoint)))
+      .catch(() => setCanSubmit(false));
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [isOpen, error]);
+
+  if (!isOpen) return null;
+
+  const run = async (action: 'save' | 'submit') => {
+    setBusy(true);
+    try {
+      if (action === 'save') {
+        const response = await api.saveDiagnostics(request);
+        setStatus(`Saved to ${(response.data as { path: string }).path}`);
+      } else {
+        await api.submitDiagnostics(request);
+        setStatus('Report sent. Thank you!');
+      }
+    } catch (e) {
+      setStatus(`Failed: ${e instanceof Error ? e.message : String(e)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center p-4">
+      <div className="absolute inset-0 bg-black/50 backdrop-blur-sm" onClick={onClose} />
+      <div className="relative bg-sp-very-dark-blue border border-sp-pale-green/20 rounded-2xl shadow-2xl max-w-2xl w-full mx-4">
+        <div className="p-6">
+          <h3 className="text-lg font-semibold text-sp-white mb-2">Report this problem</h3>
+          <p className="text-sp-white/70 mb-4 text-sm">
+            This is everything the report contains. File names, paths and addresses are redacted,
+            and no genetic data is included.
+          </p>
+
+          <textarea
+            value={comment}
+            onChange={(e) => setComment(e.target.value)}
+            placeholder="What were you doing when this happened? (optional)"
+            className="w-full mb-3 p-3 rounded-lg bg-sp-dark-blue text-sp-white text-sm border border-sp-pale-green/10"
+            rows={2}
+          />
+          <pre className="max-h-72 overflow-auto mb-4 p-3 rounded-lg bg-black/30 text-xs text-sp-white/80 whitespace-pre-wrap break-words">
+            {preview || 'Assembling report…'}
+          </pre>
+
+          {status && <div className="mb-4 text-sm text-sp-pale-green break-words">{status}</div>}
+
+          <div className="flex justify-end gap-3">
+            <button
+              onClick={onClose}
+              className="py-2 px-4 rounded-xl text-sp-white/70 hover:text-sp-white transition-colors"
+            >
+              Close
+            </button>
+            <button
+              onClick={() => run('save')}
+              disabled={busy}
+              className="py-2 px-4 rounded-xl bg-sp-dark-blue text-sp-white border border-sp-pale-green/20 hover:border-sp-pale-green/50 transition-colors disabled:opacity-50"
+            >
+              Save locally
+            </button>
+            <button
+              onClick={() => run('submit')}
+              disabled={busy || !canSubmit}
+              title={canSubmit ? undefined : 'No report endpoint is configured'}
+              className="py-2 px-4 rounded-xl font-bold bg-sp-pale-green hover:bg-sp-very-pale-green text-sp-very-dark-blue transition-colors disabled:opacity-50"
+            >
+              Send report
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,102 @@
+import { useEffect, useState } from 'react';
+import { api } from '../../lib/api';
+
+const POLL_INTERVAL_MS = 2000;
+
+interface Aggregate {
+  count: number;
+  total_ms: number;
+  mean_ms: number;
+  max_ms: number;
+  last_ms: number;
+}
+
+interface Operation extends Aggregate {
+  category: string;
+  name: string;
+}
+
+interface Metrics {
+  categories: Record<string, Aggregate>;
+  operations: Operation[];
+}
+
+/**
+ * Overlay showing where backend time goes for this session, per category and
+ * for the slowest operations. Toggled with Ctrl+Shift+P.
+ */
+export default function PerformanceHud() {
+  const [visible, setVisible] = useState(false);
+  const [metrics, setMetrics] = useState<Metrics | null>(null);
+
+  useEffect(() => {
+    const handleKey = (event: KeyboardEvent) => {
+      if (event.ctrlKey && event.shiftKey && event.key.toLowerCase() === 'p') {
+        event.preventDefault();
+        setVisible((v) => !v);
+      }
+    };
+    document.addEventListener('keydown', handleKey);
+    return () => document.removeEventListener('keydown', handleKey);
+  }, []);
+
+  useEffect(() => {
+    if (!visible) return;
+    let cancelled = false;
+    const poll = () => {
+      api.getPerformanceMetrics()
+        .then((response) => {
+          if (!cancelled) setMetrics((response.data as { session: Metrics }).session);
+        })
+        .catch(() => undefined);
+    };
+    poll();
+    const timer = setInterval(poll, POLL_INTERVAL_MS);
+    return () => {
+      cancelled = true;
+      clearInterval(timer);
+    };
+  }, [visible]);
+
+  if (!visible) return null;
+
+  return (
+    <div className="fixed top-4 right-4 z-50 w-80 p-4 rounded-xl bg-sp-very-dark-blue/95 border border-sp-pale-green/20 text-sp-white text-xs shadow-2xl">
+      <div className="flex justify-between items-center mb-2">
+        <span className="font-semibold text-sm">Performance</span>
+        <button
+          onClick={() => api.resetPerformanceMetrics().then(() => setMetrics(null)).catch(() => undefined)}
+          className="text-sp-white/60 hover:text-sp-white"
+        >
+          Reset
+        </button>
+      </div>
+      {!metrics ? (
+        <div className="text-sp-white/60">No timings yet</div>
+      ) : (
+        <>
+          <table className="w-full mb-3">
+            <tbody>
+              {Object.entries(metrics.categories).map(([category, aggregate]) => (
+                <tr key={category}>
+                  <td className="py-0.5 capitalize">{category}</td>
+                  <td className="py-0.5 text-right text-sp-white/70">{aggregate.count}×</td>
+                  <td className="py-0.5 text-right">{aggregate.total_ms.toFixed(0)} ms</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+          <div className="text-sp-white/60 mb-1">Slowest operations</div>
+          <ul className="space-y-0.5">
+            {metrics.operations.slice(0, 6).map((op) => (
+              <li key={`${op.category}:${op.name}`} className="flex justify-between gap-2">
+                <span className="truncate" title={op.name}>{op.name}</span>
+                <span className="text-sp-white/70 whitespace-nowrap">{op.mean_ms.toFixed(0)} ms avg</span>
+              </li>
+            ))}
+          </ul>
+        </>
+      )}
+    </div>
+  );
+}
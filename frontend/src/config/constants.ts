@@ -111,9 +111,10 @@ export const DATA_FORMAT = {
 // File Types
 export const FILE_TYPES = {
   ACCEPTED_FORMATS: {
-    'application/octet-stream': ['.trees', '.tsz'],
+    'application/octet-stream': ['.trees', '.tsz', '.anc', '.smc', '.hdf5'],
     'application/x-trees': ['.trees'],
     'application/x-tsz': ['.tsz'],
+    'application/gzip': ['.anc.gz', '.smc.gz'],
   },
   CSV_FORMATS: {
     'text/csv': ['.csv'],
@@ -122,6 +123,9 @@ export const FILE_TYPES = {
   EXTENSIONS: {
     TREES: '.trees',
     TSZ: '.tsz',
+    RELATE_ANC: '.anc',
+    RELATE_MUT: '.mut',
+    ARGWEAVER_SMC: '.smc',
     CSV: '.csv',
   }
 } as const;
@@ -1,5 +1,7 @@
-import { createContext, useContext, useState, ReactNode } from 'react';
+import { createContext, useContext, useEffect, useRef, useState, ReactNode } from 'react';
 import { SAMPLE_LIMITS } from '../config/constants';
+import { api } from '../lib/api';
+import { log } from '../lib/logger';
 
 interface TreeSequenceData {
   filename: string;
@@ -21,6 +23,16 @@ interface TreeSequenceData {
   is_simulated?: boolean;  // Optional - indicates if the tree sequence was generated through simulation
 }
 
+interface SessionUiState {
+  tree_sequence?: TreeSequenceData | null;
+  max_samples?: number;
+}
+
+interface SessionSnapshot {
+  files: string[];
+  ui_state: SessionUiState | null;
+}
+
 interface TreeSequenceContextType {
   treeSequence: TreeSequenceData | null;
   setTreeSequence: (data: TreeSequenceData | null) => void;
@@ -33,6 +45,36 @@ const TreeSequenceContext = createContext<TreeSequenceContextType | undefined>(u
 export function TreeSequenceProvider({ children }: { children: ReactNode }) {
   const [treeSequence, setTreeSequence] = useState<TreeSequenceData | null>(null);
   const [maxSamples, setMaxSamples] = useState<number>(SAMPLE_LIMITS.DEFAULT_MAX_SAMPLES);
+  const hydrated = useRef(false);
+
+  // Rehydrate from the backend session, which outlives page reloads and webview crashes
+  useEffect(() => {
+    api.getSessionSnapshot()
+      .then((response) => {
+        const snapshot = response.data as SessionSnapshot;
+        const saved = snapshot.ui_state?.tree_sequence;
+        if (saved && snapshot.files.includes(saved.filename)) {
+          setTreeSequence((current) => current ?? saved);
+          if (snapshot.ui_state?.max_samples) {
+            setMaxSamples(snapshot.ui_state.max_samples);
+          }
+        }
+      })
+      .catch((error) => {
+        log.debug('Session snapshot unavailable', {
+          component: 'TreeSequenceProvider',
+          error: error instanceof Error ? error : new Error(String(error))
+        });
+      })
+      .finally(() => {
+        hydrated.current = true;
+      });
+  }, []);
+
+  useEffect(() => {
+    if (!hydrated.current) return;
+    api.saveSessionSnapshot({ tree_sequence: treeSequence, max_samples: maxSamples }).catch(() => undefined);
+  }, [treeSequence, maxSamples]);
 
   // Custom setTreeSequence that also updates maxSamples appropriately
   const setTreeSequenceWithSamples = (data: TreeSequenceData | null) => {
@@ -0,0 +1,56 @@
+import { useEffect, useState } from 'react';
+import { api } from '../lib/api';
+import { log } from '../lib/logger';
+
+const POLL_INTERVAL_MS = 500;
+
+export type ReadinessState = 'starting' | 'warming' | 'ready';
+
+export interface Readiness {
+  state: ReadinessState;
+  ready: boolean;
+  current_step: string | null;
+  elapsed_seconds: number;
+}
+
+/**
+ * Polls the backend readiness state until warm-up after launch has finished.
+ * Until the backend answers at all, the state is 'starting'.
+ */
+export function useBackendReadiness(): Readiness {
+  const [readiness, setReadiness] = useState<Readiness>({
+    state: 'starting',
+    ready: false,
+    current_step: null,
+    elapsed_seconds: 0,
+  });
+
+  useEffect(() => {
+    let cancelled = false;
+    let timer: ReturnType<typeof setTimeout> | undefined;
+
+    const poll = async () => {
+      try {
+        const response = await api.getReadiness();
+        if (cancelled) return;
+        const data = response.data as Readiness;
+        setReadiness(data);
+        if (data.ready) {
+          log.info(`Backend ready after ${data.elapsed_seconds}s`, { component: 'useBackendReadiness' });
+          return;
+        }
+      } catch {
+        // Backend not listening yet
+      }
+      if (!cancelled) timer = setTimeout(poll, POLL_INTERVAL_MS);
+    };
+
+    poll();
+    return () => {
+      cancelled = true;
+      if (timer) clearTimeout(timer);
+    };
+  }, []);
+
+  return readiness;
+}
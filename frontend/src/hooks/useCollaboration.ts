@@ -0,0 +1,200 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { useNavigate, useSearchParams } from 'react-router-dom';
+import { api, CollabMessage } from '../lib/api';
+import { log } from '../lib/logger';
+
+const STORAGE_KEY = 'argscape.collab';
+const RECONNECT_DELAY_MS = 3000;
+
+export type CollabState = Record<string, unknown>;
+
+interface CollabMembership {
+  roomId: string;
+  // Only the presenter holds the token
+  token?: string;
+}
+
+function applyPatch(state: CollabState, set: Record<string, unknown>, removed: string[]): CollabState {
+  const result: CollabState = JSON.parse(JSON.stringify(state));
+  for (const path of removed) {
+    const keys = path.split('.');
+    let target: Record<string, unknown> | undefined = result;
+    for (const key of keys.slice(0, -1)) {
+      const next: unknown = target?.[key];
+      target = next && typeof next === 'object' ? next as Record<string, unknown> : undefined;
+    }
+    if (target) delete target[keys[keys.length - 1]];
+  }
+  for (const [path, value] of Object.entries(set)) {
+    const keys = path.split('.');
+    let target = result;
+    for (const key of keys.slice(0, -1)) {
+      if (!target[key] || typeof target[key] !== 'object') target[key] = {};
+      target = target[key] as Record<string, unknown>;
+    }
+    target[keys[keys.length - 1]] = value;
+  }
+  return result;
+}
+
+function loadMembership(): CollabMembership | null {
+  try {
+    const stored = sessionStorage.getItem(STORAGE_KEY);
+    return stored ? JSON.parse(stored) : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Experimental collaborative viewing. The presenter hosts a room and this hook
+ * sends their view `state` whenever it changes; followers join with the room
+ * code (or a link with `?collab=CODE`) and get `onState` called with the
+ * presenter's state as patches arrive. A presenter switching files takes the
+ * followers along. Membership survives reloads of the tab.
+ */
+export function useCollaboration(filename: string, state: CollabState, onState: (state: CollabState) => void) {
+  const navigate = useNavigate();
+  const [searchParams] = useSearchParams();
+  const [membership, setMembership] = useState<CollabMembership | null>(() => {
+    const code = searchParams.get('collab');
+    return code ? { roomId: code.toUpperCase() } : loadMembership();
+  });
+  const [connected, setConnected] = useState(false);
+  const [followers, setFollowers] = useState(0);
+  const [presenterConnected, setPresenterConnected] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const connection = useRef<ReturnType<typeof api.connectCollab> | null>(null);
+  const remote = useRef<{ version: number; state: CollabState }>({ version: 0, state: {} });
+  const onStateRef = useRef(onState);
+  onStateRef.current = onState;
+
+  const isPresenter = Boolean(membership?.token);
+
+  useEffect(() => {
+    if (membership) sessionStorage.setItem(STORAGE_KEY, JSON.stringify(membership));
+    else sessionStorage.removeItem(STORAGE_KEY);
+  }, [membership]);
+
+  useEffect(() => {
+    if (!membership) return;
+    let cancelled = false;
+    let retry: ReturnType<typeof setTimeout> | undefined;
+
+    const followState = async (next: CollabState) => {
+      remote.current.state = next;
+      const presented = typeof next.filename === 'string' ? next.filename : null;
+      if (presented && presented !== filename) {
+        // Copies the presenter's new file into this session before opening it
+        await api.joinCollabRoom(membership.roomId);
+        if (!cancelled) navigate(`/visualize/${encodeURIComponent(presented)}?collab=${membership.roomId}`);
+        return;
+      }
+      onStateRef.current(next);
+    };
+
+    const handleMessage = (message: CollabMessage) => {
+      switch (message.type) {
+        case 'snapshot':
+          remote.current.version = message.version ?? 0;
+          if (!membership.token) followState(message.state ?? {});
+          break;
+        case 'patch':
+          if (message.version !== remote.current.version + 1) {
+            connection.current?.resync();
+            break;
+          }
+          remote.current.version = message.version;
+          followState(applyPatch(remote.current.state, message.set ?? {}, message.removed ?? []));
+          break;
+        case 'presence':
+          setFollowers(message.followers ?? 0);
+          setPresenterConnected(Boolean(message.presenter_connected));
+          break;
+        case 'closed':
+          setMembership(null);
+          break;
+        case 'error':
+          log.warn('Collaboration room rejected a message', { component: 'useCollaboration', data: message.detail });
+          break;
+      }
+    };
+
+    const connect = () => {
+      const socket = api.connectCollab(membership.roomId, {
+        onMessage: handleMessage,
+        onOpen: () => {
+          setConnected(true);
+          setError(null);
+        },
+        onClose: () => {
+          connection.current = null;
+          setConnected(false);
+          if (!cancelled) retry = setTimeout(connect, RECONNECT_DELAY_MS);
+        },
+      }, membership.token);
+      connection.current = socket;
+    };
+
+    connect();
+    return () => {
+      cancelled = true;
+      clearTimeout(retry);
+      connection.current?.close();
+      connection.current = null;
+      setConnected(false);
+    };
+  }, [membership, filename, navigate]);
+
+  // The server diffs full states, so unchanged ones cost one small ack
+  const serialized = JSON.stringify(state);
+  useEffect(() => {
+    if (isPresenter && connected) connection.current?.sendState(JSON.parse(serialized));
+  }, [isPresenter, connected, serialized]);
+
+  const host = useCallback(async () => {
+    try {
+      const response = await api.createCollabRoom(filename);
+      const room = response.data as { room_id: string; presenter_token: string };
+      setMembership({ roomId: room.room_id, token: room.presenter_token });
+      return room.room_id;
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      setError(message);
+      log.warn('Could not open a collaboration room', { component: 'useCollaboration', error: err instanceof Error ? err : new Error(message) });
+      return null;
+    }
+  }, [filename]);
+
+  const join = useCallback(async (roomId: string) => {
+    try {
+      const response = await api.joinCollabRoom(roomId.trim().toUpperCase());
+      const room = response.data as { room_id: string; filename: string | null };
+      setMembership({ roomId: room.room_id });
+      if (room.filename && room.filename !== filename) {
+        navigate(`/visualize/${encodeURIComponent(room.filename)}?collab=${room.room_id}`);
+      }
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  }, [filename, navigate]);
+
+  const leave = useCallback(async () => {
+    if (membership?.token) {
+      await api.closeCollabRoom(membership.roomId, membership.token).catch(() => undefined);
+    }
+    setMembership(null);
+  }, [membership]);
+
+  return {
+    roomId: membership?.roomId ?? null,
+    isPresenter,
+    connected,
+    followers,
+    presenterConnected,
+    error,
+    host,
+    join,
+    leave,
+  };
+}
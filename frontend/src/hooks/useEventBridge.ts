@@ -0,0 +1,94 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { useNavigate } from 'react-router-dom';
+import { api, BridgeMessage } from '../lib/api';
+import { log } from '../lib/logger';
+
+const RECONNECT_DELAY_MS = 5000;
+
+interface EventBridgeHandlers {
+  navigate?: (range: [number, number]) => void;
+  select?: (nodeId: number | null) => void;
+}
+
+/**
+ * Connects this view to the backend event bridge when the `event_bridge`
+ * setting is on, so external tools can follow its position and selection and
+ * send navigation commands. Commands naming another file open that file, and
+ * a file pushed again under the current name is reloaded.
+ * Reconnects after the backend restarts.
+ */
+export function useEventBridge(filename: string, handlers: EventBridgeHandlers) {
+  const navigate = useNavigate();
+  const [connected, setConnected] = useState(false);
+  const connection = useRef<ReturnType<typeof api.connectEventBridge> | null>(null);
+  const handlersRef = useRef(handlers);
+  handlersRef.current = handlers;
+
+  useEffect(() => {
+    let cancelled = false;
+    let retry: ReturnType<typeof setTimeout> | undefined;
+
+    const handleMessage = (message: BridgeMessage) => {
+      if (message.type === 'error') {
+        log.warn('Event bridge rejected a message', { component: 'useEventBridge', data: message.detail });
+        return;
+      }
+      if (message.type === 'open_file' || (message.type === 'navigate' && message.filename && message.filename !== filename)) {
+        window.focus();
+        if (message.filename === filename) {
+          // Pushed again under the same name, e.g. re-running a notebook cell
+          window.location.reload();
+        } else {
+          navigate(`/visualize/${encodeURIComponent(message.filename!)}`);
+        }
+        return;
+      }
+      if (message.filename && message.filename !== filename) return;
+      if (message.type === 'navigate' && message.start !== undefined && message.end !== undefined) {
+        handlersRef.current.navigate?.([message.start, message.end]);
+      } else if (message.type === 'select') {
+        handlersRef.current.select?.(message.node_id ?? null);
+      }
+    };
+
+    const connect = async () => {
+      try {
+        const state = (await api.getEventBridgeState()).data as { enabled: boolean };
+        if (cancelled || !state.enabled) return;
+      } catch (error) {
+        log.debug('Event bridge unavailable', { component: 'useEventBridge', data: error });
+        if (!cancelled) retry = setTimeout(connect, RECONNECT_DELAY_MS);
+        return;
+      }
+      const socket = api.connectEventBridge({
+        onMessage: handleMessage,
+        onOpen: () => {
+          setConnected(true);
+          socket.send({ type: 'file_opened', filename });
+        },
+        onClose: () => {
+          connection.current = null;
+          setConnected(false);
+          if (!cancelled) retry = setTimeout(connect, RECONNECT_DELAY_MS);
+        },
+      });
+      connection.current = socket;
+    };
+
+    connect();
+    return () => {
+      cancelled = true;
+      clearTimeout(retry);
+      connection.current?.close();
+      connection.current = null;
+      setConnected(false);
+    };
+  }, [filename, navigate]);
+
+  /** Report a change in this view; a no-op while the bridge is off. */
+  const report = useCallback((message: BridgeMessage) => {
+    connection.current?.send({ filename, ...message });
+  }, [filename]);
+
+  return { connected, report };
+}
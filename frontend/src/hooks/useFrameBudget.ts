@@ -0,0 +1,71 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { api, RenderLod } from '../lib/api';
+import { log } from '../lib/logger';
+
+const MEASURE_WINDOW_MS = 1000;
+// Keep measuring this long after the last pointer/wheel event
+const ACTIVITY_TIMEOUT_MS = 1500;
+
+/**
+ * Measures the frame rate while the user interacts with a view and reports it
+ * to the backend, which adapts the level of detail it streams.
+ * Idle frames are not measured: an idle view always reaches the display's
+ * refresh rate and would only hide slow interaction.
+ *
+ * Call `markActivity` from the view's pointer and wheel handlers.
+ */
+export function useFrameBudget(filename: string, enabled: boolean = true) {
+  const [lod, setLod] = useState<RenderLod | null>(null);
+  const lastActivity = useRef(0);
+  const frameRef = useRef<number | null>(null);
+
+  const measure = useCallback(() => {
+    if (frameRef.current !== null) return;
+    let frames = 0;
+    let windowStart = performance.now();
+
+    const tick = (now: number) => {
+      frames += 1;
+      if (now - windowStart >= MEASURE_WINDOW_MS) {
+        const fps = (frames * 1000) / (now - windowStart);
+        frames = 0;
+        windowStart = now;
+        api.reportFrameStats(filename, fps)
+          .then(response => {
+            if (response.data.changed) {
+              log.info('Level of detail changed', { component: 'useFrameBudget', data: response.data });
+            }
+            setLod(response.data);
+          })
+          .catch(error => log.debug('Frame stats not reported', {
+            component: 'useFrameBudget',
+            error: error instanceof Error ? error : new Error(String(error))
+          }));
+      }
+      if (now - lastActivity.current > ACTIVITY_TIMEOUT_MS) {
+        frameRef.current = null;
+        return;
+      }
+      frameRef.current = requestAnimationFrame(tick);
+    };
+    frameRef.current = requestAnimationFrame(tick);
+  }, [filename]);
+
+  const markActivity = useCallback(() => {
+    if (!enabled) return;
+    lastActivity.current = performance.now();
+    measure();
+  }, [enabled, measure]);
+
+  const reset = useCallback(async () => {
+    await api.resetRenderLod(filename);
+    setLod(null);
+  }, [filename]);
+
+  useEffect(() => () => {
+    if (frameRef.current !== null) cancelAnimationFrame(frameRef.current);
+    frameRef.current = null;
+  }, []);
+
+  return { lod, markActivity, reset };
+}
@@ -0,0 +1,74 @@
+import { useEffect, useRef } from 'react';
+import { api } from '../lib/api';
+import { log } from '../lib/logger';
+import { notifyUser } from '../lib/notifications';
+
+const POLL_INTERVAL_MS = 3000;
+
+interface InboxEvent {
+  event_id: number;
+  source_name: string;
+  status: 'imported' | 'failed';
+  action: 'notify' | 'open';
+  filename?: string;
+  error?: string;
+}
+
+interface InboxEventsResponse {
+  events: InboxEvent[];
+  watching: boolean;
+  last_event_id: number;
+}
+
+/**
+ * Polls the backend inbox watcher and notifies about, or opens, newly imported files.
+ * Events from before the page loaded are skipped.
+ */
+export function useInboxWatcher() {
+  const lastEventId = useRef<number | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+
+    const openFile = (filename: string) => {
+      window.open(`/visualize/${encodeURIComponent(filename)}`, '_blank');
+    };
+
+    const poll = async () => {
+      try {
+        const response = await api.getInboxEvents(lastEventId.current ?? 0);
+        const data = response.data as InboxEventsResponse;
+        if (cancelled || !data.watching) return;
+
+        if (lastEventId.current === null) {
+          lastEventId.current = data.last_event_id;
+          return;
+        }
+
+        for (const event of data.events) {
+          lastEventId.current = Math.max(lastEventId.current, event.event_id);
+          if (event.status === 'failed') {
+            notifyUser('ARGscape inbox', `Could not load ${event.source_name}: ${event.error}`);
+          } else if (event.filename && event.action === 'open') {
+            openFile(event.filename);
+          } else if (event.filename) {
+            const filename = event.filename;
+            notifyUser('New tree sequence in inbox', `${event.source_name} is ready to view`, () => openFile(filename));
+          }
+        }
+      } catch (error) {
+        log.debug('Inbox poll failed', {
+          component: 'useInboxWatcher',
+          error: error instanceof Error ? error : new Error(String(error))
+        });
+      }
+    };
+
+    poll();
+    const timer = setInterval(poll, POLL_INTERVAL_MS);
+    return () => {
+      cancelled = true;
+      clearInterval(timer);
+    };
+  }, []);
+}
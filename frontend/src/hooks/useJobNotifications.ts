@@ -0,0 +1,107 @@
+import { useEffect, useRef } from 'react';
+import { api } from '../lib/api';
+import { log } from '../lib/logger';
+import { showJobProgress } from '../lib/jobProgress';
+import { notifyUser } from '../lib/notifications';
+
+const POLL_INTERVAL_MS = 3000;
+
+// Jobs that finish faster than this are not worth a notification
+const MIN_NOTIFY_DURATION_SECONDS = 10;
+
+type JobStatus = 'pending' | 'running' | 'completed' | 'failed' | 'cancelled';
+type NotificationMode = 'background' | 'always' | 'off';
+
+interface JobSummary {
+  job_id: string;
+  operation: string;
+  description: string;
+  status: JobStatus;
+  progress: number | null;
+  error: string | null;
+  started_at: number | null;
+  finished_at: number | null;
+}
+
+const OPERATION_LABELS: Record<string, string> = {
+  node_metrics: 'Node statistics',
+  polytomy_stats: 'Polytomy statistics',
+  relax_neighborhood: 'Layout relaxation',
+  resolve_polytomies: 'Polytomy resolution',
+  simplify: 'Simplification',
+  benchmark: 'Benchmark',
+  batch: 'Batch export',
+  dataset_download: 'Dataset download',
+  remote_open: 'Remote file download',
+};
+
+function describeJob(job: JobSummary): { title: string; body: string } {
+  const label = OPERATION_LABELS[job.operation] ?? job.operation;
+  const subject = job.description ? ` of ${job.description}` : '';
+  if (job.status === 'completed') {
+    return { title: `${label} finished`, body: `${label}${subject} completed successfully` };
+  }
+  if (job.status === 'failed') {
+    return { title: `${label} failed`, body: job.error ?? `${label}${subject} failed` };
+  }
+  return { title: `${label} cancelled`, body: `${label}${subject} was cancelled` };
+}
+
+/**
+ * Polls the background job list and shows a desktop notification when a long
+ * job finishes. By default this only happens while the window is hidden; the
+ * `job_notifications` setting can make it always notify or turn it off.
+ * Progress of running jobs is mirrored to the window title and icon.
+ */
+export function useJobNotifications() {
+  const knownStatus = useRef<Map<string, JobStatus> | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+
+    const shouldNotify = async (job: JobSummary) => {
+      const duration = (job.finished_at ?? 0) - (job.started_at ?? job.finished_at ?? 0);
+      if (duration < MIN_NOTIFY_DURATION_SECONDS) return false;
+      const settings = (await api.getSettings()).data as { job_notifications?: NotificationMode };
+      const mode = settings.job_notifications ?? 'background';
+      return mode === 'always' || (mode === 'background' && document.hidden);
+    };
+
+    const poll = async () => {
+      try {
+        const response = await api.listJobs();
+        const jobs = (response.data as { jobs: JobSummary[] }).jobs;
+        if (cancelled) return;
+        showJobProgress(jobs);
+
+        const previous = knownStatus.current;
+        knownStatus.current = new Map(jobs.map((job) => [job.job_id, job.status]));
+        // Jobs that had already finished when the page loaded are not announced
+        if (previous === null) return;
+
+        for (const job of jobs) {
+          const before = previous.get(job.job_id) ?? 'pending';
+          const wasActive = before === 'pending' || before === 'running';
+          const isFinished = job.status !== 'pending' && job.status !== 'running';
+          if (wasActive && isFinished && (await shouldNotify(job))) {
+            const { title, body } = describeJob(job);
+            notifyUser(title, body, () => window.focus());
+          }
+        }
+      } catch (error) {
+        log.debug('Job poll failed', {
+          component: 'useJobNotifications',
+          error: error instanceof Error ? error : new Error(String(error))
+        });
+      }
+    };
+
+    poll();
+    const timer = setInterval(poll, POLL_INTERVAL_MS);
+    return () => {
+      cancelled = true;
+      clearInterval(timer);
+      showJobProgress([]);
+    };
+  }, []);
+}
@@ -0,0 +1,63 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { api, CameraKeyframe } from '../lib/api';
+import { log } from '../lib/logger';
+
+interface Layout3DProgress {
+  iteration: number;
+  numIterations: number;
+}
+
+/**
+ * Streams the backend's time-as-z 3D layout for a file.
+ * `positions` updates with every snapshot so the view can show the layout
+ * settling; `camera` is set once the final layout arrives.
+ */
+export function useLayout3D(filename: string) {
+  const [positions, setPositions] = useState<Record<number, number[]> | null>(null);
+  const [camera, setCamera] = useState<CameraKeyframe[] | null>(null);
+  const [progress, setProgress] = useState<Layout3DProgress | null>(null);
+  const [running, setRunning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const abortRef = useRef<(() => void) | null>(null);
+
+  const stop = useCallback(() => {
+    abortRef.current?.();
+    abortRef.current = null;
+    setRunning(false);
+  }, []);
+
+  const start = useCallback((options: Parameters<typeof api.streamLayout3D>[2] = {}) => {
+    abortRef.current?.();
+    setError(null);
+    setCamera(null);
+    setRunning(true);
+    abortRef.current = api.streamLayout3D(filename, {
+      onPositions: (data) => {
+        setPositions(data.positions);
+        setProgress({ iteration: data.iteration, numIterations: data.num_iterations });
+      },
+      onCamera: setCamera,
+      onComplete: () => {
+        abortRef.current = null;
+        setRunning(false);
+      },
+      onError: (streamError) => {
+        log.warn('3D layout stream failed', { component: 'useLayout3D', error: streamError, data: { filename } });
+        abortRef.current = null;
+        setError(streamError.message);
+        setRunning(false);
+      },
+    }, options);
+  }, [filename]);
+
+  const clear = useCallback(() => {
+    stop();
+    setPositions(null);
+    setCamera(null);
+    setProgress(null);
+  }, [stop]);
+
+  useEffect(() => () => abortRef.current?.(), []);
+
+  return { positions, camera, progress, running, error, start, stop, clear };
+}
@@ -0,0 +1,95 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { log } from '../lib/logger';
+
+const CHANNEL_NAME = 'argscape-linked-views';
+const STORAGE_KEY = 'argscape-linked-views';
+
+export type LinkedAspect = 'position' | 'timeRange' | 'selection';
+
+export type LinkSettings = Record<LinkedAspect, boolean>;
+
+interface LinkedViewMessage {
+  source: string;
+  aspect: LinkedAspect;
+  value: unknown;
+}
+
+type LinkedViewHandlers = Partial<Record<LinkedAspect, (value: any) => void>>;
+
+const DEFAULT_LINKS: LinkSettings = { position: false, timeRange: false, selection: false };
+
+const windowId = Math.random().toString(36).slice(2);
+
+function loadLinks(): LinkSettings {
+  try {
+    const saved = localStorage.getItem(STORAGE_KEY);
+    return saved ? { ...DEFAULT_LINKS, ...JSON.parse(saved) } : DEFAULT_LINKS;
+  } catch {
+    return DEFAULT_LINKS;
+  }
+}
+
+/**
+ * Links genome position, time range and selection between ARGscape windows
+ * showing different ARGs (e.g. truth vs inferred). Each aspect is linked only
+ * while it is switched on in both windows; the choice is remembered.
+ * Positions are shared in base pairs and selections by node ID.
+ */
+export function useLinkedViews(handlers: LinkedViewHandlers) {
+  const [links, setLinks] = useState<LinkSettings>(loadLinks);
+  const channel = useRef<BroadcastChannel | null>(null);
+  const handlersRef = useRef(handlers);
+  const linksRef = useRef(links);
+  // Last value sent or received per aspect, so windows don't echo each other's updates
+  const lastValues = useRef<Partial<Record<LinkedAspect, string>>>({});
+
+  handlersRef.current = handlers;
+  linksRef.current = links;
+
+  const available = typeof BroadcastChannel !== 'undefined';
+
+  useEffect(() => {
+    if (!available) return;
+    const bc = new BroadcastChannel(CHANNEL_NAME);
+    bc.onmessage = (event: MessageEvent<LinkedViewMessage>) => {
+      const { source, aspect, value } = event.data;
+      if (source === windowId || !linksRef.current[aspect]) return;
+      const serialized = JSON.stringify(value);
+      if (lastValues.current[aspect] === serialized) return;
+      lastValues.current[aspect] = serialized;
+      handlersRef.current[aspect]?.(value);
+    };
+    channel.current = bc;
+    return () => {
+      bc.close();
+      channel.current = null;
+    };
+  }, [available]);
+
+  const publish = useCallback((aspect: LinkedAspect, value: unknown) => {
+    if (!channel.current || !linksRef.current[aspect]) return;
+    const serialized = JSON.stringify(value);
+    if (lastValues.current[aspect] === serialized) return;
+    lastValues.current[aspect] = serialized;
+    channel.current.postMessage({ source: windowId, aspect, value } as LinkedViewMessage);
+  }, []);
+
+  const setLinked = useCallback((aspect: LinkedAspect, linked: boolean) => {
+    setLinks(previous => {
+      const next = { ...previous, [aspect]: linked };
+      try {
+        localStorage.setItem(STORAGE_KEY, JSON.stringify(next));
+      } catch (error) {
+        log.warn('Could not save linked view settings', {
+          component: 'useLinkedViews',
+          error: error instanceof Error ? error : new Error(String(error))
+        });
+      }
+      return next;
+    });
+    // Send the current state on the next change instead of assuming the other window has it
+    delete lastValues.current[aspect];
+  }, []);
+
+  return { available, links, setLinked, publish };
+}
@@ -0,0 +1,65 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { useNavigate, useSearchParams } from 'react-router-dom';
+import { api, ViewLinkState } from '../lib/api';
+import { log } from '../lib/logger';
+
+/**
+ * Copyable view links. `copyLink` encodes `state` for this file and puts the
+ * link on the clipboard; opening a page with `?view=...` restores the state
+ * through `apply` once the view is `ready`. Links are tied to the file's
+ * content, so one made from another loaded file opens that file instead, and
+ * one whose file isn't loaded reports which file to load.
+ */
+export function useViewLink(filename: string, state: ViewLinkState, apply: (state: ViewLinkState) => void, ready: boolean) {
+  const navigate = useNavigate();
+  const [searchParams, setSearchParams] = useSearchParams();
+  const [error, setError] = useState<string | null>(null);
+  const applied = useRef<string | null>(null);
+  const applyRef = useRef(apply);
+  applyRef.current = apply;
+  const code = searchParams.get('view');
+
+  useEffect(() => {
+    if (!code || !ready || applied.current === code) return;
+    applied.current = code;
+    api.resolveViewLink(code)
+      .then(async response => {
+        const { state: linked, filename: match } = response.data as { state: ViewLinkState; filename: string | null };
+        if (match === null) {
+          setError(`This view link belongs to ${linked.filename ? `"${linked.filename}"` : 'another file'}; load that file to open it`);
+          return;
+        }
+        if (match !== filename) {
+          navigate(`/visualize/${encodeURIComponent(match)}?view=${encodeURIComponent(code)}`);
+          return;
+        }
+        if (linked.coloring) {
+          await api.setPalette(filename, linked.coloring).catch(err => log.warn('Could not restore the linked palette', {
+            component: 'useViewLink',
+            error: err instanceof Error ? err : new Error(String(err))
+          }));
+        }
+        setError(null);
+        applyRef.current(linked);
+        // Drop the parameter so later navigation isn't undone by a refresh
+        searchParams.delete('view');
+        setSearchParams(searchParams, { replace: true });
+      })
+      .catch(err => setError(err instanceof Error ? err.message : String(err)));
+  }, [code, ready, filename, navigate, searchParams, setSearchParams]);
+
+  const copyLink = useCallback(async () => {
+    try {
+      const response = await api.createViewLink(filename, state);
+      const url = new URL((response.data as { path: string }).path, window.location.origin).toString();
+      await navigator.clipboard.writeText(url);
+      setError(null);
+      return url;
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      return null;
+    }
+  }, [filename, state]);
+
+  return { copyLink, error };
+}
@@ -30,6 +30,242 @@ interface TsdateInferenceRequest {
   filter_sites: boolean;
 }
 
+export interface SystemCapabilities {
+  platform: string;
+  python: string;
+  gpu: { available: boolean; reason?: string | null; vendor?: string; device?: string; adapter_type?: string; backend?: string; driver?: string };
+  cpu: { model: string | null; architecture: string; available_cores: number; logical_cores: number | null; physical_cores: number | null; features: string[] | null };
+  memory: { total_mb: number | null; available_mb: number | null };
+  disks: { location: string; total_mb?: number; free_mb?: number; error?: string }[];
+  recommended: { num_threads: number; gpu_compute: boolean };
+}
+
+interface DiagnosticsParams {
+  error?: string;
+  operation?: string;
+  filename?: string;
+  user_comment?: string;
+  include_logs?: boolean;
+}
+
+interface IntrogressionParams {
+  filename: string;
+  recipient: number | string;
+  donor: number | string;
+  relative_threshold?: number;
+  max_time?: number;
+  min_length?: number;
+}
+
+interface RemoteCredentials {
+  access_key_id?: string;
+  secret_access_key?: string;
+  session_token?: string;
+  region?: string;
+  endpoint_url?: string;
+  token?: string;
+}
+
+interface GenomeBrowserParams {
+  filename: string;
+  start: number;
+  end: number;
+  contig?: string;
+  assembly?: string;
+  offset?: number;
+}
+
+export interface BridgeMessage {
+  type: 'file_opened' | 'position_changed' | 'selection_changed' | 'open_file' | 'navigate' | 'select' | 'error';
+  filename?: string | null;
+  start?: number;
+  end?: number;
+  node_id?: number | null;
+  detail?: string;
+}
+
+/** The parts of a view a view link restores; see argscape.backend.view_links. */
+export interface ViewLinkState {
+  window?: [number, number] | null;
+  tree_range?: [number, number] | null;
+  filter_mode?: string;
+  filters?: Record<string, unknown>;
+  coloring?: Record<string, unknown>;
+  camera?: Record<string, unknown>;
+  view_mode?: string;
+  selected_node?: number | null;
+  filename?: string;
+  file?: string;
+}
+
+/** Messages from a collaboration room; patches carry dotted key paths. */
+export interface CollabMessage {
+  type: 'snapshot' | 'patch' | 'presence' | 'ack' | 'closed' | 'error';
+  room_id?: string;
+  version?: number;
+  state?: Record<string, unknown>;
+  set?: Record<string, unknown>;
+  removed?: string[];
+  presenter_connected?: boolean;
+  followers?: number;
+  delivered?: number;
+  detail?: string;
+}
+
+/** A figure template as an object, or its JSON/TOML source text. */
+export interface FigureTemplateSource {
+  template?: Record<string, unknown>;
+  template_text?: string;
+  template_filename?: string;
+}
+
+/** A 3D layout keyframe; angles are for deck.gl's OrbitView. */
+export interface CameraKeyframe {
+  time: number;
+  position: number[];
+  target: number[];
+  rotation_orbit: number;
+  rotation_x: number;
+  distance: number;
+}
+
+/** Result of a pick query: a node or an edge with what tooltips and selection need. */
+export type PickHit =
+  | { kind: 'node'; id: number; time: number; population: number; individual: number; flags: number; is_sample: boolean; parents: number[]; children: number[]; position: number[]; distance: number }
+  | { kind: 'edge'; parent: number; child: number; intervals: number[][]; span: number; distance: number };
+
+/** Adaptive level of detail: each level halves the geometry the backend streams. */
+export interface RenderLod {
+  filename: string;
+  changed: boolean;
+  level: number;
+  max_level: number;
+  target_fps: number;
+  last_fps: number | null;
+  detail_fraction: number;
+}
+
+/** Why a file couldn't be loaded; `upgradable` files can be converted with upgradeTreeSequence. */
+export interface TreeSequenceLoadError {
+  kind: 'invalid' | 'truncated' | 'bad_magic' | 'version_unsupported' | 'table_inconsistent';
+  message: string;
+  hint: string | null;
+  upgradable: boolean;
+}
+
+/** One step of a file's transform stack; the display tree sequence runs enabled steps in order. */
+export interface TransformStep {
+  operation: 'trim' | 'simplify' | 'resolve_polytomies' | 'recalibrate_times';
+  params?: Record<string, unknown>;
+  enabled?: boolean;
+}
+
+export type NodeAttributeValues =
+  | { kind: 'numeric'; expression: string; values: Array<number | null>; min: number | null; max: number | null; num_missing: number }
+  | { kind: 'boolean'; expression: string; values: boolean[]; num_true: number }
+  | { kind: 'categorical'; expression: string; values: string[]; categories: string[] };
+
+export type SampleSetDefinition =
+  | { kind: 'all' }
+  | { kind: 'population'; population: number | string }
+  | { kind: 'metadata'; table?: 'nodes' | 'individuals'; key: string; op?: '==' | '!=' | '<' | '<=' | '>' | '>=' | 'in' | 'contains'; value: unknown }
+  | { kind: 'manual'; samples: number[] }
+  | { kind: 'ids'; ids: string | Array<number | string>; id_type?: 'node' | 'individual' | 'name' }
+  | { kind: 'set'; name: string }
+  | { kind: 'union' | 'intersection' | 'difference'; sets: SampleSetDefinition[] };
+
+export interface SampleSetSummary {
+  name: string;
+  definition: SampleSetDefinition;
+  num_samples: number;
+  samples: number[];
+  error: string | null;
+}
+
+export interface Hook {
+  name: string;
+  event: 'file_opened' | 'export_completed';
+  command: string[];
+  match?: string;
+  enabled?: boolean;
+  timeout?: number;
+  cwd?: string | null;
+}
+
+export interface HookRun {
+  hook: string;
+  event: Hook['event'];
+  command: string[];
+  started_at: number;
+  status: 'ok' | 'failed' | 'timeout' | 'error';
+  exit_code: number | null;
+  duration_seconds?: number;
+  stdout?: string;
+  stderr?: string;
+  error: string | null;
+}
+
+export interface TrackSummary {
+  name: string;
+  source: 'statistic' | 'missingness' | 'sweep_scan' | 'bed' | 'bedgraph' | 'merged';
+  unit: string | null;
+  params: Record<string, unknown>;
+  series: string[];
+  value_range: [number, number] | null;
+  binning: { method: 'mean' | 'max' | 'min'; num_intervals: number; min_width: number | null; max_width: number | null; overlapping: boolean };
+  extent: [number, number] | null;
+}
+
+export interface TrackView {
+  filename: string;
+  name: string;
+  start: number;
+  end: number;
+  binned: boolean;
+  method: 'mean' | 'max' | 'min' | null;
+  num_intervals: number;
+  left: number[];
+  right: number[];
+  series: Record<string, Array<number | null>>;
+  labels: Array<string | null> | null;
+  value_range: [number, number] | null;
+  track_value_range: [number, number] | null;
+  unit: string | null;
+}
+
+export interface PopulationRecord {
+  id: number;
+  name: string;
+  description: string | null;
+  color: string;
+  stored_color: string | null;
+  display_order: number;
+  named: boolean;
+  num_samples: number;
+  num_nodes: number;
+}
+
+export interface PopulationListing {
+  filename: string;
+  populations: PopulationRecord[];
+  num_unnamed: number;
+  schema_codec: string | null;
+}
+
+export interface TimeCalibration {
+  filename: string;
+  time_units: string;
+  native: string;
+  calibrated: boolean;
+  generation_time: number | null;
+  mutation_rate: number | null;
+  generations_per_unit: number | null;
+  available: Array<'native' | 'generations' | 'years'>;
+  default_display: 'native' | 'years';
+  display_units: string[];
+  warnings: string[];
+}
+
 class ApiService {
   private baseURL: string;
 
@@ -113,7 +349,10 @@ class ApiService {
       });
 
       if (!response.ok) {
-        throw new Error(ERROR_MESSAGES.UPLOAD_FAILED);
+        // Unloadable files come back with an actionable message (truncated, wrong format, ...)
+        const errorData = await response.json().catch(() => null);
+        const message = typeof errorData?.detail === 'string' ? errorData.detail : ERROR_MESSAGES.UPLOAD_FAILED;
+        throw Object.assign(new Error(message), { loadError: errorData?.error as TreeSequenceLoadError | undefined });
       }
 
       const data = await response.json();
@@ -127,14 +366,228 @@ class ApiService {
     }
   }
 
+  private async postForm(endpoint: string, formData: FormData, fallbackError: string): Promise<ApiResponse> {
+    const url = `${this.baseURL}${endpoint}`;
+    log.api.call(endpoint, 'POST');
+
+    try {
+      const response = await fetch(url, {
+        method: 'POST',
+        body: formData,
+      });
+
+      if (!response.ok) {
+        const errorData = await response.json().catch(() => null);
+        throw new Error(errorData?.detail || fallbackError);
+      }
+
+      const data = await response.json();
+      log.api.success(endpoint, 'POST', data);
+
+      return { data, status: response.status };
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : fallbackError;
+      log.api.error(endpoint, new Error(errorMsg), 'POST');
+      throw error;
+    }
+  }
+
   // Session management - simplified with IP-based sessions
   async getCurrentSession() {
     return this.request(API_CONFIG.ENDPOINTS.GET_SESSION);
   }
 
+  async getSessionSnapshot() {
+    return this.request('/session/snapshot');
+  }
+
+  async saveSessionSnapshot(uiState: Record<string, unknown>) {
+    return this.request('/session/snapshot', {
+      method: 'POST',
+      body: JSON.stringify({ ui_state: uiState }),
+    });
+  }
+
+  // View links: a compact string restoring a view of a file
+  async createViewLink(filename: string, state: ViewLinkState) {
+    return this.request('/view-links', {
+      method: 'POST',
+      body: JSON.stringify({ filename, state }),
+    });
+  }
+
+  async resolveViewLink(code: string) {
+    return this.request(`/view-links/${encodeURIComponent(code)}`);
+  }
+
+  async getSessionReplay() {
+    return this.request('/session/replay');
+  }
+
+  async getInboxStatus() {
+    return this.request('/inbox');
+  }
+
+  async getInboxEvents(after: number = 0) {
+    return this.request(`/inbox/events?after=${after}`);
+  }
+
+  async getNetworkStatus() {
+    return this.request<{ offline: boolean; outbound: string; guard_installed: boolean }>('/network-status');
+  }
+
+  async testNetworkConnection(url: string) {
+    return this.request<{ url: string; reachable: boolean; error?: string }>(
+      `/network/test?url=${encodeURIComponent(url)}`,
+      { method: 'POST' }
+    );
+  }
+
+  async getCapabilities() {
+    return this.request('/capabilities');
+  }
+
+  // Settings
+  async getSettings() {
+    return this.request('/settings');
+  }
+
+  // Diagnostic bundles
+  async previewDiagnostics(params: DiagnosticsParams) {
+    return this.request('/diagnostics/preview', { method: 'POST', body: JSON.stringify(params) });
+  }
+
+  async saveDiagnostics(params: DiagnosticsParams) {
+    return this.request('/diagnostics/save', { method: 'POST', body: JSON.stringify(params) });
+  }
+
+  async submitDiagnostics(params: DiagnosticsParams) {
+    return this.request('/diagnostics/submit', { method: 'POST', body: JSON.stringify(params) });
+  }
+
+  // Readiness after launch
+  async getReadiness() {
+    return this.request('/readiness');
+  }
+
+  // Crash recovery
+  async getCrashReport() {
+    return this.request('/crash-report');
+  }
+
+  async dismissCrashReport() {
+    return this.request('/crash-report', { method: 'DELETE' });
+  }
+
+  async getFormattingConfig() {
+    return this.request('/formatting');
+  }
+
+  async getMessageCatalog(locale: string) {
+    return this.request(`/i18n/${encodeURIComponent(locale)}`);
+  }
+
+  async updateSettings(updates: Record<string, unknown>) {
+    return this.request('/settings', {
+      method: 'POST',
+      body: JSON.stringify(updates),
+    });
+  }
+
+  async getHooks(): Promise<{ hooks: Hook[]; available: boolean; reason: string | null; runs: HookRun[]; path: string }> {
+    return this.request('/hooks');
+  }
+
+  async saveHooks(hooks: Hook[]): Promise<{ hooks: Hook[] }> {
+    return this.request('/hooks', {
+      method: 'POST',
+      body: JSON.stringify({ hooks }),
+    });
+  }
+
+  async testHook(hook: Hook, values: Record<string, unknown> = {}, run = false): Promise<{ command: string[]; ran: boolean } & Partial<HookRun>> {
+    return this.request('/hooks/test', {
+      method: 'POST',
+      body: JSON.stringify({ hook, values, run }),
+    });
+  }
+
+  async getResourceStatus() {
+    return this.request('/resources');
+  }
+
+  async getBackendStatus() {
+    return this.request('/backend/status');
+  }
+
+  async stopBackend() {
+    return this.request('/backend/stop', { method: 'POST' });
+  }
+
+  async restartBackend() {
+    return this.request('/backend/restart', { method: 'POST' });
+  }
+
+  async getSystemCapabilities() {
+    return this.request('/system-capabilities');
+  }
+
+  // First-run setup wizard
+  async getSetup() {
+    return this.request('/setup');
+  }
+
+  async checkSetupPath(path: string) {
+    return this.request('/setup/check-path', { method: 'POST', body: JSON.stringify({ path }) });
+  }
+
+  async completeSetup(choices: Record<string, unknown>) {
+    return this.request('/setup', { method: 'POST', body: JSON.stringify(choices) });
+  }
+
+  async getPerformanceMetrics() {
+    return this.request('/performance-metrics');
+  }
+
+  async resetPerformanceMetrics() {
+    return this.request('/performance-metrics/reset', { method: 'POST' });
+  }
+
   // Tree sequence operations - now using simplified endpoints
-  async uploadTreeSequence(file: File) {
-    return this.uploadFile(API_CONFIG.ENDPOINTS.UPLOAD, file);
+  // With `background`, the response is a cancellable "load" job instead of the loaded file
+  async uploadTreeSequence(file: File, options: { protected?: boolean; background?: boolean } = {}) {
+    const query = new URLSearchParams();
+    if (options.protected) query.set('protected', 'true');
+    if (options.background) query.set('background', 'true');
+    const endpoint = query.toString() ? `${API_CONFIG.ENDPOINTS.UPLOAD}?${query}` : API_CONFIG.ENDPOINTS.UPLOAD;
+    return this.uploadFile(endpoint, file);
+  }
+
+  // Files rejected as an older format (loadError.upgradable) are kept server-side for this
+  async upgradeTreeSequence(filename: string) {
+    return this.request(`/upgrade-tree-sequence/${encodeURIComponent(filename)}`, { method: 'POST' });
+  }
+
+  // Relate (.anc + optional .mut) or ARGweaver (.smc) output, converted server-side
+  async importArg(files: File[], options: { protected?: boolean } = {}) {
+    const formData = new FormData();
+    files.forEach(file => formData.append('files', file));
+    const endpoint = `/import-arg${options.protected ? '?protected=true' : ''}`;
+    return this.postForm(endpoint, formData, 'Failed to import ARG');
+  }
+
+  async setFileProtected(filename: string, isProtected: boolean = true) {
+    return this.request(`/tree-sequence/${encodeURIComponent(filename)}/protected?protected=${isProtected}`, {
+      method: 'POST',
+    });
+  }
+
+  async getDerivedDataUsage() {
+    return this.request('/derived-data');
+  }
+
+  async clearDerivedData() {
+    return this.request('/derived-data/clear', { method: 'POST' });
   }
 
   async getUploadedFiles() {
@@ -198,6 +651,131 @@ class ApiService {
     return this.request(endpoint);
   }
 
+  /**
+   * Ask the backend to prepare graph data for the tree windows around the current one,
+   * so scrubbing to a neighboring window is served from its cache.
+   */
+  async prefetchGraphData(
+    filename: string,
+    options: {
+      treeStartIdx: number;
+      treeEndIdx: number;
+      maxSamples?: number;
+      sampleOrder?: string;
+      radius?: number;
+      step?: number;
+    }
+  ) {
+    return this.request(`${API_CONFIG.ENDPOINTS.GRAPH_DATA}/${encodeURIComponent(filename)}/prefetch`, {
+      method: 'POST',
+      body: JSON.stringify({
+        tree_start_idx: options.treeStartIdx,
+        tree_end_idx: options.treeEndIdx,
+        max_samples: options.maxSamples,
+        sample_order: options.sampleOrder,
+        radius: options.radius,
+        step: options.step,
+      }),
+    });
+  }
+
+  /**
+   * Stream graph data progressively (samples first, then internal nodes by time band).
+   * Returns a function that aborts the stream.
+   */
+  streamGraphData(
+    filename: string,
+    handlers: {
+      onMetadata?: (data: any) => void;
+      onChunk: (data: any) => void;
+      onProgress?: (data: { chunk_index: number; num_chunks: number; nodes_loaded: number; total_nodes: number; fraction: number }) => void;
+      onComplete?: (data: any) => void;
+      onError?: (error: Error) => void;
+    },
+    options: { maxSamples?: number; sampleOrder?: string; numTimeBands?: number; adaptive?: boolean } = {}
+  ): () => void {
+    const params = new URLSearchParams();
+    if (options.maxSamples) params.append('max_samples', options.maxSamples.toString());
+    if (options.sampleOrder) params.append('sample_order', options.sampleOrder);
+    if (options.numTimeBands) params.append('num_time_bands', options.numTimeBands.toString());
+    if (options.adaptive) params.append('adaptive', 'true');
+
+    const endpoint = `/graph-data-stream/${encodeURIComponent(filename)}?${params}`;
+    log.api.call(endpoint, 'GET');
+    const source = new EventSource(`${this.baseURL}${endpoint}`);
+
+    const parse = (event: Event) => JSON.parse((event as MessageEvent).data);
+    source.addEventListener('metadata', (event) => handlers.onMetadata?.(parse(event)));
+    source.addEventListener('chunk', (event) => handlers.onChunk(parse(event)));
+    source.addEventListener('load-progress', (event) => handlers.onProgress?.(parse(event)));
+    source.addEventListener('complete', (event) => {
+      source.close();
+      log.api.success(endpoint, 'GET');
+      handlers.onComplete?.(parse(event));
+    });
+    source.addEventListener('error', (event) => {
+      source.close();
+      const detail = (event as MessageEvent).data ? JSON.parse((event as MessageEvent).data).detail : 'Stream interrupted';
+      const error = new Error(detail);
+      log.api.error(endpoint, error, 'GET');
+      handlers.onError?.(error);
+    });
+
+    return () => source.close();
+  }
+
+  async getPopulationGraphData(filename: string, collapsePureClades: boolean = true) {
+    const params = new URLSearchParams({ collapse_pure_clades: collapsePureClades.toString() });
+    return this.request(`/population-graph-data/${encodeURIComponent(filename)}?${params}`);
+  }
+
+  async getNodeMetrics(
+    filename: string,
+    options: {
+      includeBetweenness?: boolean;
+      maxPivots?: number;
+      randomSeed?: number;
+    } = {}
+  ) {
+    const params = new URLSearchParams();
+    if (options.includeBetweenness !== undefined) params.append('include_betweenness', options.includeBetweenness.toString());
+    if (options.maxPivots !== undefined) params.append('max_pivots', options.maxPivots.toString());
+    if (options.randomSeed !== undefined) params.append('random_seed', options.randomSeed.toString());
+    return this.request(`/node-metrics/${encodeURIComponent(filename)}?${params}`);
+  }
+
+  /** Saved derived node attributes, and the variables and functions expressions can use. */
+  async getNodeAttributes(filename: string) {
+    return this.request(`/node-attributes/${encodeURIComponent(filename)}`);
+  }
+
+  /** Save a named attribute such as `log10(time + 1)`; a null expression deletes it. */
+  async saveNodeAttribute(filename: string, name: string, expression: string | null) {
+    return this.request('/node-attributes', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ filename, name, expression }),
+    });
+  }
+
+  /** Per-node values of an expression or saved attribute. */
+  async evaluateNodeAttribute(filename: string, expression: string): Promise<NodeAttributeValues> {
+    return this.request('/node-attributes/evaluate', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ filename, expression }),
+    });
+  }
+
+  /** IDs of the nodes for which an expression is true. */
+  async filterNodesByAttribute(filename: string, expression: string): Promise<{ node_ids: number[]; num_nodes: number }> {
+    return this.request('/node-attributes/filter', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ filename, expression }),
+    });
+  }
+
   // Location inference
   async inferLocationsFast(params: {
     filename: string;
@@ -260,6 +838,8 @@ class ApiService {
     crs?: string;
     mutation_rate?: number;
     recombination_rate?: number;
+    demes_model?: string;
+    demes_samples?: Record<string, number>;
   }) {
     return this.request(API_CONFIG.ENDPOINTS.SIMULATE_TREE_SEQUENCE, {
       method: 'POST',
@@ -267,6 +847,13 @@ class ApiService {
     });
   }
 
+  async parseDemesModel(model: string, filename?: string) {
+    return this.request('/demes/parse', {
+      method: 'POST',
+      body: JSON.stringify({ model, filename }),
+    });
+  }
+
   // Custom location operations
   async uploadLocationCSV(file: File, csvType: 'sample_locations' | 'node_locations') {
     const url = `${this.baseURL}/upload-location-csv?csv_type=${csvType}`;
@@ -353,49 +940,1268 @@ class ApiService {
       body: JSON.stringify(params),
     });
   }
-}
 
-// Create singleton instance
-export const apiService = new ApiService();
+  // Tree sequence transformations
+  async getPolytomyStats(filename: string) {
+    return this.request(`/polytomy-stats/${encodeURIComponent(filename)}`);
+  }
 
-// Export convenience functions
-export const api = {
-  // Session management
-  getCurrentSession: () => apiService.getCurrentSession(),
-  
-  // Tree sequence operations
-  uploadTreeSequence: (file: File) => apiService.uploadTreeSequence(file),
-  getUploadedFiles: () => apiService.getUploadedFiles(),
-  getTreeSequenceMetadata: (filename: string) => apiService.getTreeSequenceMetadata(filename),
-  deleteTreeSequence: (filename: string) => apiService.deleteTreeSequence(filename),
-  downloadTreeSequence: (filename: string, format: 'trees' | 'tsz' = 'trees') =>
-    apiService.downloadTreeSequence(filename, format),
-  
-  // Data retrieval
-  getGraphData: (filename: string, options?: Parameters<typeof apiService.getGraphData>[1]) => 
-    apiService.getGraphData(filename, options),
-  
-  // Location inference
-  inferLocationsFast: (params: Parameters<typeof apiService.inferLocationsFast>[0]) => 
-    apiService.inferLocationsFast(params),
-  inferLocationsSparg: (params: Parameters<typeof apiService.inferLocationsSparg>[0]) =>
-    apiService.inferLocationsSparg(params),
-  inferLocationsGaiaQuadratic: (params: Parameters<typeof apiService.inferLocationsGaiaQuadratic>[0]) =>
-    apiService.inferLocationsGaiaQuadratic(params),
-  inferLocationsGaiaLinear: (params: Parameters<typeof apiService.inferLocationsGaiaLinear>[0]) =>
-    apiService.inferLocationsGaiaLinear(params),
-  inferLocationsMidpoint: (params: Parameters<typeof apiService.inferLocationsMidpoint>[0]) =>
-    apiService.inferLocationsMidpoint(params),
+  async getTopologyBlocks(filename: string, tolerance = 0) {
+    return this.request(`/topology-blocks/${encodeURIComponent(filename)}?tolerance=${tolerance}`);
+  }
 
-  // Tree sequence simulation
-  simulateTreeSequence: (params: Parameters<typeof apiService.simulateTreeSequence>[0]) =>
-    apiService.simulateTreeSequence(params),
+  async getUniqueTopologies(filename: string) {
+    return this.request(`/unique-topologies/${encodeURIComponent(filename)}`);
+  }
 
-  // Custom location operations
-  uploadLocationCSV: (file: File, csvType: 'sample_locations' | 'node_locations') =>
-    apiService.uploadLocationCSV(file, csvType),
-  updateTreeSequenceLocations: (params: Parameters<typeof apiService.updateTreeSequenceLocations>[0]) =>
-    apiService.updateTreeSequenceLocations(params),
+  /** A windowed statistic as a column table; the same numbers as `argscape stats`. */
+  async getWindowedStatistic(filename: string, options: {
+    statistic: 'diversity' | 'segregating_sites' | 'tajimas_d' | 'sfs' | 'gnn' | 'tmrca';
+    mode?: 'site' | 'branch';
+    num_windows?: number;
+    window_size?: number;
+    tree_windows?: boolean;
+    sample_sets?: 'all' | 'population' | string; // or comma-separated names of the file's sample sets
+    polarised?: boolean;
+    missing_data?: 'ignore' | 'mask' | 'drop';
+    time_units?: 'native' | 'generations' | 'years';
+    generation_time?: number;
+    mutation_rate?: number;
+    accelerator?: 'cpu' | 'auto' | 'gpu';
+  }) {
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value));
+    });
+    return this.request(`/statistics/${encodeURIComponent(filename)}?${params}`);
+  }
+
+  async getSampleSets(filename: string): Promise<{ filename: string; sample_sets: SampleSetSummary[] }> {
+    return this.request(`/sample-sets/${encodeURIComponent(filename)}`);
+  }
+
+  /** Define or redefine a named sample set; a null definition deletes it. */
+  async saveSampleSet(filename: string, name: string, definition: SampleSetDefinition | null) {
+    return this.request('/sample-sets', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ filename, name, definition }),
+    });
+  }
+
+  async getPopulations(filename: string): Promise<PopulationListing> {
+    return this.request(`/populations/${encodeURIComponent(filename)}`);
+  }
+
+  /** Write edited population metadata into a derived tree sequence. */
+  async editPopulations(
+    filename: string,
+    edits: Record<number, Partial<Pick<PopulationRecord, 'name' | 'description' | 'color' | 'display_order'>>>,
+    order?: number[]
+  ) {
+    return this.request('/populations/edit', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ filename, edits, order }),
+    });
+  }
+
+  /** What the file's node times are in, and which units they can be converted to. */
+  async getTimeUnits(filename: string): Promise<TimeCalibration> {
+    return this.request(`/time-units/${encodeURIComponent(filename)}`);
+  }
+
+  /** Store a generation time and mutation rate for the file; both null clears them. */
+  async setTimeCalibration(filename: string, calibration: { generation_time?: number | null; mutation_rate?: number | null }): Promise<TimeCalibration> {
+    return this.request('/time-units', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ filename, ...calibration }),
+    });
+  }
+
+  /** Samples isolated (missing) over parts of the genome. */
+  async getMissingData(filename: string) {
+    return this.request(`/missing-data/${encodeURIComponent(filename)}`);
+  }
+
+  async getMissingnessTrack(filename: string, options: { num_windows?: number; window_size?: number; tree_windows?: boolean; per_sample?: boolean } = {}) {
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value));
+    });
+    return this.request(`/missing-data/${encodeURIComponent(filename)}/track?${params}`);
+  }
+
+  async getTracks(filename: string): Promise<{ filename: string; tracks: TrackSummary[] }> {
+    return this.request(`/tracks/${encodeURIComponent(filename)}`);
+  }
+
+  async registerTrack(
+    filename: string,
+    name: string,
+    source: 'statistic' | 'missingness' | 'sweep_scan',
+    params: Record<string, unknown> = {}
+  ): Promise<TrackSummary> {
+    return this.request('/tracks', {
+      method: 'POST',
+      body: JSON.stringify({ filename, name, source, params }),
+    });
+  }
+
+  async uploadTrack(filename: string, file: File, options: { name?: string; kind?: 'bed' | 'bedgraph' } = {}) {
+    const formData = new FormData();
+    formData.append('file', file);
+    const params = new URLSearchParams();
+    if (options.name) params.set('name', options.name);
+    if (options.kind) params.set('kind', options.kind);
+    const query = params.toString();
+    return this.postForm(`/tracks/${encodeURIComponent(filename)}/upload${query ? `?${query}` : ''}`, formData, 'Failed to upload track');
+  }
+
+  async mergeTracks(
+    filename: string,
+    name: string,
+    tracks: string[],
+    operation: 'stack' | 'mean' | 'sum' | 'difference' | 'ratio' = 'stack'
+  ): Promise<TrackSummary> {
+    return this.request('/tracks/merge', {
+      method: 'POST',
+      body: JSON.stringify({ filename, name, tracks, operation }),
+    });
+  }
+
+  async getTrack(
+    filename: string,
+    name: string,
+    viewport: { start?: number; end?: number; max_bins?: number; method?: 'mean' | 'max' | 'min'; series?: string[] } = {}
+  ): Promise<TrackView> {
+    const params = new URLSearchParams();
+    Object.entries(viewport).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, Array.isArray(value) ? value.join(',') : String(value));
+    });
+    return this.request(`/tracks/${encodeURIComponent(filename)}/${encodeURIComponent(name)}?${params}`);
+  }
+
+  async exportTrack(filename: string, name: string, options: { format?: 'bedgraph' | 'csv'; series?: string; chrom?: string } = {}): Promise<Blob> {
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, value);
+    });
+    const endpoint = `/tracks/${encodeURIComponent(filename)}/${encodeURIComponent(name)}/export?${params}`;
+    log.api.call(endpoint, 'GET');
+
+    const response = await fetch(`${this.baseURL}${endpoint}`);
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'GET');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'GET', { size: blob.size });
+    return blob;
+  }
+
+  async deleteTrack(filename: string, name: string) {
+    return this.request(`/tracks/${encodeURIComponent(filename)}/${encodeURIComponent(name)}`, {
+      method: 'DELETE',
+    });
+  }
+
+  async getSweepScan(filename: string, options: { num_windows?: number; threshold?: number } = {}) {
+    const params = new URLSearchParams();
+    if (options.num_windows !== undefined) params.set('num_windows', String(options.num_windows));
+    if (options.threshold !== undefined) params.set('threshold', String(options.threshold));
+    const query = params.toString();
+    return this.request(`/sweep-scan/${encodeURIComponent(filename)}${query ? `?${query}` : ''}`);
+  }
+
+  async detectIntrogression(params: IntrogressionParams) {
+    return this.request('/introgression', {
+      method: 'POST',
+      body: JSON.stringify({ ...params, format: 'json' }),
+    });
+  }
+
+  async exportIntrogressionCsv(params: IntrogressionParams): Promise<Blob> {
+    const endpoint = '/introgression';
+    log.api.call(endpoint, 'POST', { filename: params.filename, format: 'csv' });
+
+    const response = await fetch(`${this.baseURL}${endpoint}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ ...params, format: 'csv' }),
+    });
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'POST');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'POST', { size: blob.size });
+    return blob;
+  }
+
+  async estimateNe(params: {
+    filename: string;
+    num_epochs?: number;
+    time_breaks?: number[];
+    num_bootstrap?: number;
+    ploidy?: number;
+    random_seed?: number;
+  }) {
+    return this.request('/ne-through-time', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async getTmrcaMatrix(params: {
+    filename: string;
+    samples?: number[];
+    positions?: number[];
+    max_samples?: number;
+    random_seed?: number;
+  }) {
+    return this.request('/tmrca-matrix', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async getSampleEmbedding(params: {
+    filename: string;
+    distance?: 'tmrca' | 'genetic';
+    dimensions?: 2 | 3;
+    samples?: number[];
+    max_samples?: number;
+    random_seed?: number;
+  }) {
+    return this.request('/sample-embedding', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async validateRedating(params: {
+    filename: string;
+    mutation_rate?: number;
+    method?: 'variational_gamma' | 'inside_outside' | 'maximization';
+    population_size?: number;
+  }) {
+    return this.request('/redate/validate', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async compareRedatedTimes(before: string, after: string) {
+    const params = new URLSearchParams({ before, after });
+    return this.request(`/redate/compare?${params.toString()}`);
+  }
+
+  async resolvePolytomies(params: {
+    filename: string;
+    method?: 'random' | 'zero_length';
+    random_seed?: number;
+  }) {
+    return this.request('/resolve-polytomies', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async getDisplayTimes(
+    filename: string,
+    mode: 'time' | 'log' | 'rank' | 'years' = 'time',
+    generationTime?: number
+  ) {
+    const params = new URLSearchParams({ mode });
+    if (generationTime !== undefined) params.append('generation_time', generationTime.toString());
+    return this.request(`/display-times/${encodeURIComponent(filename)}?${params}`);
+  }
+
+  // Metadata editor
+  async getMetadataSchemas(filename: string) {
+    return this.request(`/metadata-schemas/${encodeURIComponent(filename)}`);
+  }
+
+  async getTableMetadata(
+    filename: string,
+    table: string,
+    options: { offset?: number; limit?: number; ids?: number[] } = {}
+  ) {
+    const params = new URLSearchParams();
+    if (options.offset !== undefined) params.append('offset', options.offset.toString());
+    if (options.limit !== undefined) params.append('limit', options.limit.toString());
+    if (options.ids) params.append('ids', options.ids.join(','));
+    return this.request(`/metadata/${encodeURIComponent(filename)}/${encodeURIComponent(table)}?${params}`);
+  }
+
+  async getTransformStack(filename: string) {
+    return this.request(`/transforms/${encodeURIComponent(filename)}`);
+  }
+
+  async applyTransformStack(filename: string, steps: TransformStep[]) {
+    return this.request('/transforms', {
+      method: 'POST',
+      body: JSON.stringify({ filename, steps }),
+    });
+  }
+
+  async getContigs(filename: string) {
+    return this.request(`/contigs/${encodeURIComponent(filename)}`);
+  }
+
+  // contigs: null clears a hand-set layout and goes back to detection
+  async setContigs(filename: string, contigs: { name: string; start: number; end: number }[] | null) {
+    return this.request('/contigs', {
+      method: 'POST',
+      body: JSON.stringify({ filename, contigs }),
+    });
+  }
+
+  async getNodeMetadata(params: {
+    filename: string;
+    node_id: number;
+    full?: boolean;
+    decode_schema?: Record<string, unknown>;
+  }) {
+    return this.request('/node-metadata', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async editMetadata(params: {
+    filename: string;
+    edits?: Record<string, Record<number, unknown>>;
+    schemas?: Record<string, Record<string, unknown> | null>;
+  }) {
+    return this.request('/metadata/edit', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async getSimulationContext(filename: string) {
+    return this.request(`/simulation-context/${encodeURIComponent(filename)}`);
+  }
+
+  // Layout operations
+  async relaxLayoutNeighborhood(params: {
+    filename: string;
+    positions: Record<number, [number, number]>;
+    anchor_node_ids?: number[];
+    free_node_ids?: number[];
+    hops?: number;
+    iterations?: number;
+    lock_y?: boolean;
+  }) {
+    return this.request('/layout/relax-neighborhood', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async bundleEdges(params: {
+    filename: string;
+    positions: Record<number, number[]>;
+    cycles?: number;
+    compatibility_threshold?: number;
+    max_neighbors?: number;
+  }) {
+    return this.request('/layout/bundle-edges', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async placeLabels(params: {
+    filename: string;
+    positions: Record<number, number[]>;
+    zoom?: number;
+    font_size?: number;
+    node_radius?: number;
+    viewport?: [number, number, number, number];
+    candidate_node_ids?: number[];
+    texts?: Record<number, string>;
+    max_labels?: number;
+  }) {
+    return this.request('/layout/labels', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async buildPickIndex(filename: string, positions: Record<number, number[]>) {
+    return this.request('/layout/pick-index', {
+      method: 'POST',
+      body: JSON.stringify({ filename, positions }),
+    });
+  }
+
+  async pick(filename: string, x: number, y: number, radius: number, kinds: ('node' | 'edge')[] = ['node', 'edge']) {
+    const params = new URLSearchParams({ x: x.toString(), y: y.toString(), radius: radius.toString(), kinds: kinds.join(',') });
+    return this.request<{ hit: PickHit | null }>(`/layout/pick/${encodeURIComponent(filename)}?${params}`);
+  }
+
+  async reportFrameStats(filename: string, fps: number, targetFps?: number) {
+    return this.request<RenderLod>('/render/frame-stats', {
+      method: 'POST',
+      body: JSON.stringify({ filename, fps, target_fps: targetFps }),
+    });
+  }
+
+  async getRenderLod(filename: string) {
+    return this.request<RenderLod>(`/render/lod/${encodeURIComponent(filename)}`);
+  }
+
+  async resetRenderLod(filename: string) {
+    return this.request(`/render/lod/${encodeURIComponent(filename)}`, { method: 'DELETE' });
+  }
+
+  async buildTileSet(filename: string, positions: Record<number, number[]>) {
+    return this.request<{ url_template: string; tile_size: number; max_zoom: number; origin: number[]; world_size: number; population_colors: Record<string, string> }>(
+      `/tiles/${encodeURIComponent(filename)}`,
+      { method: 'POST', body: JSON.stringify({ positions }) }
+    );
+  }
+
+  tileUrl(filename: string, z: number, x: number, y: number, format: 'json' | 'png' = 'json'): string {
+    return `${this.baseURL}/tiles/${encodeURIComponent(filename)}/${z}/${x}/${y}.${format}`;
+  }
+
+  async exportLayout(params: {
+    filename: string;
+    positions: Record<number, number[]>;
+    algorithm: string;
+    parameters?: Record<string, unknown>;
+    random_seed?: number;
+    format?: 'json' | 'parquet';
+    archive?: boolean;
+  }): Promise<Blob> {
+    const endpoint = '/layout/export';
+    log.api.call(endpoint, 'POST', { filename: params.filename, format: params.format });
+
+    const response = await fetch(`${this.baseURL}${endpoint}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(params),
+    });
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'POST');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'POST', { size: blob.size });
+    return blob;
+  }
+
+  streamLayout3D(
+    filename: string,
+    handlers: {
+      onPositions: (data: { iteration: number; num_iterations: number; final: boolean; positions: Record<number, number[]> }) => void;
+      onCamera?: (keyframes: CameraKeyframe[]) => void;
+      onComplete?: () => void;
+      onError?: (error: Error) => void;
+    },
+    options: { iterations?: number; snapshotEvery?: number; randomSeed?: number; logTime?: boolean } = {}
+  ): () => void {
+    const params = new URLSearchParams();
+    if (options.iterations) params.append('iterations', options.iterations.toString());
+    if (options.snapshotEvery) params.append('snapshot_every', options.snapshotEvery.toString());
+    if (options.randomSeed !== undefined) params.append('random_seed', options.randomSeed.toString());
+    if (options.logTime) params.append('log_time', 'true');
+
+    const endpoint = `/layout-3d-stream/${encodeURIComponent(filename)}?${params}`;
+    log.api.call(endpoint, 'GET');
+    const source = new EventSource(`${this.baseURL}${endpoint}`);
+
+    const parse = (event: Event) => JSON.parse((event as MessageEvent).data);
+    source.addEventListener('positions', (event) => handlers.onPositions(parse(event)));
+    source.addEventListener('camera', (event) => handlers.onCamera?.(parse(event).keyframes));
+    source.addEventListener('complete', () => {
+      source.close();
+      log.api.success(endpoint, 'GET');
+      handlers.onComplete?.();
+    });
+    source.addEventListener('error', (event) => {
+      source.close();
+      const detail = (event as MessageEvent).data ? JSON.parse((event as MessageEvent).data).detail : 'Stream interrupted';
+      const error = new Error(detail);
+      log.api.error(endpoint, error, 'GET');
+      handlers.onError?.(error);
+    });
+
+    return () => source.close();
+  }
+
+  async downloadLayout3D(
+    filename: string,
+    options: { format?: 'gltf' | 'obj'; geometry?: 'mesh' | 'lines'; nodeRadius?: number; edgeRadius?: number } = {}
+  ): Promise<Blob> {
+    const format = options.format ?? 'gltf';
+    const params = new URLSearchParams();
+    if (options.geometry) params.append('geometry', options.geometry);
+    if (options.nodeRadius) params.append('node_radius', options.nodeRadius.toString());
+    if (options.edgeRadius) params.append('edge_radius', options.edgeRadius.toString());
+    const endpoint = `/layout-3d/${encodeURIComponent(filename)}/${format}?${params}`;
+    log.api.call(endpoint, 'GET', { filename, format });
+
+    const response = await fetch(`${this.baseURL}${endpoint}`);
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'GET');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'GET', { size: blob.size });
+    return blob;
+  }
+
+  async estimateExport(params: {
+    filename: string;
+    format: 'svg' | 'png' | 'trees' | 'tsz' | 'layout_json' | 'layout_parquet' | 'edges_csv';
+    element_counts?: { nodes?: number; edges?: number; mutations?: number; labels?: number };
+    width?: number;
+    height?: number;
+    scale?: number;
+  }) {
+    return this.request('/export/estimate', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  /** SVG of the ARG, or null when it is above the rasterization limit and should be exported as PNG. */
+  async exportSvg(params: {
+    filename: string;
+    positions?: Record<number, number[]>;
+    group_by?: 'population' | 'time_band';
+    num_time_bands?: number;
+    show_labels?: boolean;
+    show_mutations?: boolean;
+    force?: boolean;
+    bundle_edges?: boolean;
+  }): Promise<Blob | null> {
+    const endpoint = '/export/svg';
+    log.api.call(endpoint, 'POST', { filename: params.filename, group_by: params.group_by });
+
+    const response = await fetch(`${this.baseURL}${endpoint}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(params),
+    });
+
+    if (response.status === 413) {
+      const errorData = await response.json().catch(() => null);
+      log.info('SVG export above the rasterization limit', { data: { detail: errorData?.detail } });
+      return null;
+    }
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'POST');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'POST', { size: blob.size });
+    return blob;
+  }
+
+  /** A single read-only HTML file of the view that opens without ARGscape. */
+  async exportHtmlBundle(params: {
+    filename: string;
+    positions?: Record<number, number[]>;
+    title?: string;
+    algorithm?: string;
+    parameters?: Record<string, unknown>;
+  }): Promise<Blob> {
+    const endpoint = '/export/html';
+    log.api.call(endpoint, 'POST', { filename: params.filename });
+
+    const response = await fetch(`${this.baseURL}${endpoint}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(params),
+    });
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'POST');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'POST', { size: blob.size });
+    return blob;
+  }
+
+  // Figure templates
+  async getDefaultFigureTemplate() {
+    return this.request('/figures/default-template');
+  }
+
+  async validateFigureTemplate(template: FigureTemplateSource) {
+    return this.request('/figures/validate', {
+      method: 'POST',
+      body: JSON.stringify(template),
+    });
+  }
+
+  async renderFigure(params: FigureTemplateSource & { filename: string; format?: 'png' | 'svg' | 'pdf' }): Promise<Blob> {
+    const endpoint = '/figures/render';
+    log.api.call(endpoint, 'POST', { filename: params.filename, format: params.format });
+
+    const response = await fetch(`${this.baseURL}${endpoint}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(params),
+    });
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'POST');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'POST', { size: blob.size });
+    return blob;
+  }
+
+  async importLayout(filename: string, file: File) {
+    return this.uploadFile(`/layout/import?filename=${encodeURIComponent(filename)}`, file);
+  }
+
+  async getStoredLayout(filename: string) {
+    return this.request(`/layout/${encodeURIComponent(filename)}`);
+  }
+
+  // Palettes and legends
+  async getPalettePresets() {
+    return this.request('/palettes');
+  }
+
+  async getPalette(filename: string) {
+    return this.request(`/palettes/${encodeURIComponent(filename)}`);
+  }
+
+  async setPalette(filename: string, choice: {
+    categorical?: string;
+    continuous?: string;
+    population_overrides?: Record<number, string>;
+  }) {
+    return this.request(`/palettes/${encodeURIComponent(filename)}`, {
+      method: 'POST',
+      body: JSON.stringify(choice),
+    });
+  }
+
+  async getAxes(filename: string, options: {
+    start?: number;
+    end?: number;
+    num_ticks?: number;
+    locale?: string;
+    generation_time?: number;
+    log_time?: boolean;
+  } = {}) {
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value !== undefined) params.append(key, String(value));
+    });
+    const query = params.toString();
+    return this.request(`/axes/${encodeURIComponent(filename)}${query ? `?${query}` : ''}`);
+  }
+
+  async getLegend(filename: string, params: {
+    color_by: 'population' | 'time' | 'statistic' | 'category';
+    values?: Record<number, number | null>;
+    attribute?: string; // A derived node attribute (name or expression), instead of values
+    statistic_name?: string;
+    num_bins?: number;
+    log_scale?: boolean;
+  }) {
+    return this.request(`/legend/${encodeURIComponent(filename)}`, {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  // Accessibility
+  async describeNode(filename: string, nodeId: number) {
+    return this.request(`/describe/${encodeURIComponent(filename)}/node/${nodeId}`);
+  }
+
+  async describeTree(filename: string, treeIndex: number) {
+    return this.request(`/describe/${encodeURIComponent(filename)}/tree/${treeIndex}`);
+  }
+
+  async describeBreakpoint(filename: string, treeIndex: number) {
+    return this.request(`/describe/${encodeURIComponent(filename)}/breakpoint/${treeIndex}`);
+  }
+
+  async getNavigationOrder(filename: string, treeIndex?: number) {
+    const query = treeIndex !== undefined ? `?tree_index=${treeIndex}` : '';
+    return this.request(`/navigation-order/${encodeURIComponent(filename)}${query}`);
+  }
+
+  // Guided tours
+  async getTours(filename?: string) {
+    const query = filename ? `?filename=${encodeURIComponent(filename)}` : '';
+    return this.request(`/tours${query}`);
+  }
+
+  async updateTour(tourId: string, action: 'start' | 'advance' | 'back' | 'dismiss' | 'reset') {
+    return this.request(`/tours/${encodeURIComponent(tourId)}/${action}`, { method: 'POST' });
+  }
+
+  async loadTourSampleData() {
+    return this.request('/tours/sample-data', { method: 'POST' });
+  }
+
+  // Background jobs
+  async submitJob(params: {
+    operation:
+      | 'node_metrics'
+      | 'polytomy_stats'
+      | 'topology_blocks'
+      | 'unique_topologies'
+      | 'sweep_scan'
+      | 'introgression'
+      | 'ne_through_time'
+      | 'tmrca_matrix'
+      | 'sample_embedding'
+      | 'redate'
+      | 'relax_neighborhood'
+      | 'resolve_polytomies'
+      | 'simplify'
+      | 'export_tree_sequence'
+      | 'benchmark'; // local launches only
+    filename?: string;
+    params?: Record<string, unknown>;
+  }) {
+    return this.request('/jobs', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async listJobs() {
+    return this.request('/jobs');
+  }
+
+  async getJob(jobId: string) {
+    return this.request(`/jobs/${encodeURIComponent(jobId)}`);
+  }
+
+  async cancelJob(jobId: string) {
+    return this.request(`/jobs/${encodeURIComponent(jobId)}/cancel`, { method: 'POST' });
+  }
+
+  // The file written by a completed export_tree_sequence job (available once)
+  async downloadJobFile(jobId: string): Promise<Blob> {
+    const response = await fetch(`${this.baseURL}/jobs/${encodeURIComponent(jobId)}/download`);
+    if (!response.ok) {
+      throw new Error(ERROR_MESSAGES.DOWNLOAD_FAILED);
+    }
+    return response.blob();
+  }
+
+  // Batch processing
+  async submitBatch(params: {
+    filenames: string[];
+    steps?: Array<'validate' | 'simplify' | 'layout' | 'export_tables' | 'export_image'>;
+    workers?: number;
+    keep_unary?: boolean;
+    layout_iterations?: number;
+    image_format?: 'png' | 'svg';
+  }) {
+    return this.request('/batch', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  getBatchDownloadUrl(jobId: string) {
+    return `${this.baseURL}/batch/${encodeURIComponent(jobId)}/download`;
+  }
+
+  // Reference genome
+  // Server paths are only accepted by a local launch; hosted clients upload instead
+  async loadReference(params: { fasta_path: string; fai_path?: string; gff_path?: string }) {
+    return this.request('/reference/load', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async uploadReference(fasta: File, options: { fai?: File; gff?: File } = {}) {
+    const formData = new FormData();
+    formData.append('fasta', fasta);
+    if (options.fai) formData.append('fai', options.fai);
+    if (options.gff) formData.append('gff', options.gff);
+    return this.postForm('/reference/upload', formData, 'Failed to upload reference');
+  }
+
+  async getReference() {
+    return this.request('/reference');
+  }
+
+  async getReferenceSequence(start: number, end: number, contig?: string) {
+    const params = new URLSearchParams({ start: start.toString(), end: end.toString() });
+    if (contig) params.append('contig', contig);
+    return this.request(`/reference/sequence?${params}`);
+  }
+
+  async getReferenceMutationContext(
+    filename: string,
+    options: { start?: number; end?: number; contig?: string; offset?: number } = {}
+  ) {
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value !== undefined) params.append(key, value.toString());
+    });
+    return this.request(`/reference/mutations/${encodeURIComponent(filename)}?${params}`);
+  }
+
+  // Liftover between assemblies
+  async uploadLiftoverChain(file: File) {
+    const formData = new FormData();
+    formData.append('file', file);
+    return this.postForm('/liftover/chain', formData, 'Failed to upload chain file');
+  }
+
+  // Local launches only, like loadReference
+  async loadLiftoverChain(chainPath: string) {
+    return this.request('/liftover/chain/load', {
+      method: 'POST',
+      body: JSON.stringify({ chain_path: chainPath }),
+    });
+  }
+
+  async getLiftoverChain() {
+    return this.request('/liftover/chain');
+  }
+
+  async liftover(params: {
+    intervals?: Array<{ chrom: string; start?: number; end?: number; position?: number; [key: string]: unknown }>;
+    bed?: string;
+    min_match?: number;
+    target_chrom?: string;
+  }) {
+    return this.request('/liftover', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  // Derived-result archive
+  async archiveResult(params: {
+    filename: string;
+    kind: 'statistic' | 'layout' | 'export';
+    name: string;
+    value?: unknown;
+    job_id?: string;
+    operation?: string;
+    params?: Record<string, unknown>;
+  }) {
+    return this.request('/archive', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async listArchivedResults(options: { filename?: string; kind?: 'statistic' | 'layout' | 'export' } = {}) {
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value !== undefined) params.append(key, value);
+    });
+    return this.request(`/archive?${params}`);
+  }
+
+  async openArchivedResult(entryId: string) {
+    return this.request(`/archive/${encodeURIComponent(entryId)}`);
+  }
+
+  async deleteArchivedResult(entryId: string) {
+    return this.request(`/archive/${encodeURIComponent(entryId)}`, { method: 'DELETE' });
+  }
+
+  async clearArchive() {
+    return this.request('/archive', { method: 'DELETE' });
+  }
+
+  // Recombination and mutation rate maps
+  async uploadRateMap(kind: 'recombination' | 'mutation', file: File) {
+    const formData = new FormData();
+    formData.append('file', file);
+    return this.postForm(`/rate-maps/${kind}/upload`, formData, `Failed to upload ${kind} map`);
+  }
+
+  // Local launches only, like loadReference
+  async loadRateMap(kind: 'recombination' | 'mutation', path: string) {
+    return this.request(`/rate-maps/${kind}/load`, {
+      method: 'POST',
+      body: JSON.stringify({ path }),
+    });
+  }
+
+  async getRateMaps() {
+    return this.request('/rate-maps');
+  }
+
+  async getRateMapWindows(
+    kind: 'recombination' | 'mutation',
+    options: { start?: number; end?: number; num_windows?: number; contig?: string; filename?: string } = {}
+  ) {
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value !== undefined) params.append(key, value.toString());
+    });
+    return this.request(`/rate-maps/${kind}/windows?${params}`);
+  }
+
+  async removeRateMap(kind: 'recombination' | 'mutation') {
+    return this.request(`/rate-maps/${kind}`, { method: 'DELETE' });
+  }
+
+  // Published datasets
+  async lookupDataset(reference: string) {
+    return this.request('/datasets/lookup', {
+      method: 'POST',
+      body: JSON.stringify({ reference }),
+    });
+  }
+
+  async downloadDataset(reference: string, fileName: string) {
+    return this.request('/datasets/download', {
+      method: 'POST',
+      body: JSON.stringify({ reference, file_name: fileName }),
+    });
+  }
+
+  // Remote files (S3, GCS, HTTPS)
+  async probeRemoteFile(url: string, credentials?: RemoteCredentials) {
+    return this.request('/remote/probe', {
+      method: 'POST',
+      body: JSON.stringify({ url, credentials }),
+    });
+  }
+
+  async openRemoteFile(url: string, credentials?: RemoteCredentials) {
+    return this.request('/remote/open', {
+      method: 'POST',
+      body: JSON.stringify({ url, credentials }),
+    });
+  }
+
+  // Download cache
+  async listDownloadCache() {
+    return this.request('/download-cache');
+  }
+
+  async openCachedDownload(sha256: string) {
+    return this.request(`/download-cache/${encodeURIComponent(sha256)}/open`, { method: 'POST' });
+  }
+
+  async pinCachedDownload(sha256: string, pinned: boolean = true) {
+    return this.request(`/download-cache/${encodeURIComponent(sha256)}/pin`, {
+      method: 'POST',
+      body: JSON.stringify({ pinned }),
+    });
+  }
+
+  async evictCachedDownload(sha256: string) {
+    return this.request(`/download-cache/${encodeURIComponent(sha256)}`, { method: 'DELETE' });
+  }
+
+  async clearDownloadCache(includePinned: boolean = false) {
+    return this.request(`/download-cache?include_pinned=${includePinned}`, { method: 'DELETE' });
+  }
+
+  // Audit trail
+  async listAuditLogs() {
+    return this.request('/audit');
+  }
+
+  async getFileAuditLog(filename: string) {
+    return this.request(`/audit/file/${encodeURIComponent(filename)}`);
+  }
+
+  async exportAuditLog(auditId: string): Promise<Blob> {
+    const endpoint = `/audit/${encodeURIComponent(auditId)}/export`;
+    log.api.call(endpoint, 'GET');
+
+    const response = await fetch(`${this.baseURL}${endpoint}`);
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'GET');
+      throw error;
+    }
+
+    const blob = await response.blob();
+    log.api.success(endpoint, 'GET', { size: blob.size });
+    return blob;
+  }
+
+  async deleteAuditLog(auditId: string) {
+    return this.request(`/audit/${encodeURIComponent(auditId)}`, { method: 'DELETE' });
+  }
+
+  // Genome browser hand-off
+  async getGenomeBrowserLinks(params: GenomeBrowserParams) {
+    return this.request('/genome-browser/links', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  async downloadIgvBatchScript(params: GenomeBrowserParams & { snapshot_directory?: string }): Promise<Blob> {
+    const endpoint = '/genome-browser/igv-batch';
+    log.api.call(endpoint, 'POST', { filename: params.filename });
+
+    const response = await fetch(`${this.baseURL}${endpoint}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(params),
+    });
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => null);
+      const error = new Error(errorData?.detail || ERROR_MESSAGES.DOWNLOAD_FAILED);
+      log.api.error(endpoint, error, 'POST');
+      throw error;
+    }
+
+    log.api.success(endpoint, 'POST');
+    return response.blob();
+  }
+
+  async parseGenomeRegion(params: { filename: string; region: string; contig?: string; offset?: number }) {
+    return this.request('/genome-browser/parse-region', {
+      method: 'POST',
+      body: JSON.stringify(params),
+    });
+  }
+
+  // Event bridge for external tools
+  async getEventBridgeState() {
+    return this.request('/events');
+  }
+
+  async publishBridgeMessage(message: BridgeMessage) {
+    return this.request('/events', {
+      method: 'POST',
+      body: JSON.stringify(message),
+    });
+  }
+
+  /**
+   * Connect to the event bridge WebSocket as a viewer.
+   * Returns a function that sends a message and one that closes the socket.
+   */
+  connectEventBridge(handlers: { onMessage: (message: BridgeMessage) => void; onOpen?: () => void; onClose?: () => void }) {
+    const url = new URL(`${this.baseURL}/events/ws`, window.location.href);
+    url.protocol = url.protocol === 'https:' ? 'wss:' : 'ws:';
+    url.searchParams.set('client', 'viewer');
+    const socket = new WebSocket(url.toString());
+
+    socket.onmessage = (event) => {
+      try {
+        handlers.onMessage(JSON.parse(event.data));
+      } catch (error) {
+        log.warn('Ignoring malformed event bridge message', {
+          component: 'ApiService',
+          error: error instanceof Error ? error : new Error(String(error))
+        });
+      }
+    };
+    socket.onopen = () => handlers.onOpen?.();
+    socket.onclose = () => handlers.onClose?.();
+
+    return {
+      send: (message: BridgeMessage) => {
+        if (socket.readyState === WebSocket.OPEN) socket.send(JSON.stringify(message));
+      },
+      close: () => {
+        socket.onclose = null;
+        socket.close();
+      },
+    };
+  }
+  // Collaborative viewing rooms
+  async createCollabRoom(filename?: string) {
+    return this.request('/collab/rooms', {
+      method: 'POST',
+      body: JSON.stringify({ filename }),
+    });
+  }
+
+  async getCollabRoom(roomId: string) {
+    return this.request(`/collab/rooms/${encodeURIComponent(roomId)}`);
+  }
+
+  async joinCollabRoom(roomId: string) {
+    return this.request(`/collab/rooms/${encodeURIComponent(roomId)}/join`, { method: 'POST' });
+  }
+
+  async closeCollabRoom(roomId: string, token: string) {
+    return this.request(`/collab/rooms/${encodeURIComponent(roomId)}?token=${encodeURIComponent(token)}`, { method: 'DELETE' });
+  }
+
+  /**
+   * Connect to a collaboration room, as its presenter when a token is given.
+   * Presenters send their full view state; the server diffs it for followers.
+   */
+  connectCollab(roomId: string, handlers: { onMessage: (message: CollabMessage) => void; onOpen?: () => void; onClose?: () => void }, presenterToken?: string) {
+    const url = new URL(`${this.baseURL}/collab/ws/${encodeURIComponent(roomId)}`, window.location.href);
+    url.protocol = url.protocol === 'https:' ? 'wss:' : 'ws:';
+    url.searchParams.set('role', presenterToken ? 'presenter' : 'follower');
+    if (presenterToken) url.searchParams.set('token', presenterToken);
+    const socket = new WebSocket(url.toString());
+
+    socket.onmessage = (event) => {
+      try {
+        handlers.onMessage(JSON.parse(event.data));
+      } catch (error) {
+        log.warn('Ignoring malformed collaboration message', {
+          component: 'ApiService',
+          error: error instanceof Error ? error : new Error(String(error))
+        });
+      }
+    };
+    socket.onopen = () => handlers.onOpen?.();
+    socket.onclose = () => handlers.onClose?.();
+
+    const send = (message: Record<string, unknown>) => {
+      if (socket.readyState === WebSocket.OPEN) socket.send(JSON.stringify(message));
+    };
+    return {
+      sendState: (state: Record<string, unknown>) => send({ type: 'state', state }),
+      resync: () => send({ type: 'resync' }),
+      close: () => {
+        socket.onclose = null;
+        socket.close();
+      },
+    };
+  }
+}
+
+// Create singleton instance
+export const apiService = new ApiService();
+
+// Export convenience functions
+export const api = {
+  // Session management
+  getCurrentSession: () => apiService.getCurrentSession(),
+  getSessionSnapshot: () => apiService.getSessionSnapshot(),
+  saveSessionSnapshot: (uiState: Record<string, unknown>) => apiService.saveSessionSnapshot(uiState),
+  getSessionReplay: () => apiService.getSessionReplay(),
+  createViewLink: (filename: string, state: ViewLinkState) => apiService.createViewLink(filename, state),
+  resolveViewLink: (code: string) => apiService.resolveViewLink(code),
+
+  getCapabilities: () => apiService.getCapabilities(),
+  getNetworkStatus: () => apiService.getNetworkStatus(),
+  getInboxStatus: () => apiService.getInboxStatus(),
+  getInboxEvents: (after?: number) => apiService.getInboxEvents(after),
+  testNetworkConnection: (url: string) => apiService.testNetworkConnection(url),
+
+  // Settings
+  getSettings: () => apiService.getSettings(),
+  previewDiagnostics: (params: DiagnosticsParams) => apiService.previewDiagnostics(params),
+  saveDiagnostics: (params: DiagnosticsParams) => apiService.saveDiagnostics(params),
+  submitDiagnostics: (params: DiagnosticsParams) => apiService.submitDiagnostics(params),
+  getReadiness: () => apiService.getReadiness(),
+  getCrashReport: () => apiService.getCrashReport(),
+  dismissCrashReport: () => apiService.dismissCrashReport(),
+  getFormattingConfig: () => apiService.getFormattingConfig(),
+  getMessageCatalog: (locale: string) => apiService.getMessageCatalog(locale),
+  updateSettings: (updates: Record<string, unknown>) => apiService.updateSettings(updates),
+  getHooks: () => apiService.getHooks(),
+  saveHooks: (hooks: Hook[]) => apiService.saveHooks(hooks),
+  testHook: (hook: Hook, values?: Record<string, unknown>, run?: boolean) => apiService.testHook(hook, values, run),
+  getResourceStatus: () => apiService.getResourceStatus(),
+  getBackendStatus: () => apiService.getBackendStatus(),
+  stopBackend: () => apiService.stopBackend(),
+  restartBackend: () => apiService.restartBackend(),
+  getSystemCapabilities: () => apiService.getSystemCapabilities(),
+  getSetup: () => apiService.getSetup(),
+  checkSetupPath: (path: string) => apiService.checkSetupPath(path),
+  completeSetup: (choices: Record<string, unknown>) => apiService.completeSetup(choices),
+  getPerformanceMetrics: () => apiService.getPerformanceMetrics(),
+  resetPerformanceMetrics: () => apiService.resetPerformanceMetrics(),
+  
+  // Tree sequence operations
+  uploadTreeSequence: (file: File, options?: { protected?: boolean; background?: boolean }) => apiService.uploadTreeSequence(file, options),
+  upgradeTreeSequence: (filename: string) => apiService.upgradeTreeSequence(filename),
+  importArg: (files: File[], options?: { protected?: boolean }) => apiService.importArg(files, options),
+  setFileProtected: (filename: string, isProtected?: boolean) => apiService.setFileProtected(filename, isProtected),
+  getUploadedFiles: () => apiService.getUploadedFiles(),
+  getDerivedDataUsage: () => apiService.getDerivedDataUsage(),
+  clearDerivedData: () => apiService.clearDerivedData(),
+  getTreeSequenceMetadata: (filename: string) => apiService.getTreeSequenceMetadata(filename),
+  deleteTreeSequence: (filename: string) => apiService.deleteTreeSequence(filename),
+  downloadTreeSequence: (filename: string, format: 'trees' | 'tsz' = 'trees') =>
+    apiService.downloadTreeSequence(filename, format),
+  
+  // Data retrieval
+  getGraphData: (filename: string, options?: Parameters<typeof apiService.getGraphData>[1]) => 
+    apiService.getGraphData(filename, options),
+  prefetchGraphData: (filename: string, options: Parameters<typeof apiService.prefetchGraphData>[1]) =>
+    apiService.prefetchGraphData(filename, options),
+  streamGraphData: (...args: Parameters<typeof apiService.streamGraphData>) =>
+    apiService.streamGraphData(...args),
+  getPopulationGraphData: (filename: string, collapsePureClades?: boolean) =>
+    apiService.getPopulationGraphData(filename, collapsePureClades),
+  getNodeAttributes: (filename: string) => apiService.getNodeAttributes(filename),
+  saveNodeAttribute: (filename: string, name: string, expression: string | null) =>
+    apiService.saveNodeAttribute(filename, name, expression),
+  evaluateNodeAttribute: (filename: string, expression: string) => apiService.evaluateNodeAttribute(filename, expression),
+  filterNodesByAttribute: (filename: string, expression: string) => apiService.filterNodesByAttribute(filename, expression),
+  getNodeMetrics: (filename: string, options?: Parameters<typeof apiService.getNodeMetrics>[1]) =>
+    apiService.getNodeMetrics(filename, options),
+  
+  // Location inference
+  inferLocationsFast: (params: Parameters<typeof apiService.inferLocationsFast>[0]) => 
+    apiService.inferLocationsFast(params),
+  inferLocationsSparg: (params: Parameters<typeof apiService.inferLocationsSparg>[0]) =>
+    apiService.inferLocationsSparg(params),
+  inferLocationsGaiaQuadratic: (params: Parameters<typeof apiService.inferLocationsGaiaQuadratic>[0]) =>
+    apiService.inferLocationsGaiaQuadratic(params),
+  inferLocationsGaiaLinear: (params: Parameters<typeof apiService.inferLocationsGaiaLinear>[0]) =>
+    apiService.inferLocationsGaiaLinear(params),
+  inferLocationsMidpoint: (params: Parameters<typeof apiService.inferLocationsMidpoint>[0]) =>
+    apiService.inferLocationsMidpoint(params),
+
+  // Tree sequence simulation
+  simulateTreeSequence: (params: Parameters<typeof apiService.simulateTreeSequence>[0]) =>
+    apiService.simulateTreeSequence(params),
+  parseDemesModel: (model: string, filename?: string) => apiService.parseDemesModel(model, filename),
+
+  // Custom location operations
+  uploadLocationCSV: (file: File, csvType: 'sample_locations' | 'node_locations') =>
+    apiService.uploadLocationCSV(file, csvType),
+  updateTreeSequenceLocations: (params: Parameters<typeof apiService.updateTreeSequenceLocations>[0]) =>
+    apiService.updateTreeSequenceLocations(params),
 
   // Geographic operations
   getAvailableCRS: () => apiService.getAvailableCRS(),
@@ -410,4 +2216,191 @@ export const api = {
   // Temporal inference
   inferTimesTsdate: (params: TsdateInferenceRequest) =>
     apiService.inferTimesTsdate(params),
+
+  // Tree sequence transformations
+  getPolytomyStats: (filename: string) => apiService.getPolytomyStats(filename),
+  getTopologyBlocks: (filename: string, tolerance?: number) => apiService.getTopologyBlocks(filename, tolerance),
+  getUniqueTopologies: (filename: string) => apiService.getUniqueTopologies(filename),
+  getWindowedStatistic: (filename: string, options: Parameters<typeof apiService.getWindowedStatistic>[1]) =>
+    apiService.getWindowedStatistic(filename, options),
+  getSampleSets: (filename: string) => apiService.getSampleSets(filename),
+  saveSampleSet: (filename: string, name: string, definition: SampleSetDefinition | null) =>
+    apiService.saveSampleSet(filename, name, definition),
+  getPopulations: (filename: string) => apiService.getPopulations(filename),
+  editPopulations: (filename: string, edits: Parameters<typeof apiService.editPopulations>[1], order?: number[]) =>
+    apiService.editPopulations(filename, edits, order),
+  getTimeUnits: (filename: string) => apiService.getTimeUnits(filename),
+  setTimeCalibration: (filename: string, calibration: Parameters<typeof apiService.setTimeCalibration>[1]) =>
+    apiService.setTimeCalibration(filename, calibration),
+  getMissingData: (filename: string) => apiService.getMissingData(filename),
+  getMissingnessTrack: (filename: string, options?: Parameters<typeof apiService.getMissingnessTrack>[1]) =>
+    apiService.getMissingnessTrack(filename, options),
+  getTracks: (filename: string) => apiService.getTracks(filename),
+  registerTrack: (filename: string, name: string, source: Parameters<typeof apiService.registerTrack>[2], params?: Record<string, unknown>) =>
+    apiService.registerTrack(filename, name, source, params),
+  uploadTrack: (filename: string, file: File, options?: Parameters<typeof apiService.uploadTrack>[2]) =>
+    apiService.uploadTrack(filename, file, options),
+  mergeTracks: (filename: string, name: string, tracks: string[], operation?: Parameters<typeof apiService.mergeTracks>[3]) =>
+    apiService.mergeTracks(filename, name, tracks, operation),
+  getTrack: (filename: string, name: string, viewport?: Parameters<typeof apiService.getTrack>[2]) =>
+    apiService.getTrack(filename, name, viewport),
+  exportTrack: (filename: string, name: string, options?: Parameters<typeof apiService.exportTrack>[2]) =>
+    apiService.exportTrack(filename, name, options),
+  deleteTrack: (filename: string, name: string) => apiService.deleteTrack(filename, name),
+  getSweepScan: (filename: string, options?: Parameters<typeof apiService.getSweepScan>[1]) =>
+    apiService.getSweepScan(filename, options),
+  detectIntrogression: (params: IntrogressionParams) => apiService.detectIntrogression(params),
+  exportIntrogressionCsv: (params: IntrogressionParams) => apiService.exportIntrogressionCsv(params),
+  estimateNe: (params: Parameters<typeof apiService.estimateNe>[0]) => apiService.estimateNe(params),
+  getTmrcaMatrix: (params: Parameters<typeof apiService.getTmrcaMatrix>[0]) => apiService.getTmrcaMatrix(params),
+  getSampleEmbedding: (params: Parameters<typeof apiService.getSampleEmbedding>[0]) =>
+    apiService.getSampleEmbedding(params),
+  validateRedating: (params: Parameters<typeof apiService.validateRedating>[0]) => apiService.validateRedating(params),
+  compareRedatedTimes: (before: string, after: string) => apiService.compareRedatedTimes(before, after),
+  resolvePolytomies: (params: Parameters<typeof apiService.resolvePolytomies>[0]) =>
+    apiService.resolvePolytomies(params),
+  getDisplayTimes: (filename: string, mode?: Parameters<typeof apiService.getDisplayTimes>[1], generationTime?: number) =>
+    apiService.getDisplayTimes(filename, mode, generationTime),
+  getSimulationContext: (filename: string) => apiService.getSimulationContext(filename),
+  getMetadataSchemas: (filename: string) => apiService.getMetadataSchemas(filename),
+  getTableMetadata: (filename: string, table: string, options?: Parameters<typeof apiService.getTableMetadata>[2]) =>
+    apiService.getTableMetadata(filename, table, options),
+  getTransformStack: (filename: string) => apiService.getTransformStack(filename),
+  applyTransformStack: (filename: string, steps: TransformStep[]) => apiService.applyTransformStack(filename, steps),
+  getContigs: (filename: string) => apiService.getContigs(filename),
+  setContigs: (filename: string, contigs: Parameters<typeof apiService.setContigs>[1]) => apiService.setContigs(filename, contigs),
+  getNodeMetadata: (params: Parameters<typeof apiService.getNodeMetadata>[0]) => apiService.getNodeMetadata(params),
+  editMetadata: (params: Parameters<typeof apiService.editMetadata>[0]) => apiService.editMetadata(params),
+
+  // Layout operations
+  relaxLayoutNeighborhood: (params: Parameters<typeof apiService.relaxLayoutNeighborhood>[0]) =>
+    apiService.relaxLayoutNeighborhood(params),
+  bundleEdges: (params: Parameters<typeof apiService.bundleEdges>[0]) => apiService.bundleEdges(params),
+  placeLabels: (params: Parameters<typeof apiService.placeLabels>[0]) => apiService.placeLabels(params),
+  buildPickIndex: (filename: string, positions: Record<number, number[]>) => apiService.buildPickIndex(filename, positions),
+  pick: (...args: Parameters<typeof apiService.pick>) => apiService.pick(...args),
+  reportFrameStats: (filename: string, fps: number, targetFps?: number) => apiService.reportFrameStats(filename, fps, targetFps),
+  getRenderLod: (filename: string) => apiService.getRenderLod(filename),
+  resetRenderLod: (filename: string) => apiService.resetRenderLod(filename),
+  buildTileSet: (filename: string, positions: Record<number, number[]>) => apiService.buildTileSet(filename, positions),
+  tileUrl: (...args: Parameters<typeof apiService.tileUrl>) => apiService.tileUrl(...args),
+  exportLayout: (params: Parameters<typeof apiService.exportLayout>[0]) =>
+    apiService.exportLayout(params),
+  streamLayout3D: (...args: Parameters<typeof apiService.streamLayout3D>) =>
+    apiService.streamLayout3D(...args),
+  downloadLayout3D: (filename: string, options?: Parameters<typeof apiService.downloadLayout3D>[1]) =>
+    apiService.downloadLayout3D(filename, options),
+  estimateExport: (params: Parameters<typeof apiService.estimateExport>[0]) =>
+    apiService.estimateExport(params),
+  exportSvg: (params: Parameters<typeof apiService.exportSvg>[0]) => apiService.exportSvg(params),
+  exportHtmlBundle: (params: Parameters<typeof apiService.exportHtmlBundle>[0]) => apiService.exportHtmlBundle(params),
+  getDefaultFigureTemplate: () => apiService.getDefaultFigureTemplate(),
+  validateFigureTemplate: (template: FigureTemplateSource) => apiService.validateFigureTemplate(template),
+  renderFigure: (params: Parameters<typeof apiService.renderFigure>[0]) => apiService.renderFigure(params),
+  importLayout: (filename: string, file: File) => apiService.importLayout(filename, file),
+  getStoredLayout: (filename: string) => apiService.getStoredLayout(filename),
+  getPalettePresets: () => apiService.getPalettePresets(),
+  getPalette: (filename: string) => apiService.getPalette(filename),
+  setPalette: (filename: string, choice: Parameters<typeof apiService.setPalette>[1]) =>
+    apiService.setPalette(filename, choice),
+  getAxes: (filename: string, options?: Parameters<typeof apiService.getAxes>[1]) =>
+    apiService.getAxes(filename, options),
+  getLegend: (filename: string, params: Parameters<typeof apiService.getLegend>[1]) =>
+    apiService.getLegend(filename, params),
+
+  // Accessibility
+  describeNode: (filename: string, nodeId: number) => apiService.describeNode(filename, nodeId),
+  describeTree: (filename: string, treeIndex: number) => apiService.describeTree(filename, treeIndex),
+  describeBreakpoint: (filename: string, treeIndex: number) => apiService.describeBreakpoint(filename, treeIndex),
+  getNavigationOrder: (filename: string, treeIndex?: number) => apiService.getNavigationOrder(filename, treeIndex),
+
+  // Guided tours
+  getTours: (filename?: string) => apiService.getTours(filename),
+  updateTour: (tourId: string, action: Parameters<typeof apiService.updateTour>[1]) =>
+    apiService.updateTour(tourId, action),
+  loadTourSampleData: () => apiService.loadTourSampleData(),
+
+  // Background jobs
+  submitJob: (params: Parameters<typeof apiService.submitJob>[0]) => apiService.submitJob(params),
+  listJobs: () => apiService.listJobs(),
+  getJob: (jobId: string) => apiService.getJob(jobId),
+  cancelJob: (jobId: string) => apiService.cancelJob(jobId),
+  downloadJobFile: (jobId: string) => apiService.downloadJobFile(jobId),
+
+  // Batch processing
+  submitBatch: (params: Parameters<typeof apiService.submitBatch>[0]) => apiService.submitBatch(params),
+  getBatchDownloadUrl: (jobId: string) => apiService.getBatchDownloadUrl(jobId),
+
+  // Reference genome
+  loadReference: (params: Parameters<typeof apiService.loadReference>[0]) => apiService.loadReference(params),
+  uploadReference: (fasta: File, options?: { fai?: File; gff?: File }) => apiService.uploadReference(fasta, options),
+  getReference: () => apiService.getReference(),
+  getReferenceSequence: (start: number, end: number, contig?: string) =>
+    apiService.getReferenceSequence(start, end, contig),
+  getReferenceMutationContext: (filename: string, options?: Parameters<typeof apiService.getReferenceMutationContext>[1]) =>
+    apiService.getReferenceMutationContext(filename, options),
+
+  // Liftover
+  uploadLiftoverChain: (file: File) => apiService.uploadLiftoverChain(file),
+  loadLiftoverChain: (chainPath: string) => apiService.loadLiftoverChain(chainPath),
+  getLiftoverChain: () => apiService.getLiftoverChain(),
+  liftover: (params: Parameters<typeof apiService.liftover>[0]) => apiService.liftover(params),
+
+  // Derived-result archive
+  archiveResult: (params: Parameters<typeof apiService.archiveResult>[0]) => apiService.archiveResult(params),
+  listArchivedResults: (options?: Parameters<typeof apiService.listArchivedResults>[0]) =>
+    apiService.listArchivedResults(options),
+  openArchivedResult: (entryId: string) => apiService.openArchivedResult(entryId),
+  deleteArchivedResult: (entryId: string) => apiService.deleteArchivedResult(entryId),
+  clearArchive: () => apiService.clearArchive(),
+
+  // Rate maps
+  uploadRateMap: (kind: 'recombination' | 'mutation', file: File) => apiService.uploadRateMap(kind, file),
+  loadRateMap: (kind: 'recombination' | 'mutation', path: string) => apiService.loadRateMap(kind, path),
+  getRateMaps: () => apiService.getRateMaps(),
+  getRateMapWindows: (kind: 'recombination' | 'mutation', options?: Parameters<typeof apiService.getRateMapWindows>[1]) =>
+    apiService.getRateMapWindows(kind, options),
+  removeRateMap: (kind: 'recombination' | 'mutation') => apiService.removeRateMap(kind),
+
+  // Published datasets
+  lookupDataset: (reference: string) => apiService.lookupDataset(reference),
+  downloadDataset: (reference: string, fileName: string) => apiService.downloadDataset(reference, fileName),
+
+  // Remote files
+  probeRemoteFile: (url: string, credentials?: Parameters<typeof apiService.probeRemoteFile>[1]) =>
+    apiService.probeRemoteFile(url, credentials),
+  openRemoteFile: (url: string, credentials?: Parameters<typeof apiService.openRemoteFile>[1]) =>
+    apiService.openRemoteFile(url, credentials),
+
+  // Download cache
+  listDownloadCache: () => apiService.listDownloadCache(),
+  openCachedDownload: (sha256: string) => apiService.openCachedDownload(sha256),
+  pinCachedDownload: (sha256: string, pinned?: boolean) => apiService.pinCachedDownload(sha256, pinned),
+  evictCachedDownload: (sha256: string) => apiService.evictCachedDownload(sha256),
+  clearDownloadCache: (includePinned?: boolean) => apiService.clearDownloadCache(includePinned),
+
+  // Audit trail
+  listAuditLogs: () => apiService.listAuditLogs(),
+  getFileAuditLog: (filename: string) => apiService.getFileAuditLog(filename),
+  exportAuditLog: (auditId: string) => apiService.exportAuditLog(auditId),
+  deleteAuditLog: (auditId: string) => apiService.deleteAuditLog(auditId),
+
+  // Genome browser hand-off
+  getGenomeBrowserLinks: (params: Parameters<typeof apiService.getGenomeBrowserLinks>[0]) =>
+    apiService.getGenomeBrowserLinks(params),
+  downloadIgvBatchScript: (params: Parameters<typeof apiService.downloadIgvBatchScript>[0]) =>
+    apiService.downloadIgvBatchScript(params),
+  parseGenomeRegion: (params: Parameters<typeof apiService.parseGenomeRegion>[0]) =>
+    apiService.parseGenomeRegion(params),
+
+  // Event bridge
+  getEventBridgeState: () => apiService.getEventBridgeState(),
+  publishBridgeMessage: (message: BridgeMessage) => apiService.publishBridgeMessage(message),
+  connectEventBridge: (handlers: Parameters<typeof apiService.connectEventBridge>[0]) =>
+    apiService.connectEventBridge(handlers),
+  createCollabRoom: (filename?: string) => apiService.createCollabRoom(filename),
+  getCollabRoom: (roomId: string) => apiService.getCollabRoom(roomId),
+  joinCollabRoom: (roomId: string) => apiService.joinCollabRoom(roomId),
+  closeCollabRoom: (roomId: string, token: string) => apiService.closeCollabRoom(roomId, token),
+  connectCollab: (...args: Parameters<typeof apiService.connectCollab>) => apiService.connectCollab(...args),
 }; 
\ No newline at end of file
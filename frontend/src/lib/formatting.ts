@@ -0,0 +1,64 @@
+import { api } from './api';
+
+/**
+ * Number and unit formatting matching the backend's argscape.backend.formatting,
+ * so values shown on screen agree with axis labels and exports.
+ */
+
+export interface FormattingConfig {
+  locale: string;
+  thousands_separator: string;
+  decimal_separator: string;
+  si_prefixes: { factor: number; prefix: string }[];
+  generation_time_years: number | null;
+}
+
+let config: FormattingConfig = {
+  locale: 'en',
+  thousands_separator: ',',
+  decimal_separator: '.',
+  si_prefixes: [
+    { factor: 1e12, prefix: 'T' },
+    { factor: 1e9, prefix: 'G' },
+    { factor: 1e6, prefix: 'M' },
+    { factor: 1e3, prefix: 'k' },
+  ],
+  generation_time_years: null,
+};
+
+/** Fetch the configured locale and generation time; defaults apply until this resolves. */
+export async function loadFormattingConfig(): Promise<FormattingConfig> {
+  const response = await api.getFormattingConfig();
+  config = response.data as FormattingConfig;
+  return config;
+}
+
+export function getFormattingConfig(): FormattingConfig {
+  return config;
+}
+
+export function formatNumber(value: number, decimals: number = 0): string {
+  const [integer, fraction] = Math.abs(value).toFixed(decimals).split('.');
+  const grouped = integer.replace(/\B(?=(\d{3})+(?!\d))/g, '\0').split('\0').join(config.thousands_separator);
+  const sign = value < 0 && Number(Math.abs(value).toFixed(decimals)) !== 0 ? '-' : '';
+  return sign + grouped + (fraction ? config.decimal_separator + fraction : '');
+}
+
+export function formatSI(value: number, unit: string = '', decimals: number = 1): string {
+  if (!Number.isFinite(value)) return String(value);
+  for (const { factor, prefix } of config.si_prefixes) {
+    if (Math.abs(value) >= factor) {
+      return `${formatNumber(value / factor, decimals)} ${prefix}${unit}`;
+    }
+  }
+  const number = formatNumber(value, Number.isInteger(value) ? 0 : decimals);
+  return unit ? `${number} ${unit}` : number;
+}
+
+/** A node time with its unit, converted to years when a generation time is configured. */
+export function formatTime(value: number, timeUnits: string = 'generations', decimals: number = 0): string {
+  if (config.generation_time_years !== null && timeUnits === 'generations') {
+    return `${formatNumber(value * config.generation_time_years, decimals)} years`;
+  }
+  return `${formatNumber(value, decimals)} ${timeUnits}`;
+}
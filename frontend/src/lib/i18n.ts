@@ -0,0 +1,34 @@
+import { api } from './api';
+import { log } from './logger';
+
+/**
+ * UI message catalog. Messages are loaded from the backend (bundled catalogs
+ * plus overrides in the user's config directory); until then, or for missing
+ * keys, the key itself is shown.
+ */
+
+let messages: Record<string, string> = {};
+let currentLocale = 'en';
+
+export async function loadMessages(locale: string): Promise<void> {
+  try {
+    const response = await api.getMessageCatalog(locale);
+    messages = (response.data as { messages: Record<string, string> }).messages;
+    currentLocale = locale;
+  } catch (error) {
+    log.warn(`Could not load messages for locale ${locale}`, {
+      component: 'i18n',
+      error: error instanceof Error ? error : new Error(String(error))
+    });
+  }
+}
+
+export function getLocale(): string {
+  return currentLocale;
+}
+
+/** Look up a message, replacing `{name}` placeholders with `params`. */
+export function t(key: string, params: Record<string, string | number> = {}): string {
+  const template = messages[key] ?? key;
+  return template.replace(/\{(\w+)\}/g, (match, name) => (name in params ? String(params[name]) : match));
+}
@@ -0,0 +1,99 @@
+/**
+ * Background job progress outside the page: a percentage in the window title
+ * (shown on taskbar buttons and tabs), a progress ring drawn on the favicon,
+ * and the app badge (dock/taskbar icon) when the app is installed.
+ */
+
+interface ActiveJob {
+  status: string;
+  progress: number | null;
+}
+
+const FAVICON_SIZE = 64;
+const FAVICON_HREF = '/favicon.svg';
+const RING_COLOR = '#2ca02c';
+
+let baseTitle: string | null = null;
+let faviconImage: HTMLImageElement | null = null;
+let lastSignature = '';
+let ringFraction: number | null = null;
+
+type BadgingNavigator = Navigator & {
+  setAppBadge?: (count?: number) => Promise<void>;
+  clearAppBadge?: () => Promise<void>;
+};
+
+function getFaviconLinks(): HTMLLinkElement[] {
+  return Array.from(document.querySelectorAll<HTMLLinkElement>('link[rel~="icon"]'));
+}
+
+function setFavicon(href: string) {
+  for (const link of getFaviconLinks()) {
+    link.href = href;
+  }
+}
+
+function drawProgressFavicon(fraction: number | null) {
+  ringFraction = fraction;
+  const draw = () => {
+    if (!lastSignature) return;
+    const canvas = document.createElement('canvas');
+    canvas.width = canvas.height = FAVICON_SIZE;
+    const context = canvas.getContext('2d');
+    if (!context || !faviconImage) return;
+
+    context.drawImage(faviconImage, 0, 0, FAVICON_SIZE, FAVICON_SIZE);
+    const center = FAVICON_SIZE / 2;
+    context.lineWidth = FAVICON_SIZE / 8;
+    context.strokeStyle = 'rgba(0, 0, 0, 0.25)';
+    context.beginPath();
+    context.arc(center, center, center - context.lineWidth / 2, 0, 2 * Math.PI);
+    context.stroke();
+    // Unknown progress shows a quarter arc
+    const sweep = ringFraction === null ? 0.25 : ringFraction;
+    context.strokeStyle = RING_COLOR;
+    context.beginPath();
+    context.arc(center, center, center - context.lineWidth / 2, -Math.PI / 2, -Math.PI / 2 + sweep * 2 * Math.PI);
+    context.stroke();
+    setFavicon(canvas.toDataURL('image/png'));
+  };
+
+  if (faviconImage?.complete) {
+    draw();
+  } else if (!faviconImage) {
+    faviconImage = new Image();
+    faviconImage.onload = draw;
+    faviconImage.src = FAVICON_HREF;
+  }
+}
+
+/**
+ * Reflect the state of the session's running jobs. Overall progress is the mean
+ * of the jobs that report it; with no running jobs everything is restored.
+ */
+export function showJobProgress(jobs: ActiveJob[]) {
+  const running = jobs.filter((job) => job.status === 'pending' || job.status === 'running');
+  const reported = running.map((job) => job.progress).filter((p): p is number => p !== null);
+  const fraction = reported.length ? reported.reduce((a, b) => a + b, 0) / reported.length : null;
+
+  const signature = running.length ? `${running.length}:${fraction === null ? '-' : Math.round(fraction * 100)}` : '';
+  if (signature === lastSignature) return;
+  lastSignature = signature;
+
+  if (baseTitle === null) baseTitle = document.title;
+  const badging = navigator as BadgingNavigator;
+
+  if (!running.length) {
+    document.title = baseTitle;
+    baseTitle = null;
+    setFavicon(FAVICON_HREF);
+    badging.clearAppBadge?.().catch(() => undefined);
+    return;
+  }
+
+  const label = fraction === null ? '…' : `${Math.round(fraction * 100)}%`;
+  const jobsLabel = running.length > 1 ? ` ${running.length} jobs` : '';
+  document.title = `[${label}${jobsLabel}] ${baseTitle}`;
+  drawProgressFavicon(fraction);
+  badging.setAppBadge?.(running.length).catch(() => undefined);
+}
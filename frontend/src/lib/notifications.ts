@@ -0,0 +1,32 @@
+import { log } from './logger';
+
+/**
+ * Desktop notifications through the browser Notification API.
+ * Permission is requested on first use; when it is denied or unsupported the
+ * message is only logged.
+ */
+export async function notifyUser(title: string, body: string, onClick?: () => void): Promise<boolean> {
+  if (typeof Notification === 'undefined') {
+    log.info(`${title}: ${body}`, { component: 'notifications' });
+    return false;
+  }
+
+  let permission = Notification.permission;
+  if (permission === 'default') {
+    permission = await Notification.requestPermission();
+  }
+  if (permission !== 'granted') {
+    log.info(`${title}: ${body}`, { component: 'notifications' });
+    return false;
+  }
+
+  const notification = new Notification(title, { body, icon: '/favicon.svg' });
+  if (onClick) {
+    notification.onclick = () => {
+      window.focus();
+      onClick();
+      notification.close();
+    };
+  }
+  return true;
+}
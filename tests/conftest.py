@@ -0,0 +1,15 @@
+"""
+Shared test setup.
+Backend modules create their settings, session storage and audit folders at
+import time, so every folder is pointed at a throwaway directory here, before
+any test imports argscape.backend.
+"""
+
+import os
+import tempfile
+
+_root = tempfile.mkdtemp(prefix="argscape-tests-")
+os.environ["ARGSCAPE_CONFIG_DIR"] = os.path.join(_root, "config")
+os.environ["PERSISTENT_SESSION_PATH"] = os.path.join(_root, "sessions")
+for name in ("ARGSCAPE_AUTH_TOKEN", "ARGSCAPE_PROTECTED_MODE", "ARGSCAPE_SANDBOX", "ARGSCAPE_HOOKS"):
+    os.environ.pop(name, None)
@@ -0,0 +1,61 @@
+"""Per-launch token checks (argscape.backend.auth)."""
+
+from starlette.requests import Request
+
+from argscape.backend.auth import (
+    AUTH_COOKIE_NAME,
+    AUTH_TOKEN_ENV_VAR,
+    generate_auth_token,
+    get_auth_token,
+    get_request_token,
+    is_local_mode,
+    is_request_authorized,
+    token_matches,
+)
+
+TOKEN = "launch-token"
+
+
+def make_request(path: str = "/api/files", method: str = "GET", headers: dict = None) -> Request:
+    raw = [(key.lower().encode(), value.encode()) for key, value in (headers or {}).items()]
+    return Request({"type": "http", "method": method, "path": path, "headers": raw, "query_string": b""})
+
+
+def test_generated_tokens_are_long_and_unique():
+    tokens = {generate_auth_token() for _ in range(10)}
+    assert len(tokens) == 10
+    assert all(len(token) >= 32 for token in tokens)
+
+
+def test_token_matches():
+    assert token_matches(TOKEN, TOKEN)
+    assert not token_matches("launch-tokeN", TOKEN)
+    assert not token_matches(None, TOKEN)
+    assert not token_matches("", TOKEN)
+
+
+def test_bearer_header_and_cookie_are_accepted():
+    assert get_request_token(make_request(headers={"Authorization": f"Bearer {TOKEN}"})) == TOKEN
+    assert is_request_authorized(make_request(headers={"Authorization": f"Bearer {TOKEN}"}), TOKEN)
+    assert is_request_authorized(make_request(headers={"Cookie": f"{AUTH_COOKIE_NAME}={TOKEN}"}), TOKEN)
+
+
+def test_missing_or_wrong_token_is_rejected():
+    assert not is_request_authorized(make_request(), TOKEN)
+    assert not is_request_authorized(make_request(headers={"Authorization": "Bearer wrong"}), TOKEN)
+    assert not is_request_authorized(make_request(headers={"Cookie": f"{AUTH_COOKIE_NAME}=wrong"}), TOKEN)
+    assert not is_request_authorized(make_request(headers={"Authorization": f"Basic {TOKEN}"}), TOKEN)
+
+
+def test_health_and_preflight_need_no_token():
+    assert is_request_authorized(make_request(path="/api/health"), TOKEN)
+    assert is_request_authorized(make_request(method="OPTIONS"), TOKEN)
+
+
+def test_local_mode_follows_the_launch_token(monkeypatch):
+    monkeypatch.delenv(AUTH_TOKEN_ENV_VAR, raising=False)
+    assert get_auth_token() is None
+    assert not is_local_mode()
+    monkeypatch.setenv(AUTH_TOKEN_ENV_VAR, TOKEN)
+    assert get_auth_token() == TOKEN
+    assert is_local_mode()
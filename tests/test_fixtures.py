@@ -0,0 +1,54 @@
+"""
+Golden-file checks for the synthetic fixtures (argscape.backend.fixtures).
+Each fixture is rebuilt and its summary compared with tests/golden/<name>.json.
+After an intended change, rewrite the golden files with
+ARGSCAPE_UPDATE_GOLDEN=1 pytest tests/test_fixtures.py and review the diff.
+"""
+
+import json
+import os
+from pathlib import Path
+
+import pytest
+
+from argscape.backend.fixtures import FIXTURE_NAMES, FIXTURES, check_golden, compare_summaries, generate_fixture, summarize
+from argscape.commands import run_subcommand
+
+GOLDEN_DIR = Path(__file__).parent / "golden"
+UPDATE_GOLDEN = os.getenv("ARGSCAPE_UPDATE_GOLDEN") == "1"
+
+
+@pytest.mark.parametrize("name", FIXTURE_NAMES)
+def test_fixture_matches_golden(name):
+    ts = generate_fixture(name)
+    path = GOLDEN_DIR / f"{name}.json"
+    if UPDATE_GOLDEN:
+        path.write_text(json.dumps(summarize(ts), indent=2) + "\n", encoding="utf-8")
+    assert check_golden(ts, path) == []
+
+
+@pytest.mark.parametrize("name", FIXTURE_NAMES)
+def test_fixture_matches_documented_properties(name):
+    assert compare_summaries(FIXTURES[name][2], summarize(generate_fixture(name))) == []
+
+
+@pytest.mark.parametrize("name", FIXTURE_NAMES)
+def test_fixture_is_deterministic(name):
+    assert summarize(generate_fixture(name)) == summarize(generate_fixture(name))
+
+
+def test_every_fixture_has_a_golden_file():
+    assert sorted(p.stem for p in GOLDEN_DIR.glob("*.json")) == sorted(FIXTURE_NAMES)
+
+
+def test_generate_fixture_command_round_trip(tmp_path):
+    output, golden = tmp_path / "binary_tree.trees", tmp_path / "binary_tree.json"
+    argv = ["generate-fixture", "binary_tree", str(output), "--golden", str(golden), "--progress", "none"]
+    assert run_subcommand(argv) == 0
+    assert run_subcommand(["generate-fixture", "binary_tree", str(output), "--check", str(golden), "--progress", "none"]) == 0
+
+
+def test_generate_fixture_command_reports_mismatch(tmp_path):
+    golden = tmp_path / "wrong.json"
+    golden.write_text(json.dumps({"num_trees": 3}), encoding="utf-8")
+    assert run_subcommand(["generate-fixture", "recombination", "--check", str(golden), "--progress", "none"]) == 1
@@ -0,0 +1,120 @@
+"""Automation hooks (argscape.backend.hooks): validation, gating and how commands run."""
+
+import json
+import sys
+
+import pytest
+
+from argscape.backend.auth import AUTH_TOKEN_ENV_VAR
+from argscape.backend.hooks import EVENT_EXPORT_COMPLETED, EVENT_FILE_OPENED, HookRunner, hooks_available, render_command, validate_hook
+
+
+def make_hook(command, event=EVENT_FILE_OPENED, **extra):
+    return {"name": "test hook", "event": event, "command": command, **extra}
+
+
+@pytest.fixture
+def local_hooks(monkeypatch):
+    monkeypatch.setenv(AUTH_TOKEN_ENV_VAR, "launch-token")
+    monkeypatch.setenv("ARGSCAPE_HOOKS", "1")
+
+
+@pytest.mark.parametrize("hook", [
+    make_hook("touch {path}"),
+    make_hook([]),
+    make_hook(["echo", "{output}"]),
+    make_hook(["{path}", "--flag"]),
+    make_hook(["echo", "{path!r}"]),
+    make_hook(["echo", "{path:>10}"]),
+    make_hook(["echo"], event="file_closed"),
+    make_hook(["echo"], timeout=0),
+])
+def test_malformed_hooks_are_rejected(hook):
+    with pytest.raises(ValueError):
+        validate_hook(hook)
+
+
+def test_validate_hook_fills_defaults():
+    hook = validate_hook(make_hook(["echo", "{filename}"]))
+    assert hook["match"] == "*"
+    assert hook["enabled"] is True
+    assert hook["cwd"] is None
+
+
+def test_render_command_fills_each_argument():
+    hook = validate_hook(make_hook(["cp", "{path}", "backup/{format}.out"], event=EVENT_EXPORT_COMPLETED))
+    argv = render_command(hook, {"path": "/tmp/a b; rm -rf ~", "format": "trees"})
+    assert argv == ["cp", "/tmp/a b; rm -rf ~", "backup/trees.out"]
+
+
+def test_hooks_need_local_mode(monkeypatch):
+    monkeypatch.delenv(AUTH_TOKEN_ENV_VAR, raising=False)
+    monkeypatch.setenv("ARGSCAPE_HOOKS", "1")
+    assert "started locally" in hooks_available()
+
+
+def test_hooks_need_the_setting(monkeypatch):
+    monkeypatch.setenv(AUTH_TOKEN_ENV_VAR, "launch-token")
+    monkeypatch.delenv("ARGSCAPE_HOOKS", raising=False)
+    assert hooks_available() is not None
+
+
+def test_hooks_off_in_protected_mode(local_hooks, monkeypatch):
+    monkeypatch.setenv("ARGSCAPE_PROTECTED_MODE", "1")
+    assert hooks_available() is not None
+
+
+def test_hooks_available_locally(local_hooks):
+    assert hooks_available() is None
+
+
+def test_run_hook_passes_values_but_not_the_environment(tmp_path, monkeypatch):
+    monkeypatch.setenv("ARGSCAPE_TEST_SECRET", "hunter2")
+    script = "import os; print(os.environ['ARGSCAPE_HOOK_FILENAME'], os.environ.get('ARGSCAPE_TEST_SECRET'))"
+    hook = validate_hook(make_hook([sys.executable, "-c", script], cwd=str(tmp_path)))
+    run = HookRunner(path=tmp_path / "hooks.json").run_hook(hook, {"filename": "example.trees"})
+    assert run["status"] == "ok"
+    assert run["stdout"].split() == ["example.trees", "None"]
+
+
+def test_run_hook_reports_failure_and_missing_programs(tmp_path):
+    runner = HookRunner(path=tmp_path / "hooks.json")
+    failing = validate_hook(make_hook([sys.executable, "-c", "raise SystemExit(3)"], cwd=str(tmp_path)))
+    assert runner.run_hook(failing, {})["exit_code"] == 3
+    missing = validate_hook(make_hook(["argscape-no-such-program"], cwd=str(tmp_path)))
+    assert runner.run_hook(missing, {})["status"] == "error"
+
+
+def test_run_hook_times_out(tmp_path):
+    hook = validate_hook(make_hook([sys.executable, "-c", "import time; time.sleep(30)"], cwd=str(tmp_path), timeout=0.5))
+    run = HookRunner(path=tmp_path / "hooks.json").run_hook(hook, {})
+    assert run["status"] == "timeout"
+    assert run["duration_seconds"] < 10
+
+
+def test_hooks_are_saved_and_reloaded(tmp_path):
+    path = tmp_path / "hooks.json"
+    HookRunner(path=path).set_hooks([make_hook(["echo", "{filename}"])])
+    assert json.loads(path.read_text())[0]["name"] == "test hook"
+    assert [h["name"] for h in HookRunner(path=path).get_hooks()] == ["test hook"]
+
+
+def test_set_hooks_rejects_duplicates_and_keeps_the_old_ones(tmp_path):
+    runner = HookRunner(path=tmp_path / "hooks.json")
+    runner.set_hooks([make_hook(["echo"])])
+    with pytest.raises(ValueError):
+        runner.set_hooks([make_hook(["echo"]), make_hook(["true"])])
+    assert len(runner.get_hooks()) == 1
+
+
+def test_fire_does_nothing_outside_local_mode(tmp_path, monkeypatch):
+    monkeypatch.delenv(AUTH_TOKEN_ENV_VAR, raising=False)
+    monkeypatch.setenv("ARGSCAPE_HOOKS", "1")
+    runner = HookRunner(path=tmp_path / "hooks.json")
+    runner.set_hooks([make_hook(["echo"])])
+    staged = tmp_path / "staged" / "export.trees"
+    staged.parent.mkdir()
+    staged.write_bytes(b"export")
+    assert runner.fire(EVENT_FILE_OPENED, {}, "x.trees", staged=staged) == 0
+    assert not staged.parent.exists()
+    assert runner.recent_runs() == []
@@ -0,0 +1,72 @@
+"""Protected data handling: nothing about a protected file reaches disk (argscape.backend.protected)."""
+
+import pytest
+
+from argscape.backend.audit import EVENT_OPERATION, AuditLog
+from argscape.backend.fixtures import generate_fixture
+from argscape.backend.protected import SCRUBBED_NAME, SCRUBBED_PATH, register_sensitive_name, scrub_text
+from argscape.backend.session_storage import PersistentSessionStorage, session_storage
+
+
+def _files_under(path):
+    return sorted(p.relative_to(path).as_posix() for p in path.rglob("*") if p.is_file())
+
+
+@pytest.fixture
+def storage(tmp_path):
+    store = PersistentSessionStorage(storage_base_path=str(tmp_path / "sessions"))
+    yield store
+    store.shutdown()
+
+
+def test_scrub_text_removes_paths_and_protected_names():
+    register_sensitive_name("cohort_private.trees")
+    text = scrub_text("Loaded cohort_private.trees from /home/user/data/cohort_private.trees")
+    assert "cohort_private" not in text
+    assert "/home/user" not in text
+    assert SCRUBBED_NAME in text and SCRUBBED_PATH in text
+
+
+def test_scrub_text_keeps_url_paths():
+    line = '127.0.0.1:5000 - "GET /api/graph-data/example.trees HTTP/1.1" 200'
+    assert scrub_text(line) == line
+    assert scrub_text("Wrote /tmp/argscape/layout.json") == f"Wrote {SCRUBBED_PATH}"
+
+
+def test_protected_upload_is_kept_in_memory(storage, tmp_path):
+    session_id = storage.get_or_create_session("203.0.113.7")
+    before = _files_under(tmp_path / "sessions")
+    storage.store_file(session_id, "private.trees", b"contents", protected=True)
+    storage.store_tree_sequence(session_id, "private.trees", generate_fixture("binary_tree"), protected=True)
+    storage.store_tree_sequence(session_id, "private_simplified.trees", generate_fixture("binary_tree"), derived_from="private.trees")
+    storage.store_session_data(session_id, "layout:private.trees", {"x": 1}, filename="private.trees")
+
+    assert storage.is_file_protected(session_id, "private_simplified.trees")
+    assert storage.get_tree_sequence(session_id, "private.trees") is not None
+    written = [name for name in _files_under(tmp_path / "sessions") if name not in before]
+    assert not any(name.endswith((".trees", ".data")) or "/data/" in name for name in written)
+    # Not even the name: session metadata lists only unprotected files
+    assert not any(b"private" in (tmp_path / "sessions" / name).read_bytes() for name in written)
+
+
+def test_unprotected_upload_is_written(storage, tmp_path):
+    session_id = storage.get_or_create_session("203.0.113.8")
+    storage.store_tree_sequence(session_id, "public.trees", generate_fixture("binary_tree"))
+    assert any(name.endswith("public.trees.trees") for name in _files_under(tmp_path / "sessions"))
+
+
+def test_protected_files_are_not_audited(tmp_path):
+    session_id = session_storage.get_or_create_session("203.0.113.9")
+    session_storage.store_tree_sequence(session_id, "audited_private.trees", generate_fixture("binary_tree"), protected=True)
+    log = AuditLog(tmp_path / "audit")
+    log.record_open(session_id, "audited_private.trees", "0" * 64)
+    log.record(session_id, "audited_private.trees", EVENT_OPERATION, "simplify", {"samples": [0, 1]})
+    assert not (tmp_path / "audit").exists()
+
+
+def test_protected_mode_covers_every_file(storage, tmp_path, monkeypatch):
+    monkeypatch.setenv("ARGSCAPE_PROTECTED_MODE", "1")
+    session_id = storage.get_or_create_session("203.0.113.10")
+    storage.store_tree_sequence(session_id, "anything.trees", generate_fixture("binary_tree"))
+    assert storage.is_file_protected(session_id, "anything.trees")
+    assert not any(name.endswith("anything.trees.trees") for name in _files_under(tmp_path / "sessions"))
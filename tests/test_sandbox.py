@@ -0,0 +1,119 @@
+"""
+Restricted mode (argscape.backend.sandbox).
+apply_sandbox() can't be undone, so the enforcement checks run it in a child
+interpreter and report what it was allowed to do.
+"""
+
+import json
+import os
+import subprocess
+import sys
+import textwrap
+from pathlib import Path
+
+import pytest
+
+from argscape.backend.sandbox import _is_write_open, get_capabilities_report, is_loopback_host
+
+REPO_ROOT = Path(__file__).resolve().parent.parent
+
+CHILD = textwrap.dedent("""
+    import json, socket, sys
+    from pathlib import Path
+    from argscape.backend.sandbox import apply_sandbox, get_capabilities_report
+
+    storage, config, outside = (Path(p) for p in sys.argv[1:4])
+    apply_sandbox(storage, config)
+
+    def attempt(action):
+        try:
+            action()
+            return "allowed"
+        except PermissionError:
+            return "blocked"
+
+    def read_passwd():
+        with open("/etc/passwd") as f:
+            f.read()
+
+    def write_storage():
+        with open(storage / "ok.txt", "w") as f:
+            f.write("ok")
+
+    def write_outside():
+        with open(outside, "w") as f:
+            f.write("escaped")
+
+    results = {
+        "read_etc_passwd": attempt(read_passwd),
+        "write_storage": attempt(write_storage),
+        "write_outside": attempt(write_outside),
+        "connect_public": attempt(lambda: socket.create_connection(("192.0.2.1", 9), timeout=1)),
+        "resolve_public": attempt(lambda: socket.getaddrinfo("example.org", 443)),
+        "report": get_capabilities_report(),
+    }
+    print(json.dumps(results))
+""")
+
+
+@pytest.fixture(scope="module")
+def sandboxed(tmp_path_factory):
+    if sys.platform == "win32":
+        pytest.skip("POSIX paths")
+    root = tmp_path_factory.mktemp("sandbox")
+    # Outside every write root: the temp folder is writable, the tests folder is only readable
+    outside = Path(__file__).resolve().parent / "sandbox-escape.txt"
+    pythonpath = [str(REPO_ROOT)] + [p for p in os.environ.get("PYTHONPATH", "").split(os.pathsep) if p]
+    env = {**os.environ, "PYTHONPATH": os.pathsep.join(pythonpath)}
+    try:
+        completed = subprocess.run(
+            [sys.executable, "-c", CHILD, str(root / "storage"), str(root / "config"), str(outside)],
+            capture_output=True, text=True, env=env, timeout=60, check=True,
+        )
+        escaped = outside.exists()
+    finally:
+        outside.unlink(missing_ok=True)
+    return {**json.loads(completed.stdout.strip().splitlines()[-1]), "escaped": escaped}
+
+
+def test_reads_outside_the_allowed_roots_are_blocked(sandboxed):
+    assert sandboxed["read_etc_passwd"] == "blocked"
+
+
+def test_writes_are_limited_to_storage(sandboxed):
+    assert sandboxed["write_storage"] == "allowed"
+    assert sandboxed["write_outside"] == "blocked"
+    assert not sandboxed["escaped"]
+
+
+def test_network_is_loopback_only(sandboxed):
+    assert sandboxed["connect_public"] == "blocked"
+    assert sandboxed["resolve_public"] == "blocked"
+
+
+def test_report_is_honest_about_its_limits(sandboxed):
+    report = sandboxed["report"]
+    assert report["sandbox_enabled"]
+    assert report["enforcement"].startswith("Best-effort")
+    assert report["limitations"]
+    assert "/etc" not in report["filesystem"]["read"]
+
+
+def test_report_when_disabled():
+    assert get_capabilities_report()["sandbox_enabled"] is False
+
+
+def test_is_loopback_host():
+    assert is_loopback_host("127.0.0.1")
+    assert is_loopback_host("::1")
+    assert is_loopback_host("localhost")
+    assert not is_loopback_host("192.0.2.1")
+    assert not is_loopback_host("example.org")
+
+
+def test_is_write_open():
+    assert _is_write_open("w", None)
+    assert _is_write_open("r+", None)
+    assert not _is_write_open("r", None)
+    assert _is_write_open(None, os.O_WRONLY | os.O_CREAT)
+    assert not _is_write_open(None, os.O_RDONLY)
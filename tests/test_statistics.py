@@ -0,0 +1,190 @@
+"""
+Numeric regression checks for the analyses, on the synthetic fixtures.
+Expected values come from tskit's own statistics where it has one, and are
+otherwise worked out by hand from the fixture tables (see fixtures.py for
+node times and edges).
+"""
+
+import numpy as np
+import pytest
+
+from argscape.backend.fixtures import _add_edges, _add_nodes, _finish, _new_tables, generate_fixture
+from argscape.backend.gpu_stats import _cpu_values, _cross_check, branch_segments, windowed_branch_values
+from argscape.backend.hardware import get_gpu_info
+from argscape.backend.ts_cache import evict_tree_sequence
+from argscape.backend.tskit_utils.coalescence import estimate_ne
+from argscape.backend.tskit_utils.node_metrics import compute_node_metrics
+from argscape.backend.tskit_utils.polytomies import _resolution_times, resolve_polytomies
+from argscape.backend.tskit_utils.sweeps import sweep_scan
+from argscape.backend.tskit_utils.time_scales import compute_display_times, get_display_times
+from argscape.backend.tskit_utils.tmrca import compute_tmrca_matrix
+from argscape.backend.tskit_utils.topology import topology_blocks, unique_topologies
+
+
+def _mean_tmrca(ts, a, b):
+    return sum(tree.tmrca(a, b) * tree.span for tree in ts.trees()) / ts.sequence_length
+
+
+def test_resolved_polytomies_are_binary_and_keep_node_times():
+    ts = generate_fixture("polytomy")
+    resolved, info = resolve_polytomies(ts, random_seed=1)
+    assert info["num_polytomies_resolved"] == 2
+    assert info["num_nodes_added"] == 2
+    assert resolved.num_nodes == ts.num_nodes + 2
+    np.testing.assert_array_equal(resolved.nodes_time[:ts.num_nodes], ts.nodes_time)
+    np.testing.assert_array_equal(resolved.samples(), ts.samples())
+    for tree in resolved.trees():
+        assert max(tree.num_children(u) for u in tree.nodes()) == 2
+        # The new nodes sit between their children and their parent
+        for u in range(ts.num_nodes, resolved.num_nodes):
+            assert max(resolved.nodes_time[c] for c in tree.children(u)) < resolved.nodes_time[u] < resolved.nodes_time[tree.parent(u)]
+
+
+def test_polytomy_over_several_intervals_counts_once():
+    tables = _new_tables()
+    _add_nodes(tables, 4, [1.0])
+    # Node 4 has three children over [0, 50) and four over [50, 100)
+    _add_edges(tables, [(0, 100, 4, [0, 1, 2]), (50, 100, 4, [3])])
+    _, info = resolve_polytomies(_finish(tables), random_seed=2)
+    assert info["num_polytomies_resolved"] == 1
+    assert info["num_nodes_added"] == 3
+
+
+@pytest.mark.parametrize("method", ["random", "zero_length"])
+def test_resolution_times_fit_a_narrow_interval(method):
+    parent, lower = 1.0, 1.0 - 1e-12
+    times = _resolution_times(parent, lower, 3, method)
+    assert lower < times[0] < times[1] < times[2] < parent
+
+
+def test_resolution_times_without_room_raise():
+    lower = np.nextafter(np.nextafter(1.0, 0), 0)
+    assert _resolution_times(1.0, lower, 1, "zero_length") == [np.nextafter(1.0, 0)]
+    with pytest.raises(ValueError, match="No room"):
+        _resolution_times(1.0, lower, 2, "zero_length")
+
+
+def test_display_times_match_node_times():
+    ts = generate_fixture("binary_tree")
+    np.testing.assert_array_equal(compute_display_times(ts, "time"), [0, 0, 0, 0, 1, 2, 3])
+    np.testing.assert_allclose(compute_display_times(ts, "log"), np.log10(ts.nodes_time + 1))
+    np.testing.assert_array_equal(compute_display_times(ts, "rank"), [0, 0, 0, 0, 1, 2, 3])
+    np.testing.assert_array_equal(compute_display_times(ts, "years", 25), [0, 0, 0, 0, 25, 50, 75])
+    with pytest.raises(ValueError):
+        compute_display_times(ts, "years")
+
+
+def test_display_times_are_cached_per_tree_sequence():
+    ts = generate_fixture("binary_tree")
+    first = get_display_times(ts, "years", 25)
+    assert get_display_times(ts, "years", 25) is first
+    assert get_display_times(ts, "years", 30)["max"] == 90
+    assert get_display_times(generate_fixture("binary_tree"), "years", 25) is not first
+    evict_tree_sequence(ts)
+    assert get_display_times(ts, "years", 25) is not first
+
+
+def test_betweenness_on_a_balanced_tree():
+    # Every internal node of the 7-node tree lies on 9 of the 15 paths between other nodes
+    metrics = compute_node_metrics(generate_fixture("binary_tree"))
+    assert metrics["betweenness_exact"]
+    np.testing.assert_allclose(metrics["betweenness"], [0, 0, 0, 0, 0.6, 0.6, 0.6])
+
+
+@pytest.mark.parametrize("name", ["binary_tree", "recombination"])
+def test_single_epoch_ne_is_mean_tmrca_over_ploidy(name):
+    ts = generate_fixture(name)
+    result = estimate_ne(ts, time_breaks=[0], num_bootstrap=0, ploidy=2)
+    expected = ts.diversity(mode="branch") / 2 / 2
+    for curve in result["curves"]:
+        assert curve["ne"] == pytest.approx([expected])
+
+
+def test_ne_per_epoch():
+    # Over [0, 2) one of 6 pairs coalesces in 11 pair-generations; after 2, 5 pairs in 4
+    result = estimate_ne(generate_fixture("binary_tree"), time_breaks=[0, 2], num_bootstrap=0, ploidy=2)
+    curve = result["curves"][0]
+    assert curve["coalescence_rate"] == pytest.approx([1 / 11, 5 / 4])
+    assert curve["ne"] == pytest.approx([5.5, 0.4])
+    assert result["epochs"] == [{"start": 0.0, "end": 2.0}, {"start": 2.0, "end": None}]
+
+
+def test_tmrca_matrix_matches_tskit():
+    ts = generate_fixture("recombination")
+    samples = [int(u) for u in ts.samples()]
+    result = compute_tmrca_matrix(ts)
+    assert result["samples"] == samples
+    expected = [[_mean_tmrca(ts, a, b) if a != b else 0.0 for b in samples] for a in samples]
+    np.testing.assert_allclose(result["matrix"], expected)
+
+    result = compute_tmrca_matrix(ts, positions=[25, 75])
+    for entry, position in zip(result["matrices"], [25, 75]):
+        tree = ts.at(position)
+        assert entry["interval"] == list(tree.interval)
+        expected = [[tree.tmrca(a, b) if a != b else 0.0 for b in samples] for a in samples]
+        np.testing.assert_allclose(entry["matrix"], expected)
+
+
+def test_topology_blocks_and_hashes():
+    ts = generate_fixture("recombination")
+    # The two trees share only the root clade, so they are 4 clades apart
+    assert topology_blocks(ts)["num_blocks"] == 2
+    assert topology_blocks(ts, tolerance=3)["num_blocks"] == 2
+    merged = topology_blocks(ts, tolerance=4)
+    assert merged["num_blocks"] == 1
+    assert merged["blocks"][0]["span"] == 100
+
+    result = unique_topologies(ts)
+    assert result["num_unique"] == 2
+    assert result["tree_hashes"] == ["2102790763847efce508a08a579bf75c", "ae022090d54f45593d92ebb1e4a92474"]
+    # The same labelled topology hashes the same in another tree sequence, as tskit ranks it the same
+    binary_tree = generate_fixture("binary_tree")
+    assert binary_tree.first().rank() == ts.first().rank()
+    assert unique_topologies(binary_tree)["tree_hashes"] == result["tree_hashes"][:1]
+
+
+def test_sweep_scan_values():
+    ts = generate_fixture("binary_tree")
+    result = sweep_scan(ts, num_windows=2, threshold=0.3)
+    # Haplotypes [1, 0, 0, 0] in the first window and [0, 0, 1, 1] in the second
+    assert result["haplotype_homozygosity"] == pytest.approx([0.625, 0.5])
+    assert result["relative_tmrca"] == pytest.approx([1.0, 1.0])
+    assert result["genome_mean_tmrca"] == pytest.approx(2.5)
+    np.testing.assert_allclose(result["tajimas_d"], ts.Tajimas_D(mode="branch", windows=[0, 50, 100]))
+    assert result["scores"] == pytest.approx([1 / 3, -1 / 3])
+    assert result["candidates"] == [{"left": 0.0, "right": 50.0, "num_windows": 1, "max_score": pytest.approx(1 / 3)}]
+
+
+@pytest.mark.parametrize("name", ["binary_tree", "recombination", "polytomy"])
+def test_branch_segments_reproduce_tskit_diversity(name):
+    # What the GPU kernel sums: each segment's weight times its overlap with the window
+    ts = generate_fixture(name)
+    windows = np.array([0, 20, 50, 75, 100], dtype=float)
+    segments = branch_segments(ts, ts.samples())
+    overlap = np.clip(
+        np.minimum(segments["right"][:, None], windows[None, 1:]) - np.maximum(segments["left"][:, None], windows[None, :-1]), 0, None
+    )
+    values = (segments["weight"][:, None] * overlap).sum(axis=0) / np.diff(windows)
+    np.testing.assert_allclose(values, ts.diversity(mode="branch", windows=windows))
+
+
+def test_gpu_cross_check_catches_wrong_values():
+    ts = generate_fixture("recombination")
+    windows = np.linspace(0, 100, 11)
+    sample_sets = [[int(u) for u in ts.samples()]]
+    expected = _cpu_values(ts, "tmrca", windows, sample_sets).reshape(10, 1)
+    assert _cross_check(ts, "tmrca", windows, sample_sets, expected)["passed"]
+    assert not _cross_check(ts, "tmrca", windows, sample_sets, expected * 1.01)["passed"]
+
+    values, report = windowed_branch_values(ts, "tmrca", windows, sample_sets, accelerator="cpu")
+    assert report["used"] == "cpu"
+    np.testing.assert_allclose(values[:, 0], ts.diversity(mode="branch", windows=windows) / 2)
+
+
+@pytest.mark.skipif(not get_gpu_info()["available"], reason="No GPU adapter")
+def test_gpu_values_match_tskit():
+    ts = generate_fixture("recombination")
+    windows = np.linspace(0, 100, 11)
+    values, report = windowed_branch_values(ts, "diversity", windows, [[int(u) for u in ts.samples()]], accelerator="gpu")
+    assert report["used"] == "gpu", report["reason"]
+    np.testing.assert_allclose(values[:, 0], ts.diversity(mode="branch", windows=windows), rtol=1e-4)